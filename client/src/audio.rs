@@ -1,10 +1,12 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use cpal::traits::{DeviceTrait, StreamTrait};
 use crossbeam_channel::Sender;
 use rubato::Resampler;
 
 use space_lt_common::warn;
 
+use crate::suspend::SuspendableStream;
+
 pub struct CaptureConfig {
     pub sample_rate: u32,
     pub channels: u16,
@@ -15,9 +17,63 @@ const CAPTURE_MAX_ATTEMPTS: u32 = 3;
 /// Delay between capture stream retry attempts.
 const CAPTURE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
 
+/// Which input channel feeds the recognizer (`--capture-channel`).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CaptureChannel {
+    /// Average all channels into mono.
+    #[default]
+    Mix,
+    /// Keep only this channel (0-based) of each frame.
+    Only(u16),
+}
+
+impl CaptureChannel {
+    /// Parse `--capture-channel`: "mix", or a channel number counted from 1.
+    pub fn parse(s: &str) -> Result<Self> {
+        if s == "mix" {
+            return Ok(CaptureChannel::Mix);
+        }
+        match s.parse::<u16>() {
+            Ok(n) if n >= 1 => Ok(CaptureChannel::Only(n - 1)),
+            _ => bail!("expected \"mix\" or a channel number from 1, got \"{s}\""),
+        }
+    }
+
+    /// Check that the device has the selected channel.
+    pub fn validate(self, channels: u16) -> Result<()> {
+        match self {
+            CaptureChannel::Only(index) if index >= channels => bail!(
+                "--capture-channel {} is out of range: the input device has {channels} channel{}",
+                index + 1,
+                if channels == 1 { "" } else { "s" }
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reduce interleaved `samples` to mono, normalized to [-1.0, 1.0].
+    fn to_mono(self, samples: &[i16], channels: usize) -> Vec<f64> {
+        match self {
+            _ if channels == 1 => samples.iter().map(|&s| s as f64 / 32768.0).collect(),
+            CaptureChannel::Mix => samples
+                .chunks(channels)
+                .map(|frame| {
+                    let sum: f64 = frame.iter().map(|&s| s as f64).sum();
+                    (sum / channels as f64) / 32768.0
+                })
+                .collect(),
+            CaptureChannel::Only(index) => samples
+                .chunks_exact(channels)
+                .map(|frame| frame[index as usize] as f64 / 32768.0)
+                .collect(),
+        }
+    }
+}
+
 pub fn start_capture(
     device: &cpal::Device,
     sender: Sender<Vec<i16>>,
+    channel: CaptureChannel,
 ) -> Result<(cpal::Stream, CaptureConfig)> {
     let config = device
         .default_input_config()
@@ -25,6 +81,7 @@ pub fn start_capture(
 
     let sample_rate = config.sample_rate();
     let channels = config.channels();
+    channel.validate(channels)?;
 
     let stream_config: cpal::StreamConfig = config.into();
 
@@ -83,6 +140,64 @@ pub fn start_capture(
     unreachable!("loop always returns or errors")
 }
 
+/// Running capture stream, kept with what is needed to rebuild it after a suspend.
+pub struct CaptureStream {
+    stream: cpal::Stream,
+    device: cpal::Device,
+    sender: Sender<Vec<i16>>,
+    config: CaptureConfig,
+    channel: CaptureChannel,
+}
+
+impl CaptureStream {
+    pub fn start(
+        device: &cpal::Device,
+        sender: Sender<Vec<i16>>,
+        channel: CaptureChannel,
+    ) -> Result<Self> {
+        let (stream, config) = start_capture(device, sender.clone(), channel)?;
+        Ok(Self {
+            stream,
+            device: device.clone(),
+            sender,
+            config,
+            channel,
+        })
+    }
+
+    pub fn config(&self) -> &CaptureConfig {
+        &self.config
+    }
+}
+
+impl SuspendableStream for CaptureStream {
+    fn name(&self) -> &str {
+        "capture"
+    }
+
+    fn pause(&mut self) -> Result<()> {
+        self.stream.pause().context("pausing capture stream")
+    }
+
+    fn resume(&mut self) -> Result<()> {
+        self.stream.play().context("resuming capture stream")
+    }
+
+    fn rebuild(&mut self) -> Result<()> {
+        let (stream, config) = start_capture(&self.device, self.sender.clone(), self.channel)?;
+        if config.sample_rate != self.config.sample_rate || config.channels != self.config.channels
+        {
+            // The capture resampler was built for the old format
+            warn!(
+                "[client] Capture format changed to {}Hz/{}ch after rebuild, audio may be distorted",
+                config.sample_rate, config.channels
+            );
+        }
+        self.stream = stream;
+        Ok(())
+    }
+}
+
 /// Resampler function type. Accepts audio samples and returns resampled output.
 ///
 /// **Flush convention:** Calling with an empty slice (`&[]`) flushes the internal
@@ -92,13 +207,76 @@ pub fn start_capture(
 /// resampler state for subsequent chunks.
 pub type ResamplerFn = Box<dyn FnMut(&[i16]) -> Vec<i16>>;
 
-/// Create a resampler that converts audio from `source_rate` to `target_rate`.
+/// Sinc filter length of the resampler (taps on each side).
+const SINC_LEN: usize = 128;
+/// Cutoff of the resampler's anti-aliasing filter, relative to Nyquist.
+const SINC_CUTOFF: f32 = 0.95;
+
+/// Trade-off between resampling quality and CPU use.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ResampleQuality {
+    /// Cubic polynomial interpolation: cheap, good enough for speech recognition.
+    #[default]
+    Fast,
+    /// Band-limited sinc interpolation, for what the user listens to.
+    High,
+}
+
+impl ResampleQuality {
+    /// Parse `--resample-quality` ("fast" or "high").
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "fast" => Ok(ResampleQuality::Fast),
+            "high" => Ok(ResampleQuality::High),
+            other => bail!("expected \"fast\" or \"high\", got \"{other}\""),
+        }
+    }
+}
+
+/// What `create_resampler` does for these parameters, for logs and session metadata.
+pub fn describe_resampler(
+    source_rate: u32,
+    target_rate: u32,
+    channels: u16,
+    channel: CaptureChannel,
+    quality: ResampleQuality,
+) -> String {
+    if source_rate == target_rate && channels == 1 {
+        return "none (passthrough)".into();
+    }
+    let mut out = match quality {
+        ResampleQuality::Fast => {
+            format!("cubic, {source_rate} \u{2192} {target_rate} Hz")
+        }
+        ResampleQuality::High => format!(
+            "sinc {SINC_LEN} taps, cutoff {SINC_CUTOFF}, {source_rate} \u{2192} {target_rate} Hz"
+        ),
+    };
+    match channel {
+        _ if channels == 1 => {}
+        CaptureChannel::Mix => out.push_str(&format!(", {channels} ch \u{2192} mono")),
+        CaptureChannel::Only(index) => {
+            out.push_str(&format!(", channel {} of {channels}", index + 1))
+        }
+    }
+    out
+}
+
+/// Create a resampler that converts audio from `source_rate` to `target_rate`,
+/// with the interpolation picked by `quality`. Multi-channel input is reduced to
+/// mono as `channel` says.
 ///
 /// Uses a carry-over buffer to avoid zero-padding artifacts at chunk boundaries.
 /// Only full resampler frames (1024 samples) are processed; leftover samples are
 /// carried over to the next call. Call with `&[]` to flush remaining samples at
 /// end of stream.
-pub fn create_resampler(source_rate: u32, target_rate: u32, channels: u16) -> Result<ResamplerFn> {
+pub fn create_resampler(
+    source_rate: u32,
+    target_rate: u32,
+    channels: u16,
+    channel: CaptureChannel,
+    quality: ResampleQuality,
+) -> Result<ResamplerFn> {
     if source_rate == target_rate && channels == 1 {
         return Ok(Box::new(|samples: &[i16]| samples.to_vec()));
     }
@@ -107,21 +285,32 @@ pub fn create_resampler(source_rate: u32, target_rate: u32, channels: u16) -> Re
     let ratio = target_rate as f64 / source_rate as f64;
 
     use rubato::{
-        Async, FixedAsync, SincInterpolationParameters, SincInterpolationType, WindowFunction,
-    };
-
-    let params = SincInterpolationParameters {
-        sinc_len: 128,
-        f_cutoff: 0.95,
-        interpolation: SincInterpolationType::Quadratic,
-        oversampling_factor: 256,
-        window: WindowFunction::Blackman2,
+        Async, FixedAsync, PolynomialDegree, SincInterpolationParameters, SincInterpolationType,
+        WindowFunction,
     };
 
     let chunk_size = 1024;
-    let mut resampler =
-        Async::<f64>::new_sinc(ratio, 1.1, &params, chunk_size, 1, FixedAsync::Input)
-            .map_err(|e| anyhow::anyhow!("Failed to create resampler: {e}"))?;
+    let mut resampler = match quality {
+        ResampleQuality::Fast => Async::<f64>::new_poly(
+            ratio,
+            1.1,
+            PolynomialDegree::Cubic,
+            chunk_size,
+            1,
+            FixedAsync::Input,
+        ),
+        ResampleQuality::High => {
+            let params = SincInterpolationParameters {
+                sinc_len: SINC_LEN,
+                f_cutoff: SINC_CUTOFF,
+                interpolation: SincInterpolationType::Quadratic,
+                oversampling_factor: 256,
+                window: WindowFunction::Blackman2,
+            };
+            Async::<f64>::new_sinc(ratio, 1.1, &params, chunk_size, 1, FixedAsync::Input)
+        }
+    }
+    .map_err(|e| anyhow::anyhow!("Failed to create resampler: {e}"))?;
 
     // Carry-over buffer: leftover samples from previous call (< chunk_size)
     let mut leftover: Vec<f64> = Vec::new();
@@ -132,17 +321,7 @@ pub fn create_resampler(source_rate: u32, target_rate: u32, channels: u16) -> Re
         let is_flush = samples.is_empty();
 
         // Convert to mono f64 normalized [-1.0, 1.0]
-        let mono: Vec<f64> = if ch == 1 {
-            samples.iter().map(|&s| s as f64 / 32768.0).collect()
-        } else {
-            samples
-                .chunks(ch)
-                .map(|frame| {
-                    let sum: f64 = frame.iter().map(|&s| s as f64).sum();
-                    (sum / ch as f64) / 32768.0
-                })
-                .collect()
-        };
+        let mono = channel.to_mono(samples, ch);
 
         // Prepend carry-over from previous call
         let mut combined = std::mem::take(&mut leftover);
@@ -220,33 +399,289 @@ pub fn create_resampler(source_rate: u32, target_rate: u32, channels: u16) -> Re
     }))
 }
 
+/// WSOLA analysis frame length (~30ms: a few pitch periods of speech).
+const STRETCH_FRAME_MS: u32 = 30;
+/// Stride used when comparing candidate frames (correlation on every 4th sample).
+const STRETCH_CORR_STEP: usize = 4;
+
+/// RMS level of a chunk, normalized to 0.0 (silence) ..= 1.0 (full-scale square wave).
+pub fn level_meter(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_sq: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    ((sum_sq / samples.len() as f64).sqrt() / 32768.0) as f32
+}
+
+/// Peak absolute level of a chunk, normalized to 0.0 ..= 1.0.
+pub fn peak_level(samples: &[i16]) -> f32 {
+    let peak = samples.iter().map(|&s| s.unsigned_abs()).max().unwrap_or(0);
+    peak as f32 / 32768.0
+}
+
+/// Convert a normalized level to dBFS, floored at -96 dB for silence.
+pub fn to_dbfs(level: f32) -> f32 {
+    if level <= 0.0 {
+        return -96.0;
+    }
+    (20.0 * level.log10()).max(-96.0)
+}
+
+/// Capture rate the denoiser's model was trained for, in Hz.
+pub const DENOISE_SAMPLE_RATE: u32 = 48000;
+
+/// RNNoise-style noise suppression for the capture path (`--denoise`).
+///
+/// Works on the device's interleaved samples, before resampling: each channel
+/// gets its own model state, fed in 10 ms frames. Samples that don't fill a
+/// frame are carried over to the next call; call with `&[]` to flush them
+/// (zero-padded) at end of stream. When disabled, samples pass through unchanged.
+pub struct Denoiser {
+    channels: Vec<nnnoiseless::DenoiseState<'static>>,
+    pending: Vec<i16>,
+}
+
+impl Denoiser {
+    /// A denoiser for `channels` interleaved channels at [`DENOISE_SAMPLE_RATE`].
+    pub fn new(channels: u16) -> Self {
+        Self {
+            channels: (0..channels.max(1))
+                .map(|_| *nnnoiseless::DenoiseState::new())
+                .collect(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// A no-op passthrough.
+    pub fn disabled() -> Self {
+        Self {
+            channels: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.channels.is_empty()
+    }
+
+    /// Denoise `samples`, returning every full frame available so far.
+    pub fn process(&mut self, samples: &[i16]) -> Vec<i16> {
+        if !self.is_enabled() {
+            return samples.to_vec();
+        }
+        let ch = self.channels.len();
+        let frame_len = nnnoiseless::FRAME_SIZE * ch;
+
+        let flush = samples.is_empty();
+        self.pending.extend_from_slice(samples);
+        let carried = self.pending.len();
+        if flush {
+            self.pending
+                .resize(carried.div_ceil(frame_len) * frame_len, 0);
+        }
+
+        let mut output = Vec::with_capacity(self.pending.len());
+        let mut input = vec![0.0f32; nnnoiseless::FRAME_SIZE];
+        let mut denoised = vec![0.0f32; nnnoiseless::FRAME_SIZE];
+        let mut frames = self.pending.chunks_exact(frame_len);
+        for frame in &mut frames {
+            let start = output.len();
+            output.resize(start + frame_len, 0);
+            for (c, state) in self.channels.iter_mut().enumerate() {
+                for (dst, &s) in input.iter_mut().zip(frame.iter().skip(c).step_by(ch)) {
+                    *dst = s as f32;
+                }
+                state.process_frame(&mut denoised, &input);
+                for (dst, &s) in output[start + c..].iter_mut().step_by(ch).zip(&denoised) {
+                    *dst = s.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+                }
+            }
+        }
+        let rest = frames.remainder().to_vec();
+        if flush {
+            // Drop the padding: what comes out matches what went in
+            output.truncate(carried);
+        }
+        self.pending = rest;
+        output
+    }
+}
+
+/// AGC target peak level (-12 dBFS), normalized to full scale.
+const AGC_TARGET_PEAK: f32 = 0.251;
+/// Default cap on the AGC gain, overridden with `--agc-max-gain`.
+pub const DEFAULT_AGC_MAX_GAIN: f32 = 4.0;
+/// Envelope attack time: a louder peak is tracked almost at once so the gain drops before clipping.
+const AGC_ATTACK_MS: f32 = 0.1;
+/// Envelope release time: slow, so the gain doesn't pump up between syllables.
+const AGC_RELEASE_MS: f32 = 400.0;
+/// Envelope floor, keeps the gain finite on digital silence.
+const AGC_ENVELOPE_FLOOR: f32 = 1e-4;
+
+/// Automatic gain control for microphone capture.
+///
+/// Follows the peak envelope of the signal (fast attack, slow release) and scales
+/// each sample so the envelope sits at about -12 dBFS. The gain never exceeds
+/// `max_gain`, so a quiet room isn't boosted into loud noise.
+pub struct Agc {
+    max_gain: f32,
+    attack: f32,
+    release: f32,
+    envelope: f32,
+}
+
+impl Agc {
+    pub fn new(sample_rate: u32, max_gain: f32) -> Self {
+        let coeff = |ms: f32| 1.0 - (-1000.0 / (ms * sample_rate as f32)).exp();
+        Self {
+            max_gain,
+            attack: coeff(AGC_ATTACK_MS),
+            release: coeff(AGC_RELEASE_MS),
+            envelope: AGC_TARGET_PEAK,
+        }
+    }
+
+    /// Current gain, as applied to the last processed sample.
+    pub fn gain(&self) -> f32 {
+        (AGC_TARGET_PEAK / self.envelope.max(AGC_ENVELOPE_FLOOR)).min(self.max_gain)
+    }
+
+    /// Apply the gain in place, updating the envelope sample by sample.
+    pub fn process(&mut self, samples: &mut [i16]) {
+        for s in samples {
+            let level = s.unsigned_abs() as f32 / 32768.0;
+            let coeff = if level > self.envelope {
+                self.attack
+            } else {
+                self.release
+            };
+            self.envelope += coeff * (level - self.envelope);
+            // Never let the sample exceed what the envelope has seen, whatever the attack lag
+            let gain = self
+                .gain()
+                .min(AGC_TARGET_PEAK / level.max(AGC_ENVELOPE_FLOOR));
+            *s = (*s as f32 * gain)
+                .round()
+                .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+    }
+}
+
+/// Change playback speed without changing pitch (WSOLA).
+///
+/// `speed` < 1.0 slows down (0.75 → output ~1.33× longer), > 1.0 speeds up.
+/// Each output frame is taken from the input position, within a small search window,
+/// whose waveform best continues the previous frame, so pitch periods line up and
+/// the overlap-add doesn't produce the phasing of naive resampling.
+pub fn time_stretch(samples: &[i16], speed: f32, sample_rate: u32) -> Vec<i16> {
+    let frame_len = ((sample_rate * STRETCH_FRAME_MS / 1000) as usize).max(32) & !1;
+    if samples.len() < frame_len * 2 || !(0.25..=4.0).contains(&speed) || speed == 1.0 {
+        return samples.to_vec();
+    }
+
+    let hop_out = frame_len / 2;
+    let hop_in = hop_out as f32 * speed;
+    let tolerance = frame_len / 4;
+    let out_len = (samples.len() as f32 / speed).round() as usize;
+
+    let input: Vec<f32> = samples.iter().map(|&s| s as f32).collect();
+    let window: Vec<f32> = (0..frame_len)
+        .map(|i| {
+            let x = std::f32::consts::PI * 2.0 * i as f32 / frame_len as f32;
+            0.5 - 0.5 * x.cos()
+        })
+        .collect();
+
+    let mut out = vec![0.0f32; out_len + frame_len];
+    let mut weight = vec![0.0f32; out_len + frame_len];
+    let mut prev_pos = 0usize;
+
+    for k in 0.. {
+        let out_pos = k * hop_out;
+        if out_pos >= out_len {
+            break;
+        }
+        let nominal = (k as f32 * hop_in) as usize;
+        let last_start = input.len() - frame_len;
+
+        let pos = if k == 0 {
+            0
+        } else {
+            // The segment that would naturally follow the previous frame
+            let natural = (prev_pos + hop_out).min(last_start);
+            let lo = nominal.saturating_sub(tolerance).min(last_start);
+            let hi = (nominal + tolerance).min(last_start);
+            (lo..=hi)
+                .max_by(|&a, &b| {
+                    let ca = similarity(&input, a, natural, hop_out);
+                    let cb = similarity(&input, b, natural, hop_out);
+                    ca.total_cmp(&cb)
+                })
+                .unwrap_or(lo)
+        };
+
+        for i in 0..frame_len {
+            out[out_pos + i] += input[pos + i] * window[i];
+            weight[out_pos + i] += window[i];
+        }
+        prev_pos = pos;
+    }
+
+    out.truncate(out_len);
+    out.iter()
+        .zip(&weight)
+        .map(|(&s, &w)| {
+            let v = if w > 1e-3 { s / w } else { s };
+            v.round().clamp(-32768.0, 32767.0) as i16
+        })
+        .collect()
+}
+
+/// Sub-sampled cross-correlation between two `len`-sample regions of `x`.
+fn similarity(x: &[f32], a: usize, b: usize, len: usize) -> f32 {
+    (0..len)
+        .step_by(STRETCH_CORR_STEP)
+        .map(|i| x[a + i] * x[b + i])
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// The resampler tests run against both qualities.
+    const QUALITIES: [ResampleQuality; 2] = [ResampleQuality::Fast, ResampleQuality::High];
+
     #[test]
     fn resampler_noop_mono() {
-        let mut resample = create_resampler(16000, 16000, 1).unwrap();
-        let input: Vec<i16> = (0..1600).collect();
-        let output = resample(&input);
-        assert_eq!(output, input);
+        for quality in QUALITIES {
+            let mut resample =
+                create_resampler(16000, 16000, 1, CaptureChannel::Mix, quality).unwrap();
+            let input: Vec<i16> = (0..1600).collect();
+            let output = resample(&input);
+            assert_eq!(output, input);
+        }
     }
 
     #[test]
+    #[allow(clippy::unnecessary_cast)]
     fn resampler_48k_to_16k() {
-        let mut resample = create_resampler(48000, 16000, 1).unwrap();
-        // 100ms at 48kHz = 4800 samples
-        let input: Vec<i16> = vec![0; 4800];
-        let output = resample(&input);
-        let flush = resample(&[]);
-        let total = output.len() + flush.len();
-        // Expected ~1600 samples (100ms at 16kHz), allow some margin
-        let expected = 1600;
-        let margin = 200;
-        assert!(
-            (total as i32 - expected as i32).unsigned_abs() < margin,
-            "Expected ~{expected} samples, got {total}",
-        );
+        for quality in QUALITIES {
+            let mut resample =
+                create_resampler(48000, 16000, 1, CaptureChannel::Mix, quality).unwrap();
+            // 100ms at 48kHz = 4800 samples
+            let input: Vec<i16> = vec![0; 4800];
+            let output = resample(&input);
+            let flush = resample(&[]);
+            let total = output.len() + flush.len();
+            // Expected ~1600 samples (100ms at 16kHz), allow some margin
+            let expected = 1600;
+            let margin = 200;
+            assert!(
+                (total as i32 - expected as i32).unsigned_abs() < margin,
+                "Expected ~{expected} samples, got {total}",
+            );
+        }
     }
 
     /// Generate a 440Hz sine wave at the given sample rate.
@@ -262,86 +697,368 @@ mod tests {
 
     #[test]
     fn resampler_carry_over_no_discontinuity() {
-        let mut resample = create_resampler(16000, 48000, 1).unwrap();
-        let signal = sine_wave(16000, 2.0); // 32000 samples
-        let chunk_size = 4000;
+        for quality in QUALITIES {
+            let mut resample =
+                create_resampler(16000, 48000, 1, CaptureChannel::Mix, quality).unwrap();
+            let signal = sine_wave(16000, 2.0); // 32000 samples
+            let chunk_size = 4000;
+
+            // Process in chunks (simulating TCP chunks)
+            let mut chunked_output: Vec<i16> = Vec::new();
+            for chunk in signal.chunks(chunk_size) {
+                chunked_output.extend_from_slice(&resample(chunk));
+            }
+            chunked_output.extend_from_slice(&resample(&[])); // flush
+
+            // Verify no discontinuity: max sample-to-sample delta should be bounded
+            // For a 440Hz sine at 48kHz, max natural delta ≈ 2π*440/48000 * 20000 ≈ 1152
+            // We use 3000 as threshold to catch pops while allowing natural signal variation
+            let mut max_delta: i32 = 0;
+            for w in chunked_output.windows(2) {
+                let delta = (w[1] as i32 - w[0] as i32).abs();
+                max_delta = max_delta.max(delta);
+            }
+            assert!(
+                max_delta < 3000,
+                "Discontinuity detected: max sample delta = {max_delta} (threshold 3000)"
+            );
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn resampler_flush_produces_remaining_samples() {
+        for quality in QUALITIES {
+            let mut resample =
+                create_resampler(16000, 48000, 1, CaptureChannel::Mix, quality).unwrap();
+            // 500 samples < chunk_size (1024), all goes to carry-over
+            let input = sine_wave(16000, 0.03125); // 500 samples
+            let output = resample(&input);
+            let flush = resample(&[]);
+            let total = output.len() + flush.len();
+            // Expected ~1500 samples (500 * 3.0 ratio)
+            assert!(
+                total > 0,
+                "Flush should produce output for carried-over samples"
+            );
+            let expected = 1500;
+            assert!(
+                (total as i32 - expected as i32).unsigned_abs() < 100,
+                "Expected ~{expected} samples, got {total}"
+            );
+        }
+    }
+
+    #[test]
+    fn resampler_carry_over_matches_single_pass() {
+        for quality in QUALITIES {
+            // Chunked processing (4000+4000 + flush)
+            let mut chunked =
+                create_resampler(16000, 48000, 1, CaptureChannel::Mix, quality).unwrap();
+            let signal = sine_wave(16000, 0.5); // 8000 samples
+            let mut chunked_out: Vec<i16> = Vec::new();
+            chunked_out.extend_from_slice(&chunked(&signal[..4000]));
+            chunked_out.extend_from_slice(&chunked(&signal[4000..]));
+            chunked_out.extend_from_slice(&chunked(&[])); // flush
+
+            // Single-pass processing (8000 + flush)
+            let mut single =
+                create_resampler(16000, 48000, 1, CaptureChannel::Mix, quality).unwrap();
+            let mut single_out: Vec<i16> = Vec::new();
+            single_out.extend_from_slice(&single(&signal));
+            single_out.extend_from_slice(&single(&[])); // flush
+
+            // Output lengths should match within resampler chunk_size (1024 output frames)
+            let margin = 1024;
+            let diff = (chunked_out.len() as i32 - single_out.len() as i32).unsigned_abs();
+            assert!(
+                diff < margin as u32,
+                "Chunked ({}) vs single-pass ({}) differ by {diff} (max {margin})",
+                chunked_out.len(),
+                single_out.len()
+            );
+        }
+    }
 
-        // Process in chunks (simulating TCP chunks)
-        let mut chunked_output: Vec<i16> = Vec::new();
-        for chunk in signal.chunks(chunk_size) {
-            chunked_output.extend_from_slice(&resample(chunk));
+    #[test]
+    fn resampler_noop_flush_is_empty() {
+        for quality in QUALITIES {
+            let mut resample =
+                create_resampler(16000, 16000, 1, CaptureChannel::Mix, quality).unwrap();
+            let input: Vec<i16> = (0..100).collect();
+            let output = resample(&input);
+            assert_eq!(output, input);
+            // Flush on no-op resampler should return empty (no carry-over)
+            let flush = resample(&[]);
+            assert!(flush.is_empty() || flush == Vec::<i16>::new());
         }
-        chunked_output.extend_from_slice(&resample(&[])); // flush
+    }
+
+    #[test]
+    fn describe_resampler_matches_the_conversion() {
+        use CaptureChannel::{Mix, Only};
+        use ResampleQuality::{Fast, High};
+        assert_eq!(
+            describe_resampler(16000, 16000, 1, Mix, High),
+            "none (passthrough)"
+        );
+        assert_eq!(
+            describe_resampler(48000, 16000, 2, Mix, High),
+            "sinc 128 taps, cutoff 0.95, 48000 \u{2192} 16000 Hz, 2 ch \u{2192} mono"
+        );
+        assert_eq!(
+            describe_resampler(48000, 16000, 1, Mix, Fast),
+            "cubic, 48000 \u{2192} 16000 Hz"
+        );
+        // Same rate but stereo still goes through the resampler (downmix)
+        assert!(describe_resampler(16000, 16000, 2, Mix, High).starts_with("sinc"));
+        assert_eq!(
+            describe_resampler(48000, 16000, 4, Only(0), Fast),
+            "cubic, 48000 \u{2192} 16000 Hz, channel 1 of 4"
+        );
+    }
+
+    /// Interleaved frames where channel `c` of frame `i` holds `1000 * (c + 1) + i`.
+    fn interleaved(channels: u16, frames: i16) -> Vec<i16> {
+        (0..frames)
+            .flat_map(|i| (0..channels as i16).map(move |c| 1000 * (c + 1) + i))
+            .collect()
+    }
 
-        // Verify no discontinuity: max sample-to-sample delta should be bounded
-        // For a 440Hz sine at 48kHz, max natural delta ≈ 2π*440/48000 * 20000 ≈ 1152
-        // We use 3000 as threshold to catch pops while allowing natural signal variation
-        let mut max_delta: i32 = 0;
-        for w in chunked_output.windows(2) {
-            let delta = (w[1] as i32 - w[0] as i32).abs();
-            max_delta = max_delta.max(delta);
+    #[test]
+    fn capture_channel_extracts_one_channel() {
+        for channels in [2u16, 4] {
+            let samples = interleaved(channels, 8);
+            for c in 0..channels {
+                let mono = CaptureChannel::Only(c).to_mono(&samples, channels as usize);
+                let expected: Vec<f64> = (0..8)
+                    .map(|i| (1000 * (c as i16 + 1) + i) as f64 / 32768.0)
+                    .collect();
+                assert_eq!(mono, expected, "channel {c} of {channels}");
+            }
         }
+    }
+
+    #[test]
+    fn capture_channel_mix_averages() {
+        let mono = CaptureChannel::Mix.to_mono(&interleaved(4, 2), 4);
+        // (1000 + 2000 + 3000 + 4000) / 4, then + 1 for the second frame
+        assert_eq!(mono, [2500.0 / 32768.0, 2501.0 / 32768.0]);
+        // A lone mic on channel 1 of 4 ends up 12 dB down when mixed
+        let lone: Vec<i16> = [8000, 0, 0, 0].repeat(4);
+        let mixed = CaptureChannel::Mix.to_mono(&lone, 4)[0];
+        let picked = CaptureChannel::Only(0).to_mono(&lone, 4)[0];
+        assert_eq!(picked / mixed, 4.0);
+    }
+
+    #[test]
+    fn capture_channel_parse_and_validate() {
+        assert_eq!(CaptureChannel::parse("mix").unwrap(), CaptureChannel::Mix);
+        assert_eq!(CaptureChannel::parse("1").unwrap(), CaptureChannel::Only(0));
+        assert_eq!(CaptureChannel::parse("4").unwrap(), CaptureChannel::Only(3));
+        assert!(CaptureChannel::parse("0").is_err());
+        assert!(CaptureChannel::parse("left").is_err());
+
+        assert!(CaptureChannel::Only(3).validate(4).is_ok());
+        assert!(CaptureChannel::Mix.validate(1).is_ok());
+        let err = CaptureChannel::Only(2).validate(2).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--capture-channel 3 is out of range: the input device has 2 channels"
+        );
+    }
+
+    #[test]
+    fn parse_resample_quality() {
+        assert_eq!(
+            ResampleQuality::parse("fast").unwrap(),
+            ResampleQuality::Fast
+        );
+        assert_eq!(
+            ResampleQuality::parse("high").unwrap(),
+            ResampleQuality::High
+        );
+        assert!(ResampleQuality::parse("best").is_err());
+        assert_eq!(ResampleQuality::default(), ResampleQuality::Fast);
+    }
+
+    // --- time_stretch tests ---
+
+    fn sine(freq: f32, rate: u32, secs: f32) -> Vec<i16> {
+        (0..(rate as f32 * secs) as usize)
+            .map(|i| {
+                let t = i as f32 / rate as f32;
+                ((2.0 * std::f32::consts::PI * freq * t).sin() * 10000.0) as i16
+            })
+            .collect()
+    }
+
+    fn zero_crossings(samples: &[i16]) -> usize {
+        samples
+            .windows(2)
+            .filter(|w| (w[0] < 0) != (w[1] < 0))
+            .count()
+    }
+
+    #[test]
+    fn time_stretch_slow_length_matches_factor() {
+        let input = sine(220.0, 48000, 2.0);
+        let output = time_stretch(&input, 0.75, 48000);
+        let expected = input.len() as f32 / 0.75;
+        let ratio = output.len() as f32 / expected;
         assert!(
-            max_delta < 3000,
-            "Discontinuity detected: max sample delta = {max_delta} (threshold 3000)"
+            (0.98..=1.02).contains(&ratio),
+            "Expected ~{expected} samples, got {}",
+            output.len()
         );
     }
 
     #[test]
-    fn resampler_flush_produces_remaining_samples() {
-        let mut resample = create_resampler(16000, 48000, 1).unwrap();
-        // 500 samples < chunk_size (1024), all goes to carry-over
-        let input = sine_wave(16000, 0.03125); // 500 samples
-        let output = resample(&input);
-        let flush = resample(&[]);
-        let total = output.len() + flush.len();
-        // Expected ~1500 samples (500 * 3.0 ratio)
+    fn time_stretch_fast_length_matches_factor() {
+        let input = sine(220.0, 16000, 2.0);
+        let output = time_stretch(&input, 1.5, 16000);
+        let expected = input.len() as f32 / 1.5;
+        let ratio = output.len() as f32 / expected;
+        assert!((0.98..=1.02).contains(&ratio), "got {}", output.len());
+    }
+
+    #[test]
+    fn time_stretch_preserves_pitch() {
+        let input = sine(300.0, 16000, 2.0);
+        let output = time_stretch(&input, 0.75, 16000);
+        // Same frequency → same zero-crossing density per sample
+        let density_in = zero_crossings(&input) as f32 / input.len() as f32;
+        let density_out = zero_crossings(&output) as f32 / output.len() as f32;
         assert!(
-            total > 0,
-            "Flush should produce output for carried-over samples"
+            (density_out / density_in - 1.0).abs() < 0.05,
+            "pitch drifted: {density_in} vs {density_out}"
         );
-        let expected = 1500;
+    }
+
+    #[test]
+    fn time_stretch_passthrough_cases() {
+        let input = sine(220.0, 16000, 1.0);
+        assert_eq!(time_stretch(&input, 1.0, 16000), input);
+        assert!(time_stretch(&[], 0.75, 16000).is_empty());
+        // Too short to stretch: returned unchanged
+        assert_eq!(time_stretch(&input[..100], 0.75, 16000), &input[..100]);
+    }
+
+    #[test]
+    fn level_meter_silence_and_full_scale() {
+        assert_eq!(level_meter(&[]), 0.0);
+        assert_eq!(level_meter(&[0; 160]), 0.0);
+        let square: Vec<i16> = (0..160)
+            .map(|i| if i % 2 == 0 { i16::MAX } else { i16::MIN })
+            .collect();
+        assert!((level_meter(&square) - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn level_meter_sine_rms() {
+        // A sine's RMS is amplitude / sqrt(2); sine() uses amplitude 10000
+        let s = sine(440.0, 16000, 0.1);
+        let amplitude = 10000.0 / 32768.0;
+        let rms = level_meter(&s);
+        let expected = amplitude * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((rms - expected).abs() < 0.005, "rms = {rms}");
+        assert!((peak_level(&s) - amplitude).abs() < 0.005);
+    }
+
+    /// Feed `input` in uneven chunks, then flush.
+    fn denoise_in_chunks(denoiser: &mut Denoiser, input: &[i16], sizes: &[usize]) -> Vec<i16> {
+        let mut output = Vec::new();
+        let mut rest = input;
+        for &size in sizes.iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            let (chunk, tail) = rest.split_at(size.min(rest.len()));
+            output.extend(denoiser.process(chunk));
+            rest = tail;
+        }
+        output.extend(denoiser.process(&[]));
+        output
+    }
+
+    #[test]
+    fn denoiser_frame_buffering_keeps_every_sample() {
+        let input = sine(440.0, DENOISE_SAMPLE_RATE, 0.5);
+        for (channels, sizes) in [(1, &[333, 1000, 7][..]), (2, &[960, 250]), (1, &[480])] {
+            let mut denoiser = Denoiser::new(channels);
+            // Not a whole number of frames (an even count, for stereo)
+            let input = &input[..input.len() - 62];
+            let output = denoise_in_chunks(&mut denoiser, input, sizes);
+            assert_eq!(output.len(), input.len(), "{channels} ch, chunks {sizes:?}");
+            // Nothing left over once flushed
+            assert!(denoiser.process(&[]).is_empty());
+        }
+    }
+
+    #[test]
+    fn denoiser_holds_partial_frames_until_full() {
+        let mut denoiser = Denoiser::new(1);
+        assert!(denoiser.process(&[100; 300]).is_empty());
+        assert_eq!(denoiser.process(&[100; 300]).len(), 480);
+        assert_eq!(denoiser.process(&[]).len(), 120);
+    }
+
+    #[test]
+    fn disabled_denoiser_is_a_passthrough() {
+        let input = sine(440.0, 16000, 0.01);
+        let mut denoiser = Denoiser::disabled();
+        assert!(!denoiser.is_enabled());
+        assert_eq!(denoiser.process(&input[..7]), &input[..7]);
+        assert!(denoiser.process(&[]).is_empty());
+    }
+
+    #[test]
+    fn agc_brings_quiet_speech_to_target() {
+        // 2500 ≈ -22 dBFS, within reach of the default 4× cap
+        let mut input: Vec<i16> = sine(440.0, 16000, 2.0).iter().map(|&s| s / 4).collect();
+        let mut agc = Agc::new(16000, DEFAULT_AGC_MAX_GAIN);
+        for chunk in input.chunks_mut(320) {
+            agc.process(chunk);
+        }
+        let tail = peak_level(&input[input.len() - 8000..]);
         assert!(
-            (total as i32 - expected as i32).unsigned_abs() < 100,
-            "Expected ~{expected} samples, got {total}"
+            (to_dbfs(tail) + 12.0).abs() < 1.0,
+            "tail peak = {} dBFS",
+            to_dbfs(tail)
         );
     }
 
     #[test]
-    fn resampler_carry_over_matches_single_pass() {
-        // Chunked processing (4000+4000 + flush)
-        let mut chunked = create_resampler(16000, 48000, 1).unwrap();
-        let signal = sine_wave(16000, 0.5); // 8000 samples
-        let mut chunked_out: Vec<i16> = Vec::new();
-        chunked_out.extend_from_slice(&chunked(&signal[..4000]));
-        chunked_out.extend_from_slice(&chunked(&signal[4000..]));
-        chunked_out.extend_from_slice(&chunked(&[])); // flush
-
-        // Single-pass processing (8000 + flush)
-        let mut single = create_resampler(16000, 48000, 1).unwrap();
-        let mut single_out: Vec<i16> = Vec::new();
-        single_out.extend_from_slice(&single(&signal));
-        single_out.extend_from_slice(&single(&[])); // flush
-
-        // Output lengths should match within resampler chunk_size (1024 output frames)
-        let margin = 1024;
-        let diff = (chunked_out.len() as i32 - single_out.len() as i32).unsigned_abs();
+    fn agc_gain_is_capped() {
+        let mut input: Vec<i16> = sine(440.0, 16000, 2.0).iter().map(|&s| s / 100).collect();
+        let mut agc = Agc::new(16000, 4.0);
+        agc.process(&mut input);
+        assert!((agc.gain() - 4.0).abs() < 0.001);
+        assert!(peak_level(&input) <= 4.0 * 100.0 / 32768.0 + 0.001);
+    }
+
+    #[test]
+    fn agc_does_not_clip_sudden_loud_input() {
+        let mut agc = Agc::new(16000, DEFAULT_AGC_MAX_GAIN);
+        let mut quiet = vec![0i16; 16000];
+        agc.process(&mut quiet);
+        assert!((agc.gain() - DEFAULT_AGC_MAX_GAIN).abs() < 0.001);
+
+        let mut loud: Vec<i16> = (0..1600)
+            .map(|i| if (i / 20) % 2 == 0 { 30000 } else { -30000 })
+            .collect();
+        agc.process(&mut loud);
         assert!(
-            diff < margin as u32,
-            "Chunked ({}) vs single-pass ({}) differ by {diff} (max {margin})",
-            chunked_out.len(),
-            single_out.len()
+            peak_level(&loud) <= AGC_TARGET_PEAK + 0.001,
+            "peak = {}",
+            peak_level(&loud)
         );
     }
 
     #[test]
-    fn resampler_noop_flush_is_empty() {
-        let mut resample = create_resampler(16000, 16000, 1).unwrap();
-        let input: Vec<i16> = (0..100).collect();
-        let output = resample(&input);
-        assert_eq!(output, input);
-        // Flush on no-op resampler should return empty (no carry-over)
-        let flush = resample(&[]);
-        assert!(flush.is_empty() || flush == Vec::<i16>::new());
+    fn dbfs_floor_for_silence() {
+        assert_eq!(to_dbfs(0.0), -96.0);
+        assert_eq!(to_dbfs(1e-9), -96.0);
+        assert!((to_dbfs(0.1) + 20.0).abs() < 0.001);
     }
 }
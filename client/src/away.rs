@@ -0,0 +1,276 @@
+use crossbeam_channel::{Receiver, Sender};
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use space_lt_common::{debug, warn};
+
+/// How often logind's lock state is checked.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A gap between the boot clock and the monotonic clock growing by more than
+/// this between two polls means the system was asleep.
+const SLEEP_THRESHOLD: Duration = Duration::from_secs(3);
+
+/// The user stepping away from (or coming back to) the machine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AwayEvent {
+    /// The screen was locked.
+    Locked,
+    /// The screen was unlocked.
+    Unlocked,
+    /// The system slept this long and has just woken up.
+    Slept(Duration),
+}
+
+/// Source of [`AwayEvent`]s, polled by the main loop.
+pub trait AwayDetector {
+    /// Next pending event, without blocking.
+    fn poll(&mut self) -> Option<AwayEvent>;
+}
+
+/// What the main loop must do for an event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AwayAction {
+    /// Stop listening, pause the server and the capture stream.
+    Pause,
+    /// Bring the capture stream back; listening waits for the hotkey.
+    Resume,
+    /// Both at once: the system slept and woke up between two polls.
+    PauseAndResume,
+}
+
+impl AwayAction {
+    pub fn pauses(self) -> bool {
+        matches!(self, AwayAction::Pause | AwayAction::PauseAndResume)
+    }
+
+    pub fn resumes(self) -> bool {
+        matches!(self, AwayAction::Resume | AwayAction::PauseAndResume)
+    }
+}
+
+/// Tracks whether the user is away, so repeated or overlapping events (a lock
+/// followed by a sleep, for instance) pause and resume only once.
+#[derive(Debug, Default)]
+pub struct AwayState {
+    away: bool,
+}
+
+impl AwayState {
+    pub fn on_event(&mut self, event: AwayEvent) -> Option<AwayAction> {
+        match (event, self.away) {
+            (AwayEvent::Locked, false) => {
+                self.away = true;
+                Some(AwayAction::Pause)
+            }
+            (AwayEvent::Unlocked, true) => {
+                self.away = false;
+                Some(AwayAction::Resume)
+            }
+            // Woke up on the lock screen: wait for the unlock
+            (AwayEvent::Slept(_), true) => None,
+            (AwayEvent::Slept(_), false) => Some(AwayAction::PauseAndResume),
+            (AwayEvent::Locked, true) | (AwayEvent::Unlocked, false) => None,
+        }
+    }
+
+    /// Take the detector's next event and return what to do about it.
+    pub fn poll(&mut self, detector: &mut dyn AwayDetector) -> Option<AwayAction> {
+        detector.poll().and_then(|event| self.on_event(event))
+    }
+}
+
+/// Detects screen locks through logind and sleep through clock jumps.
+pub struct SystemAwayDetector {
+    lock_rx: Option<Receiver<bool>>,
+    sleep_offset: Option<Duration>,
+}
+
+impl SystemAwayDetector {
+    /// Start watching. Lock detection needs `loginctl` and a logind session;
+    /// without them only sleep is detected.
+    pub fn start(shutdown: Arc<AtomicBool>) -> Self {
+        let lock_rx = match std::env::var("XDG_SESSION_ID") {
+            Ok(session) if !session.is_empty() => {
+                let (tx, rx) = crossbeam_channel::bounded(4);
+                match std::thread::Builder::new()
+                    .name("lock_watch".into())
+                    .spawn(move || lock_watch_loop(&session, tx, shutdown))
+                {
+                    Ok(_) => Some(rx),
+                    Err(e) => {
+                        warn!("[client] Failed to start the screen lock watcher: {e}");
+                        None
+                    }
+                }
+            }
+            _ => {
+                debug!("[client] No logind session, screen locks will not pause the session");
+                None
+            }
+        };
+        Self {
+            lock_rx,
+            sleep_offset: sleep_offset(),
+        }
+    }
+}
+
+impl AwayDetector for SystemAwayDetector {
+    fn poll(&mut self) -> Option<AwayEvent> {
+        if let Some(rx) = &self.lock_rx
+            && let Ok(locked) = rx.try_recv()
+        {
+            return Some(if locked {
+                AwayEvent::Locked
+            } else {
+                AwayEvent::Unlocked
+            });
+        }
+        let offset = sleep_offset()?;
+        let slept = self
+            .sleep_offset
+            .and_then(|previous| slept_between(previous, offset));
+        self.sleep_offset = Some(offset);
+        slept.map(AwayEvent::Slept)
+    }
+}
+
+/// Time spent asleep since boot: the boot clock keeps counting during sleep,
+/// the monotonic clock does not.
+fn sleep_offset() -> Option<Duration> {
+    let read = |clock| {
+        // SAFETY: timespec is plain data, fully written by clock_gettime on success.
+        let mut ts: libc::timespec = unsafe { std::mem::zeroed() };
+        (unsafe { libc::clock_gettime(clock, &mut ts) } == 0)
+            .then(|| Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+    };
+    let boot = read(libc::CLOCK_BOOTTIME)?;
+    let monotonic = read(libc::CLOCK_MONOTONIC)?;
+    Some(boot.saturating_sub(monotonic))
+}
+
+/// How long the system slept between two sleep offsets, if long enough to count.
+fn slept_between(previous: Duration, current: Duration) -> Option<Duration> {
+    let slept = current.saturating_sub(previous);
+    (slept > SLEEP_THRESHOLD).then_some(slept)
+}
+
+/// Poll logind's LockedHint and send each change (the first reading only if locked).
+fn lock_watch_loop(session: &str, tx: Sender<bool>, shutdown: Arc<AtomicBool>) {
+    let mut last = false;
+    while !shutdown.load(Ordering::SeqCst) {
+        let output = Command::new("loginctl")
+            .args(["show-session", session, "-p", "LockedHint", "--value"])
+            .output();
+        let locked = match output {
+            Ok(out) if out.status.success() => {
+                match parse_locked_hint(&String::from_utf8_lossy(&out.stdout)) {
+                    Some(locked) => locked,
+                    None => {
+                        debug!("[client] Unexpected LockedHint value, stopping lock watcher");
+                        return;
+                    }
+                }
+            }
+            Ok(_) | Err(_) => {
+                debug!("[client] loginctl unavailable, screen locks will not pause the session");
+                return;
+            }
+        };
+        if locked != last {
+            last = locked;
+            if tx.send(locked).is_err() {
+                return;
+            }
+        }
+        std::thread::sleep(LOCK_POLL_INTERVAL);
+    }
+}
+
+fn parse_locked_hint(value: &str) -> Option<bool> {
+    match value.trim() {
+        "yes" => Some(true),
+        "no" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    struct ScriptedDetector(VecDeque<AwayEvent>);
+
+    impl AwayDetector for ScriptedDetector {
+        fn poll(&mut self) -> Option<AwayEvent> {
+            self.0.pop_front()
+        }
+    }
+
+    #[test]
+    fn injected_events_drive_the_state() {
+        let mut detector = ScriptedDetector(VecDeque::from([
+            AwayEvent::Locked,
+            AwayEvent::Locked,
+            AwayEvent::Unlocked,
+        ]));
+        let mut state = AwayState::default();
+        let actions: Vec<_> = (0..4).map(|_| state.poll(&mut detector)).collect();
+        assert_eq!(
+            actions,
+            [
+                Some(AwayAction::Pause),
+                None,
+                Some(AwayAction::Resume),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn lock_and_sleep_pause_and_resume_once() {
+        let mut state = AwayState::default();
+        assert_eq!(state.on_event(AwayEvent::Unlocked), None);
+        assert_eq!(state.on_event(AwayEvent::Locked), Some(AwayAction::Pause));
+        assert!(state.away);
+        assert_eq!(state.on_event(AwayEvent::Locked), None);
+        // Slept while locked: still away until the unlock
+        assert_eq!(
+            state.on_event(AwayEvent::Slept(Duration::from_secs(600))),
+            None
+        );
+        assert_eq!(
+            state.on_event(AwayEvent::Unlocked),
+            Some(AwayAction::Resume)
+        );
+        assert!(!state.away);
+        // Sleep without a lock screen: pause and resume in one go
+        let action = state
+            .on_event(AwayEvent::Slept(Duration::from_secs(60)))
+            .unwrap();
+        assert!(action.pauses() && action.resumes());
+        assert!(!state.away);
+    }
+
+    #[test]
+    fn sleep_needs_a_clock_jump_above_threshold() {
+        let base = Duration::from_secs(100);
+        assert_eq!(slept_between(base, base + Duration::from_millis(10)), None);
+        assert_eq!(
+            slept_between(base, base + Duration::from_secs(30)),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(slept_between(base, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn locked_hint_values() {
+        assert_eq!(parse_locked_hint("yes\n"), Some(true));
+        assert_eq!(parse_locked_hint("no\n"), Some(false));
+        assert_eq!(parse_locked_hint(""), None);
+    }
+}
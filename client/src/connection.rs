@@ -1,9 +1,11 @@
 use anyhow::{Context, Result};
 use std::io::{BufReader, BufWriter};
 use std::net::{SocketAddr, TcpStream};
+use std::sync::Arc;
 use std::time::Duration;
 
-use space_lt_common::protocol::{ServerMsg, read_server_msg};
+use space_lt_common::protocol::{ClientMsg, ServerMsg, read_server_msg, write_client_msg};
+use space_lt_common::transport::{TlsClientConfig, Transport};
 use space_lt_common::{info, warn};
 
 // Re-export from common for use by main.rs
@@ -11,33 +13,78 @@ pub use space_lt_common::protocol::is_disconnect;
 
 /// Connect timeout for TCP connection attempts.
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Connect and Ready timeout of the address check at setup.
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Port used when the address has none.
+const DEFAULT_PORT: u16 = 9500;
 /// Maximum reconnection attempts with exponential backoff.
 const MAX_CONNECT_ATTEMPTS: u32 = 3;
 /// Exponential backoff delays in seconds (1s, 2s, 4s).
 const BACKOFF_SECS: [u64; 3] = [1, 2, 4];
 
+/// `addr` with the default port when it has none.
+pub fn with_default_port(addr: &str) -> String {
+    if addr.contains(':') {
+        addr.to_string()
+    } else {
+        format!("{addr}:{DEFAULT_PORT}")
+    }
+}
+
 /// TCP connection to the server, replacing the old SSH-based RemoteTranscriber.
 pub struct TcpConnection {
-    reader: BufReader<TcpStream>,
-    writer: BufWriter<TcpStream>,
+    reader: BufReader<Transport>,
+    writer: BufWriter<Transport>,
+    /// Set when the server reported a session already running (unix secs it started).
+    active_since: Option<u64>,
 }
 
 impl TcpConnection {
     /// Connect to the server at the given address, wait for Ready handshake.
     ///
-    /// Times out after 10 seconds if the server is unreachable.
-    pub fn connect(addr: &str) -> Result<Self> {
+    /// Times out after 10 seconds if the server is unreachable. With `tls`, the
+    /// TLS handshake runs before the Ready handshake and all framing goes through
+    /// the encrypted tunnel.
+    pub fn connect(addr: &str, tls: Option<&Arc<TlsClientConfig>>) -> Result<Self> {
+        Self::connect_within(addr, tls, CONNECT_TIMEOUT, None)
+    }
+
+    /// Like [`TcpConnection::connect`], giving up when the connection or the
+    /// Ready takes longer than `timeout` (the address check at setup).
+    pub fn connect_timeout(
+        addr: &str,
+        tls: Option<&Arc<TlsClientConfig>>,
+        timeout: Duration,
+    ) -> Result<Self> {
+        Self::connect_within(addr, tls, timeout, Some(timeout))
+    }
+
+    fn connect_within(
+        addr: &str,
+        tls: Option<&Arc<TlsClientConfig>>,
+        connect_timeout: Duration,
+        ready_timeout: Option<Duration>,
+    ) -> Result<Self> {
         info!("[client] Connecting to {addr}...");
 
         let socket_addr: SocketAddr = addr
             .parse()
             .context("invalid server address (expected IP:port)")?;
-        let stream = TcpStream::connect_timeout(&socket_addr, CONNECT_TIMEOUT)
+        let stream = TcpStream::connect_timeout(&socket_addr, connect_timeout)
             .context("connecting to server")?;
+        stream
+            .set_read_timeout(ready_timeout)
+            .context("setting read timeout")?;
 
         // Disable Nagle's algorithm for low-latency audio streaming
         stream.set_nodelay(true).context("setting TCP_NODELAY")?;
 
+        let stream = match tls {
+            Some(config) => Transport::connect_tls(stream, &socket_addr, config.clone())
+                .with_context(|| format!("securing connection to {addr}"))?,
+            None => Transport::Plain(stream),
+        };
+
         let reader = BufReader::new(
             stream
                 .try_clone()
@@ -45,14 +92,27 @@ impl TcpConnection {
         );
         let writer = BufWriter::new(stream);
 
-        let mut conn = Self { reader, writer };
+        let mut conn = Self {
+            reader,
+            writer,
+            active_since: None,
+        };
 
         // Wait for Ready from server
         let msg = conn.read_server_msg().context("waiting for server Ready")?;
         match msg {
             ServerMsg::Ready => info!("[client] Server ready"),
+            ServerMsg::ReadyActiveSession(since) => {
+                info!("[client] Server ready (a session is already running)");
+                conn.active_since = Some(since);
+            }
+            ServerMsg::Error(e) => anyhow::bail!("Server refused the connection: {e}"),
             other => anyhow::bail!("Expected Ready, got {other:?}"),
         }
+        conn.writer
+            .get_ref()
+            .set_read_timeout(None)
+            .context("clearing read timeout")?;
 
         Ok(conn)
     }
@@ -61,7 +121,7 @@ impl TcpConnection {
     ///
     /// Useful when the server may not be ready at client startup, or after a
     /// TCP connection drop.
-    pub fn connect_with_retry(addr: &str) -> Result<Self> {
+    pub fn connect_with_retry(addr: &str, tls: Option<&Arc<TlsClientConfig>>) -> Result<Self> {
         let mut last_err = None;
         for attempt in 1..=MAX_CONNECT_ATTEMPTS {
             if attempt > 1 {
@@ -69,7 +129,7 @@ impl TcpConnection {
                 info!("[client] Retrying in {delay}s...");
                 std::thread::sleep(Duration::from_secs(delay));
             }
-            match Self::connect(addr) {
+            match Self::connect(addr, tls) {
                 Ok(conn) => return Ok(conn),
                 Err(e) => {
                     warn!(
@@ -84,13 +144,28 @@ impl TcpConnection {
             .context("all TCP connection attempts failed"))
     }
 
+    /// When the server still has a session running, the unix time it started.
+    ///
+    /// The caller must answer with `answer_takeover` before using the connection.
+    pub fn active_session_since(&self) -> Option<u64> {
+        self.active_since
+    }
+
+    /// Tell the server to hand us the running session (`true`) or start a fresh one.
+    pub fn answer_takeover(&mut self, take_over: bool) -> Result<()> {
+        write_client_msg(&mut self.writer, &ClientMsg::SessionTakeover(take_over))
+            .context("sending session takeover choice")?;
+        self.active_since = None;
+        Ok(())
+    }
+
     /// Read the next server message.
     pub fn read_server_msg(&mut self) -> Result<ServerMsg> {
         read_server_msg(&mut self.reader)
     }
 
     /// Get a clone of the underlying TCP stream for shutdown signaling.
-    pub fn try_clone_stream(&self) -> Result<TcpStream> {
+    pub fn try_clone_stream(&self) -> Result<Transport> {
         self.writer
             .get_ref()
             .try_clone()
@@ -98,7 +173,7 @@ impl TcpConnection {
     }
 
     /// Split into reader and writer for separate thread ownership.
-    pub fn into_split(self) -> (BufReader<TcpStream>, BufWriter<TcpStream>) {
+    pub fn into_split(self) -> (BufReader<Transport>, BufWriter<Transport>) {
         (self.reader, self.writer)
     }
 }
@@ -106,9 +181,66 @@ impl TcpConnection {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use space_lt_common::protocol::{ClientMsg, write_client_msg, write_server_msg};
+    use space_lt_common::protocol::write_server_msg;
     use std::io::BufWriter as StdBufWriter;
     use std::net::TcpListener;
+    use std::path::PathBuf;
+
+    /// Temp files removed when dropped, even if the test fails.
+    struct TempFiles(Vec<PathBuf>);
+
+    impl Drop for TempFiles {
+        fn drop(&mut self) {
+            for path in &self.0 {
+                std::fs::remove_file(path).ok();
+            }
+        }
+    }
+
+    #[test]
+    fn connect_timeout_gives_up_on_a_silent_server() {
+        // Accepts (in the backlog) but never sends Ready
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let start = std::time::Instant::now();
+        let result = TcpConnection::connect_timeout(&addr, None, Duration::from_millis(200));
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        drop(listener);
+        assert!(TcpConnection::connect_timeout(&addr, None, CHECK_TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn connect_timeout_clears_the_timeout_after_ready() {
+        use std::io::Write;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server_handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = StdBufWriter::new(stream);
+            write_server_msg(&mut writer, &ServerMsg::Ready).unwrap();
+            writer.flush().unwrap();
+            // Slower than the check's timeout
+            std::thread::sleep(Duration::from_millis(400));
+            write_server_msg(&mut writer, &ServerMsg::Text("later".into())).unwrap();
+        });
+
+        let mut conn = TcpConnection::connect_timeout(
+            &format!("127.0.0.1:{port}"),
+            None,
+            Duration::from_millis(200),
+        )
+        .unwrap();
+        assert!(matches!(conn.read_server_msg().unwrap(), ServerMsg::Text(t) if t == "later"));
+        server_handle.join().unwrap();
+    }
+
+    #[test]
+    fn default_port_is_added_when_missing() {
+        assert_eq!(with_default_port("10.0.0.2"), "10.0.0.2:9500");
+        assert_eq!(with_default_port("10.0.0.2:9600"), "10.0.0.2:9600");
+    }
 
     #[test]
     fn connect_receives_ready() {
@@ -123,7 +255,7 @@ mod tests {
             std::thread::sleep(Duration::from_millis(100));
         });
 
-        let conn = TcpConnection::connect(&format!("127.0.0.1:{port}")).unwrap();
+        let conn = TcpConnection::connect(&format!("127.0.0.1:{port}"), None).unwrap();
         drop(conn);
         server_handle.join().unwrap();
     }
@@ -139,7 +271,7 @@ mod tests {
             write_server_msg(&mut writer, &ServerMsg::Error("bad".into())).unwrap();
         });
 
-        let result = TcpConnection::connect(&format!("127.0.0.1:{port}"));
+        let result = TcpConnection::connect(&format!("127.0.0.1:{port}"), None);
         assert!(result.is_err());
         server_handle.join().unwrap();
     }
@@ -163,7 +295,7 @@ mod tests {
             std::thread::sleep(Duration::from_millis(100));
         });
 
-        let conn = TcpConnection::connect_with_retry(&format!("127.0.0.1:{port}")).unwrap();
+        let conn = TcpConnection::connect_with_retry(&format!("127.0.0.1:{port}"), None).unwrap();
         drop(conn);
         server_handle.join().unwrap();
     }
@@ -194,7 +326,7 @@ mod tests {
             write_server_msg(&mut writer, &ServerMsg::TtsEnd).unwrap();
         });
 
-        let conn = TcpConnection::connect(&format!("127.0.0.1:{port}")).unwrap();
+        let conn = TcpConnection::connect(&format!("127.0.0.1:{port}"), None).unwrap();
         let (mut reader, mut writer) = conn.into_split();
 
         // Send AudioSegment via writer half
@@ -215,4 +347,80 @@ mod tests {
 
         server_handle.join().unwrap();
     }
+
+    #[test]
+    fn connect_tls_receives_ready_through_tunnel() {
+        let dir = std::env::temp_dir();
+        let pid = std::process::id();
+        let cert_path = dir.join(format!("space_lt_client_tls_{pid}.crt"));
+        let key_path = dir.join(format!("space_lt_client_tls_{pid}.key"));
+        let _cleanup = TempFiles(vec![cert_path.clone(), key_path.clone()]);
+        let ck = rcgen::generate_simple_self_signed(vec!["127.0.0.1".into()]).unwrap();
+        std::fs::write(&cert_path, ck.cert.pem()).unwrap();
+        std::fs::write(&key_path, ck.key_pair.serialize_pem()).unwrap();
+
+        let server_cfg = space_lt_common::transport::server_config(&cert_path, &key_path).unwrap();
+        let client_cfg =
+            space_lt_common::transport::client_config(Some(&cert_path), false).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server_handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let transport = Transport::accept_tls(stream, server_cfg).unwrap();
+            let mut reader = BufReader::new(transport.try_clone().unwrap());
+            let mut writer = StdBufWriter::new(transport);
+            write_server_msg(&mut writer, &ServerMsg::Ready).unwrap();
+
+            let msg = space_lt_common::protocol::read_client_msg(&mut reader).unwrap();
+            assert!(matches!(msg, ClientMsg::PauseRequest));
+        });
+
+        let conn = TcpConnection::connect(&format!("127.0.0.1:{port}"), Some(&client_cfg)).unwrap();
+        let (_reader, mut writer) = conn.into_split();
+        write_client_msg(&mut writer, &ClientMsg::PauseRequest).unwrap();
+        server_handle.join().unwrap();
+    }
+
+    #[test]
+    fn connect_tls_to_plain_server_fails() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server_handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = StdBufWriter::new(stream);
+            write_server_msg(&mut writer, &ServerMsg::Ready).unwrap();
+        });
+
+        let client_cfg = space_lt_common::transport::client_config(None, true).unwrap();
+        let err = TcpConnection::connect(&format!("127.0.0.1:{port}"), Some(&client_cfg))
+            .err()
+            .expect("TLS against a plain server should fail");
+        assert!(format!("{err:#}").contains("TLS handshake failed"));
+        server_handle.join().unwrap();
+    }
+
+    #[test]
+    fn connect_reports_active_session_and_sends_takeover() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server_handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = StdBufWriter::new(stream);
+            write_server_msg(&mut writer, &ServerMsg::ReadyActiveSession(1_771_000_000)).unwrap();
+
+            let msg = space_lt_common::protocol::read_client_msg(&mut reader).unwrap();
+            assert!(matches!(msg, ClientMsg::SessionTakeover(true)));
+        });
+
+        let mut conn = TcpConnection::connect(&format!("127.0.0.1:{port}"), None).unwrap();
+        assert_eq!(conn.active_session_since(), Some(1_771_000_000));
+        conn.answer_takeover(true).unwrap();
+        assert_eq!(conn.active_session_since(), None);
+        server_handle.join().unwrap();
+    }
 }
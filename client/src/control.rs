@@ -0,0 +1,282 @@
+//! `--control`: the control socket (see `space_lt_common::control`). The
+//! session state goes to every connected process; their commands come back
+//! as the key actions they stand for.
+
+use anyhow::{Context, Result, bail};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use space_lt_common::control::{self, ClientState, Command};
+use space_lt_common::{debug, info, warn};
+
+use crate::PollAction;
+use crate::keyboard::Keys;
+use crate::session_view::SessionEvent;
+
+/// Commands not yet picked up by the main loop; more are dropped.
+const COMMAND_QUEUE: usize = 8;
+
+/// How often the state is re-checked without session events (a prompt opening
+/// sends none).
+const STATE_POLL: Duration = Duration::from_millis(100);
+
+/// A process that stops reading is dropped rather than holding up the others.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The key action a command stands for.
+fn action(command: Command) -> PollAction {
+    match command {
+        Command::Cancel => PollAction::Cancel,
+        Command::Replay => PollAction::Replay,
+        Command::SlowReplay => PollAction::SlowReplay,
+    }
+}
+
+/// What the session events say the client is doing.
+#[derive(Debug, Default)]
+struct Activity {
+    listening: bool,
+    thinking: bool,
+    speaking: bool,
+}
+
+impl Activity {
+    fn apply(&mut self, event: &SessionEvent) {
+        match event {
+            SessionEvent::Listening(listening) => {
+                self.listening = *listening;
+                // A new turn supersedes the one waiting for a reply
+                if *listening {
+                    self.thinking = false;
+                }
+            }
+            SessionEvent::Speaking(speaking) => self.speaking = *speaking,
+            SessionEvent::Thinking(label) => self.thinking = label.is_some(),
+            _ => {}
+        }
+    }
+
+    /// An open prompt comes first: nothing goes on until it is answered.
+    fn state(&self, prompt_open: bool) -> ClientState {
+        if prompt_open {
+            ClientState::NeedsInput
+        } else if self.listening {
+            ClientState::Listening
+        } else if self.speaking {
+            ClientState::Speaking
+        } else if self.thinking {
+            ClientState::Thinking
+        } else {
+            ClientState::Idle
+        }
+    }
+}
+
+/// The connected processes, and the state they were last sent.
+struct Watchers {
+    streams: Vec<UnixStream>,
+    state: ClientState,
+}
+
+/// Removes the socket file when the client is done with it.
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+/// Serve the control socket at `path` until `shutdown`. Returns the sender
+/// the session events go to, and the commands received.
+pub fn start(
+    path: &Path,
+    keys: Keys,
+    shutdown: Arc<AtomicBool>,
+) -> Result<(ControlSocket, Sender<SessionEvent>, Receiver<PollAction>)> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            bail!("{} is in use by another client", path.display());
+        }
+        std::fs::remove_file(path).context("removing stale control socket")?;
+    }
+    let listener =
+        UnixListener::bind(path).with_context(|| format!("binding {}", path.display()))?;
+    info!("[client] Control socket: {}", path.display());
+
+    let (events_tx, events_rx) = crossbeam_channel::unbounded();
+    let (commands_tx, commands_rx) = crossbeam_channel::bounded(COMMAND_QUEUE);
+    let watchers = Arc::new(Mutex::new(Watchers {
+        streams: Vec::new(),
+        state: ClientState::Idle,
+    }));
+    let accept_watchers = watchers.clone();
+    std::thread::Builder::new()
+        .name("control_accept".into())
+        .spawn(move || accept_loop(listener, accept_watchers, commands_tx))?;
+    std::thread::Builder::new()
+        .name("control_publish".into())
+        .spawn(move || publish_loop(events_rx, keys, watchers, shutdown))?;
+    Ok((
+        ControlSocket {
+            path: path.to_path_buf(),
+        },
+        events_tx,
+        commands_rx,
+    ))
+}
+
+fn accept_loop(
+    listener: UnixListener,
+    watchers: Arc<Mutex<Watchers>>,
+    commands: Sender<PollAction>,
+) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("[client] Control socket: {e}");
+                continue;
+            }
+        };
+        let Ok(reader) = stream.try_clone() else {
+            continue;
+        };
+        let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+        {
+            let mut watchers = watchers.lock().unwrap_or_else(|e| e.into_inner());
+            // Where things stand, then every change
+            let mut stream = stream;
+            if writeln!(stream, "{}", control::state_line(watchers.state)).is_err() {
+                continue;
+            }
+            watchers.streams.push(stream);
+        }
+        debug!("[client] Control process connected");
+        let commands = commands.clone();
+        let _ = std::thread::Builder::new()
+            .name("control_commands".into())
+            .spawn(move || command_loop(reader, commands));
+    }
+}
+
+/// Pass on the commands of one process until it disconnects.
+fn command_loop(stream: UnixStream, commands: Sender<PollAction>) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match control::parse_command(&line) {
+            Ok(command) => {
+                debug!("[client] Control command: {}", command.name());
+                if commands.try_send(action(command)).is_err() {
+                    warn!("[client] Control command {} dropped", command.name());
+                }
+            }
+            Err(e) => warn!("[client] Control socket: {e:#}"),
+        }
+    }
+    debug!("[client] Control process disconnected");
+}
+
+fn publish_loop(
+    events: Receiver<SessionEvent>,
+    keys: Keys,
+    watchers: Arc<Mutex<Watchers>>,
+    shutdown: Arc<AtomicBool>,
+) {
+    let mut activity = Activity::default();
+    while !shutdown.load(Ordering::SeqCst) {
+        match events.recv_timeout(STATE_POLL) {
+            Ok(event) => activity.apply(&event),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        let state = activity.state(keys.prompt_open());
+        let mut watchers = watchers.lock().unwrap_or_else(|e| e.into_inner());
+        if state != watchers.state {
+            watchers.state = state;
+            let line = control::state_line(state);
+            watchers
+                .streams
+                .retain_mut(|stream| writeln!(stream, "{line}").is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_events_make_the_state() {
+        let mut activity = Activity::default();
+        assert_eq!(activity.state(false), ClientState::Idle);
+        activity.apply(&SessionEvent::Listening(true));
+        assert_eq!(activity.state(false), ClientState::Listening);
+        activity.apply(&SessionEvent::Listening(false));
+        activity.apply(&SessionEvent::Thinking(Some("Thinking".into())));
+        assert_eq!(activity.state(false), ClientState::Thinking);
+        activity.apply(&SessionEvent::Speaking(true));
+        assert_eq!(activity.state(false), ClientState::Speaking);
+        // The feedback choice after the reply
+        assert_eq!(activity.state(true), ClientState::NeedsInput);
+        activity.apply(&SessionEvent::Thinking(None));
+        activity.apply(&SessionEvent::Speaking(false));
+        assert_eq!(activity.state(false), ClientState::Idle);
+        // Talking again drops the wait for the last reply
+        activity.apply(&SessionEvent::Thinking(Some("Thinking".into())));
+        activity.apply(&SessionEvent::Listening(true));
+        activity.apply(&SessionEvent::Listening(false));
+        assert_eq!(activity.state(false), ClientState::Idle);
+    }
+
+    #[test]
+    fn a_control_process_follows_the_state_and_sends_commands() {
+        let path =
+            std::env::temp_dir().join(format!("space_lt_control_test_{}.sock", std::process::id()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (keys_tx, keys_rx) = crossbeam_channel::bounded(1);
+        let keys = Keys::from_channel(keys_rx);
+        let (socket, events, commands) = start(&path, keys.clone(), shutdown.clone()).unwrap();
+        // Taken: a second client is refused
+        assert!(start(&path, keys.clone(), shutdown.clone()).is_err());
+
+        let tray = UnixStream::connect(&path).unwrap();
+        let mut lines = BufReader::new(tray.try_clone().unwrap()).lines();
+        let mut next_state = || control::parse_state(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(next_state(), Some(ClientState::Idle));
+        events.send(SessionEvent::Listening(true)).unwrap();
+        assert_eq!(next_state(), Some(ClientState::Listening));
+        events.send(SessionEvent::Listening(false)).unwrap();
+        assert_eq!(next_state(), Some(ClientState::Idle));
+        let prompt = keys.prompt();
+        assert_eq!(next_state(), Some(ClientState::NeedsInput));
+        drop(prompt);
+        assert_eq!(next_state(), Some(ClientState::Idle));
+
+        let mut tray_w = tray;
+        writeln!(tray_w, "{{\"command\": \"launch\"}}").unwrap();
+        writeln!(tray_w, "{}", control::command_line(Command::Replay)).unwrap();
+        writeln!(tray_w, "{}", control::command_line(Command::Cancel)).unwrap();
+        let timeout = Duration::from_secs(2);
+        // The unknown one is skipped
+        assert_eq!(commands.recv_timeout(timeout).unwrap(), PollAction::Replay);
+        assert_eq!(commands.recv_timeout(timeout).unwrap(), PollAction::Cancel);
+
+        shutdown.store(true, Ordering::SeqCst);
+        drop(keys_tx);
+        drop(socket);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{Result, bail};
+use cpal::traits::{DeviceTrait, HostTrait};
+
+use space_lt_common::warn;
+
+/// An audio device as listed by the setup screens.
+pub struct AudioDevice {
+    pub device: cpal::Device,
+    pub name: String,
+    /// Native rate of its default config; `None` when it reports none
+    /// (listed, but cannot be picked).
+    pub default_rate: Option<u32>,
+}
+
+impl AudioDevice {
+    pub fn label(&self, is_default: bool) -> String {
+        let default = if is_default { " (default)" } else { "" };
+        match self.default_rate {
+            Some(rate) => format!("{}{default} \u{2014} {rate} Hz", self.name),
+            None => format!(
+                "{}{default} \u{2014} unusable (no default config)",
+                self.name
+            ),
+        }
+    }
+}
+
+pub fn device_name(device: &cpal::Device) -> String {
+    device
+        .description()
+        .map(|d: cpal::DeviceDescription| d.name().to_string())
+        .unwrap_or_else(|_| "Default".into())
+}
+
+/// Input devices of `host`, and the index of the default one (if listed).
+pub fn list_input_devices(host: &cpal::Host) -> Result<(Vec<AudioDevice>, Option<usize>)> {
+    let devices = host
+        .input_devices()
+        .map_err(|e| anyhow::anyhow!("Could not list audio input devices: {e}"))?
+        .map(|device| AudioDevice {
+            name: device_name(&device),
+            default_rate: device.default_input_config().ok().map(|c| c.sample_rate()),
+            device,
+        })
+        .collect();
+    Ok(with_default(devices, host.default_input_device()))
+}
+
+/// Output devices of `host`, and the index of the default one (if listed).
+pub fn list_output_devices(host: &cpal::Host) -> Result<(Vec<AudioDevice>, Option<usize>)> {
+    let devices = host
+        .output_devices()
+        .map_err(|e| anyhow::anyhow!("Could not list audio output devices: {e}"))?
+        .map(|device| AudioDevice {
+            name: device_name(&device),
+            default_rate: device.default_output_config().ok().map(|c| c.sample_rate()),
+            device,
+        })
+        .collect();
+    Ok(with_default(devices, host.default_output_device()))
+}
+
+/// Locate `default` in `devices` by id, or by name when ids are unavailable.
+fn with_default(
+    devices: Vec<AudioDevice>,
+    default: Option<cpal::Device>,
+) -> (Vec<AudioDevice>, Option<usize>) {
+    let default = default.and_then(|d| {
+        let id = d.id().ok();
+        let name = device_name(&d);
+        devices
+            .iter()
+            .position(|candidate| match (&id, candidate.device.id().ok()) {
+                (Some(id), Some(candidate_id)) => *id == candidate_id,
+                _ => candidate.name == name,
+            })
+    });
+    (devices, default)
+}
+
+/// Pick the device whose name best matches `query` (`--input-device`,
+/// `--output-device`); `kind` ("input" or "output") names them in errors.
+///
+/// Matching ignores case and punctuation: every word of the query must appear
+/// in the name. An exact name wins over partial matches; several partial
+/// matches are an error listing them.
+pub fn match_device_name(names: &[&str], query: &str, kind: &str) -> Result<usize> {
+    let words = |s: &str| -> Vec<String> {
+        s.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let wanted = words(query);
+    if wanted.is_empty() {
+        bail!("--{kind}-device needs a device name");
+    }
+    let matches: Vec<usize> = names
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| {
+            let name = words(name).join(" ");
+            wanted.iter().all(|w| name.contains(w.as_str()))
+        })
+        .map(|(i, _)| i)
+        .collect();
+    if let Some(&exact) = matches.iter().find(|&&i| words(names[i]) == wanted) {
+        return Ok(exact);
+    }
+    match matches.as_slice() {
+        [one] => Ok(*one),
+        [] => bail!(
+            "No {kind} device matches \"{query}\". Available: {}",
+            names.join(", ")
+        ),
+        several => bail!(
+            "\"{query}\" matches several {kind} devices: {}",
+            several
+                .iter()
+                .map(|&i| names[i])
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// The device of `devices` (name, device) matching `query`, or `None` with a
+/// warning so the caller falls back to the default device.
+pub fn find_or_default<T>(devices: Vec<(String, T)>, query: &str, kind: &str) -> Option<T> {
+    let names: Vec<&str> = devices.iter().map(|(name, _)| name.as_str()).collect();
+    match match_device_name(&names, query, kind) {
+        Ok(idx) => devices.into_iter().nth(idx).map(|(_, device)| device),
+        Err(e) => {
+            warn!("[client] {e:#}; using the default {kind} device");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 4] = [
+        "HD Webcam C270 Mono",
+        "Jabra Evolve2 65, USB Audio",
+        "Jabra Evolve2 65 Mono",
+        "default",
+    ];
+
+    #[test]
+    fn device_match_ignores_case_and_punctuation() {
+        assert_eq!(match_device_name(&NAMES, "webcam", "input").unwrap(), 0);
+        assert_eq!(match_device_name(&NAMES, "JABRA usb", "input").unwrap(), 1);
+        assert_eq!(
+            match_device_name(&NAMES, "evolve2-65 mono", "input").unwrap(),
+            2
+        );
+        assert_eq!(match_device_name(&NAMES, "Default", "input").unwrap(), 3);
+    }
+
+    #[test]
+    fn device_match_prefers_exact_names_and_reports_ambiguity() {
+        let err = match_device_name(&NAMES, "jabra", "input")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("several"), "{err}");
+        assert!(err.contains("Jabra Evolve2 65 Mono"));
+        // An exact name wins even when it is also part of another name
+        let names = ["USB Mic", "USB Mic Pro"];
+        assert_eq!(match_device_name(&names, "usb mic", "input").unwrap(), 0);
+    }
+
+    #[test]
+    fn device_match_fails_without_a_match() {
+        let err = match_device_name(&NAMES, "yeti", "input")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("No input device matches \"yeti\""), "{err}");
+        assert!(err.contains("HD Webcam C270 Mono"));
+        assert!(match_device_name(&NAMES, "  ", "input").is_err());
+    }
+
+    #[test]
+    fn output_device_falls_back_to_default_when_not_found() {
+        let devices = || {
+            vec![
+                ("Built-in Speakers".to_string(), 0),
+                ("Jabra Evolve2 65, USB Audio".to_string(), 1),
+            ]
+        };
+        assert_eq!(find_or_default(devices(), "jabra", "output"), Some(1));
+        assert_eq!(find_or_default(devices(), "Bose QC", "output"), None);
+        assert_eq!(
+            find_or_default(Vec::<(String, u8)>::new(), "jabra", "output"),
+            None
+        );
+    }
+}
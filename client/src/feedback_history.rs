@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+/// Default number of feedback blocks kept for recall.
+pub const DEFAULT_MAX_ENTRIES: usize = 100;
+
+/// One feedback block received during the session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedbackEntry {
+    /// Local wall-clock time the feedback arrived (HH:MM:SS).
+    pub time: String,
+    /// The user sentence the feedback applies to, if known.
+    pub sentence: Option<String>,
+    /// Raw feedback text as sent by the server.
+    pub feedback: String,
+}
+
+/// Bounded, insertion-ordered store of the session's feedback blocks.
+pub struct FeedbackHistory {
+    entries: VecDeque<FeedbackEntry>,
+    max_entries: usize,
+}
+
+impl FeedbackHistory {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            max_entries,
+        }
+    }
+
+    /// Append an entry, dropping the oldest one past the cap.
+    pub fn push(&mut self, entry: FeedbackEntry) {
+        if self.max_entries == 0 {
+            return;
+        }
+        if self.entries.len() == self.max_entries {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The last `n` entries, oldest first.
+    pub fn recent(&self, n: usize) -> impl Iterator<Item = &FeedbackEntry> {
+        self.entries
+            .iter()
+            .skip(self.entries.len().saturating_sub(n))
+    }
+
+    /// All entries, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &FeedbackEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(n: usize) -> FeedbackEntry {
+        FeedbackEntry {
+            time: format!("10:00:{n:02}"),
+            sentence: Some(format!("sentence {n}")),
+            feedback: format!("RED: mistake {n}"),
+        }
+    }
+
+    fn feedback<'a>(entries: impl Iterator<Item = &'a FeedbackEntry>) -> Vec<&'a str> {
+        entries.map(|e| e.feedback.as_str()).collect()
+    }
+
+    #[test]
+    fn keeps_insertion_order() {
+        let mut history = FeedbackHistory::new(10);
+        for n in 0..3 {
+            history.push(entry(n));
+        }
+        assert_eq!(
+            feedback(history.iter()),
+            ["RED: mistake 0", "RED: mistake 1", "RED: mistake 2"]
+        );
+    }
+
+    #[test]
+    fn cap_drops_oldest_entries() {
+        let mut history = FeedbackHistory::new(DEFAULT_MAX_ENTRIES);
+        for n in 0..DEFAULT_MAX_ENTRIES + 5 {
+            history.push(entry(n));
+        }
+        assert_eq!(history.len(), DEFAULT_MAX_ENTRIES);
+        assert_eq!(history.iter().next().unwrap(), &entry(5));
+        assert_eq!(
+            history.iter().last().unwrap(),
+            &entry(DEFAULT_MAX_ENTRIES + 4)
+        );
+    }
+
+    #[test]
+    fn recent_returns_last_n_oldest_first() {
+        let mut history = FeedbackHistory::new(10);
+        assert_eq!(history.recent(3).count(), 0);
+        for n in 0..5 {
+            history.push(entry(n));
+        }
+        assert_eq!(
+            feedback(history.recent(2)),
+            ["RED: mistake 3", "RED: mistake 4"]
+        );
+        assert_eq!(history.recent(50).count(), 5);
+    }
+}
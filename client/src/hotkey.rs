@@ -1,12 +1,262 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use evdev::{Device, EventType, KeyCode};
-use std::sync::Arc;
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use space_lt_common::{debug, warn};
 
+/// How the hotkey drives listening.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HotkeyMode {
+    /// Each press flips listening on or off.
+    Toggle,
+    /// Listening while the key is held; releasing it ends the turn.
+    Hold,
+}
+
+/// Where hotkey presses come from.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum HotkeyBackend {
+    /// The keyboards' evdev devices: works whichever window is focused.
+    #[default]
+    Evdev,
+    /// Key presses in the client's terminal ([Space] toggles listening), for
+    /// users who cannot read the evdev devices.
+    Terminal,
+}
+
+impl HotkeyBackend {
+    /// Parse `--hotkey-backend` ("evdev" or "terminal").
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "evdev" => Ok(HotkeyBackend::Evdev),
+            "terminal" => Ok(HotkeyBackend::Terminal),
+            other => bail!("expected \"evdev\" or \"terminal\", got \"{other}\""),
+        }
+    }
+}
+
+/// Whether any keyboard's evdev device can be read.
+pub fn keyboards_available() -> bool {
+    !find_keyboards().is_empty()
+}
+
+/// A debounced change of the hotkey's physical state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyTransition {
+    Press,
+    Release,
+}
+
+/// Debounce a raw evdev key value (1 down, 0 up, 2 autorepeat) against the
+/// key's state on this device. Autorepeats, and the repeated key-downs some
+/// keyboards send instead, are not transitions.
+pub fn key_transition(value: i32, held: &mut bool) -> Option<KeyTransition> {
+    match value {
+        1 if !*held => {
+            *held = true;
+            Some(KeyTransition::Press)
+        }
+        0 if *held => {
+            *held = false;
+            Some(KeyTransition::Release)
+        }
+        _ => None,
+    }
+}
+
+/// The listening state after `transition`, or `None` when it changes nothing.
+/// Presses are ignored while `suspended`; a release still ends a held turn.
+pub fn next_listening(
+    mode: HotkeyMode,
+    transition: KeyTransition,
+    listening: bool,
+    suspended: bool,
+) -> Option<bool> {
+    match (mode, transition) {
+        (_, KeyTransition::Press) if suspended => None,
+        (HotkeyMode::Toggle, KeyTransition::Press) => Some(!listening),
+        (HotkeyMode::Toggle, KeyTransition::Release) => None,
+        (HotkeyMode::Hold, KeyTransition::Press) => (!listening).then_some(true),
+        (HotkeyMode::Hold, KeyTransition::Release) => listening.then_some(false),
+    }
+}
+
+/// Esc cancels (setup screens, replays, prompts): it cannot be the hotkey too.
+pub const CANCEL_KEY: KeyCode = KeyCode::KEY_ESC;
+
+/// Keys whose evdev name reads poorly once the prefix is gone.
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::KEY_SCROLLLOCK, "ScrollLock"),
+    (KeyCode::KEY_CAPSLOCK, "CapsLock"),
+    (KeyCode::KEY_NUMLOCK, "NumLock"),
+    (KeyCode::KEY_SYSRQ, "PrintScreen"),
+    (KeyCode::KEY_PAGEUP, "PageUp"),
+    (KeyCode::KEY_PAGEDOWN, "PageDown"),
+    (KeyCode::KEY_LEFTCTRL, "Left Ctrl"),
+    (KeyCode::KEY_RIGHTCTRL, "Right Ctrl"),
+    (KeyCode::KEY_LEFTALT, "Left Alt"),
+    (KeyCode::KEY_RIGHTALT, "Right Alt"),
+    (KeyCode::KEY_LEFTSHIFT, "Left Shift"),
+    (KeyCode::KEY_RIGHTSHIFT, "Right Shift"),
+    (KeyCode::KEY_LEFTMETA, "Left Super"),
+    (KeyCode::KEY_RIGHTMETA, "Right Super"),
+];
+
+/// Display name of a key: `KEY_F13` is "F13", `KEY_PROG1` "Prog1",
+/// `KEY_SCROLLLOCK` "ScrollLock". Codes evdev has no name for show as numbers.
+pub fn key_name(key: KeyCode) -> String {
+    if let Some((_, name)) = KEY_NAMES.iter().find(|(k, _)| *k == key) {
+        return name.to_string();
+    }
+    let debug = format!("{key:?}");
+    let Some(raw) = debug
+        .strip_prefix("KEY_")
+        .or_else(|| debug.strip_prefix("BTN_"))
+    else {
+        return format!("Key {}", key.code());
+    };
+    if let Some(number) = raw.strip_prefix('F')
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+    {
+        return raw.to_string();
+    }
+    let mut chars = raw.chars();
+    match chars.next() {
+        Some(first) => first.to_string() + &chars.as_str().to_lowercase().replace('_', " "),
+        None => debug,
+    }
+}
+
+/// Keys used while typing: the main block (letters, digits, punctuation,
+/// Enter, Space, modifiers), the keypad, arrows and the navigation block.
+pub fn is_typing_key(key: KeyCode) -> bool {
+    let code = key.code();
+    (KeyCode::KEY_1.code()..=KeyCode::KEY_CAPSLOCK.code()).contains(&code)
+        || (KeyCode::KEY_KP7.code()..=KeyCode::KEY_KPDOT.code()).contains(&code)
+        || matches!(
+            key,
+            KeyCode::KEY_KPENTER
+                | KeyCode::KEY_KPSLASH
+                | KeyCode::KEY_RIGHTCTRL
+                | KeyCode::KEY_RIGHTALT
+                | KeyCode::KEY_LEFTMETA
+                | KeyCode::KEY_RIGHTMETA
+                | KeyCode::KEY_HOME
+                | KeyCode::KEY_END
+                | KeyCode::KEY_UP
+                | KeyCode::KEY_DOWN
+                | KeyCode::KEY_LEFT
+                | KeyCode::KEY_RIGHT
+                | KeyCode::KEY_PAGEUP
+                | KeyCode::KEY_PAGEDOWN
+                | KeyCode::KEY_INSERT
+                | KeyCode::KEY_DELETE
+        )
+}
+
+/// Check a key chosen as the hotkey: the cancel key is refused, a typing
+/// key is allowed with a warning (returned).
+pub fn check_hotkey(key: KeyCode) -> Result<Option<String>> {
+    if key == CANCEL_KEY {
+        bail!("{} is the cancel key, pick another one", key_name(key));
+    }
+    Ok(is_typing_key(key).then(|| {
+        format!(
+            "{} is used for typing: every press in another window will toggle listening too",
+            key_name(key)
+        )
+    }))
+}
+
+/// Parse `--hotkey`: an evdev key name, with or without its `KEY_` prefix
+/// ("F9", "key_scrolllock"), or a key code ("67").
+pub fn parse_hotkey(s: &str) -> Result<KeyCode> {
+    let key = match s.parse::<u16>() {
+        Ok(code) => KeyCode::new(code),
+        Err(_) => {
+            let name = s.trim().to_uppercase();
+            let name = if name.starts_with("KEY_") || name.starts_with("BTN_") {
+                name
+            } else {
+                format!("KEY_{name}")
+            };
+            name.parse()
+                .map_err(|_| anyhow::anyhow!("unknown key \"{s}\""))?
+        }
+    };
+    if let Some(warning) = check_hotkey(key)? {
+        warn!("{warning}");
+    }
+    Ok(key)
+}
+
+/// Reports the keys pressed on any keyboard, for the hotkey setup screen.
+/// The reader threads stop at their next event once it is dropped.
+pub struct KeyCapture {
+    rx: Receiver<KeyCode>,
+    stop: Arc<AtomicBool>,
+}
+
+impl KeyCapture {
+    /// Open every keyboard; `None` when none can be read.
+    pub fn start() -> Option<Self> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut opened = 0;
+        for (path, name) in find_keyboards() {
+            let mut device = match Device::open(&path) {
+                Ok(d) => d,
+                Err(e) => {
+                    debug!("Cannot open {} ({name}): {e}", path.display());
+                    continue;
+                }
+            };
+            let tx = tx.clone();
+            let stop = stop.clone();
+            let spawned = std::thread::Builder::new()
+                .name("key-capture".to_string())
+                .spawn(move || {
+                    while let Ok(events) = device.fetch_events() {
+                        if stop.load(Ordering::SeqCst) {
+                            return;
+                        }
+                        for event in events {
+                            if event.event_type() == EventType::KEY
+                                && event.value() == 1
+                                && tx.send(KeyCode::new(event.code())).is_err()
+                            {
+                                return;
+                            }
+                        }
+                    }
+                });
+            if spawned.is_ok() {
+                opened += 1;
+            }
+        }
+        (opened > 0).then_some(Self { rx, stop })
+    }
+
+    /// The next key pressed, if any.
+    pub fn try_next(&self) -> Option<KeyCode> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for KeyCapture {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
 /// List all keyboard-like evdev devices (filtering out non-keyboards).
-fn find_keyboards() -> Vec<(std::path::PathBuf, String)> {
+fn find_keyboards() -> Vec<(PathBuf, String)> {
     evdev::enumerate()
         .filter(|(_, dev)| {
             if !dev.supported_events().contains(EventType::KEY) {
@@ -33,58 +283,534 @@ fn find_keyboards() -> Vec<(std::path::PathBuf, String)> {
         .collect()
 }
 
+/// Default window for a double-tap on the hotkey (`--double-tap-ms`).
+pub const DEFAULT_DOUBLE_TAP_MS: u64 = 400;
+
+/// Double-tapping the hotkey cancels the current exchange (Toggle mode).
+pub struct DoubleTap {
+    /// Longest time between the two presses.
+    pub window: Duration,
+    /// Set on a double-tap, for the main loop to act on.
+    pub cancel_pressed: Arc<AtomicBool>,
+}
+
+/// A hotkey press, once it is known whether a second one followed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tap {
+    Single,
+    Double,
+}
+
+/// Tells single presses from double-taps by their timestamps.
+///
+/// A press is held back for the window: a second press within it makes a
+/// double-tap and both are swallowed, so listening never flickers on and off
+/// (which in Manual mode would send a tiny segment).
+#[derive(Debug)]
+pub struct TapClassifier {
+    window: Duration,
+    /// The press being held back.
+    pending: Option<Instant>,
+}
+
+impl TapClassifier {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: None,
+        }
+    }
+
+    /// A press at `at`. Returns the gesture it completes: a double-tap, or
+    /// the single press held back before it when its window had passed.
+    pub fn press(&mut self, at: Instant) -> Option<Tap> {
+        match self.pending.replace(at) {
+            Some(first) if at.saturating_duration_since(first) <= self.window => {
+                self.pending = None;
+                Some(Tap::Double)
+            }
+            Some(_) => Some(Tap::Single),
+            None => None,
+        }
+    }
+
+    /// When the held-back press becomes a single press, if there is one.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.map(|first| first + self.window)
+    }
+
+    /// The held-back press, as a single press once its window passed at `now`.
+    pub fn expire(&mut self, now: Instant) -> Option<Tap> {
+        let deadline = self.deadline()?;
+        if now < deadline {
+            return None;
+        }
+        self.pending = None;
+        Some(Tap::Single)
+    }
+}
+
+/// Classify the presses of every keyboard and apply them: a single press
+/// toggles listening, a double-tap sets `cancel_pressed`.
+fn run_taps(
+    presses: Receiver<Instant>,
+    double_tap: DoubleTap,
+    is_listening: Arc<AtomicBool>,
+    suspended: Arc<AtomicBool>,
+) {
+    let mut classifier = TapClassifier::new(double_tap.window);
+    loop {
+        let tap = match classifier.deadline() {
+            Some(deadline) => match presses.recv_deadline(deadline) {
+                Ok(at) => classifier.press(at),
+                Err(RecvTimeoutError::Timeout) => classifier.expire(Instant::now()),
+                Err(RecvTimeoutError::Disconnected) => return,
+            },
+            None => match presses.recv() {
+                Ok(at) => classifier.press(at),
+                Err(_) => return,
+            },
+        };
+        let suspended = suspended.load(Ordering::SeqCst);
+        match tap {
+            Some(Tap::Single) => {
+                if let Some(listening) = next_listening(
+                    HotkeyMode::Toggle,
+                    KeyTransition::Press,
+                    is_listening.load(Ordering::SeqCst),
+                    suspended,
+                ) {
+                    is_listening.store(listening, Ordering::SeqCst);
+                }
+            }
+            Some(Tap::Double) if !suspended => {
+                debug!("Hotkey double-tap: cancel");
+                double_tap.cancel_pressed.store(true, Ordering::SeqCst);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// How often `/dev/input` is checked for keyboards plugged in or removed.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A new device node may not be readable yet (udev sets its permissions
+/// just after creating it): opening it is retried this many times.
+const OPEN_ATTEMPTS: u32 = 5;
+const OPEN_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// What every listener thread shares.
+#[derive(Clone)]
+struct Listener {
+    key: KeyCode,
+    mode: HotkeyMode,
+    is_listening: Arc<AtomicBool>,
+    suspended: Arc<AtomicBool>,
+    /// Devices with a running listener thread.
+    live: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Presses go to the double-tap classifier instead, when it runs.
+    taps: Option<Sender<Instant>>,
+}
+
+/// Keyboards to start and stop listening to.
+#[derive(Debug, Default, PartialEq)]
+struct DeviceChanges {
+    added: Vec<PathBuf>,
+    removed: Vec<PathBuf>,
+}
+
+/// Compare the devices with a listener to the keyboards present now.
+fn device_changes(live: &HashSet<PathBuf>, present: &[PathBuf]) -> DeviceChanges {
+    let mut added: Vec<PathBuf> = present
+        .iter()
+        .filter(|path| !live.contains(*path))
+        .cloned()
+        .collect();
+    let mut removed: Vec<PathBuf> = live
+        .iter()
+        .filter(|path| !present.contains(path))
+        .cloned()
+        .collect();
+    added.sort();
+    added.dedup();
+    removed.sort();
+    DeviceChanges { added, removed }
+}
+
+/// The event device nodes, to tell cheaply whether a rescan is needed.
+fn event_nodes() -> BTreeSet<PathBuf> {
+    std::fs::read_dir("/dev/input")
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with("event"))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Listen for the hotkey on ALL detected keyboards simultaneously.
-/// Spawns one thread per keyboard device. Any of them pressing the key triggers PTT.
-pub fn listen_all_keyboards(key: KeyCode, is_listening: Arc<AtomicBool>) -> Result<()> {
-    let keyboards = find_keyboards();
+/// Spawns one thread per keyboard device. Any of them pressing the key triggers PTT,
+/// as a toggle or while held depending on `mode`.
+/// Presses are ignored while `suspended` is set (e.g. while the text prompt is open).
+///
+/// Keyboards plugged in later get a listener too: `/dev/input` is rescanned
+/// every `RESCAN_INTERVAL`. The thread of a removed keyboard ends quietly.
+///
+/// With `double_tap` (Toggle mode only), two presses within its window cancel
+/// instead of toggling twice; single presses then take effect once the window
+/// has passed.
+pub fn listen_all_keyboards(
+    key: KeyCode,
+    mode: HotkeyMode,
+    is_listening: Arc<AtomicBool>,
+    suspended: Arc<AtomicBool>,
+    double_tap: Option<DoubleTap>,
+) -> Result<()> {
+    let taps = match double_tap {
+        Some(double_tap) if mode == HotkeyMode::Toggle => {
+            let (tx, rx) = crossbeam_channel::unbounded();
+            let is_listening = is_listening.clone();
+            let suspended = suspended.clone();
+            std::thread::Builder::new()
+                .name("hotkey-taps".to_string())
+                .spawn(move || run_taps(rx, double_tap, is_listening, suspended))?;
+            Some(tx)
+        }
+        _ => None,
+    };
+    let listener = Listener {
+        key,
+        mode,
+        is_listening,
+        suspended,
+        live: Arc::new(Mutex::new(HashSet::new())),
+        taps,
+    };
 
+    // The first scan happens now, so a missing keyboard is reported at startup
+    let keyboards = find_keyboards();
     if keyboards.is_empty() {
         warn!("No keyboard devices found for hotkey. Is the user in the 'input' group?");
-        return Ok(());
     }
+    let mut nodes = event_nodes();
+    rescan(&listener, keyboards);
+
+    std::thread::Builder::new()
+        .name("hotkey-scan".to_string())
+        .spawn(move || {
+            loop {
+                std::thread::sleep(RESCAN_INTERVAL);
+                let current = event_nodes();
+                if current != nodes {
+                    nodes = current;
+                    rescan(&listener, find_keyboards());
+                }
+            }
+        })?;
+
+    Ok(())
+}
 
+/// Start a listener on each keyboard that has none.
+fn rescan(listener: &Listener, keyboards: Vec<(PathBuf, String)>) {
+    let present: Vec<PathBuf> = keyboards.iter().map(|(path, _)| path.clone()).collect();
+    let changes = match listener.live.lock() {
+        Ok(live) => device_changes(&live, &present),
+        Err(_) => return,
+    };
+    for path in &changes.removed {
+        debug!("Keyboard removed: {}", path.display());
+    }
     for (path, name) in keyboards {
-        let is_listening = is_listening.clone();
-        let path_display = path.display().to_string();
-
-        std::thread::Builder::new()
-            .name(format!(
-                "hotkey-{}",
-                path.file_name().unwrap_or_default().to_string_lossy()
-            ))
-            .spawn(move || {
-                let mut device = match Device::open(&path) {
-                    Ok(d) => d,
-                    Err(e) => {
-                        warn!("Cannot open {path_display} ({name}): {e}");
-                        return;
+        if !changes.added.contains(&path) {
+            continue;
+        }
+        if let Ok(mut live) = listener.live.lock() {
+            live.insert(path.clone());
+        }
+        let thread_name = format!(
+            "hotkey-{}",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        let thread_listener = listener.clone();
+        let thread_path = path.clone();
+        let spawned = std::thread::Builder::new()
+            .name(thread_name)
+            .spawn(move || listen_on(&thread_listener, &thread_path, &name));
+        if let Err(e) = spawned {
+            warn!(
+                "Cannot start the hotkey listener on {}: {e}",
+                path.display()
+            );
+            if let Ok(mut live) = listener.live.lock() {
+                live.remove(&path);
+            }
+        }
+    }
+}
+
+/// Open a device, retrying briefly while a fresh node is not readable yet.
+fn open_device(path: &Path) -> std::io::Result<Device> {
+    let mut attempt = 1;
+    loop {
+        match Device::open(path) {
+            Ok(device) => return Ok(device),
+            Err(e) if attempt >= OPEN_ATTEMPTS => return Err(e),
+            Err(_) => {
+                attempt += 1;
+                std::thread::sleep(OPEN_RETRY_DELAY);
+            }
+        }
+    }
+}
+
+/// One keyboard's listener thread, until the device goes away.
+fn listen_on(listener: &Listener, path: &Path, name: &str) {
+    let path_display = path.display();
+    match open_device(path) {
+        Ok(mut device) => {
+            debug!("Hotkey listener on: {name} ({path_display})");
+            let mut held = false;
+            // Ends when the keyboard is unplugged
+            while let Ok(events) = device.fetch_events() {
+                for event in events {
+                    if event.event_type() != EventType::KEY || event.code() != listener.key.code() {
+                        continue;
                     }
-                };
-
-                debug!("Hotkey listener on: {name} ({path_display})");
-
-                loop {
-                    match device.fetch_events() {
-                        Ok(events) => {
-                            for event in events {
-                                if event.event_type() == EventType::KEY
-                                    && event.code() == key.code()
-                                    && event.value() == 1
-                                {
-                                    // Toggle on key press (not release, not repeat)
-                                    let prev = is_listening.load(Ordering::SeqCst);
-                                    is_listening.store(!prev, Ordering::SeqCst);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            warn!("Hotkey device lost ({name}): {e}");
-                            return;
+                    let Some(transition) = key_transition(event.value(), &mut held) else {
+                        continue;
+                    };
+                    if let Some(taps) = &listener.taps {
+                        if transition == KeyTransition::Press {
+                            let _ = taps.send(Instant::now());
                         }
+                        continue;
+                    }
+                    if let Some(listening) = next_listening(
+                        listener.mode,
+                        transition,
+                        listener.is_listening.load(Ordering::SeqCst),
+                        listener.suspended.load(Ordering::SeqCst),
+                    ) {
+                        listener.is_listening.store(listening, Ordering::SeqCst);
                     }
                 }
-            })?;
+            }
+            debug!("Hotkey listener stopped: {name} ({path_display})");
+        }
+        Err(e) => debug!("Cannot open {path_display} ({name}): {e}"),
     }
+    if let Ok(mut live) = listener.live.lock() {
+        live.remove(path);
+    }
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed raw key values through the debouncer and the mode, like the
+    /// listener thread does.
+    fn run(mode: HotkeyMode, values: &[i32], suspended: bool) -> Vec<bool> {
+        let mut held = false;
+        let mut listening = false;
+        let mut states = Vec::new();
+        for &value in values {
+            if let Some(transition) = key_transition(value, &mut held)
+                && let Some(next) = next_listening(mode, transition, listening, suspended)
+            {
+                listening = next;
+                states.push(listening);
+            }
+        }
+        states
+    }
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names
+            .iter()
+            .map(|name| PathBuf::from(format!("/dev/input/{name}")))
+            .collect()
+    }
+
+    #[test]
+    fn device_changes_find_plugged_and_removed_keyboards() {
+        let live: HashSet<PathBuf> = paths(&["event3", "event5"]).into_iter().collect();
+        assert_eq!(
+            device_changes(&live, &paths(&["event5", "event9", "event3"])),
+            DeviceChanges {
+                added: paths(&["event9"]),
+                removed: vec![],
+            }
+        );
+        assert_eq!(
+            device_changes(&live, &paths(&["event3"])),
+            DeviceChanges {
+                added: vec![],
+                removed: paths(&["event5"]),
+            }
+        );
+        // Docked: everything is new
+        assert_eq!(
+            device_changes(&HashSet::new(), &paths(&["event7", "event4"])),
+            DeviceChanges {
+                added: paths(&["event4", "event7"]),
+                removed: vec![],
+            }
+        );
+        assert_eq!(
+            device_changes(&live, &paths(&["event3", "event5"])),
+            DeviceChanges::default()
+        );
+    }
+
+    #[test]
+    fn key_names_drop_the_evdev_prefix() {
+        assert_eq!(key_name(KeyCode::KEY_F2), "F2");
+        assert_eq!(key_name(KeyCode::KEY_F13), "F13");
+        assert_eq!(key_name(KeyCode::KEY_MACRO), "Macro");
+        assert_eq!(key_name(KeyCode::KEY_PROG1), "Prog1");
+        assert_eq!(key_name(KeyCode::KEY_PAUSE), "Pause");
+        assert_eq!(key_name(KeyCode::KEY_FN), "Fn");
+        assert_eq!(key_name(KeyCode::KEY_SCROLLLOCK), "ScrollLock");
+        assert_eq!(key_name(KeyCode::KEY_RIGHTCTRL), "Right Ctrl");
+        assert_eq!(key_name(KeyCode::BTN_TRIGGER_HAPPY1), "Trigger happy1");
+        assert_eq!(key_name(KeyCode::new(0x2ff)), "Key 767");
+    }
+
+    #[test]
+    fn hotkey_flag_takes_names_and_codes() {
+        assert_eq!(parse_hotkey("F9").unwrap(), KeyCode::KEY_F9);
+        assert_eq!(
+            parse_hotkey("key_scrolllock").unwrap(),
+            KeyCode::KEY_SCROLLLOCK
+        );
+        assert_eq!(parse_hotkey("67").unwrap(), KeyCode::KEY_F9);
+        assert!(parse_hotkey("F99").is_err());
+        assert!(parse_hotkey("esc").is_err());
+    }
+
+    #[test]
+    fn cancel_key_is_refused_and_typing_keys_warned() {
+        assert!(check_hotkey(CANCEL_KEY).is_err());
+        assert_eq!(check_hotkey(KeyCode::KEY_F9).unwrap(), None);
+        assert_eq!(check_hotkey(KeyCode::KEY_MACRO).unwrap(), None);
+        for key in [
+            KeyCode::KEY_A,
+            KeyCode::KEY_SPACE,
+            KeyCode::KEY_ENTER,
+            KeyCode::KEY_KP5,
+            KeyCode::KEY_LEFT,
+            KeyCode::KEY_LEFTSHIFT,
+        ] {
+            let warning = check_hotkey(key).unwrap();
+            assert!(warning.unwrap().contains("used for typing"), "{key:?}");
+        }
+    }
+
+    #[test]
+    fn backend_parses_its_names() {
+        assert_eq!(HotkeyBackend::parse("evdev").unwrap(), HotkeyBackend::Evdev);
+        assert_eq!(
+            HotkeyBackend::parse("terminal").unwrap(),
+            HotkeyBackend::Terminal
+        );
+        assert!(HotkeyBackend::parse("x11").is_err());
+    }
+
+    /// Classify presses at `offsets_ms`, checking expiry `now_ms` after the
+    /// last one.
+    fn taps(offsets_ms: &[u64], now_ms: u64) -> Vec<Tap> {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut classifier = TapClassifier::new(Duration::from_millis(DEFAULT_DOUBLE_TAP_MS));
+        let mut out = Vec::new();
+        for &offset in offsets_ms {
+            out.extend(classifier.expire(at(offset)));
+            out.extend(classifier.press(at(offset)));
+        }
+        let last = offsets_ms.last().copied().unwrap_or(0);
+        out.extend(classifier.expire(at(last + now_ms)));
+        out
+    }
+
+    #[test]
+    fn presses_within_the_window_are_a_double_tap() {
+        assert_eq!(taps(&[0, 250], 1000), [Tap::Double]);
+        assert_eq!(taps(&[0, 399], 1000), [Tap::Double]);
+        // Nothing toggles while the window is open
+        assert_eq!(taps(&[0], 399), []);
+        assert_eq!(taps(&[0], 400), [Tap::Single]);
+    }
+
+    #[test]
+    fn slow_presses_are_single_taps() {
+        assert_eq!(taps(&[0, 401], 1000), [Tap::Single, Tap::Single]);
+        assert_eq!(taps(&[0, 2000, 5000], 1000), [Tap::Single; 3]);
+        // A third quick press starts over after a double-tap
+        assert_eq!(taps(&[0, 100, 200], 1000), [Tap::Double, Tap::Single]);
+        assert_eq!(taps(&[0, 100, 900, 1000], 1000), [Tap::Double, Tap::Double]);
+    }
+
+    #[test]
+    fn late_press_completes_the_held_back_single() {
+        // No expiry check between the presses: the first one is released by the second
+        let start = Instant::now();
+        let mut classifier = TapClassifier::new(Duration::from_millis(400));
+        assert_eq!(classifier.press(start), None);
+        assert_eq!(
+            classifier.press(start + Duration::from_millis(600)),
+            Some(Tap::Single)
+        );
+        assert_eq!(
+            classifier.deadline(),
+            Some(start + Duration::from_millis(1000))
+        );
+    }
+
+    #[test]
+    fn repeats_and_duplicate_key_downs_are_debounced() {
+        let mut held = false;
+        assert_eq!(key_transition(1, &mut held), Some(KeyTransition::Press));
+        assert_eq!(key_transition(2, &mut held), None);
+        assert_eq!(key_transition(1, &mut held), None);
+        assert_eq!(key_transition(0, &mut held), Some(KeyTransition::Release));
+        assert_eq!(key_transition(0, &mut held), None);
+    }
+
+    #[test]
+    fn toggle_flips_on_each_press() {
+        assert_eq!(
+            run(HotkeyMode::Toggle, &[1, 2, 2, 0, 1, 0], false),
+            [true, false]
+        );
+    }
+
+    #[test]
+    fn hold_listens_only_while_the_key_is_down() {
+        assert_eq!(
+            run(HotkeyMode::Hold, &[1, 2, 2, 1, 0, 1, 0], false),
+            [true, false, true, false]
+        );
+    }
+
+    #[test]
+    fn suspended_ignores_presses_but_not_releases() {
+        assert_eq!(run(HotkeyMode::Hold, &[1, 0], true), Vec::<bool>::new());
+        // Held when the prompt opened: the release still ends the turn
+        assert_eq!(
+            next_listening(HotkeyMode::Hold, KeyTransition::Release, true, true),
+            Some(false)
+        );
+        assert_eq!(
+            next_listening(HotkeyMode::Toggle, KeyTransition::Release, true, true),
+            None
+        );
+    }
 }
@@ -51,6 +51,13 @@ impl Injector {
         })
     }
 
+    /// Close dotool's input and wait until it has typed everything.
+    pub fn finish(mut self) -> Result<()> {
+        drop(self.child.stdin.take());
+        self.child.wait().context("waiting for dotool")?;
+        Ok(())
+    }
+
     fn respawn(&mut self) -> Result<()> {
         let _ = self.child.kill();
         let _ = self.child.wait();
@@ -0,0 +1,335 @@
+use anyhow::Result;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TrySendError};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use space_lt_common::{debug, warn};
+
+use crate::hotkey::{self, HotkeyMode, KeyTransition};
+use crate::suspend::TerminalMode;
+
+/// How often the reader thread re-checks the shutdown flag.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Key presses buffered while nobody reads them (e.g. while listening).
+const KEY_QUEUE: usize = 64;
+
+/// Terminal settings from before raw input was enabled.
+static ORIGINAL: Mutex<Option<libc::termios>> = Mutex::new(None);
+/// Set while raw input is applied.
+static RAW: AtomicBool = AtomicBool::new(false);
+/// Set by the final restore; raw input is never re-enabled after it.
+static RESTORED: AtomicBool = AtomicBool::new(false);
+
+fn original() -> std::sync::MutexGuard<'static, Option<libc::termios>> {
+    ORIGINAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_attrs(attrs: &libc::termios) -> Result<()> {
+    // SAFETY: `attrs` is a valid termios; stdin is only read, never closed.
+    if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, attrs) } != 0 {
+        anyhow::bail!("tcsetattr: {}", std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Switch stdin to unbuffered, unechoed input.
+///
+/// Unlike crossterm's raw mode, output processing and the signal keys are
+/// kept: log lines still start at column 0, and Ctrl+C / Ctrl+Z still reach
+/// their handlers.
+pub fn enable_raw_input() -> Result<()> {
+    if RESTORED.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    let mut original = original();
+    // SAFETY: termios is plain data, fully written by tcgetattr on success.
+    let mut attrs: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut attrs) } != 0 {
+        anyhow::bail!("tcgetattr: {}", std::io::Error::last_os_error());
+    }
+    if original.is_none() {
+        *original = Some(attrs);
+    }
+    attrs.c_lflag &= !(libc::ICANON | libc::ECHO);
+    attrs.c_cc[libc::VMIN] = 1;
+    attrs.c_cc[libc::VTIME] = 0;
+    set_attrs(&attrs)?;
+    RAW.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Go back to the original terminal settings (raw input can be re-enabled).
+pub fn disable_raw_input() -> Result<()> {
+    if let Some(attrs) = original().as_ref() {
+        set_attrs(attrs)?;
+    }
+    RAW.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+pub fn is_raw_input() -> bool {
+    RAW.load(Ordering::SeqCst)
+}
+
+/// Put the terminal back as it was, once: later calls (a normal shutdown after
+/// the panic hook already ran, for instance) do nothing.
+pub fn restore_terminal() {
+    if RESTORED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Some(attrs) = original().take()
+        && let Err(e) = set_attrs(&attrs)
+    {
+        warn!("[client] Could not restore the terminal: {e}");
+    }
+    RAW.store(false, Ordering::SeqCst);
+}
+
+/// Raw input as seen by `suspend`, which releases it while stopped.
+pub struct RawInputTerminal;
+
+impl TerminalMode for RawInputTerminal {
+    fn is_raw(&self) -> bool {
+        is_raw_input()
+    }
+
+    fn set_raw(&mut self, raw: bool) -> Result<()> {
+        if raw {
+            enable_raw_input()
+        } else {
+            disable_raw_input()
+        }
+    }
+}
+
+/// Restores the terminal when dropped, so early returns from `run_client` do too.
+pub struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// The terminal hotkey backend: [Space] toggles listening and [c] cancels
+/// (like Esc), handled by the keyboard thread so the main loop sees the same
+/// `is_listening` flag as with the evdev listener.
+#[derive(Clone)]
+pub struct TerminalHotkey {
+    pub is_listening: Arc<AtomicBool>,
+    pub suspended: Arc<AtomicBool>,
+}
+
+/// What the keyboard thread does with a key press.
+#[derive(Debug, PartialEq)]
+enum Route {
+    Queue(KeyEvent),
+    ToggleListening,
+}
+
+/// Route a key press. The terminal hotkey keys are only taken while no
+/// prompt is open, so they can still be typed into one.
+fn route(key: KeyEvent, terminal_hotkey: bool, prompt_open: bool) -> Route {
+    if !terminal_hotkey || prompt_open || key.modifiers != KeyModifiers::NONE {
+        return Route::Queue(key);
+    }
+    match key.code {
+        KeyCode::Char(' ') => Route::ToggleListening,
+        KeyCode::Char('c') => Route::Queue(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
+        _ => Route::Queue(key),
+    }
+}
+
+/// Key presses from the session's keyboard thread.
+///
+/// The thread owns raw input for the session; the idle-key poll and the
+/// prompts (feedback, typed text, summary, word number) consume from the
+/// channel instead of toggling the terminal themselves. While a prompt is
+/// open, [`Keys::poll_idle`] yields nothing so the main loop cannot steal its
+/// keys.
+#[derive(Clone)]
+pub struct Keys {
+    rx: Receiver<KeyEvent>,
+    prompt_open: Arc<AtomicBool>,
+    /// Set when no keyboard is read at all (headless): feedback continues on
+    /// its own after this delay.
+    auto_continue: Option<Duration>,
+}
+
+impl Keys {
+    /// Enable raw input, install the panic hook that restores the terminal, and
+    /// start the keyboard thread (it exits on `shutdown`). With
+    /// `terminal_hotkey`, the thread also drives listening.
+    pub fn spawn(
+        shutdown: Arc<AtomicBool>,
+        terminal_hotkey: Option<TerminalHotkey>,
+    ) -> Result<(Self, TerminalGuard)> {
+        if let Err(e) = enable_raw_input() {
+            warn!("[client] Raw keyboard input unavailable: {e}");
+        }
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            previous(info);
+        }));
+
+        let (tx, rx) = crossbeam_channel::bounded(KEY_QUEUE);
+        let keys = Self::from_channel(rx);
+        let prompt_open = keys.prompt_open.clone();
+        std::thread::Builder::new()
+            .name("keyboard".into())
+            .spawn(move || read_loop(tx, shutdown, prompt_open, terminal_hotkey))?;
+        Ok((keys, TerminalGuard))
+    }
+
+    /// Keys fed from `rx` (the keyboard thread's channel, or injected events).
+    pub fn from_channel(rx: Receiver<KeyEvent>) -> Self {
+        Self {
+            rx,
+            prompt_open: Arc::default(),
+            auto_continue: None,
+        }
+    }
+
+    /// No keyboard and no raw input, for a client run without a terminal:
+    /// nothing is ever pressed, and feedback continues after `auto_continue`.
+    pub fn headless(auto_continue: Duration) -> Self {
+        let (_, rx) = crossbeam_channel::bounded(0);
+        Self {
+            auto_continue: Some(auto_continue),
+            ..Self::from_channel(rx)
+        }
+    }
+
+    /// How long feedback stays up before continuing on its own; `None` while
+    /// keys are read.
+    pub fn auto_continue(&self) -> Option<Duration> {
+        self.auto_continue
+    }
+
+    /// Next pending key for the idle poll, unless a prompt is reading keys.
+    pub fn poll_idle(&self) -> Option<KeyEvent> {
+        if self.prompt_open() {
+            return None;
+        }
+        self.rx.try_recv().ok()
+    }
+
+    /// Next key within `timeout`. `Disconnected` means no keyboard is available.
+    pub fn next_timeout(&self, timeout: Duration) -> Result<KeyEvent, RecvTimeoutError> {
+        self.rx.recv_timeout(timeout)
+    }
+
+    /// Route keys to a prompt until the returned guard is dropped.
+    pub fn prompt(&self) -> Prompt<'_> {
+        self.prompt_open.store(true, Ordering::SeqCst);
+        Prompt { keys: self }
+    }
+
+    pub fn prompt_open(&self) -> bool {
+        self.prompt_open.load(Ordering::SeqCst)
+    }
+}
+
+/// An open prompt; see [`Keys::prompt`].
+pub struct Prompt<'a> {
+    keys: &'a Keys,
+}
+
+impl Drop for Prompt<'_> {
+    fn drop(&mut self) {
+        self.keys.prompt_open.store(false, Ordering::SeqCst);
+    }
+}
+
+fn read_loop(
+    tx: Sender<KeyEvent>,
+    shutdown: Arc<AtomicBool>,
+    prompt_open: Arc<AtomicBool>,
+    terminal_hotkey: Option<TerminalHotkey>,
+) {
+    while !shutdown.load(Ordering::SeqCst) {
+        match event::poll(POLL_INTERVAL) {
+            Ok(false) => {}
+            Ok(true) => match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    let routed = route(
+                        key,
+                        terminal_hotkey.is_some(),
+                        prompt_open.load(Ordering::SeqCst),
+                    );
+                    let key = match (routed, &terminal_hotkey) {
+                        (Route::Queue(key), _) => key,
+                        (Route::ToggleListening, Some(hotkey)) => {
+                            // Terminals report no releases: always a toggle
+                            if let Some(listening) = hotkey::next_listening(
+                                HotkeyMode::Toggle,
+                                KeyTransition::Press,
+                                hotkey.is_listening.load(Ordering::SeqCst),
+                                hotkey.suspended.load(Ordering::SeqCst),
+                            ) {
+                                hotkey.is_listening.store(listening, Ordering::SeqCst);
+                            }
+                            continue;
+                        }
+                        (Route::ToggleListening, None) => continue,
+                    };
+                    match tx.try_send(key) {
+                        Ok(()) => {}
+                        Err(TrySendError::Full(_)) => debug!("[client] Key queue full, dropping"),
+                        Err(TrySendError::Disconnected(_)) => break,
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("[client] Keyboard read failed: {e}");
+                    break;
+                }
+            },
+            Err(e) => {
+                warn!("[client] Keyboard input unavailable: {e}");
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_poll_yields_nothing_while_a_prompt_is_open() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let keys = Keys::from_channel(rx);
+        tx.send(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE))
+            .unwrap();
+        {
+            let _prompt = keys.prompt();
+            assert!(keys.prompt_open());
+            assert!(keys.poll_idle().is_none());
+        }
+        assert_eq!(keys.poll_idle().unwrap().code, KeyCode::Char('q'));
+        assert!(keys.poll_idle().is_none());
+    }
+
+    #[test]
+    fn terminal_hotkey_takes_space_and_c_outside_prompts() {
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(route(key(' '), true, false), Route::ToggleListening);
+        assert_eq!(route(key('c'), true, false), Route::Queue(esc));
+        assert_eq!(route(key('q'), true, false), Route::Queue(key('q')));
+        // Typed into a prompt
+        assert_eq!(route(key(' '), true, true), Route::Queue(key(' ')));
+        assert_eq!(route(key('c'), true, true), Route::Queue(key('c')));
+        // Ctrl+C is not a cancel
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(route(ctrl_c, true, false), Route::Queue(ctrl_c));
+        // The evdev backend leaves every key alone
+        assert_eq!(route(key(' '), false, false), Route::Queue(key(' ')));
+    }
+}
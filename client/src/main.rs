@@ -1,22 +1,48 @@
 mod audio;
+mod away;
 mod connection;
+mod control;
+mod devices;
+mod feedback_history;
 mod hotkey;
-#[allow(dead_code)]
 mod inject;
+mod keyboard;
+mod outbox;
 mod playback;
+mod playback_queue;
+mod replay;
+mod session_view;
+mod settings;
+mod setup_state;
+mod status_line;
+mod suspend;
 mod tui;
+mod turn_log;
 mod vad;
+mod word_tokens;
 
 use anyhow::Result;
-use space_lt_common::protocol::{ClientMsg, ServerMsg, write_client_msg};
-use space_lt_common::{debug, info, warn};
-use std::io::{BufReader, BufWriter, Write};
-use std::net::{Shutdown, TcpStream};
+use crossbeam_channel::RecvTimeoutError;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use feedback_history::{FeedbackEntry, FeedbackHistory};
+use playback_queue::{Feeder, PlaybackQueue, Push};
+use space_lt_common::protocol::{
+    AudioInputInfo, ClientMsg, RETRYABLE_ERROR_PREFIX, ServerMsg, TurnStats, write_client_msg,
+};
+use space_lt_common::style::{self, palette};
+use space_lt_common::transport::{self, TlsClientConfig, Transport};
+use space_lt_common::{debug, info, profile, warn};
+use std::io::{BufReader, BufWriter, IsTerminal, Write};
+use std::net::Shutdown;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::{Duration, Instant};
 
 use connection::is_disconnect;
+use replay::ReplayBuffer;
+use session_view::{SessionEvent, SessionOutput};
+use turn_log::{RewindChoice, TurnLog};
+use word_tokens::WordNumbers;
 
 fn find_arg_value(args: &[String], flag: &str) -> Option<String> {
     args.iter()
@@ -46,86 +72,514 @@ fn check_input_group() {
 fn main() -> Result<()> {
     let args: Vec<String> = std::env::args().collect();
 
+    // --color: auto (a terminal, unless NO_COLOR is set), always or never
+    let color = find_arg_value(&args, "--color")
+        .map(|s| style::ColorChoice::parse(&s))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --color value: {e}"))?
+        .unwrap_or_default();
+    style::init(color);
+
     if args.iter().any(|a| a == "--debug") {
         space_lt_common::log::set_debug(true);
     }
 
+    // --trace-protocol: log every message sent or received
+    if args.iter().any(|a| a == "--trace-protocol") {
+        space_lt_common::trace::enable();
+    }
+
+    // --dump-protocol: print the wire format description (JSON) and exit
+    if args.iter().any(|a| a == "--dump-protocol") {
+        print!("{}", space_lt_common::protocol::describe_protocol_json());
+        return Ok(());
+    }
+
     let server_arg = find_arg_value(&args, "--server");
-    run_client(server_arg)
-}
 
-fn run_client(server_override: Option<String>) -> Result<()> {
-    info!("Space LT — Voice Conversation Client");
-    check_input_group();
+    // TLS: --tls uses the bundled root store, --tls-ca / --tls-insecure imply --tls
+    let tls_ca = find_arg_value(&args, "--tls-ca");
+    let tls_insecure = args.iter().any(|a| a == "--tls-insecure");
+    let tls = if args.iter().any(|a| a == "--tls") || tls_ca.is_some() || tls_insecure {
+        if tls_ca.is_some() && tls_insecure {
+            anyhow::bail!("--tls-ca and --tls-insecure are mutually exclusive");
+        }
+        if tls_insecure {
+            warn!("TLS certificate verification disabled (--tls-insecure)");
+        }
+        Some(transport::client_config(
+            tls_ca.as_deref().map(std::path::Path::new),
+            tls_insecure,
+        )?)
+    } else {
+        None
+    };
 
-    // 1. TUI setup
-    let config = tui::run_setup()?;
+    // --profile: collect per-stage timings and print them on exit
+    let profile_json = find_arg_value(&args, "--profile-json");
+    let profiling = args.iter().any(|a| a == "--profile") || profile_json.is_some();
+    profile::set_enabled(profiling);
+
+    let replay_buffer_secs: u32 = find_arg_value(&args, "--replay-buffer-secs")
+        .map(|s| s.parse())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --replay-buffer-secs value: {e}"))?
+        .unwrap_or(replay::DEFAULT_REPLAY_BUFFER_SECS);
+
+    let playback_buffer_ms: u32 = find_arg_value(&args, "--playback-buffer-ms")
+        .map(|s| s.parse())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --playback-buffer-ms value: {e}"))?
+        .unwrap_or(playback_queue::DEFAULT_HIGH_WATER_MS);
+
+    // --timings: print a latency breakdown after each exchange
+    let timings = args.iter().any(|a| a == "--timings");
+
+    // --input-device: pick the microphone by name instead of on the setup screen
+    let input_device = find_arg_value(&args, "--input-device");
+    // --output-device: play TTS on this device instead of the one picked at setup
+    let output_device = find_arg_value(&args, "--output-device");
+
+    // --resample-quality: capture resampler (Whisper input), "fast" by default
+    let resample_quality = find_arg_value(&args, "--resample-quality")
+        .map(|s| audio::ResampleQuality::parse(&s))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --resample-quality value: {e}"))?
+        .unwrap_or_default();
+
+    // --capture-channel: which input channel carries the mic, "mix" (average all) by default
+    let capture_channel = find_arg_value(&args, "--capture-channel")
+        .map(|s| audio::CaptureChannel::parse(&s))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --capture-channel value: {e}"))?
+        .unwrap_or_default();
+
+    // --agc: automatic gain control on the captured audio, capped at --agc-max-gain
+    let agc_max_gain: f32 = find_arg_value(&args, "--agc-max-gain")
+        .map(|s| s.parse())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --agc-max-gain value: {e}"))?
+        .unwrap_or(audio::DEFAULT_AGC_MAX_GAIN);
+    if agc_max_gain.is_nan() || agc_max_gain < 1.0 {
+        anyhow::bail!("Invalid --agc-max-gain value: must be at least 1.0");
+    }
+    let agc_max_gain = args.iter().any(|a| a == "--agc").then_some(agc_max_gain);
+
+    // --denoise: noise suppression on the captured audio, before resampling
+    let denoise = args.iter().any(|a| a == "--denoise");
+
+    // --vad-preset: auto mode pause tolerance (skips the setup screen); --vad picks
+    // the speech detector (webrtc or energy);
+    // --vad-silence-ms / --vad-threshold / --vad-min-speech-ms / --vad-pre-roll-ms /
+    // --vad-max-segment-ms override its fields
+    let vad_preset = find_arg_value(&args, "--vad-preset")
+        .map(|s| vad::VadPreset::parse(&s))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --vad-preset value: {e}"))?;
+    let vad_overrides = vad::VadOverrides {
+        engine: find_arg_value(&args, "--vad")
+            .map(|s| vad::VadEngine::parse(&s))
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --vad value: {e}"))?,
+        silence_duration_ms: find_arg_value(&args, "--vad-silence-ms")
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --vad-silence-ms value: {e}"))?,
+        speech_threshold: find_arg_value(&args, "--vad-threshold")
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --vad-threshold value: {e}"))?,
+        min_speech_ms: find_arg_value(&args, "--vad-min-speech-ms")
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --vad-min-speech-ms value: {e}"))?,
+        pre_roll_ms: find_arg_value(&args, "--vad-pre-roll-ms")
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --vad-pre-roll-ms value: {e}"))?,
+        max_segment_ms: find_arg_value(&args, "--vad-max-segment-ms")
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --vad-max-segment-ms value: {e}"))?,
+    };
 
-    let server_addr = server_override.unwrap_or(config.server_addr);
-    let server_addr = if server_addr.contains(':') {
-        server_addr
+    // --hotkey-backend: "terminal" takes [Space] in the client's terminal instead of
+    // reading the keyboards (used anyway when none can be read)
+    let hotkey_backend = find_arg_value(&args, "--hotkey-backend")
+        .map(|s| hotkey::HotkeyBackend::parse(&s))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --hotkey-backend value: {e}"))?
+        .unwrap_or_default();
+
+    // --double-tap-cancel: double-tapping the hotkey cancels the exchange, with presses
+    // --double-tap-ms apart at most
+    let double_tap_ms: u64 = find_arg_value(&args, "--double-tap-ms")
+        .map(|s| s.parse())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --double-tap-ms value: {e}"))?
+        .unwrap_or(hotkey::DEFAULT_DOUBLE_TAP_MS);
+    let double_tap = args
+        .iter()
+        .any(|a| a == "--double-tap-cancel")
+        .then(|| Duration::from_millis(double_tap_ms));
+
+    // --tui-session: run the session in a full-screen layout instead of plain output
+    let tui_session = args.iter().any(|a| a == "--tui-session");
+    // --control: the state for a tray indicator, and its commands back
+    let control_socket = find_arg_value(&args, "--control-socket")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            args.iter()
+                .any(|a| a == "--control")
+                .then(space_lt_common::control::default_socket_path)
+        });
+
+    // --no-tui (implied without a terminal): no setup screens and no key prompts,
+    // the setup comes from --server, --voice-mode and --hotkey
+    let voice_mode = find_arg_value(&args, "--voice-mode")
+        .map(|s| tui::parse_voice_mode(&s))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --voice-mode value: {e}"))?;
+    let hotkey = find_arg_value(&args, "--hotkey")
+        .map(|s| hotkey::parse_hotkey(&s))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --hotkey value: {e}"))?;
+    // --feedback-delay-secs: without key prompts, how long feedback stays up before
+    // the session continues
+    let feedback_delay_secs: u64 = find_arg_value(&args, "--feedback-delay-secs")
+        .map(|s| s.parse())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --feedback-delay-secs value: {e}"))?
+        .unwrap_or(DEFAULT_FEEDBACK_DELAY_SECS);
+    let has_terminal = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    let headless = if args.iter().any(|a| a == "--no-tui") || !has_terminal {
+        if tui_session {
+            anyhow::bail!("--tui-session needs a terminal");
+        }
+        if hotkey_backend == hotkey::HotkeyBackend::Terminal {
+            anyhow::bail!("--hotkey-backend terminal needs a terminal");
+        }
+        let setup = tui::HeadlessSetup::from_flags(server_arg.as_deref(), voice_mode, hotkey);
+        if !has_terminal {
+            info!("No terminal: running without the setup screens or key prompts");
+        }
+        Some(setup?)
     } else {
-        format!("{server_addr}:9500")
+        None
+    };
+
+    let result = run_client(
+        server_arg,
+        tls,
+        replay_buffer_secs,
+        playback_buffer_ms,
+        timings,
+        input_device,
+        output_device,
+        resample_quality,
+        capture_channel,
+        agc_max_gain,
+        denoise,
+        vad_preset,
+        vad_overrides,
+        hotkey_backend,
+        double_tap,
+        tui_session,
+        control_socket,
+        headless,
+        Duration::from_secs(feedback_delay_secs),
+    );
+    if profiling && let Err(e) = profile::dump(profile_json.as_deref().map(std::path::Path::new)) {
+        warn!("Could not write profile: {e:#}");
+    }
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_client(
+    server_override: Option<String>,
+    tls: Option<Arc<TlsClientConfig>>,
+    replay_buffer_secs: u32,
+    playback_buffer_ms: u32,
+    timings: bool,
+    input_device: Option<String>,
+    output_device: Option<String>,
+    resample_quality: audio::ResampleQuality,
+    capture_channel: audio::CaptureChannel,
+    agc_max_gain: Option<f32>,
+    denoise: bool,
+    vad_preset: Option<vad::VadPreset>,
+    vad_overrides: vad::VadOverrides,
+    hotkey_backend: hotkey::HotkeyBackend,
+    double_tap: Option<Duration>,
+    tui_session: bool,
+    control_socket: Option<std::path::PathBuf>,
+    headless: Option<tui::HeadlessSetup>,
+    feedback_delay: Duration,
+) -> Result<()> {
+    info!("Space LT — Voice Conversation Client");
+    let hotkey_backend = match hotkey_backend {
+        hotkey::HotkeyBackend::Evdev => {
+            check_input_group();
+            if hotkey::keyboards_available() {
+                hotkey::HotkeyBackend::Evdev
+            } else if headless.is_some() {
+                anyhow::bail!("No keyboard device can be read for the hotkey.");
+            } else {
+                warn!("No keyboard device can be read: falling back to the terminal hotkey.");
+                hotkey::HotkeyBackend::Terminal
+            }
+        }
+        backend => backend,
+    };
+
+    // 1. TUI setup, offering the last choices as defaults (headless: the flags)
+    let mut config = match headless.clone() {
+        Some(setup) => tui::headless_setup(
+            setup,
+            input_device.as_deref(),
+            output_device.as_deref(),
+            vad_preset,
+        )?,
+        None => {
+            let mut last_setup = setup_state::SetupState::load();
+            let config = tui::run_setup(
+                server_override.as_deref(),
+                tls.as_ref(),
+                input_device.as_deref(),
+                output_device.as_deref(),
+                vad_preset,
+                hotkey_backend,
+                &last_setup,
+            )?;
+            last_setup.remember(&config);
+            if let Err(e) = last_setup.save() {
+                warn!("[client] Could not save the setup choices: {e:#}");
+            }
+            config
+        }
     };
 
+    let server_addr = connection::with_default_port(&config.server_addr);
+
     debug!("  Server:  {server_addr}");
     debug!("  Device:  {}", config.device_name);
+    debug!(
+        "  Output:  {}",
+        config.output_device.as_deref().unwrap_or("default")
+    );
     debug!("  Hotkey:  {:?}", config.hotkey);
     debug!("  Mode:    {:?}", config.voice_mode);
+    debug!("  Trigger: {:?} ({hotkey_backend:?})", config.hotkey_mode);
+    debug!("  TLS:     {}", if tls.is_some() { "on" } else { "off" });
+
+    // 2. TCP connect + Ready handshake (with exponential backoff retry), unless
+    // the address check at setup already did it
+    let mut conn = match config.connection.take() {
+        Some(conn) => conn,
+        None => {
+            debug!("Connecting to server...");
+            connection::TcpConnection::connect_with_retry(&server_addr, tls.as_ref())?
+        }
+    };
 
-    // 2. TCP connect + Ready handshake (with exponential backoff retry)
-    debug!("Connecting to server...");
-    let conn = connection::TcpConnection::connect_with_retry(&server_addr)?;
+    // 2b. Session recovery: the server still has a session running (e.g. after a crash)
+    if let Some(since) = conn.active_session_since() {
+        let take_over = if headless.is_some() {
+            info!("[client] A session is still running on the server: taking it over");
+            true
+        } else {
+            read_takeover_choice(since)?
+        };
+        conn.answer_takeover(take_over)?;
+    }
     let feedback_stream = conn.try_clone_stream()?;
     let shutdown_stream = conn.try_clone_stream()?;
     let (reader, writer) = conn.into_split();
 
+    // 2c. Session view (--tui-session): from here on stderr is shown in it, until
+    // the end of run_client
+    let (output, _session_screen) = if tui_session {
+        let (output, screen) = session_view::start()?;
+        (output, Some(screen))
+    } else {
+        (SessionOutput::classic(), None)
+    };
+
     // 3. Start playback
-    let (playback_tx, playback_rx) = crossbeam_channel::bounded::<Vec<i16>>(32);
+    // Bounded by buffered duration: tcp_reader waits above playback_buffer_ms
+    let playback_queue = Arc::new(PlaybackQueue::new(playback_buffer_ms));
     let playback_clear = Arc::new(AtomicBool::new(false));
-    let (_playback_stream, output_rate) =
-        playback::start_playback(playback_rx, playback_clear.clone())?;
-
-    // 3b. Replay support: shared buffer for last TTS response + clone of playback_tx
-    let last_tts_audio: Arc<std::sync::Mutex<Vec<i16>>> =
-        Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut settings = settings::ClientSettings::load();
+    let playback_gain = Arc::new(AtomicU32::new(settings.volume));
+    // TTS playback state tracking (for hotkey interrupt); playback prebuffers
+    // a reply while it is still arriving
+    let is_playing = Arc::new(AtomicBool::new(false));
+    let is_playing_reader = is_playing.clone();
+    let mut playback_stream = playback::PlaybackStream::start(
+        playback_queue.clone(),
+        playback_clear.clone(),
+        playback_gain.clone(),
+        is_playing.clone(),
+        config.output_device.clone(),
+    )?;
+    // Follows the playback device: a rebuilt stream may run at another rate
+    let output_rate = playback_stream.shared_output_rate();
+
+    // 3b. Replay support: shared buffer for last TTS response + handle on the playback queue
+    // (sized by tcp_reader_loop from replay_buffer_secs and the playback rate)
+    let last_tts_audio = Arc::new(std::sync::Mutex::new(ReplayBuffer::new(0)));
     let last_tts_audio_writer = last_tts_audio.clone();
-    let replay_tx = playback_tx.clone();
+    let replay_queue = playback_queue.clone();
+    // Set while a background replay is still feeding playback; cancel stops it
+    let replay_active = Arc::new(AtomicBool::new(false));
+    let replay_cancel = Arc::new(AtomicBool::new(false));
 
     // 4. Shutdown flag
     let shutdown = Arc::new(AtomicBool::new(false));
 
-    // 5. TTS playback state tracking (for hotkey interrupt)
-    let is_playing = Arc::new(AtomicBool::new(false));
-    let is_playing_reader = is_playing.clone();
+    // 5b. Feedback received this session (recalled with 'h', appended to the summary)
+    let feedback_history = Arc::new(std::sync::Mutex::new(FeedbackHistory::new(
+        feedback_history::DEFAULT_MAX_ENTRIES,
+    )));
+    let feedback_history_reader = feedback_history.clone();
+    // Answered turns, offered by the rewind picker ('b')
+    let turn_log = Arc::new(std::sync::Mutex::new(TurnLog::default()));
+    let turn_log_reader = turn_log.clone();
+    // Set while a pronounced word ('p' + number) is streaming: kept out of the replay buffer
+    let word_audio = Arc::new(AtomicBool::new(false));
+    let word_audio_reader = word_audio.clone();
+
+    // 5c. Keyboard thread: owns raw input until the end of run_client (or a panic),
+    // and drives listening with the terminal hotkey backend
+    let is_listening = Arc::new(AtomicBool::new(false));
+    let hotkey_suspended = Arc::new(AtomicBool::new(false));
+    let terminal_hotkey =
+        (hotkey_backend == hotkey::HotkeyBackend::Terminal).then(|| keyboard::TerminalHotkey {
+            is_listening: is_listening.clone(),
+            suspended: hotkey_suspended.clone(),
+        });
+    let (keys, _terminal_guard) = match headless {
+        Some(_) => (keyboard::Keys::headless(feedback_delay), None),
+        None => {
+            let (keys, guard) = keyboard::Keys::spawn(shutdown.clone(), terminal_hotkey)?;
+            (keys, Some(guard))
+        }
+    };
+    let reader_keys = keys.clone();
+
+    // 5d. Control socket (--control): the session state out, tray commands in
+    let (output, commands, _control_socket) = match &control_socket {
+        Some(path) => {
+            let (socket, events, commands) = control::start(path, keys.clone(), shutdown.clone())?;
+            (output.with_control(events), commands, Some(socket))
+        }
+        None => (output, crossbeam_channel::never(), None),
+    };
 
-    // 6. Spawn tcp_reader thread
+    // 6. Spawn tcp_reader thread (it owns the "thinking…" spinner between turns)
+    let wait_indicator = status_line::WaitIndicator::spawn(shutdown.clone());
+    let reader_wait_indicator = wait_indicator.clone();
     let tcp_shutdown = shutdown.clone();
     let (summary_tx, summary_rx) = crossbeam_channel::bounded::<String>(1);
+    // The server's answer to ListVoices, for the voice picker ('v')
+    let (voices_tx, voices_rx) = crossbeam_channel::bounded::<Vec<String>>(1);
+    let reader_output = output.clone();
+    // Takes the reply audio off the reader, which must not wait for room in the queue
+    let feeder = Feeder::spawn(playback_queue.clone(), shutdown.clone())?;
+    // Segments sent and not acknowledged yet: the reader takes the acks
+    let outbox = Arc::new(outbox::Outbox::default());
+    let reader_outbox = outbox.clone();
     let tcp_reader_handle = std::thread::Builder::new()
         .name("tcp_reader".into())
         .spawn(move || {
             tcp_reader_loop(
                 reader,
                 BufWriter::new(feedback_stream),
-                playback_tx,
+                playback_queue,
+                feeder,
+                reader_outbox,
                 output_rate,
                 tcp_shutdown,
                 is_playing_reader,
                 summary_tx,
+                voices_tx,
                 last_tts_audio_writer,
+                replay_buffer_secs,
+                reader_wait_indicator,
+                feedback_history_reader,
+                turn_log_reader,
+                word_audio_reader,
+                reader_keys,
+                reader_output,
             )
         })?;
 
     // 7. Start audio capture
     let (audio_tx, audio_rx) = crossbeam_channel::bounded::<Vec<i16>>(64);
-    let (_capture_stream, capture_config) = audio::start_capture(&config.device, audio_tx)?;
-    let mut resample =
-        audio::create_resampler(capture_config.sample_rate, 16000, capture_config.channels)?;
+    let mut capture_stream =
+        audio::CaptureStream::start(&config.device, audio_tx, capture_channel)?;
+    let capture_config = capture_stream.config();
+    let mut resample = audio::create_resampler(
+        capture_config.sample_rate,
+        16000,
+        capture_config.channels,
+        capture_channel,
+        resample_quality,
+    )?;
+    let audio_input = AudioInputInfo {
+        device: config.device_name.clone(),
+        sample_rate: capture_config.sample_rate,
+        channels: capture_config.channels,
+        resampler: audio::describe_resampler(
+            capture_config.sample_rate,
+            16000,
+            capture_config.channels,
+            capture_channel,
+            resample_quality,
+        ),
+    };
+    info!("[client] Audio input: {audio_input}");
+    let mut denoiser = if !denoise {
+        audio::Denoiser::disabled()
+    } else if capture_config.sample_rate != audio::DENOISE_SAMPLE_RATE {
+        warn!(
+            "[client] --denoise needs a {} Hz input, {} runs at {} Hz: noise suppression off",
+            audio::DENOISE_SAMPLE_RATE,
+            config.device_name,
+            capture_config.sample_rate
+        );
+        audio::Denoiser::disabled()
+    } else {
+        info!("[client] Noise suppression on");
+        audio::Denoiser::new(capture_config.channels)
+    };
+    let mut agc = agc_max_gain.map(|max_gain| {
+        info!("[client] Automatic gain control on (max gain {max_gain}×)");
+        audio::Agc::new(16000, max_gain)
+    });
 
-    // 8. Hotkey
-    let is_listening = Arc::new(AtomicBool::new(false));
-    hotkey::listen_all_keyboards(config.hotkey, is_listening.clone())?;
+    // 8. Hotkey (a double-tap sets cancel_pressed)
+    let cancel_pressed = Arc::new(AtomicBool::new(false));
+    if double_tap.is_some() && config.hotkey_mode == hotkey::HotkeyMode::Hold {
+        warn!("[client] Double-tap cancel only works with a toggle hotkey (ignored)");
+    }
+    match hotkey_backend {
+        hotkey::HotkeyBackend::Evdev => hotkey::listen_all_keyboards(
+            config.hotkey,
+            config.hotkey_mode,
+            is_listening.clone(),
+            hotkey_suspended.clone(),
+            double_tap.map(|window| hotkey::DoubleTap {
+                window,
+                cancel_pressed: cancel_pressed.clone(),
+            }),
+        )?,
+        hotkey::HotkeyBackend::Terminal => {
+            warn!("Terminal hotkey: [Space] toggles listening, [c] cancels.");
+            warn!("  Keys only reach the client while its terminal is focused.");
+        }
+    }
 
     // 9. Ctrl+C handler
     let shutdown_clone = shutdown.clone();
@@ -133,36 +587,504 @@ fn run_client(server_override: Option<String>) -> Result<()> {
         shutdown_clone.store(true, Ordering::SeqCst);
     })?;
 
+    // 9b. Ctrl+Z: suspend cleanly instead of leaving raw mode or audio streams behind
+    if let Err(e) = suspend::install_handlers() {
+        warn!("[client] Ctrl+Z handling unavailable: {e}");
+    }
+
     // 10. Main audio/VAD loop
-    info!("Ready! Press {:?} to toggle listening.", config.hotkey);
+    let talk = match (hotkey_backend, config.hotkey_mode) {
+        (hotkey::HotkeyBackend::Terminal, _) => "Press [Space] to toggle listening".to_string(),
+        (_, hotkey::HotkeyMode::Toggle) => {
+            format!("Press {:?} to toggle listening", config.hotkey)
+        }
+        (_, hotkey::HotkeyMode::Hold) => format!("Hold {:?} while you speak", config.hotkey),
+    };
+    if headless.is_some() {
+        info!(
+            "Ready! {talk}. Feedback continues after {}s.",
+            feedback_delay.as_secs()
+        );
+    } else {
+        info!(
+            "Ready! {talk}, [t] to type a message, [l] to translate the last reply, [x] to hear it more simply, [a] to toggle aside mode (speech not sent), [d] to disregard your last message, [b] to rewind the conversation, [v] to pick the tutor's voice, [g] to switch the language you speak, [m] to switch voice mode, [h] for past feedback, [p]+number to hear a suggested word, [+/-] for volume."
+        );
+    }
 
-    let voice_mode = config.voice_mode;
-    let mut voice_detector = vad::VoiceDetector::new()?;
+    let mut voice_mode = config.voice_mode;
+    let vad_config = vad_overrides.apply(config.vad_preset.config());
+    debug!("[client] VAD: {vad_config:?}");
+    let mut voice_detector = vad::VoiceDetector::new(vad_config)?;
     let mut writer = writer;
+    if timings && let Err(e) = write_client_msg(&mut writer, &ClientMsg::EnableTimings) {
+        warn!("[client] Failed to request timings: {e}");
+    }
+    // Once per session, so the server log says which mic a session used
+    if let Err(e) = write_client_msg(&mut writer, &ClientMsg::AudioInput(audio_input.clone())) {
+        warn!("[client] Failed to report the audio input: {e}");
+    }
     let mut was_listening = false;
+    let mut simplify_throttle = SimplifyThrottle::default();
+    let mut aside = Aside::default();
+    // Screen lock / system sleep: pause until the user explicitly resumes
+    let mut away_detector = away::SystemAwayDetector::start(shutdown.clone());
+    let mut away_state = away::AwayState::default();
+    // PauseRequest sent for an away pause; the next listening turn resumes the server
+    let mut paused_while_away = false;
+    let mut mic_meter = MicMeter::new(output.clone());
     let mut chunk_count: u64 = 0;
     let mut listening_chunks: u64 = 0;
     let mut audio_accumulator: Vec<i16> = Vec::new(); // Manual mode: raw audio buffer
-    let quit_requested = Arc::new(AtomicBool::new(false));
+    // Summaries abandoned with Esc: they still arrive, and are discarded
+    let mut abandoned_summaries: u32 = 0;
 
     loop {
         if shutdown.load(Ordering::SeqCst) {
             break;
         }
+        discard_abandoned_summaries(&summary_rx, &mut abandoned_summaries);
+
+        // Suspend (Ctrl+Z) or resume after an external stop
+        let resumed = if suspend::take_request() {
+            mic_meter.reset();
+            info!("[client] Suspending (fg to resume)");
+            let snapshot = suspend::suspend(
+                &mut keyboard::RawInputTerminal,
+                &mut [&mut capture_stream, &mut playback_stream],
+                is_listening.load(Ordering::SeqCst),
+                keys.prompt_open(),
+                suspend::stop_process,
+            );
+            suspend::take_continued();
+            Some(snapshot)
+        } else if suspend::take_continued() {
+            let snapshot = suspend::SuspendSnapshot {
+                raw_mode: keyboard::is_raw_input(),
+                listening: is_listening.load(Ordering::SeqCst),
+                prompt_open: keys.prompt_open(),
+            };
+            suspend::restore(
+                &snapshot,
+                &mut keyboard::RawInputTerminal,
+                &mut [&mut capture_stream, &mut playback_stream],
+            );
+            Some(snapshot)
+        } else {
+            None
+        };
+        if let Some(snapshot) = resumed {
+            mic_meter.reset();
+            // Mic audio queued around the stop is stale
+            while audio_rx.try_recv().is_ok() {}
+            voice_detector.reset();
+            info!("{}", snapshot.resync_line());
+        }
+
+        // Playback device lost (e.g. headset unplugged): reopen on the default device
+        if let playback::Recovery::Rebuilt { rate_changed } = playback_stream.recover() {
+            if rate_changed {
+                info!(
+                    "[client] Playback restored at {}Hz",
+                    playback_stream.output_rate()
+                );
+            } else {
+                info!("[client] Playback restored");
+            }
+        }
+
+        // A segment the server did not acknowledge in time is sent again
+        for msg in outbox.overdue(Instant::now()) {
+            if let Err(e) = write_client_msg(&mut writer, &msg) {
+                warn!("[client] Send error: {e}");
+                if is_disconnect(&e) {
+                    shutdown.store(true, Ordering::SeqCst);
+                }
+                break;
+            }
+        }
+
+        // Stepped away: stop listening without sending what was being said
+        if let Some(action) = away_state.poll(&mut away_detector) {
+            if action.pauses() {
+                let was_on = is_listening.swap(false, Ordering::SeqCst);
+                hotkey_suspended.store(true, Ordering::SeqCst);
+                was_listening = false;
+                audio_accumulator.clear();
+                voice_detector.reset();
+                mic_meter.reset();
+                suspend::pause_streams(&mut [&mut capture_stream]);
+                while audio_rx.try_recv().is_ok() {}
+                if let Err(e) = write_client_msg(&mut writer, &ServerPause::Pause.msg()) {
+                    warn!("[client] Failed to send PauseRequest: {e}");
+                    if is_disconnect(&e) {
+                        shutdown.store(true, Ordering::SeqCst);
+                        break;
+                    }
+                }
+                paused_while_away = true;
+                if was_on {
+                    info!("[client] Away — listening stopped, session paused");
+                } else {
+                    info!("[client] Away — session paused");
+                }
+            }
+            if action.resumes() {
+                suspend::resume_streams(&mut [&mut capture_stream]);
+                while audio_rx.try_recv().is_ok() {}
+                hotkey_suspended.store(false, Ordering::SeqCst);
+                info!(
+                    "[client] Session paused while away \u{2014} press {:?} to resume",
+                    config.hotkey
+                );
+            }
+        }
 
-        // Check for 'q' (quit) or '3' (replay) when not listening
+        // Check for 'q' (quit), '3'/'5' (replay), Esc (cancel), 't' (type), +/- (volume),
+        // 'm' (voice mode), 'h' (feedback history), 'l' (translate), 'x' (simplify), 'a' (aside)
+        // or 'd' (disregard) when not listening
         if !is_listening.load(Ordering::SeqCst) {
-            match poll_key_action() {
+            let action = match poll_key_action(&keys) {
+                // A command acts like the key it stands for
+                PollAction::None if !keys.prompt_open() => {
+                    commands.try_recv().unwrap_or(PollAction::None)
+                }
+                action => action,
+            };
+            match action {
                 PollAction::Quit => {
                     info!("[client] Quit requested (q)");
-                    quit_requested.store(true, Ordering::SeqCst);
-                    break;
+                    // Mic off while the summary is offered (TCP still open)
+                    suspend::pause_streams(&mut [&mut capture_stream]);
+                    let hotkey_was_suspended = hotkey_suspended.swap(true, Ordering::SeqCst);
+                    let outcome = offer_summary(
+                        &keys,
+                        &shutdown,
+                        &mut writer,
+                        &summary_rx,
+                        &mut abandoned_summaries,
+                        &audio_input,
+                        &feedback_history,
+                    );
+                    if outcome == QuitOutcome::Exit {
+                        break;
+                    }
+                    // Back to the session: nothing said during the prompt counts
+                    suspend::resume_streams(&mut [&mut capture_stream]);
+                    while audio_rx.try_recv().is_ok() {}
+                    is_listening.store(false, Ordering::SeqCst);
+                    was_listening = false;
+                    audio_accumulator.clear();
+                    voice_detector.reset();
+                    mic_meter.reset();
+                    hotkey_suspended.store(hotkey_was_suspended, Ordering::SeqCst);
+                    info!(
+                        "[client] Back to the session \u{2014} press {:?} to talk, [q] to quit",
+                        config.hotkey
+                    );
+                }
+                PollAction::Replay | PollAction::SlowReplay
+                    if !is_playing.load(Ordering::SeqCst)
+                        && !replay_active.load(Ordering::SeqCst) =>
+                {
+                    let speed = match action {
+                        PollAction::SlowReplay => SLOW_REPLAY_SPEED,
+                        _ => 1.0,
+                    };
+                    spawn_replay(
+                        &last_tts_audio,
+                        &replay_queue,
+                        speed,
+                        playback_stream.output_rate(),
+                        &replay_active,
+                        &replay_cancel,
+                    );
+                }
+                PollAction::Replay | PollAction::SlowReplay => {}
+                PollAction::Cancel => {
+                    if replay_active.load(Ordering::SeqCst) {
+                        info!("[REPLAY] Cancelled");
+                        replay_cancel.store(true, Ordering::SeqCst);
+                        playback_clear.store(true, Ordering::SeqCst);
+                    } else if is_playing.load(Ordering::SeqCst) {
+                        // e.g. a simpler rephrasing that turned out not to be needed
+                        info!("[client] Reply cancelled");
+                        if let Err(e) = write_client_msg(&mut writer, &ClientMsg::InterruptTts) {
+                            warn!("[client] Failed to send InterruptTts: {e}");
+                            if is_disconnect(&e) {
+                                shutdown.store(true, Ordering::SeqCst);
+                            }
+                        }
+                        is_playing.store(false, Ordering::SeqCst);
+                        playback_clear.store(true, Ordering::SeqCst);
+                    }
+                }
+                PollAction::TypeText => {
+                    // Keep the hotkey from starting a recording while the prompt is open
+                    hotkey_suspended.store(true, Ordering::SeqCst);
+                    let typed = read_text_input(&keys, &shutdown);
+                    hotkey_suspended.store(false, Ordering::SeqCst);
+                    // Discard mic audio captured while typing
+                    while audio_rx.try_recv().is_ok() {}
+
+                    if let Some(text) = typed {
+                        // Auto mode pauses the server while idle, which would mute the reply
+                        let mut msgs = Vec::new();
+                        if voice_mode == tui::VoiceMode::Auto {
+                            msgs.push(ClientMsg::ResumeRequest);
+                        }
+                        msgs.push(ClientMsg::TextInput(text));
+                        for msg in &msgs {
+                            if let Err(e) = write_client_msg(&mut writer, msg) {
+                                warn!("[client] Failed to send typed text: {e}");
+                                if is_disconnect(&e) {
+                                    shutdown.store(true, Ordering::SeqCst);
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+                PollAction::VolumeUp | PollAction::VolumeDown => {
+                    let step = match action {
+                        PollAction::VolumeUp => VOLUME_STEP as i64,
+                        _ => -(VOLUME_STEP as i64),
+                    };
+                    let volume = (settings.volume as i64 + step)
+                        .clamp(0, settings::MAX_VOLUME as i64)
+                        as u32;
+                    if volume != settings.volume {
+                        settings.volume = volume;
+                        playback_gain.store(volume, Ordering::Relaxed);
+                        info!("[client] Volume: {volume}%");
+                        if let Err(e) = settings.save() {
+                            warn!("[client] Could not save volume: {e:#}");
+                        }
+                    }
                 }
-                PollAction::Replay => {
-                    if !is_playing.load(Ordering::SeqCst) {
-                        replay_last_audio(&last_tts_audio, &replay_tx);
+                PollAction::ToggleMode => {
+                    let switch = switch_voice_mode(voice_mode, was_listening);
+                    let mut msgs = Vec::new();
+                    if switch.send_accumulated
+                        && !audio_accumulator.is_empty()
+                        && let Some(segment) = aside.pass(std::mem::take(&mut audio_accumulator))
+                    {
+                        msgs.push(outbox.number(segment, Instant::now()));
+                    }
+                    if switch.flush_vad {
+                        if let Some(segment) = voice_detector.flush().and_then(|s| aside.pass(s)) {
+                            msgs.push(outbox.number(segment, Instant::now()));
+                        }
+                        voice_detector.reset();
+                    }
+                    msgs.extend(switch.server.map(ServerPause::msg));
+                    for msg in &msgs {
+                        if let Err(e) = write_client_msg(&mut writer, msg) {
+                            warn!("[client] Failed to send mode switch: {e}");
+                            if is_disconnect(&e) {
+                                shutdown.store(true, Ordering::SeqCst);
+                            }
+                            break;
+                        }
+                    }
+                    voice_mode = switch.mode;
+                    info!("[client] Voice mode: {voice_mode:?}");
+                }
+                PollAction::ShowHistory => {
+                    if let Ok(history) = feedback_history.lock() {
+                        display_feedback_history(&history, FEEDBACK_RECALL_COUNT);
+                    }
+                }
+                PollAction::PronounceWord => {
+                    let Some(n) = read_word_number(&keys) else {
+                        continue;
+                    };
+                    let word = feedback_history.lock().ok().and_then(|history| {
+                        let entry = history.iter().last()?;
+                        feedback_words(&entry.feedback).get(n - 1).cloned()
+                    });
+                    match word {
+                        None => info!("[client] No word [{n}] in the last feedback"),
+                        Some(_) if is_playing.load(Ordering::SeqCst) => {
+                            info!("[client] Wait for playback to finish to hear a word");
+                        }
+                        Some(word) => {
+                            word_audio.store(true, Ordering::SeqCst);
+                            if let Err(e) =
+                                write_client_msg(&mut writer, &ClientMsg::SpeakWord(word))
+                            {
+                                word_audio.store(false, Ordering::SeqCst);
+                                warn!("[client] Failed to request word audio: {e}");
+                                if is_disconnect(&e) {
+                                    shutdown.store(true, Ordering::SeqCst);
+                                }
+                            }
+                        }
+                    }
+                }
+                PollAction::Translate => {
+                    if let Err(e) = write_client_msg(&mut writer, &ClientMsg::TranslateLast) {
+                        warn!("[client] Failed to request a translation: {e}");
+                        if is_disconnect(&e) {
+                            shutdown.store(true, Ordering::SeqCst);
+                        }
+                    }
+                }
+                PollAction::Simplify => {
+                    if !simplify_throttle.try_send(Instant::now()) {
+                        info!("[client] Simpler version already requested, please wait");
+                        continue;
+                    }
+                    info!("[client] Asking for a simpler version of the last reply...");
+                    // Auto mode pauses the server while idle, which would mute the reply
+                    let mut msgs = Vec::new();
+                    if voice_mode == tui::VoiceMode::Auto {
+                        msgs.push(ClientMsg::ResumeRequest);
+                    }
+                    msgs.push(ClientMsg::SimplifyLast);
+                    for msg in &msgs {
+                        if let Err(e) = write_client_msg(&mut writer, msg) {
+                            warn!("[client] Failed to request a simpler version: {e}");
+                            if is_disconnect(&e) {
+                                shutdown.store(true, Ordering::SeqCst);
+                            }
+                            break;
+                        }
+                    }
+                }
+                PollAction::ToggleAside => {
+                    if aside.toggle() {
+                        info!("[client] Aside mode on: what you say is not sent to the tutor");
+                    } else {
+                        info!("[client] Aside mode off");
+                    }
+                }
+                PollAction::Disregard => {
+                    // Its reply (if any) is about something the tutor wasn't meant to hear
+                    if is_playing.load(Ordering::SeqCst) {
+                        if let Err(e) = write_client_msg(&mut writer, &ClientMsg::InterruptTts) {
+                            warn!("[client] Failed to send InterruptTts: {e}");
+                        }
+                        is_playing.store(false, Ordering::SeqCst);
+                        playback_clear.store(true, Ordering::SeqCst);
+                    }
+                    if let Err(e) = write_client_msg(&mut writer, &ClientMsg::DisregardLast) {
+                        warn!("[client] Failed to disregard the last message: {e}");
+                        if is_disconnect(&e) {
+                            shutdown.store(true, Ordering::SeqCst);
+                        }
+                    } else if let Ok(mut log) = turn_log.lock() {
+                        log.disregard();
+                    }
+                }
+                PollAction::ScrollBack => {
+                    output.send(SessionEvent::Scroll(session_view::SCROLL_STEP));
+                }
+                PollAction::ScrollForward => {
+                    output.send(SessionEvent::Scroll(-session_view::SCROLL_STEP));
+                }
+                PollAction::Rewind => {
+                    let turn = {
+                        let Ok(log) = turn_log.lock() else {
+                            continue;
+                        };
+                        let choices = log.rewind_choices(9);
+                        if choices.is_empty() {
+                            info!("[client] Nothing to rewind yet");
+                            continue;
+                        }
+                        display_rewind_choices(&choices);
+                        let Some(n) = read_number_key(&keys, REWIND_KEY_TIMEOUT) else {
+                            info!("[client] Rewind cancelled");
+                            continue;
+                        };
+                        match choices.get(n - 1) {
+                            Some(choice) => choice.turn,
+                            None => {
+                                info!("[client] No choice [{n}]");
+                                continue;
+                            }
+                        }
+                    };
+                    if is_playing.load(Ordering::SeqCst) {
+                        if let Err(e) = write_client_msg(&mut writer, &ClientMsg::InterruptTts) {
+                            warn!("[client] Failed to send InterruptTts: {e}");
+                        }
+                        is_playing.store(false, Ordering::SeqCst);
+                        playback_clear.store(true, Ordering::SeqCst);
+                    }
+                    // Auto mode pauses the server while idle, which would mute the announcement
+                    let mut msgs = Vec::new();
+                    if voice_mode == tui::VoiceMode::Auto {
+                        msgs.push(ClientMsg::ResumeRequest);
+                    }
+                    msgs.push(ClientMsg::BranchTo(turn));
+                    let mut sent = true;
+                    for msg in &msgs {
+                        if let Err(e) = write_client_msg(&mut writer, msg) {
+                            warn!("[client] Failed to rewind the conversation: {e}");
+                            if is_disconnect(&e) {
+                                shutdown.store(true, Ordering::SeqCst);
+                            }
+                            sent = false;
+                            break;
+                        }
+                    }
+                    if sent && let Ok(mut log) = turn_log.lock() {
+                        log.rewind(turn);
+                    }
+                }
+                PollAction::PickVoice => {
+                    while voices_rx.try_recv().is_ok() {}
+                    if let Err(e) = write_client_msg(&mut writer, &ClientMsg::ListVoices) {
+                        warn!("[client] Failed to ask for the voices: {e}");
+                        if is_disconnect(&e) {
+                            shutdown.store(true, Ordering::SeqCst);
+                        }
+                        continue;
+                    }
+                    let Ok(voices) = voices_rx.recv_timeout(VOICE_LIST_TIMEOUT) else {
+                        info!("[client] The server did not list its voices");
+                        continue;
+                    };
+                    if voices.is_empty() {
+                        info!("[client] The server's TTS has a single voice");
+                        continue;
+                    }
+                    display_voices(&voices);
+                    hotkey_suspended.store(true, Ordering::SeqCst);
+                    let typed = read_line_input(&keys, &shutdown, "Voice name or number");
+                    hotkey_suspended.store(false, Ordering::SeqCst);
+                    while audio_rx.try_recv().is_ok() {}
+                    let Some(name) = typed.map(|typed| voice_choice(&voices, &typed)) else {
+                        continue;
+                    };
+                    // An unknown name comes back as a server error
+                    info!("[client] Switching to voice {name}");
+                    if let Err(e) = write_client_msg(&mut writer, &ClientMsg::SetVoice(name)) {
+                        warn!("[client] Failed to switch voices: {e}");
+                        if is_disconnect(&e) {
+                            shutdown.store(true, Ordering::SeqCst);
+                        }
+                    }
+                }
+                PollAction::PickLanguage => {
+                    hotkey_suspended.store(true, Ordering::SeqCst);
+                    let typed = read_line_input(&keys, &shutdown, "Language code (or auto)");
+                    hotkey_suspended.store(false, Ordering::SeqCst);
+                    while audio_rx.try_recv().is_ok() {}
+                    let Some(code) = typed.map(|typed| typed.trim().to_lowercase()) else {
+                        continue;
+                    };
+                    // An unknown code comes back as a server error
+                    info!("[client] Switching transcription to {code}");
+                    if let Err(e) = write_client_msg(&mut writer, &ClientMsg::SetLanguage(code)) {
+                        warn!("[client] Failed to switch languages: {e}");
+                        if is_disconnect(&e) {
+                            shutdown.store(true, Ordering::SeqCst);
+                        }
                     }
                 }
+                PollAction::Suspend => suspend::request(),
                 PollAction::None => {}
             }
         }
@@ -174,23 +1096,60 @@ fn run_client(server_override: Option<String>) -> Result<()> {
         };
 
         chunk_count += 1;
+        // Hotkey double-tap: drop the turn being recorded and stop the reply
+        if cancel_pressed.swap(false, Ordering::SeqCst) {
+            if is_listening.swap(false, Ordering::SeqCst) || was_listening {
+                // Nothing said so far is sent when listening stops below
+                audio_accumulator.clear();
+                voice_detector.reset();
+            }
+            // A turn already sent is dropped by the server, even mid-transcription
+            if let Err(e) = write_client_msg(&mut writer, &ClientMsg::CancelExchange) {
+                warn!("[client] Failed to send CancelExchange: {e}");
+                if is_disconnect(&e) {
+                    shutdown.store(true, Ordering::SeqCst);
+                    break;
+                }
+            }
+            if is_playing.load(Ordering::SeqCst) {
+                if let Err(e) = write_client_msg(&mut writer, &ClientMsg::InterruptTts) {
+                    warn!("[client] Failed to send InterruptTts: {e}");
+                    if is_disconnect(&e) {
+                        shutdown.store(true, Ordering::SeqCst);
+                        break;
+                    }
+                }
+                is_playing.store(false, Ordering::SeqCst);
+                playback_clear.store(true, Ordering::SeqCst);
+            }
+            if replay_active.load(Ordering::SeqCst) {
+                replay_cancel.store(true, Ordering::SeqCst);
+                playback_clear.store(true, Ordering::SeqCst);
+            }
+            info!("[CANCELLED]");
+        }
+
         let listening = is_listening.load(Ordering::SeqCst);
+        if listening != was_listening {
+            output.send(SessionEvent::Listening(listening));
+        }
 
         if was_listening && !listening {
+            mic_meter.reset();
             // Send accumulated audio before pausing
             match voice_mode {
                 tui::VoiceMode::Manual => {
-                    if !audio_accumulator.is_empty() {
-                        let segment = std::mem::take(&mut audio_accumulator);
+                    if !audio_accumulator.is_empty()
+                        && let Some(segment) = aside.pass(std::mem::take(&mut audio_accumulator))
+                    {
                         let duration_ms = segment.len() as f64 / 16.0;
                         debug!(
                             "[SENDING...] segment: {} samples ({:.0}ms)",
                             segment.len(),
                             duration_ms
                         );
-                        if let Err(e) =
-                            write_client_msg(&mut writer, &ClientMsg::AudioSegment(segment))
-                        {
+                        let msg = outbox.number(segment, Instant::now());
+                        if let Err(e) = write_client_msg(&mut writer, &msg) {
                             if is_disconnect(&e) {
                                 shutdown.store(true, Ordering::SeqCst);
                                 break;
@@ -201,16 +1160,15 @@ fn run_client(server_override: Option<String>) -> Result<()> {
                 }
                 tui::VoiceMode::Auto => {
                     // Flush any in-progress VAD segment before pausing
-                    if let Some(segment) = voice_detector.flush() {
+                    if let Some(segment) = voice_detector.flush().and_then(|s| aside.pass(s)) {
                         let duration_ms = segment.len() as f64 / 16.0;
                         debug!(
                             "[SENDING...] flush: {} samples ({:.0}ms)",
                             segment.len(),
                             duration_ms
                         );
-                        if let Err(e) =
-                            write_client_msg(&mut writer, &ClientMsg::AudioSegment(segment))
-                        {
+                        let msg = outbox.number(segment, Instant::now());
+                        if let Err(e) = write_client_msg(&mut writer, &msg) {
                             if is_disconnect(&e) {
                                 shutdown.store(true, Ordering::SeqCst);
                                 break;
@@ -238,6 +1196,9 @@ fn run_client(server_override: Option<String>) -> Result<()> {
         }
 
         if !was_listening && listening {
+            // A new turn supersedes the one still waiting for a reply
+            wait_indicator.stop();
+            mic_meter.reset();
             // Interrupt TTS if currently playing (hotkey ON during playback)
             if is_playing.load(Ordering::SeqCst) {
                 info!("[BARGE-IN] Hotkey interrupt");
@@ -251,8 +1212,14 @@ fn run_client(server_override: Option<String>) -> Result<()> {
                 is_playing.store(false, Ordering::SeqCst);
                 playback_clear.store(true, Ordering::SeqCst);
             }
+            // A replay is local audio only: just stop it
+            if replay_active.load(Ordering::SeqCst) {
+                replay_cancel.store(true, Ordering::SeqCst);
+                playback_clear.store(true, Ordering::SeqCst);
+            }
             audio_accumulator.clear();
-            if voice_mode == tui::VoiceMode::Auto {
+            if voice_mode == tui::VoiceMode::Auto || paused_while_away {
+                paused_while_away = false;
                 if let Err(e) = write_client_msg(&mut writer, &ClientMsg::ResumeRequest) {
                     warn!("[client] Failed to send ResumeRequest: {e}");
                     if is_disconnect(&e) {
@@ -281,15 +1248,29 @@ fn run_client(server_override: Option<String>) -> Result<()> {
 
         listening_chunks += 1;
 
-        let resampled = resample(&chunk);
+        let denoised = profile::time("denoise", || denoiser.process(&chunk));
+        if denoised.is_empty() {
+            // Still filling the first denoiser frame (an empty slice would flush the resampler)
+            continue;
+        }
+        let mut resampled = profile::time("resample_capture", || resample(&denoised));
         if resampled.is_empty() {
             if listening_chunks.is_multiple_of(100) {
                 debug!("  WARNING: resampler producing empty output");
             }
             continue;
         }
+        if let Some(agc) = &mut agc {
+            agc.process(&mut resampled);
+        }
+
+        // The wait spinner owns the status line while a reply is pending
+        if !wait_indicator.is_active() {
+            mic_meter.update(&resampled);
+        }
 
         if listening_chunks == 1 {
+            mic_meter.clear();
             debug!(
                 "  Audio chunk: {} samples -> resampled to {} samples",
                 chunk.len(),
@@ -304,16 +1285,21 @@ fn run_client(server_override: Option<String>) -> Result<()> {
             }
             tui::VoiceMode::Auto => {
                 // VAD auto-segmentation: send segments when silence detected
-                let segments = voice_detector.process_samples(&resampled);
-                for segment in segments {
+                let output = profile::time("vad", || voice_detector.process_samples(&resampled));
+                mic_meter.set_speech_probability(output.speech_probability);
+                let segments = output.segments;
+                if !segments.is_empty() {
+                    mic_meter.clear();
+                }
+                for segment in segments.into_iter().filter_map(|s| aside.pass(s)) {
                     let duration_ms = segment.len() as f64 / 16.0;
                     debug!(
                         "[SENDING...] segment: {} samples ({:.0}ms)",
                         segment.len(),
                         duration_ms
                     );
-                    if let Err(e) = write_client_msg(&mut writer, &ClientMsg::AudioSegment(segment))
-                    {
+                    let msg = outbox.number(segment, Instant::now());
+                    if let Err(e) = write_client_msg(&mut writer, &msg) {
                         if is_disconnect(&e) {
                             info!("[client] Server disconnected");
                             shutdown.store(true, Ordering::SeqCst);
@@ -326,42 +1312,17 @@ fn run_client(server_override: Option<String>) -> Result<()> {
         }
     }
 
-    // 11. Post-loop: summary prompt or direct shutdown
-    drop(_capture_stream);
-
-    if quit_requested.load(Ordering::SeqCst) && !shutdown.load(Ordering::SeqCst) {
-        // User pressed 'q' — offer summary generation (TCP still open)
-        eprintln!();
-        eprintln!("  \x1b[1mGenerate session summary? [y/n]\x1b[0m");
-        eprint!("  > ");
-        let _ = std::io::stderr().flush();
-
-        let generate = read_summary_choice(&shutdown);
-
-        if generate {
-            info!("Generating summary...");
-            if let Err(e) = write_client_msg(&mut writer, &ClientMsg::SummaryRequest) {
-                warn!("[client] Failed to send SummaryRequest: {e}");
-            } else {
-                match summary_rx.recv() {
-                    Ok(summary) => match save_summary(&summary) {
-                        Ok(path) => {
-                            info!("Session summary saved to: {}", path.display());
-                        }
-                        Err(e) => {
-                            warn!("[client] Failed to save summary: {e}");
-                        }
-                    },
-                    Err(_) => {
-                        warn!("[client] Summary channel closed before receiving response");
-                    }
-                }
-            }
-        }
-    }
+    // 11. Post-loop (the summary was offered when 'q' was pressed)
+    drop(capture_stream);
 
     // 12. Graceful shutdown
     info!("Shutting down...");
+    if let Some(summary) = playback_stream
+        .stats()
+        .summary(playback_stream.output_rate())
+    {
+        info!("{summary}");
+    }
     shutdown.store(true, Ordering::SeqCst);
     let _ = shutdown_stream.shutdown(Shutdown::Both);
 
@@ -380,99 +1341,551 @@ fn run_client(server_override: Option<String>) -> Result<()> {
 }
 
 /// Result of non-blocking key poll when not listening.
+#[derive(Debug, PartialEq)]
 enum PollAction {
     None,
     Quit,
     Replay,
+    SlowReplay,
+    Cancel,
+    TypeText,
+    VolumeUp,
+    VolumeDown,
+    Suspend,
+    ToggleMode,
+    ShowHistory,
+    PronounceWord,
+    Translate,
+    Simplify,
+    ToggleAside,
+    Disregard,
+    Rewind,
+    PickVoice,
+    PickLanguage,
+    /// PageUp / PageDown in the session view.
+    ScrollBack,
+    ScrollForward,
 }
 
-/// Check for 'q' (quit) or '3' (replay) key press using crossterm polling (non-blocking).
-fn poll_key_action() -> PollAction {
-    use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
-    use crossterm::terminal;
+/// Aside mode ('a'): speech meant for someone in the room is still captured,
+/// but dropped on the client instead of being sent to the tutor.
+#[derive(Default)]
+struct Aside {
+    on: bool,
+}
 
-    if terminal::is_raw_mode_enabled().unwrap_or(false) {
-        return PollAction::None;
+impl Aside {
+    /// Flip aside mode; returns whether it is now on.
+    fn toggle(&mut self) -> bool {
+        self.on = !self.on;
+        self.on
     }
 
-    if terminal::enable_raw_mode().is_err() {
-        return PollAction::None;
-    }
-
-    let action = if event::poll(Duration::from_millis(0)).unwrap_or(false) {
-        match event::read() {
-            Ok(Event::Key(KeyEvent {
-                code: KeyCode::Char('q'),
-                kind: KeyEventKind::Press,
-                ..
-            })) => PollAction::Quit,
-            Ok(Event::Key(KeyEvent {
-                code: KeyCode::Char('3'),
-                kind: KeyEventKind::Press,
-                ..
-            })) => PollAction::Replay,
-            _ => PollAction::None,
+    /// The segment to send, or `None` (noted on screen) when it is an aside.
+    fn pass(&self, segment: Vec<i16>) -> Option<Vec<i16>> {
+        if !self.on {
+            return Some(segment);
         }
-    } else {
-        PollAction::None
-    };
-
-    let _ = terminal::disable_raw_mode();
-    action
+        eprintln!(
+            "  {}",
+            palette().dim(&format!(
+                "[aside omitted] {:.1}s",
+                segment.len() as f64 / 16000.0
+            ))
+        );
+        None
+    }
 }
 
-/// Chunk size for replay playback (matches typical TTS chunk size).
-const REPLAY_CHUNK_SIZE: usize = 4000;
+/// Minimum time between two simplify requests ('x').
+const SIMPLIFY_COOLDOWN: Duration = Duration::from_secs(5);
 
-/// Maximum replay buffer size in samples (~5 minutes at 16 kHz mono).
-const REPLAY_BUFFER_MAX_SAMPLES: usize = 16_000 * 60 * 5;
+/// Rate limit for the simplify key, so repeated presses don't queue several
+/// rephrasings of the same reply.
+#[derive(Default)]
+struct SimplifyThrottle {
+    last_sent: Option<Instant>,
+}
+
+impl SimplifyThrottle {
+    /// Whether a request may be sent at `now`; if so, it counts as sent.
+    fn try_send(&mut self, now: Instant) -> bool {
+        if self
+            .last_sent
+            .is_some_and(|last| now.duration_since(last) < SIMPLIFY_COOLDOWN)
+        {
+            return false;
+        }
+        self.last_sent = Some(now);
+        true
+    }
+}
+
+/// Server pause request needed to match a voice mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ServerPause {
+    Pause,
+    Resume,
+}
+
+impl ServerPause {
+    fn msg(self) -> ClientMsg {
+        match self {
+            ServerPause::Pause => ClientMsg::PauseRequest,
+            ServerPause::Resume => ClientMsg::ResumeRequest,
+        }
+    }
+}
+
+/// What the main loop must do to flip the voice mode at runtime.
+#[derive(Debug, PartialEq)]
+struct ModeSwitch {
+    mode: tui::VoiceMode,
+    /// Send the Manual-mode accumulator as a segment before VAD takes over.
+    send_accumulated: bool,
+    /// Send the in-progress VAD segment so nothing said so far is lost.
+    flush_vad: bool,
+    /// Put the server in the pause state the new mode expects.
+    server: Option<ServerPause>,
+}
+
+/// Flip between Manual and Auto. `listening` is the loop's view of the hotkey
+/// state, so a toggle it has not processed yet is handled by the new mode.
+///
+/// Auto pauses the server while idle and resumes it while listening; Manual
+/// never pauses it.
+fn switch_voice_mode(current: tui::VoiceMode, listening: bool) -> ModeSwitch {
+    match current {
+        tui::VoiceMode::Manual => ModeSwitch {
+            mode: tui::VoiceMode::Auto,
+            send_accumulated: listening,
+            flush_vad: false,
+            server: Some(if listening {
+                ServerPause::Resume
+            } else {
+                ServerPause::Pause
+            }),
+        },
+        tui::VoiceMode::Auto => ModeSwitch {
+            mode: tui::VoiceMode::Manual,
+            send_accumulated: false,
+            flush_vad: true,
+            // Auto left the server paused while idle, and Manual never resumes it
+            server: (!listening).then_some(ServerPause::Resume),
+        },
+    }
+}
+
+/// Check for a pending idle key press (non-blocking); see [`key_action`].
+fn poll_key_action(keys: &keyboard::Keys) -> PollAction {
+    keys.poll_idle().map_or(PollAction::None, key_action)
+}
+
+/// Map an idle key press: 'q' (quit), '3' (replay), '5' (slow replay), Esc (cancel),
+/// 't' (type), '+'/'-' (volume), 'm' (voice mode), 'h' (feedback history), 'p'
+/// (pronounce a word), 'l' (translate the last reply), 'x' (rephrase it more simply),
+/// 'a' (aside mode), 'd' (disregard the last message), 'b' (rewind the conversation),
+/// 'v' (pick the tutor's voice), 'g' (switch the transcription language) or
+/// PageUp/PageDown (scroll the session view).
+fn key_action(key: KeyEvent) -> PollAction {
+    match key.code {
+        // Ctrl+Z normally arrives as SIGTSTP; a key press is handled the same way
+        KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => PollAction::Suspend,
+        KeyCode::Char('q') => PollAction::Quit,
+        KeyCode::Char('3') => PollAction::Replay,
+        KeyCode::Char('5') => PollAction::SlowReplay,
+        KeyCode::Esc => PollAction::Cancel,
+        KeyCode::Char('t') => PollAction::TypeText,
+        KeyCode::Char('+' | '=') => PollAction::VolumeUp,
+        KeyCode::Char('-') => PollAction::VolumeDown,
+        KeyCode::Char('m') => PollAction::ToggleMode,
+        KeyCode::Char('h') => PollAction::ShowHistory,
+        KeyCode::Char('p') => PollAction::PronounceWord,
+        KeyCode::Char('l') => PollAction::Translate,
+        KeyCode::Char('x') => PollAction::Simplify,
+        KeyCode::Char('a') => PollAction::ToggleAside,
+        KeyCode::Char('d') => PollAction::Disregard,
+        KeyCode::Char('b') => PollAction::Rewind,
+        KeyCode::Char('v') => PollAction::PickVoice,
+        KeyCode::Char('g') => PollAction::PickLanguage,
+        KeyCode::PageUp => PollAction::ScrollBack,
+        KeyCode::PageDown => PollAction::ScrollForward,
+        _ => PollAction::None,
+    }
+}
+
+/// Ask whether to take over the session still running on the server or start fresh.
+fn read_takeover_choice(since: u64) -> Result<bool> {
+    use crossterm::event::{self, Event, KeyEventKind};
+    use crossterm::terminal;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let minutes = now.saturating_sub(since) / 60;
+
+    eprintln!();
+    eprintln!(
+        "  {}",
+        palette().bold(&format!(
+            "A session is still running on the server (started {minutes} min ago)."
+        ))
+    );
+    eprintln!("  [1] Take over existing session");
+    eprintln!("  [2] Start fresh");
+    eprint!("  > ");
+    let _ = std::io::stderr().flush();
+
+    if terminal::enable_raw_mode().is_err() {
+        // Fallback to line-based input if raw mode fails
+        let mut input = String::new();
+        let _ = std::io::stdin().read_line(&mut input);
+        return Ok(input.trim() != "2");
+    }
+
+    let result = loop {
+        let Ok(Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        })) = event::read()
+        else {
+            continue;
+        };
+        match code {
+            KeyCode::Char('1') => break Ok(true),
+            KeyCode::Char('2') => break Ok(false),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                break Err(anyhow::anyhow!("Session recovery cancelled by user."));
+            }
+            _ => {}
+        }
+    };
+
+    let _ = terminal::disable_raw_mode();
+    eprintln!();
+    if let Ok(take_over) = result {
+        info!(
+            "[client] {}",
+            if take_over {
+                "Taking over the running session"
+            } else {
+                "Starting a fresh session"
+            }
+        );
+    }
+    result
+}
 
-/// Replay the last TTS response audio through the playback channel.
+/// Read one line of typed input (Enter sends, Esc cancels).
+/// Returns `None` if cancelled, left empty, or shutdown was requested.
+fn read_text_input(keys: &keyboard::Keys, shutdown: &Arc<AtomicBool>) -> Option<String> {
+    read_line_input(keys, shutdown, "Type your message")
+}
+
+/// Read one line under `title`, like [`read_text_input`].
+fn read_line_input(
+    keys: &keyboard::Keys,
+    shutdown: &Arc<AtomicBool>,
+    title: &str,
+) -> Option<String> {
+    let _prompt = keys.prompt();
+    eprint!(
+        "  {} (Enter to send, Esc to cancel)\r\n  > ",
+        palette().bold(title)
+    );
+    let _ = std::io::stderr().flush();
+
+    let mut line = String::new();
+    let result = loop {
+        if shutdown.load(Ordering::SeqCst) {
+            break None;
+        }
+        let key = match keys.next_timeout(Duration::from_millis(100)) {
+            Ok(key) => key,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => {
+                // No keyboard thread: fall back to line-based input (no Esc support)
+                let mut input = String::new();
+                let _ = std::io::stdin().read_line(&mut input);
+                let line = input.trim();
+                return (!line.is_empty()).then(|| line.to_string());
+            }
+        };
+        match key.code {
+            KeyCode::Enter => break Some(line),
+            KeyCode::Esc => break None,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break None,
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                suspend::request();
+                break None;
+            }
+            KeyCode::Backspace if line.pop().is_some() => eprint!("\x08 \x08"),
+            KeyCode::Char(c) => {
+                line.push(c);
+                eprint!("{c}");
+            }
+            _ => {}
+        }
+        let _ = std::io::stderr().flush();
+    };
+
+    eprintln!();
+
+    match result {
+        Some(line) if !line.trim().is_empty() => Some(line.trim().to_string()),
+        Some(_) => None,
+        None => {
+            info!("[client] Text input cancelled");
+            None
+        }
+    }
+}
+
+/// Chunk size for replay playback (matches typical TTS chunk size).
+const REPLAY_CHUNK_SIZE: usize = 4000;
+
+/// Playback speed for the slow replay key ('5').
+const SLOW_REPLAY_SPEED: f32 = 0.75;
+
+/// Number of past feedback blocks shown by the 'h' key.
+const FEEDBACK_RECALL_COUNT: usize = 10;
+
+/// Playback volume change per '+'/'-' key press, in percent.
+const VOLUME_STEP: u32 = 10;
+
+/// Replay the last TTS response audio through the playback queue.
+///
+/// `speed` below 1.0 time-stretches the audio (pitch preserved). Stops early when
+/// `cancel` is set. Waits while the playback queue is above its high-water mark.
 fn replay_last_audio(
-    audio: &Arc<std::sync::Mutex<Vec<i16>>>,
-    playback_tx: &crossbeam_channel::Sender<Vec<i16>>,
+    audio: &Arc<std::sync::Mutex<ReplayBuffer>>,
+    playback: &PlaybackQueue,
+    speed: f32,
+    sample_rate: u32,
+    cancel: &AtomicBool,
 ) {
     let samples = if let Ok(buf) = audio.lock() {
-        buf.clone()
+        buf.to_vec()
     } else {
         return;
     };
     if samples.is_empty() {
         return;
     }
-    info!("[REPLAY]");
+    let samples = if speed == 1.0 {
+        info!("[REPLAY]");
+        samples
+    } else {
+        info!("[REPLAY] {speed}x");
+        audio::time_stretch(&samples, speed, sample_rate)
+    };
     for chunk in samples.chunks(REPLAY_CHUNK_SIZE) {
-        if playback_tx.send(chunk.to_vec()).is_err() {
+        if cancel.load(Ordering::SeqCst) || playback.push(chunk.to_vec(), cancel) != Push::Queued {
             break;
         }
     }
 }
 
-/// Read a single keypress for summary choice (y/n).
-fn read_summary_choice(shutdown: &Arc<AtomicBool>) -> bool {
-    use crossterm::event::{self, Event, KeyCode, KeyEvent};
-    use crossterm::terminal;
+/// Run `replay_last_audio` on a background thread so the main loop keeps polling
+/// keys (and can cancel it). `active` stays set until the queued audio has drained.
+fn spawn_replay(
+    audio: &Arc<std::sync::Mutex<ReplayBuffer>>,
+    playback: &Arc<PlaybackQueue>,
+    speed: f32,
+    sample_rate: u32,
+    active: &Arc<AtomicBool>,
+    cancel: &Arc<AtomicBool>,
+) {
+    active.store(true, Ordering::SeqCst);
+    cancel.store(false, Ordering::SeqCst);
+    let (audio, playback) = (audio.clone(), playback.clone());
+    let (active_thread, cancel) = (active.clone(), cancel.clone());
 
-    if terminal::enable_raw_mode().is_err() {
-        return false;
+    let spawned = std::thread::Builder::new()
+        .name("replay".into())
+        .spawn(move || {
+            replay_last_audio(&audio, &playback, speed, sample_rate, &cancel);
+            while !playback.is_empty() && !cancel.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            active_thread.store(false, Ordering::SeqCst);
+        });
+    if let Err(e) = spawned {
+        warn!("[client] Failed to start replay: {e}");
+        active.store(false, Ordering::SeqCst);
     }
+}
 
+/// Read a single keypress for summary choice (y/n).
+fn read_summary_choice(keys: &keyboard::Keys, shutdown: &Arc<AtomicBool>) -> bool {
+    let _prompt = keys.prompt();
     let result = loop {
         if shutdown.load(Ordering::SeqCst) {
             break false;
         }
-        if event::poll(Duration::from_millis(500)).unwrap_or(false)
-            && let Ok(Event::Key(KeyEvent { code, .. })) = event::read()
-        {
-            break match code {
-                KeyCode::Char('y') | KeyCode::Char('Y') => true,
-                KeyCode::Char('n') | KeyCode::Char('N') => false,
-                _ => continue,
-            };
+        match keys.next_timeout(Duration::from_millis(500)) {
+            Ok(key) => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => break true,
+                KeyCode::Char('n') | KeyCode::Char('N') => break false,
+                _ => {}
+            },
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break false,
         }
     };
+    eprintln!();
+    result
+}
 
-    let _ = terminal::disable_raw_mode();
+/// How often the summary wait checks for Esc and for the summary.
+const SUMMARY_POLL: Duration = Duration::from_millis(100);
+
+/// What to do after 'q'.
+#[derive(Debug, PartialEq)]
+enum QuitOutcome {
+    Exit,
+    /// The summary was cancelled and the user went back to the session.
+    Resume,
+}
+
+/// Offer a session summary on quit, wait for it (Esc cancels) and save it.
+///
+/// Cancelling asks whether to exit anyway or go back to the session. The
+/// orchestrator still finishes the cancelled summary; it is counted in
+/// `abandoned` so it is discarded when it arrives.
+fn offer_summary(
+    keys: &keyboard::Keys,
+    shutdown: &Arc<AtomicBool>,
+    writer: &mut impl Write,
+    summary_rx: &crossbeam_channel::Receiver<String>,
+    abandoned: &mut u32,
+    audio_input: &AudioInputInfo,
+    feedback_history: &std::sync::Mutex<FeedbackHistory>,
+) -> QuitOutcome {
+    if shutdown.load(Ordering::SeqCst) {
+        return QuitOutcome::Exit;
+    }
+    eprintln!();
+    eprintln!("  {}", palette().bold("Generate session summary? [y/n]"));
+    eprint!("  > ");
+    let _ = std::io::stderr().flush();
+    if !read_summary_choice(keys, shutdown) {
+        return QuitOutcome::Exit;
+    }
+
+    info!("Generating summary... (Esc to cancel)");
+    if let Err(e) = write_client_msg(writer, &ClientMsg::SummaryRequest) {
+        warn!("[client] Failed to send SummaryRequest: {e}");
+        return QuitOutcome::Exit;
+    }
+    match wait_for_summary(summary_rx, keys, shutdown, abandoned) {
+        SummaryWait::Received(mut summary) => {
+            summary.insert_str(0, &audio_input_header(audio_input));
+            if let Ok(history) = feedback_history.lock()
+                && !history.is_empty()
+            {
+                summary.push_str(&feedback_history_markdown(&history));
+            }
+            match save_summary(&summary) {
+                Ok(path) => info!("Session summary saved to: {}", path.display()),
+                Err(e) => warn!("[client] Failed to save summary: {e}"),
+            }
+            QuitOutcome::Exit
+        }
+        SummaryWait::Closed => {
+            warn!("[client] Summary channel closed before receiving response");
+            QuitOutcome::Exit
+        }
+        SummaryWait::Cancelled => {
+            *abandoned += 1;
+            info!("[client] Summary cancelled");
+            if read_resume_choice(keys, shutdown) {
+                QuitOutcome::Resume
+            } else {
+                QuitOutcome::Exit
+            }
+        }
+    }
+}
+
+/// Result of [`wait_for_summary`].
+#[derive(Debug, PartialEq)]
+enum SummaryWait {
+    Received(String),
+    /// Esc was pressed.
+    Cancelled,
+    /// The reader stopped (session ended, or shutdown) before the summary came.
+    Closed,
+}
+
+/// Wait for the requested summary, skipping `abandoned` earlier ones that
+/// arrive first. Esc gives up on it.
+fn wait_for_summary(
+    summary_rx: &crossbeam_channel::Receiver<String>,
+    keys: &keyboard::Keys,
+    shutdown: &Arc<AtomicBool>,
+    abandoned: &mut u32,
+) -> SummaryWait {
+    let _prompt = keys.prompt();
+    loop {
+        if shutdown.load(Ordering::SeqCst) {
+            return SummaryWait::Closed;
+        }
+        match summary_rx.try_recv() {
+            Ok(_) if *abandoned > 0 => {
+                *abandoned -= 1;
+                debug!("[client] Discarding a cancelled summary");
+                continue;
+            }
+            Ok(summary) => return SummaryWait::Received(summary),
+            Err(crossbeam_channel::TryRecvError::Disconnected) => return SummaryWait::Closed,
+            Err(crossbeam_channel::TryRecvError::Empty) => {}
+        }
+        match keys.next_timeout(SUMMARY_POLL) {
+            Ok(key) if key.code == KeyCode::Esc => return SummaryWait::Cancelled,
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+            // No keyboard: nothing can cancel, keep waiting
+            Err(RecvTimeoutError::Disconnected) => std::thread::sleep(SUMMARY_POLL),
+        }
+    }
+}
+
+/// Drop cancelled summaries that arrived while the session went on; left in
+/// the channel they would block the reader thread.
+fn discard_abandoned_summaries(
+    summary_rx: &crossbeam_channel::Receiver<String>,
+    abandoned: &mut u32,
+) {
+    while *abandoned > 0 && summary_rx.try_recv().is_ok() {
+        *abandoned -= 1;
+        debug!("[client] Discarding a cancelled summary");
+    }
+}
+
+/// After cancelling the summary: exit anyway ('1') or go back to the session ('2').
+fn read_resume_choice(keys: &keyboard::Keys, shutdown: &Arc<AtomicBool>) -> bool {
+    eprintln!("  [1] Exit anyway");
+    eprintln!("  [2] Back to the session");
+    eprint!("  > ");
+    let _ = std::io::stderr().flush();
+    let _prompt = keys.prompt();
+    let result = loop {
+        if shutdown.load(Ordering::SeqCst) {
+            break false;
+        }
+        match keys.next_timeout(Duration::from_millis(500)) {
+            Ok(key) => match key.code {
+                KeyCode::Char('1') => break false,
+                KeyCode::Char('2') => break true,
+                _ => {}
+            },
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break false,
+        }
+    };
     eprintln!();
     result
 }
@@ -489,8 +1902,8 @@ fn save_summary(content: &str) -> Result<std::path::PathBuf> {
     Ok(path)
 }
 
-/// Format current local time as YYYY-MM-DD_HH-MM.
-fn format_timestamp() -> String {
+/// Current local time broken down by the C library.
+fn local_time() -> libc::tm {
     let secs = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -498,6 +1911,18 @@ fn format_timestamp() -> String {
 
     let mut tm: libc::tm = unsafe { std::mem::zeroed() };
     unsafe { libc::localtime_r(&secs, &mut tm) };
+    tm
+}
+
+/// Format current local time as HH:MM:SS.
+fn format_clock_time() -> String {
+    let tm = local_time();
+    format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec)
+}
+
+/// Format current local time as YYYY-MM-DD_HH-MM.
+fn format_timestamp() -> String {
+    let tm = local_time();
     format!(
         "{:04}-{:02}-{:02}_{:02}-{:02}",
         tm.tm_year + 1900,
@@ -508,48 +1933,105 @@ fn format_timestamp() -> String {
     )
 }
 
+/// How long to wait for the word number after 'p'.
+const WORD_KEY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long the rewind picker ('b') waits for a choice.
+const REWIND_KEY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long the voice picker ('v') waits for the server's list.
+const VOICE_LIST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Voices shown per line by the voice picker.
+const VOICES_PER_LINE: usize = 6;
+
+/// After 'p', wait briefly for the number (1-9) of the word to pronounce.
+fn read_word_number(keys: &keyboard::Keys) -> Option<usize> {
+    read_number_key(keys, WORD_KEY_TIMEOUT)
+}
+
+/// Wait up to `timeout` for a number key (1-9); any other key gives `None`.
+fn read_number_key(keys: &keyboard::Keys, timeout: Duration) -> Option<usize> {
+    match keys.next_timeout(timeout).ok()?.code {
+        KeyCode::Char(c @ '1'..='9') => c.to_digit(10).map(|d| d as usize),
+        _ => None,
+    }
+}
+
+/// Without key prompts, how long feedback stays up before the session continues.
+const DEFAULT_FEEDBACK_DELAY_SECS: u64 = 5;
+/// How often that wait checks the shutdown flag.
+const AUTO_CONTINUE_POLL: Duration = Duration::from_millis(100);
+
 /// Feedback choice result.
+#[derive(Debug, PartialEq)]
 enum FeedbackAction {
     Continue,
     Retry,
     Replay,
+    /// Hear suggested word `n` (1-based).
+    Pronounce(usize),
+    /// Type the corrected sentence into the focused window.
+    TypeCorrection,
 }
 
 /// Read a single keypress for feedback choice (no Enter needed).
-/// Returns Continue ('1'), Retry ('2'), or Replay ('3').
-fn read_feedback_choice(shutdown: &Arc<AtomicBool>) -> FeedbackAction {
-    use crossterm::event::{self, Event, KeyCode, KeyEvent};
-    use crossterm::terminal;
-
-    if terminal::enable_raw_mode().is_err() {
-        // Fallback to line-based input if raw mode fails
-        let mut input = String::new();
-        let _ = std::io::stdin().read_line(&mut input);
-        return match input.trim() {
-            "2" => FeedbackAction::Retry,
-            "3" => FeedbackAction::Replay,
-            _ => FeedbackAction::Continue,
-        };
+/// Returns Continue ('1'), Retry ('2'), Replay ('3'), Pronounce ('p' + number)
+/// or TypeCorrection ('i').
+fn read_feedback_choice(keys: &keyboard::Keys, shutdown: &Arc<AtomicBool>) -> FeedbackAction {
+    if let Some(delay) = keys.auto_continue() {
+        // Headless: nobody answers, the feedback just stays up for a while
+        let deadline = Instant::now() + delay;
+        while !shutdown.load(Ordering::SeqCst) {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            std::thread::sleep(left.min(AUTO_CONTINUE_POLL));
+        }
+        return FeedbackAction::Continue;
     }
-
+    let _prompt = keys.prompt();
     let result = loop {
         if shutdown.load(Ordering::SeqCst) {
             break FeedbackAction::Continue;
         }
-        // Poll with timeout so we can check shutdown
-        if event::poll(Duration::from_millis(500)).unwrap_or(false)
-            && let Ok(Event::Key(KeyEvent { code, .. })) = event::read()
-        {
-            break match code {
-                KeyCode::Char('1') => FeedbackAction::Continue,
-                KeyCode::Char('2') => FeedbackAction::Retry,
-                KeyCode::Char('3') => FeedbackAction::Replay,
-                _ => continue, // ignore other keys
-            };
-        }
+        let key = match keys.next_timeout(Duration::from_millis(500)) {
+            Ok(key) => key,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => {
+                // No keyboard thread: fall back to line-based input
+                let mut input = String::new();
+                let _ = std::io::stdin().read_line(&mut input);
+                return match input.trim() {
+                    "2" => FeedbackAction::Retry,
+                    "3" => FeedbackAction::Replay,
+                    "i" => FeedbackAction::TypeCorrection,
+                    s => match s.strip_prefix('p').and_then(|n| n.parse().ok()) {
+                        Some(n) => FeedbackAction::Pronounce(n),
+                        None => FeedbackAction::Continue,
+                    },
+                };
+            }
+        };
+        break match key.code {
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                // The main loop suspends and restores raw input around the stop
+                suspend::request();
+                continue;
+            }
+            KeyCode::Char('1') => FeedbackAction::Continue,
+            KeyCode::Char('2') => FeedbackAction::Retry,
+            KeyCode::Char('3') => FeedbackAction::Replay,
+            KeyCode::Char('i') => FeedbackAction::TypeCorrection,
+            KeyCode::Char('p') => match read_word_number(keys) {
+                Some(n) => FeedbackAction::Pronounce(n),
+                None => continue,
+            },
+            _ => continue, // ignore other keys
+        };
     };
 
-    let _ = terminal::disable_raw_mode();
     eprintln!(); // newline after the ">" prompt
     result
 }
@@ -636,22 +2118,144 @@ fn parse_corrected_parts(text: &str) -> Vec<(bool, &str)> {
     parts
 }
 
-/// Display a corrected sentence line with green-highlighted corrected parts.
-fn display_corrected_line(text: &str) {
+/// Level (dBFS) under which the microphone is considered silent.
+const MIC_LOW_DBFS: f32 = -55.0;
+/// How long the level must stay low before the meter turns into a warning.
+const MIC_LOW_WARN_AFTER: Duration = Duration::from_secs(2);
+/// Minimum time between two meter redraws.
+const MIC_METER_REFRESH: Duration = Duration::from_millis(80);
+/// Bottom of the meter scale in dBFS; the top is 0 dBFS.
+const MIC_METER_FLOOR_DBFS: f32 = -60.0;
+const MIC_METER_WIDTH: usize = 20;
+/// Width of the speech bar next to the level (Auto mode).
+const SPEECH_METER_WIDTH: usize = 5;
+
+/// Microphone level bar, drawn on the status line while listening.
+///
+/// In the session view, the level goes to its status bar instead.
+struct MicMeter {
+    last_draw: Option<Instant>,
+    quiet_since: Option<Instant>,
+    /// The VAD's latest speech probability (Auto mode only).
+    speech: Option<f32>,
+    output: SessionOutput,
+}
+
+impl MicMeter {
+    fn new(output: SessionOutput) -> Self {
+        Self {
+            last_draw: None,
+            quiet_since: None,
+            speech: None,
+            output,
+        }
+    }
+
+    /// Show how sure the VAD is that it hears speech (drawn on the next update).
+    fn set_speech_probability(&mut self, probability: f32) {
+        self.speech = Some(probability);
+    }
+
+    /// Feed a resampled chunk and redraw the bar if the refresh interval passed.
+    fn update(&mut self, samples: &[i16]) {
+        let now = Instant::now();
+        let rms_db = audio::to_dbfs(audio::level_meter(samples));
+        if rms_db < MIC_LOW_DBFS {
+            self.quiet_since.get_or_insert(now);
+        } else {
+            self.quiet_since = None;
+        }
+        if self
+            .last_draw
+            .is_some_and(|t| now.duration_since(t) < MIC_METER_REFRESH)
+        {
+            return;
+        }
+        if self.output.is_view() {
+            self.output.send(SessionEvent::MicLevel(rms_db));
+            self.last_draw = Some(now);
+            return;
+        }
+        if !status_line::enabled() {
+            return;
+        }
+        let too_quiet = self
+            .quiet_since
+            .is_some_and(|t| now.duration_since(t) >= MIC_LOW_WARN_AFTER);
+        let peak_db = audio::to_dbfs(audio::peak_level(samples));
+        status_line::draw(&format_mic_meter(rms_db, peak_db, too_quiet, self.speech));
+        self.last_draw = Some(now);
+    }
+
+    /// Erase the bar so a regular log line can be printed.
+    fn clear(&mut self) {
+        if self.last_draw.take().is_some() {
+            status_line::clear();
+        }
+    }
+
+    /// Erase the bar and forget the silence timer and speech probability
+    /// (listening state changed).
+    fn reset(&mut self) {
+        self.clear();
+        self.quiet_since = None;
+        self.speech = None;
+    }
+}
+
+/// Render the meter line: a dim bar for the RMS level, followed by the speech
+/// bar when the VAD runs, or a red warning when the microphone has been silent
+/// for too long.
+fn format_mic_meter(rms_db: f32, peak_db: f32, too_quiet: bool, speech: Option<f32>) -> String {
+    if too_quiet {
+        return format!(
+            "  {}",
+            palette().alert(&format!(
+                "mic silent ({rms_db:.0} dB) \u{2014} check that it is unmuted"
+            ))
+        );
+    }
+    let fraction = (rms_db - MIC_METER_FLOOR_DBFS) / -MIC_METER_FLOOR_DBFS;
+    let mut line = format!(
+        "mic [{}] {rms_db:.0} dB (peak {peak_db:.0})",
+        meter_bar(fraction, MIC_METER_WIDTH)
+    );
+    if let Some(speech) = speech {
+        line.push_str(&format!(
+            "  voice [{}]",
+            meter_bar(speech, SPEECH_METER_WIDTH)
+        ));
+    }
+    format!("  {}", palette().dim(&line))
+}
+
+/// A bar `width` cells wide, filled to `fraction` (clamped to 0.0-1.0).
+fn meter_bar(fraction: f32, width: usize) -> String {
+    let filled = ((fraction * width as f32).round().max(0.0) as usize).min(width);
+    format!(
+        "{}{}",
+        "\u{2588}".repeat(filled),
+        "\u{00b7}".repeat(width - filled)
+    )
+}
+
+/// A corrected sentence line with green-highlighted corrected parts.
+fn corrected_line(text: &str) -> Option<String> {
     let trimmed = text.trim();
     let parts = parse_corrected_parts(trimmed);
     if parts.is_empty() {
-        return;
+        return None;
     }
-    eprint!("  \x1b[32m\u{2713}\x1b[0m ");
+    let palette = palette();
+    let mut line = format!("  {} ", palette.ok_mark());
     for (is_corrected, segment) in &parts {
         if *is_corrected {
-            eprint!("\x1b[32m{segment}\x1b[0m");
+            line.push_str(&palette.highlight(segment));
         } else {
-            eprint!("{segment}");
+            line.push_str(segment);
         }
     }
-    eprintln!("\x1b[0m");
+    Some(line)
 }
 
 /// Display language feedback with ANSI colors.
@@ -659,69 +2263,263 @@ fn display_corrected_line(text: &str) {
 /// Lines prefixed with `RED:` are shown in red with a cross mark.
 /// Lines prefixed with `BLUE:` are shown in blue with an arrow.
 /// Other color prefixes Claude might invent are mapped to red or blue.
+///
+/// Suggested replacement words get a dim `[n]` tag; 'p' + n pronounces them.
 fn display_feedback(text: &str) {
+    for line in feedback_block(text) {
+        eprintln!("{line}");
+    }
+}
+
+/// Content of the last `CORRECTED:` line of a feedback block, markers included.
+fn last_corrected(text: &str) -> Option<&str> {
+    text.lines().map(str::trim).rev().find_map(|line| {
+        (line.len() >= 10 && line[..10].eq_ignore_ascii_case("CORRECTED:"))
+            .then(|| line[10..].trim())
+    })
+}
+
+/// The corrected sentence of a feedback block as plain text (`<<`/`>>`
+/// markers stripped), or `None` when there is none.
+fn corrected_sentence(text: &str) -> Option<String> {
+    let sentence: String = parse_corrected_parts(last_corrected(text)?)
+        .into_iter()
+        .map(|(_, part)| part)
+        .collect();
+    let sentence = sentence.trim();
+    (!sentence.is_empty()).then(|| sentence.to_string())
+}
+
+/// Time to switch to the target window before the correction is typed ('i').
+const TYPE_CORRECTION_DELAY: Duration = Duration::from_secs(3);
+
+/// Type `sentence` into the focused window after [`TYPE_CORRECTION_DELAY`].
+/// Without uinput (or dotool), print it to stdout for copying instead.
+fn type_correction(sentence: &str) {
+    let mut injector = match inject::Injector::new(&inject::detect_xkb_layout()) {
+        Ok(injector) => injector,
+        Err(e) => {
+            warn!("[client] Cannot type the correction: {e}");
+            println!("{sentence}");
+            return;
+        }
+    };
+    info!(
+        "[client] Typing the correction in {}s: focus the target window",
+        TYPE_CORRECTION_DELAY.as_secs()
+    );
+    std::thread::sleep(TYPE_CORRECTION_DELAY);
+    let typed =
+        inject::TextInjector::type_text(&mut injector, sentence).and_then(|()| injector.finish());
+    if let Err(e) = typed {
+        warn!("[client] Failed to type the correction: {e}");
+        println!("{sentence}");
+    }
+}
+
+/// The lines [`display_feedback`] prints, ANSI colors included (the session
+/// view shows them with the same colors).
+fn feedback_block(text: &str) -> Vec<String> {
     // Extract the LAST CORRECTED: line before the main loop
     // (must happen before classify_feedback_line to avoid ALLCAPS catch-all)
-    let mut corrected_content: Option<&str> = None;
-    for line in text.lines() {
-        let trimmed = line.trim();
-        if trimmed.len() >= 10 && trimmed[..10].eq_ignore_ascii_case("CORRECTED:") {
-            corrected_content = Some(trimmed[10..].trim());
-        }
+    let corrected_content = last_corrected(text);
+
+    let palette = palette();
+    let mut lines = vec![palette.dim("--- feedback ---")];
+    let mut numbers = WordNumbers::default();
+    for (severity, content) in feedback_lines(text) {
+        let content = numbers.tag_line(content);
+        lines.push(match severity {
+            "red" => format!("  {}", palette.error(&content)),
+            _ => format!("  {}", palette.hint(&content)),
+        });
     }
 
-    eprintln!("\x1b[2m--- feedback ---\x1b[0m");
-    for line in text.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
+    // Display corrected sentence last (green ✓)
+    if let Some(line) = corrected_content.and_then(corrected_line) {
+        lines.push(line);
+    }
+
+    lines.push(palette.dim("----------------"));
+    lines
+}
+
+/// The feedback lines shown as red/blue items, with their prefix stripped
+/// (blank and CORRECTED: lines are skipped).
+fn feedback_lines(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    text.lines().map(str::trim).filter_map(|line| {
+        if line.is_empty() || (line.len() >= 10 && line[..10].eq_ignore_ascii_case("CORRECTED:")) {
+            return None;
         }
-        // Skip CORRECTED lines (already extracted)
-        if trimmed.len() >= 10 && trimmed[..10].eq_ignore_ascii_case("CORRECTED:") {
-            continue;
+        Some(classify_feedback_line(line).unwrap_or(("blue", line)))
+    })
+}
+
+/// The suggested words of a feedback block, numbered as `display_feedback` tags them.
+fn feedback_words(text: &str) -> Vec<String> {
+    let mut numbers = WordNumbers::default();
+    for (_, content) in feedback_lines(text) {
+        numbers.tag_line(content);
+    }
+    numbers.into_words()
+}
+
+/// Print the last `count` feedback blocks, each under the sentence it applied to.
+fn display_feedback_history(history: &FeedbackHistory, count: usize) {
+    if history.is_empty() {
+        info!("[client] No feedback yet this session");
+        return;
+    }
+    let palette = palette();
+    eprintln!(
+        "{} {}",
+        palette.bold("Feedback history"),
+        palette.dim(&format!(
+            "(last {} of {})",
+            count.min(history.len()),
+            history.len()
+        ))
+    );
+    for entry in history.recent(count) {
+        let time = palette.dim(&format!("[{}]", entry.time));
+        match &entry.sentence {
+            Some(sentence) => eprintln!("{time} You: {sentence}"),
+            None => eprintln!("{time}"),
         }
-        let (severity, content) = classify_feedback_line(trimmed).unwrap_or(("blue", trimmed));
-        match severity {
-            "red" => eprintln!("  \x1b[31m\u{2717} {content}\x1b[0m"),
-            _ => eprintln!("  \x1b[34m\u{279c} {content}\x1b[0m"),
+        display_feedback(&entry.feedback);
+    }
+}
+
+/// List the turns the rewind picker offers, numbered from the most recent.
+fn display_rewind_choices(choices: &[RewindChoice]) {
+    eprintln!(
+        "{} {}",
+        palette().bold("Rewind the conversation"),
+        palette().dim(&format!("(1-{}, any other key cancels)", choices.len()))
+    );
+    for (i, choice) in choices.iter().enumerate() {
+        match choice.sentence {
+            Some(sentence) => eprintln!("  [{}] back to turn {}: {sentence}", i + 1, choice.turn),
+            None => eprintln!("  [{}] back to the start", i + 1),
         }
     }
+}
+
+/// List the voices the picker offers, numbered from 1.
+fn display_voices(voices: &[String]) {
+    eprintln!(
+        "{} {}",
+        palette().bold("Voices"),
+        palette().dim("(name or number)")
+    );
+    let numbered: Vec<String> = voices
+        .iter()
+        .enumerate()
+        .map(|(i, voice)| format!("[{}] {voice:<14}", i + 1))
+        .collect();
+    for line in numbered.chunks(VOICES_PER_LINE) {
+        eprintln!("  {}", line.concat().trim_end());
+    }
+}
 
-    // Display corrected sentence last (green ✓)
-    if let Some(content) = corrected_content {
-        display_corrected_line(content);
+/// The voice picked by `typed`: its number in `voices`, or a name as typed
+/// (checked by the server).
+fn voice_choice(voices: &[String], typed: &str) -> String {
+    typed
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| voices.get(n.checked_sub(1)?))
+        .cloned()
+        .unwrap_or_else(|| typed.to_string())
+}
+
+/// Show the translation of the last reply, under its own separator like feedback.
+fn display_translation(text: &str) {
+    eprintln!("{}", palette().dim("--- translation ---"));
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        eprintln!("  {}", palette().accent(line.trim()));
     }
+}
 
-    eprintln!("\x1b[2m----------------\x1b[0m");
+/// Header line of the summary file recording the capture setup.
+fn audio_input_header(info: &AudioInputInfo) -> String {
+    format!(
+        "> Audio input: {} ({} Hz, {} ch; resampler: {})\n\n",
+        info.device, info.sample_rate, info.channels, info.resampler
+    )
+}
+
+/// Render the whole feedback history as a markdown section for the summary file.
+fn feedback_history_markdown(history: &FeedbackHistory) -> String {
+    let mut out = String::from("\n\n## Feedback history\n");
+    for entry in history.iter() {
+        match &entry.sentence {
+            Some(sentence) => {
+                out.push_str(&format!("\n### {} \u{2014} {sentence}\n\n", entry.time))
+            }
+            None => out.push_str(&format!("\n### {}\n\n", entry.time)),
+        }
+        for line in entry
+            .feedback
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+        {
+            if line.len() >= 10 && line[..10].eq_ignore_ascii_case("CORRECTED:") {
+                let corrected: String = parse_corrected_parts(line[10..].trim())
+                    .into_iter()
+                    .map(|(is_corrected, part)| {
+                        if is_corrected {
+                            format!("**{part}**")
+                        } else {
+                            part.to_string()
+                        }
+                    })
+                    .collect();
+                out.push_str(&format!("- \u{2713} {corrected}\n"));
+                continue;
+            }
+            match classify_feedback_line(line).unwrap_or(("blue", line)) {
+                ("red", content) => out.push_str(&format!("- \u{2717} {content}\n")),
+                (_, content) => out.push_str(&format!("- \u{279c} {content}\n")),
+            }
+        }
+    }
+    out
 }
 
 /// TCP reader loop: reads ServerMsg from TCP, routes TtsAudioChunk to playback.
 #[allow(clippy::too_many_arguments)]
 fn tcp_reader_loop(
-    mut reader: BufReader<TcpStream>,
-    mut feedback_writer: BufWriter<TcpStream>,
-    playback_tx: crossbeam_channel::Sender<Vec<i16>>,
-    output_rate: u32,
+    mut reader: BufReader<Transport>,
+    mut feedback_writer: BufWriter<Transport>,
+    playback: Arc<PlaybackQueue>,
+    feeder: Feeder,
+    outbox: Arc<outbox::Outbox>,
+    output_rate: Arc<AtomicU32>,
     shutdown: Arc<AtomicBool>,
     is_playing: Arc<AtomicBool>,
     summary_tx: crossbeam_channel::Sender<String>,
-    last_tts_audio: Arc<std::sync::Mutex<Vec<i16>>>,
+    voices_tx: crossbeam_channel::Sender<Vec<String>>,
+    last_tts_audio: Arc<std::sync::Mutex<ReplayBuffer>>,
+    replay_buffer_secs: u32,
+    wait_indicator: status_line::WaitIndicator,
+    feedback_history: Arc<std::sync::Mutex<FeedbackHistory>>,
+    turn_log: Arc<std::sync::Mutex<TurnLog>>,
+    word_audio: Arc<AtomicBool>,
+    keys: keyboard::Keys,
+    output: SessionOutput,
 ) {
-    // Create resampler if playback device isn't 16kHz
-    let mut resample: Option<audio::ResamplerFn> = if output_rate != 16000 {
-        match audio::create_resampler(16000, output_rate, 1) {
-            Ok(r) => {
-                debug!("[client] TTS resampling: 16kHz → {output_rate}Hz");
-                Some(r)
-            }
-            Err(e) => {
-                warn!("[client] Failed to create playback resampler: {e}");
-                None
-            }
-        }
-    } else {
-        None
-    };
+    // The buffer holds resampled output, so its size depends on the device rate
+    let mut current_rate = output_rate.load(Ordering::SeqCst);
+    size_replay_buffer(&last_tts_audio, replay_buffer_secs, current_rate);
+    let mut resample = playback_resampler(current_rate);
+
+    // Sentence the next feedback block applies to
+    let mut last_sentence: Option<String> = None;
+    // What the session view was last told
+    let mut thinking = false;
+    let mut speaking = false;
 
     loop {
         if shutdown.load(Ordering::SeqCst) {
@@ -731,8 +2529,11 @@ fn tcp_reader_loop(
         let msg = match space_lt_common::protocol::read_server_msg(&mut reader) {
             Ok(msg) => msg,
             Err(e) => {
-                if is_disconnect(&e) {
-                    debug!("[client] Server disconnected");
+                if shutdown.load(Ordering::SeqCst) {
+                    debug!("[client] Connection closed");
+                } else if is_disconnect(&e) {
+                    // No SessionEnded first: the server or the network went away
+                    info!("[client] Lost connection to the server");
                 } else {
                     warn!("[client] Read error: {e}");
                 }
@@ -741,49 +2542,93 @@ fn tcp_reader_loop(
             }
         };
 
+        // The playback stream was rebuilt on a device with another rate
+        let rate = output_rate.load(Ordering::SeqCst);
+        if rate != current_rate {
+            current_rate = rate;
+            resample = playback_resampler(rate);
+            if let Ok(mut buf) = last_tts_audio.lock() {
+                buf.clear();
+            }
+            size_replay_buffer(&last_tts_audio, replay_buffer_secs, rate);
+        }
+
+        // Anything but a status update ends the wait (and prints over the spinner)
+        if !matches!(
+            msg,
+            ServerMsg::StatusNotification(_)
+                | ServerMsg::PartialTranscript(_)
+                | ServerMsg::SegmentReceived(_)
+        ) {
+            wait_indicator.stop();
+            if std::mem::take(&mut thinking) {
+                output.send(SessionEvent::Thinking(None));
+            }
+        }
+        let now_speaking = match msg {
+            ServerMsg::TtsAudioChunk(_) => !word_audio.load(Ordering::SeqCst),
+            ServerMsg::TtsEnd | ServerMsg::Error(_) => false,
+            _ => speaking,
+        };
+        if now_speaking != speaking {
+            speaking = now_speaking;
+            output.send(SessionEvent::Speaking(speaking));
+        }
+
         match msg {
             ServerMsg::TtsAudioChunk(samples) => {
-                debug!("[client] TtsAudioChunk: {} samples", samples.len());
+                debug!(
+                    "[client] TtsAudioChunk: {} samples ({}ms queued)",
+                    samples.len(),
+                    playback.buffered_ms()
+                );
                 is_playing.store(true, Ordering::SeqCst);
                 let output = match &mut resample {
-                    Some(r) => r(&samples),
+                    Some(r) => profile::time("resample_playback", || r(&samples)),
                     None => samples,
                 };
-                // Accumulate for replay (capped to prevent unbounded growth)
-                if let Ok(mut buf) = last_tts_audio.lock()
-                    && buf.len() + output.len() <= REPLAY_BUFFER_MAX_SAMPLES
+                // Accumulate for replay (oldest samples evicted past the cap),
+                // unless this is a pronounced word
+                if !word_audio.load(Ordering::SeqCst)
+                    && let Ok(mut buf) = last_tts_audio.lock()
                 {
-                    buf.extend_from_slice(&output);
-                }
-                if playback_tx.send(output).is_err() {
-                    debug!("[client] Playback channel closed");
-                    break;
+                    buf.push(&output);
                 }
+                // Parked while too much audio is queued, so the messages behind it
+                // are still read; a barge-in clear drops it
+                feeder.push(output);
             }
             ServerMsg::TtsEnd => {
                 debug!("[client] TtsEnd received");
-                // Flush resampler carry-over buffer (sends remaining samples)
+                let was_word = word_audio.swap(false, Ordering::SeqCst);
+                // Flush resampler carry-over buffer: joins the last chunk, or is
+                // dropped if it would only play as a click after the queue drained
                 if let Some(r) = &mut resample {
                     let tail = r(&[]);
-                    if !tail.is_empty() {
-                        if let Ok(mut buf) = last_tts_audio.lock()
-                            && buf.len() + tail.len() <= REPLAY_BUFFER_MAX_SAMPLES
-                        {
-                            buf.extend_from_slice(&tail);
-                        }
-                        let _ = playback_tx.send(tail);
+                    // A replay plays it right after the last chunk, never as a click
+                    if !was_word && let Ok(mut buf) = last_tts_audio.lock() {
+                        buf.push(&tail);
                     }
+                    feeder.push_tail(tail);
                 }
                 is_playing.store(false, Ordering::SeqCst);
+                if was_word {
+                    continue;
+                }
                 let has_audio = last_tts_audio
                     .lock()
                     .map(|buf| !buf.is_empty())
                     .unwrap_or(false);
-                if has_audio {
-                    eprintln!("  \x1b[2m[3] Replay\x1b[0m");
+                if has_audio && keys.auto_continue().is_none() {
+                    eprintln!(
+                        "  {}",
+                        palette().dim(
+                            "[3] Replay  [5] Slow replay  [l] Translate  [x] Simpler  [d] Disregard"
+                        )
+                    );
                 }
             }
-            ServerMsg::Ready => {
+            ServerMsg::Ready | ServerMsg::ReadyActiveSession(_) => {
                 debug!("[client] Unexpected Ready (ignoring)");
             }
             ServerMsg::Text(text) => {
@@ -793,28 +2638,135 @@ fn tcp_reader_loop(
                 {
                     buf.clear();
                 }
-                info!("[client] {text}");
+                let echo = user_echo(&text);
+                let turn = if let Some((language, sentence)) = echo {
+                    let shown = match language {
+                        Some(language) => format!("[{language}] {sentence}"),
+                        None => sentence.to_string(),
+                    };
+                    Some(SessionEvent::User(shown))
+                } else {
+                    text.strip_prefix("AI:")
+                        .map(|reply| SessionEvent::Ai(reply.trim().to_string()))
+                };
+                match turn {
+                    Some(turn) if output.is_view() => output.send(turn),
+                    _ => info!("[client] {text}"),
+                }
+                // The transcription echo starts the wait for the reply
+                if let Some((_, sentence)) = echo {
+                    last_sentence = Some(sentence.to_string());
+                    if let Ok(mut log) = turn_log.lock() {
+                        log.user_said(sentence);
+                    }
+                    wait_indicator.start();
+                    thinking = true;
+                    output.send(SessionEvent::Thinking(Some(
+                        status_line::DEFAULT_WAIT_LABEL.to_string(),
+                    )));
+                } else if text.starts_with("AI:")
+                    && let Ok(mut log) = turn_log.lock()
+                {
+                    log.replied();
+                }
             }
             ServerMsg::Error(err) => {
-                warn!("[client] Server error: {err}");
+                eprintln!("{}", format_server_error(&err));
+                if err.starts_with(RETRYABLE_ERROR_PREFIX) {
+                    // Only this exchange failed: drop whatever it left behind and
+                    // go back to idle
+                    if let Some(r) = &mut resample {
+                        let _ = r(&[]);
+                    }
+                    if let Ok(mut buf) = last_tts_audio.lock() {
+                        buf.clear();
+                    }
+                    word_audio.store(false, Ordering::SeqCst);
+                    is_playing.store(false, Ordering::SeqCst);
+                    last_sentence = None;
+                }
             }
             ServerMsg::Feedback(text) => {
-                display_feedback(&text);
+                if output.is_view() {
+                    output.send(SessionEvent::Feedback(feedback_block(&text)));
+                } else {
+                    display_feedback(&text);
+                }
+                if let Ok(mut history) = feedback_history.lock() {
+                    history.push(FeedbackEntry {
+                        time: format_clock_time(),
+                        sentence: last_sentence.clone(),
+                        feedback: text.clone(),
+                    });
+                }
 
-                // Feedback choice loop (supports replay before deciding)
+                // Feedback choice loop (supports replay and word audio before deciding)
+                let words = feedback_words(&text);
+                let correction = corrected_sentence(&text);
                 let proceed = loop {
-                    eprintln!("  \x1b[1m[1] Continue  [2] Retry and re-speak  [3] Replay\x1b[0m");
-                    eprint!("  > ");
-                    let _ = std::io::stderr().flush();
+                    if let Some(delay) = keys.auto_continue() {
+                        let waiting = format!("Continuing in {}s", delay.as_secs());
+                        eprintln!("  {}", palette().dim(&waiting));
+                    } else {
+                        let mut choices =
+                            "[1] Continue  [2] Retry and re-speak  [3] Replay".to_string();
+                        if !words.is_empty() {
+                            choices.push_str(&format!("  [p1-{}] Hear a word", words.len()));
+                        }
+                        if correction.is_some() {
+                            choices.push_str("  [i] Type the correction");
+                        }
+                        eprintln!("  {}", palette().bold(&choices));
+                        eprint!("  > ");
+                        let _ = std::io::stderr().flush();
+                    }
 
-                    match read_feedback_choice(&shutdown) {
+                    match read_feedback_choice(&keys, &shutdown) {
                         FeedbackAction::Replay => {
-                            replay_last_audio(&last_tts_audio, &playback_tx);
+                            feeder.wait_idle(&shutdown);
+                            replay_last_audio(
+                                &last_tts_audio,
+                                &playback,
+                                1.0,
+                                current_rate,
+                                &shutdown,
+                            );
+                        }
+                        FeedbackAction::Pronounce(n) => {
+                            let Some(word) = words.get(n - 1) else {
+                                continue;
+                            };
+                            let played = write_client_msg(
+                                &mut feedback_writer,
+                                &ClientMsg::SpeakWord(word.clone()),
+                            )
+                            .and_then(|()| {
+                                play_word_inline(&mut reader, &feeder, &mut resample, &shutdown)
+                            });
+                            if let Err(e) = played {
+                                if is_disconnect(&e) {
+                                    debug!("[client] Server disconnected during word audio");
+                                    shutdown.store(true, Ordering::SeqCst);
+                                } else {
+                                    warn!("[client] Word audio failed: {e}");
+                                }
+                            }
+                            if shutdown.load(Ordering::SeqCst) {
+                                break true;
+                            }
+                        }
+                        FeedbackAction::TypeCorrection => {
+                            if let Some(sentence) = &correction {
+                                type_correction(sentence);
+                            }
                         }
                         FeedbackAction::Continue => break true,
                         FeedbackAction::Retry => break false,
                     }
                 };
+                if shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
 
                 if let Err(e) =
                     write_client_msg(&mut feedback_writer, &ClientMsg::FeedbackChoice(proceed))
@@ -834,12 +2786,151 @@ fn tcp_reader_loop(
                 }
             }
             ServerMsg::StatusNotification(text) => {
-                eprintln!("  \x1b[2;3m{text}\x1b[0m");
+                if output.is_view() && thinking {
+                    output.send(SessionEvent::Thinking(Some(text)));
+                } else if !wait_indicator.show_status(&text) {
+                    eprintln!("  {}", palette().dim_italic(&text));
+                }
+            }
+            ServerMsg::SegmentReceived(seq) => outbox.acknowledge(seq),
+            ServerMsg::PartialTranscript(text) => {
+                // Shown until the transcription echo replaces it
+                let shown = format!("\u{2026} {text}");
+                if output.is_view() {
+                    thinking = true;
+                    output.send(SessionEvent::Thinking(Some(shown)));
+                } else {
+                    eprintln!("  {}", palette().dim_italic(&shown));
+                }
             }
             ServerMsg::SessionSummary(text) => {
                 debug!("[client] SessionSummary: {} bytes", text.len());
                 let _ = summary_tx.send(text);
             }
+            ServerMsg::SessionEnded(reason) => {
+                info!("[client] Session ended: {reason}");
+                shutdown.store(true, Ordering::SeqCst);
+                break;
+            }
+            ServerMsg::TurnStats(stats) => {
+                eprintln!("  {}", palette().dim(&format_turn_stats(&stats)));
+            }
+            ServerMsg::Translation(text) => display_translation(&text),
+            ServerMsg::Warning(text) => eprintln!("  {}", palette().warning(&text)),
+            ServerMsg::VoiceList(voices) => {
+                // Nobody waits for a late answer: the picker gave up on it
+                let _ = voices_tx.try_send(voices);
+            }
+        }
+    }
+    wait_indicator.stop();
+}
+
+/// Size the replay buffer for `output_rate` (it holds resampled output).
+fn size_replay_buffer(
+    last_tts_audio: &std::sync::Mutex<ReplayBuffer>,
+    replay_buffer_secs: u32,
+    output_rate: u32,
+) {
+    if let Ok(mut buf) = last_tts_audio.lock() {
+        buf.set_max_samples(replay::samples_for(replay_buffer_secs, output_rate));
+        debug!(
+            "[client] Replay buffer: {replay_buffer_secs}s at {output_rate}Hz (up to {:.1} MB)",
+            (buf.max_samples() * std::mem::size_of::<i16>()) as f64 / 1_000_000.0
+        );
+    }
+}
+
+/// Resampler from the 16 kHz TTS audio to the playback rate (None at 16 kHz).
+/// Always high quality: this is what the user hears.
+fn playback_resampler(output_rate: u32) -> Option<audio::ResamplerFn> {
+    if output_rate == 16000 {
+        return None;
+    }
+    match audio::create_resampler(
+        16000,
+        output_rate,
+        1,
+        audio::CaptureChannel::Mix,
+        audio::ResampleQuality::High,
+    ) {
+        Ok(r) => {
+            debug!("[client] TTS resampling: 16kHz → {output_rate}Hz");
+            Some(r)
+        }
+        Err(e) => {
+            warn!("[client] Failed to create playback resampler: {e}");
+            None
+        }
+    }
+}
+
+/// The sentence of the server's "You: …" echo, and the language the server
+/// detected in it ("You [es]: …", with `--language auto`).
+fn user_echo(text: &str) -> Option<(Option<&str>, &str)> {
+    let rest = text.strip_prefix("You")?;
+    if let Some(sentence) = rest.strip_prefix(':') {
+        return Some((None, sentence.trim()));
+    }
+    let (language, sentence) = rest.strip_prefix(" [")?.split_once("]:")?;
+    Some((Some(language), sentence.trim()))
+}
+
+/// A server error as shown to the user (red, with the feedback cross mark), plus
+/// a hint when only the current exchange failed.
+fn format_server_error(err: &str) -> String {
+    match err.strip_prefix(RETRYABLE_ERROR_PREFIX) {
+        Some(msg) => format!(
+            "  {}\n  {}",
+            palette().error(msg),
+            palette().dim("Press your hotkey and try again.")
+        ),
+        None => format!("  {}", palette().error(&format!("Server error: {err}"))),
+    }
+}
+
+/// One-line latency breakdown, e.g. "stt 1.2s | llm 4.8s | tts 0.9s | first audio 6.9s".
+fn format_turn_stats(stats: &TurnStats) -> String {
+    let secs = |ms: u32| ms as f64 / 1000.0;
+    format!(
+        "stt {:.1}s | llm {:.1}s | tts {:.1}s | first audio {:.1}s",
+        secs(stats.stt_ms),
+        secs(stats.llm_ms),
+        secs(stats.tts_ms),
+        secs(stats.first_audio_ms)
+    )
+}
+
+/// Play a pronounced word requested from the feedback prompt. The reader thread
+/// is blocked in that prompt, so it consumes the word's chunks itself, up to
+/// TtsEnd; nothing goes to the replay buffer.
+fn play_word_inline(
+    reader: &mut BufReader<Transport>,
+    feeder: &Feeder,
+    resample: &mut Option<audio::ResamplerFn>,
+    shutdown: &AtomicBool,
+) -> Result<()> {
+    loop {
+        match space_lt_common::protocol::read_server_msg(reader)? {
+            ServerMsg::TtsAudioChunk(samples) => {
+                let output = match resample {
+                    Some(r) => r(&samples),
+                    None => samples,
+                };
+                feeder.push(output);
+            }
+            ServerMsg::TtsEnd => {
+                if let Some(r) = resample {
+                    feeder.push_tail(r(&[]));
+                }
+                return Ok(());
+            }
+            ServerMsg::SessionEnded(reason) => {
+                info!("[client] Session ended: {reason}");
+                shutdown.store(true, Ordering::SeqCst);
+                return Ok(());
+            }
+            other => debug!("[client] Ignoring {other:?} while a word plays"),
         }
     }
 }
@@ -848,6 +2939,494 @@ fn tcp_reader_loop(
 mod tests {
     use super::*;
 
+    /// Keys pre-loaded with `events`. Keep the sender alive: a closed channel
+    /// makes prompts fall back to reading stdin.
+    fn injected_keys(events: &[KeyEvent]) -> (keyboard::Keys, crossbeam_channel::Sender<KeyEvent>) {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        for event in events {
+            tx.send(*event).unwrap();
+        }
+        (keyboard::Keys::from_channel(rx), tx)
+    }
+
+    fn char_key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn messages_behind_a_full_playback_queue_are_still_read() {
+        use space_lt_common::protocol::write_server_msg;
+        use std::net::{TcpListener, TcpStream};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        // 100 ms at 16 kHz, and nothing playing it
+        let playback = Arc::new(PlaybackQueue::new(100));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (summary_tx, _summary_rx) = crossbeam_channel::bounded(1);
+        let (voices_tx, voices_rx) = crossbeam_channel::bounded(1);
+        let turn_log = Arc::new(std::sync::Mutex::new(TurnLog::default()));
+        let outbox = Arc::new(outbox::Outbox::default());
+        let sent = Instant::now();
+        outbox.number(vec![0; 8000], sent);
+        let reader = {
+            let playback = playback.clone();
+            let feeder = Feeder::spawn(playback.clone(), shutdown.clone()).unwrap();
+            let outbox = outbox.clone();
+            let shutdown = shutdown.clone();
+            let turn_log = turn_log.clone();
+            let transport = Transport::Plain(client);
+            let feedback_writer = BufWriter::new(transport.try_clone().unwrap());
+            std::thread::spawn(move || {
+                tcp_reader_loop(
+                    BufReader::new(transport),
+                    feedback_writer,
+                    playback,
+                    feeder,
+                    outbox,
+                    Arc::new(AtomicU32::new(16000)),
+                    shutdown.clone(),
+                    Arc::new(AtomicBool::new(false)),
+                    summary_tx,
+                    voices_tx,
+                    Arc::new(std::sync::Mutex::new(ReplayBuffer::new(16000))),
+                    1,
+                    status_line::WaitIndicator::spawn(shutdown),
+                    Arc::new(std::sync::Mutex::new(FeedbackHistory::new(10))),
+                    turn_log,
+                    Arc::new(AtomicBool::new(false)),
+                    keyboard::Keys::headless(Duration::from_secs(1)),
+                    SessionOutput::classic(),
+                )
+            })
+        };
+
+        let mut w = BufWriter::new(server.try_clone().unwrap());
+        for _ in 0..3 {
+            write_server_msg(&mut w, &ServerMsg::TtsAudioChunk(vec![0; 4000])).unwrap();
+        }
+        write_server_msg(&mut w, &ServerMsg::SegmentReceived(1)).unwrap();
+        write_server_msg(&mut w, &ServerMsg::Text("You: I went".into())).unwrap();
+        write_server_msg(&mut w, &ServerMsg::Text("AI: Where to?".into())).unwrap();
+        write_server_msg(&mut w, &ServerMsg::VoiceList(vec!["af_bella".into()])).unwrap();
+
+        assert_eq!(
+            voices_rx.recv_timeout(Duration::from_secs(2)).unwrap(),
+            ["af_bella"]
+        );
+        assert_eq!(turn_log.lock().unwrap().len(), 1);
+        assert!(outbox.overdue(sent + outbox::RETRANSMIT_AFTER).is_empty());
+        // The queue still holds the first chunk only
+        assert_eq!(playback.buffered_ms(), 250);
+
+        drop(w);
+        server.shutdown(Shutdown::Both).unwrap();
+        reader.join().unwrap();
+        assert!(shutdown.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn turn_stats_line_uses_tenths_of_seconds() {
+        let stats = TurnStats {
+            stt_ms: 1234,
+            llm_ms: 4800,
+            tts_ms: 950,
+            first_audio_ms: 6984,
+        };
+        assert_eq!(
+            format_turn_stats(&stats),
+            "stt 1.2s | llm 4.8s | tts 0.9s | first audio 7.0s"
+        );
+    }
+
+    #[test]
+    fn feedback_choice_reads_injected_keys() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (keys, _tx) = injected_keys(&[char_key('x'), char_key('2')]);
+        assert_eq!(
+            read_feedback_choice(&keys, &shutdown),
+            FeedbackAction::Retry
+        );
+        assert!(!keys.prompt_open());
+
+        let (keys, _tx) = injected_keys(&[char_key('p'), char_key('4')]);
+        assert_eq!(
+            read_feedback_choice(&keys, &shutdown),
+            FeedbackAction::Pronounce(4)
+        );
+
+        // 'p' followed by a non-digit is dropped; the next key still answers
+        let (keys, _tx) = injected_keys(&[char_key('p'), char_key('x'), char_key('3')]);
+        assert_eq!(
+            read_feedback_choice(&keys, &shutdown),
+            FeedbackAction::Replay
+        );
+
+        let (keys, _tx) = injected_keys(&[char_key('i')]);
+        assert_eq!(
+            read_feedback_choice(&keys, &shutdown),
+            FeedbackAction::TypeCorrection
+        );
+    }
+
+    #[test]
+    fn headless_feedback_continues_after_the_delay() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let keys = keyboard::Keys::headless(Duration::from_millis(50));
+        assert!(keys.poll_idle().is_none());
+        let started = Instant::now();
+        assert_eq!(
+            read_feedback_choice(&keys, &shutdown),
+            FeedbackAction::Continue
+        );
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        // A shutdown ends the wait early
+        let keys = keyboard::Keys::headless(Duration::from_secs(60));
+        shutdown.store(true, Ordering::SeqCst);
+        let started = Instant::now();
+        assert_eq!(
+            read_feedback_choice(&keys, &shutdown),
+            FeedbackAction::Continue
+        );
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn corrected_sentence_is_the_last_one_without_markers() {
+        let feedback = "RED: wrong tense\ncorrected: I <<goed>> home\nCORRECTED: I <<went>> home <<yesterday>>.\nBLUE: nice";
+        assert_eq!(
+            corrected_sentence(feedback).as_deref(),
+            Some("I went home yesterday.")
+        );
+        assert_eq!(
+            corrected_sentence("CORRECTED: Already right").as_deref(),
+            Some("Already right")
+        );
+        assert_eq!(corrected_sentence("RED: no correction"), None);
+        assert_eq!(corrected_sentence("CORRECTED:   "), None);
+        assert_eq!(corrected_sentence("CORRECTED: <<>>"), None);
+    }
+
+    #[test]
+    fn choice_prompts_give_up_on_shutdown() {
+        let shutdown = Arc::new(AtomicBool::new(true));
+        let (keys, _tx) = injected_keys(&[]);
+        assert_eq!(
+            read_feedback_choice(&keys, &shutdown),
+            FeedbackAction::Continue
+        );
+        assert!(!read_summary_choice(&keys, &shutdown));
+        assert_eq!(read_text_input(&keys, &shutdown), None);
+    }
+
+    #[test]
+    fn summary_choice_waits_for_y_or_n() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (keys, _tx) = injected_keys(&[char_key('1'), char_key('Y')]);
+        assert!(read_summary_choice(&keys, &shutdown));
+        let (keys, _tx) = injected_keys(&[char_key('n')]);
+        assert!(!read_summary_choice(&keys, &shutdown));
+    }
+
+    #[test]
+    fn cancelled_summary_resumes_and_is_not_taken_for_the_next() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (summary_tx, summary_rx) = crossbeam_channel::bounded::<String>(1);
+        let audio_input = AudioInputInfo {
+            device: "mic".into(),
+            sample_rate: 16000,
+            channels: 1,
+            resampler: "none".into(),
+        };
+        let history = std::sync::Mutex::new(FeedbackHistory::new(4));
+        let mut abandoned = 0;
+
+        // q → y → Esc while waiting → back to the session
+        let (keys, _tx) = injected_keys(&[
+            char_key('y'),
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            char_key('2'),
+        ]);
+        let mut sent = Vec::new();
+        let outcome = offer_summary(
+            &keys,
+            &shutdown,
+            &mut sent,
+            &summary_rx,
+            &mut abandoned,
+            &audio_input,
+            &history,
+        );
+        assert_eq!(outcome, QuitOutcome::Resume);
+        assert_eq!(abandoned, 1);
+        let mut cursor = std::io::Cursor::new(sent);
+        assert!(matches!(
+            space_lt_common::protocol::read_client_msg(&mut cursor).unwrap(),
+            ClientMsg::SummaryRequest
+        ));
+
+        // The cancelled summary arrives during the session, then the user quits
+        // again: only the new one answers the second request
+        summary_tx.send("old".into()).unwrap();
+        discard_abandoned_summaries(&summary_rx, &mut abandoned);
+        assert_eq!(abandoned, 0);
+        summary_tx.send("new".into()).unwrap();
+        let (keys, _tx) = injected_keys(&[]);
+        assert_eq!(
+            wait_for_summary(&summary_rx, &keys, &shutdown, &mut abandoned),
+            SummaryWait::Received("new".into())
+        );
+
+        // Cancelled again, but this time the late summary is still in flight
+        let (keys, _tx) = injected_keys(&[KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)]);
+        assert_eq!(
+            wait_for_summary(&summary_rx, &keys, &shutdown, &mut abandoned),
+            SummaryWait::Cancelled
+        );
+        abandoned += 1;
+        summary_tx.send("stale".into()).unwrap();
+        let late = std::thread::spawn(move || summary_tx.send("fresh".into()));
+        let (keys, _tx) = injected_keys(&[]);
+        assert_eq!(
+            wait_for_summary(&summary_rx, &keys, &shutdown, &mut abandoned),
+            SummaryWait::Received("fresh".into())
+        );
+        late.join().unwrap().unwrap();
+
+        // Exit anyway
+        let (keys, _tx) = injected_keys(&[
+            char_key('y'),
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            char_key('1'),
+        ]);
+        let outcome = offer_summary(
+            &keys,
+            &shutdown,
+            &mut Vec::new(),
+            &summary_rx,
+            &mut abandoned,
+            &audio_input,
+            &history,
+        );
+        assert_eq!(outcome, QuitOutcome::Exit);
+    }
+
+    #[test]
+    fn text_input_edits_and_submits() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let mut typed: Vec<KeyEvent> = " Salutx".chars().map(char_key).collect();
+        typed.push(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        typed.push(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let (keys, _tx) = injected_keys(&typed);
+        assert_eq!(read_text_input(&keys, &shutdown).as_deref(), Some("Salut"));
+
+        let (keys, _tx) = injected_keys(&[
+            char_key('a'),
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+        ]);
+        assert_eq!(read_text_input(&keys, &shutdown), None);
+    }
+
+    #[test]
+    fn idle_keys_map_to_actions() {
+        let (keys, _tx) = injected_keys(&[
+            char_key('q'),
+            char_key('='),
+            KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+            char_key('z'),
+            char_key('l'),
+            char_key('x'),
+            char_key('a'),
+            char_key('d'),
+            char_key('b'),
+            char_key('v'),
+            char_key('g'),
+        ]);
+        assert_eq!(poll_key_action(&keys), PollAction::Quit);
+        assert_eq!(poll_key_action(&keys), PollAction::VolumeUp);
+        assert_eq!(poll_key_action(&keys), PollAction::Suspend);
+        assert_eq!(poll_key_action(&keys), PollAction::None);
+        assert_eq!(poll_key_action(&keys), PollAction::Translate);
+        assert_eq!(poll_key_action(&keys), PollAction::Simplify);
+        assert_eq!(poll_key_action(&keys), PollAction::ToggleAside);
+        assert_eq!(poll_key_action(&keys), PollAction::Disregard);
+        assert_eq!(poll_key_action(&keys), PollAction::Rewind);
+        assert_eq!(poll_key_action(&keys), PollAction::PickVoice);
+        assert_eq!(poll_key_action(&keys), PollAction::PickLanguage);
+        assert_eq!(poll_key_action(&keys), PollAction::None);
+    }
+
+    #[test]
+    fn voices_are_picked_by_number_or_name() {
+        let voices = ["af_alloy".to_string(), "ff_siwis".to_string()];
+        assert_eq!(voice_choice(&voices, "2"), "ff_siwis");
+        assert_eq!(voice_choice(&voices, "af_alloy"), "af_alloy");
+        // Out of range numbers and unknown names go to the server as typed
+        assert_eq!(voice_choice(&voices, "0"), "0");
+        assert_eq!(voice_choice(&voices, "3"), "3");
+        assert_eq!(voice_choice(&voices, "nobody"), "nobody");
+    }
+
+    #[test]
+    fn aside_mode_keeps_segments_from_the_server() {
+        let mut aside = Aside::default();
+        assert_eq!(aside.pass(vec![1, 2, 3]), Some(vec![1, 2, 3]));
+        assert!(aside.toggle());
+        assert_eq!(aside.pass(vec![0; 16000]), None);
+        assert!(!aside.toggle());
+        assert_eq!(aside.pass(vec![4]), Some(vec![4]));
+    }
+
+    #[test]
+    fn simplify_throttle_drops_repeated_presses() {
+        let mut throttle = SimplifyThrottle::default();
+        let t0 = Instant::now();
+        assert!(throttle.try_send(t0));
+        assert!(!throttle.try_send(t0 + Duration::from_millis(200)));
+        assert!(!throttle.try_send(t0 + SIMPLIFY_COOLDOWN - Duration::from_millis(1)));
+        assert!(throttle.try_send(t0 + SIMPLIFY_COOLDOWN));
+        // The cooldown restarts from the last request that went through
+        assert!(!throttle.try_send(t0 + SIMPLIFY_COOLDOWN + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn user_echo_keeps_the_detected_language() {
+        assert_eq!(user_echo("You: I went"), Some((None, "I went")));
+        assert_eq!(user_echo("You [es]: Hola"), Some((Some("es"), "Hola")));
+        assert_eq!(user_echo("AI: You: no"), None);
+        assert_eq!(user_echo("Your turn"), None);
+    }
+
+    #[test]
+    fn server_error_hint_only_for_retryable_errors() {
+        let retry = format_server_error("retry: Could not transcribe your speech");
+        assert!(retry.contains("\u{2717} Could not transcribe your speech"));
+        assert!(retry.contains("Press your hotkey and try again."));
+
+        let fatal = format_server_error("TTS engine unavailable");
+        assert!(fatal.contains("\u{2717} Server error: TTS engine unavailable"));
+        assert!(!fatal.contains("try again"));
+    }
+
+    #[test]
+    fn audio_input_header_names_device_and_rate() {
+        let header = audio_input_header(&AudioInputInfo {
+            device: "USB Mic".into(),
+            sample_rate: 44100,
+            channels: 1,
+            resampler: "sinc".into(),
+        });
+        assert_eq!(
+            header,
+            "> Audio input: USB Mic (44100 Hz, 1 ch; resampler: sinc)\n\n"
+        );
+    }
+
+    #[test]
+    fn feedback_history_markdown_renders_entries() {
+        let mut history = FeedbackHistory::new(10);
+        history.push(FeedbackEntry {
+            time: "10:02:13".into(),
+            sentence: Some("I goed to the store".into()),
+            feedback: "RED: \"goed\" should be \"went\"\nBLUE: try \"shop\"\nCORRECTED: I <<went>> to the store".into(),
+        });
+        let md = feedback_history_markdown(&history);
+        assert!(md.contains("## Feedback history"));
+        assert!(md.contains("### 10:02:13 \u{2014} I goed to the store"));
+        assert!(md.contains("- \u{2717} \"goed\" should be \"went\""));
+        assert!(md.contains("- \u{279c} try \"shop\""));
+        assert!(md.contains("- \u{2713} I **went** to the store"));
+    }
+
+    #[test]
+    fn mode_switch_while_idle_matches_server_pause_state() {
+        // Manual never paused the server; Auto expects it paused while idle
+        assert_eq!(
+            switch_voice_mode(tui::VoiceMode::Manual, false),
+            ModeSwitch {
+                mode: tui::VoiceMode::Auto,
+                send_accumulated: false,
+                flush_vad: false,
+                server: Some(ServerPause::Pause),
+            }
+        );
+        // Auto left the server paused; Manual would never resume it
+        assert_eq!(
+            switch_voice_mode(tui::VoiceMode::Auto, false),
+            ModeSwitch {
+                mode: tui::VoiceMode::Manual,
+                send_accumulated: false,
+                flush_vad: true,
+                server: Some(ServerPause::Resume),
+            }
+        );
+    }
+
+    #[test]
+    fn mode_switch_while_listening_keeps_audio() {
+        // Audio accumulated so far is sent, then VAD segments the rest
+        let to_auto = switch_voice_mode(tui::VoiceMode::Manual, true);
+        assert!(to_auto.send_accumulated);
+        assert_eq!(to_auto.server, Some(ServerPause::Resume));
+
+        // The in-progress VAD segment is flushed; the server stays resumed
+        let to_manual = switch_voice_mode(tui::VoiceMode::Auto, true);
+        assert_eq!(to_manual.mode, tui::VoiceMode::Manual);
+        assert!(to_manual.flush_vad);
+        assert!(!to_manual.send_accumulated);
+        assert_eq!(to_manual.server, None);
+    }
+
+    #[test]
+    fn mode_switch_round_trip() {
+        let there = switch_voice_mode(tui::VoiceMode::Auto, false);
+        let back = switch_voice_mode(there.mode, false);
+        assert_eq!(back.mode, tui::VoiceMode::Auto);
+    }
+
+    #[test]
+    fn mic_meter_bar_scales_with_level() {
+        let empty = format_mic_meter(-90.0, -80.0, false, None);
+        assert!(empty.contains(&"\u{00b7}".repeat(MIC_METER_WIDTH)));
+        let half = format_mic_meter(-30.0, -12.0, false, None);
+        assert!(half.contains(&format!(
+            "[{}{}]",
+            "\u{2588}".repeat(10),
+            "\u{00b7}".repeat(10)
+        )));
+        assert!(half.contains("-30 dB (peak -12)"));
+        let full = format_mic_meter(3.0, 0.0, false, None);
+        assert!(full.contains(&"\u{2588}".repeat(MIC_METER_WIDTH)));
+        assert!(!full.contains("voice"));
+    }
+
+    #[test]
+    fn mic_meter_shows_speech_probability() {
+        let line = format_mic_meter(-30.0, -12.0, false, Some(0.6));
+        assert!(line.contains(&format!(
+            "voice [{}{}]",
+            "\u{2588}".repeat(3),
+            "\u{00b7}".repeat(2)
+        )));
+        let line = format_mic_meter(-30.0, -12.0, false, Some(0.0));
+        assert!(line.contains(&format!(
+            "voice [{}]",
+            "\u{00b7}".repeat(SPEECH_METER_WIDTH)
+        )));
+        // The warning replaces the whole line
+        assert!(!format_mic_meter(-96.0, -96.0, true, Some(1.0)).contains("voice"));
+    }
+
+    #[test]
+    fn mic_meter_warns_when_silent() {
+        let line = format_mic_meter(-96.0, -96.0, true, None);
+        assert!(line.contains("\x1b[31m"));
+        assert!(line.contains("mic silent"));
+    }
+
     #[test]
     fn parse_corrected_parts_with_markers() {
         let result = parse_corrected_parts("I <<went>> to the store");
@@ -0,0 +1,157 @@
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use space_lt_common::protocol::ClientMsg;
+use space_lt_common::{info, warn};
+
+/// A segment the server has not acknowledged after this long is sent again.
+pub const RETRANSMIT_AFTER: Duration = Duration::from_secs(5);
+
+/// Times a segment is sent before it is given up on.
+pub const MAX_SENDS: u32 = 3;
+
+/// Speech segments sent to the server and not acknowledged yet.
+///
+/// Each segment goes out as a `NumberedSegment` and is kept until the server
+/// answers with its `SegmentReceived`, so one lost on the way (a send error
+/// that did not end the connection) is sent again. The server drops the
+/// copies of a segment it already has.
+#[derive(Default)]
+pub struct Outbox {
+    state: Mutex<OutboxState>,
+}
+
+#[derive(Default)]
+struct OutboxState {
+    last_seq: u32,
+    unacked: Vec<Unacked>,
+}
+
+struct Unacked {
+    seq: u32,
+    samples: Vec<i16>,
+    sent: Instant,
+    sends: u32,
+}
+
+impl Outbox {
+    fn lock(&self) -> MutexGuard<'_, OutboxState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Number `samples` and keep them until the server acknowledges them.
+    /// Returns the message to send now.
+    pub fn number(&self, samples: Vec<i16>, now: Instant) -> ClientMsg {
+        let mut state = self.lock();
+        state.last_seq = state.last_seq.wrapping_add(1);
+        let seq = state.last_seq;
+        state.unacked.push(Unacked {
+            seq,
+            samples: samples.clone(),
+            sent: now,
+            sends: 1,
+        });
+        ClientMsg::NumberedSegment(seq, samples)
+    }
+
+    /// The server has segment `seq`.
+    pub fn acknowledge(&self, seq: u32) {
+        self.lock().unacked.retain(|segment| segment.seq != seq);
+    }
+
+    /// The segments to send again because their acknowledgment is overdue,
+    /// oldest first. One already sent [`MAX_SENDS`] times is dropped instead.
+    pub fn overdue(&self, now: Instant) -> Vec<ClientMsg> {
+        let mut resend = Vec::new();
+        self.lock().unacked.retain_mut(|segment| {
+            if now.duration_since(segment.sent) < RETRANSMIT_AFTER {
+                return true;
+            }
+            if segment.sends >= MAX_SENDS {
+                warn!(
+                    "[client] The server never acknowledged segment {}, giving up on it",
+                    segment.seq
+                );
+                return false;
+            }
+            info!(
+                "[client] Segment {} not acknowledged, sending it again",
+                segment.seq
+            );
+            segment.sends += 1;
+            segment.sent = now;
+            resend.push(ClientMsg::NumberedSegment(
+                segment.seq,
+                segment.samples.clone(),
+            ));
+            true
+        });
+        resend
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seqs(msgs: &[ClientMsg]) -> Vec<u32> {
+        msgs.iter()
+            .map(|msg| match msg {
+                ClientMsg::NumberedSegment(seq, _) => *seq,
+                other => panic!("Expected NumberedSegment, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn segments_are_numbered_in_order() {
+        let outbox = Outbox::default();
+        let now = Instant::now();
+        let sent = [
+            outbox.number(vec![1; 10], now),
+            outbox.number(vec![2; 10], now),
+        ];
+        assert_eq!(seqs(&sent), [1, 2]);
+        match &sent[1] {
+            ClientMsg::NumberedSegment(_, samples) => assert_eq!(samples, &vec![2; 10]),
+            other => panic!("Expected NumberedSegment, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn only_unacknowledged_segments_are_sent_again() {
+        let outbox = Outbox::default();
+        let start = Instant::now();
+        outbox.number(vec![1; 10], start);
+        outbox.number(vec![2; 10], start);
+        outbox.number(vec![3; 10], start);
+        outbox.acknowledge(2);
+
+        assert!(outbox.overdue(start + RETRANSMIT_AFTER / 2).is_empty());
+        let resent = outbox.overdue(start + RETRANSMIT_AFTER);
+        assert_eq!(seqs(&resent), [1, 3]);
+        match &resent[0] {
+            ClientMsg::NumberedSegment(_, samples) => assert_eq!(samples, &vec![1; 10]),
+            other => panic!("Expected NumberedSegment, got {other:?}"),
+        }
+
+        // Acknowledged after the resend: nothing more
+        outbox.acknowledge(1);
+        outbox.acknowledge(3);
+        assert!(outbox.overdue(start + RETRANSMIT_AFTER * 3).is_empty());
+    }
+
+    #[test]
+    fn a_segment_is_given_up_after_max_sends() {
+        let outbox = Outbox::default();
+        let mut now = Instant::now();
+        outbox.number(vec![0; 10], now);
+        for _ in 1..MAX_SENDS {
+            now += RETRANSMIT_AFTER;
+            assert_eq!(seqs(&outbox.overdue(now)), [1]);
+        }
+        now += RETRANSMIT_AFTER;
+        assert!(outbox.overdue(now).is_empty());
+        assert!(outbox.lock().unacked.is_empty());
+    }
+}
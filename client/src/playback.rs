@@ -1,31 +1,199 @@
 use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use crossbeam_channel::Receiver;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
-use space_lt_common::{info, warn};
+use space_lt_common::{debug, info, warn};
 
-/// Start an audio output stream that plays TTS audio from the given channel.
+use crate::devices;
+use crate::playback_queue::PlaybackQueue;
+use crate::suspend::SuspendableStream;
+
+/// Fade-out applied to the playing audio when playback is cleared (barge-in).
+const FADE_OUT_MS: u32 = 30;
+
+/// Audio queued before a reply starts playing.
+const PREBUFFER_MS: u64 = 150;
+
+/// Added to the prebuffer after each underrun, up to [`PREBUFFER_MAX_MS`].
+const PREBUFFER_STEP_MS: u64 = 100;
+
+const PREBUFFER_MAX_MS: u64 = 600;
+
+/// Underrun counters, updated by the playback callback and kept across stream
+/// rebuilds.
+#[derive(Debug, Default)]
+pub struct PlaybackStats {
+    underruns: AtomicU64,
+    silent_samples: AtomicU64,
+}
+
+impl PlaybackStats {
+    /// Times a reply ran out of audio while more was still coming.
+    pub fn underruns(&self) -> u64 {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Silence played because of those underruns, in output samples.
+    pub fn silent_samples(&self) -> u64 {
+        self.silent_samples.load(Ordering::Relaxed)
+    }
+
+    /// One-line report for the end of the session, or `None` if playback never
+    /// starved.
+    pub fn summary(&self, output_rate: u32) -> Option<String> {
+        let underruns = self.underruns();
+        if underruns == 0 {
+            return None;
+        }
+        let silent_secs = self.silent_samples() as f64 / output_rate.max(1) as f64;
+        Some(format!(
+            "Playback underruns: {underruns} ({silent_secs:.1}s of silence inserted)"
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GateState {
+    /// Waiting for the prebuffer to fill before (re)starting a reply.
+    Priming,
+    Draining,
+    /// Ran dry mid-reply; priming again, and the silence counts as underrun.
+    Starved,
+}
+
+/// Prebuffer gate of the playback callback: holds a reply back until enough of
+/// it is queued to ride out slow synthesis.
+///
+/// Each underrun raises the target by [`PREBUFFER_STEP_MS`], so a slow machine
+/// settles on a prebuffer it can sustain.
+struct Prebuffer {
+    target_ms: u64,
+    max_ms: u64,
+    state: GateState,
+}
+
+impl Prebuffer {
+    /// `max_ms` caps the target; it must stay below the queue's high-water mark,
+    /// or the gate would wait for audio the producer is not allowed to queue.
+    fn new(target_ms: u64, max_ms: u64) -> Self {
+        Self {
+            target_ms: target_ms.min(max_ms),
+            max_ms,
+            state: GateState::Priming,
+        }
+    }
+
+    /// Whether the callback may drain the queue: once the target is queued, or
+    /// right away when the reply is not `streaming` anymore (its end is queued).
+    fn ready(&mut self, buffered_ms: u64, streaming: bool) -> bool {
+        if self.state != GateState::Draining && (buffered_ms >= self.target_ms || !streaming) {
+            self.state = GateState::Draining;
+        }
+        self.state == GateState::Draining
+    }
+
+    /// The queue ran dry. Returns `true` for an underrun: playing audio ran out
+    /// while the reply is still `streaming`.
+    fn ran_dry(&mut self, streaming: bool) -> bool {
+        if !streaming {
+            self.state = GateState::Priming;
+            return false;
+        }
+        if self.state != GateState::Draining {
+            return false;
+        }
+        self.state = GateState::Starved;
+        self.target_ms = (self.target_ms + PREBUFFER_STEP_MS).min(self.max_ms);
+        true
+    }
+
+    fn is_starved(&self) -> bool {
+        self.state == GateState::Starved
+    }
+
+    /// Barge-in: the queue was flushed, the next audio is a new reply.
+    fn reset(&mut self) {
+        self.state = GateState::Priming;
+    }
+}
+
+/// Fill `data` from `queue` once `gate` opens, and silence the rest.
+fn drain(
+    queue: &PlaybackQueue,
+    gate: &mut Prebuffer,
+    streaming: bool,
+    data: &mut [i16],
+    gain_percent: u32,
+    stats: &PlaybackStats,
+) {
+    let mut offset = 0;
+    if gate.ready(queue.buffered_ms(), streaming) {
+        queue.pop_with(data.len(), |samples| {
+            let end = offset + samples.len();
+            copy_with_gain(&mut data[offset..end], samples, gain_percent);
+            offset = end;
+        });
+    }
+    if offset == data.len() {
+        return;
+    }
+
+    // Not enough queued audio: fill the remainder with silence (self-healing)
+    data[offset..].fill(0);
+    if gate.ran_dry(streaming) {
+        stats.underruns.fetch_add(1, Ordering::Relaxed);
+        debug!("[client] Playback buffer underrun, prebuffering again");
+    }
+    if gate.is_starved() {
+        stats
+            .silent_samples
+            .fetch_add((data.len() - offset) as u64, Ordering::Relaxed);
+    }
+}
+
+/// Start an audio output stream that plays TTS audio from the given queue.
 ///
 /// The `clear` flag allows the caller to flush the playback buffer (e.g. on barge-in).
-/// When set to `true`, the callback drains leftover and channel, fills silence, and resets the flag.
+/// When set to `true`, the callback clears the queue and resets the flag; the
+/// audio that was about to play is faded out over [`FADE_OUT_MS`] instead of
+/// being cut mid-waveform, which would click.
+///
+/// `gain` is the playback volume in percent, read on every callback so it can be
+/// changed while audio is playing.
+///
+/// `streaming` is set while a reply is still arriving. Playback of a reply waits
+/// until [`PREBUFFER_MS`] of it are queued (more after underruns), or until
+/// `streaming` clears because its end is already queued. Underruns are counted
+/// in `stats`.
+///
+/// `failed` is set when the stream dies (device unplugged, stream invalidated);
+/// see [`PlaybackStream::recover`].
+///
+/// `device` names the output device to open (matched like `--output-device`);
+/// without it, or when no device matches, the default device is used.
 ///
 /// Returns the cpal Stream (must be kept alive for playback to continue)
 /// and the actual output sample rate (for resampling if needed).
 pub fn start_playback(
-    audio_rx: Receiver<Vec<i16>>,
+    queue: Arc<PlaybackQueue>,
     clear: Arc<AtomicBool>,
+    gain: Arc<AtomicU32>,
+    streaming: Arc<AtomicBool>,
+    stats: Arc<PlaybackStats>,
+    failed: Arc<AtomicBool>,
+    device: Option<&str>,
 ) -> Result<(cpal::Stream, u32)> {
     let host = cpal::default_host();
-    let device = host
-        .default_output_device()
-        .ok_or_else(|| anyhow::anyhow!("No default audio output device found"))?;
+    let device = match device.and_then(|query| find_output_device(&host, query)) {
+        Some(device) => device,
+        None => host
+            .default_output_device()
+            .ok_or_else(|| anyhow::anyhow!("No default audio output device found"))?,
+    };
 
-    let device_name = device
-        .description()
-        .map(|d: cpal::DeviceDescription| d.name().to_string())
-        .unwrap_or_else(|_| "Default".into());
+    let device_name = devices::device_name(&device);
 
     let default_config = device
         .default_output_config()
@@ -47,67 +215,51 @@ pub fn start_playback(
         buffer_size: cpal::BufferSize::Default,
     };
 
-    // Residual samples from a chunk that didn't fully fit into the previous callback.
-    // Capped at 1 second of audio (output_rate samples) to prevent unbounded growth.
-    let max_leftover = output_rate as usize;
-    let mut leftover: Vec<i16> = Vec::new();
+    queue.set_sample_rate(output_rate);
+
+    // Barge-in fade: the head of the queue, ramped down, played before silence
+    let fade_len = (output_rate * FADE_OUT_MS / 1000) as usize;
+    let mut fade: Vec<i16> = Vec::with_capacity(fade_len);
+    let mut fade_pos = 0;
+    // Stay clear of the high-water mark, which bounds what can be queued
+    let prebuffer_max = PREBUFFER_MAX_MS.min(queue.high_water_ms() as u64 * 3 / 4);
+    let mut gate = Prebuffer::new(PREBUFFER_MS, prebuffer_max);
 
     let stream = device
         .build_output_stream(
             &config,
             move |data: &mut [i16], _: &cpal::OutputCallbackInfo| {
-                // Barge-in clear: flush all buffered audio and output silence
-                if clear.load(Ordering::SeqCst) {
-                    clear.store(false, Ordering::SeqCst);
-                    leftover.clear();
-                    while audio_rx.try_recv().is_ok() {}
-                    data.fill(0);
-                    return;
-                }
+                let gain_percent = gain.load(Ordering::Relaxed);
 
-                let mut offset = 0;
-
-                // First, drain any leftover samples from the previous callback
-                if !leftover.is_empty() {
-                    let n = leftover.len().min(data.len());
-                    data[..n].copy_from_slice(&leftover[..n]);
-                    offset = n;
-                    if n < leftover.len() {
-                        leftover.drain(..n);
-                    } else {
-                        leftover.clear();
-                    }
+                // Barge-in clear: keep a short fade-out of what was playing, flush the rest
+                if clear.swap(false, Ordering::SeqCst) {
+                    fade.clear();
+                    fade_pos = 0;
+                    queue.pop_with(fade_len, |samples| fade.extend_from_slice(samples));
+                    queue.clear();
+                    apply_fade_out(&mut fade, fade_len);
+                    gate.reset();
+                }
+                if fade_pos < fade.len() {
+                    let n = (fade.len() - fade_pos).min(data.len());
+                    copy_with_gain(&mut data[..n], &fade[fade_pos..fade_pos + n], gain_percent);
+                    fade_pos += n;
+                    data[n..].fill(0);
+                    return;
                 }
 
-                // Then pull from channel
-                while offset < data.len() {
-                    match audio_rx.try_recv() {
-                        Ok(chunk) => {
-                            let remaining = data.len() - offset;
-                            let n = chunk.len().min(remaining);
-                            data[offset..offset + n].copy_from_slice(&chunk[..n]);
-                            offset += n;
-                            // Save leftover if chunk was bigger than remaining space
-                            if n < chunk.len() && leftover.len() < max_leftover {
-                                let cap = (max_leftover - leftover.len()).min(chunk.len() - n);
-                                leftover.extend_from_slice(&chunk[n..n + cap]);
-                            }
-                        }
-                        Err(_) => {
-                            // No data available — fill remainder with silence (self-healing)
-                            if offset > 0 {
-                                // Buffer underrun: started writing audio but ran out mid-callback
-                                space_lt_common::debug!(
-                                    "[client] Playback buffer underrun, filling with silence"
-                                );
-                            }
-                            data[offset..].fill(0);
-                            break;
-                        }
+                let streaming = streaming.load(Ordering::SeqCst);
+                drain(&queue, &mut gate, streaming, data, gain_percent, &stats);
+            },
+            move |err| {
+                if is_fatal(&err) {
+                    if !failed.swap(true, Ordering::SeqCst) {
+                        warn!("[client] Playback stream lost: {err}");
                     }
+                } else {
+                    debug!("[client] Playback error: {err}");
                 }
             },
-            |err| warn!("[client] Playback error: {err}"),
             None,
         )
         .context("building output stream")?;
@@ -116,3 +268,442 @@ pub fn start_playback(
 
     Ok((stream, output_rate))
 }
+
+/// The output device named like `query`, or `None` (with a warning) to use
+/// the default one.
+fn find_output_device(host: &cpal::Host, query: &str) -> Option<cpal::Device> {
+    let outputs = match host.output_devices() {
+        Ok(outputs) => outputs
+            .map(|device| (devices::device_name(&device), device))
+            .collect(),
+        Err(e) => {
+            warn!("[client] Could not list audio output devices: {e}");
+            Vec::new()
+        }
+    };
+    devices::find_or_default(outputs, query, "output")
+}
+
+/// Errors after which the stream produces no more audio.
+fn is_fatal(err: &cpal::StreamError) -> bool {
+    !matches!(err, cpal::StreamError::BufferUnderrun)
+}
+
+/// Wait between attempts to reopen a lost output device.
+const REOPEN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Outcome of [`rebuild_on_failure`].
+#[derive(Debug, PartialEq)]
+pub enum Recovery {
+    /// The stream is alive, nothing was done.
+    Healthy,
+    /// A new stream replaced the dead one.
+    Rebuilt { rate_changed: bool },
+}
+
+/// Replace `stream` with one from `open` if `failed` is set.
+///
+/// `open` returns the new stream and its output rate. The playback queue is
+/// kept across the rebuild, unless the rate changed: its audio was resampled for
+/// the old device. `failed` stays set when `open` fails, so the next call retries.
+pub fn rebuild_on_failure<S>(
+    stream: &mut S,
+    failed: &AtomicBool,
+    output_rate: &AtomicU32,
+    queue: &PlaybackQueue,
+    open: impl FnOnce() -> Result<(S, u32)>,
+) -> Result<Recovery> {
+    if !failed.load(Ordering::SeqCst) {
+        return Ok(Recovery::Healthy);
+    }
+    let (new_stream, rate) = open()?;
+    *stream = new_stream;
+    failed.store(false, Ordering::SeqCst);
+    let rate_changed = output_rate.swap(rate, Ordering::SeqCst) != rate;
+    if rate_changed {
+        queue.clear();
+    }
+    Ok(Recovery::Rebuilt { rate_changed })
+}
+
+/// Running playback stream, kept with what is needed to rebuild it after a
+/// suspend or when its device goes away.
+pub struct PlaybackStream {
+    stream: cpal::Stream,
+    queue: Arc<PlaybackQueue>,
+    clear: Arc<AtomicBool>,
+    gain: Arc<AtomicU32>,
+    streaming: Arc<AtomicBool>,
+    stats: Arc<PlaybackStats>,
+    failed: Arc<AtomicBool>,
+    output_rate: Arc<AtomicU32>,
+    next_attempt: Instant,
+    /// Requested output device; `None` for the default one.
+    device: Option<String>,
+}
+
+impl PlaybackStream {
+    pub fn start(
+        queue: Arc<PlaybackQueue>,
+        clear: Arc<AtomicBool>,
+        gain: Arc<AtomicU32>,
+        streaming: Arc<AtomicBool>,
+        device: Option<String>,
+    ) -> Result<Self> {
+        let failed = Arc::new(AtomicBool::new(false));
+        let stats = Arc::new(PlaybackStats::default());
+        let (stream, output_rate) = start_playback(
+            queue.clone(),
+            clear.clone(),
+            gain.clone(),
+            streaming.clone(),
+            stats.clone(),
+            failed.clone(),
+            device.as_deref(),
+        )?;
+        Ok(Self {
+            stream,
+            queue,
+            clear,
+            gain,
+            streaming,
+            stats,
+            failed,
+            output_rate: Arc::new(AtomicU32::new(output_rate)),
+            next_attempt: Instant::now(),
+            device,
+        })
+    }
+
+    /// Current output rate; changes when the stream is rebuilt on another device.
+    pub fn output_rate(&self) -> u32 {
+        self.output_rate.load(Ordering::SeqCst)
+    }
+
+    /// The output rate, shared with the threads that resample for playback.
+    pub fn shared_output_rate(&self) -> Arc<AtomicU32> {
+        self.output_rate.clone()
+    }
+
+    /// Underrun counters of this stream and the ones rebuilt from it.
+    pub fn stats(&self) -> &PlaybackStats {
+        &self.stats
+    }
+
+    /// Watchdog step: if the stream died, reopen it on the requested device, or
+    /// the current default one while it is missing (at most every [`REOPEN_INTERVAL`]).
+    pub fn recover(&mut self) -> Recovery {
+        if !self.failed.load(Ordering::SeqCst) || Instant::now() < self.next_attempt {
+            return Recovery::Healthy;
+        }
+        self.next_attempt = Instant::now() + REOPEN_INTERVAL;
+        match self.reopen() {
+            Ok(recovery) => recovery,
+            Err(e) => {
+                debug!("[client] Playback device not back yet: {e:#}");
+                Recovery::Healthy
+            }
+        }
+    }
+
+    fn reopen(&mut self) -> Result<Recovery> {
+        let (queue, clear, gain, streaming, stats, failed) = (
+            self.queue.clone(),
+            self.clear.clone(),
+            self.gain.clone(),
+            self.streaming.clone(),
+            self.stats.clone(),
+            self.failed.clone(),
+        );
+        rebuild_on_failure(
+            &mut self.stream,
+            &self.failed,
+            &self.output_rate,
+            &self.queue,
+            || {
+                start_playback(
+                    queue,
+                    clear,
+                    gain,
+                    streaming,
+                    stats,
+                    failed,
+                    self.device.as_deref(),
+                )
+            },
+        )
+    }
+}
+
+impl SuspendableStream for PlaybackStream {
+    fn name(&self) -> &str {
+        "playback"
+    }
+
+    fn pause(&mut self) -> Result<()> {
+        self.stream.pause().context("pausing playback stream")
+    }
+
+    fn resume(&mut self) -> Result<()> {
+        self.stream.play().context("resuming playback stream")
+    }
+
+    fn rebuild(&mut self) -> Result<()> {
+        self.failed.store(true, Ordering::SeqCst);
+        self.reopen().map(|_| ())
+    }
+}
+
+/// Ramp `samples` linearly down to zero over the first `fade_len` samples
+/// (or all of them, if fewer) and silence anything after.
+fn apply_fade_out(samples: &mut [i16], fade_len: usize) {
+    let ramp = fade_len.min(samples.len());
+    for (i, s) in samples[..ramp].iter_mut().enumerate() {
+        *s = (*s as i64 * (ramp - i - 1) as i64 / ramp as i64) as i16;
+    }
+    samples[ramp..].fill(0);
+}
+
+/// Copy `src` into `dst` scaled by `gain_percent`, saturating at the i16 range.
+fn copy_with_gain(dst: &mut [i16], src: &[i16], gain_percent: u32) {
+    if gain_percent == 100 {
+        dst.copy_from_slice(src);
+        return;
+    }
+    for (d, &s) in dst.iter_mut().zip(src) {
+        let scaled = s as i64 * gain_percent as i64 / 100;
+        *d = scaled.clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue_with_audio() -> PlaybackQueue {
+        let queue = PlaybackQueue::new(10_000);
+        queue.set_sample_rate(16000);
+        queue.push(vec![1; 160], &AtomicBool::new(false));
+        queue
+    }
+
+    #[test]
+    fn healthy_stream_is_not_reopened() {
+        let queue = queue_with_audio();
+        let mut stream = "old";
+        let recovery = rebuild_on_failure(
+            &mut stream,
+            &AtomicBool::new(false),
+            &AtomicU32::new(16000),
+            &queue,
+            || panic!("must not reopen"),
+        )
+        .unwrap();
+        assert_eq!(recovery, Recovery::Healthy);
+        assert_eq!(stream, "old");
+    }
+
+    #[test]
+    fn failed_stream_is_reopened_keeping_queued_audio() {
+        let queue = queue_with_audio();
+        let failed = AtomicBool::new(true);
+        let rate = AtomicU32::new(16000);
+        let mut stream = "old";
+
+        // Device not back yet: stays failed for the next attempt
+        let err = rebuild_on_failure(&mut stream, &failed, &rate, &queue, || {
+            anyhow::bail!("no output device")
+        });
+        assert!(err.is_err());
+        assert!(failed.load(Ordering::SeqCst));
+        assert_eq!(stream, "old");
+
+        let recovery =
+            rebuild_on_failure(&mut stream, &failed, &rate, &queue, || Ok(("new", 16000))).unwrap();
+        assert_eq!(
+            recovery,
+            Recovery::Rebuilt {
+                rate_changed: false
+            }
+        );
+        assert_eq!(stream, "new");
+        assert!(!failed.load(Ordering::SeqCst));
+        assert!(!queue.is_empty());
+    }
+
+    #[test]
+    fn rate_change_drops_audio_resampled_for_the_old_device() {
+        let queue = queue_with_audio();
+        let failed = AtomicBool::new(true);
+        let rate = AtomicU32::new(16000);
+        let mut stream = "old";
+        let recovery =
+            rebuild_on_failure(&mut stream, &failed, &rate, &queue, || Ok(("usb", 48000))).unwrap();
+        assert_eq!(recovery, Recovery::Rebuilt { rate_changed: true });
+        assert_eq!(rate.load(Ordering::SeqCst), 48000);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn only_buffer_underruns_are_survivable() {
+        assert!(!is_fatal(&cpal::StreamError::BufferUnderrun));
+        assert!(is_fatal(&cpal::StreamError::DeviceNotAvailable));
+        assert!(is_fatal(&cpal::StreamError::StreamInvalidated));
+    }
+
+    #[test]
+    fn gain_unity_copies_unchanged() {
+        let src = [0, 1, -1, i16::MAX, i16::MIN];
+        let mut dst = [0i16; 5];
+        copy_with_gain(&mut dst, &src, 100);
+        assert_eq!(dst, src);
+    }
+
+    #[test]
+    fn gain_scales_and_clips_at_200_percent() {
+        let src = [1000, -1000, 20000, -20000, i16::MAX, i16::MIN];
+        let mut dst = [0i16; 6];
+        copy_with_gain(&mut dst, &src, 200);
+        assert_eq!(dst, [2000, -2000, i16::MAX, i16::MIN, i16::MAX, i16::MIN]);
+    }
+
+    #[test]
+    fn gain_attenuates_and_mutes() {
+        let src = [1000, -1000];
+        let mut dst = [0i16; 2];
+        copy_with_gain(&mut dst, &src, 50);
+        assert_eq!(dst, [500, -500]);
+        copy_with_gain(&mut dst, &src, 0);
+        assert_eq!(dst, [0, 0]);
+    }
+
+    #[test]
+    fn fade_out_ramps_down_to_silence() {
+        let mut samples = vec![-20000i16; 100];
+        apply_fade_out(&mut samples, 60);
+        assert_eq!(samples[0], -19666); // 59/60 of the level
+        for w in samples[..60].windows(2) {
+            assert!(w[1].unsigned_abs() <= w[0].unsigned_abs(), "{w:?}");
+        }
+        assert!(samples[30].unsigned_abs() < 11000);
+        assert_eq!(samples[59], 0);
+        assert!(samples[60..].iter().all(|&s| s == 0));
+    }
+
+    /// Fake output device: runs the callback's drain step over 10 ms buffers of
+    /// a 1 kHz queue, so sample counts and milliseconds coincide.
+    struct Harness {
+        queue: PlaybackQueue,
+        gate: Prebuffer,
+        stats: PlaybackStats,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let queue = PlaybackQueue::new(3000);
+            queue.set_sample_rate(1000);
+            Self {
+                queue,
+                gate: Prebuffer::new(PREBUFFER_MS, PREBUFFER_MAX_MS),
+                stats: PlaybackStats::default(),
+            }
+        }
+
+        fn push(&self, ms: usize) {
+            self.queue.push(vec![1; ms], &AtomicBool::new(false));
+        }
+
+        /// One callback; returns how many samples were audio rather than silence.
+        fn callback(&mut self, streaming: bool) -> usize {
+            let mut data = [0i16; 10];
+            drain(
+                &self.queue,
+                &mut self.gate,
+                streaming,
+                &mut data,
+                100,
+                &self.stats,
+            );
+            data.iter().filter(|&&s| s != 0).count()
+        }
+    }
+
+    #[test]
+    fn prebuffer_holds_a_reply_until_enough_is_queued() {
+        let mut h = Harness::new();
+        h.push(100);
+        assert_eq!(h.callback(true), 0);
+        h.push(50);
+        assert_eq!(h.callback(true), 10);
+        assert_eq!(h.stats.underruns(), 0);
+    }
+
+    #[test]
+    fn short_reply_plays_at_once_when_its_end_is_queued() {
+        let mut h = Harness::new();
+        h.push(25);
+        assert_eq!(h.callback(true), 0);
+        assert_eq!(h.callback(false), 10);
+        assert_eq!(h.callback(false), 10);
+        // Ran out with nothing more coming: the end of the reply, not an underrun
+        assert_eq!(h.callback(false), 5);
+        assert_eq!(h.stats.underruns(), 0);
+        assert_eq!(h.stats.silent_samples(), 0);
+        assert_eq!(h.stats.summary(1000), None);
+    }
+
+    #[test]
+    fn underrun_is_counted_and_primes_a_larger_buffer() {
+        let mut h = Harness::new();
+        h.push(155);
+        for _ in 0..15 {
+            assert_eq!(h.callback(true), 10);
+        }
+        // 5 ms left: starved mid-callback
+        assert_eq!(h.callback(true), 5);
+        assert_eq!(h.stats.underruns(), 1);
+        // Silence while the larger prebuffer (250 ms) fills is counted too
+        h.push(200);
+        assert_eq!(h.callback(true), 0);
+        assert_eq!(h.stats.silent_samples(), 15);
+        h.push(50);
+        assert_eq!(h.callback(true), 10);
+        assert_eq!(h.stats.underruns(), 1);
+        assert_eq!(
+            h.stats.summary(1000).as_deref(),
+            Some("Playback underruns: 1 (0.0s of silence inserted)")
+        );
+    }
+
+    #[test]
+    fn prebuffer_growth_is_capped() {
+        let mut gate = Prebuffer::new(PREBUFFER_MS, 300);
+        for _ in 0..5 {
+            gate.ready(1000, true);
+            assert!(gate.ran_dry(true));
+        }
+        assert_eq!(gate.target_ms, 300);
+        assert!(!gate.ready(299, true));
+        assert!(gate.ready(300, true));
+    }
+
+    #[test]
+    fn barge_in_reset_is_not_an_underrun() {
+        let mut h = Harness::new();
+        h.push(200);
+        assert_eq!(h.callback(true), 10);
+        h.queue.clear();
+        h.gate.reset();
+        assert_eq!(h.callback(true), 0);
+        assert_eq!(h.stats.underruns(), 0);
+    }
+
+    #[test]
+    fn fade_out_shorter_than_the_ramp_still_ends_silent() {
+        let mut samples = vec![1000i16; 10];
+        apply_fade_out(&mut samples, 1440);
+        assert_eq!(samples[0], 900);
+        assert_eq!(samples[9], 0);
+        apply_fade_out(&mut [], 1440);
+    }
+}
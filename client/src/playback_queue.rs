@@ -0,0 +1,553 @@
+use crossbeam_channel::{Receiver, Sender};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use space_lt_common::{debug, warn};
+
+/// Default amount of audio the queue accepts before producers wait (`--playback-buffer-ms`).
+pub const DEFAULT_HIGH_WATER_MS: u32 = 3000;
+
+/// Resampler flush tails shorter than this are dropped when nothing is left to
+/// append them to: played after the queue ran dry they only cause an underrun
+/// and, on some devices, a tick.
+pub const TAIL_DROP_MS: u32 = 10;
+
+/// Audio a [`Feeder`] parks at most. Past it the oldest parked audio is
+/// dropped, so a playback device that stopped draining cannot grow it forever.
+pub const MAX_PARKED_MS: u32 = 60_000;
+
+/// How often a waiting producer re-checks its cancel flag.
+const WAIT_SLICE: Duration = Duration::from_millis(50);
+
+/// Result of [`PlaybackQueue::push`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Push {
+    Queued,
+    /// The queue was cleared (barge-in) while waiting; the chunk was dropped.
+    Cleared,
+    /// The caller's cancel flag was set while waiting; the chunk was dropped.
+    Cancelled,
+}
+
+/// Result of [`PlaybackQueue::push_tail`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tail {
+    /// Appended to the last queued chunk, so it plays without a gap.
+    Appended,
+    /// The queue had already drained and the tail was too short to be worth playing.
+    Dropped,
+    /// Long enough to play on its own: queued like any chunk.
+    Pushed(Push),
+}
+
+/// Audio queue between the TCP reader and the playback callback, bounded by
+/// buffered duration rather than by message count.
+///
+/// Chunks vary from a few samples (resampler tails) to 12000 samples (48 kHz
+/// output), so a message-count bound says little about latency or memory.
+/// Producers wait while the queue holds more than the high-water mark; a single
+/// chunk is always accepted when below it, so the overshoot is one chunk at most.
+pub struct PlaybackQueue {
+    state: Mutex<QueueState>,
+    space: Condvar,
+    high_water_ms: u32,
+    sample_rate: AtomicU32,
+}
+
+#[derive(Default)]
+struct QueueState {
+    chunks: VecDeque<Vec<i16>>,
+    /// Samples of the front chunk already played.
+    front_offset: usize,
+    buffered: usize,
+    /// Bumped by `clear` so waiting producers drop their stale chunk.
+    generation: u64,
+}
+
+impl PlaybackQueue {
+    pub fn new(high_water_ms: u32) -> Self {
+        Self {
+            state: Mutex::default(),
+            space: Condvar::new(),
+            high_water_ms,
+            sample_rate: AtomicU32::new(16000),
+        }
+    }
+
+    /// Rate of the queued samples (the playback device rate), used for durations.
+    pub fn set_sample_rate(&self, sample_rate: u32) {
+        self.sample_rate.store(sample_rate, Ordering::Relaxed);
+        self.space.notify_all();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn high_water_samples(&self) -> usize {
+        self.high_water_ms as usize * self.sample_rate.load(Ordering::Relaxed) as usize / 1000
+    }
+
+    /// Append a chunk, waiting while the queue is above the high-water mark.
+    ///
+    /// Gives up when `cancel` is set or the queue is cleared in the meantime.
+    pub fn push(&self, chunk: Vec<i16>, cancel: &AtomicBool) -> Push {
+        self.push_at(chunk, self.generation(), cancel)
+    }
+
+    /// Bumped by every [`clear`](Self::clear).
+    fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// [`push`](Self::push) for a chunk received before `generation` ended:
+    /// dropped if the queue was cleared since.
+    fn push_at(&self, chunk: Vec<i16>, generation: u64, cancel: &AtomicBool) -> Push {
+        if chunk.is_empty() {
+            return Push::Queued;
+        }
+        let mut state = self.lock();
+        loop {
+            if state.generation != generation {
+                return Push::Cleared;
+            }
+            if state.buffered < self.high_water_samples() {
+                break;
+            }
+            if cancel.load(Ordering::SeqCst) {
+                return Push::Cancelled;
+            }
+            state = self
+                .space
+                .wait_timeout(state, WAIT_SLICE)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        state.buffered += chunk.len();
+        state.chunks.push_back(chunk);
+        Push::Queued
+    }
+
+    #[cfg(test)]
+    fn push_tail(&self, tail: Vec<i16>, cancel: &AtomicBool) -> Tail {
+        self.push_tail_at(tail, self.generation(), cancel)
+    }
+
+    /// Queue the flush tail of a resampler at the end of a response, received
+    /// before `generation` ended.
+    ///
+    /// The tail belongs right after the last chunk, so it is appended to it
+    /// while that chunk is still queued. Once the queue has drained, a tail
+    /// shorter than [`TAIL_DROP_MS`] is dropped; a longer one is real audio and
+    /// is pushed normally.
+    fn push_tail_at(&self, tail: Vec<i16>, generation: u64, cancel: &AtomicBool) -> Tail {
+        if tail.is_empty() {
+            return Tail::Dropped;
+        }
+        let mut state = self.lock();
+        if state.generation != generation {
+            return Tail::Pushed(Push::Cleared);
+        }
+        if let Some(last) = state.chunks.back_mut() {
+            last.extend_from_slice(&tail);
+            state.buffered += tail.len();
+            return Tail::Appended;
+        }
+        drop(state);
+        let min_samples =
+            TAIL_DROP_MS as usize * self.sample_rate.load(Ordering::Relaxed) as usize / 1000;
+        if tail.len() < min_samples {
+            return Tail::Dropped;
+        }
+        Tail::Pushed(self.push_at(tail, generation, cancel))
+    }
+
+    /// Take up to `max` samples in order, handing each contiguous run to `sink`.
+    /// Returns the number of samples taken.
+    pub fn pop_with(&self, max: usize, mut sink: impl FnMut(&[i16])) -> usize {
+        let mut state = self.lock();
+        let mut taken = 0;
+        while taken < max {
+            let offset = state.front_offset;
+            let Some(front) = state.chunks.front() else {
+                break;
+            };
+            let n = (front.len() - offset).min(max - taken);
+            sink(&front[offset..offset + n]);
+            taken += n;
+            if offset + n == front.len() {
+                state.chunks.pop_front();
+                state.front_offset = 0;
+            } else {
+                state.front_offset += n;
+            }
+        }
+        state.buffered -= taken;
+        drop(state);
+        if taken > 0 {
+            self.space.notify_all();
+        }
+        taken
+    }
+
+    /// Drop everything queued and release waiting producers.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.chunks.clear();
+        state.front_offset = 0;
+        state.buffered = 0;
+        state.generation += 1;
+        drop(state);
+        self.space.notify_all();
+    }
+
+    /// Queued duration above which producers wait (`--playback-buffer-ms`).
+    pub fn high_water_ms(&self) -> u32 {
+        self.high_water_ms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().buffered == 0
+    }
+
+    /// Duration of the audio currently queued.
+    pub fn buffered_ms(&self) -> u64 {
+        let rate = self.sample_rate.load(Ordering::Relaxed).max(1) as u64;
+        self.lock().buffered as u64 * 1000 / rate
+    }
+}
+
+/// Audio handed to a [`Feeder`], with the queue generation it arrived in.
+enum Feed {
+    Chunk(Vec<i16>),
+    Tail(Vec<i16>),
+}
+
+impl Feed {
+    fn samples(&self) -> usize {
+        match self {
+            Feed::Chunk(samples) | Feed::Tail(samples) => samples.len(),
+        }
+    }
+}
+
+/// Pushes audio into a [`PlaybackQueue`] from a thread of its own, so the TCP
+/// reader never waits on the high-water mark and keeps reading the messages
+/// behind the audio. Chunks over the mark are parked in order until the queue
+/// has room; those parked before a [`PlaybackQueue::clear`] are dropped.
+/// At most [`MAX_PARKED_MS`] of audio is parked: the oldest goes first.
+pub struct Feeder {
+    queue: Arc<PlaybackQueue>,
+    tx: Sender<(u64, Feed)>,
+    /// Lets `send` take back the oldest parked audio when over the limit.
+    rx: Receiver<(u64, Feed)>,
+    parked: Arc<AtomicUsize>,
+    parked_samples: Arc<AtomicUsize>,
+    max_parked_ms: u32,
+}
+
+impl Feeder {
+    /// Start the feeding thread; it stops once `cancel` is set.
+    pub fn spawn(queue: Arc<PlaybackQueue>, cancel: Arc<AtomicBool>) -> std::io::Result<Self> {
+        Self::spawn_with_limit(queue, cancel, MAX_PARKED_MS)
+    }
+
+    fn spawn_with_limit(
+        queue: Arc<PlaybackQueue>,
+        cancel: Arc<AtomicBool>,
+        max_parked_ms: u32,
+    ) -> std::io::Result<Self> {
+        let (tx, rx) = crossbeam_channel::unbounded::<(u64, Feed)>();
+        let parked = Arc::new(AtomicUsize::new(0));
+        let parked_samples = Arc::new(AtomicUsize::new(0));
+        let (feed_queue, feed_rx) = (queue.clone(), rx.clone());
+        let (feed_parked, feed_samples) = (parked.clone(), parked_samples.clone());
+        std::thread::Builder::new()
+            .name("playback_feeder".into())
+            .spawn(move || {
+                for (generation, feed) in feed_rx {
+                    let len = feed.samples();
+                    let pushed = match feed {
+                        Feed::Chunk(chunk) => feed_queue.push_at(chunk, generation, &cancel),
+                        Feed::Tail(tail) => {
+                            let len = tail.len();
+                            match feed_queue.push_tail_at(tail, generation, &cancel) {
+                                Tail::Appended => Push::Queued,
+                                Tail::Dropped => {
+                                    debug!("[client] Dropping {len}-sample resampler tail");
+                                    Push::Queued
+                                }
+                                Tail::Pushed(pushed) => pushed,
+                            }
+                        }
+                    };
+                    feed_parked.fetch_sub(1, Ordering::SeqCst);
+                    feed_samples.fetch_sub(len, Ordering::SeqCst);
+                    match pushed {
+                        Push::Queued => {}
+                        Push::Cleared => debug!("[client] Playback cleared, dropping stale chunk"),
+                        Push::Cancelled => break,
+                    }
+                }
+            })?;
+        Ok(Self {
+            queue,
+            tx,
+            rx,
+            parked,
+            parked_samples,
+            max_parked_ms,
+        })
+    }
+
+    /// Queue `chunk` behind the parked ones. Never waits.
+    pub fn push(&self, chunk: Vec<i16>) {
+        self.send(Feed::Chunk(chunk));
+    }
+
+    /// Queue a resampler flush tail behind the parked chunks: appended to the
+    /// last one, or dropped when short and the queue has drained.
+    pub fn push_tail(&self, tail: Vec<i16>) {
+        self.send(Feed::Tail(tail));
+    }
+
+    fn send(&self, feed: Feed) {
+        let len = feed.samples();
+        let rate = self.queue.sample_rate.load(Ordering::Relaxed).max(1) as usize;
+        let max_samples = self.max_parked_ms as usize * rate / 1000;
+        let mut dropped = 0;
+        while self.parked_samples.load(Ordering::SeqCst) + len > max_samples {
+            // Only the chunk the thread is pushing is left otherwise
+            let Ok((_, oldest)) = self.rx.try_recv() else {
+                break;
+            };
+            dropped += oldest.samples();
+            self.parked.fetch_sub(1, Ordering::SeqCst);
+            self.parked_samples
+                .fetch_sub(oldest.samples(), Ordering::SeqCst);
+        }
+        if dropped > 0 {
+            warn!(
+                "[client] Playback is falling behind, dropping {}ms of parked audio",
+                dropped * 1000 / rate
+            );
+        }
+
+        self.parked.fetch_add(1, Ordering::SeqCst);
+        self.parked_samples.fetch_add(len, Ordering::SeqCst);
+        // The thread is gone only once cancelled
+        if self.tx.send((self.queue.generation(), feed)).is_err() {
+            self.parked.fetch_sub(1, Ordering::SeqCst);
+            self.parked_samples.fetch_sub(len, Ordering::SeqCst);
+        }
+    }
+
+    /// Whether everything handed over is in the queue (or dropped).
+    pub fn is_idle(&self) -> bool {
+        self.parked.load(Ordering::SeqCst) == 0
+    }
+
+    /// Wait until [`is_idle`](Self::is_idle), so audio pushed straight into the
+    /// queue next plays after the parked chunks rather than between them.
+    pub fn wait_idle(&self, cancel: &AtomicBool) {
+        while !self.is_idle() && !cancel.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn queue(high_water_ms: u32, rate: u32) -> PlaybackQueue {
+        let q = PlaybackQueue::new(high_water_ms);
+        q.set_sample_rate(rate);
+        q
+    }
+
+    fn drain(q: &PlaybackQueue, max: usize) -> Vec<i16> {
+        let mut out = Vec::new();
+        q.pop_with(max, |s| out.extend_from_slice(s));
+        out
+    }
+
+    #[test]
+    fn depth_is_tracked_in_milliseconds() {
+        let q = queue(3000, 16000);
+        let never = AtomicBool::new(false);
+        assert_eq!(q.push(vec![0; 4000], &never), Push::Queued);
+        assert_eq!(q.push(vec![0; 12000], &never), Push::Queued);
+        assert_eq!(q.buffered_ms(), 1000);
+        drain(&q, 8000);
+        assert_eq!(q.buffered_ms(), 500);
+    }
+
+    #[test]
+    fn pop_spans_and_splits_chunks_in_order() {
+        let q = queue(3000, 16000);
+        let never = AtomicBool::new(false);
+        q.push(vec![1, 2, 3], &never);
+        q.push(vec![4, 5], &never);
+        assert_eq!(drain(&q, 4), vec![1, 2, 3, 4]);
+        assert_eq!(drain(&q, 4), vec![5]);
+        assert!(q.is_empty());
+        assert!(drain(&q, 4).is_empty());
+    }
+
+    #[test]
+    fn push_above_high_water_waits_for_consumer() {
+        // 100 ms at 1 kHz = 100 samples
+        let q = Arc::new(queue(100, 1000));
+        let never = AtomicBool::new(false);
+        // Below the mark a chunk is accepted even if it overshoots
+        assert_eq!(q.push(vec![0; 150], &never), Push::Queued);
+
+        let producer = {
+            let q = q.clone();
+            std::thread::spawn(move || q.push(vec![7; 10], &AtomicBool::new(false)))
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(
+            !producer.is_finished(),
+            "producer should wait above the mark"
+        );
+        assert_eq!(q.buffered_ms(), 150);
+
+        drain(&q, 60);
+        assert_eq!(producer.join().unwrap(), Push::Queued);
+        assert_eq!(q.buffered_ms(), 100);
+    }
+
+    #[test]
+    fn tail_is_appended_to_the_pending_chunk() {
+        let q = queue(3000, 44100);
+        let never = AtomicBool::new(false);
+        q.push(vec![1, 2, 3], &never);
+        q.push(vec![4, 5], &never);
+        drain(&q, 4);
+        assert_eq!(q.push_tail(vec![6, 7], &never), Tail::Appended);
+        // Still one chunk behind the partly played front: no gap, no extra message
+        assert_eq!(q.lock().chunks.len(), 1);
+        assert_eq!(drain(&q, 10), vec![5, 6, 7]);
+    }
+
+    #[test]
+    fn short_tail_after_drain_is_dropped() {
+        let q = queue(3000, 44100);
+        let never = AtomicBool::new(false);
+        q.push(vec![0; 441], &never);
+        drain(&q, 441);
+        // 40 samples at 44.1 kHz, under 1 ms
+        assert_eq!(q.push_tail(vec![9; 40], &never), Tail::Dropped);
+        assert!(q.is_empty());
+        assert_eq!(q.push_tail(Vec::new(), &never), Tail::Dropped);
+    }
+
+    #[test]
+    fn long_tail_after_drain_still_plays() {
+        let q = queue(3000, 44100);
+        let never = AtomicBool::new(false);
+        // 20 ms
+        assert_eq!(
+            q.push_tail(vec![3; 882], &never),
+            Tail::Pushed(Push::Queued)
+        );
+        assert_eq!(q.buffered_ms(), 20);
+        assert_eq!(drain(&q, 1000), vec![3; 882]);
+    }
+
+    #[test]
+    fn clear_releases_waiting_producer_without_queueing() {
+        let q = Arc::new(queue(100, 1000));
+        let never = AtomicBool::new(false);
+        q.push(vec![0; 100], &never);
+        let producer = {
+            let q = q.clone();
+            std::thread::spawn(move || q.push(vec![7; 10], &AtomicBool::new(false)))
+        };
+        std::thread::sleep(Duration::from_millis(60));
+        q.clear();
+        assert_eq!(producer.join().unwrap(), Push::Cleared);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn cancel_flag_stops_waiting() {
+        let q = queue(100, 1000);
+        let never = AtomicBool::new(false);
+        q.push(vec![0; 100], &never);
+        assert_eq!(q.push(vec![1], &AtomicBool::new(true)), Push::Cancelled);
+        assert_eq!(q.buffered_ms(), 100);
+    }
+
+    #[test]
+    fn feeder_parks_chunks_over_the_mark_without_waiting() {
+        let q = Arc::new(queue(100, 1000));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let feeder = Feeder::spawn(q.clone(), cancel.clone()).unwrap();
+        for n in 1..=3 {
+            feeder.push(vec![n; 100]);
+        }
+        feeder.push_tail(vec![4; 5]);
+        std::thread::sleep(Duration::from_millis(60));
+        // One chunk in, the rest parked in order
+        assert_eq!(q.buffered_ms(), 100);
+        assert!(!feeder.is_idle());
+        let mut played = Vec::new();
+        while played.len() < 305 {
+            played.extend(drain(&q, 100));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(
+            played,
+            [vec![1; 100], vec![2; 100], vec![3; 100], vec![4; 5]].concat()
+        );
+        feeder.wait_idle(&cancel);
+
+        // Parked before a clear: dropped
+        feeder.push(vec![5; 100]);
+        feeder.push(vec![6; 100]);
+        std::thread::sleep(Duration::from_millis(60));
+        q.clear();
+        feeder.wait_idle(&cancel);
+        assert!(q.is_empty());
+        // After it: played
+        feeder.push(vec![7; 10]);
+        feeder.wait_idle(&cancel);
+        assert_eq!(drain(&q, 100), vec![7; 10]);
+        cancel.store(true, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn feeder_drops_the_oldest_parked_audio_past_its_limit() {
+        let q = Arc::new(queue(100, 1000));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let feeder = Feeder::spawn_with_limit(q.clone(), cancel.clone(), 300).unwrap();
+        // The first fills the queue, the second waits in the feeding thread
+        feeder.push(vec![1; 100]);
+        feeder.push(vec![2; 100]);
+        std::thread::sleep(Duration::from_millis(60));
+        // Playback is stuck: nothing is drained
+        for n in 3..=20 {
+            feeder.push(vec![n; 100]);
+            assert!(feeder.parked_samples.load(Ordering::SeqCst) <= 300);
+        }
+
+        let mut played = Vec::new();
+        while !feeder.is_idle() || !q.is_empty() {
+            played.extend(drain(&q, 100));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        // The newest audio is kept, in order
+        assert_eq!(
+            played,
+            [vec![1; 100], vec![2; 100], vec![19; 100], vec![20; 100]].concat()
+        );
+        assert_eq!(feeder.parked_samples.load(Ordering::SeqCst), 0);
+        cancel.store(true, Ordering::SeqCst);
+    }
+}
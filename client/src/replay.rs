@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+
+/// Default replay history length in seconds (`--replay-buffer-secs`).
+pub const DEFAULT_REPLAY_BUFFER_SECS: u32 = 300;
+
+/// Bounded buffer of the last TTS response, in playback-rate samples.
+///
+/// When full, the oldest samples are dropped so replay always covers the most
+/// recent speech.
+pub struct ReplayBuffer {
+    samples: VecDeque<i16>,
+    max_samples: usize,
+}
+
+impl ReplayBuffer {
+    pub fn new(max_samples: usize) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            max_samples,
+        }
+    }
+
+    /// Change the cap, dropping the oldest samples if the buffer is now over it.
+    pub fn set_max_samples(&mut self, max_samples: usize) {
+        self.max_samples = max_samples;
+        let excess = self.samples.len().saturating_sub(max_samples);
+        self.samples.drain(..excess);
+        self.samples.shrink_to(max_samples);
+    }
+
+    pub fn max_samples(&self) -> usize {
+        self.max_samples
+    }
+
+    /// Append samples, evicting the oldest ones beyond the cap.
+    pub fn push(&mut self, samples: &[i16]) {
+        let incoming = &samples[samples.len().saturating_sub(self.max_samples)..];
+        let excess = (self.samples.len() + incoming.len()).saturating_sub(self.max_samples);
+        self.samples.drain(..excess);
+        self.samples.extend(incoming);
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Copy of the buffered samples, oldest first.
+    pub fn to_vec(&self) -> Vec<i16> {
+        self.samples.iter().copied().collect()
+    }
+}
+
+/// Samples needed to hold `secs` of mono audio at `sample_rate`.
+pub fn samples_for(secs: u32, sample_rate: u32) -> usize {
+    secs as usize * sample_rate as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_below_cap_keeps_everything() {
+        let mut buf = ReplayBuffer::new(10);
+        buf.push(&[1, 2, 3]);
+        buf.push(&[4, 5]);
+        assert_eq!(buf.to_vec(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn push_past_cap_drops_oldest() {
+        let mut buf = ReplayBuffer::new(5);
+        buf.push(&[1, 2, 3, 4]);
+        buf.push(&[5, 6, 7]);
+        assert_eq!(buf.to_vec(), vec![3, 4, 5, 6, 7]);
+        buf.push(&[8]);
+        assert_eq!(buf.to_vec(), vec![4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn oversized_chunk_keeps_its_tail() {
+        let mut buf = ReplayBuffer::new(3);
+        buf.push(&[1]);
+        buf.push(&[2, 3, 4, 5, 6]);
+        assert_eq!(buf.to_vec(), vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn clear_then_reuse() {
+        let mut buf = ReplayBuffer::new(4);
+        buf.push(&[1, 2, 3, 4, 5, 6]);
+        buf.clear();
+        assert!(buf.is_empty());
+        buf.push(&[7, 8]);
+        assert_eq!(buf.to_vec(), vec![7, 8]);
+    }
+
+    #[test]
+    fn shrinking_cap_drops_oldest() {
+        let mut buf = ReplayBuffer::new(6);
+        buf.push(&[1, 2, 3, 4, 5, 6]);
+        buf.set_max_samples(2);
+        assert_eq!(buf.to_vec(), vec![5, 6]);
+        assert_eq!(buf.max_samples(), 2);
+    }
+
+    #[test]
+    fn cap_scales_with_output_rate() {
+        assert_eq!(samples_for(300, 16_000), 4_800_000);
+        assert_eq!(samples_for(300, 48_000), 14_400_000);
+    }
+}
@@ -0,0 +1,549 @@
+//! Full-screen session layout (`--tui-session`).
+//!
+//! The reader thread and the main loop publish [`SessionEvent`]s instead of
+//! printing the conversation; everything else still written to stderr (log
+//! lines, prompts, feedback history) is captured through a pipe and shown in
+//! the conversation pane with its colors. [`SessionView`] is the view model:
+//! the render thread only draws what it holds.
+
+use anyhow::{Result, bail};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use crossterm::{cursor, execute, terminal};
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use std::fs::File;
+use std::io::{BufRead, BufReader, IsTerminal, Stdout};
+use std::os::fd::FromRawFd;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use space_lt_common::warn;
+
+/// Lines kept in the conversation pane.
+const MAX_LINES: usize = 2000;
+/// Redraw interval while no event arrives.
+const RENDER_TICK: Duration = Duration::from_millis(100);
+/// Rows scrolled by PageUp / PageDown.
+pub const SCROLL_STEP: i32 = 10;
+/// Bottom of the status bar's mic level in dBFS; the top is 0 dBFS.
+const MIC_FLOOR_DBFS: f32 = -60.0;
+const MIC_BAR_WIDTH: usize = 10;
+
+const KEY_HINTS: &str = "[hotkey] talk  [t] type  [3] replay  [l] translate  [x] simpler  [h] history  [PgUp/PgDn] scroll  [q] quit";
+
+/// Something the session shows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    /// The transcription of the user's turn.
+    User(String),
+    /// The tutor's reply.
+    Ai(String),
+    /// A feedback block, as `feedback_block` formats it.
+    Feedback(Vec<String>),
+    /// Any other output line, ANSI colors included.
+    Log(String),
+    Listening(bool),
+    Speaking(bool),
+    /// Waiting for a reply (with the server's latest status), or not.
+    Thinking(Option<String>),
+    /// Microphone level in dBFS, while listening.
+    MicLevel(f32),
+    /// Scroll the conversation by rows (positive goes back).
+    Scroll(i32),
+}
+
+/// Where the session's conversation goes: printed as before, or published to
+/// the session view. Events also go to the control socket when it is on.
+#[derive(Clone, Default)]
+pub struct SessionOutput {
+    tx: Option<Sender<SessionEvent>>,
+    control: Option<Sender<SessionEvent>>,
+}
+
+impl SessionOutput {
+    /// Plain output on stderr (the default).
+    pub fn classic() -> Self {
+        Self::default()
+    }
+
+    pub fn is_view(&self) -> bool {
+        self.tx.is_some()
+    }
+
+    /// The same output, with its events copied to `control`.
+    pub fn with_control(self, control: Sender<SessionEvent>) -> Self {
+        Self {
+            control: Some(control),
+            ..self
+        }
+    }
+
+    /// Publish `event`; does nothing with classic output and no control socket.
+    pub fn send(&self, event: SessionEvent) {
+        if let Some(control) = &self.control {
+            let _ = control.send(event.clone());
+        }
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(event);
+        }
+    }
+}
+
+/// What the session view shows.
+#[derive(Debug, Default)]
+pub struct SessionView {
+    lines: Vec<Line<'static>>,
+    listening: bool,
+    speaking: bool,
+    thinking: Option<String>,
+    mic_dbfs: Option<f32>,
+    /// Rows scrolled back from the newest one.
+    scroll: usize,
+}
+
+impl SessionView {
+    pub fn apply(&mut self, event: SessionEvent) {
+        match event {
+            SessionEvent::User(text) => self.push(Line::from(vec![
+                Span::styled("You: ", Style::new().fg(Color::Green).bold()),
+                Span::raw(text),
+            ])),
+            SessionEvent::Ai(text) => self.push(Line::from(vec![
+                Span::styled("AI: ", Style::new().fg(Color::Cyan).bold()),
+                Span::raw(text),
+            ])),
+            SessionEvent::Feedback(lines) => {
+                for line in lines {
+                    self.push(parse_ansi(&line));
+                }
+            }
+            SessionEvent::Log(line) => self.push(parse_ansi(&line)),
+            SessionEvent::Listening(listening) => {
+                self.listening = listening;
+                self.mic_dbfs = None;
+                if listening {
+                    // A new turn supersedes the one waiting for a reply
+                    self.thinking = None;
+                }
+            }
+            SessionEvent::Speaking(speaking) => self.speaking = speaking,
+            SessionEvent::Thinking(label) => self.thinking = label,
+            SessionEvent::MicLevel(dbfs) => self.mic_dbfs = Some(dbfs),
+            SessionEvent::Scroll(rows) => {
+                self.scroll = self.scroll.saturating_add_signed(rows as isize);
+            }
+        }
+    }
+
+    fn push(&mut self, line: Line<'static>) {
+        self.lines.push(line);
+        if self.lines.len() > MAX_LINES {
+            self.lines.drain(..self.lines.len() - MAX_LINES);
+        }
+    }
+
+    /// The status bar: listening (with the mic level), speaking, thinking or idle.
+    pub fn status(&self) -> String {
+        if self.listening {
+            let mut status = "\u{25cf} Listening".to_string();
+            if let Some(dbfs) = self.mic_dbfs {
+                let fraction = (dbfs - MIC_FLOOR_DBFS) / -MIC_FLOOR_DBFS;
+                status.push_str(&format!(
+                    "  mic [{}] {dbfs:.0} dB",
+                    crate::meter_bar(fraction, MIC_BAR_WIDTH)
+                ));
+            }
+            status
+        } else if self.speaking {
+            "\u{266a} Speaking".to_string()
+        } else if let Some(label) = &self.thinking {
+            format!("\u{2026} {}", label.trim())
+        } else {
+            "Idle".to_string()
+        }
+    }
+
+    /// The conversation rows that fit `width` x `height`, wrapped, oldest
+    /// first. Scrolling back stops at the first row.
+    pub fn rows(&mut self, width: usize, height: usize) -> Vec<Line<'static>> {
+        let rows: Vec<Line<'static>> = self
+            .lines
+            .iter()
+            .flat_map(|line| wrap(line, width))
+            .collect();
+        self.scroll = self.scroll.min(rows.len().saturating_sub(height));
+        let end = rows.len() - self.scroll;
+        rows[end.saturating_sub(height)..end].to_vec()
+    }
+
+    /// The conversation as plain text, to leave in the terminal afterwards.
+    pub fn transcript(&self) -> impl Iterator<Item = String> + '_ {
+        self.lines.iter().map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        })
+    }
+}
+
+/// Split `line` into rows of at most `width` characters.
+fn wrap(line: &Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let width = width.max(1);
+    let mut rows = vec![Line::default()];
+    let mut used = 0;
+    for span in &line.spans {
+        let mut rest: &str = &span.content;
+        while !rest.is_empty() {
+            if used == width {
+                rows.push(Line::default());
+                used = 0;
+            }
+            let take = rest
+                .char_indices()
+                .nth(width - used)
+                .map_or(rest.len(), |(i, _)| i);
+            let (head, tail) = rest.split_at(take);
+            rows.last_mut()
+                .unwrap()
+                .spans
+                .push(Span::styled(head.to_string(), span.style));
+            used += head.chars().count();
+            rest = tail;
+        }
+    }
+    rows
+}
+
+/// A line printed for the terminal, with its SGR colors turned into styles
+/// (other escape sequences and carriage returns are dropped).
+pub fn parse_ansi(text: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut style = Style::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                let mut params = String::new();
+                let mut end = None;
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        end = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if end != Some('m') {
+                    continue;
+                }
+                if !current.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut current), style));
+                }
+                style = apply_sgr(style, &params);
+            }
+            '\r' => {}
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        spans.push(Span::styled(current, style));
+    }
+    Line::from(spans)
+}
+
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    for param in params.split(';') {
+        style = match param {
+            "" | "0" => Style::new(),
+            "1" => style.add_modifier(Modifier::BOLD),
+            "2" => style.add_modifier(Modifier::DIM),
+            "3" => style.add_modifier(Modifier::ITALIC),
+            "31" => style.fg(Color::Red),
+            "32" => style.fg(Color::Green),
+            "33" => style.fg(Color::Yellow),
+            "34" => style.fg(Color::Blue),
+            "35" => style.fg(Color::Magenta),
+            "36" => style.fg(Color::Cyan),
+            "39" => style.fg(Color::Reset),
+            _ => style,
+        };
+    }
+    style
+}
+
+/// The running session view: the render thread, and stderr captured into it.
+/// Dropping it puts the terminal back and prints the conversation.
+pub struct SessionScreen {
+    stop: Arc<AtomicBool>,
+    render: Option<JoinHandle<SessionView>>,
+    capture: Option<JoinHandle<()>>,
+    /// The terminal's stderr, put back at the end.
+    saved_stderr: libc::c_int,
+}
+
+/// Switch to the session view. Needs stdout to be a terminal.
+pub fn start() -> Result<(SessionOutput, SessionScreen)> {
+    if !std::io::stdout().is_terminal() {
+        bail!("--tui-session needs a terminal");
+    }
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let (pipe, saved_stderr) = capture_stderr()?;
+
+    let mut stdout = std::io::stdout();
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        leave_screen();
+        previous(info);
+    }));
+    let terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let capture_tx = tx.clone();
+    let capture = std::thread::Builder::new()
+        .name("stderr_capture".into())
+        .spawn(move || capture_loop(pipe, capture_tx))?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let render_stop = stop.clone();
+    let render = std::thread::Builder::new()
+        .name("session_view".into())
+        .spawn(move || render_loop(rx, render_stop, terminal))?;
+
+    Ok((
+        SessionOutput {
+            tx: Some(tx),
+            control: None,
+        },
+        SessionScreen {
+            stop,
+            render: Some(render),
+            capture: Some(capture),
+            saved_stderr,
+        },
+    ))
+}
+
+/// Point stderr at a pipe; returns its read end and the original stderr.
+fn capture_stderr() -> Result<(File, libc::c_int)> {
+    let mut fds = [0; 2];
+    // SAFETY: pipe writes two new descriptors into `fds` on success.
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        bail!("pipe: {}", std::io::Error::last_os_error());
+    }
+    // SAFETY: plain descriptor operations on descriptors this function owns
+    // (and stderr, which stays open throughout).
+    unsafe {
+        let saved = libc::dup(libc::STDERR_FILENO);
+        if saved < 0 || libc::dup2(fds[1], libc::STDERR_FILENO) < 0 {
+            let err = std::io::Error::last_os_error();
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+            bail!("redirecting stderr: {err}");
+        }
+        libc::close(fds[1]);
+        Ok((File::from_raw_fd(fds[0]), saved))
+    }
+}
+
+fn capture_loop(pipe: File, tx: Sender<SessionEvent>) {
+    let mut reader = BufReader::new(pipe);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf);
+                let _ = tx.send(SessionEvent::Log(line.trim_end().to_string()));
+            }
+        }
+    }
+}
+
+fn render_loop(
+    rx: Receiver<SessionEvent>,
+    stop: Arc<AtomicBool>,
+    mut terminal: Terminal<CrosstermBackend<Stdout>>,
+) -> SessionView {
+    let mut view = SessionView::default();
+    loop {
+        match rx.recv_timeout(RENDER_TICK) {
+            Ok(event) => view.apply(event),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        while let Ok(event) = rx.try_recv() {
+            view.apply(event);
+        }
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let drawn = terminal.draw(|frame| {
+            let [pane, status, footer] = Layout::vertical([
+                Constraint::Min(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .areas(frame.area());
+            let rows = view.rows(pane.width as usize, pane.height as usize);
+            frame.render_widget(Paragraph::new(rows), pane);
+            frame.render_widget(
+                Paragraph::new(view.status()).style(Style::new().add_modifier(Modifier::REVERSED)),
+                status,
+            );
+            frame.render_widget(
+                Paragraph::new(KEY_HINTS).style(Style::new().add_modifier(Modifier::DIM)),
+                footer,
+            );
+        });
+        if drawn.is_err() {
+            break;
+        }
+    }
+    view
+}
+
+fn leave_screen() {
+    let _ = execute!(
+        std::io::stdout(),
+        terminal::LeaveAlternateScreen,
+        cursor::Show
+    );
+}
+
+impl Drop for SessionScreen {
+    fn drop(&mut self) {
+        // Put stderr back: the capture thread reads what is left, then ends
+        // SAFETY: `saved_stderr` is a descriptor dup'ed by `capture_stderr`.
+        unsafe {
+            libc::dup2(self.saved_stderr, libc::STDERR_FILENO);
+            libc::close(self.saved_stderr);
+        }
+        if let Some(capture) = self.capture.take() {
+            let _ = capture.join();
+        }
+        self.stop.store(true, Ordering::SeqCst);
+        let view = self.render.take().and_then(|render| render.join().ok());
+        leave_screen();
+        match view {
+            Some(view) => {
+                for line in view.transcript() {
+                    eprintln!("{line}");
+                }
+            }
+            None => warn!("[client] The session view stopped unexpectedly"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    fn texts(rows: &[Line]) -> Vec<String> {
+        rows.iter().map(text).collect()
+    }
+
+    #[test]
+    fn turns_and_logs_become_lines() {
+        let mut view = SessionView::default();
+        view.apply(SessionEvent::User("Je suis allé".into()));
+        view.apply(SessionEvent::Ai("Très bien !".into()));
+        view.apply(SessionEvent::Log("\x1b[33mWARNING:\x1b[0m low disk".into()));
+        let rows = view.rows(80, 10);
+        assert_eq!(
+            texts(&rows),
+            ["You: Je suis allé", "AI: Très bien !", "WARNING: low disk"]
+        );
+        assert_eq!(rows[0].spans[0].style.fg, Some(Color::Green));
+        assert_eq!(rows[1].spans[0].style.fg, Some(Color::Cyan));
+        assert_eq!(rows[2].spans[0].style.fg, Some(Color::Yellow));
+        assert_eq!(rows[2].spans[1].style, Style::new());
+    }
+
+    #[test]
+    fn feedback_keeps_its_colors() {
+        let mut view = SessionView::default();
+        view.apply(SessionEvent::Feedback(vec![
+            "\x1b[2m--- feedback ---\x1b[0m".into(),
+            "  \x1b[31m\u{2717} suis allé \u{2192} suis allée\x1b[0m".into(),
+            "  \x1b[34m\u{279c} nicer\x1b[0m".into(),
+            "  \x1b[32m\u{2713}\x1b[0m Je \x1b[32msuis allée\x1b[0m".into(),
+        ]));
+        let rows = view.rows(80, 10);
+        assert!(rows[0].spans[0].style.add_modifier.contains(Modifier::DIM));
+        assert_eq!(rows[1].spans[1].style.fg, Some(Color::Red));
+        assert_eq!(rows[2].spans[1].style.fg, Some(Color::Blue));
+        assert_eq!(text(&rows[3]), "  \u{2713} Je suis allée");
+        assert_eq!(rows[3].spans[3].style.fg, Some(Color::Green));
+    }
+
+    #[test]
+    fn ansi_parsing_combines_and_resets_styles() {
+        let line = parse_ansi("\r\x1b[2K  \x1b[2;3mthinking\x1b[0m done");
+        assert_eq!(text(&line), "  thinking done");
+        let italic_dim = Style::new()
+            .add_modifier(Modifier::DIM)
+            .add_modifier(Modifier::ITALIC);
+        assert_eq!(line.spans[1].style, italic_dim);
+        assert_eq!(line.spans[2].style, Style::new());
+        assert_eq!(
+            parse_ansi("\x1b[1;33m\u{26a0} x").spans[0].style,
+            Style::new().bold().fg(Color::Yellow)
+        );
+    }
+
+    #[test]
+    fn status_follows_the_session_state() {
+        let mut view = SessionView::default();
+        assert_eq!(view.status(), "Idle");
+        view.apply(SessionEvent::Thinking(Some("Searching the web... ".into())));
+        assert_eq!(view.status(), "\u{2026} Searching the web...");
+        view.apply(SessionEvent::Speaking(true));
+        assert_eq!(view.status(), "\u{266a} Speaking");
+        view.apply(SessionEvent::Listening(true));
+        assert_eq!(view.status(), "\u{25cf} Listening");
+        view.apply(SessionEvent::MicLevel(-30.0));
+        assert!(view.status().ends_with("] -30 dB"));
+        // Listening superseded the wait
+        view.apply(SessionEvent::Speaking(false));
+        view.apply(SessionEvent::Listening(false));
+        assert_eq!(view.status(), "Idle");
+    }
+
+    #[test]
+    fn long_lines_wrap_and_scrolling_stays_in_range() {
+        let mut view = SessionView::default();
+        view.apply(SessionEvent::Ai("abcdefgh".into()));
+        for i in 1..=5 {
+            view.apply(SessionEvent::Log(format!("line {i}")));
+        }
+        assert_eq!(texts(&view.rows(6, 20))[..2], ["AI: ab", "cdefgh"]);
+        assert_eq!(texts(&view.rows(80, 2)), ["line 4", "line 5"]);
+        view.apply(SessionEvent::Scroll(SCROLL_STEP));
+        // Stops at the first row
+        assert_eq!(texts(&view.rows(80, 2)), ["AI: abcdefgh", "line 1"]);
+        view.apply(SessionEvent::Scroll(-1));
+        assert_eq!(texts(&view.rows(80, 2)), ["line 1", "line 2"]);
+        view.apply(SessionEvent::Scroll(-SCROLL_STEP));
+        assert_eq!(texts(&view.rows(80, 2)), ["line 4", "line 5"]);
+    }
+
+    #[test]
+    fn classic_output_publishes_nothing() {
+        let output = SessionOutput::classic();
+        assert!(!output.is_view());
+        output.send(SessionEvent::Listening(true));
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use space_lt_common::warn;
+
+/// Default playback gain in percent (unity).
+pub const DEFAULT_VOLUME: u32 = 100;
+/// Upper bound for the playback gain in percent.
+pub const MAX_VOLUME: u32 = 200;
+
+/// Client preferences persisted between sessions.
+///
+/// Stored as `key = value` lines in `~/.config/space_lt/client.conf`
+/// (or under `$XDG_CONFIG_HOME` when set). Unknown keys are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientSettings {
+    /// Playback gain in percent, 0..=MAX_VOLUME.
+    pub volume: u32,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            volume: DEFAULT_VOLUME,
+        }
+    }
+}
+
+impl ClientSettings {
+    /// Load settings from the config file, falling back to defaults if it is
+    /// missing or unreadable.
+    pub fn load() -> Self {
+        let path = settings_path();
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!("[client] Could not read {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Write settings to the config file, creating its directory if needed.
+    pub fn save(&self) -> Result<()> {
+        let path = settings_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        std::fs::write(&path, self.serialize())
+            .with_context(|| format!("writing {}", path.display()))
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut settings = Self::default();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            if key.trim() == "volume" {
+                match value.trim().parse::<u32>() {
+                    Ok(v) => settings.volume = v.min(MAX_VOLUME),
+                    Err(_) => warn!("[client] Ignoring invalid volume '{}'", value.trim()),
+                }
+            }
+        }
+        settings
+    }
+
+    fn serialize(&self) -> String {
+        format!("volume = {}\n", self.volume)
+    }
+}
+
+fn settings_path() -> PathBuf {
+    space_lt_common::paths::config_dir().join("client.conf")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_roundtrip() {
+        let settings = ClientSettings { volume: 150 };
+        assert_eq!(ClientSettings::parse(&settings.serialize()), settings);
+    }
+
+    #[test]
+    fn parse_ignores_comments_unknown_keys_and_bad_values() {
+        let s = ClientSettings::parse("# comment\nfoo = bar\nvolume = loud\n");
+        assert_eq!(s, ClientSettings::default());
+    }
+
+    #[test]
+    fn parse_clamps_volume() {
+        assert_eq!(ClientSettings::parse("volume = 900").volume, MAX_VOLUME);
+    }
+}
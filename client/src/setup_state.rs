@@ -0,0 +1,162 @@
+use anyhow::{Context, Result};
+use evdev::KeyCode;
+use std::path::PathBuf;
+
+use crate::hotkey::HotkeyMode;
+use crate::tui::{SetupConfig, VoiceMode};
+use crate::vad::VadPreset;
+
+/// The choices made at the last setup, offered as the defaults of the next one.
+///
+/// Stored as `key = value` lines in `setup.state`, next to `client.conf`.
+/// Choices are kept by value, not by row, so they still land on the right row
+/// when a list changes between versions. A missing, unreadable or corrupt
+/// file (or line) gives today's defaults, without a warning: nothing is lost.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SetupState {
+    pub server_addr: Option<String>,
+    pub hotkey: Option<KeyCode>,
+    /// The voice mode row: Hold listens like Manual.
+    pub mode: Option<(VoiceMode, HotkeyMode)>,
+    /// Pause tolerance, last picked in Auto mode.
+    pub vad_preset: Option<VadPreset>,
+}
+
+impl SetupState {
+    pub fn load() -> Self {
+        std::fs::read_to_string(state_path())
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = state_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        std::fs::write(&path, self.serialize())
+            .with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Keep the choices of a finished setup. Manual mode asks for no pause
+    /// tolerance, so the last Auto one is kept.
+    pub fn remember(&mut self, config: &SetupConfig) {
+        self.server_addr = Some(config.server_addr.clone());
+        self.hotkey = Some(config.hotkey);
+        self.mode = Some((config.voice_mode, config.hotkey_mode));
+        if config.voice_mode == VoiceMode::Auto {
+            self.vad_preset = Some(config.vad_preset);
+        }
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut state = Self::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "server" if !value.is_empty() => state.server_addr = Some(value.to_string()),
+                "hotkey" => state.hotkey = value.parse().ok().map(KeyCode::new),
+                "mode" => {
+                    state.mode = match value {
+                        "manual" => Some((VoiceMode::Manual, HotkeyMode::Toggle)),
+                        "auto" => Some((VoiceMode::Auto, HotkeyMode::Toggle)),
+                        "hold" => Some((VoiceMode::Manual, HotkeyMode::Hold)),
+                        _ => None,
+                    }
+                }
+                "vad_preset" => state.vad_preset = VadPreset::parse(value).ok(),
+                _ => {}
+            }
+        }
+        state
+    }
+
+    fn serialize(&self) -> String {
+        let mut out = String::new();
+        if let Some(server_addr) = &self.server_addr {
+            out.push_str(&format!("server = {server_addr}\n"));
+        }
+        if let Some(hotkey) = self.hotkey {
+            out.push_str(&format!("hotkey = {}\n", hotkey.code()));
+        }
+        if let Some(mode) = self.mode {
+            let mode = match mode {
+                (_, HotkeyMode::Hold) => "hold",
+                (VoiceMode::Manual, _) => "manual",
+                (VoiceMode::Auto, _) => "auto",
+            };
+            out.push_str(&format!("mode = {mode}\n"));
+        }
+        if let Some(preset) = self.vad_preset {
+            out.push_str(&format!("vad_preset = {}\n", preset.name()));
+        }
+        out
+    }
+}
+
+/// Row of `last` in `choices`, or `None` when there is no last choice or the
+/// list no longer offers it.
+pub fn choice_index<T: PartialEq>(choices: &[T], last: Option<T>) -> Option<usize> {
+    let last = last?;
+    choices.iter().position(|choice| *choice == last)
+}
+
+fn state_path() -> PathBuf {
+    space_lt_common::paths::config_dir().join("setup.state")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_round_trips() {
+        let state = SetupState {
+            server_addr: Some("10.0.0.2:9500".to_string()),
+            hotkey: Some(KeyCode::KEY_F9),
+            mode: Some((VoiceMode::Manual, HotkeyMode::Hold)),
+            vad_preset: Some(VadPreset::Relaxed),
+        };
+        assert_eq!(SetupState::parse(&state.serialize()), state);
+        assert_eq!(
+            SetupState::parse(&SetupState::default().serialize()),
+            SetupState::default()
+        );
+    }
+
+    #[test]
+    fn corrupt_lines_fall_back_to_defaults() {
+        let state = SetupState::parse(
+            "\u{0}garbage\nhotkey = F9\nmode = sing\nserver =\nvad_preset = auto = x\n",
+        );
+        assert_eq!(state, SetupState::default());
+        // Good lines survive bad ones
+        let state = SetupState::parse("mode = ???\nhotkey = 60\n");
+        assert_eq!(state.hotkey, Some(KeyCode::KEY_F2));
+        assert_eq!(state.mode, None);
+    }
+
+    #[test]
+    fn choices_map_to_rows_by_value() {
+        let modes = [
+            (VoiceMode::Manual, HotkeyMode::Toggle),
+            (VoiceMode::Auto, HotkeyMode::Toggle),
+            (VoiceMode::Manual, HotkeyMode::Hold),
+        ];
+        let hold = Some((VoiceMode::Manual, HotkeyMode::Hold));
+        assert_eq!(choice_index(&modes, hold), Some(2));
+        // The terminal hotkey offers no Hold row
+        assert_eq!(choice_index(&modes[..2], hold), None);
+        assert_eq!(choice_index(&modes[..2], None), None);
+        // A row added in front moves the choice with it
+        let presets = [
+            VadPreset::Aggressive,
+            VadPreset::Relaxed,
+            VadPreset::Default,
+        ];
+        assert_eq!(choice_index(&presets, Some(VadPreset::Relaxed)), Some(1));
+    }
+}
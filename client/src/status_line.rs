@@ -0,0 +1,185 @@
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+use space_lt_common::style::palette;
+use space_lt_common::warn;
+
+/// Whether something is currently drawn on the status line.
+static DRAWN: Mutex<bool> = Mutex::new(false);
+
+/// The status line is only drawn when stderr is a terminal.
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| std::io::stderr().is_terminal())
+}
+
+/// Overwrite the in-place status line (mic meter, wait spinner) with `text`.
+pub fn draw(text: &str) {
+    if !enabled() {
+        return;
+    }
+    let mut drawn = DRAWN.lock().unwrap_or_else(|e| e.into_inner());
+    eprint!("\r\x1b[2K{text}");
+    let _ = std::io::stderr().flush();
+    *drawn = true;
+}
+
+/// Erase the status line so a regular log line can be printed.
+pub fn clear() {
+    let mut drawn = DRAWN.lock().unwrap_or_else(|e| e.into_inner());
+    if *drawn {
+        eprint!("\r\x1b[2K");
+        let _ = std::io::stderr().flush();
+        *drawn = false;
+    }
+}
+
+/// Label shown until a `StatusNotification` replaces it.
+pub const DEFAULT_WAIT_LABEL: &str = "thinking\u{2026}";
+const SPINNER: [char; 10] = [
+    '\u{280b}', '\u{2819}', '\u{2839}', '\u{2838}', '\u{283c}', '\u{2834}', '\u{2826}', '\u{2827}',
+    '\u{2807}', '\u{280f}',
+];
+const TICK: Duration = Duration::from_millis(120);
+
+struct Waiting {
+    label: String,
+    since: Instant,
+}
+
+/// Spinner with elapsed time shown while waiting for the reply to a turn.
+///
+/// Owned by `tcp_reader_loop`, which starts it on the "You: …" echo and stops it
+/// before printing anything else. A timer thread redraws the line; it holds the
+/// state lock while drawing, so a `stop` never interleaves with a redraw.
+#[derive(Clone)]
+pub struct WaitIndicator {
+    state: Arc<Mutex<Option<Waiting>>>,
+}
+
+impl WaitIndicator {
+    /// Create the indicator and its timer thread, which exits on `shutdown`.
+    pub fn spawn(shutdown: Arc<AtomicBool>) -> Self {
+        let state = Arc::new(Mutex::new(None));
+        let weak = Arc::downgrade(&state);
+        if let Err(e) = std::thread::Builder::new()
+            .name("wait_ticker".into())
+            .spawn(move || ticker_loop(weak, shutdown))
+        {
+            warn!("[client] Failed to start wait indicator: {e}");
+        }
+        Self { state }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Waiting>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start (or keep) waiting; the elapsed time counts from the first call.
+    pub fn start(&self) {
+        if !enabled() {
+            return;
+        }
+        let mut state = self.lock();
+        if state.is_none() {
+            *state = Some(Waiting {
+                label: DEFAULT_WAIT_LABEL.to_string(),
+                since: Instant::now(),
+            });
+        }
+    }
+
+    /// Show a status notification on the spinner line.
+    /// Returns `false` when not waiting, in which case the caller prints it.
+    pub fn show_status(&self, text: &str) -> bool {
+        match self.lock().as_mut() {
+            Some(waiting) => {
+                waiting.label = text.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop waiting and erase the spinner line.
+    pub fn stop(&self) {
+        let mut state = self.lock();
+        if state.take().is_some() {
+            clear();
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.lock().is_some()
+    }
+}
+
+fn ticker_loop(state: Weak<Mutex<Option<Waiting>>>, shutdown: Arc<AtomicBool>) {
+    let mut frame = 0;
+    loop {
+        std::thread::sleep(TICK);
+        let Some(state) = state.upgrade() else {
+            break;
+        };
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        if shutdown.load(Ordering::SeqCst) {
+            if state.take().is_some() {
+                clear();
+            }
+            break;
+        }
+        if let Some(waiting) = state.as_ref() {
+            draw(&format_wait_line(
+                frame,
+                &waiting.label,
+                waiting.since.elapsed(),
+            ));
+            frame += 1;
+        }
+    }
+}
+
+/// Render the spinner line, e.g. "⠹ thinking… 7s".
+fn format_wait_line(frame: usize, label: &str, elapsed: Duration) -> String {
+    let line = format!(
+        "{} {} {}s",
+        SPINNER[frame % SPINNER.len()],
+        label.trim(),
+        elapsed.as_secs()
+    );
+    format!("  {}", palette().dim_italic(&line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wait_line_shows_label_and_whole_seconds() {
+        let line = format_wait_line(0, DEFAULT_WAIT_LABEL, Duration::from_millis(7900));
+        assert!(line.contains("\u{280b} thinking\u{2026} 7s"));
+        let line = format_wait_line(11, " Searching the web... ", Duration::from_secs(12));
+        assert!(line.contains("\u{2819} Searching the web... 12s"));
+    }
+
+    #[test]
+    fn status_is_only_absorbed_while_waiting() {
+        let indicator = WaitIndicator {
+            state: Arc::default(),
+        };
+        assert!(!indicator.show_status("Thinking..."));
+        *indicator.lock() = Some(Waiting {
+            label: DEFAULT_WAIT_LABEL.to_string(),
+            since: Instant::now(),
+        });
+        assert!(indicator.show_status("Searching the web..."));
+        assert_eq!(
+            indicator.lock().as_ref().unwrap().label,
+            "Searching the web..."
+        );
+        indicator.stop();
+        assert!(!indicator.is_active());
+    }
+}
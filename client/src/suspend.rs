@@ -0,0 +1,343 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use space_lt_common::{debug, warn};
+
+/// Set by the SIGTSTP handler (or a Ctrl+Z key seen in raw mode).
+static SUSPEND_REQUESTED: AtomicBool = AtomicBool::new(false);
+/// Set by the SIGCONT handler; covers stops the client did not initiate.
+static CONTINUED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_sigtstp(_: libc::c_int) {
+    SUSPEND_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn on_sigcont(_: libc::c_int) {
+    CONTINUED.store(true, Ordering::SeqCst);
+}
+
+/// Catch SIGTSTP and SIGCONT so the main loop can suspend cleanly.
+///
+/// The handlers only set flags; the actual work happens in [`suspend`].
+pub fn install_handlers() -> Result<()> {
+    let handlers: [(libc::c_int, extern "C" fn(libc::c_int)); 2] =
+        [(libc::SIGTSTP, on_sigtstp), (libc::SIGCONT, on_sigcont)];
+    for (signal, handler) in handlers {
+        // SAFETY: the handlers only touch atomics, which is async-signal-safe.
+        let rc = unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handler as usize;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(signal, &action, std::ptr::null_mut())
+        };
+        if rc != 0 {
+            anyhow::bail!(
+                "installing handler for signal {signal}: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Ask the main loop to suspend. Used for Ctrl+Z seen as a key in raw mode,
+/// where the terminal does not turn it into SIGTSTP.
+pub fn request() {
+    SUSPEND_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Consume a pending suspend request.
+pub fn take_request() -> bool {
+    SUSPEND_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Consume a pending SIGCONT notification.
+pub fn take_continued() -> bool {
+    CONTINUED.swap(false, Ordering::SeqCst)
+}
+
+/// Stop the whole process until SIGCONT (what the default SIGTSTP action does).
+pub fn stop_process() {
+    // SAFETY: raising a signal on ourselves has no memory-safety preconditions.
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+}
+
+/// Terminal input mode that must be released while stopped.
+pub trait TerminalMode {
+    fn is_raw(&self) -> bool;
+    fn set_raw(&mut self, raw: bool) -> Result<()>;
+}
+
+/// An audio stream that is paused while stopped and may need rebuilding after.
+pub trait SuspendableStream {
+    fn name(&self) -> &str;
+    fn pause(&mut self) -> Result<()>;
+    fn resume(&mut self) -> Result<()>;
+    /// Replace the stream with a fresh one (used when `resume` fails).
+    fn rebuild(&mut self) -> Result<()>;
+}
+
+/// What was active when the client was suspended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuspendSnapshot {
+    pub raw_mode: bool,
+    pub listening: bool,
+    /// A prompt (feedback choice, typed text, ...) was waiting for a key.
+    pub prompt_open: bool,
+}
+
+impl SuspendSnapshot {
+    /// Status line printed after resuming so the user knows where they are.
+    pub fn resync_line(&self) -> String {
+        let state = if self.listening {
+            "still listening"
+        } else {
+            "not listening"
+        };
+        let prompt = if self.prompt_open {
+            ", waiting for your choice"
+        } else {
+            ""
+        };
+        format!("[client] Resumed — {state}{prompt}")
+    }
+}
+
+/// Release the terminal and audio streams, run `stop`, then restore them.
+///
+/// `stop` blocks until the process is continued (normally [`stop_process`]).
+pub fn suspend(
+    terminal: &mut dyn TerminalMode,
+    streams: &mut [&mut dyn SuspendableStream],
+    listening: bool,
+    prompt_open: bool,
+    stop: impl FnOnce(),
+) -> SuspendSnapshot {
+    let snapshot = SuspendSnapshot {
+        raw_mode: terminal.is_raw(),
+        listening,
+        prompt_open,
+    };
+
+    if snapshot.raw_mode
+        && let Err(e) = terminal.set_raw(false)
+    {
+        warn!("[client] Could not leave raw mode before suspending: {e}");
+    }
+    pause_streams(streams);
+
+    stop();
+
+    restore(&snapshot, terminal, streams);
+    snapshot
+}
+
+/// Bring the terminal mode and audio streams back after a stop.
+pub fn restore(
+    snapshot: &SuspendSnapshot,
+    terminal: &mut dyn TerminalMode,
+    streams: &mut [&mut dyn SuspendableStream],
+) {
+    if snapshot.raw_mode
+        && let Err(e) = terminal.set_raw(true)
+    {
+        warn!("[client] Could not re-enable raw mode: {e}");
+    }
+    resume_streams(streams);
+}
+
+/// Pause audio streams (errors are only logged: the stream may already be gone).
+pub fn pause_streams(streams: &mut [&mut dyn SuspendableStream]) {
+    for stream in streams.iter_mut() {
+        if let Err(e) = stream.pause() {
+            debug!("[client] Could not pause {} stream: {e}", stream.name());
+        }
+    }
+}
+
+/// Resume audio streams, rebuilding those that do not come back.
+pub fn resume_streams(streams: &mut [&mut dyn SuspendableStream]) {
+    for stream in streams.iter_mut() {
+        if let Err(e) = stream.resume() {
+            warn!(
+                "[client] {} stream did not resume ({e}), rebuilding",
+                stream.name()
+            );
+            if let Err(e) = stream.rebuild() {
+                warn!("[client] Could not rebuild {} stream: {e:#}", stream.name());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    struct MockTerminal {
+        raw: bool,
+        log: Log,
+    }
+
+    impl TerminalMode for MockTerminal {
+        fn is_raw(&self) -> bool {
+            self.raw
+        }
+        fn set_raw(&mut self, raw: bool) -> Result<()> {
+            self.raw = raw;
+            self.log.borrow_mut().push(format!("raw={raw}"));
+            Ok(())
+        }
+    }
+
+    struct MockStream {
+        name: &'static str,
+        resume_fails: bool,
+        log: Log,
+    }
+
+    impl SuspendableStream for MockStream {
+        fn name(&self) -> &str {
+            self.name
+        }
+        fn pause(&mut self) -> Result<()> {
+            self.log.borrow_mut().push(format!("pause {}", self.name));
+            Ok(())
+        }
+        fn resume(&mut self) -> Result<()> {
+            self.log.borrow_mut().push(format!("resume {}", self.name));
+            if self.resume_fails {
+                anyhow::bail!("device lost");
+            }
+            Ok(())
+        }
+        fn rebuild(&mut self) -> Result<()> {
+            self.log.borrow_mut().push(format!("rebuild {}", self.name));
+            Ok(())
+        }
+    }
+
+    fn stream(name: &'static str, resume_fails: bool, log: &Log) -> MockStream {
+        MockStream {
+            name,
+            resume_fails,
+            log: log.clone(),
+        }
+    }
+
+    #[test]
+    fn suspend_from_raw_mode_releases_and_restores_everything() {
+        let log: Log = Rc::default();
+        let mut terminal = MockTerminal {
+            raw: true,
+            log: log.clone(),
+        };
+        let mut capture = stream("capture", false, &log);
+        let mut playback = stream("playback", false, &log);
+
+        let stop_log = log.clone();
+        let snapshot = suspend(
+            &mut terminal,
+            &mut [&mut capture, &mut playback],
+            false,
+            true,
+            || stop_log.borrow_mut().push("stop".into()),
+        );
+
+        assert_eq!(
+            snapshot,
+            SuspendSnapshot {
+                raw_mode: true,
+                listening: false,
+                prompt_open: true,
+            }
+        );
+        assert!(terminal.raw);
+        assert_eq!(
+            *log.borrow(),
+            [
+                "raw=false",
+                "pause capture",
+                "pause playback",
+                "stop",
+                "raw=true",
+                "resume capture",
+                "resume playback",
+            ]
+        );
+    }
+
+    #[test]
+    fn cooked_terminal_is_left_alone() {
+        let log: Log = Rc::default();
+        let mut terminal = MockTerminal {
+            raw: false,
+            log: log.clone(),
+        };
+        let snapshot = suspend(&mut terminal, &mut [], true, false, || {});
+        assert!(!snapshot.raw_mode);
+        assert!(snapshot.listening);
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn failed_resume_rebuilds_stream() {
+        let log: Log = Rc::default();
+        let mut terminal = MockTerminal {
+            raw: false,
+            log: log.clone(),
+        };
+        let mut capture = stream("capture", true, &log);
+        let mut playback = stream("playback", false, &log);
+        suspend(
+            &mut terminal,
+            &mut [&mut capture, &mut playback],
+            false,
+            false,
+            || {},
+        );
+        assert_eq!(
+            *log.borrow(),
+            [
+                "pause capture",
+                "pause playback",
+                "resume capture",
+                "rebuild capture",
+                "resume playback",
+            ]
+        );
+    }
+
+    #[test]
+    fn resync_line_reflects_snapshot() {
+        let idle = SuspendSnapshot {
+            raw_mode: true,
+            listening: false,
+            prompt_open: false,
+        };
+        assert_eq!(idle.resync_line(), "[client] Resumed — not listening");
+        let prompt = SuspendSnapshot {
+            raw_mode: true,
+            listening: true,
+            prompt_open: true,
+        };
+        assert_eq!(
+            prompt.resync_line(),
+            "[client] Resumed — still listening, waiting for your choice"
+        );
+    }
+
+    #[test]
+    fn request_flag_is_consumed_once() {
+        request();
+        assert!(take_request());
+        assert!(!take_request());
+    }
+}
@@ -1,11 +1,21 @@
 use anyhow::{Result, bail};
-use cpal::traits::{DeviceTrait, HostTrait};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use evdev::KeyCode as EvdevKeyCode;
 use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
 use ratatui::style::{Modifier, Style};
-use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
-use std::time::Duration;
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use space_lt_common::transport::TlsClientConfig;
+
+use crate::audio::{self, CaptureChannel};
+use crate::connection::{self, TcpConnection};
+use crate::devices::{self, AudioDevice};
+use crate::hotkey::{self, HotkeyBackend, HotkeyMode, KeyCapture};
+use crate::setup_state::{self, SetupState};
+use crate::vad::VadPreset;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VoiceMode {
@@ -13,105 +23,753 @@ pub enum VoiceMode {
     Auto,   // VAD auto-segmentation on silence (original behavior)
 }
 
+/// How long the microphone test listens.
+const MIC_TEST_DURATION: Duration = Duration::from_secs(4);
+/// Bottom of the microphone test gauge in dBFS; the top is 0 dBFS.
+const MIC_TEST_FLOOR_DBFS: f32 = -60.0;
+/// Loudest level (dBFS) under which speech is too quiet to transcribe well.
+const MIC_TEST_QUIET_DBFS: f32 = -40.0;
+
 pub struct SetupConfig {
     pub server_addr: String,
+    /// The connection the address check opened (Ready received), for the
+    /// session to use; `None` when it was skipped or failed.
+    pub connection: Option<TcpConnection>,
     pub device: cpal::Device,
     pub device_name: String,
+    /// Output device name for TTS playback; `None` follows the system default.
+    pub output_device: Option<String>,
     pub hotkey: EvdevKeyCode,
+    /// Toggle, or Hold when "Hold" was chosen (which listens like Manual).
+    pub hotkey_mode: HotkeyMode,
     pub voice_mode: VoiceMode,
+    /// Auto mode segmentation; `Default` when Manual was chosen.
+    pub vad_preset: VadPreset,
 }
 
-pub fn run_setup() -> Result<SetupConfig> {
-    // Auto-detect default audio input device
-    let host = cpal::default_host();
-    let device = host
-        .default_input_device()
-        .ok_or_else(|| anyhow::anyhow!("No default audio input device found."))?;
-    let device_name = device
-        .description()
-        .map(|d: cpal::DeviceDescription| d.name().to_string())
-        .unwrap_or_else(|_| "Default".into());
-
-    let mut terminal = ratatui::init();
+/// What a setup screen returned.
+#[derive(Debug, Clone, PartialEq)]
+enum ScreenResult<T> {
+    Confirmed(T),
+    /// Esc or Left: back to the previous screen.
+    Back,
+    /// q: quit the setup.
+    Cancelled,
+}
 
-    // Screen 1: Server address input
-    let server_addr = match text_input_screen(&mut terminal, "Server Address", "127.0.0.1:9500") {
-        Ok(t) => t,
-        Err(e) => {
-            ratatui::restore();
-            return Err(e);
+impl<T> ScreenResult<T> {
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> ScreenResult<U> {
+        match self {
+            ScreenResult::Confirmed(value) => ScreenResult::Confirmed(f(value)),
+            ScreenResult::Back => ScreenResult::Back,
+            ScreenResult::Cancelled => ScreenResult::Cancelled,
         }
-    };
+    }
+}
 
-    // Screen 2: Push-to-Talk Key selection
-    let hotkey_choices = vec![
-        "F2".to_string(),
-        "F3".to_string(),
-        "F4".to_string(),
-        "F9".to_string(),
-        "F10".to_string(),
-        "F11".to_string(),
-        "F12".to_string(),
-        "ScrollLock".to_string(),
-        "Pause".to_string(),
+/// The setup screens, in order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SetupStep {
+    Server,
+    /// The device list, then the microphone test.
+    InputDevice,
+    OutputDevice,
+    Hotkey,
+    VoiceMode,
+    PauseTolerance,
+}
+
+impl SetupStep {
+    const ALL: [SetupStep; 6] = [
+        SetupStep::Server,
+        SetupStep::InputDevice,
+        SetupStep::OutputDevice,
+        SetupStep::Hotkey,
+        SetupStep::VoiceMode,
+        SetupStep::PauseTolerance,
     ];
-    let hotkey_idx = match select_screen(&mut terminal, "Select Push-to-Talk Key", &hotkey_choices)
-    {
-        Ok(idx) => idx,
-        Err(e) => {
-            ratatui::restore();
-            return Err(e);
-        }
-    };
+}
+
+/// Where setup goes after a screen.
+#[derive(Debug, PartialEq)]
+enum Flow {
+    Show(SetupStep),
+    Done,
+    Cancel,
+}
+
+/// The step after `current` returned `result`: the next step `shown` allows,
+/// or the previous one on Back. Back on the first shown step cancels.
+fn next_step<T>(
+    current: SetupStep,
+    result: &ScreenResult<T>,
+    shown: impl Fn(SetupStep) -> bool,
+) -> Flow {
+    let idx = SetupStep::ALL
+        .iter()
+        .position(|&step| step == current)
+        .unwrap_or(0);
+    match result {
+        ScreenResult::Confirmed(_) => SetupStep::ALL[idx + 1..]
+            .iter()
+            .copied()
+            .find(|&step| shown(step))
+            .map_or(Flow::Done, Flow::Show),
+        ScreenResult::Back => SetupStep::ALL[..idx]
+            .iter()
+            .rev()
+            .copied()
+            .find(|&step| shown(step))
+            .map_or(Flow::Cancel, Flow::Show),
+        ScreenResult::Cancelled => Flow::Cancel,
+    }
+}
 
-    // Screen 3: Voice Mode selection
-    let mode_choices = vec![
-        "Manual (hotkey controls when to send)".to_string(),
-        "Auto (VAD segments on silence)".to_string(),
+/// Run the setup screens. `server` (`--server`) skips the address screen
+/// and its check, which connects with `tls`,
+/// `input_device` and `output_device` (from
+/// `--input-device` and `--output-device`) skip the matching device screen,
+/// `vad_preset` (`--vad-preset`) the sensitivity one. The terminal hotkey
+/// backend skips the key screen ([Space] is the hotkey) and cannot hold.
+/// The `last` setup's choices are the defaults.
+///
+/// Esc (or Left) goes back to the previous screen, which shows the choice
+/// made there; Esc on the first screen and q cancel the setup.
+pub fn run_setup(
+    server: Option<&str>,
+    tls: Option<&Arc<TlsClientConfig>>,
+    input_device: Option<&str>,
+    output_device: Option<&str>,
+    vad_preset: Option<VadPreset>,
+    hotkey_backend: HotkeyBackend,
+    last: &SetupState,
+) -> Result<SetupConfig> {
+    let host = cpal::default_host();
+    let (mut devices, default_idx) = devices::list_input_devices(&host)?;
+    if devices.is_empty() {
+        bail!("No audio input device found.");
+    }
+
+    let preselected = input_device
+        .map(|query| find_input_device(&devices, query))
+        .transpose()?;
+    let input_labels: Vec<String> = devices
+        .iter()
+        .enumerate()
+        .map(|(i, d)| d.label(Some(i) == default_idx))
+        .collect();
+    let input_usable: Vec<bool> = devices.iter().map(|d| d.default_rate.is_some()).collect();
+
+    let mut modes = vec![
+        (VoiceMode::Manual, HotkeyMode::Toggle),
+        (VoiceMode::Auto, HotkeyMode::Toggle),
     ];
-    let mode_idx = match select_screen(&mut terminal, "Select Voice Mode", &mode_choices) {
-        Ok(idx) => idx,
-        Err(e) => {
-            ratatui::restore();
-            return Err(e);
-        }
-    };
+    // Terminals report no key releases
+    if hotkey_backend == HotkeyBackend::Evdev {
+        modes.push((VoiceMode::Manual, HotkeyMode::Hold));
+    }
+    let mode_labels: Vec<String> = modes
+        .iter()
+        .map(|mode| match mode {
+            (_, HotkeyMode::Hold) => "Hold (speak while holding the hotkey)",
+            (VoiceMode::Manual, _) => "Manual (hotkey controls when to send)",
+            (VoiceMode::Auto, _) => "Auto (VAD segments on silence)",
+        })
+        .map(String::from)
+        .collect();
 
+    // Choices so far; flags fill the ones whose screen is skipped
+    let mut server_addr = server.map(str::to_string);
+    let mut connection = None;
+    let mut device_idx = preselected;
+    let mut output_choice = output_device.map(|query| Some(query.to_string()));
+    let mut hotkey = (hotkey_backend == HotkeyBackend::Terminal).then_some(EvdevKeyCode::KEY_SPACE);
+    let mut mode_idx: Option<usize> = None;
+    let mut preset_choice = vad_preset;
+
+    let mut terminal = ratatui::init();
+    let screens = (|| -> Result<()> {
+        let mut step = if server.is_none() {
+            SetupStep::Server
+        } else {
+            SetupStep::InputDevice
+        };
+        loop {
+            let result = match step {
+                // Checked with a quick connect
+                SetupStep::Server => server_address_screen(
+                    &mut terminal,
+                    server_addr.as_deref().unwrap_or_default(),
+                    last.server_addr.as_deref(),
+                    tls,
+                )?
+                .map(|(addr, checked)| {
+                    server_addr = Some(addr);
+                    connection = checked;
+                }),
+                SetupStep::InputDevice => {
+                    let picked = match preselected {
+                        Some(idx) => ScreenResult::Confirmed(idx),
+                        None => select_screen_from(
+                            &mut terminal,
+                            "Select Audio Input",
+                            &input_labels,
+                            device_idx.or(default_idx).unwrap_or(0),
+                            &input_usable,
+                        )?,
+                    };
+                    match picked {
+                        ScreenResult::Confirmed(idx) => {
+                            device_idx = Some(idx);
+                            match mic_test_screen(&mut terminal, &devices[idx].device)? {
+                                // Back from the test picks the device again
+                                ScreenResult::Back if preselected.is_none() => continue,
+                                result => result,
+                            }
+                        }
+                        other => other.map(|_| ()),
+                    }
+                }
+                // Resolved when playback starts
+                SetupStep::OutputDevice => {
+                    let previous = output_choice.clone().flatten();
+                    select_output_device(&mut terminal, &host, previous.as_deref())?
+                        .map(|name| output_choice = Some(name))
+                }
+                // Pressed, or picked from a list when no keyboard can be read
+                SetupStep::Hotkey => {
+                    let initial = hotkey.or(last.hotkey);
+                    match KeyCapture::start() {
+                        Some(capture) => hotkey_capture_screen(&mut terminal, &capture, initial)?,
+                        None => hotkey_list_screen(&mut terminal, initial)?,
+                    }
+                    .map(|key| hotkey = Some(key))
+                }
+                SetupStep::VoiceMode => {
+                    let initial = mode_idx
+                        .or_else(|| setup_state::choice_index(&modes, last.mode))
+                        .unwrap_or(0);
+                    select_screen_from(
+                        &mut terminal,
+                        "Select Voice Mode",
+                        &mode_labels,
+                        initial,
+                        &[],
+                    )?
+                    .map(|idx| mode_idx = Some(idx))
+                }
+                SetupStep::PauseTolerance => {
+                    let labels: Vec<String> = VadPreset::ALL.iter().map(|p| p.label()).collect();
+                    let initial = setup_state::choice_index(
+                        &VadPreset::ALL,
+                        preset_choice.or(last.vad_preset),
+                    )
+                    .or_else(|| {
+                        setup_state::choice_index(&VadPreset::ALL, Some(VadPreset::Default))
+                    })
+                    .unwrap_or(0);
+                    select_screen_from(
+                        &mut terminal,
+                        "Select Pause Tolerance",
+                        &labels,
+                        initial,
+                        &[],
+                    )?
+                    .map(|idx| preset_choice = Some(VadPreset::ALL[idx]))
+                }
+            };
+            let auto = mode_idx.is_some_and(|idx| modes[idx].0 == VoiceMode::Auto);
+            let shown = |step| match step {
+                SetupStep::Server => server.is_none(),
+                SetupStep::InputDevice | SetupStep::VoiceMode => true,
+                SetupStep::OutputDevice => output_device.is_none(),
+                SetupStep::Hotkey => hotkey_backend == HotkeyBackend::Evdev,
+                // Auto mode only, unless --vad-preset chose it
+                SetupStep::PauseTolerance => auto && vad_preset.is_none(),
+            };
+            match next_step(step, &result, shown) {
+                Flow::Show(next) => step = next,
+                Flow::Done => return Ok(()),
+                Flow::Cancel => bail!("Setup cancelled by user."),
+            }
+        }
+    })();
     ratatui::restore();
+    screens?;
 
-    let voice_mode = match mode_idx {
-        0 => VoiceMode::Manual,
-        _ => VoiceMode::Auto,
+    let (Some(server_addr), Some(device_idx), Some(output_device), Some(hotkey), Some(mode_idx)) =
+        (server_addr, device_idx, output_choice, hotkey, mode_idx)
+    else {
+        bail!("Setup ended before every choice was made");
     };
-
-    let hotkey = match hotkey_idx {
-        0 => EvdevKeyCode::KEY_F2,
-        1 => EvdevKeyCode::KEY_F3,
-        2 => EvdevKeyCode::KEY_F4,
-        3 => EvdevKeyCode::KEY_F9,
-        4 => EvdevKeyCode::KEY_F10,
-        5 => EvdevKeyCode::KEY_F11,
-        6 => EvdevKeyCode::KEY_F12,
-        7 => EvdevKeyCode::KEY_SCROLLLOCK,
-        8 => EvdevKeyCode::KEY_PAUSE,
-        _ => EvdevKeyCode::KEY_F2,
+    let AudioDevice {
+        device,
+        name: device_name,
+        ..
+    } = devices.swap_remove(device_idx);
+    let (voice_mode, hotkey_mode) = modes[mode_idx];
+    let vad_preset = match voice_mode {
+        VoiceMode::Manual => vad_preset.unwrap_or(VadPreset::Default),
+        VoiceMode::Auto => preset_choice.unwrap_or(VadPreset::Default),
     };
 
     Ok(SetupConfig {
         server_addr,
+        connection,
         device,
         device_name,
+        output_device,
         hotkey,
+        hotkey_mode,
         voice_mode,
+        vad_preset,
     })
 }
 
+/// Row of the usable input device `query` names.
+fn find_input_device(devices: &[AudioDevice], query: &str) -> Result<usize> {
+    let usable: Vec<usize> = (0..devices.len())
+        .filter(|&i| devices[i].default_rate.is_some())
+        .collect();
+    let names: Vec<&str> = usable.iter().map(|&i| devices[i].name.as_str()).collect();
+    Ok(usable[devices::match_device_name(&names, query, "input")?])
+}
+
+/// The choices of the setup screens, given as flags for a client run without
+/// them (`--no-tui`, or no terminal).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessSetup {
+    pub server_addr: String,
+    pub voice_mode: VoiceMode,
+    pub hotkey_mode: HotkeyMode,
+    pub hotkey: EvdevKeyCode,
+}
+
+impl HeadlessSetup {
+    /// Every flag is required: nothing can be asked.
+    pub fn from_flags(
+        server: Option<&str>,
+        mode: Option<(VoiceMode, HotkeyMode)>,
+        hotkey: Option<EvdevKeyCode>,
+    ) -> Result<Self> {
+        if let (Some(server), Some((voice_mode, hotkey_mode)), Some(hotkey)) =
+            (server, mode, hotkey)
+        {
+            return Ok(Self {
+                server_addr: server.to_string(),
+                voice_mode,
+                hotkey_mode,
+                hotkey,
+            });
+        }
+        let missing: Vec<&str> = [
+            (server.is_none(), "--server"),
+            (mode.is_none(), "--voice-mode"),
+            (hotkey.is_none(), "--hotkey"),
+        ]
+        .into_iter()
+        .filter_map(|(missing, flag)| missing.then_some(flag))
+        .collect();
+        bail!(
+            "Without the setup screens, {} must be given",
+            missing.join(", ")
+        )
+    }
+}
+
+/// Parse `--voice-mode`: "manual", "auto", or "hold" (manual, listening
+/// while the hotkey is held).
+pub fn parse_voice_mode(s: &str) -> Result<(VoiceMode, HotkeyMode)> {
+    match s {
+        "manual" => Ok((VoiceMode::Manual, HotkeyMode::Toggle)),
+        "auto" => Ok((VoiceMode::Auto, HotkeyMode::Toggle)),
+        "hold" => Ok((VoiceMode::Manual, HotkeyMode::Hold)),
+        other => bail!("expected \"manual\", \"auto\" or \"hold\", got \"{other}\""),
+    }
+}
+
+/// The setup from flags alone, without a screen: the microphone is
+/// `input_device`, or the system default.
+pub fn headless_setup(
+    setup: HeadlessSetup,
+    input_device: Option<&str>,
+    output_device: Option<&str>,
+    vad_preset: Option<VadPreset>,
+) -> Result<SetupConfig> {
+    let host = cpal::default_host();
+    let (mut devices, default_idx) = devices::list_input_devices(&host)?;
+    let device_idx = match input_device {
+        Some(query) => find_input_device(&devices, query)?,
+        None => default_idx
+            .filter(|&i| devices[i].default_rate.is_some())
+            .ok_or_else(|| {
+                anyhow::anyhow!("No usable default audio input: pick one with --input-device")
+            })?,
+    };
+    let AudioDevice {
+        device,
+        name: device_name,
+        ..
+    } = devices.swap_remove(device_idx);
+    Ok(SetupConfig {
+        server_addr: setup.server_addr,
+        connection: None,
+        device,
+        device_name,
+        output_device: output_device.map(str::to_string),
+        hotkey: setup.hotkey,
+        hotkey_mode: setup.hotkey_mode,
+        voice_mode: setup.voice_mode,
+        vad_preset: vad_preset.unwrap_or(VadPreset::Default),
+    })
+}
+
+/// Ask for the server address (starting as `typed`) and check it by
+/// connecting; when that fails, offer to edit it or to continue anyway. The
+/// connection is kept for the session: the server takes the first client it
+/// greets as the session's, so a throwaway check would make the real one
+/// look like a second client.
+fn server_address_screen(
+    terminal: &mut ratatui::DefaultTerminal,
+    typed: &str,
+    last: Option<&str>,
+    tls: Option<&Arc<TlsClientConfig>>,
+) -> Result<ScreenResult<(String, Option<TcpConnection>)>> {
+    let title = "Server Address";
+    let placeholder = last.unwrap_or("127.0.0.1:9500");
+    let terminal = std::cell::RefCell::new(terminal);
+    confirm_server_address(
+        typed,
+        |typed| text_input_screen(&mut terminal.borrow_mut(), title, placeholder, typed),
+        |addr| {
+            let text = format!("Checking {addr}...");
+            with_spinner(&mut terminal.borrow_mut(), title, &text, || {
+                TcpConnection::connect_timeout(addr, tls, connection::CHECK_TIMEOUT)
+            })?
+        },
+        |addr, error| {
+            let choices = vec![
+                "Edit the address".to_string(),
+                "Continue anyway".to_string(),
+            ];
+            let title = format!("Cannot reach {addr}: {error:#}");
+            match select_screen_from(&mut terminal.borrow_mut(), &title, &choices, 0, &[])? {
+                ScreenResult::Confirmed(idx) => Ok(idx == 0),
+                ScreenResult::Back => Ok(true),
+                ScreenResult::Cancelled => bail!("Setup cancelled by user."),
+            }
+        },
+    )
+}
+
+/// The address check: `ask` for an address (pre-filled with `typed`, then
+/// with the last one typed), `connect` to it, and on failure let `edit_again`
+/// decide between asking again (`true`) and keeping the address unchecked.
+fn confirm_server_address<T>(
+    typed: &str,
+    mut ask: impl FnMut(&str) -> Result<ScreenResult<String>>,
+    mut connect: impl FnMut(&str) -> Result<T>,
+    mut edit_again: impl FnMut(&str, &anyhow::Error) -> Result<bool>,
+) -> Result<ScreenResult<(String, Option<T>)>> {
+    let mut typed = typed.to_string();
+    loop {
+        let input = match ask(&typed)? {
+            ScreenResult::Confirmed(input) => input,
+            ScreenResult::Back => return Ok(ScreenResult::Back),
+            ScreenResult::Cancelled => return Ok(ScreenResult::Cancelled),
+        };
+        let addr = connection::with_default_port(&input);
+        match connect(&addr) {
+            Ok(connection) => return Ok(ScreenResult::Confirmed((input, Some(connection)))),
+            Err(e) if edit_again(&addr, &e)? => typed = input,
+            Err(_) => return Ok(ScreenResult::Confirmed((input, None))),
+        }
+    }
+}
+
+/// Frames of the spinner shown while waiting.
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Run `work` on another thread, showing `text` with a spinner until it ends.
+fn with_spinner<T: Send>(
+    terminal: &mut ratatui::DefaultTerminal,
+    title: &str,
+    text: &str,
+    work: impl FnOnce() -> T + Send,
+) -> Result<T> {
+    std::thread::scope(|scope| {
+        let handle = scope.spawn(work);
+        let mut frame_idx = 0;
+        while !handle.is_finished() {
+            let spinner = SPINNER[frame_idx % SPINNER.len()];
+            terminal.draw(|frame: &mut Frame| {
+                let paragraph = Paragraph::new(format!("{spinner} {text}")).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!(" {title} ")),
+                );
+                frame.render_widget(paragraph, frame.area());
+            })?;
+            std::thread::sleep(Duration::from_millis(100));
+            frame_idx += 1;
+        }
+        let result = handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("the check panicked"));
+        // Its log lines went to the same terminal: redraw everything
+        terminal.clear()?;
+        result
+    })
+}
+
+/// Show the live level of `device` for `MIC_TEST_DURATION`, then the loudest
+/// level heard. Esc skips the test, Left goes back. The temporary capture
+/// stream is dropped before returning, so the session can open the device
+/// again.
+fn mic_test_screen(
+    terminal: &mut ratatui::DefaultTerminal,
+    device: &cpal::Device,
+) -> Result<ScreenResult<()>> {
+    let title = " Test Microphone (Esc=skip, \u{2190}=back) ";
+    let (tx, rx) = crossbeam_channel::bounded::<Vec<i16>>(64);
+    let stream = match audio::start_capture(device, tx, CaptureChannel::Mix) {
+        Ok((stream, _)) => stream,
+        Err(e) => {
+            let text = format!("Could not open the microphone: {e:#}\n\nEnter=continue");
+            return wait_for_enter(terminal, title, &text);
+        }
+    };
+    let start = Instant::now();
+    let mut level = 0.0;
+    let mut loudest = 0.0_f32;
+    while start.elapsed() < MIC_TEST_DURATION {
+        while let Ok(chunk) = rx.try_recv() {
+            level = audio::level_meter(&chunk);
+            loudest = loudest.max(level);
+        }
+        let left = MIC_TEST_DURATION.saturating_sub(start.elapsed()).as_secs() + 1;
+        terminal.draw(|frame: &mut Frame| {
+            let [gauge, text] =
+                Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(frame.area());
+            let db = audio::to_dbfs(level);
+            frame.render_widget(
+                Gauge::default()
+                    .block(Block::default().borders(Borders::ALL).title(title))
+                    .ratio(mic_test_ratio(level))
+                    .label(format!("{db:.0} dB")),
+                gauge,
+            );
+            frame.render_widget(
+                Paragraph::new(format!("Say a few words... ({left}s)")),
+                text,
+            );
+        })?;
+        if event::poll(Duration::from_millis(50))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Esc => return Ok(ScreenResult::Confirmed(())),
+                KeyCode::Left => return Ok(ScreenResult::Back),
+                _ => {}
+            }
+        }
+    }
+    drop(stream);
+
+    let text = format!("{}\n\nEnter=continue", mic_test_verdict(loudest));
+    wait_for_enter(terminal, title, &text)
+}
+
+/// Show `text` until Enter or Esc (or Left, to go back) is pressed.
+fn wait_for_enter(
+    terminal: &mut ratatui::DefaultTerminal,
+    title: &str,
+    text: &str,
+) -> Result<ScreenResult<()>> {
+    loop {
+        terminal.draw(|frame: &mut Frame| {
+            let paragraph =
+                Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(title));
+            frame.render_widget(paragraph, frame.area());
+        })?;
+        if event::poll(Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => return Ok(ScreenResult::Confirmed(())),
+                KeyCode::Left => return Ok(ScreenResult::Back),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Gauge fill for a [`audio::level_meter`] level: `MIC_TEST_FLOOR_DBFS` is
+/// empty, 0 dBFS full.
+fn mic_test_ratio(level: f32) -> f64 {
+    let db = audio::to_dbfs(level);
+    ((db - MIC_TEST_FLOOR_DBFS) / -MIC_TEST_FLOOR_DBFS).clamp(0.0, 1.0) as f64
+}
+
+/// The test result for the loudest level heard.
+fn mic_test_verdict(loudest: f32) -> String {
+    let db = audio::to_dbfs(loudest);
+    if db < MIC_TEST_QUIET_DBFS {
+        format!("Loudest: {db:.0} dB \u{2014} very quiet, check your mic")
+    } else {
+        format!("Loudest: {db:.0} dB \u{2014} OK")
+    }
+}
+
+/// Output device screen. Returns the picked device's name, or `None` for the
+/// default one (so playback keeps following the default) or when the devices
+/// cannot be listed.
+/// Ask for the push-to-talk key to be pressed, show it and ask for
+/// confirmation. Esc goes back, like on the other screens, so it is never
+/// taken as the hotkey. With a `last` key, Enter keeps it.
+fn hotkey_capture_screen(
+    terminal: &mut ratatui::DefaultTerminal,
+    capture: &KeyCapture,
+    last: Option<EvdevKeyCode>,
+) -> Result<ScreenResult<EvdevKeyCode>> {
+    let title = " Push-to-Talk Key (Esc=back) ";
+    let mut prompt = "Press the key you want to use for push-to-talk.".to_string();
+    if let Some(last) = last {
+        prompt.push_str(&format!("\n\nEnter=keep {}", hotkey::key_name(last)));
+    }
+    loop {
+        let key = loop {
+            terminal.draw(|frame: &mut Frame| {
+                let paragraph = Paragraph::new(prompt.as_str())
+                    .block(Block::default().borders(Borders::ALL).title(title));
+                frame.render_widget(paragraph, frame.area());
+            })?;
+            if let Some(key) = capture.try_next() {
+                if key == EvdevKeyCode::KEY_ENTER
+                    && let Some(last) = last
+                {
+                    drain_terminal_events()?;
+                    return Ok(ScreenResult::Confirmed(last));
+                }
+                break key;
+            }
+            // The terminal sees the press too
+            if event::poll(Duration::from_millis(50))?
+                && let Event::Key(key) = event::read()?
+                && key.code == KeyCode::Esc
+            {
+                return Ok(ScreenResult::Back);
+            }
+        };
+        let warning = match hotkey::check_hotkey(key) {
+            Ok(warning) => warning,
+            Err(_) => {
+                drain_terminal_events()?;
+                return Ok(ScreenResult::Back);
+            }
+        };
+        drain_terminal_events()?;
+
+        let mut text = format!("Detected: {} ({key:?})\n", hotkey::key_name(key));
+        if let Some(warning) = warning {
+            text.push_str(&format!("\n\u{26a0} {warning}\n"));
+        }
+        text.push_str("\nEnter=use this key, any other key=press another one");
+        let confirmed = loop {
+            terminal.draw(|frame: &mut Frame| {
+                let paragraph = Paragraph::new(text.as_str())
+                    .block(Block::default().borders(Borders::ALL).title(title));
+                frame.render_widget(paragraph, frame.area());
+            })?;
+            if event::poll(Duration::from_millis(100))?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                match key.code {
+                    KeyCode::Enter => break true,
+                    KeyCode::Esc => return Ok(ScreenResult::Back),
+                    _ => break false,
+                }
+            }
+        };
+        // Presses seen while confirming are not the new choice
+        while capture.try_next().is_some() {}
+        if confirmed {
+            return Ok(ScreenResult::Confirmed(key));
+        }
+    }
+}
+
+/// Discard the key events the terminal got for a captured press (they may
+/// arrive just after the capture).
+fn drain_terminal_events() -> Result<()> {
+    while event::poll(Duration::from_millis(50))? {
+        event::read()?;
+    }
+    Ok(())
+}
+
+/// Pick the push-to-talk key from a fixed list, when no keyboard can be read
+/// to capture it (the listener will not see the key either, but the session
+/// can still be typed). The cursor starts on the `last` key.
+fn hotkey_list_screen(
+    terminal: &mut ratatui::DefaultTerminal,
+    last: Option<EvdevKeyCode>,
+) -> Result<ScreenResult<EvdevKeyCode>> {
+    const KEYS: [EvdevKeyCode; 9] = [
+        EvdevKeyCode::KEY_F2,
+        EvdevKeyCode::KEY_F3,
+        EvdevKeyCode::KEY_F4,
+        EvdevKeyCode::KEY_F9,
+        EvdevKeyCode::KEY_F10,
+        EvdevKeyCode::KEY_F11,
+        EvdevKeyCode::KEY_F12,
+        EvdevKeyCode::KEY_SCROLLLOCK,
+        EvdevKeyCode::KEY_PAUSE,
+    ];
+    let labels: Vec<String> = KEYS.iter().map(|&key| hotkey::key_name(key)).collect();
+    let initial = setup_state::choice_index(&KEYS, last).unwrap_or(0);
+    let picked = select_screen_from(terminal, "Select Push-to-Talk Key", &labels, initial, &[])?;
+    Ok(picked.map(|idx| KEYS[idx]))
+}
+
+fn select_output_device(
+    terminal: &mut ratatui::DefaultTerminal,
+    host: &cpal::Host,
+    previous: Option<&str>,
+) -> Result<ScreenResult<Option<String>>> {
+    // Playback reports the device it opens; nothing to choose from here
+    let Ok((devices, default_idx)) = devices::list_output_devices(host) else {
+        return Ok(ScreenResult::Confirmed(None));
+    };
+    if devices.is_empty() {
+        return Ok(ScreenResult::Confirmed(None));
+    }
+    let labels: Vec<String> = devices
+        .iter()
+        .enumerate()
+        .map(|(i, d)| d.label(Some(i) == default_idx))
+        .collect();
+    let usable: Vec<bool> = devices.iter().map(|d| d.default_rate.is_some()).collect();
+    let initial = previous
+        .and_then(|name| devices.iter().position(|d| d.name == name))
+        .or(default_idx)
+        .unwrap_or(0);
+    let picked = select_screen_from(terminal, "Select Audio Output", &labels, initial, &usable)?;
+    Ok(picked.map(|idx| (Some(idx) != default_idx).then(|| devices[idx].name.clone())))
+}
+
+/// A line of text, starting as `initial`; Enter on an empty line gives the
+/// `placeholder`.
 fn text_input_screen(
     terminal: &mut ratatui::DefaultTerminal,
     title: &str,
     placeholder: &str,
-) -> Result<String> {
-    let mut input = String::new();
+    initial: &str,
+) -> Result<ScreenResult<String>> {
+    let mut input = initial.to_string();
 
     loop {
         let display_text = if input.is_empty() {
@@ -148,30 +806,45 @@ fn text_input_screen(
                     let trimmed = input.trim().to_string();
                     // If empty, use placeholder as default
                     if trimmed.is_empty() {
-                        return Ok(placeholder.to_string());
+                        return Ok(ScreenResult::Confirmed(placeholder.to_string()));
                     }
-                    return Ok(trimmed);
-                }
-                KeyCode::Esc => {
-                    bail!("Setup cancelled by user.");
+                    return Ok(ScreenResult::Confirmed(trimmed));
                 }
+                KeyCode::Esc => return Ok(ScreenResult::Back),
                 _ => {}
             }
         }
     }
 }
 
-fn select_screen(
+/// Pick one of `items`, with the cursor starting on `initial`. Items whose
+/// `enabled` entry is false are shown dimmed and cannot be picked (an empty
+/// `enabled` allows all).
+fn select_screen_from(
     terminal: &mut ratatui::DefaultTerminal,
     title: &str,
     items: &[String],
-) -> Result<usize> {
+    initial: usize,
+    enabled: &[bool],
+) -> Result<ScreenResult<usize>> {
+    let is_enabled = |idx: usize| enabled.get(idx).copied().unwrap_or(true);
     let mut state = ListState::default();
-    state.select(Some(0));
+    state.select(Some(initial));
 
     loop {
         let title = title.to_string();
-        let list_items: Vec<ListItem> = items.iter().map(|s| ListItem::new(s.as_str())).collect();
+        let list_items: Vec<ListItem> = items
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let item = ListItem::new(s.as_str());
+                if is_enabled(i) {
+                    item
+                } else {
+                    item.style(Style::default().add_modifier(Modifier::DIM))
+                }
+            })
+            .collect();
 
         terminal.draw(|frame: &mut Frame| {
             let area = frame.area();
@@ -179,7 +852,7 @@ fn select_screen(
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
-                        .title(format!(" {title} (↑↓ Enter, q=quit) ")),
+                        .title(format!(" {title} (↑↓ Enter, Esc=back, q=quit) ")),
                 )
                 .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
                 .highlight_symbol("▸ ");
@@ -194,15 +867,201 @@ fn select_screen(
                 KeyCode::Up => state.select_previous(),
                 KeyCode::Down => state.select_next(),
                 KeyCode::Enter => {
-                    if let Some(idx) = state.selected() {
-                        return Ok(idx);
+                    if let Some(idx) = state.selected()
+                        && is_enabled(idx)
+                    {
+                        return Ok(ScreenResult::Confirmed(idx));
                     }
                 }
-                KeyCode::Char('q') | KeyCode::Esc => {
-                    bail!("Setup cancelled by user.");
-                }
+                KeyCode::Esc | KeyCode::Left => return Ok(ScreenResult::Back),
+                KeyCode::Char('q') => return Ok(ScreenResult::Cancelled),
                 _ => {}
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headless_setup_needs_every_flag() {
+        let setup = HeadlessSetup::from_flags(
+            Some("10.0.0.2"),
+            Some(parse_voice_mode("hold").unwrap()),
+            Some(EvdevKeyCode::KEY_F9),
+        )
+        .unwrap();
+        assert_eq!(setup.voice_mode, VoiceMode::Manual);
+        assert_eq!(setup.hotkey_mode, HotkeyMode::Hold);
+        let err = HeadlessSetup::from_flags(Some("10.0.0.2"), None, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Without the setup screens, --voice-mode, --hotkey must be given"
+        );
+        assert!(parse_voice_mode("push").is_err());
+    }
+
+    /// Run the address check with `typed` addresses and connect results,
+    /// choosing to edit after each failure as `edits` says. Returns the
+    /// address kept, whether it got a connection, and the pre-filled texts.
+    fn confirm(typed: &[&str], reachable: &[bool], edits: &[bool]) -> (String, bool, Vec<String>) {
+        let (mut typed, mut reachable, mut edits) = (typed.iter(), reachable.iter(), edits.iter());
+        let mut prefilled = Vec::new();
+        let result = confirm_server_address(
+            "",
+            |initial| {
+                prefilled.push(initial.to_string());
+                Ok(ScreenResult::Confirmed(typed.next().unwrap().to_string()))
+            },
+            |_| match reachable.next().unwrap() {
+                true => Ok(()),
+                false => bail!("connection refused"),
+            },
+            |_, _| Ok(*edits.next().unwrap()),
+        )
+        .unwrap();
+        let ScreenResult::Confirmed((addr, connection)) = result else {
+            panic!("the address was confirmed");
+        };
+        (addr, connection.is_some(), prefilled)
+    }
+
+    #[test]
+    fn reachable_address_is_kept_at_once() {
+        assert_eq!(
+            confirm(&["10.0.0.2"], &[true], &[]),
+            ("10.0.0.2".to_string(), true, vec![String::new()])
+        );
+    }
+
+    #[test]
+    fn unreachable_address_can_be_edited_or_kept() {
+        // Edited: the typo is offered for editing
+        let (addr, connected, prefilled) =
+            confirm(&["10.0.0.22", "10.0.0.2"], &[false, true], &[true]);
+        assert_eq!(addr, "10.0.0.2");
+        assert!(connected);
+        assert_eq!(prefilled, ["", "10.0.0.22"]);
+        // Continue anyway, with no connection to hand over
+        let (addr, connected, prefilled) = confirm(&["10.0.0.9"], &[false], &[false]);
+        assert_eq!(addr, "10.0.0.9");
+        assert!(!connected);
+        assert_eq!(prefilled.len(), 1);
+    }
+
+    #[test]
+    fn check_connects_with_the_default_port() {
+        let mut connected = Vec::new();
+        confirm_server_address(
+            "",
+            |_| Ok(ScreenResult::Confirmed("10.0.0.2".to_string())),
+            |addr| {
+                connected.push(addr.to_string());
+                Ok(())
+            },
+            |_, _| Ok(false),
+        )
+        .unwrap();
+        assert_eq!(connected, ["10.0.0.2:9500"]);
+    }
+
+    /// Walk the setup steps with injected screen results (true = confirmed,
+    /// false = back), showing the steps `shown` allows. Returns the steps shown
+    /// and how the walk ended.
+    fn walk(results: &[bool], shown: impl Fn(SetupStep) -> bool) -> (Vec<SetupStep>, Flow) {
+        let mut step = SetupStep::ALL.into_iter().find(|&s| shown(s)).unwrap();
+        let mut visited = vec![step];
+        for &confirmed in results {
+            let result = if confirmed {
+                ScreenResult::Confirmed(())
+            } else {
+                ScreenResult::Back
+            };
+            match next_step(step, &result, &shown) {
+                Flow::Show(next) => {
+                    step = next;
+                    visited.push(step);
+                }
+                end => return (visited, end),
+            }
+        }
+        panic!("the walk did not end");
+    }
+
+    #[test]
+    fn setup_steps_go_forward_and_back() {
+        use SetupStep::*;
+        let (visited, end) = walk(
+            &[true, true, false, false, true, true, true, true, true, true],
+            |_| true,
+        );
+        assert_eq!(
+            visited,
+            [
+                Server,
+                InputDevice,
+                OutputDevice,
+                InputDevice,
+                Server,
+                InputDevice,
+                OutputDevice,
+                Hotkey,
+                VoiceMode,
+                PauseTolerance
+            ]
+        );
+        assert_eq!(end, Flow::Done);
+    }
+
+    #[test]
+    fn back_skips_hidden_steps_and_cancels_on_the_first() {
+        use SetupStep::*;
+        // --server and --output-device given, terminal hotkey
+        let shown = |step| !matches!(step, Server | OutputDevice | Hotkey | PauseTolerance);
+        let (visited, end) = walk(&[true, false, false], shown);
+        assert_eq!(visited, [InputDevice, VoiceMode, InputDevice]);
+        assert_eq!(end, Flow::Cancel);
+        // q cancels from anywhere
+        assert_eq!(
+            next_step(VoiceMode, &ScreenResult::<()>::Cancelled, |_| true),
+            Flow::Cancel
+        );
+        // Manual mode ends the setup at the voice mode
+        assert_eq!(
+            next_step(VoiceMode, &ScreenResult::Confirmed(()), shown),
+            Flow::Done
+        );
+    }
+
+    #[test]
+    fn back_from_the_address_screen_is_passed_on() {
+        let result = confirm_server_address(
+            "10.0.0.2",
+            |typed| {
+                assert_eq!(typed, "10.0.0.2");
+                Ok(ScreenResult::Back)
+            },
+            |_| -> Result<()> { panic!("nothing to connect to") },
+            |_, _| Ok(true),
+        )
+        .unwrap();
+        assert_eq!(result, ScreenResult::Back);
+    }
+
+    #[test]
+    fn mic_test_gauge_spans_the_floor_to_full_scale() {
+        assert_eq!(mic_test_ratio(0.0), 0.0);
+        assert_eq!(mic_test_ratio(1.0), 1.0);
+        // -30 dBFS is half way
+        assert!((mic_test_ratio(0.031_622_78) - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn mic_test_reports_quiet_microphones() {
+        assert!(mic_test_verdict(0.1).ends_with("OK"));
+        assert!(mic_test_verdict(0.001).ends_with("check your mic"));
+        assert!(mic_test_verdict(0.0).starts_with("Loudest: -96 dB"));
+    }
+}
@@ -0,0 +1,139 @@
+/// The user's turns as the client saw them, for the rewind picker ('b').
+///
+/// Turns are counted like the orchestrator counts them: a sentence that got a
+/// reply. A sentence dropped for a retry never gets one, and a disregarded
+/// turn is forgotten, so the numbers match the ones `BranchTo` expects.
+#[derive(Debug, Default)]
+pub struct TurnLog {
+    /// What the user said in each answered turn, oldest first.
+    turns: Vec<String>,
+    /// The last sentence, until its reply arrives.
+    pending: Option<String>,
+}
+
+/// A turn the conversation can be rewound to.
+#[derive(Debug, PartialEq)]
+pub struct RewindChoice<'a> {
+    /// Turns kept (0 = start over).
+    pub turn: u32,
+    /// What the user said in that turn, `None` for the start.
+    pub sentence: Option<&'a str>,
+}
+
+impl TurnLog {
+    /// The transcription of the user's turn (the server's "You:" echo).
+    pub fn user_said(&mut self, sentence: &str) {
+        self.pending = Some(sentence.to_string());
+    }
+
+    /// A reply arrived (the server's "AI:" echo). Replies that answer no
+    /// sentence (a simpler version, a rewind announcement) are not turns.
+    pub fn replied(&mut self) {
+        if let Some(sentence) = self.pending.take() {
+            self.turns.push(sentence);
+        }
+    }
+
+    /// The user disregarded their last message: unanswered, or the last turn.
+    pub fn disregard(&mut self) {
+        if self.pending.take().is_none() {
+            self.turns.pop();
+        }
+    }
+
+    /// Answered turns so far.
+    pub fn len(&self) -> u32 {
+        self.turns.len() as u32
+    }
+
+    /// Forget the turns after `turn`.
+    pub fn rewind(&mut self, turn: u32) {
+        self.turns.truncate(turn as usize);
+        self.pending = None;
+    }
+
+    /// Up to `n` turns to rewind to, newest first: the first one undoes the
+    /// last exchange.
+    pub fn rewind_choices(&self, n: usize) -> Vec<RewindChoice<'_>> {
+        (0..self.len())
+            .rev()
+            .take(n)
+            .map(|turn| RewindChoice {
+                turn,
+                sentence: turn.checked_sub(1).map(|i| self.turns[i as usize].as_str()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(sentences: &[&str]) -> TurnLog {
+        let mut log = TurnLog::default();
+        for sentence in sentences {
+            log.user_said(sentence);
+            log.replied();
+        }
+        log
+    }
+
+    #[test]
+    fn only_answered_sentences_are_turns() {
+        let mut log = log(&["one", "two"]);
+        // Retried: the sentence is replaced before any reply
+        log.user_said("tree");
+        log.user_said("three");
+        log.replied();
+        // A simpler version of the same reply
+        log.replied();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.rewind_choices(1)[0].sentence, Some("two"));
+    }
+
+    #[test]
+    fn disregard_drops_the_pending_or_last_turn() {
+        let mut log = log(&["one", "two"]);
+        log.user_said("aside");
+        log.disregard();
+        assert_eq!(log.len(), 2);
+        log.disregard();
+        assert_eq!(log.len(), 1);
+    }
+
+    #[test]
+    fn choices_go_back_from_the_last_exchange_to_the_start() {
+        let log = log(&["one", "two", "three"]);
+        assert_eq!(
+            log.rewind_choices(9),
+            [
+                RewindChoice {
+                    turn: 2,
+                    sentence: Some("two")
+                },
+                RewindChoice {
+                    turn: 1,
+                    sentence: Some("one")
+                },
+                RewindChoice {
+                    turn: 0,
+                    sentence: None
+                },
+            ]
+        );
+        assert_eq!(log.rewind_choices(1).len(), 1);
+        assert!(TurnLog::default().rewind_choices(9).is_empty());
+    }
+
+    #[test]
+    fn rewind_forgets_later_turns() {
+        let mut log = log(&["one", "two", "three"]);
+        log.user_said("four");
+        log.rewind(1);
+        assert_eq!(log.len(), 1);
+        // The unanswered sentence went with them
+        log.replied();
+        assert_eq!(log.len(), 1);
+    }
+}
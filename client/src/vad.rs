@@ -1,96 +1,494 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use std::collections::VecDeque;
 use webrtc_vad::{SampleRate, Vad, VadMode};
 
 const FRAME_SIZE: usize = 160; // 10ms at 16kHz
-const SILENCE_THRESHOLD: u32 = 50; // 500ms of silence = end of speech
-const PRE_ROLL_FRAMES: usize = 5; // 50ms pre-roll buffer
+const FRAME_MS: u32 = 10;
+/// Audio kept from before speech is detected, so the onset of the first
+/// syllable (quieter than the detection threshold) is not clipped.
+pub const DEFAULT_PRE_ROLL_MS: u32 = 300;
+
+/// Longest segment before a forced split: Whisper slows down on long input
+/// and only transcribes 30 s at a time.
+pub const DEFAULT_MAX_SEGMENT_MS: u32 = 25_000;
+
+/// Frames over which the speech probability is measured.
+const PROBABILITY_FRAMES: usize = 10; // 100ms
+
+/// How far back from the limit a forced split looks for a pause.
+const SPLIT_WINDOW_FRAMES: usize = 200; // 2s
+
+/// Frame RMS that starts speech for the energy detector, per speech threshold
+/// (0-3); it ends below half of it.
+const ENERGY_START_RMS: [f64; 4] = [300.0, 500.0, 800.0, 1200.0];
+
+/// Frames the energy detector stays voiced after the level drops, so the gaps
+/// between syllables are not taken for silence.
+const ENERGY_HANGOVER_FRAMES: u32 = 8; // 80ms
+
+/// What tells speech from silence in each frame (`--vad`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum VadEngine {
+    /// webrtc-vad's voice model.
+    #[default]
+    WebRtc,
+    /// Frame loudness: no model, but any sound loud enough counts as speech.
+    Energy,
+}
+
+impl VadEngine {
+    /// Parse `--vad` ("webrtc" or "energy").
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "webrtc" => Ok(VadEngine::WebRtc),
+            "energy" => Ok(VadEngine::Energy),
+            other => bail!("expected \"webrtc\" or \"energy\", got \"{other}\""),
+        }
+    }
+}
+
+/// Segmentation thresholds of [`VoiceDetector`] (auto mode).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VadConfig {
+    /// Silence that ends a segment.
+    pub silence_duration_ms: u32,
+    pub engine: VadEngine,
+    /// Detector aggressiveness: 0 takes the most sounds for speech, 3 the fewest
+    /// (the webrtc-vad mode, or the energy detector's level).
+    pub speech_threshold: u8,
+    /// Segments with less speech than this are dropped (coughs, clicks).
+    pub min_speech_ms: u32,
+    /// Audio before the first voiced frame prepended to each segment.
+    pub pre_roll_ms: u32,
+    /// Segments reaching this length are split without waiting for silence.
+    pub max_segment_ms: u32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        VadPreset::Default.config()
+    }
+}
+
+impl VadConfig {
+    fn mode(&self) -> VadMode {
+        match self.speech_threshold {
+            0 => VadMode::Quality,
+            1 => VadMode::LowBitrate,
+            2 => VadMode::Aggressive,
+            _ => VadMode::VeryAggressive,
+        }
+    }
+
+    fn pre_roll_frames(&self) -> usize {
+        (self.pre_roll_ms / FRAME_MS) as usize
+    }
+
+    fn max_segment_frames(&self) -> usize {
+        (self.max_segment_ms / FRAME_MS) as usize
+    }
+}
+
+/// Named [`VadConfig`]s offered at setup (`--vad-preset`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum VadPreset {
+    /// Waits out thinking pauses.
+    Relaxed,
+    #[default]
+    Default,
+    /// Answers quickly, drops short noises.
+    Aggressive,
+}
+
+impl VadPreset {
+    pub const ALL: [VadPreset; 3] = [
+        VadPreset::Relaxed,
+        VadPreset::Default,
+        VadPreset::Aggressive,
+    ];
+
+    /// Parse `--vad-preset` ("relaxed", "default" or "aggressive").
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "relaxed" => Ok(VadPreset::Relaxed),
+            "default" => Ok(VadPreset::Default),
+            "aggressive" => Ok(VadPreset::Aggressive),
+            other => bail!("expected \"relaxed\", \"default\" or \"aggressive\", got \"{other}\""),
+        }
+    }
+
+    /// The name [`VadPreset::parse`] reads.
+    pub fn name(self) -> &'static str {
+        match self {
+            VadPreset::Relaxed => "relaxed",
+            VadPreset::Default => "default",
+            VadPreset::Aggressive => "aggressive",
+        }
+    }
+
+    pub fn config(self) -> VadConfig {
+        match self {
+            VadPreset::Relaxed => VadConfig {
+                engine: VadEngine::WebRtc,
+                silence_duration_ms: 1200,
+                speech_threshold: 1,
+                min_speech_ms: 0,
+                pre_roll_ms: DEFAULT_PRE_ROLL_MS,
+                max_segment_ms: DEFAULT_MAX_SEGMENT_MS,
+            },
+            VadPreset::Default => VadConfig {
+                engine: VadEngine::WebRtc,
+                silence_duration_ms: 500,
+                speech_threshold: 2,
+                min_speech_ms: 0,
+                pre_roll_ms: DEFAULT_PRE_ROLL_MS,
+                max_segment_ms: DEFAULT_MAX_SEGMENT_MS,
+            },
+            VadPreset::Aggressive => VadConfig {
+                engine: VadEngine::WebRtc,
+                silence_duration_ms: 300,
+                speech_threshold: 3,
+                min_speech_ms: 150,
+                pre_roll_ms: DEFAULT_PRE_ROLL_MS,
+                max_segment_ms: DEFAULT_MAX_SEGMENT_MS,
+            },
+        }
+    }
+
+    /// Setup screen entry.
+    pub fn label(self) -> String {
+        let config = self.config();
+        let name = match self {
+            VadPreset::Relaxed => "Relaxed",
+            VadPreset::Default => "Default",
+            VadPreset::Aggressive => "Aggressive",
+        };
+        format!(
+            "{name} (ends after {}ms of silence)",
+            config.silence_duration_ms
+        )
+    }
+}
+
+/// Per-field overrides of a [`VadConfig`] (`--vad`, `--vad-silence-ms`,
+/// `--vad-threshold`, `--vad-min-speech-ms`, `--vad-pre-roll-ms`,
+/// `--vad-max-segment-ms`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VadOverrides {
+    pub engine: Option<VadEngine>,
+    pub silence_duration_ms: Option<u32>,
+    pub speech_threshold: Option<u8>,
+    pub min_speech_ms: Option<u32>,
+    pub pre_roll_ms: Option<u32>,
+    pub max_segment_ms: Option<u32>,
+}
+
+impl VadOverrides {
+    pub fn apply(self, config: VadConfig) -> VadConfig {
+        VadConfig {
+            engine: self.engine.unwrap_or(config.engine),
+            silence_duration_ms: self
+                .silence_duration_ms
+                .unwrap_or(config.silence_duration_ms),
+            speech_threshold: self.speech_threshold.unwrap_or(config.speech_threshold),
+            min_speech_ms: self.min_speech_ms.unwrap_or(config.min_speech_ms),
+            pre_roll_ms: self.pre_roll_ms.unwrap_or(config.pre_roll_ms),
+            max_segment_ms: self.max_segment_ms.unwrap_or(config.max_segment_ms),
+        }
+    }
+}
+
+/// Speech/silence decision for single frames.
+enum FrameDetector {
+    WebRtc(Vad),
+    Energy(EnergyVad),
+}
+
+impl FrameDetector {
+    fn new(config: &VadConfig) -> Self {
+        match config.engine {
+            VadEngine::WebRtc => FrameDetector::WebRtc(Vad::new_with_rate_and_mode(
+                SampleRate::Rate16kHz,
+                config.mode(),
+            )),
+            VadEngine::Energy => {
+                FrameDetector::Energy(EnergyVad::new(config.speech_threshold.min(3)))
+            }
+        }
+    }
+
+    fn is_voice(&mut self, frame: &[i16]) -> bool {
+        match self {
+            FrameDetector::WebRtc(vad) => vad.is_voice_segment(frame).unwrap_or(false),
+            FrameDetector::Energy(vad) => vad.is_voice(frame),
+        }
+    }
+}
+
+/// Loudness-based speech detection: frame RMS with hysteresis (speech starts
+/// above one level and ends below a lower one) and a hangover.
+pub struct EnergyVad {
+    start_rms: f64,
+    stop_rms: f64,
+    voiced: bool,
+    hangover: u32,
+}
+
+impl EnergyVad {
+    pub fn new(speech_threshold: u8) -> Self {
+        let start_rms = ENERGY_START_RMS[speech_threshold as usize];
+        Self {
+            start_rms,
+            stop_rms: start_rms / 2.0,
+            voiced: false,
+            hangover: 0,
+        }
+    }
+
+    pub fn is_voice(&mut self, frame: &[i16]) -> bool {
+        let rms = (frame.iter().map(|&s| s as f64 * s as f64).sum::<f64>()
+            / frame.len().max(1) as f64)
+            .sqrt();
+        if !self.voiced {
+            self.voiced = rms >= self.start_rms;
+        } else if rms < self.stop_rms {
+            if self.hangover == 0 {
+                self.voiced = false;
+            } else {
+                self.hangover -= 1;
+            }
+        }
+        if self.voiced && rms >= self.stop_rms {
+            self.hangover = ENERGY_HANGOVER_FRAMES;
+        }
+        self.voiced
+    }
+}
+
+/// Result of [`VoiceDetector::process_samples`].
+#[derive(Debug, Default)]
+pub struct VadOutput {
+    /// Segments completed by this chunk.
+    pub segments: Vec<Vec<i16>>,
+    /// Share of voiced frames over the last 100ms (0.0-1.0), for display.
+    /// Both detectors decide per frame, so this is the probability that a
+    /// recent frame was speech, not a model score.
+    pub speech_probability: f32,
+}
 
 pub struct VoiceDetector {
-    vad: Vad,
+    detector: FrameDetector,
+    config: VadConfig,
     is_speaking: bool,
     silence_frames: u32,
+    /// Voiced frames in the current segment, for `min_speech_ms`.
+    speech_frames: u32,
     audio_buffer: Vec<i16>,
+    /// The last `pre_roll_ms` of silence; cleared when a segment takes it or
+    /// ends, so segments never overlap.
     pre_roll_buffer: VecDeque<[i16; FRAME_SIZE]>,
+    /// The last frames of `audio_buffer`, where a forced split looks for a pause.
+    recent_frames: VecDeque<FrameStat>,
+    /// Voice decisions for the last frames, speaking or not.
+    recent_voicing: VecDeque<bool>,
+}
+
+/// A frame of the current segment, as seen by the forced split.
+#[derive(Debug, Clone, Copy)]
+struct FrameStat {
+    voiced: bool,
+    /// Sum of squares, to find the quietest frame when none is unvoiced.
+    energy: u64,
+}
+
+impl FrameStat {
+    fn new(frame: &[i16], voiced: bool) -> Self {
+        Self {
+            voiced,
+            energy: frame.iter().map(|&s| (s as i64 * s as i64) as u64).sum(),
+        }
+    }
 }
 
 impl VoiceDetector {
-    pub fn new() -> Result<Self> {
-        let vad = Vad::new_with_rate_and_mode(SampleRate::Rate16kHz, VadMode::Aggressive);
+    pub fn new(config: VadConfig) -> Result<Self> {
+        if config.speech_threshold > 3 {
+            bail!(
+                "VAD speech threshold must be 0-3, got {}",
+                config.speech_threshold
+            );
+        }
+        if config.silence_duration_ms < FRAME_MS {
+            bail!(
+                "VAD silence duration must be at least {FRAME_MS}ms, got {}ms",
+                config.silence_duration_ms
+            );
+        }
+        if config.max_segment_ms < 1000 {
+            bail!(
+                "VAD max segment must be at least 1000ms, got {}ms",
+                config.max_segment_ms
+            );
+        }
         Ok(Self {
-            vad,
+            detector: FrameDetector::new(&config),
+            config,
             is_speaking: false,
             silence_frames: 0,
+            speech_frames: 0,
             audio_buffer: Vec::new(),
-            pre_roll_buffer: VecDeque::with_capacity(PRE_ROLL_FRAMES),
+            pre_roll_buffer: VecDeque::with_capacity(config.pre_roll_frames()),
+            recent_frames: VecDeque::with_capacity(SPLIT_WINDOW_FRAMES),
+            recent_voicing: VecDeque::with_capacity(PROBABILITY_FRAMES),
         })
     }
 
-    pub fn process_samples(&mut self, samples: &[i16]) -> Vec<Vec<i16>> {
+    pub fn process_samples(&mut self, samples: &[i16]) -> VadOutput {
         let mut segments = Vec::new();
 
         for chunk in samples.chunks_exact(FRAME_SIZE) {
             let frame: [i16; FRAME_SIZE] = chunk.try_into().unwrap();
-            let is_voice = self.vad.is_voice_segment(&frame).unwrap_or(false);
+            let is_voice = self.detector.is_voice(&frame);
+            if self.recent_voicing.len() >= PROBABILITY_FRAMES {
+                self.recent_voicing.pop_front();
+            }
+            self.recent_voicing.push_back(is_voice);
 
             match (self.is_speaking, is_voice) {
                 // Silence → Silence
                 (false, false) => {
-                    if self.pre_roll_buffer.len() >= PRE_ROLL_FRAMES {
-                        self.pre_roll_buffer.pop_front();
+                    let max = self.config.pre_roll_frames();
+                    if max > 0 {
+                        if self.pre_roll_buffer.len() >= max {
+                            self.pre_roll_buffer.pop_front();
+                        }
+                        self.pre_roll_buffer.push_back(frame);
                     }
-                    self.pre_roll_buffer.push_back(frame);
                 }
                 // Silence → Voice
                 (false, true) => {
                     self.is_speaking = true;
                     self.silence_frames = 0;
+                    self.speech_frames = 1;
                     // Drain pre-roll into audio buffer
-                    for pre_frame in self.pre_roll_buffer.drain(..) {
-                        self.audio_buffer.extend_from_slice(&pre_frame);
+                    let pre_roll: Vec<_> = self.pre_roll_buffer.drain(..).collect();
+                    for pre_frame in &pre_roll {
+                        self.push_frame(pre_frame, false);
                     }
-                    self.audio_buffer.extend_from_slice(&frame);
+                    self.push_frame(&frame, true);
                 }
                 // Voice → Voice
                 (true, true) => {
                     self.silence_frames = 0;
-                    self.audio_buffer.extend_from_slice(&frame);
+                    self.speech_frames += 1;
+                    self.push_frame(&frame, true);
                 }
                 // Voice → Silence
                 (true, false) => {
-                    self.audio_buffer.extend_from_slice(&frame);
+                    self.push_frame(&frame, false);
                     self.silence_frames += 1;
-                    if self.silence_frames >= SILENCE_THRESHOLD {
-                        segments.push(std::mem::take(&mut self.audio_buffer));
+                    if self.silence_frames * FRAME_MS >= self.config.silence_duration_ms {
+                        let segment = std::mem::take(&mut self.audio_buffer);
+                        if self.speech_frames * FRAME_MS >= self.config.min_speech_ms {
+                            segments.push(segment);
+                        }
                         self.is_speaking = false;
                         self.silence_frames = 0;
                         self.pre_roll_buffer.clear();
+                        self.recent_frames.clear();
                     }
                 }
             }
+
+            if self.is_speaking
+                && self.audio_buffer.len() >= self.config.max_segment_frames() * FRAME_SIZE
+                && let Some(segment) = self.force_split()
+            {
+                segments.push(segment);
+            }
+        }
+
+        VadOutput {
+            segments,
+            speech_probability: self.speech_probability(),
+        }
+    }
+
+    fn speech_probability(&self) -> f32 {
+        if self.recent_voicing.is_empty() {
+            return 0.0;
+        }
+        let voiced = self.recent_voicing.iter().filter(|&&v| v).count();
+        voiced as f32 / self.recent_voicing.len() as f32
+    }
+
+    fn push_frame(&mut self, frame: &[i16; FRAME_SIZE], voiced: bool) {
+        self.audio_buffer.extend_from_slice(frame);
+        if self.recent_frames.len() >= SPLIT_WINDOW_FRAMES {
+            self.recent_frames.pop_front();
         }
+        self.recent_frames.push_back(FrameStat::new(frame, voiced));
+    }
+
+    /// Cut the segment that reached `max_segment_ms` after the most recent
+    /// unvoiced frame of the last 2s, or the quietest one if the speaker never
+    /// paused, rather than mid-word. The audio after the cut starts the next
+    /// segment, still speaking.
+    fn force_split(&mut self) -> Option<Vec<i16>> {
+        let recent = &self.recent_frames;
+        let cut = recent.iter().rposition(|f| !f.voiced).unwrap_or_else(|| {
+            // Latest of the quietest frames, so a steady signal splits at the limit
+            let mut quietest = 0;
+            for (i, f) in recent.iter().enumerate() {
+                if f.energy <= recent[quietest].energy {
+                    quietest = i;
+                }
+            }
+            quietest
+        });
+        let kept = recent.len() - 1 - cut;
+        let rest = self
+            .audio_buffer
+            .split_off(self.audio_buffer.len() - kept * FRAME_SIZE);
+        let segment = std::mem::replace(&mut self.audio_buffer, rest);
+        self.recent_frames.drain(..=cut);
 
-        segments
+        let rest_speech = self.recent_frames.iter().filter(|f| f.voiced).count() as u32;
+        let segment_speech = self.speech_frames - rest_speech;
+        self.speech_frames = rest_speech;
+        self.silence_frames = self
+            .recent_frames
+            .iter()
+            .rev()
+            .take_while(|f| !f.voiced)
+            .count() as u32;
+        (segment_speech * FRAME_MS >= self.config.min_speech_ms).then_some(segment)
     }
 
     /// Flush any accumulated audio, returning it as a segment without waiting
     /// for the silence threshold. Used for push-to-talk mode (hotkey toggle-off)
     /// and to avoid discarding in-progress audio on pause in auto mode.
+    ///
+    /// The segment starts with its pre-roll, like one ended by silence. Pre-roll
+    /// alone (no speech yet) is not a segment; it is discarded.
     pub fn flush(&mut self) -> Option<Vec<i16>> {
+        self.pre_roll_buffer.clear();
         if self.audio_buffer.is_empty() {
             return None;
         }
         self.is_speaking = false;
         self.silence_frames = 0;
-        self.pre_roll_buffer.clear();
+        self.recent_frames.clear();
         Some(std::mem::take(&mut self.audio_buffer))
     }
 
     pub fn reset(&mut self) {
-        // Recreate Vad instance to clear internal state (no reset API available)
-        self.vad = Vad::new_with_rate_and_mode(SampleRate::Rate16kHz, VadMode::Aggressive);
+        // Recreate the detector to clear internal state (webrtc-vad has no reset API)
+        self.detector = FrameDetector::new(&self.config);
         self.audio_buffer.clear();
         self.pre_roll_buffer.clear();
+        self.recent_frames.clear();
+        self.recent_voicing.clear();
         self.is_speaking = false;
         self.silence_frames = 0;
     }
@@ -116,26 +514,354 @@ mod tests {
         vec![0i16; FRAME_SIZE * num_frames]
     }
 
+    /// A speech onset: the square wave of `make_voice`, growing from nothing to
+    /// `peak` over `num_frames`, too quiet to be taken for speech.
+    fn make_ramp(num_frames: usize, peak: i16) -> Vec<i16> {
+        let len = FRAME_SIZE * num_frames;
+        make_voice(num_frames)
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (v as i32 * peak as i32 / 30000 * i as i32 / len as i32) as i16)
+            .collect()
+    }
+
+    /// Frames of silence that end a segment under `config`.
+    fn silence_frames(config: VadConfig) -> usize {
+        (config.silence_duration_ms / FRAME_MS) as usize
+    }
+
+    /// Frames the energy detector still takes for speech after it stops.
+    const HANGOVER: usize = ENERGY_HANGOVER_FRAMES as usize;
+
+    /// `config` with the energy detector. The segmentation tests use it: its
+    /// decisions are exact, where webrtc-vad's model adapts to the synthetic
+    /// signal and keeps reporting speech for a varying while after it stops.
+    fn with_energy(config: VadConfig) -> VadConfig {
+        VadOverrides {
+            engine: Some(VadEngine::Energy),
+            ..Default::default()
+        }
+        .apply(config)
+    }
+
+    /// Segment lengths (in frames) for `pattern`, a list of (voice frames,
+    /// silence frames), followed by enough silence to end any segment.
+    fn segment_frames(preset: VadPreset, pattern: &[(usize, usize)]) -> Vec<usize> {
+        let mut vd = VoiceDetector::new(with_energy(preset.config())).unwrap();
+        let mut segments = Vec::new();
+        for &(voice, silence) in pattern {
+            segments.extend(vd.process_samples(&make_voice(voice)).segments);
+            segments.extend(vd.process_samples(&make_silence(silence)).segments);
+        }
+        segments.extend(vd.process_samples(&make_silence(200)).segments);
+        segments.iter().map(|s| s.len() / FRAME_SIZE).collect()
+    }
+
+    #[test]
+    fn thinking_pause_splits_only_under_shorter_presets() {
+        // 0.8s pause mid-sentence (0.72s once the hangover is over)
+        let pattern = [(50, 80), (50, 0)];
+        assert_eq!(segment_frames(VadPreset::Relaxed, &pattern).len(), 1);
+        assert_eq!(segment_frames(VadPreset::Default, &pattern).len(), 2);
+        assert_eq!(segment_frames(VadPreset::Aggressive, &pattern).len(), 2);
+
+        // 0.4s pause: only the aggressive preset answers
+        let pattern = [(50, 40), (50, 0)];
+        assert_eq!(segment_frames(VadPreset::Default, &pattern).len(), 1);
+        assert_eq!(segment_frames(VadPreset::Aggressive, &pattern).len(), 2);
+    }
+
+    #[test]
+    fn segment_ends_after_the_preset_silence() {
+        // Segment = voice + hangover + trailing silence up to the threshold
+        for preset in VadPreset::ALL {
+            let frames = segment_frames(preset, &[(50, 0)]);
+            let expected = 50 + HANGOVER + silence_frames(preset.config());
+            assert_eq!(frames, vec![expected], "{preset:?}");
+        }
+    }
+
+    #[test]
+    fn short_noise_is_dropped_only_with_a_minimum_speech() {
+        // 50ms click, then a 200ms word: with the hangover, 130ms and 280ms
+        // of speech against the aggressive preset's 150ms
+        let pattern = [(5, 100), (20, 0)];
+        assert_eq!(
+            segment_frames(VadPreset::Aggressive, &pattern),
+            vec![30 + 20 + HANGOVER + 30]
+        );
+        assert_eq!(segment_frames(VadPreset::Default, &pattern).len(), 2);
+    }
+
+    #[test]
+    fn webrtc_segments_run_on_for_its_hangover() {
+        // webrtc-vad notices the end of speech up to 200ms late (100-160ms on
+        // this signal); its strictest mode does not hold a steady tone at all
+        const MAX_HANGOVER: usize = 20;
+        for preset in [VadPreset::Relaxed, VadPreset::Default] {
+            let config = preset.config();
+            let mut vd = VoiceDetector::new(config).unwrap();
+            let mut segs = vd.process_samples(&make_voice(50)).segments;
+            segs.extend(vd.process_samples(&make_silence(200)).segments);
+            assert_eq!(segs.len(), 1, "{preset:?}");
+            let frames = segs[0].len() / FRAME_SIZE;
+            let shortest = 50 + silence_frames(config);
+            assert!(
+                (shortest..=shortest + MAX_HANGOVER).contains(&frames),
+                "{preset:?}: {frames} frames"
+            );
+        }
+    }
+
+    #[test]
+    fn overrides_replace_single_preset_fields() {
+        let overrides = VadOverrides {
+            silence_duration_ms: Some(900),
+            ..Default::default()
+        };
+        let config = overrides.apply(VadPreset::Aggressive.config());
+        assert_eq!(config.silence_duration_ms, 900);
+        assert_eq!(config.min_speech_ms, 150);
+        assert_eq!(VadPreset::parse("relaxed").unwrap(), VadPreset::Relaxed);
+        assert!(VadPreset::parse("lazy").is_err());
+        assert!(
+            VoiceDetector::new(VadConfig {
+                speech_threshold: 4,
+                ..VadConfig::default()
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn segment_starts_with_the_quiet_onset() {
+        // 200ms silence, 250ms onset, 500ms of speech
+        let ramp = make_ramp(25, 600);
+        let mut vd = VoiceDetector::new(with_energy(VadConfig::default())).unwrap();
+        assert!(vd.process_samples(&make_silence(20)).segments.is_empty());
+        assert!(vd.process_samples(&ramp).segments.is_empty());
+        assert!(!vd.is_speaking, "the onset should be below the threshold");
+        vd.process_samples(&make_voice(50));
+        let segs = vd.process_samples(&make_silence(60)).segments;
+        assert_eq!(segs.len(), 1);
+
+        // 300ms pre-roll: the last 50ms of silence, then the whole onset
+        let seg = &segs[0];
+        let pre_roll = DEFAULT_PRE_ROLL_MS as usize / FRAME_MS as usize;
+        assert_eq!(seg.len(), FRAME_SIZE * (pre_roll + 50 + HANGOVER + 50));
+        let onset = FRAME_SIZE * (pre_roll - 25);
+        assert!(seg[..onset].iter().all(|&s| s == 0));
+        assert_eq!(&seg[onset..onset + ramp.len()], &ramp[..]);
+
+        // Without pre-roll the onset is lost
+        let mut vd = VoiceDetector::new(with_energy(VadConfig {
+            pre_roll_ms: 0,
+            ..VadConfig::default()
+        }))
+        .unwrap();
+        vd.process_samples(&make_silence(20));
+        vd.process_samples(&ramp);
+        vd.process_samples(&make_voice(50));
+        let segs = vd.process_samples(&make_silence(60)).segments;
+        assert_eq!(segs[0].len(), FRAME_SIZE * (50 + HANGOVER + 50));
+    }
+
+    #[test]
+    fn flush_includes_the_onset() {
+        let ramp = make_ramp(10, 600);
+        let mut vd = VoiceDetector::new(with_energy(VadConfig::default())).unwrap();
+        vd.process_samples(&ramp);
+        vd.process_samples(&make_voice(20));
+        let segment = vd.flush().unwrap();
+        assert_eq!(segment.len(), FRAME_SIZE * 30);
+        assert_eq!(&segment[..ramp.len()], &ramp[..]);
+        // As after a flush in the client: the detector's hangover is not
+        // carried into the next segment
+        vd.reset();
+
+        // Onset without speech is not a segment, and is not kept for the next
+        vd.process_samples(&ramp);
+        assert!(vd.flush().is_none());
+        vd.process_samples(&make_voice(20));
+        assert_eq!(vd.flush().unwrap().len(), FRAME_SIZE * 20);
+    }
+
+    #[test]
+    fn segments_never_share_pre_roll() {
+        // The second burst starts 100ms after the first segment ends: its
+        // pre-roll holds only those 100ms, none of the first segment
+        let config = with_energy(VadConfig::default());
+        let ending = HANGOVER + silence_frames(config);
+        let mut vd = VoiceDetector::new(config).unwrap();
+        let mut segs = vd.process_samples(&make_voice(50)).segments;
+        segs.extend(vd.process_samples(&make_silence(ending + 10)).segments);
+        segs.extend(vd.process_samples(&make_voice(50)).segments);
+        segs.extend(vd.process_samples(&make_silence(ending)).segments);
+        let frames: Vec<usize> = segs.iter().map(|s| s.len() / FRAME_SIZE).collect();
+        assert_eq!(frames, vec![50 + ending, 10 + 50 + ending]);
+    }
+
+    #[test]
+    fn long_speech_is_split_at_the_limit() {
+        // A minute without a pause: two full segments, then the rest on flush
+        let mut vd = VoiceDetector::new(VadConfig::default()).unwrap();
+        let segs = vd.process_samples(&make_voice(6000)).segments;
+        let limit = (DEFAULT_MAX_SEGMENT_MS / FRAME_MS) as usize;
+        let frames: Vec<usize> = segs.iter().map(|s| s.len() / FRAME_SIZE).collect();
+        assert_eq!(frames, vec![limit, limit]);
+        assert!(vd.is_speaking);
+        assert_eq!(vd.flush().unwrap().len(), FRAME_SIZE * (6000 - 2 * limit));
+
+        // Nothing lost or repeated across the splits
+        let total: usize = segs.iter().map(Vec::len).sum();
+        assert_eq!(total + FRAME_SIZE * (6000 - 2 * limit), FRAME_SIZE * 6000);
+    }
+
+    #[test]
+    fn forced_split_prefers_a_recent_pause() {
+        // A 120ms breath 1s before the limit (40ms once the hangover is
+        // over): the first segment ends with it, the rest continues into the
+        // next one
+        let config = with_energy(VadConfig {
+            max_segment_ms: 5000,
+            ..VadConfig::default()
+        });
+        let ending = HANGOVER + silence_frames(config);
+        let frames = {
+            let mut vd = VoiceDetector::new(config).unwrap();
+            let mut segs = vd.process_samples(&make_voice(400)).segments;
+            segs.extend(vd.process_samples(&make_silence(12)).segments);
+            segs.extend(vd.process_samples(&make_voice(300)).segments);
+            segs.extend(vd.process_samples(&make_silence(ending)).segments);
+            segs.iter()
+                .map(|s| s.len() / FRAME_SIZE)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(frames, vec![412, 300 + ending]);
+
+        // A pause older than the search window is not used
+        let mut vd = VoiceDetector::new(config).unwrap();
+        let mut segs = vd.process_samples(&make_voice(100)).segments;
+        segs.extend(vd.process_samples(&make_silence(12)).segments);
+        segs.extend(vd.process_samples(&make_voice(500)).segments);
+        assert_eq!(segs.len(), 1);
+        assert_eq!(segs[0].len(), FRAME_SIZE * 500);
+    }
+
+    /// 440 Hz tone of the given peak amplitude.
+    fn make_tone(num_frames: usize, amplitude: f64) -> Vec<i16> {
+        (0..FRAME_SIZE * num_frames)
+            .map(|i| {
+                let t = i as f64 / 16000.0;
+                (amplitude * (2.0 * std::f64::consts::PI * 440.0 * t).sin()) as i16
+            })
+            .collect()
+    }
+
+    fn energy_config() -> VadConfig {
+        VadConfig {
+            engine: VadEngine::Energy,
+            ..VadConfig::default()
+        }
+    }
+
+    /// Segment lengths (in frames) the energy detector finds in `parts`.
+    fn energy_segments(parts: &[Vec<i16>]) -> Vec<usize> {
+        let mut vd = VoiceDetector::new(energy_config()).unwrap();
+        let mut segments = Vec::new();
+        for part in parts {
+            segments.extend(vd.process_samples(part).segments);
+        }
+        segments.iter().map(|s| s.len() / FRAME_SIZE).collect()
+    }
+
+    #[test]
+    fn energy_detector_segments_tone_and_silence() {
+        let config = energy_config();
+        let hangover = ENERGY_HANGOVER_FRAMES as usize;
+        let ending = silence_frames(config) + 10;
+        // Two tones 1s apart; the second gets the pre-roll
+        let frames = energy_segments(&[
+            make_tone(50, 8000.0),
+            make_silence(100),
+            make_tone(30, 8000.0),
+            make_silence(ending),
+        ]);
+        let pre_roll = (config.pre_roll_ms / FRAME_MS) as usize;
+        let tail = hangover + silence_frames(config);
+        assert_eq!(frames, vec![50 + tail, pre_roll + 30 + tail]);
+
+        // A gap shorter than the silence threshold does not split
+        let frames = energy_segments(&[
+            make_tone(50, 8000.0),
+            make_silence(20),
+            make_tone(50, 8000.0),
+            make_silence(ending),
+        ]);
+        assert_eq!(frames, vec![50 + 20 + 50 + tail]);
+    }
+
+    #[test]
+    fn energy_detector_has_hysteresis() {
+        let tail = ENERGY_HANGOVER_FRAMES as usize + silence_frames(energy_config());
+        let ending = make_silence(silence_frames(energy_config()) + 10);
+        // RMS ~570: under the start level (800), over the stop level (400)
+        let quiet = make_tone(30, 800.0);
+        // Quiet alone never starts speech
+        assert!(energy_segments(&[quiet.clone(), ending.clone()]).is_empty());
+        // After loud speech it keeps the segment going
+        assert_eq!(
+            energy_segments(&[make_tone(20, 8000.0), quiet, ending]),
+            vec![20 + 30 + tail]
+        );
+        assert_eq!(VadEngine::parse("energy").unwrap(), VadEngine::Energy);
+        assert!(VadEngine::parse("silero").is_err());
+    }
+
+    #[test]
+    fn speech_probability_follows_the_signal() {
+        for engine in [VadEngine::WebRtc, VadEngine::Energy] {
+            let mut vd = VoiceDetector::new(VadConfig {
+                engine,
+                ..VadConfig::default()
+            })
+            .unwrap();
+            let output = vd.process_samples(&make_voice(30));
+            assert_eq!(output.speech_probability, 1.0, "{engine:?}");
+            // Half of the last 100ms is silence (the energy detector's hangover
+            // keeps it voiced a little longer)
+            let output = vd.process_samples(&make_silence(5));
+            assert!(output.speech_probability >= 0.5, "{engine:?}");
+            let output = vd.process_samples(&make_silence(30));
+            assert_eq!(output.speech_probability, 0.0, "{engine:?}");
+            vd.process_samples(&make_voice(5));
+            vd.reset();
+            assert_eq!(vd.process_samples(&[]).speech_probability, 0.0);
+        }
+    }
+
     #[test]
     fn silence_produces_no_segments() {
-        let mut vd = VoiceDetector::new().unwrap();
-        let segments = vd.process_samples(&make_silence(100));
+        let mut vd = VoiceDetector::new(VadConfig::default()).unwrap();
+        let segments = vd.process_samples(&make_silence(100)).segments;
         assert!(segments.is_empty());
     }
 
     #[test]
     fn loud_then_silence_produces_segment() {
-        let mut vd = VoiceDetector::new().unwrap();
+        let mut vd = VoiceDetector::new(VadConfig::default()).unwrap();
 
         // Feed voice (50 frames = 500ms)
-        let segs = vd.process_samples(&make_voice(50));
+        let segs = vd.process_samples(&make_voice(50)).segments;
         assert!(
             segs.is_empty(),
             "Should not emit segment while still speaking"
         );
 
         // Feed enough silence to trigger end-of-speech
-        let segs = vd.process_samples(&make_silence(SILENCE_THRESHOLD as usize + 20));
+        let segs = vd
+            .process_samples(&make_silence(silence_frames(VadConfig::default()) + 20))
+            .segments;
         assert_eq!(segs.len(), 1, "Should emit exactly one segment");
 
         // Segment should include voice frames + some pre-roll
@@ -150,10 +876,10 @@ mod tests {
 
     #[test]
     fn reset_discards_accumulated_audio() {
-        let mut vd = VoiceDetector::new().unwrap();
+        let mut vd = VoiceDetector::new(VadConfig::default()).unwrap();
 
         // Feed voice to start speaking state
-        let segs = vd.process_samples(&make_voice(30));
+        let segs = vd.process_samples(&make_voice(30)).segments;
         assert!(segs.is_empty());
         assert!(vd.is_speaking);
 
@@ -164,19 +890,21 @@ mod tests {
         assert!(vd.pre_roll_buffer.is_empty());
 
         // Feed silence — should not produce segment (VAD state is fresh)
-        let segs = vd.process_samples(&make_silence(100));
+        let segs = vd.process_samples(&make_silence(100)).segments;
         assert!(segs.is_empty());
     }
 
     #[test]
     fn multiple_speech_bursts() {
-        let mut vd = VoiceDetector::new().unwrap();
+        let mut vd = VoiceDetector::new(VadConfig::default()).unwrap();
         let mut total_segments = Vec::new();
 
         for _ in 0..2 {
-            total_segments.extend(vd.process_samples(&make_voice(50)));
-            total_segments
-                .extend(vd.process_samples(&make_silence(SILENCE_THRESHOLD as usize + 20)));
+            total_segments.extend(vd.process_samples(&make_voice(50)).segments);
+            total_segments.extend(
+                vd.process_samples(&make_silence(silence_frames(VadConfig::default()) + 20))
+                    .segments,
+            );
         }
 
         assert_eq!(total_segments.len(), 2, "Should emit 2 separate segments");
@@ -184,10 +912,10 @@ mod tests {
 
     #[test]
     fn flush_returns_accumulated_audio() {
-        let mut vd = VoiceDetector::new().unwrap();
+        let mut vd = VoiceDetector::new(VadConfig::default()).unwrap();
 
         // Feed voice to start accumulating (no silence → no auto-segment)
-        let segs = vd.process_samples(&make_voice(30));
+        let segs = vd.process_samples(&make_voice(30)).segments;
         assert!(segs.is_empty());
         assert!(vd.is_speaking);
 
@@ -200,13 +928,13 @@ mod tests {
 
     #[test]
     fn flush_empty_returns_none() {
-        let mut vd = VoiceDetector::new().unwrap();
+        let mut vd = VoiceDetector::new(VadConfig::default()).unwrap();
         assert!(vd.flush().is_none());
     }
 
     #[test]
     fn flush_resets_speaking_state() {
-        let mut vd = VoiceDetector::new().unwrap();
+        let mut vd = VoiceDetector::new(VadConfig::default()).unwrap();
 
         vd.process_samples(&make_voice(20));
         assert!(vd.is_speaking);
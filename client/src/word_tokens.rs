@@ -0,0 +1,164 @@
+use std::ops::Range;
+
+use space_lt_common::style::palette;
+
+/// Most words numbered in one feedback block ('p' then 1-9).
+pub const MAX_WORDS: usize = 9;
+
+/// Numbers the suggested words of a feedback block, line by line.
+///
+/// A word repeated across lines keeps its first number, so the tags shown by
+/// `tag_line` always match the order of `words`.
+#[derive(Default)]
+pub struct WordNumbers {
+    words: Vec<String>,
+}
+
+impl WordNumbers {
+    /// Number the new words of one feedback line and return the line with a
+    /// dim `[n]` tag after each of them.
+    pub fn tag_line(&mut self, content: &str) -> String {
+        let mut out = String::with_capacity(content.len());
+        let mut pos = 0;
+        for (offset, word) in correction_words(content) {
+            let n = match self
+                .words
+                .iter()
+                .position(|w| w.to_lowercase() == word.to_lowercase())
+            {
+                Some(i) => i + 1,
+                None if self.words.len() < MAX_WORDS => {
+                    self.words.push(word.to_string());
+                    self.words.len()
+                }
+                None => continue,
+            };
+            let end = offset + word.len();
+            out.push_str(&content[pos..end]);
+            out.push_str(&palette().dim_inline(&format!("[{n}]")));
+            pos = end;
+        }
+        out.push_str(&content[pos..]);
+        out
+    }
+
+    /// The numbered words, word `n` at index `n - 1`.
+    pub fn into_words(self) -> Vec<String> {
+        self.words
+    }
+}
+
+/// Words suggested by a `"original" → "replacement"` feedback line: the words
+/// of the quoted replacement missing from the original, or the whole
+/// replacement when it only drops or reorders words. Offsets are byte offsets
+/// into `content`; lines without a quoted replacement yield nothing.
+fn correction_words(content: &str) -> Vec<(usize, &str)> {
+    let Some((arrow, arrow_len)) = ["\u{2192}", "->"]
+        .iter()
+        .find_map(|a| content.find(a).map(|i| (i, a.len())))
+    else {
+        return Vec::new();
+    };
+    let before = &content[..arrow];
+    let after_start = arrow + arrow_len;
+    let Some(quoted) = first_quoted(&content[after_start..]) else {
+        return Vec::new();
+    };
+    let start = after_start + quoted.start;
+    let replacement = &content[start..after_start + quoted.end];
+    let original = first_quoted(before).map_or(before, |r| &before[r]);
+    let original: Vec<String> = word_spans(original)
+        .map(|(_, w)| w.to_lowercase())
+        .collect();
+
+    let mut words: Vec<(usize, &str)> = Vec::new();
+    for (offset, word) in word_spans(replacement) {
+        let lower = word.to_lowercase();
+        if !original.contains(&lower) && !words.iter().any(|(_, w)| w.to_lowercase() == lower) {
+            words.push((start + offset, word));
+        }
+    }
+    if words.is_empty() {
+        let phrase = replacement.trim();
+        if !phrase.is_empty() {
+            words.push((
+                start + (phrase.as_ptr() as usize - replacement.as_ptr() as usize),
+                phrase,
+            ));
+        }
+    }
+    words
+}
+
+/// Byte range of the text inside the first pair of quotes.
+fn first_quoted(s: &str) -> Option<Range<usize>> {
+    [('"', '"'), ('\u{201c}', '\u{201d}'), ('\u{ab}', '\u{bb}')]
+        .iter()
+        .filter_map(|&(open, close)| {
+            let inner = s.find(open)? + open.len_utf8();
+            let len = s[inner..].find(close)?;
+            Some(inner..inner + len)
+        })
+        .min_by_key(|r| r.start)
+}
+
+/// Words of `s` with surrounding punctuation removed, with their byte offsets.
+fn word_spans(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    s.split_whitespace().filter_map(move |w| {
+        let word = w.trim_matches(|c: char| !c.is_alphanumeric());
+        (!word.is_empty()).then(|| (word.as_ptr() as usize - s.as_ptr() as usize, word))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(content: &str) -> Vec<&str> {
+        correction_words(content)
+            .into_iter()
+            .map(|(_, w)| w)
+            .collect()
+    }
+
+    #[test]
+    fn picks_words_missing_from_the_original() {
+        assert_eq!(
+            words("\"it is good\" \u{2192} \"it's appealing\" (more natural)"),
+            ["it's", "appealing"]
+        );
+        assert_eq!(
+            words("\u{ab}les oiseau\u{bb} -> \u{ab}les oiseaux\u{bb}"),
+            ["oiseaux"]
+        );
+        // Nothing new: the whole replacement is offered
+        assert_eq!(
+            words("\"I have went\" \u{2192} \"I went\" (past simple)"),
+            ["I went"]
+        );
+        assert!(words("Try to use the past tense here").is_empty());
+        assert!(words("\"good\" \u{2192} something else").is_empty());
+    }
+
+    #[test]
+    fn tags_follow_the_words_and_numbering_spans_lines() {
+        let mut numbers = WordNumbers::default();
+        let first = numbers.tag_line("\"a good\" \u{2192} \"a great, lovely\"");
+        assert_eq!(
+            first,
+            "\"a good\" \u{2192} \"a great\x1b[2m[1]\x1b[22m, lovely\x1b[2m[2]\x1b[22m\""
+        );
+        let second = numbers.tag_line("\"nice\" \u{2192} \"Great\" (stronger)");
+        assert!(second.contains("Great\x1b[2m[1]\x1b[22m"));
+        assert_eq!(numbers.tag_line("no correction here"), "no correction here");
+        assert_eq!(numbers.into_words(), ["great", "lovely"]);
+    }
+
+    #[test]
+    fn numbering_stops_at_nine_words() {
+        let mut numbers = WordNumbers::default();
+        let line = numbers.tag_line("\"x\" \u{2192} \"a b c d e f g h i j k\"");
+        assert!(line.contains("i\x1b[2m[9]\x1b[22m j k"));
+        assert_eq!(numbers.into_words().len(), MAX_WORDS);
+    }
+}
@@ -0,0 +1,220 @@
+//! The client's control socket (`--control`): a local process such as
+//! `space_lt_tray` follows the session state and sends commands back. One
+//! flat JSON object per line each way.
+//!
+//! ```text
+//! client → tray   {"event": "state", "state": "listening"}
+//! tray → client   {"command": "cancel"}
+//! ```
+
+use anyhow::{Result, bail};
+use std::path::PathBuf;
+
+/// What the client is doing, as an indicator shows it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClientState {
+    Idle,
+    Listening,
+    /// Waiting for the tutor's reply.
+    Thinking,
+    /// Playing the tutor's reply.
+    Speaking,
+    /// A prompt waits for a key (a feedback choice, typed text).
+    NeedsInput,
+}
+
+impl ClientState {
+    pub const ALL: [ClientState; 5] = [
+        ClientState::Idle,
+        ClientState::Listening,
+        ClientState::Thinking,
+        ClientState::Speaking,
+        ClientState::NeedsInput,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ClientState::Idle => "idle",
+            ClientState::Listening => "listening",
+            ClientState::Thinking => "thinking",
+            ClientState::Speaking => "speaking",
+            ClientState::NeedsInput => "needs_input",
+        }
+    }
+}
+
+/// What a control process can ask of the client: the keys it stands for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    /// Esc.
+    Cancel,
+    /// '3'.
+    Replay,
+    /// '5'.
+    SlowReplay,
+}
+
+impl Command {
+    pub const ALL: [Command; 3] = [Command::Cancel, Command::Replay, Command::SlowReplay];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::Cancel => "cancel",
+            Command::Replay => "replay",
+            Command::SlowReplay => "slow_replay",
+        }
+    }
+}
+
+/// The line announcing `state` (newline excluded).
+pub fn state_line(state: ClientState) -> String {
+    format!(r#"{{"event": "state", "state": "{}"}}"#, state.name())
+}
+
+/// The state a line announces; `None` for events of other kinds, which a
+/// control process of an older version skips.
+pub fn parse_state(line: &str) -> Result<Option<ClientState>> {
+    let fields = parse_object(line)?;
+    if field(&fields, "event")? != "state" {
+        return Ok(None);
+    }
+    let name = field(&fields, "state")?;
+    match ClientState::ALL.into_iter().find(|s| s.name() == name) {
+        Some(state) => Ok(Some(state)),
+        None => bail!("unknown state \"{name}\""),
+    }
+}
+
+/// The line sending `command` (newline excluded).
+pub fn command_line(command: Command) -> String {
+    format!(r#"{{"command": "{}"}}"#, command.name())
+}
+
+pub fn parse_command(line: &str) -> Result<Command> {
+    let fields = parse_object(line)?;
+    let name = field(&fields, "command")?;
+    match Command::ALL.into_iter().find(|c| c.name() == name) {
+        Some(command) => Ok(command),
+        None => bail!("unknown command \"{name}\""),
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/space_lt_control.sock`, or in the temp dir without one.
+pub fn default_socket_path() -> PathBuf {
+    std::env::var("XDG_RUNTIME_DIR")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("space_lt_control.sock")
+}
+
+fn field<'a>(fields: &'a [(String, String)], key: &str) -> Result<&'a str> {
+    match fields.iter().find(|(k, _)| k == key) {
+        Some((_, value)) => Ok(value),
+        None => bail!("no \"{key}\" field"),
+    }
+}
+
+/// The keys and values of a JSON object whose values are all strings.
+fn parse_object(line: &str) -> Result<Vec<(String, String)>> {
+    let Some(body) = line
+        .trim()
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+    else {
+        bail!("not a JSON object: {line}");
+    };
+    let mut rest = body.trim_start();
+    let mut fields = Vec::new();
+    while !rest.is_empty() {
+        let (key, after) = parse_string(rest)?;
+        let Some(after) = after.trim_start().strip_prefix(':') else {
+            bail!("expected ':' after \"{key}\"");
+        };
+        let (value, after) = parse_string(after.trim_start())?;
+        fields.push((key, value));
+        rest = after.trim_start();
+        if let Some(after) = rest.strip_prefix(',') {
+            rest = after.trim_start();
+        } else if !rest.is_empty() {
+            bail!("expected ',' after \"{}\"", fields[fields.len() - 1].0);
+        }
+    }
+    Ok(fields)
+}
+
+/// The string literal `text` starts with, and what follows it.
+fn parse_string(text: &str) -> Result<(String, &str)> {
+    let Some(body) = text.strip_prefix('"') else {
+        bail!("expected a string at: {text}");
+    };
+    let mut out = String::new();
+    let mut chars = body.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((out, &body[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => out.push('\n'),
+                Some((_, c @ ('"' | '\\' | '/'))) => out.push(c),
+                _ => bail!("unsupported escape in: {text}"),
+            },
+            c => out.push(c),
+        }
+    }
+    bail!("unterminated string: {text}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_state_and_command_survives_its_line() {
+        for state in ClientState::ALL {
+            assert_eq!(parse_state(&state_line(state)).unwrap(), Some(state));
+        }
+        for command in Command::ALL {
+            assert_eq!(parse_command(&command_line(command)).unwrap(), command);
+        }
+        assert_eq!(
+            state_line(ClientState::NeedsInput),
+            r#"{"event": "state", "state": "needs_input"}"#
+        );
+        assert_eq!(command_line(Command::Cancel), r#"{"command": "cancel"}"#);
+    }
+
+    #[test]
+    fn lines_are_read_as_json() {
+        // Spacing and field order are free
+        assert_eq!(
+            parse_state(r#"  {"state":"speaking" ,"event":"state"}  "#).unwrap(),
+            Some(ClientState::Speaking)
+        );
+        assert_eq!(
+            parse_command(r#"{"command": "replay", "from": "tray \"1\""}"#).unwrap(),
+            Command::Replay
+        );
+        // Later event kinds are skipped
+        assert_eq!(
+            parse_state(r#"{"event": "volume", "level": "80"}"#).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn bad_lines_are_errors() {
+        for line in [
+            "",
+            "cancel",
+            r#"{"command": "quit"}"#,
+            r#"{"cmd": "cancel"}"#,
+            r#"{"command" "cancel"}"#,
+            r#"{"command": "cancel" "x": "y"}"#,
+            r#"{"command": "cancel"#,
+        ] {
+            assert!(parse_command(line).is_err(), "{line}");
+        }
+        assert!(parse_state(r#"{"event": "state", "state": "asleep"}"#).is_err());
+    }
+}
@@ -1,3 +1,10 @@
+pub mod control;
 pub mod log;
 pub mod models;
+pub mod paths;
+pub mod profile;
 pub mod protocol;
+pub mod reply_text;
+pub mod style;
+pub mod trace;
+pub mod transport;
@@ -29,7 +29,7 @@ macro_rules! debug {
 #[macro_export]
 macro_rules! warn {
     ($($arg:tt)*) => {{
-        eprint!("\x1b[33mWARNING:\x1b[0m ");
+        eprint!("{} ", $crate::style::palette().warning_label());
         eprintln!($($arg)*)
     }};
 }
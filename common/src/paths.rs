@@ -0,0 +1,45 @@
+//! Where the programs keep their files.
+
+use std::path::PathBuf;
+
+/// `~/.config/space_lt`, or `$XDG_CONFIG_HOME/space_lt` when set: the
+/// client's settings and setup state, and the server's config file.
+pub fn config_dir() -> PathBuf {
+    config_dir_from(
+        std::env::var("XDG_CONFIG_HOME").ok(),
+        std::env::var("HOME").ok(),
+    )
+}
+
+fn config_dir_from(xdg_config_home: Option<String>, home: Option<String>) -> PathBuf {
+    let base = xdg_config_home
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(home.unwrap_or_else(|| ".".to_string())).join(".config"));
+    base.join("space_lt")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_dir_follows_xdg_then_home() {
+        let dir = |xdg: Option<&str>, home: Option<&str>| {
+            config_dir_from(xdg.map(String::from), home.map(String::from))
+        };
+        assert_eq!(
+            dir(Some("/xdg"), Some("/home/u")),
+            PathBuf::from("/xdg/space_lt")
+        );
+        assert_eq!(
+            dir(Some(""), Some("/home/u")),
+            PathBuf::from("/home/u/.config/space_lt")
+        );
+        assert_eq!(
+            dir(None, Some("/home/u")),
+            PathBuf::from("/home/u/.config/space_lt")
+        );
+        assert_eq!(dir(None, None), PathBuf::from("./.config/space_lt"));
+    }
+}
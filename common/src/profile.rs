@@ -0,0 +1,333 @@
+//! Lightweight per-stage timing histograms, enabled with `--profile`.
+//!
+//! Instrumentation points call [`start`] and [`record`] (or [`time`]) around a
+//! stage. While profiling is disabled `start` is a single atomic load and
+//! `record` does nothing, so the hooks can stay in hot paths.
+
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECORDER: Recorder = Recorder::new();
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Start timing a stage. Returns `None` when profiling is disabled.
+pub fn start() -> Option<Instant> {
+    is_enabled().then(Instant::now)
+}
+
+/// Record the time elapsed since `start` under `stage`.
+pub fn record(stage: &'static str, start: Option<Instant>) {
+    if let Some(start) = start {
+        RECORDER.record(stage, start.elapsed());
+    }
+}
+
+/// Run `f` and record its duration under `stage`.
+pub fn time<T>(stage: &'static str, f: impl FnOnce() -> T) -> T {
+    let t = start();
+    let out = f();
+    record(stage, t);
+    out
+}
+
+/// Log the collected timings as a table and optionally write them as JSON.
+pub fn dump(json_path: Option<&Path>) -> Result<()> {
+    let stats = RECORDER.snapshot();
+    crate::info!("{}", format_table(&stats));
+    if let Some(path) = json_path {
+        std::fs::write(path, format_json(&stats))
+            .with_context(|| format!("writing profile to {}", path.display()))?;
+        crate::info!("Profile written to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Summary of one stage's recorded durations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageStats {
+    pub stage: &'static str,
+    pub count: u64,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+/// Thread-safe collection of per-stage histograms.
+pub struct Recorder {
+    stages: Mutex<Vec<(&'static str, Histogram)>>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recorder {
+    pub const fn new() -> Self {
+        Self {
+            stages: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn record(&self, stage: &'static str, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let mut stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        match stages.iter_mut().find(|(name, _)| *name == stage) {
+            Some((_, hist)) => hist.add(micros),
+            None => {
+                let mut hist = Histogram::new();
+                hist.add(micros);
+                stages.push((stage, hist));
+            }
+        }
+    }
+
+    /// Per-stage summaries in first-recorded order.
+    pub fn snapshot(&self) -> Vec<StageStats> {
+        let stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        stages
+            .iter()
+            .map(|(stage, hist)| StageStats {
+                stage,
+                count: hist.count,
+                p50: Duration::from_micros(hist.quantile(0.50)),
+                p95: Duration::from_micros(hist.quantile(0.95)),
+                max: Duration::from_micros(hist.max),
+            })
+            .collect()
+    }
+}
+
+/// Values below this are counted exactly; above it each power of two is split
+/// into `SUB_BUCKETS` linear buckets (at most 12.5% relative error).
+const LINEAR_LIMIT: u64 = 16;
+const SUB_BUCKETS: u64 = 8;
+const SUB_BITS: u32 = 3;
+const BUCKETS: usize = (LINEAR_LIMIT + (64 - 4) * SUB_BUCKETS) as usize;
+
+/// Log-linear histogram of durations in microseconds.
+struct Histogram {
+    counts: Box<[u64; BUCKETS]>,
+    count: u64,
+    max: u64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            counts: Box::new([0; BUCKETS]),
+            count: 0,
+            max: 0,
+        }
+    }
+
+    fn add(&mut self, micros: u64) {
+        self.counts[bucket_index(micros)] += 1;
+        self.count += 1;
+        self.max = self.max.max(micros);
+    }
+
+    /// Upper bound of the bucket holding the `q` quantile, capped at the max.
+    fn quantile(&self, q: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((q * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, &c) in self.counts.iter().enumerate() {
+            seen += c;
+            if seen >= rank {
+                return bucket_upper(i).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+fn bucket_index(v: u64) -> usize {
+    if v < LINEAR_LIMIT {
+        return v as usize;
+    }
+    let exp = 63 - v.leading_zeros();
+    let sub = (v >> (exp - SUB_BITS)) & (SUB_BUCKETS - 1);
+    (LINEAR_LIMIT + (exp as u64 - 4) * SUB_BUCKETS + sub) as usize
+}
+
+fn bucket_upper(index: usize) -> u64 {
+    let index = index as u64;
+    if index < LINEAR_LIMIT {
+        return index;
+    }
+    let exp = (index - LINEAR_LIMIT) / SUB_BUCKETS + 4;
+    let sub = (index - LINEAR_LIMIT) % SUB_BUCKETS;
+    let lower = (SUB_BUCKETS + sub) << (exp - SUB_BITS as u64);
+    lower.saturating_add((1 << (exp - SUB_BITS as u64)) - 1)
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Render stage summaries as an aligned text table (times in milliseconds).
+pub fn format_table(stats: &[StageStats]) -> String {
+    if stats.is_empty() {
+        return "Profile: no timings recorded".to_string();
+    }
+    let width = stats
+        .iter()
+        .map(|s| s.stage.len())
+        .max()
+        .unwrap_or(0)
+        .max("stage".len());
+    let mut out = String::from("Profile (ms):\n");
+    let _ = writeln!(
+        out,
+        "  {:<width$}  {:>8}  {:>10}  {:>10}  {:>10}",
+        "stage", "count", "p50", "p95", "max"
+    );
+    for s in stats {
+        let _ = writeln!(
+            out,
+            "  {:<width$}  {:>8}  {:>10.2}  {:>10.2}  {:>10.2}",
+            s.stage,
+            s.count,
+            ms(s.p50),
+            ms(s.p95),
+            ms(s.max)
+        );
+    }
+    out.pop();
+    out
+}
+
+/// Render stage summaries as a JSON array (times in milliseconds).
+pub fn format_json(stats: &[StageStats]) -> String {
+    let entries: Vec<String> = stats
+        .iter()
+        .map(|s| {
+            format!(
+                r#"{{"stage": "{}", "count": {}, "p50_ms": {:.3}, "p95_ms": {:.3}, "max_ms": {:.3}}}"#,
+                s.stage.replace('\\', "\\\\").replace('"', "\\\""),
+                s.count,
+                ms(s.p50),
+                ms(s.p95),
+                ms(s.max)
+            )
+        })
+        .collect();
+    format!("[\n  {}\n]\n", entries.join(",\n  "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn bucket_bounds_contain_value() {
+        for v in [
+            0,
+            1,
+            15,
+            16,
+            17,
+            100,
+            1_000,
+            123_456,
+            10_000_000,
+            u64::MAX / 2,
+        ] {
+            let i = bucket_index(v);
+            assert!(
+                bucket_upper(i) >= v,
+                "value {v} above bucket {i} upper bound"
+            );
+            if i > 0 {
+                assert!(bucket_upper(i - 1) < v, "value {v} fits in previous bucket");
+            }
+        }
+        assert!(bucket_index(u64::MAX) < BUCKETS);
+    }
+
+    #[test]
+    fn quantiles_are_within_bucket_error() {
+        let rec = Recorder::new();
+        for ms in 1..=100u64 {
+            rec.record("stage", Duration::from_millis(ms));
+        }
+        let s = &rec.snapshot()[0];
+        assert_eq!(s.count, 100);
+        assert_eq!(s.max, Duration::from_millis(100));
+        let p50 = s.p50.as_millis() as f64;
+        let p95 = s.p95.as_millis() as f64;
+        assert!((50.0..=50.0 * 1.125).contains(&p50), "p50 = {p50}");
+        assert!((95.0..=100.0).contains(&p95), "p95 = {p95}");
+    }
+
+    #[test]
+    fn concurrent_records_are_all_counted() {
+        let rec = Arc::new(Recorder::new());
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let rec = rec.clone();
+                std::thread::spawn(move || {
+                    let stage = if t % 2 == 0 { "even" } else { "odd" };
+                    for i in 0..1000 {
+                        rec.record(stage, Duration::from_micros(i));
+                        rec.record("shared", Duration::from_micros(t));
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        let stats = rec.snapshot();
+        let count = |name: &str| stats.iter().find(|s| s.stage == name).unwrap().count;
+        assert_eq!(stats.len(), 3);
+        assert_eq!(count("even"), 4000);
+        assert_eq!(count("odd"), 4000);
+        assert_eq!(count("shared"), 8000);
+        let shared = stats.iter().find(|s| s.stage == "shared").unwrap();
+        assert_eq!(shared.max, Duration::from_micros(7));
+    }
+
+    #[test]
+    fn disabled_recorder_skips_timing() {
+        // The global flag is off by default in tests
+        assert!(start().is_none());
+        assert_eq!(time("noop", || 42), 42);
+    }
+
+    #[test]
+    fn table_and_json_format() {
+        let stats = vec![StageStats {
+            stage: "transcription",
+            count: 3,
+            p50: Duration::from_micros(1500),
+            p95: Duration::from_millis(2),
+            max: Duration::from_millis(3),
+        }];
+        let table = format_table(&stats);
+        assert!(table.contains("transcription"));
+        assert!(table.contains("1.50"));
+        let json = format_json(&stats);
+        assert!(json.contains(r#""stage": "transcription""#));
+        assert!(json.contains(r#""p95_ms": 2.000"#));
+        assert_eq!(format_table(&[]), "Profile: no timings recorded");
+    }
+}
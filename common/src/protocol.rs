@@ -1,6 +1,8 @@
 use anyhow::{Result, bail};
 use std::io::{ErrorKind, Read, Write};
 
+use crate::{profile, trace};
+
 /// Check if an error indicates a peer disconnection (EOF, broken pipe, or reset).
 ///
 /// Shared by both client and server for consistent disconnect detection.
@@ -19,12 +21,86 @@ pub fn is_disconnect(e: &anyhow::Error) -> bool {
 
 #[derive(Debug)]
 pub enum ClientMsg {
-    AudioSegment(Vec<i16>), // tag 0x01, payload = raw i16 LE bytes
-    PauseRequest,           // tag 0x02, empty payload
-    ResumeRequest,          // tag 0x03, empty payload
-    InterruptTts,           // tag 0x04, empty payload
-    FeedbackChoice(bool),   // tag 0x05, payload = 1 byte (0x01=continue, 0x00=retry)
-    SummaryRequest,         // tag 0x06, empty payload
+    AudioSegment(Vec<i16>),         // tag 0x01, payload = raw i16 LE bytes
+    PauseRequest,                   // tag 0x02, empty payload
+    ResumeRequest,                  // tag 0x03, empty payload
+    InterruptTts,                   // tag 0x04, empty payload
+    FeedbackChoice(bool),           // tag 0x05, payload = 1 byte (0x01=continue, 0x00=retry)
+    SummaryRequest,                 // tag 0x06, empty payload
+    TextInput(String),              // tag 0x07, payload = UTF-8 (typed instead of spoken)
+    SessionTakeover(bool),          // tag 0x08, payload = 1 byte (0x01=take over, 0x00=start fresh)
+    SpeakWord(String),              // tag 0x09, payload = UTF-8 (word to pronounce on its own)
+    EnableTimings,                  // tag 0x0A, empty payload (send TurnStats after each exchange)
+    AudioInput(AudioInputInfo), // tag 0x0B, payload = see AudioInputInfo (sent once, for the logs)
+    TranslateLast,              // tag 0x0C, empty payload (translate the last reply)
+    SimplifyLast,               // tag 0x0D, empty payload (rephrase the last reply more simply)
+    DisregardLast,              // tag 0x0E, empty payload (the last message was an aside)
+    BranchTo(u32),              // tag 0x0F, payload = u32 LE turn to rewind the conversation to
+    ListVoices,                 // tag 0x10, empty payload (answered by VoiceList)
+    SetVoice(String),           // tag 0x11, payload = UTF-8 voice name for the following replies
+    CancelExchange,             // tag 0x12, empty payload (drop the exchange in flight)
+    SetLanguage(String), // tag 0x13, payload = UTF-8 language code for the following segments
+    NumberedSegment(u32, Vec<i16>), // tag 0x14, payload = u32 LE sequence number, then raw i16 LE bytes
+}
+
+/// The client's capture setup, reported once at session start.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AudioInputInfo {
+    /// Input device name as shown by the audio host.
+    pub device: String,
+    /// Native capture rate of the device, in Hz.
+    pub sample_rate: u32,
+    /// Native channel count of the device.
+    pub channels: u16,
+    /// How captured audio is converted to 16 kHz mono (e.g. "sinc 48000 → 16000 Hz, 2 ch → mono").
+    pub resampler: String,
+}
+
+impl AudioInputInfo {
+    /// Wire payload: `[sample_rate u32 LE][channels u16 LE][device_len u16 LE][device][resampler]`,
+    /// both strings UTF-8 (the resampler takes the rest of the payload).
+    fn encode(&self) -> Vec<u8> {
+        let mut end = self.device.len().min(u16::MAX as usize);
+        while !self.device.is_char_boundary(end) {
+            end -= 1;
+        }
+        let device = &self.device.as_bytes()[..end];
+        let mut out = Vec::with_capacity(8 + device.len() + self.resampler.len());
+        out.extend_from_slice(&self.sample_rate.to_le_bytes());
+        out.extend_from_slice(&self.channels.to_le_bytes());
+        out.extend_from_slice(&(device.len() as u16).to_le_bytes());
+        out.extend_from_slice(device);
+        out.extend_from_slice(self.resampler.as_bytes());
+        out
+    }
+
+    fn decode(payload: &[u8]) -> Result<Self> {
+        if payload.len() < 8 {
+            bail!("AudioInput payload length {} is below 8", payload.len());
+        }
+        let sample_rate = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+        let channels = u16::from_le_bytes([payload[4], payload[5]]);
+        let device_len = u16::from_le_bytes([payload[6], payload[7]]) as usize;
+        let Some(device) = payload.get(8..8 + device_len) else {
+            bail!("AudioInput device name overruns the payload");
+        };
+        Ok(Self {
+            device: String::from_utf8(device.to_vec())?,
+            sample_rate,
+            channels,
+            resampler: String::from_utf8(payload[8 + device_len..].to_vec())?,
+        })
+    }
+}
+
+impl std::fmt::Display for AudioInputInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} \u{2014} {} Hz, {} ch, resampler: {}",
+            self.device, self.sample_rate, self.channels, self.resampler
+        )
+    }
 }
 
 // --- Server messages (server → client, tags 0x80-0xFF) ---
@@ -32,6 +108,7 @@ pub enum ClientMsg {
 #[derive(Debug)]
 pub enum ServerMsg {
     Ready,                      // tag 0x80, empty payload
+    ReadyActiveSession(u64),    // tag 0x80, payload = u64 LE unix secs the running session started
     Text(String),               // tag 0x81, payload = UTF-8
     Error(String),              // tag 0x82, payload = UTF-8
     TtsAudioChunk(Vec<i16>),    // tag 0x83, payload = raw i16 LE bytes
@@ -39,6 +116,78 @@ pub enum ServerMsg {
     Feedback(String),           // tag 0x85, payload = UTF-8 (language feedback, not spoken)
     SessionSummary(String),     // tag 0x86, payload = UTF-8 markdown
     StatusNotification(String), // tag 0x87, payload = UTF-8 (e.g. "Thinking...", "Searching the web...")
+    SessionEnded(String), // tag 0x88, payload = UTF-8 reason (sent before a deliberate teardown)
+    TurnStats(TurnStats), // tag 0x89, payload = 4 × u32 LE milliseconds (see TurnStats)
+    Translation(String),  // tag 0x8A, payload = UTF-8 (translation of the last reply, not spoken)
+    Warning(String),      // tag 0x8B, payload = UTF-8 (setup problem, sent right after Ready)
+    VoiceList(Vec<String>), // tag 0x8C, payload = UTF-8 voice names separated by '\n'
+    PartialTranscript(String), // tag 0x8D, payload = UTF-8 (transcription so far, replaced by Text)
+    SegmentReceived(u32), // tag 0x8E, payload = u32 LE sequence number of a NumberedSegment
+}
+
+/// Prefix of a `ServerMsg::Error` for a failure limited to one exchange (e.g. a
+/// segment that could not be transcribed): the session goes on and the user can
+/// simply try again.
+pub const RETRYABLE_ERROR_PREFIX: &str = "retry: ";
+
+/// Opens a `TranscribedText` spoken in a language the server does not expect
+/// (`--language auto` detected one outside `--languages`): `[lang:de] Hallo`.
+const LANGUAGE_TAG_OPEN: &str = "[lang:";
+
+/// `text` tagged as spoken in the unexpected `language`.
+pub fn tag_language(language: &str, text: &str) -> String {
+    format!("{LANGUAGE_TAG_OPEN}{language}] {text}")
+}
+
+/// The language tag of a `TranscribedText`, if any, and the text without it.
+pub fn split_language_tag(text: &str) -> (Option<&str>, &str) {
+    let tagged = text
+        .strip_prefix(LANGUAGE_TAG_OPEN)
+        .and_then(|rest| rest.split_once(']'))
+        .filter(|(language, _)| !language.is_empty() && !language.contains(' '));
+    match tagged {
+        Some((language, rest)) => (Some(language), rest.trim_start()),
+        None => (None, text),
+    }
+}
+
+/// Latency breakdown of one exchange, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TurnStats {
+    /// From receiving the user's turn to forwarding its text (transcription).
+    pub stt_ms: u32,
+    /// From forwarding the text to the orchestrator's first answer (feedback or reply).
+    pub llm_ms: u32,
+    /// From receiving the reply to its first audio chunk (synthesis).
+    pub tts_ms: u32,
+    /// From receiving the user's turn to the first audio chunk, without time
+    /// spent waiting for a feedback choice.
+    pub first_audio_ms: u32,
+}
+
+impl TurnStats {
+    /// Wire payload: the four fields as u32 LE, in declaration order.
+    fn encode(&self) -> Vec<u8> {
+        [self.stt_ms, self.llm_ms, self.tts_ms, self.first_audio_ms]
+            .iter()
+            .flat_map(|ms| ms.to_le_bytes())
+            .collect()
+    }
+
+    fn decode(payload: &[u8]) -> Result<Self> {
+        let Ok(payload) = <&[u8; 16]>::try_from(payload) else {
+            bail!("TurnStats payload length {} is not 16", payload.len());
+        };
+        let ms = |i: usize| {
+            u32::from_le_bytes([payload[i], payload[i + 1], payload[i + 2], payload[i + 3]])
+        };
+        Ok(Self {
+            stt_ms: ms(0),
+            llm_ms: ms(4),
+            tts_ms: ms(8),
+            first_audio_ms: ms(12),
+        })
+    }
 }
 
 // --- Orchestrator messages (orchestrator ↔ server, tags 0xA0-0xBF, Unix socket) ---
@@ -54,6 +203,12 @@ pub enum OrchestratorMsg {
     SummaryRequest,             // tag 0xA6, empty payload
     SummaryResponse(String),    // tag 0xA7, payload = UTF-8 markdown
     StatusNotification(String), // tag 0xA8, payload = UTF-8 (e.g. "Thinking...", "Searching the web...")
+    TranslateRequest,           // tag 0xA9, empty payload
+    Translation(String),        // tag 0xAA, payload = UTF-8 (translation for display)
+    SimplifyRequest,            // tag 0xAB, empty payload
+    DisregardLast,              // tag 0xAC, empty payload
+    BranchTo(u32),              // tag 0xAD, payload = u32 LE turn
+    CancelExchange,             // tag 0xAE, empty payload
 }
 
 // --- Server-to-Orchestrator messages (read by orchestrator, combines server + orchestrator tags) ---
@@ -63,173 +218,668 @@ pub enum ServerOrcMsg {
     Ready,                   // tag 0x80, empty payload
     Error(String),           // tag 0x82, payload = UTF-8
     TranscribedText(String), // tag 0xA0, payload = UTF-8
+    SessionEnd,              // tag 0xA3, empty payload
     FeedbackChoice(bool),    // tag 0xA5, payload = 1 byte (0x01=continue, 0x00=retry)
     SummaryRequest,          // tag 0xA6, empty payload
+    TranslateRequest,        // tag 0xA9, empty payload
+    SimplifyRequest,         // tag 0xAB, empty payload
+    DisregardLast,           // tag 0xAC, empty payload
+    BranchTo(u32),           // tag 0xAD, payload = u32 LE turn
+    CancelExchange,          // tag 0xAE, empty payload
 }
 
-// --- Wire format: [tag: u8][length: u32 LE][payload] ---
+/// Payload of VoiceList: the names joined by newlines (none: empty payload).
+fn encode_voices(voices: &[String]) -> Vec<u8> {
+    voices.join("\n").into_bytes()
+}
 
-pub fn write_client_msg(w: &mut impl Write, msg: &ClientMsg) -> Result<()> {
-    match msg {
-        ClientMsg::AudioSegment(samples) => {
-            let payload_len = samples.len() * 2; // i16 = 2 bytes
-            w.write_all(&[0x01])?;
-            w.write_all(&(payload_len as u32).to_le_bytes())?;
-            for &s in samples {
-                w.write_all(&s.to_le_bytes())?;
-            }
-            w.flush()?;
-        }
-        ClientMsg::PauseRequest => {
-            w.write_all(&[0x02])?;
-            w.write_all(&0u32.to_le_bytes())?;
-            w.flush()?;
-        }
-        ClientMsg::ResumeRequest => {
-            w.write_all(&[0x03])?;
-            w.write_all(&0u32.to_le_bytes())?;
-            w.flush()?;
-        }
-        ClientMsg::InterruptTts => {
-            w.write_all(&[0x04])?;
-            w.write_all(&0u32.to_le_bytes())?;
-            w.flush()?;
-        }
-        ClientMsg::FeedbackChoice(proceed) => {
-            w.write_all(&[0x05])?;
-            w.write_all(&1u32.to_le_bytes())?;
-            w.write_all(&[if *proceed { 0x01 } else { 0x00 }])?;
-            w.flush()?;
-        }
-        ClientMsg::SummaryRequest => {
-            w.write_all(&[0x06])?;
-            w.write_all(&0u32.to_le_bytes())?;
-            w.flush()?;
-        }
+fn decode_voices(payload: Vec<u8>) -> Result<Vec<String>> {
+    let text = String::from_utf8(payload)?;
+    Ok(text
+        .split('\n')
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Payload of NumberedSegment: the sequence number as u32 LE, then the samples.
+fn encode_numbered_segment(seq: u32, samples: &[i16]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + samples.len() * 2);
+    out.extend_from_slice(&seq.to_le_bytes());
+    for sample in samples {
+        out.extend_from_slice(&sample.to_le_bytes());
     }
-    Ok(())
+    out
 }
 
-pub fn read_client_msg(r: &mut impl Read) -> Result<ClientMsg> {
-    let mut tag = [0u8; 1];
-    r.read_exact(&mut tag)?;
+fn decode_numbered_segment(payload: &[u8]) -> Result<(u32, Vec<i16>)> {
+    let Some((seq, samples)) = payload.split_first_chunk::<4>() else {
+        bail!(
+            "NumberedSegment payload length {} is below 4",
+            payload.len()
+        );
+    };
+    if !samples.len().is_multiple_of(2) {
+        bail!(
+            "NumberedSegment has {} sample bytes, not a multiple of 2",
+            samples.len()
+        );
+    }
+    let samples = samples
+        .chunks_exact(2)
+        .map(|c| i16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    Ok((u32::from_le_bytes(*seq), samples))
+}
 
-    let mut len_buf = [0u8; 4];
-    r.read_exact(&mut len_buf)?;
-    let len = u32::from_le_bytes(len_buf) as usize;
+/// Payload of SegmentReceived: the sequence number as u32 LE.
+fn decode_segment_seq(payload: &[u8]) -> Result<u32> {
+    let Ok(seq) = <[u8; 4]>::try_from(payload) else {
+        bail!("SegmentReceived payload length {} is not 4", payload.len());
+    };
+    Ok(u32::from_le_bytes(seq))
+}
 
-    match tag[0] {
-        0x01 => {
-            if !len.is_multiple_of(2) {
-                bail!("AudioSegment payload length {len} is not a multiple of 2");
-            }
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            let samples: Vec<i16> = payload
-                .chunks_exact(2)
-                .map(|c| i16::from_le_bytes([c[0], c[1]]))
-                .collect();
-            Ok(ClientMsg::AudioSegment(samples))
+/// Payload of the BranchTo messages: the turn as u32 LE.
+fn decode_turn(payload: &[u8]) -> Result<u32> {
+    let Ok(turn) = <[u8; 4]>::try_from(payload) else {
+        bail!("BranchTo payload length {} is not 4", payload.len());
+    };
+    Ok(u32::from_le_bytes(turn))
+}
+
+// --- Message table: the single description of every tag ---
+// The readers and writers below dispatch on it, and `--dump-protocol` prints it.
+
+/// Revision of the wire format described by the message tables. Bump it when a
+/// tag is added or a payload changes.
+pub const PROTOCOL_VERSION: u32 = 11;
+
+/// Which way a message travels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    ClientToServer,
+    ServerToClient,
+    OrchestratorToServer,
+    ServerToOrchestrator,
+}
+
+impl Direction {
+    fn id(self) -> &'static str {
+        match self {
+            Direction::ClientToServer => "client_to_server",
+            Direction::ServerToClient => "server_to_client",
+            Direction::OrchestratorToServer => "orchestrator_to_server",
+            Direction::ServerToOrchestrator => "server_to_orchestrator",
         }
-        0x02 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
-            }
-            Ok(ClientMsg::PauseRequest)
+    }
+}
+
+/// How a message's payload is encoded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Payload {
+    /// No payload; stray bytes are skipped.
+    Empty,
+    /// UTF-8 text taking the whole payload.
+    Utf8,
+    /// One byte, 0x00 = false; an empty payload reads as `default`.
+    Flag { default: bool },
+    /// i16 samples, little-endian.
+    Samples,
+    /// A fixed layout, decoded by the message itself.
+    Struct(&'static str),
+}
+
+impl Payload {
+    fn id(self) -> &'static str {
+        match self {
+            Payload::Empty => "empty",
+            Payload::Utf8 => "utf8",
+            Payload::Flag { .. } => "flag",
+            Payload::Samples => "i16_le_samples",
+            Payload::Struct(_) => "struct",
         }
-        0x03 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
-            }
-            Ok(ClientMsg::ResumeRequest)
+    }
+}
+
+/// One row of a message table.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageSpec {
+    pub tag: u8,
+    pub name: &'static str,
+    pub directions: &'static [Direction],
+    pub payload: Payload,
+    pub description: &'static str,
+}
+
+const C2S: &[Direction] = &[Direction::ClientToServer];
+const S2C: &[Direction] = &[Direction::ServerToClient];
+const S2C_S2O: &[Direction] = &[Direction::ServerToClient, Direction::ServerToOrchestrator];
+const O2S: &[Direction] = &[Direction::OrchestratorToServer];
+const S2O: &[Direction] = &[Direction::ServerToOrchestrator];
+const O2S_S2O: &[Direction] = &[
+    Direction::OrchestratorToServer,
+    Direction::ServerToOrchestrator,
+];
+
+const fn spec(
+    tag: u8,
+    name: &'static str,
+    directions: &'static [Direction],
+    payload: Payload,
+    description: &'static str,
+) -> MessageSpec {
+    MessageSpec {
+        tag,
+        name,
+        directions,
+        payload,
+        description,
+    }
+}
+
+/// Client → server messages (TCP, tags 0x01-0x7F).
+pub const CLIENT_MESSAGES: &[MessageSpec] = &[
+    spec(
+        0x01,
+        "AudioSegment",
+        C2S,
+        Payload::Samples,
+        "One speech segment, 16 kHz mono",
+    ),
+    spec(0x02, "PauseRequest", C2S, Payload::Empty, "Stop listening"),
+    spec(0x03, "ResumeRequest", C2S, Payload::Empty, "Listen again"),
+    spec(
+        0x04,
+        "InterruptTts",
+        C2S,
+        Payload::Empty,
+        "Barge-in: stop the current reply",
+    ),
+    spec(
+        0x05,
+        "FeedbackChoice",
+        C2S,
+        Payload::Flag { default: true },
+        "After a correction: true = continue, false = retry",
+    ),
+    spec(
+        0x06,
+        "SummaryRequest",
+        C2S,
+        Payload::Empty,
+        "End the session with a summary",
+    ),
+    spec(0x07, "TextInput", C2S, Payload::Utf8, "A typed turn"),
+    spec(
+        0x08,
+        "SessionTakeover",
+        C2S,
+        Payload::Flag { default: false },
+        "Answer to ReadyActiveSession: true = take over, false = start fresh",
+    ),
+    spec(
+        0x09,
+        "SpeakWord",
+        C2S,
+        Payload::Utf8,
+        "A word to pronounce on its own",
+    ),
+    spec(
+        0x0A,
+        "EnableTimings",
+        C2S,
+        Payload::Empty,
+        "Send TurnStats after each exchange",
+    ),
+    spec(
+        0x0B,
+        "AudioInput",
+        C2S,
+        Payload::Struct(
+            "u32 LE sample rate, u16 LE channels, u16 LE device name length, device name (UTF-8), resampler (UTF-8, rest of payload)",
+        ),
+        "The capture setup, sent once for the logs",
+    ),
+    spec(
+        0x0C,
+        "TranslateLast",
+        C2S,
+        Payload::Empty,
+        "Translate the last reply",
+    ),
+    spec(
+        0x0D,
+        "SimplifyLast",
+        C2S,
+        Payload::Empty,
+        "Rephrase the last reply in simpler words",
+    ),
+    spec(
+        0x0E,
+        "DisregardLast",
+        C2S,
+        Payload::Empty,
+        "The last message was not meant for the tutor",
+    ),
+    spec(
+        0x0F,
+        "BranchTo",
+        C2S,
+        Payload::Struct("u32 LE turn number"),
+        "Rewind the conversation to just after this turn",
+    ),
+    spec(
+        0x10,
+        "ListVoices",
+        C2S,
+        Payload::Empty,
+        "Ask for the TTS voices, answered by VoiceList",
+    ),
+    spec(
+        0x11,
+        "SetVoice",
+        C2S,
+        Payload::Utf8,
+        "Speak the following replies with this voice; an unknown name gets an Error",
+    ),
+    spec(
+        0x12,
+        "CancelExchange",
+        C2S,
+        Payload::Empty,
+        "Drop the exchange in flight: its transcription and its reply",
+    ),
+    spec(
+        0x13,
+        "SetLanguage",
+        C2S,
+        Payload::Utf8,
+        "Transcribe the following segments in this language (or auto); an unknown code gets an Error",
+    ),
+    spec(
+        0x14,
+        "NumberedSegment",
+        C2S,
+        Payload::Struct("u32 LE sequence number, then i16 LE samples"),
+        "An AudioSegment the server acknowledges with SegmentReceived; a copy sent again is dropped",
+    ),
+];
+
+/// Server → client messages (TCP, tags 0x80-0x9F).
+pub const SERVER_MESSAGES: &[MessageSpec] = &[
+    spec(
+        0x80,
+        "Ready",
+        S2C_S2O,
+        Payload::Struct(
+            "empty, or u64 LE unix seconds when the running session started (ReadyActiveSession)",
+        ),
+        "Session ready; to the orchestrator, the answer to SessionStart",
+    ),
+    spec(0x81, "Text", S2C, Payload::Utf8, "Transcribed text"),
+    spec(
+        0x82,
+        "Error",
+        S2C_S2O,
+        Payload::Utf8,
+        "Error message; a `retry: ` prefix means only this exchange failed",
+    ),
+    spec(
+        0x83,
+        "TtsAudioChunk",
+        S2C,
+        Payload::Samples,
+        "Synthesized speech",
+    ),
+    spec(
+        0x84,
+        "TtsEnd",
+        S2C,
+        Payload::Empty,
+        "End of the current reply's audio",
+    ),
+    spec(
+        0x85,
+        "Feedback",
+        S2C,
+        Payload::Utf8,
+        "Language feedback, displayed and not spoken",
+    ),
+    spec(
+        0x86,
+        "SessionSummary",
+        S2C,
+        Payload::Utf8,
+        "Session summary (markdown)",
+    ),
+    spec(
+        0x87,
+        "StatusNotification",
+        S2C,
+        Payload::Utf8,
+        "Progress, e.g. \"Thinking...\"",
+    ),
+    spec(
+        0x88,
+        "SessionEnded",
+        S2C,
+        Payload::Utf8,
+        "Reason, sent before a deliberate teardown",
+    ),
+    spec(
+        0x89,
+        "TurnStats",
+        S2C,
+        Payload::Struct("4 x u32 LE milliseconds: stt, llm, tts, first audio"),
+        "Latency breakdown of the last exchange",
+    ),
+    spec(
+        0x8A,
+        "Translation",
+        S2C,
+        Payload::Utf8,
+        "Translation of the last reply, not spoken",
+    ),
+    spec(
+        0x8B,
+        "Warning",
+        S2C,
+        Payload::Utf8,
+        "Setup problem to show the user (e.g. a TTS language mismatch), sent right after Ready",
+    ),
+    spec(
+        0x8C,
+        "VoiceList",
+        S2C,
+        Payload::Struct("voice names (UTF-8), separated by \\n"),
+        "The TTS voices SetVoice accepts",
+    ),
+    spec(
+        0x8D,
+        "PartialTranscript",
+        S2C,
+        Payload::Utf8,
+        "Transcription so far of the segment being transcribed; its Text follows",
+    ),
+    spec(
+        0x8E,
+        "SegmentReceived",
+        S2C,
+        Payload::Struct("u32 LE sequence number"),
+        "Acknowledges a NumberedSegment; one left unacknowledged is sent again",
+    ),
+];
+
+/// Orchestrator ↔ server messages (Unix socket, tags 0xA0-0xBF).
+pub const ORCHESTRATOR_MESSAGES: &[MessageSpec] = &[
+    spec(
+        0xA0,
+        "TranscribedText",
+        S2O,
+        Payload::Utf8,
+        "The user's turn; a `[lang:xx] ` prefix marks a language outside the server's `--languages`",
+    ),
+    spec(
+        0xA1,
+        "ResponseText",
+        O2S,
+        Payload::Utf8,
+        "The tutor's reply, to synthesize",
+    ),
+    spec(
+        0xA2,
+        "SessionStart",
+        O2S,
+        Payload::Utf8,
+        "Session config (JSON)",
+    ),
+    spec(
+        0xA3,
+        "SessionEnd",
+        O2S_S2O,
+        Payload::Empty,
+        "End the session; from the server, it is shutting down",
+    ),
+    spec(
+        0xA4,
+        "FeedbackText",
+        O2S,
+        Payload::Utf8,
+        "Language feedback for display",
+    ),
+    spec(
+        0xA5,
+        "FeedbackChoice",
+        S2O,
+        Payload::Flag { default: true },
+        "The user's choice after a correction: true = continue, false = retry",
+    ),
+    spec(
+        0xA6,
+        "SummaryRequest",
+        S2O,
+        Payload::Empty,
+        "The user asked for a summary",
+    ),
+    spec(
+        0xA7,
+        "SummaryResponse",
+        O2S,
+        Payload::Utf8,
+        "Session summary (markdown)",
+    ),
+    spec(
+        0xA8,
+        "StatusNotification",
+        O2S,
+        Payload::Utf8,
+        "Progress, e.g. \"Thinking...\"",
+    ),
+    spec(
+        0xA9,
+        "TranslateRequest",
+        S2O,
+        Payload::Empty,
+        "Translate the last reply",
+    ),
+    spec(
+        0xAA,
+        "Translation",
+        O2S,
+        Payload::Utf8,
+        "Translation for display",
+    ),
+    spec(
+        0xAB,
+        "SimplifyRequest",
+        S2O,
+        Payload::Empty,
+        "Rephrase the last reply in simpler words, as a ResponseText",
+    ),
+    spec(
+        0xAC,
+        "DisregardLast",
+        S2O,
+        Payload::Empty,
+        "Ignore the last TranscribedText (and drop its reply if not sent yet)",
+    ),
+    spec(
+        0xAD,
+        "BranchTo",
+        S2O,
+        Payload::Struct("u32 LE turn number"),
+        "Rewind the conversation to just after this turn, announced as a ResponseText",
+    ),
+    spec(
+        0xAE,
+        "CancelExchange",
+        S2O,
+        Payload::Empty,
+        "The user cancelled the last TranscribedText: its reply is dropped, forget the exchange",
+    ),
+];
+
+/// Every message table, in tag order.
+pub const MESSAGE_TABLES: [&[MessageSpec]; 3] =
+    [CLIENT_MESSAGES, SERVER_MESSAGES, ORCHESTRATOR_MESSAGES];
+
+/// Machine-readable description of the wire format (`--dump-protocol`), as JSON.
+pub fn describe_protocol_json() -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let entries: Vec<String> = MESSAGE_TABLES
+        .iter()
+        .flat_map(|table| table.iter())
+        .map(|m| {
+            let directions: Vec<String> = m.directions.iter().map(|d| quote(d.id())).collect();
+            let detail = match m.payload {
+                Payload::Flag { default } => format!(r#", "default": {default}"#),
+                Payload::Struct(layout) => format!(r#", "layout": {}"#, quote(layout)),
+                _ => String::new(),
+            };
+            format!(
+                r#"{{"tag": "0x{:02X}", "name": {}, "directions": [{}], "payload": "{}"{detail}, "description": {}}}"#,
+                m.tag,
+                quote(m.name),
+                directions.join(", "),
+                m.payload.id(),
+                quote(m.description)
+            )
+        })
+        .collect();
+    format!(
+        "{{\n  \"version\": {PROTOCOL_VERSION},\n  \"framing\": \"[tag u8][length u32 LE][payload]\",\n  \"messages\": [\n    {}\n  ]\n}}\n",
+        entries.join(",\n    ")
+    )
+}
+
+// --- Wire format: [tag: u8][length: u32 LE][payload] ---
+// Writers encode into the (buffered) writer, then flush once; the two steps are
+// profiled separately as `protocol_encode` and `socket_write`.
+
+/// Payload of an outgoing message, encoded as its table row says.
+enum Body<'a> {
+    Empty,
+    Text(&'a str),
+    Flag(bool),
+    Samples(&'a [i16]),
+    Bytes(Vec<u8>),
+}
+
+impl Body<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Body::Empty => 0,
+            Body::Text(text) => text.len(),
+            Body::Flag(_) => 1,
+            Body::Samples(samples) => samples.len() * 2,
+            Body::Bytes(payload) => payload.len(),
         }
-        0x04 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
-            }
-            Ok(ClientMsg::InterruptTts)
-        }
-        0x05 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            let proceed = payload.first().copied().unwrap_or(0x01) != 0x00;
-            Ok(ClientMsg::FeedbackChoice(proceed))
-        }
-        0x06 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
-            }
-            Ok(ClientMsg::SummaryRequest)
+    }
+
+    fn preview(&self) -> trace::Preview<'_> {
+        match self {
+            Body::Empty => trace::Preview::Empty,
+            Body::Text(text) => trace::Preview::Text(text),
+            Body::Flag(flag) => trace::Preview::Flag(*flag),
+            Body::Samples(samples) => trace::Preview::Samples(samples.len()),
+            Body::Bytes(_) => trace::Preview::Bytes,
         }
-        other => bail!("Unknown client message tag: 0x{other:02x}"),
+    }
+
+    fn fits(&self, payload: Payload) -> bool {
+        matches!(
+            (self, payload),
+            (Body::Empty, Payload::Empty)
+                | (Body::Text(_), Payload::Utf8)
+                | (Body::Flag(_), Payload::Flag { .. })
+                | (Body::Samples(_), Payload::Samples)
+                | (Body::Bytes(_), Payload::Struct(_))
+        )
     }
 }
 
-pub fn write_server_msg(w: &mut impl Write, msg: &ServerMsg) -> Result<()> {
-    match msg {
-        ServerMsg::Ready => {
-            w.write_all(&[0x80])?;
-            w.write_all(&0u32.to_le_bytes())?;
-            w.flush()?;
-        }
-        ServerMsg::Text(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0x81])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
+/// Payload of an incoming message, decoded as its table row says.
+enum Value {
+    Empty,
+    Text(String),
+    Flag(bool),
+    Samples(Vec<i16>),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    fn preview(&self) -> trace::Preview<'_> {
+        match self {
+            Value::Empty => trace::Preview::Empty,
+            Value::Text(text) => trace::Preview::Text(text),
+            Value::Flag(flag) => trace::Preview::Flag(*flag),
+            Value::Samples(samples) => trace::Preview::Samples(samples.len()),
+            Value::Bytes(_) => trace::Preview::Bytes,
         }
-        ServerMsg::Error(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0x82])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
+    }
+}
+
+fn by_tag(table: &'static [MessageSpec], tag: u8) -> Option<&'static MessageSpec> {
+    table.iter().find(|m| m.tag == tag)
+}
+
+/// Write the message called `name` in `table`.
+fn write_frame(w: &mut impl Write, table: &[MessageSpec], name: &str, body: Body) -> Result<()> {
+    let encode = profile::start();
+    let Some(spec) = table.iter().find(|m| m.name == name) else {
+        bail!("{name} is missing from the protocol table");
+    };
+    debug_assert!(
+        body.fits(spec.payload),
+        "{name} does not match its table row"
+    );
+    if trace::is_enabled() {
+        trace::frame(
+            trace::Direction::Sent,
+            spec.name,
+            spec.tag,
+            body.len(),
+            body.preview(),
+        );
+    }
+    w.write_all(&[spec.tag])?;
+    match body {
+        Body::Empty => w.write_all(&0u32.to_le_bytes())?,
+        Body::Text(text) => {
+            w.write_all(&(text.len() as u32).to_le_bytes())?;
+            w.write_all(text.as_bytes())?;
+        }
+        Body::Flag(flag) => {
+            w.write_all(&1u32.to_le_bytes())?;
+            w.write_all(&[flag as u8])?;
         }
-        ServerMsg::TtsAudioChunk(samples) => {
+        Body::Samples(samples) => {
             let payload_len = samples.len() * 2; // i16 = 2 bytes
-            w.write_all(&[0x83])?;
             w.write_all(&(payload_len as u32).to_le_bytes())?;
             for &s in samples {
                 w.write_all(&s.to_le_bytes())?;
             }
-            w.flush()?;
-        }
-        ServerMsg::TtsEnd => {
-            w.write_all(&[0x84])?;
-            w.write_all(&0u32.to_le_bytes())?;
-            w.flush()?;
-        }
-        ServerMsg::Feedback(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0x85])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
         }
-        ServerMsg::SessionSummary(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0x86])?;
+        Body::Bytes(payload) => {
             w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
-        }
-        ServerMsg::StatusNotification(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0x87])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
+            w.write_all(&payload)?;
         }
     }
+    profile::record("protocol_encode", encode);
+    let flush = profile::start();
+    w.flush()?;
+    profile::record("socket_write", flush);
     Ok(())
 }
 
-pub fn read_server_msg(r: &mut impl Read) -> Result<ServerMsg> {
+/// Read one frame, look its tag up with `find` and decode the payload as the
+/// row says. Returns the message name for the caller's dispatch.
+fn read_frame(
+    r: &mut impl Read,
+    kind: &str,
+    find: impl Fn(u8) -> Option<&'static MessageSpec>,
+) -> Result<(&'static str, Value)> {
     let mut tag = [0u8; 1];
     r.read_exact(&mut tag)?;
 
@@ -237,241 +887,227 @@ pub fn read_server_msg(r: &mut impl Read) -> Result<ServerMsg> {
     r.read_exact(&mut len_buf)?;
     let len = u32::from_le_bytes(len_buf) as usize;
 
-    match tag[0] {
-        0x80 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
-            }
-            Ok(ServerMsg::Ready)
-        }
-        0x81 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(ServerMsg::Text(String::from_utf8(payload)?))
-        }
-        0x82 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(ServerMsg::Error(String::from_utf8(payload)?))
-        }
-        0x83 => {
-            if !len.is_multiple_of(2) {
-                bail!("TtsAudioChunk payload length {len} is not a multiple of 2");
-            }
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            let samples: Vec<i16> = payload
+    let Some(spec) = find(tag[0]) else {
+        bail!("Unknown {kind} message tag: 0x{:02x}", tag[0]);
+    };
+    if spec.payload == Payload::Samples && !len.is_multiple_of(2) {
+        bail!("{} payload length {len} is not a multiple of 2", spec.name);
+    }
+    let mut payload = vec![0u8; len];
+    r.read_exact(&mut payload)?;
+    let value = match spec.payload {
+        Payload::Empty => Value::Empty,
+        Payload::Utf8 => Value::Text(String::from_utf8(payload)?),
+        Payload::Flag { default } => Value::Flag(payload.first().map_or(default, |&b| b != 0x00)),
+        Payload::Samples => Value::Samples(
+            payload
                 .chunks_exact(2)
                 .map(|c| i16::from_le_bytes([c[0], c[1]]))
-                .collect();
-            Ok(ServerMsg::TtsAudioChunk(samples))
-        }
-        0x84 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
-            }
-            Ok(ServerMsg::TtsEnd)
-        }
-        0x85 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(ServerMsg::Feedback(String::from_utf8(payload)?))
-        }
-        0x86 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(ServerMsg::SessionSummary(String::from_utf8(payload)?))
-        }
-        0x87 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(ServerMsg::StatusNotification(String::from_utf8(payload)?))
-        }
-        other => bail!("Unknown server message tag: 0x{other:02x}"),
+                .collect(),
+        ),
+        Payload::Struct(_) => Value::Bytes(payload),
+    };
+    if trace::is_enabled() {
+        trace::frame(
+            trace::Direction::Received,
+            spec.name,
+            spec.tag,
+            len,
+            value.preview(),
+        );
     }
+    Ok((spec.name, value))
 }
 
-pub fn write_orchestrator_msg(w: &mut impl Write, msg: &OrchestratorMsg) -> Result<()> {
-    match msg {
-        OrchestratorMsg::TranscribedText(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0xA0])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
-        }
-        OrchestratorMsg::ResponseText(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0xA1])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
-        }
-        OrchestratorMsg::SessionStart(json) => {
-            let payload = json.as_bytes();
-            w.write_all(&[0xA2])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
-        }
-        OrchestratorMsg::SessionEnd => {
-            w.write_all(&[0xA3])?;
-            w.write_all(&0u32.to_le_bytes())?;
-            w.flush()?;
-        }
-        OrchestratorMsg::FeedbackText(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0xA4])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
-        }
-        OrchestratorMsg::FeedbackChoice(proceed) => {
-            w.write_all(&[0xA5])?;
-            w.write_all(&1u32.to_le_bytes())?;
-            w.write_all(&[if *proceed { 0x01 } else { 0x00 }])?;
-            w.flush()?;
-        }
-        OrchestratorMsg::SummaryRequest => {
-            w.write_all(&[0xA6])?;
-            w.write_all(&0u32.to_le_bytes())?;
-            w.flush()?;
-        }
-        OrchestratorMsg::SummaryResponse(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0xA7])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
-        }
-        OrchestratorMsg::StatusNotification(text) => {
-            let payload = text.as_bytes();
-            w.write_all(&[0xA8])?;
-            w.write_all(&(payload.len() as u32).to_le_bytes())?;
-            w.write_all(payload)?;
-            w.flush()?;
-        }
-    }
-    Ok(())
+pub fn write_client_msg(w: &mut impl Write, msg: &ClientMsg) -> Result<()> {
+    let (name, body) = match msg {
+        ClientMsg::AudioSegment(samples) => ("AudioSegment", Body::Samples(samples)),
+        ClientMsg::PauseRequest => ("PauseRequest", Body::Empty),
+        ClientMsg::ResumeRequest => ("ResumeRequest", Body::Empty),
+        ClientMsg::InterruptTts => ("InterruptTts", Body::Empty),
+        ClientMsg::FeedbackChoice(proceed) => ("FeedbackChoice", Body::Flag(*proceed)),
+        ClientMsg::SummaryRequest => ("SummaryRequest", Body::Empty),
+        ClientMsg::TextInput(text) => ("TextInput", Body::Text(text)),
+        ClientMsg::SessionTakeover(take_over) => ("SessionTakeover", Body::Flag(*take_over)),
+        ClientMsg::SpeakWord(word) => ("SpeakWord", Body::Text(word)),
+        ClientMsg::EnableTimings => ("EnableTimings", Body::Empty),
+        ClientMsg::AudioInput(info) => ("AudioInput", Body::Bytes(info.encode())),
+        ClientMsg::TranslateLast => ("TranslateLast", Body::Empty),
+        ClientMsg::SimplifyLast => ("SimplifyLast", Body::Empty),
+        ClientMsg::DisregardLast => ("DisregardLast", Body::Empty),
+        ClientMsg::BranchTo(turn) => ("BranchTo", Body::Bytes(turn.to_le_bytes().to_vec())),
+        ClientMsg::ListVoices => ("ListVoices", Body::Empty),
+        ClientMsg::SetVoice(name) => ("SetVoice", Body::Text(name)),
+        ClientMsg::CancelExchange => ("CancelExchange", Body::Empty),
+        ClientMsg::SetLanguage(code) => ("SetLanguage", Body::Text(code)),
+        ClientMsg::NumberedSegment(seq, samples) => (
+            "NumberedSegment",
+            Body::Bytes(encode_numbered_segment(*seq, samples)),
+        ),
+    };
+    write_frame(w, CLIENT_MESSAGES, name, body)
 }
 
-pub fn read_orchestrator_msg(r: &mut impl Read) -> Result<OrchestratorMsg> {
-    let mut tag = [0u8; 1];
-    r.read_exact(&mut tag)?;
+pub fn read_client_msg(r: &mut impl Read) -> Result<ClientMsg> {
+    Ok(
+        match read_frame(r, "client", |tag| by_tag(CLIENT_MESSAGES, tag))? {
+            ("AudioSegment", Value::Samples(samples)) => ClientMsg::AudioSegment(samples),
+            ("PauseRequest", Value::Empty) => ClientMsg::PauseRequest,
+            ("ResumeRequest", Value::Empty) => ClientMsg::ResumeRequest,
+            ("InterruptTts", Value::Empty) => ClientMsg::InterruptTts,
+            ("FeedbackChoice", Value::Flag(proceed)) => ClientMsg::FeedbackChoice(proceed),
+            ("SummaryRequest", Value::Empty) => ClientMsg::SummaryRequest,
+            ("TextInput", Value::Text(text)) => ClientMsg::TextInput(text),
+            ("SessionTakeover", Value::Flag(take_over)) => ClientMsg::SessionTakeover(take_over),
+            ("SpeakWord", Value::Text(word)) => ClientMsg::SpeakWord(word),
+            ("EnableTimings", Value::Empty) => ClientMsg::EnableTimings,
+            ("AudioInput", Value::Bytes(payload)) => {
+                ClientMsg::AudioInput(AudioInputInfo::decode(&payload)?)
+            }
+            ("TranslateLast", Value::Empty) => ClientMsg::TranslateLast,
+            ("SimplifyLast", Value::Empty) => ClientMsg::SimplifyLast,
+            ("DisregardLast", Value::Empty) => ClientMsg::DisregardLast,
+            ("BranchTo", Value::Bytes(payload)) => ClientMsg::BranchTo(decode_turn(&payload)?),
+            ("ListVoices", Value::Empty) => ClientMsg::ListVoices,
+            ("SetVoice", Value::Text(name)) => ClientMsg::SetVoice(name),
+            ("CancelExchange", Value::Empty) => ClientMsg::CancelExchange,
+            ("SetLanguage", Value::Text(code)) => ClientMsg::SetLanguage(code),
+            ("NumberedSegment", Value::Bytes(payload)) => {
+                let (seq, samples) = decode_numbered_segment(&payload)?;
+                ClientMsg::NumberedSegment(seq, samples)
+            }
+            (name, _) => bail!("No client message matches the {name} table row"),
+        },
+    )
+}
 
-    let mut len_buf = [0u8; 4];
-    r.read_exact(&mut len_buf)?;
-    let len = u32::from_le_bytes(len_buf) as usize;
+pub fn write_server_msg(w: &mut impl Write, msg: &ServerMsg) -> Result<()> {
+    let (name, body) = match msg {
+        ServerMsg::Ready => ("Ready", Body::Bytes(Vec::new())),
+        ServerMsg::ReadyActiveSession(since) => {
+            ("Ready", Body::Bytes(since.to_le_bytes().to_vec()))
+        }
+        ServerMsg::Text(text) => ("Text", Body::Text(text)),
+        ServerMsg::Error(text) => ("Error", Body::Text(text)),
+        ServerMsg::TtsAudioChunk(samples) => ("TtsAudioChunk", Body::Samples(samples)),
+        ServerMsg::TtsEnd => ("TtsEnd", Body::Empty),
+        ServerMsg::Feedback(text) => ("Feedback", Body::Text(text)),
+        ServerMsg::SessionSummary(text) => ("SessionSummary", Body::Text(text)),
+        ServerMsg::StatusNotification(text) => ("StatusNotification", Body::Text(text)),
+        ServerMsg::SessionEnded(reason) => ("SessionEnded", Body::Text(reason)),
+        ServerMsg::TurnStats(stats) => ("TurnStats", Body::Bytes(stats.encode())),
+        ServerMsg::Translation(text) => ("Translation", Body::Text(text)),
+        ServerMsg::Warning(text) => ("Warning", Body::Text(text)),
+        ServerMsg::VoiceList(voices) => ("VoiceList", Body::Bytes(encode_voices(voices))),
+        ServerMsg::PartialTranscript(text) => ("PartialTranscript", Body::Text(text)),
+        ServerMsg::SegmentReceived(seq) => {
+            ("SegmentReceived", Body::Bytes(seq.to_le_bytes().to_vec()))
+        }
+    };
+    write_frame(w, SERVER_MESSAGES, name, body)
+}
 
-    match tag[0] {
-        0xA0 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(OrchestratorMsg::TranscribedText(String::from_utf8(
-                payload,
-            )?))
-        }
-        0xA1 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(OrchestratorMsg::ResponseText(String::from_utf8(payload)?))
-        }
-        0xA2 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(OrchestratorMsg::SessionStart(String::from_utf8(payload)?))
-        }
-        0xA3 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
+pub fn read_server_msg(r: &mut impl Read) -> Result<ServerMsg> {
+    Ok(
+        match read_frame(r, "server", |tag| by_tag(SERVER_MESSAGES, tag))? {
+            // An 8-byte payload announces a session that is still running;
+            // older servers send an empty payload.
+            ("Ready", Value::Bytes(payload)) => match payload.first_chunk::<8>() {
+                Some(since) => ServerMsg::ReadyActiveSession(u64::from_le_bytes(*since)),
+                None => ServerMsg::Ready,
+            },
+            ("Text", Value::Text(text)) => ServerMsg::Text(text),
+            ("Error", Value::Text(text)) => ServerMsg::Error(text),
+            ("TtsAudioChunk", Value::Samples(samples)) => ServerMsg::TtsAudioChunk(samples),
+            ("TtsEnd", Value::Empty) => ServerMsg::TtsEnd,
+            ("Feedback", Value::Text(text)) => ServerMsg::Feedback(text),
+            ("SessionSummary", Value::Text(text)) => ServerMsg::SessionSummary(text),
+            ("StatusNotification", Value::Text(text)) => ServerMsg::StatusNotification(text),
+            ("SessionEnded", Value::Text(reason)) => ServerMsg::SessionEnded(reason),
+            ("TurnStats", Value::Bytes(payload)) => {
+                ServerMsg::TurnStats(TurnStats::decode(&payload)?)
             }
-            Ok(OrchestratorMsg::SessionEnd)
-        }
-        0xA4 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(OrchestratorMsg::FeedbackText(String::from_utf8(payload)?))
-        }
-        0xA5 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            let proceed = payload.first().copied().unwrap_or(0x01) != 0x00;
-            Ok(OrchestratorMsg::FeedbackChoice(proceed))
-        }
-        0xA6 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
+            ("Translation", Value::Text(text)) => ServerMsg::Translation(text),
+            ("Warning", Value::Text(text)) => ServerMsg::Warning(text),
+            ("VoiceList", Value::Bytes(payload)) => ServerMsg::VoiceList(decode_voices(payload)?),
+            ("PartialTranscript", Value::Text(text)) => ServerMsg::PartialTranscript(text),
+            ("SegmentReceived", Value::Bytes(payload)) => {
+                ServerMsg::SegmentReceived(decode_segment_seq(&payload)?)
             }
-            Ok(OrchestratorMsg::SummaryRequest)
-        }
-        0xA7 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(OrchestratorMsg::SummaryResponse(String::from_utf8(
-                payload,
-            )?))
-        }
-        0xA8 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(OrchestratorMsg::StatusNotification(String::from_utf8(
-                payload,
-            )?))
-        }
-        other => bail!("Unknown orchestrator message tag: 0x{other:02x}"),
-    }
+            (name, _) => bail!("No server message matches the {name} table row"),
+        },
+    )
+}
+
+pub fn write_orchestrator_msg(w: &mut impl Write, msg: &OrchestratorMsg) -> Result<()> {
+    let (name, body) = match msg {
+        OrchestratorMsg::TranscribedText(text) => ("TranscribedText", Body::Text(text)),
+        OrchestratorMsg::ResponseText(text) => ("ResponseText", Body::Text(text)),
+        OrchestratorMsg::SessionStart(json) => ("SessionStart", Body::Text(json)),
+        OrchestratorMsg::SessionEnd => ("SessionEnd", Body::Empty),
+        OrchestratorMsg::FeedbackText(text) => ("FeedbackText", Body::Text(text)),
+        OrchestratorMsg::FeedbackChoice(proceed) => ("FeedbackChoice", Body::Flag(*proceed)),
+        OrchestratorMsg::SummaryRequest => ("SummaryRequest", Body::Empty),
+        OrchestratorMsg::SummaryResponse(text) => ("SummaryResponse", Body::Text(text)),
+        OrchestratorMsg::StatusNotification(text) => ("StatusNotification", Body::Text(text)),
+        OrchestratorMsg::TranslateRequest => ("TranslateRequest", Body::Empty),
+        OrchestratorMsg::Translation(text) => ("Translation", Body::Text(text)),
+        OrchestratorMsg::SimplifyRequest => ("SimplifyRequest", Body::Empty),
+        OrchestratorMsg::DisregardLast => ("DisregardLast", Body::Empty),
+        OrchestratorMsg::BranchTo(turn) => ("BranchTo", Body::Bytes(turn.to_le_bytes().to_vec())),
+        OrchestratorMsg::CancelExchange => ("CancelExchange", Body::Empty),
+    };
+    write_frame(w, ORCHESTRATOR_MESSAGES, name, body)
+}
+
+pub fn read_orchestrator_msg(r: &mut impl Read) -> Result<OrchestratorMsg> {
+    let find = |tag| by_tag(ORCHESTRATOR_MESSAGES, tag);
+    Ok(match read_frame(r, "orchestrator", find)? {
+        ("TranscribedText", Value::Text(text)) => OrchestratorMsg::TranscribedText(text),
+        ("ResponseText", Value::Text(text)) => OrchestratorMsg::ResponseText(text),
+        ("SessionStart", Value::Text(json)) => OrchestratorMsg::SessionStart(json),
+        ("SessionEnd", Value::Empty) => OrchestratorMsg::SessionEnd,
+        ("FeedbackText", Value::Text(text)) => OrchestratorMsg::FeedbackText(text),
+        ("FeedbackChoice", Value::Flag(proceed)) => OrchestratorMsg::FeedbackChoice(proceed),
+        ("SummaryRequest", Value::Empty) => OrchestratorMsg::SummaryRequest,
+        ("SummaryResponse", Value::Text(text)) => OrchestratorMsg::SummaryResponse(text),
+        ("StatusNotification", Value::Text(text)) => OrchestratorMsg::StatusNotification(text),
+        ("TranslateRequest", Value::Empty) => OrchestratorMsg::TranslateRequest,
+        ("Translation", Value::Text(text)) => OrchestratorMsg::Translation(text),
+        ("SimplifyRequest", Value::Empty) => OrchestratorMsg::SimplifyRequest,
+        ("DisregardLast", Value::Empty) => OrchestratorMsg::DisregardLast,
+        ("BranchTo", Value::Bytes(payload)) => OrchestratorMsg::BranchTo(decode_turn(&payload)?),
+        ("CancelExchange", Value::Empty) => OrchestratorMsg::CancelExchange,
+        (name, _) => bail!("No orchestrator message matches the {name} table row"),
+    })
 }
 
 /// Read a server-to-orchestrator message from the Unix socket.
 ///
-/// Handles tags from both ServerMsg (0x80 Ready, 0x82 Error) and
-/// OrchestratorMsg (0xA0 TranscribedText) since the server writes
-/// both types on the same Unix socket stream.
+/// Accepts every row marked server → orchestrator, from the server table
+/// (0x80 Ready, 0x82 Error) and the orchestrator table (0xA0 TranscribedText...),
+/// since the server writes both types on the same Unix socket stream.
 pub fn read_server_orc_msg(r: &mut impl Read) -> Result<ServerOrcMsg> {
-    let mut tag = [0u8; 1];
-    r.read_exact(&mut tag)?;
-
-    let mut len_buf = [0u8; 4];
-    r.read_exact(&mut len_buf)?;
-    let len = u32::from_le_bytes(len_buf) as usize;
-
-    match tag[0] {
-        0x80 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
-            }
-            Ok(ServerOrcMsg::Ready)
-        }
-        0x82 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(ServerOrcMsg::Error(String::from_utf8(payload)?))
-        }
-        0xA0 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            Ok(ServerOrcMsg::TranscribedText(String::from_utf8(payload)?))
-        }
-        0xA5 => {
-            let mut payload = vec![0u8; len];
-            r.read_exact(&mut payload)?;
-            let proceed = payload.first().copied().unwrap_or(0x01) != 0x00;
-            Ok(ServerOrcMsg::FeedbackChoice(proceed))
-        }
-        0xA6 => {
-            if len > 0 {
-                let mut discard = vec![0u8; len];
-                r.read_exact(&mut discard)?;
-            }
-            Ok(ServerOrcMsg::SummaryRequest)
-        }
-        other => bail!("Unknown server-to-orchestrator message tag: 0x{other:02x}"),
-    }
+    let find = |tag| {
+        [SERVER_MESSAGES, ORCHESTRATOR_MESSAGES]
+            .into_iter()
+            .flatten()
+            .find(|m| m.tag == tag && m.directions.contains(&Direction::ServerToOrchestrator))
+    };
+    Ok(match read_frame(r, "server-to-orchestrator", find)? {
+        ("Ready", _) => ServerOrcMsg::Ready,
+        ("Error", Value::Text(text)) => ServerOrcMsg::Error(text),
+        ("TranscribedText", Value::Text(text)) => ServerOrcMsg::TranscribedText(text),
+        ("SessionEnd", Value::Empty) => ServerOrcMsg::SessionEnd,
+        ("FeedbackChoice", Value::Flag(proceed)) => ServerOrcMsg::FeedbackChoice(proceed),
+        ("SummaryRequest", Value::Empty) => ServerOrcMsg::SummaryRequest,
+        ("TranslateRequest", Value::Empty) => ServerOrcMsg::TranslateRequest,
+        ("SimplifyRequest", Value::Empty) => ServerOrcMsg::SimplifyRequest,
+        ("DisregardLast", Value::Empty) => ServerOrcMsg::DisregardLast,
+        ("BranchTo", Value::Bytes(payload)) => ServerOrcMsg::BranchTo(decode_turn(&payload)?),
+        ("CancelExchange", Value::Empty) => ServerOrcMsg::CancelExchange,
+        (name, _) => bail!("No server-to-orchestrator message matches the {name} table row"),
+    })
 }
 
 #[cfg(test)]
@@ -1061,6 +1697,71 @@ mod tests {
         assert!(matches!(msg, ServerOrcMsg::SummaryRequest));
     }
 
+    #[test]
+    fn translate_last_roundtrip() {
+        let mut buf = Vec::new();
+        write_client_msg(&mut buf, &ClientMsg::TranslateLast).unwrap();
+        assert_eq!(buf, [0x0C, 0, 0, 0, 0]);
+        let mut cursor = Cursor::new(buf);
+        assert!(matches!(
+            read_client_msg(&mut cursor).unwrap(),
+            ClientMsg::TranslateLast
+        ));
+    }
+
+    #[test]
+    fn read_server_orc_msg_translate_request() {
+        let mut buf = Vec::new();
+        write_orchestrator_msg(&mut buf, &OrchestratorMsg::TranslateRequest).unwrap();
+        let mut cursor = Cursor::new(buf);
+        let msg = read_server_orc_msg(&mut cursor).unwrap();
+        assert!(matches!(msg, ServerOrcMsg::TranslateRequest));
+    }
+
+    #[test]
+    fn translation_roundtrip_both_links() {
+        let mut buf = Vec::new();
+        write_orchestrator_msg(&mut buf, &OrchestratorMsg::Translation("Bonjour".into())).unwrap();
+        let mut cursor = Cursor::new(buf);
+        match read_orchestrator_msg(&mut cursor).unwrap() {
+            OrchestratorMsg::Translation(t) => assert_eq!(t, "Bonjour"),
+            other => panic!("Expected Translation, got {other:?}"),
+        }
+
+        let mut buf = Vec::new();
+        write_server_msg(&mut buf, &ServerMsg::Translation("Bonjour".into())).unwrap();
+        assert_eq!(buf[0], 0x8A);
+        let mut cursor = Cursor::new(buf);
+        match read_server_msg(&mut cursor).unwrap() {
+            ServerMsg::Translation(t) => assert_eq!(t, "Bonjour"),
+            other => panic!("Expected Translation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn voice_messages_roundtrip() {
+        let mut buf = Vec::new();
+        write_client_msg(&mut buf, &ClientMsg::ListVoices).unwrap();
+        write_client_msg(&mut buf, &ClientMsg::SetVoice("ff_siwis".into())).unwrap();
+        let mut cursor = Cursor::new(buf);
+        assert!(matches!(
+            read_client_msg(&mut cursor).unwrap(),
+            ClientMsg::ListVoices
+        ));
+        match read_client_msg(&mut cursor).unwrap() {
+            ClientMsg::SetVoice(name) => assert_eq!(name, "ff_siwis"),
+            other => panic!("Expected SetVoice, got {other:?}"),
+        }
+
+        let voices = vec!["af_alloy".to_string(), "ff_siwis".to_string()];
+        let mut buf = Vec::new();
+        write_server_msg(&mut buf, &ServerMsg::VoiceList(voices.clone())).unwrap();
+        match read_server_msg(&mut Cursor::new(buf)).unwrap() {
+            ServerMsg::VoiceList(list) => assert_eq!(list, voices),
+            other => panic!("Expected VoiceList, got {other:?}"),
+        }
+    }
+
     #[test]
     fn is_disconnect_detects_unexpected_eof() {
         let err = anyhow::Error::new(std::io::Error::new(
@@ -1106,6 +1807,106 @@ mod tests {
         }
     }
 
+    #[test]
+    fn round_trip_session_ended() {
+        let reason = "The server is shutting down".to_string();
+        let mut buf = Vec::new();
+        write_server_msg(&mut buf, &ServerMsg::SessionEnded(reason.clone())).unwrap();
+        assert_eq!(buf[0], 0x88);
+        let mut cursor = Cursor::new(buf);
+        match read_server_msg(&mut cursor).unwrap() {
+            ServerMsg::SessionEnded(decoded) => assert_eq!(decoded, reason),
+            other => panic!("Expected SessionEnded, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn round_trip_turn_stats() {
+        let stats = TurnStats {
+            stt_ms: 1200,
+            llm_ms: 4800,
+            tts_ms: 900,
+            first_audio_ms: 6900,
+        };
+        let mut buf = Vec::new();
+        write_server_msg(&mut buf, &ServerMsg::TurnStats(stats)).unwrap();
+        assert_eq!(buf[0], 0x89);
+        let mut cursor = Cursor::new(buf);
+        match read_server_msg(&mut cursor).unwrap() {
+            ServerMsg::TurnStats(decoded) => assert_eq!(decoded, stats),
+            other => panic!("Expected TurnStats, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn turn_stats_rejects_wrong_length() {
+        let mut buf = vec![0x89];
+        buf.extend_from_slice(&4u32.to_le_bytes());
+        buf.extend_from_slice(&[0; 4]);
+        let mut cursor = Cursor::new(buf);
+        assert!(read_server_msg(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn round_trip_enable_timings() {
+        let mut buf = Vec::new();
+        write_client_msg(&mut buf, &ClientMsg::EnableTimings).unwrap();
+        assert_eq!(buf, [0x0A, 0, 0, 0, 0]);
+        let mut cursor = Cursor::new(buf);
+        assert!(matches!(
+            read_client_msg(&mut cursor).unwrap(),
+            ClientMsg::EnableTimings
+        ));
+    }
+
+    #[test]
+    fn round_trip_audio_input() {
+        let info = AudioInputInfo {
+            device: "Blue Yeti Stéréo".into(),
+            sample_rate: 48000,
+            channels: 2,
+            resampler: "sinc 48000 \u{2192} 16000 Hz, 2 ch \u{2192} mono".into(),
+        };
+        let mut buf = Vec::new();
+        write_client_msg(&mut buf, &ClientMsg::AudioInput(info.clone())).unwrap();
+        assert_eq!(buf[0], 0x0B);
+        assert_eq!(&buf[5..9], &48000u32.to_le_bytes());
+        let mut cursor = Cursor::new(buf);
+        match read_client_msg(&mut cursor).unwrap() {
+            ClientMsg::AudioInput(decoded) => assert_eq!(decoded, info),
+            other => panic!("Expected AudioInput, got {other:?}"),
+        }
+
+        // Empty strings are allowed
+        let mut buf = Vec::new();
+        write_client_msg(&mut buf, &ClientMsg::AudioInput(AudioInputInfo::default())).unwrap();
+        let mut cursor = Cursor::new(buf);
+        match read_client_msg(&mut cursor).unwrap() {
+            ClientMsg::AudioInput(decoded) => assert_eq!(decoded, AudioInputInfo::default()),
+            other => panic!("Expected AudioInput, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn audio_input_rejects_truncated_payload() {
+        // Too short for the fixed fields
+        let mut buf = vec![0x0B];
+        buf.extend_from_slice(&4u32.to_le_bytes());
+        buf.extend_from_slice(&[0; 4]);
+        assert!(read_client_msg(&mut Cursor::new(buf)).is_err());
+
+        // Device length pointing past the end
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&16000u32.to_le_bytes());
+        payload.extend_from_slice(&1u16.to_le_bytes());
+        payload.extend_from_slice(&10u16.to_le_bytes());
+        payload.extend_from_slice(b"mic");
+        let mut buf = vec![0x0B];
+        buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&payload);
+        assert!(read_client_msg(&mut Cursor::new(buf)).is_err());
+    }
+
     #[test]
     fn round_trip_orchestrator_status_notification() {
         let text = "Searching the web...".to_string();
@@ -1119,4 +1920,388 @@ mod tests {
             other => panic!("Expected StatusNotification, got {other:?}"),
         }
     }
+
+    // --- TextInput tests ---
+
+    #[test]
+    fn round_trip_text_input() {
+        let text = "Je n'ai pas de micro aujourd'hui".to_string();
+        let mut buf = Vec::new();
+        write_client_msg(&mut buf, &ClientMsg::TextInput(text.clone())).unwrap();
+        assert_eq!(buf[0], 0x07);
+        let mut cursor = Cursor::new(buf);
+        let msg = read_client_msg(&mut cursor).unwrap();
+        match msg {
+            ClientMsg::TextInput(decoded) => assert_eq!(decoded, text),
+            other => panic!("Expected TextInput, got {other:?}"),
+        }
+    }
+
+    // --- Tracing tests ---
+
+    #[test]
+    fn traced_exchange_logs_each_frame_both_ways() {
+        use std::sync::{Arc, Mutex};
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let captured = Arc::clone(&lines);
+        trace::set_sink(Some(Arc::new(move |line: &str| {
+            captured.lock().unwrap().push(line.to_string());
+        })));
+
+        let mut buf = Vec::new();
+        let text = ClientMsg::TextInput("trace me, s'il te plaît".into());
+        write_client_msg(&mut buf, &text).unwrap();
+        write_client_msg(&mut buf, &ClientMsg::AudioSegment(vec![0; 4321])).unwrap();
+        let mut cursor = Cursor::new(buf);
+        read_client_msg(&mut cursor).unwrap();
+        read_client_msg(&mut cursor).unwrap();
+        trace::set_sink(None);
+
+        // The sink is global: keep only this exchange's lines
+        let lines: Vec<String> = lines
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|l| l.contains("trace me") || l.contains("AudioSegment 0x01 8642 B"))
+            .cloned()
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "[trace] \u{2192} TextInput 0x07 24 B \"trace me, s'il te plaît\"",
+                "[trace] \u{2192} AudioSegment 0x01 8642 B 270ms audio",
+                "[trace] \u{2190} TextInput 0x07 24 B \"trace me, s'il te plaît\"",
+                "[trace] \u{2190} AudioSegment 0x01 8642 B 270ms audio",
+            ]
+        );
+        assert!(!trace::is_enabled());
+    }
+
+    #[test]
+    fn text_input_rejects_invalid_utf8() {
+        let mut buf = vec![0x07];
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        buf.extend_from_slice(&[0xff, 0xfe]);
+        let mut cursor = Cursor::new(buf);
+        assert!(read_client_msg(&mut cursor).is_err());
+    }
+
+    // --- Session takeover tests ---
+
+    #[test]
+    fn round_trip_ready_active_session() {
+        let mut buf = Vec::new();
+        write_server_msg(&mut buf, &ServerMsg::ReadyActiveSession(1_771_000_000)).unwrap();
+        assert_eq!(buf[0], 0x80);
+        let mut cursor = Cursor::new(buf);
+        match read_server_msg(&mut cursor).unwrap() {
+            ServerMsg::ReadyActiveSession(since) => assert_eq!(since, 1_771_000_000),
+            other => panic!("Expected ReadyActiveSession, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn ready_active_session_reads_as_ready_for_orchestrator() {
+        let mut buf = Vec::new();
+        write_server_msg(&mut buf, &ServerMsg::ReadyActiveSession(42)).unwrap();
+        let mut cursor = Cursor::new(buf);
+        assert!(matches!(
+            read_server_orc_msg(&mut cursor).unwrap(),
+            ServerOrcMsg::Ready
+        ));
+    }
+
+    #[test]
+    fn round_trip_session_takeover() {
+        for take_over in [true, false] {
+            let mut buf = Vec::new();
+            write_client_msg(&mut buf, &ClientMsg::SessionTakeover(take_over)).unwrap();
+            let mut cursor = Cursor::new(buf);
+            match read_client_msg(&mut cursor).unwrap() {
+                ClientMsg::SessionTakeover(decoded) => assert_eq!(decoded, take_over),
+                other => panic!("Expected SessionTakeover, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_speak_word() {
+        let mut buf = Vec::new();
+        write_client_msg(&mut buf, &ClientMsg::SpeakWord("oiseaux".into())).unwrap();
+        assert_eq!(buf[0], 0x09);
+        let mut cursor = Cursor::new(buf);
+        match read_client_msg(&mut cursor).unwrap() {
+            ClientMsg::SpeakWord(word) => assert_eq!(word, "oiseaux"),
+            other => panic!("Expected SpeakWord, got {other:?}"),
+        }
+    }
+
+    // --- Golden wire bytes: one frame per variant, pinned byte for byte ---
+
+    const HE: [u8; 3] = [0x68, 0xC3, 0xA9]; // "hé"
+
+    fn frame(tag: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(payload);
+        out
+    }
+
+    fn audio_input() -> AudioInputInfo {
+        AudioInputInfo {
+            device: "Mic".into(),
+            sample_rate: 48000,
+            channels: 2,
+            resampler: "sinc".into(),
+        }
+    }
+
+    #[test]
+    fn golden_client_frames() {
+        let cases: Vec<(ClientMsg, Vec<u8>)> = vec![
+            (
+                ClientMsg::AudioSegment(vec![1, -2]),
+                frame(0x01, &[0x01, 0x00, 0xFE, 0xFF]),
+            ),
+            (ClientMsg::PauseRequest, frame(0x02, &[])),
+            (ClientMsg::ResumeRequest, frame(0x03, &[])),
+            (ClientMsg::InterruptTts, frame(0x04, &[])),
+            (ClientMsg::FeedbackChoice(true), frame(0x05, &[0x01])),
+            (ClientMsg::FeedbackChoice(false), frame(0x05, &[0x00])),
+            (ClientMsg::SummaryRequest, frame(0x06, &[])),
+            (ClientMsg::TextInput("hé".into()), frame(0x07, &HE)),
+            (ClientMsg::SessionTakeover(true), frame(0x08, &[0x01])),
+            (ClientMsg::SessionTakeover(false), frame(0x08, &[0x00])),
+            (ClientMsg::SpeakWord("hé".into()), frame(0x09, &HE)),
+            (ClientMsg::EnableTimings, frame(0x0A, &[])),
+            (
+                ClientMsg::AudioInput(audio_input()),
+                frame(
+                    0x0B,
+                    &[
+                        0x80, 0xBB, 0x00, 0x00, 0x02, 0x00, 0x03, 0x00, b'M', b'i', b'c', b's',
+                        b'i', b'n', b'c',
+                    ],
+                ),
+            ),
+            (ClientMsg::TranslateLast, frame(0x0C, &[])),
+            (ClientMsg::SimplifyLast, frame(0x0D, &[])),
+            (ClientMsg::DisregardLast, frame(0x0E, &[])),
+            (
+                ClientMsg::BranchTo(0x0102),
+                frame(0x0F, &[0x02, 0x01, 0x00, 0x00]),
+            ),
+            (ClientMsg::ListVoices, frame(0x10, &[])),
+            (ClientMsg::SetVoice("hé".into()), frame(0x11, &HE)),
+            (ClientMsg::CancelExchange, frame(0x12, &[])),
+            (ClientMsg::SetLanguage("hé".into()), frame(0x13, &HE)),
+            (
+                ClientMsg::NumberedSegment(0x0102, vec![1, -2]),
+                frame(0x14, &[0x02, 0x01, 0, 0, 0x01, 0x00, 0xFE, 0xFF]),
+            ),
+        ];
+        for (msg, expected) in cases {
+            let mut buf = Vec::new();
+            write_client_msg(&mut buf, &msg).unwrap();
+            assert_eq!(buf, expected, "{msg:?}");
+            let decoded = read_client_msg(&mut Cursor::new(buf)).unwrap();
+            assert_eq!(format!("{decoded:?}"), format!("{msg:?}"));
+        }
+    }
+
+    #[test]
+    fn golden_server_frames() {
+        let cases: Vec<(ServerMsg, Vec<u8>)> = vec![
+            (ServerMsg::Ready, frame(0x80, &[])),
+            (
+                ServerMsg::ReadyActiveSession(0x0102_0304_0506_0708),
+                frame(0x80, &[0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]),
+            ),
+            (ServerMsg::Text("hé".into()), frame(0x81, &HE)),
+            (ServerMsg::Error("hé".into()), frame(0x82, &HE)),
+            (
+                ServerMsg::TtsAudioChunk(vec![1, -2]),
+                frame(0x83, &[0x01, 0x00, 0xFE, 0xFF]),
+            ),
+            (ServerMsg::TtsEnd, frame(0x84, &[])),
+            (ServerMsg::Feedback("hé".into()), frame(0x85, &HE)),
+            (ServerMsg::SessionSummary("hé".into()), frame(0x86, &HE)),
+            (ServerMsg::StatusNotification("hé".into()), frame(0x87, &HE)),
+            (ServerMsg::SessionEnded("hé".into()), frame(0x88, &HE)),
+            (
+                ServerMsg::TurnStats(TurnStats {
+                    stt_ms: 1,
+                    llm_ms: 2,
+                    tts_ms: 3,
+                    first_audio_ms: 0x0102,
+                }),
+                frame(
+                    0x89,
+                    &[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 0x02, 0x01, 0, 0],
+                ),
+            ),
+            (ServerMsg::Translation("hé".into()), frame(0x8A, &HE)),
+            (ServerMsg::Warning("hé".into()), frame(0x8B, &HE)),
+            (
+                ServerMsg::VoiceList(vec!["af".into(), "hé".into()]),
+                frame(0x8C, &[b'a', b'f', b'\n', 0x68, 0xC3, 0xA9]),
+            ),
+            (ServerMsg::VoiceList(Vec::new()), frame(0x8C, &[])),
+            (ServerMsg::PartialTranscript("hé".into()), frame(0x8D, &HE)),
+            (
+                ServerMsg::SegmentReceived(0x0102),
+                frame(0x8E, &[0x02, 0x01, 0, 0]),
+            ),
+        ];
+        for (msg, expected) in cases {
+            let mut buf = Vec::new();
+            write_server_msg(&mut buf, &msg).unwrap();
+            assert_eq!(buf, expected, "{msg:?}");
+            let decoded = read_server_msg(&mut Cursor::new(buf)).unwrap();
+            assert_eq!(format!("{decoded:?}"), format!("{msg:?}"));
+        }
+    }
+
+    #[test]
+    fn golden_orchestrator_frames() {
+        let cases: Vec<(OrchestratorMsg, Vec<u8>)> = vec![
+            (
+                OrchestratorMsg::TranscribedText("hé".into()),
+                frame(0xA0, &HE),
+            ),
+            (OrchestratorMsg::ResponseText("hé".into()), frame(0xA1, &HE)),
+            (OrchestratorMsg::SessionStart("hé".into()), frame(0xA2, &HE)),
+            (OrchestratorMsg::SessionEnd, frame(0xA3, &[])),
+            (OrchestratorMsg::FeedbackText("hé".into()), frame(0xA4, &HE)),
+            (OrchestratorMsg::FeedbackChoice(true), frame(0xA5, &[0x01])),
+            (OrchestratorMsg::FeedbackChoice(false), frame(0xA5, &[0x00])),
+            (OrchestratorMsg::SummaryRequest, frame(0xA6, &[])),
+            (
+                OrchestratorMsg::SummaryResponse("hé".into()),
+                frame(0xA7, &HE),
+            ),
+            (
+                OrchestratorMsg::StatusNotification("hé".into()),
+                frame(0xA8, &HE),
+            ),
+            (OrchestratorMsg::TranslateRequest, frame(0xA9, &[])),
+            (OrchestratorMsg::Translation("hé".into()), frame(0xAA, &HE)),
+            (OrchestratorMsg::SimplifyRequest, frame(0xAB, &[])),
+            (OrchestratorMsg::DisregardLast, frame(0xAC, &[])),
+            (
+                OrchestratorMsg::BranchTo(7),
+                frame(0xAD, &[0x07, 0x00, 0x00, 0x00]),
+            ),
+            (OrchestratorMsg::CancelExchange, frame(0xAE, &[])),
+        ];
+        for (msg, expected) in cases {
+            let mut buf = Vec::new();
+            write_orchestrator_msg(&mut buf, &msg).unwrap();
+            assert_eq!(buf, expected, "{msg:?}");
+            let decoded = read_orchestrator_msg(&mut Cursor::new(buf)).unwrap();
+            assert_eq!(format!("{decoded:?}"), format!("{msg:?}"));
+        }
+    }
+
+    #[test]
+    fn language_tag_round_trips() {
+        let tagged = tag_language("de", "Guten Tag");
+        assert_eq!(tagged, "[lang:de] Guten Tag");
+        assert_eq!(split_language_tag(&tagged), (Some("de"), "Guten Tag"));
+        assert_eq!(split_language_tag("Hello"), (None, "Hello"));
+        // Brackets the user said are not a tag
+        assert_eq!(
+            split_language_tag("[lang: what] is it"),
+            (None, "[lang: what] is it")
+        );
+    }
+
+    #[test]
+    fn golden_server_to_orchestrator_frames() {
+        let cases: Vec<(Vec<u8>, &str)> = vec![
+            (frame(0x80, &[]), "Ready"),
+            (frame(0x80, &[0; 8]), "Ready"),
+            (frame(0x82, &HE), "Error(\"hé\")"),
+            (frame(0xA0, &HE), "TranscribedText(\"hé\")"),
+            (frame(0xA3, &[]), "SessionEnd"),
+            (frame(0xA5, &[0x00]), "FeedbackChoice(false)"),
+            (frame(0xA5, &[]), "FeedbackChoice(true)"),
+            (frame(0xA6, &[]), "SummaryRequest"),
+            (frame(0xA9, &[]), "TranslateRequest"),
+            (frame(0xAB, &[]), "SimplifyRequest"),
+            (frame(0xAC, &[]), "DisregardLast"),
+            (frame(0xAD, &[0x07, 0x00, 0x00, 0x00]), "BranchTo(7)"),
+            (frame(0xAE, &[]), "CancelExchange"),
+        ];
+        for (bytes, expected) in cases {
+            let decoded = read_server_orc_msg(&mut Cursor::new(bytes)).unwrap();
+            assert_eq!(format!("{decoded:?}"), expected);
+        }
+        // Tags the server never sends to the orchestrator
+        for tag in [0x81, 0xA1, 0xA2] {
+            assert!(read_server_orc_msg(&mut Cursor::new(frame(tag, &[]))).is_err());
+        }
+    }
+
+    #[test]
+    fn lenient_payloads_are_kept() {
+        // Empty messages skip stray payload bytes, flags default when missing
+        let mut stream = frame(0x02, &[0xFF, 0xFF]);
+        stream.extend(frame(0x05, &[]));
+        stream.extend(frame(0x08, &[]));
+        let mut cursor = Cursor::new(stream);
+        assert!(matches!(
+            read_client_msg(&mut cursor).unwrap(),
+            ClientMsg::PauseRequest
+        ));
+        assert!(matches!(
+            read_client_msg(&mut cursor).unwrap(),
+            ClientMsg::FeedbackChoice(true)
+        ));
+        assert!(matches!(
+            read_client_msg(&mut cursor).unwrap(),
+            ClientMsg::SessionTakeover(false)
+        ));
+        let err = read_client_msg(&mut Cursor::new(frame(0x01, &[0]))).unwrap_err();
+        assert!(err.to_string().contains("not a multiple of 2"), "{err}");
+        let err = read_client_msg(&mut Cursor::new(frame(0x14, &[1, 0, 0, 0, 0]))).unwrap_err();
+        assert!(err.to_string().contains("not a multiple of 2"), "{err}");
+        assert!(read_client_msg(&mut Cursor::new(frame(0x14, &[1, 0]))).is_err());
+        assert!(read_server_msg(&mut Cursor::new(frame(0x8E, &[1]))).is_err());
+    }
+
+    #[test]
+    fn message_tables_have_unique_tags_in_their_ranges() {
+        let ranges = [(0x01, 0x7F), (0x80, 0x9F), (0xA0, 0xBF)];
+        let mut seen = std::collections::HashSet::new();
+        for (table, (lo, hi)) in MESSAGE_TABLES.iter().zip(ranges) {
+            for m in table.iter() {
+                assert!((lo..=hi).contains(&m.tag), "{} out of range", m.name);
+                assert!(seen.insert(m.tag), "tag 0x{:02X} used twice", m.tag);
+                assert!(!m.directions.is_empty(), "{} has no direction", m.name);
+            }
+        }
+    }
+
+    #[test]
+    fn dump_protocol_lists_every_tag() {
+        let json = describe_protocol_json();
+        assert!(json.contains(&format!(r#""version": {PROTOCOL_VERSION}"#)));
+        for m in MESSAGE_TABLES.iter().flat_map(|t| t.iter()) {
+            assert!(
+                json.contains(&format!(
+                    r#""tag": "0x{:02X}", "name": "{}""#,
+                    m.tag, m.name
+                )),
+                "{}",
+                m.name
+            );
+        }
+        assert!(json.contains(
+            r#""name": "FeedbackChoice", "directions": ["client_to_server"], "payload": "flag", "default": true"#
+        ));
+        assert!(json.contains(r#""directions": ["server_to_client", "server_to_orchestrator"]"#));
+        assert!(json.contains(r#"e.g. \"Thinking...\""#));
+    }
 }
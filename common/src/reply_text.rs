@@ -0,0 +1,198 @@
+//! Replies as plain sentences. The tutor is asked for plain conversational
+//! text, but a reply sometimes comes with a bullet list, bold markers, emoji
+//! or a URL, which the TTS reads out as "asterisk asterisk" or letter by
+//! letter.
+
+/// `reply` without markdown, emoji and URLs: list items and headings become
+/// sentences, links keep their label, a bare URL is said "a link".
+///
+/// A reply that would come out empty (nothing but emoji, say) is returned as
+/// it is.
+pub fn sanitize_reply(reply: &str) -> String {
+    let mut sentences: Vec<String> = Vec::new();
+    for line in reply.lines() {
+        let line = strip_line_syntax(line.trim());
+        let line = clean_inline(line);
+        if line.is_empty() {
+            continue;
+        }
+        // A line break ends a sentence
+        if let Some(previous) = sentences.last_mut()
+            && !previous.ends_with(['.', '!', '?', ':', ';', ',', '\u{2026}'])
+        {
+            previous.push('.');
+        }
+        sentences.push(line);
+    }
+    if sentences.is_empty() {
+        return reply.to_string();
+    }
+    sentences.join(" ")
+}
+
+/// `line` without its heading, quote or list marker, or empty for a rule
+/// (`---`).
+fn strip_line_syntax(line: &str) -> &str {
+    if line.len() >= 3 && line.chars().all(|c| matches!(c, '-' | '*' | '_' | ' ')) {
+        return "";
+    }
+    let mut line = match line.trim_start_matches('#').strip_prefix(' ') {
+        Some(heading) if line.starts_with('#') => heading.trim_start(),
+        _ => line,
+    };
+    while let Some(rest) = line.strip_prefix('>') {
+        line = rest.trim_start();
+    }
+    for bullet in ["- ", "* ", "+ ", "\u{2022} "] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            return rest.trim_start();
+        }
+    }
+    // "1. " or "2) "
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    if digits > 0
+        && let Some(rest) = line[digits..]
+            .strip_prefix(". ")
+            .or_else(|| line[digits..].strip_prefix(") "))
+    {
+        return rest.trim_start();
+    }
+    line
+}
+
+/// `text` without emphasis characters and emoji, links replaced by their
+/// label and URLs by "a link", whitespace collapsed.
+fn clean_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let word_start = !out.ends_with(|c: char| !c.is_whitespace() && c != '(');
+        if let Some((label, len)) = markdown_link(rest) {
+            out.push_str(&clean_inline(label));
+            rest = &rest[len..];
+            continue;
+        }
+        if word_start && let Some(len) = url_len(rest) {
+            out.push_str("a link");
+            rest = &rest[len..];
+            continue;
+        }
+        let next = rest[c.len_utf8()..].chars().next();
+        let inside_word =
+            out.ends_with(char::is_alphanumeric) && next.is_some_and(char::is_alphanumeric);
+        match c {
+            '*' | '`' | '~' => {}
+            // snake_case stays, _emphasis_ goes
+            '_' if !inside_word => {}
+            c if is_emoji(c) => {}
+            c => out.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `[label](url)` at the start of `text`: the label and the link's length.
+fn markdown_link(text: &str) -> Option<(&str, usize)> {
+    let rest = text.strip_prefix('[')?;
+    let label_end = rest.find("](")?;
+    let url_end = rest[label_end + 2..].find(')')?;
+    let label = &rest[..label_end];
+    (!label.contains('[')).then_some((label, 1 + label_end + 2 + url_end + 1))
+}
+
+/// The length of the URL `text` starts with, trailing punctuation excluded.
+fn url_len(text: &str) -> Option<usize> {
+    if !["http://", "https://", "www."].iter().any(|scheme| {
+        text.get(..scheme.len())
+            .is_some_and(|s| s.eq_ignore_ascii_case(scheme))
+    }) {
+        return None;
+    }
+    let token = &text[..text.find(char::is_whitespace).unwrap_or(text.len())];
+    Some(
+        token
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '"', '\''])
+            .len(),
+    )
+}
+
+/// Emoji and the joiners and selectors that build them.
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, flags, skin tones
+            | 0x2600..=0x27BF // symbols and dingbats
+            | 0x2B00..=0x2BFF // arrows and stars
+            | 0xFE00..=0xFE0F // variation selectors
+            | 0x200D // zero-width joiner
+            | 0x20E3 // keycap
+            | 0xE0020..=0xE007F // tag characters
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messy_replies_become_plain_sentences() {
+        for (reply, spoken) in [
+            // Plain replies are kept
+            (
+                "That's a great question! Where did you go?",
+                "That's a great question! Where did you go?",
+            ),
+            ("Use snake_case names.", "Use snake_case names."),
+            // Emphasis and code
+            (
+                "That's **exactly** right, and _very_ natural.",
+                "That's exactly right, and very natural.",
+            ),
+            ("Say `I went`, not ~~I goed~~.", "Say I went, not I goed."),
+            // Lists and headings
+            (
+                "Here are some options:\n- go to the park\n- read a book\n- cook dinner",
+                "Here are some options: go to the park. read a book. cook dinner",
+            ),
+            (
+                "## Tips\n1. Speak slowly.\n2) Ask questions!",
+                "Tips. Speak slowly. Ask questions!",
+            ),
+            (
+                "**Great job!**\n\n> Practice makes perfect\n\n---\nNext one?",
+                "Great job! Practice makes perfect. Next one?",
+            ),
+            // Emoji
+            ("Well done! 🎉👏", "Well done!"),
+            ("I ❤️ this 👍🏽 answer.", "I this answer."),
+            ("Go 🇫🇷 team!", "Go team!"),
+            // Links and URLs
+            (
+                "Check [the BBC site](https://www.bbc.co.uk/learning) for more.",
+                "Check the BBC site for more.",
+            ),
+            (
+                "See https://en.wikipedia.org/wiki/English_grammar.",
+                "See a link.",
+            ),
+            (
+                "Try www.duolingo.com, it's free (or http://example.com).",
+                "Try a link, it's free (or a link).",
+            ),
+            // Not markdown
+            ("Is 5 > 3? Yes.", "Is 5 > 3? Yes."),
+            ("#1 fan, 2. place", "#1 fan, 2. place"),
+            ("[SPEED:0.6] Slowly.", "[SPEED:0.6] Slowly."),
+        ] {
+            assert_eq!(sanitize_reply(reply), spoken, "{reply}");
+        }
+    }
+
+    #[test]
+    fn a_reply_is_never_emptied() {
+        assert_eq!(sanitize_reply("🎉👏"), "🎉👏");
+        assert_eq!(sanitize_reply("**"), "**");
+        assert_eq!(sanitize_reply(""), "");
+    }
+}
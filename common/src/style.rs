@@ -0,0 +1,223 @@
+//! Terminal colors, or plain text for `NO_COLOR`, `--color never` and output
+//! that is not a terminal.
+//!
+//! Display code styles its text here instead of writing escape codes. The
+//! plain form keeps what a color meant with a readable prefix (`[ERR]`,
+//! `[HINT]`, ...) and drops what was only decoration.
+
+use anyhow::{Result, bail};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+/// The `--color` choice.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColorChoice {
+    /// Color a terminal, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => bail!("expected \"auto\", \"always\" or \"never\", got \"{s}\""),
+        }
+    }
+
+    /// Whether to color, given the `NO_COLOR` variable and whether stderr
+    /// (where the session is printed) is a terminal.
+    fn resolve(self, no_color: Option<&str>, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            // An empty NO_COLOR does not count (no-color.org)
+            ColorChoice::Auto => no_color.is_none_or(str::is_empty) && terminal,
+        }
+    }
+}
+
+/// Decide once, at startup, whether output is colored.
+pub fn init(choice: ColorChoice) {
+    let no_color = std::env::var("NO_COLOR").ok();
+    let color = choice.resolve(no_color.as_deref(), std::io::stderr().is_terminal());
+    COLOR.store(color, Ordering::SeqCst);
+}
+
+/// The palette chosen at startup (colored until `init` says otherwise).
+pub fn palette() -> Palette {
+    if COLOR.load(Ordering::Relaxed) {
+        Palette::COLOR
+    } else {
+        Palette::PLAIN
+    }
+}
+
+/// Styles text, with ANSI colors or as plain text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    color: bool,
+}
+
+impl Palette {
+    pub const COLOR: Palette = Palette { color: true };
+    pub const PLAIN: Palette = Palette { color: false };
+
+    pub fn is_color(self) -> bool {
+        self.color
+    }
+
+    /// A mistake or failure: red cross, `[ERR]` in plain text.
+    pub fn error(self, text: &str) -> String {
+        self.marked("31", "\u{2717}", "[ERR]", text)
+    }
+
+    /// A suggestion: blue arrow, `[HINT]` in plain text.
+    pub fn hint(self, text: &str) -> String {
+        self.marked("34", "\u{279c}", "[HINT]", text)
+    }
+
+    /// A warning: bold yellow sign, `[WARN]` in plain text.
+    pub fn warning(self, text: &str) -> String {
+        self.marked("1;33", "\u{26a0}", "[WARN]", text)
+    }
+
+    /// Red text with no mark, `[!]` in plain text.
+    pub fn alert(self, text: &str) -> String {
+        if self.color {
+            paint("31", text)
+        } else {
+            format!("[!] {text}")
+        }
+    }
+
+    /// The green check in front of a corrected sentence, `[OK]` in plain text.
+    pub fn ok_mark(self) -> String {
+        if self.color {
+            paint("32", "\u{2713}")
+        } else {
+            "[OK]".to_string()
+        }
+    }
+
+    /// A corrected part of a sentence: green, `*between stars*` in plain text.
+    pub fn highlight(self, text: &str) -> String {
+        if self.color {
+            paint("32", text)
+        } else {
+            format!("*{text}*")
+        }
+    }
+
+    /// The log's "WARNING:" label, yellow.
+    pub fn warning_label(self) -> String {
+        self.decorate("33", "WARNING:")
+    }
+
+    pub fn bold(self, text: &str) -> String {
+        self.decorate("1", text)
+    }
+
+    /// Separators, hints and other secondary text.
+    pub fn dim(self, text: &str) -> String {
+        self.decorate("2", text)
+    }
+
+    /// Dim text that keeps the color of the line around it.
+    pub fn dim_inline(self, text: &str) -> String {
+        if self.color {
+            // 22 = normal intensity, where 0 would also reset the color
+            format!("\x1b[2m{text}\x1b[22m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Status text (the tutor working, the spinner).
+    pub fn dim_italic(self, text: &str) -> String {
+        self.decorate("2;3", text)
+    }
+
+    /// A translation.
+    pub fn accent(self, text: &str) -> String {
+        self.decorate("36", text)
+    }
+
+    /// Colored `mark text`, or `label text` in plain text.
+    fn marked(self, sgr: &str, mark: &str, label: &str, text: &str) -> String {
+        if self.color {
+            paint(sgr, &format!("{mark} {text}"))
+        } else {
+            format!("{label} {text}")
+        }
+    }
+
+    /// Styling with no meaning of its own: plain text keeps the text only.
+    fn decorate(self, sgr: &str, text: &str) -> String {
+        if self.color {
+            paint(sgr, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn paint(sgr: &str, text: &str) -> String {
+    format!("\x1b[{sgr}m{text}\x1b[0m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_keeps_the_usual_escape_codes() {
+        let p = Palette::COLOR;
+        assert_eq!(p.error("x"), "\x1b[31m\u{2717} x\x1b[0m");
+        assert_eq!(p.hint("x"), "\x1b[34m\u{279c} x\x1b[0m");
+        assert_eq!(p.warning("x"), "\x1b[1;33m\u{26a0} x\x1b[0m");
+        assert_eq!(p.ok_mark(), "\x1b[32m\u{2713}\x1b[0m");
+        assert_eq!(p.highlight("x"), "\x1b[32mx\x1b[0m");
+        assert_eq!(p.dim("---"), "\x1b[2m---\x1b[0m");
+        assert_eq!(p.dim_inline("[1]"), "\x1b[2m[1]\x1b[22m");
+    }
+
+    #[test]
+    fn plain_text_has_no_escapes_and_keeps_the_meaning() {
+        let p = Palette::PLAIN;
+        assert_eq!(p.error("x"), "[ERR] x");
+        assert_eq!(p.hint("x"), "[HINT] x");
+        assert_eq!(p.warning("x"), "[WARN] x");
+        assert_eq!(p.alert("x"), "[!] x");
+        assert_eq!(p.ok_mark(), "[OK]");
+        assert_eq!(p.highlight("x"), "*x*");
+        for styled in [
+            p.dim("---"),
+            p.dim_inline("---"),
+            p.bold("---"),
+            p.dim_italic("---"),
+            p.accent("---"),
+        ] {
+            assert_eq!(styled, "---");
+        }
+        assert_eq!(p.warning_label(), "WARNING:");
+    }
+
+    #[test]
+    fn choice_follows_flag_then_no_color_then_terminal() {
+        assert_eq!(ColorChoice::parse("never").unwrap(), ColorChoice::Never);
+        assert!(ColorChoice::parse("sometimes").is_err());
+        assert!(ColorChoice::Auto.resolve(None, true));
+        assert!(!ColorChoice::Auto.resolve(None, false));
+        assert!(!ColorChoice::Auto.resolve(Some("1"), true));
+        assert!(ColorChoice::Auto.resolve(Some(""), true));
+        assert!(ColorChoice::Always.resolve(Some("1"), false));
+        assert!(!ColorChoice::Never.resolve(None, true));
+    }
+}
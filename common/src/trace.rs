@@ -0,0 +1,156 @@
+//! Per-message protocol tracing, enabled with `--trace-protocol`.
+//!
+//! The protocol read and write functions report every frame here, so no call
+//! site needs its own logging. While tracing is disabled a frame costs a single
+//! atomic load; the line is only formatted for an installed sink.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Prefix of every trace line, to grep them out of the log.
+pub const PREFIX: &str = "[trace]";
+
+/// Longest text preview, in characters.
+const PREVIEW_CHARS: usize = 60;
+
+/// Sample rate of every audio payload on the wire.
+const AUDIO_RATE: usize = 16000;
+
+/// Receives each formatted trace line.
+pub type Sink = Arc<dyn Fn(&str) + Send + Sync>;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SINK: RwLock<Option<Sink>> = RwLock::new(None);
+
+/// Trace to stderr, with the rest of the log.
+pub fn enable() {
+    set_sink(Some(Arc::new(|line| crate::info!("{line}"))));
+}
+
+/// Install `sink`, or stop tracing with `None`.
+pub fn set_sink(sink: Option<Sink>) {
+    let enabled = sink.is_some();
+    *SINK.write().unwrap_or_else(|e| e.into_inner()) = sink;
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Which way a frame went.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Direction {
+    Sent,
+    Received,
+}
+
+/// What a trace line shows of a payload.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Preview<'a> {
+    Empty,
+    Text(&'a str),
+    Flag(bool),
+    /// Audio, shown as a duration.
+    Samples(usize),
+    /// Struct payload, shown as a size only.
+    Bytes,
+}
+
+/// Report one frame to the sink.
+pub(crate) fn frame(direction: Direction, name: &str, tag: u8, len: usize, preview: Preview) {
+    let sink = SINK.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(sink) = sink {
+        sink(&format_frame(direction, name, tag, len, preview));
+    }
+}
+
+/// `[trace] → TextInput 0x07 5 B "Salut"`, `[trace] ← TtsAudioChunk 0x83 8000 B 250ms audio`.
+pub(crate) fn format_frame(
+    direction: Direction,
+    name: &str,
+    tag: u8,
+    len: usize,
+    preview: Preview,
+) -> String {
+    let arrow = match direction {
+        Direction::Sent => "\u{2192}",
+        Direction::Received => "\u{2190}",
+    };
+    let mut line = format!("{PREFIX} {arrow} {name} 0x{tag:02x} {len} B");
+    match preview {
+        Preview::Empty | Preview::Bytes => {}
+        Preview::Text(text) => {
+            let mut chars = text.chars();
+            let shown: String = chars.by_ref().take(PREVIEW_CHARS).collect();
+            let more = if chars.next().is_some() {
+                "\u{2026}"
+            } else {
+                ""
+            };
+            line.push_str(&format!(" {shown:?}{more}"));
+        }
+        Preview::Flag(flag) => line.push_str(&format!(" {flag}")),
+        Preview::Samples(samples) => {
+            line.push_str(&format!(" {}ms audio", samples * 1000 / AUDIO_RATE));
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_show_direction_size_and_preview() {
+        assert_eq!(
+            format_frame(
+                Direction::Sent,
+                "TextInput",
+                0x07,
+                5,
+                Preview::Text("Salut")
+            ),
+            "[trace] \u{2192} TextInput 0x07 5 B \"Salut\""
+        );
+        assert_eq!(
+            format_frame(
+                Direction::Received,
+                "TtsAudioChunk",
+                0x83,
+                8000,
+                Preview::Samples(4000)
+            ),
+            "[trace] \u{2190} TtsAudioChunk 0x83 8000 B 250ms audio"
+        );
+        assert_eq!(
+            format_frame(Direction::Sent, "PauseRequest", 0x02, 0, Preview::Empty),
+            "[trace] \u{2192} PauseRequest 0x02 0 B"
+        );
+        assert_eq!(
+            format_frame(
+                Direction::Received,
+                "FeedbackChoice",
+                0xa5,
+                1,
+                Preview::Flag(true)
+            ),
+            "[trace] \u{2190} FeedbackChoice 0xa5 1 B true"
+        );
+    }
+
+    #[test]
+    fn long_text_is_truncated_and_escaped() {
+        let text = format!("{}\nend", "é".repeat(70));
+        let line = format_frame(
+            Direction::Sent,
+            "Text",
+            0x81,
+            text.len(),
+            Preview::Text(&text),
+        );
+        assert!(line.ends_with(&format!("\"{}\"\u{2026}", "é".repeat(60))));
+        assert!(!line.contains('\n'));
+    }
+}
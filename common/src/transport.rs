@@ -0,0 +1,610 @@
+use anyhow::{Context, Result};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::time::Duration;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, ring};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{
+    ClientConfig, ClientConnection, Connection, DigitallySignedStruct, RootCertStore, ServerConfig,
+    ServerConnection, SignatureScheme,
+};
+
+pub use rustls::{ClientConfig as TlsClientConfig, ServerConfig as TlsServerConfig};
+
+/// Size of the scratch buffer used to pull TLS records off the socket.
+const TLS_READ_BUF: usize = 16 * 1024;
+
+/// A client that has not finished its TLS handshake by then is dropped.
+pub const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The client ↔ server TCP link, either plain or wrapped in TLS.
+///
+/// Framing is identical in both modes: the TLV protocol runs unchanged inside
+/// the tunnel. Like `TcpStream`, a transport can be cloned to hand the read and
+/// write halves to different threads, and `shutdown()` on any clone unblocks a
+/// reader stuck on another one.
+pub enum Transport {
+    Plain(TcpStream),
+    Tls(TlsStream),
+}
+
+impl Transport {
+    /// Run the server side of a TLS handshake on an accepted stream.
+    pub fn accept_tls(stream: TcpStream, config: Arc<ServerConfig>) -> Result<Self> {
+        Self::accept_tls_within(stream, config, TLS_HANDSHAKE_TIMEOUT)
+    }
+
+    fn accept_tls_within(
+        stream: TcpStream,
+        config: Arc<ServerConfig>,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let conn = ServerConnection::new(config).context("creating TLS server session")?;
+        // A peer that never speaks would otherwise hold its thread forever
+        stream
+            .set_read_timeout(Some(timeout))
+            .context("setting the TLS handshake timeout")?;
+        let tls = TlsStream::handshake(stream, conn.into())?;
+        tls.sock
+            .set_read_timeout(None)
+            .context("clearing the TLS handshake timeout")?;
+        Ok(Transport::Tls(tls))
+    }
+
+    /// Run the client side of a TLS handshake, verifying the server against `addr`.
+    pub fn connect_tls(
+        stream: TcpStream,
+        addr: &SocketAddr,
+        config: Arc<ClientConfig>,
+    ) -> Result<Self> {
+        let name = ServerName::IpAddress(addr.ip().into());
+        let conn = ClientConnection::new(config, name).context("creating TLS client session")?;
+        TlsStream::handshake(stream, conn.into()).map(Transport::Tls)
+    }
+
+    pub fn is_tls(&self) -> bool {
+        matches!(self, Transport::Tls(_))
+    }
+
+    /// Clone the transport; both handles share the same underlying connection.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Transport::Plain(s) => s.try_clone().map(Transport::Plain),
+            Transport::Tls(s) => s.try_clone().map(Transport::Tls),
+        }
+    }
+
+    /// Shut down the underlying socket (no TLS close_notify is sent).
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.tcp().shutdown(how)
+    }
+
+    pub fn set_read_timeout(&self, timeout: Option<std::time::Duration>) -> io::Result<()> {
+        self.tcp().set_read_timeout(timeout)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.tcp().peer_addr()
+    }
+
+    fn tcp(&self) -> &TcpStream {
+        match self {
+            Transport::Plain(s) => s,
+            Transport::Tls(s) => &s.sock,
+        }
+    }
+}
+
+impl Read for Transport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Transport::Plain(s) => s.read(buf),
+            Transport::Tls(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Transport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Transport::Plain(s) => s.write(buf),
+            Transport::Tls(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Transport::Plain(s) => s.flush(),
+            Transport::Tls(s) => s.flush(),
+        }
+    }
+}
+
+/// A TLS session over a TCP socket that can be split across threads.
+///
+/// `rustls::StreamOwned` can't be cloned, so instead each handle owns its own
+/// clone of the socket and shares the session state behind a mutex. The
+/// session lock is never held across socket I/O: readers pull records off the
+/// socket without it, and writers encrypt under it but send outside it, so a
+/// write blocked on a full socket never stalls the reader that would drain
+/// the other direction.
+pub struct TlsStream {
+    sock: TcpStream,
+    conn: Arc<Mutex<Connection>>,
+    /// Held while records are written to the socket, so they go out in order.
+    sending: Arc<Mutex<()>>,
+}
+
+impl TlsStream {
+    fn handshake(mut sock: TcpStream, mut conn: Connection) -> Result<Self> {
+        while conn.is_handshaking() {
+            conn.complete_io(&mut sock).map_err(|e| {
+                if e.kind() == io::ErrorKind::UnexpectedEof {
+                    anyhow::anyhow!(
+                        "TLS handshake failed: peer closed the connection (is TLS enabled on both ends?)"
+                    )
+                } else if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) {
+                    anyhow::anyhow!("TLS handshake failed: the peer did not answer in time")
+                } else {
+                    anyhow::anyhow!("TLS handshake failed: {e}")
+                }
+            })?;
+        }
+        // Push out anything queued after the handshake (e.g. session tickets)
+        while conn.wants_write() {
+            conn.write_tls(&mut sock)
+                .context("flushing TLS handshake")?;
+        }
+        Ok(Self {
+            sock,
+            conn: Arc::new(Mutex::new(conn)),
+            sending: Arc::default(),
+        })
+    }
+
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            sock: self.sock.try_clone()?,
+            conn: self.conn.clone(),
+            sending: self.sending.clone(),
+        })
+    }
+
+    fn lock(&self) -> io::Result<MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| io::Error::other("TLS session lock poisoned"))
+    }
+
+    /// Send the records the session has queued.
+    ///
+    /// With `wait` unset, records are left to a thread that is already
+    /// sending: it looks for more once it lets go. A reader uses that, so it
+    /// never waits behind a writer blocked on a full socket.
+    fn send_pending(&self, wait: bool) -> io::Result<()> {
+        loop {
+            let sending = if wait {
+                self.sending.lock().ok()
+            } else {
+                match self.sending.try_lock() {
+                    Ok(guard) => Some(guard),
+                    Err(TryLockError::WouldBlock) => return Ok(()),
+                    Err(TryLockError::Poisoned(_)) => None,
+                }
+            };
+            let Some(sending) = sending else {
+                return Err(io::Error::other("TLS send lock poisoned"));
+            };
+            loop {
+                let mut records = Vec::new();
+                {
+                    let mut conn = self.lock()?;
+                    while conn.wants_write() {
+                        conn.write_tls(&mut records)?;
+                    }
+                }
+                if records.is_empty() {
+                    break;
+                }
+                (&self.sock).write_all(&records)?;
+            }
+            drop(sending);
+            // Records queued by a reader that found us sending
+            if !self.lock()?.wants_write() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut scratch = [0u8; TLS_READ_BUF];
+        loop {
+            {
+                let mut conn = self.lock()?;
+                match conn.reader().read(buf) {
+                    Ok(n) => return Ok(n),
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(e),
+                }
+            }
+
+            let n = (&self.sock).read(&mut scratch)?;
+            if n == 0 {
+                return Ok(0);
+            }
+
+            {
+                let mut conn = self.lock()?;
+                let mut records = &scratch[..n];
+                while !records.is_empty() {
+                    conn.read_tls(&mut records)?;
+                    conn.process_new_packets()
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                }
+            }
+            self.send_pending(false)?;
+        }
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.lock()?.writer().write(buf)?;
+        self.send_pending(true)?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock()?.writer().flush()?;
+        self.send_pending(true)?;
+        (&self.sock).flush()
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .with_context(|| format!("reading certificates from {}", path.display()))?
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("parsing certificates in {}", path.display()))?;
+    if certs.is_empty() {
+        anyhow::bail!("no certificates found in {}", path.display());
+    }
+    Ok(certs)
+}
+
+/// Build the server TLS config from a PEM certificate chain and private key.
+pub fn server_config(cert_path: &Path, key_path: &Path) -> Result<Arc<ServerConfig>> {
+    let certs = load_certs(cert_path)?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .with_context(|| format!("reading private key from {}", key_path.display()))?;
+
+    let config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .context("selecting TLS protocol versions")?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("TLS certificate and key do not match")?;
+    Ok(Arc::new(config))
+}
+
+/// Build the client TLS config.
+///
+/// - `ca_path`: trust only the certificates in this PEM file (e.g. a self-signed server cert)
+/// - `insecure`: skip certificate verification entirely (encryption without authentication)
+/// - neither: trust the bundled Mozilla root store
+pub fn client_config(ca_path: Option<&Path>, insecure: bool) -> Result<Arc<ClientConfig>> {
+    let builder = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .context("selecting TLS protocol versions")?;
+
+    let config = if insecure {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerification(provider())))
+            .with_no_client_auth()
+    } else {
+        let mut roots = RootCertStore::empty();
+        match ca_path {
+            Some(path) => {
+                for cert in load_certs(path)? {
+                    roots
+                        .add(cert)
+                        .with_context(|| format!("adding CA from {}", path.display()))?;
+                }
+            }
+            None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+        builder.with_root_certificates(roots).with_no_client_auth()
+    };
+    Ok(Arc::new(config))
+}
+
+/// Accepts any server certificate but still checks handshake signatures.
+#[derive(Debug)]
+struct NoVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{ServerMsg, read_server_msg, write_server_msg};
+    use std::io::{BufReader, BufWriter};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    static TEST_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    /// A cert and its key in temp files, removed when dropped.
+    struct TestCert {
+        cert: PathBuf,
+        key: PathBuf,
+    }
+
+    impl Drop for TestCert {
+        fn drop(&mut self) {
+            std::fs::remove_file(&self.cert).ok();
+            std::fs::remove_file(&self.key).ok();
+        }
+    }
+
+    /// Write a fresh self-signed cert for 127.0.0.1 to temp files.
+    fn self_signed() -> TestCert {
+        let n = TEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        let dir = std::env::temp_dir();
+        let cert = dir.join(format!("space_lt_tls_{pid}_{n}.crt"));
+        let key = dir.join(format!("space_lt_tls_{pid}_{n}.key"));
+
+        let ck = rcgen::generate_simple_self_signed(vec!["127.0.0.1".into()]).unwrap();
+        std::fs::write(&cert, ck.cert.pem()).unwrap();
+        std::fs::write(&key, ck.key_pair.serialize_pem()).unwrap();
+        TestCert { cert, key }
+    }
+
+    fn tls_pair(
+        client_cfg: Arc<ClientConfig>,
+        server_cfg: Arc<ServerConfig>,
+    ) -> (
+        Result<Transport>,
+        std::thread::JoinHandle<Result<Transport>>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            Transport::accept_tls(stream, server_cfg)
+        });
+
+        let stream = TcpStream::connect(addr).unwrap();
+        (Transport::connect_tls(stream, &addr, client_cfg), server)
+    }
+
+    #[test]
+    fn tls_roundtrip_with_ca() {
+        let tls = self_signed();
+        let server_cfg = server_config(&tls.cert, &tls.key).unwrap();
+        let client_cfg = client_config(Some(&tls.cert), false).unwrap();
+
+        let (client, server) = tls_pair(client_cfg, server_cfg);
+        let client = client.unwrap();
+        let server = server.join().unwrap().unwrap();
+        assert!(client.is_tls() && server.is_tls());
+
+        let mut server_writer = BufWriter::new(server.try_clone().unwrap());
+        write_server_msg(&mut server_writer, &ServerMsg::Ready).unwrap();
+        write_server_msg(
+            &mut server_writer,
+            &ServerMsg::TtsAudioChunk(vec![7i16; 4000]),
+        )
+        .unwrap();
+
+        let mut reader = BufReader::new(client);
+        assert!(matches!(
+            read_server_msg(&mut reader).unwrap(),
+            ServerMsg::Ready
+        ));
+        match read_server_msg(&mut reader).unwrap() {
+            ServerMsg::TtsAudioChunk(samples) => {
+                assert_eq!(samples.len(), 4000);
+                assert!(samples.iter().all(|&s| s == 7));
+            }
+            other => panic!("Expected TtsAudioChunk, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn tls_shutdown_unblocks_reader_on_clone() {
+        let tls = self_signed();
+        let (client, server) = tls_pair(
+            client_config(None, true).unwrap(),
+            server_config(&tls.cert, &tls.key).unwrap(),
+        );
+        let client = client.unwrap();
+        let _server = server.join().unwrap().unwrap();
+
+        let cleanup = client.try_clone().unwrap();
+        let reader = std::thread::spawn(move || {
+            let mut reader = BufReader::new(client);
+            read_server_msg(&mut reader)
+        });
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        cleanup.shutdown(Shutdown::Both).unwrap();
+
+        let err = reader.join().unwrap().unwrap_err();
+        assert!(crate::protocol::is_disconnect(&err));
+    }
+
+    #[test]
+    fn tls_large_writes_both_ways_do_not_deadlock() {
+        // Well past what the socket buffers hold: with the readers starting
+        // late, both writers are blocked on a full socket by then
+        const LEN: usize = 8 * 1024 * 1024;
+        let tls = self_signed();
+        let (client, server) = tls_pair(
+            client_config(None, true).unwrap(),
+            server_config(&tls.cert, &tls.key).unwrap(),
+        );
+        let ends = [client.unwrap(), server.join().unwrap().unwrap()];
+
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        for (i, end) in ends.into_iter().enumerate() {
+            let mut writer = end.try_clone().unwrap();
+            std::thread::spawn(move || writer.write_all(&vec![i as u8; LEN]).unwrap());
+            let done_tx = done_tx.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(200));
+                let mut received = Vec::new();
+                let mut reader = end;
+                (&mut reader)
+                    .take(LEN as u64)
+                    .read_to_end(&mut received)
+                    .unwrap();
+                let peer = 1 - i as u8;
+                done_tx
+                    .send(received.len() == LEN && received.iter().all(|&b| b == peer))
+                    .unwrap();
+            });
+        }
+        for _ in 0..2 {
+            let intact = done_rx
+                .recv_timeout(Duration::from_secs(30))
+                .expect("the transfer stalled");
+            assert!(intact);
+        }
+    }
+
+    #[test]
+    fn tls_rejects_untrusted_cert() {
+        let tls = self_signed();
+        let other = self_signed();
+        let (client, server) = tls_pair(
+            client_config(Some(&other.cert), false).unwrap(),
+            server_config(&tls.cert, &tls.key).unwrap(),
+        );
+
+        let err = client.err().expect("handshake should fail");
+        assert!(format!("{err:#}").contains("TLS handshake failed"));
+        assert!(server.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn tls_handshake_against_plain_server_fails_clearly() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = BufWriter::new(stream);
+            write_server_msg(&mut writer, &ServerMsg::Ready).unwrap();
+        });
+
+        let stream = TcpStream::connect(addr).unwrap();
+        let err = Transport::connect_tls(stream, &addr, client_config(None, true).unwrap())
+            .err()
+            .expect("handshake should fail");
+        assert!(format!("{err:#}").contains("TLS handshake failed"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn silent_peer_times_out_the_handshake() {
+        let tls = self_signed();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        // Connects and never says a word
+        let _silent = TcpStream::connect(addr).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        let started = std::time::Instant::now();
+        let err = Transport::accept_tls_within(
+            stream,
+            server_config(&tls.cert, &tls.key).unwrap(),
+            Duration::from_millis(100),
+        )
+        .err()
+        .expect("handshake should time out");
+        assert!(
+            format!("{err:#}").contains("did not answer in time"),
+            "{err:#}"
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn tls_roundtrip_clears_the_handshake_timeout() {
+        let tls = self_signed();
+        let (client, server) = tls_pair(
+            client_config(None, true).unwrap(),
+            server_config(&tls.cert, &tls.key).unwrap(),
+        );
+        let _client = client.unwrap();
+        let server = server.join().unwrap().unwrap();
+        assert_eq!(server.tcp().read_timeout().unwrap(), None);
+    }
+
+    #[test]
+    fn server_config_rejects_missing_files() {
+        let missing = Path::new("/nonexistent/space_lt.pem");
+        assert!(server_config(missing, missing).is_err());
+        assert!(client_config(Some(missing), false).is_err());
+    }
+}
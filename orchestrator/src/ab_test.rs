@@ -0,0 +1,287 @@
+use anyhow::{Result, bail};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::claude::LlmBackend;
+
+/// One of the two agent prompts compared in an A/B session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variant {
+    /// The `--agent` prompt.
+    A,
+    /// The `--agent-b` prompt.
+    B,
+}
+
+impl Variant {
+    fn index(self) -> usize {
+        match self {
+            Variant::A => 0,
+            Variant::B => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Variant::A => write!(f, "A"),
+            Variant::B => write!(f, "B"),
+        }
+    }
+}
+
+/// How turns are assigned to the two variants (`--ab-order`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AbOrder {
+    /// A, B, A, B...
+    Alternate,
+    /// A coin flip per turn, reproducible from the seed.
+    Random { seed: u64 },
+}
+
+impl AbOrder {
+    /// Parse `--ab-order` ("alternate" or "random"). A random order without
+    /// `seed` gets one from the clock; it is logged and printed in the summary.
+    pub fn parse(order: &str, seed: Option<u64>) -> Result<Self> {
+        match order {
+            "alternate" => Ok(AbOrder::Alternate),
+            "random" => Ok(AbOrder::Random {
+                seed: seed.unwrap_or_else(clock_seed),
+            }),
+            other => bail!("expected \"alternate\" or \"random\", got \"{other}\""),
+        }
+    }
+}
+
+impl std::fmt::Display for AbOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbOrder::Alternate => write!(f, "alternate"),
+            AbOrder::Random { seed } => write!(f, "random, seed {seed}"),
+        }
+    }
+}
+
+fn clock_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+/// Per-variant counters reported in the session summary.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VariantStats {
+    pub turns: u32,
+    pub corrections: u32,
+    pub retries: u32,
+    /// Total LLM time of the variant's answered turns.
+    pub llm_time: Duration,
+}
+
+/// The variant answering a turn, and whether its conversation already exists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AbTurn {
+    pub variant: Variant,
+    pub continue_session: bool,
+}
+
+/// A/B comparison of two agent prompts within one session. Each variant keeps
+/// its own conversation: variant A uses the voice loop's backend, variant B the
+/// backend given here (for the Claude CLI, one session dir each).
+pub struct AbTest {
+    backend_b: Box<dyn LlmBackend>,
+    agent_b: PathBuf,
+    order: AbOrder,
+    rng: u64,
+    turns_started: u64,
+    /// Variant of the last turn, kept when the user retries it
+    current: Option<Variant>,
+    /// Whether each variant has been queried yet
+    started: [bool; 2],
+    stats: [VariantStats; 2],
+}
+
+impl AbTest {
+    pub fn new(backend_b: Box<dyn LlmBackend>, agent_b: PathBuf, order: AbOrder) -> Self {
+        let rng = match order {
+            AbOrder::Alternate => 0,
+            AbOrder::Random { seed } => seed,
+        };
+        Self {
+            backend_b,
+            agent_b,
+            order,
+            rng,
+            turns_started: 0,
+            current: None,
+            started: [false; 2],
+            stats: Default::default(),
+        }
+    }
+
+    pub fn backend_b(&self) -> &dyn LlmBackend {
+        self.backend_b.as_ref()
+    }
+
+    pub fn agent_b(&self) -> &Path {
+        &self.agent_b
+    }
+
+    /// Pick the variant for the next turn. A rephrased turn (`retry`) stays
+    /// with the variant that gave the correction.
+    pub fn start_turn(&mut self, retry: bool) -> AbTurn {
+        let variant = match (retry, self.current) {
+            (true, Some(current)) => current,
+            _ => {
+                let pick_b = match self.order {
+                    AbOrder::Alternate => self.turns_started % 2 == 1,
+                    AbOrder::Random { .. } => self.next_random() & 1 == 1,
+                };
+                let variant = if pick_b { Variant::B } else { Variant::A };
+                self.turns_started += 1;
+                variant
+            }
+        };
+        self.current = Some(variant);
+        let continue_session = std::mem::replace(&mut self.started[variant.index()], true);
+        AbTurn {
+            variant,
+            continue_session,
+        }
+    }
+
+    /// Whether `variant` has a conversation to continue.
+    pub fn has_context(&self, variant: Variant) -> bool {
+        self.started[variant.index()]
+    }
+
+    /// Count an answered turn.
+    pub fn record_turn(&mut self, variant: Variant, had_feedback: bool, llm_time: Duration) {
+        let stats = &mut self.stats[variant.index()];
+        stats.turns += 1;
+        if had_feedback {
+            stats.corrections += 1;
+        }
+        stats.llm_time += llm_time;
+    }
+
+    /// Count a turn the user chose to redo after the variant's feedback.
+    pub fn record_retry(&mut self, variant: Variant) {
+        self.stats[variant.index()].retries += 1;
+    }
+
+    pub fn stats(&self, variant: Variant) -> &VariantStats {
+        &self.stats[variant.index()]
+    }
+
+    /// Markdown section appended to the session summary.
+    pub fn summary_section(&self, agent_a: &Path) -> String {
+        let mut out = format!("### A/B Test ({})\n", self.order);
+        for (variant, agent) in [(Variant::A, agent_a), (Variant::B, self.agent_b.as_path())] {
+            let stats = self.stats(variant);
+            let avg_llm = match stats.turns {
+                0 => "-".to_string(),
+                n => format!("{:.1}s", stats.llm_time.as_secs_f64() / n as f64),
+            };
+            out.push_str(&format!(
+                "- **{variant}** ({}): {} turns, {} corrections, {} retries, {avg_llm} avg LLM time\n",
+                agent.display(),
+                stats.turns,
+                stats.corrections,
+                stats.retries
+            ));
+        }
+        out
+    }
+
+    /// splitmix64 step.
+    fn next_random(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claude::MockLlmBackend;
+
+    fn ab(order: AbOrder) -> AbTest {
+        AbTest::new(
+            Box::new(MockLlmBackend::new(vec!["B".into()])),
+            PathBuf::from("agent_b.md"),
+            order,
+        )
+    }
+
+    fn variants(ab: &mut AbTest, n: usize) -> Vec<Variant> {
+        (0..n).map(|_| ab.start_turn(false).variant).collect()
+    }
+
+    #[test]
+    fn alternate_order_and_continue_flags() {
+        let mut ab = ab(AbOrder::Alternate);
+        let turns: Vec<AbTurn> = (0..4).map(|_| ab.start_turn(false)).collect();
+        let order: Vec<Variant> = turns.iter().map(|t| t.variant).collect();
+        assert_eq!(order, [Variant::A, Variant::B, Variant::A, Variant::B]);
+        let flags: Vec<bool> = turns.iter().map(|t| t.continue_session).collect();
+        assert_eq!(flags, [false, false, true, true]);
+    }
+
+    #[test]
+    fn retry_stays_with_the_same_variant() {
+        let mut ab = ab(AbOrder::Alternate);
+        assert_eq!(ab.start_turn(false).variant, Variant::A);
+        assert_eq!(ab.start_turn(true).variant, Variant::A);
+        assert_eq!(ab.start_turn(false).variant, Variant::B);
+    }
+
+    #[test]
+    fn random_order_is_reproducible_from_the_seed() {
+        let a = variants(&mut ab(AbOrder::Random { seed: 42 }), 32);
+        let b = variants(&mut ab(AbOrder::Random { seed: 42 }), 32);
+        assert_eq!(a, b);
+        assert!(a.contains(&Variant::A) && a.contains(&Variant::B));
+        assert_ne!(a, variants(&mut ab(AbOrder::Random { seed: 43 }), 32));
+    }
+
+    #[test]
+    fn parse_order() {
+        assert_eq!(
+            AbOrder::parse("alternate", None).unwrap(),
+            AbOrder::Alternate
+        );
+        assert_eq!(
+            AbOrder::parse("random", Some(7)).unwrap(),
+            AbOrder::Random { seed: 7 }
+        );
+        assert!(matches!(
+            AbOrder::parse("random", None).unwrap(),
+            AbOrder::Random { .. }
+        ));
+        assert!(AbOrder::parse("zigzag", None).is_err());
+    }
+
+    #[test]
+    fn summary_section_breaks_stats_down_per_variant() {
+        let mut ab = ab(AbOrder::Random { seed: 9 });
+        ab.record_turn(Variant::A, true, Duration::from_secs(2));
+        ab.record_turn(Variant::A, false, Duration::from_secs(4));
+        ab.record_retry(Variant::A);
+        let section = ab.summary_section(Path::new("agent.md"));
+        assert!(section.starts_with("### A/B Test (random, seed 9)\n"));
+        assert!(section.contains(
+            "- **A** (agent.md): 2 turns, 1 corrections, 1 retries, 3.0s avg LLM time\n"
+        ));
+        assert!(
+            section.contains(
+                "- **B** (agent_b.md): 0 turns, 0 corrections, 0 retries, - avg LLM time\n"
+            )
+        );
+    }
+}
@@ -29,6 +29,13 @@ pub trait LlmBackend: Send + Sync {
     ) -> Result<String> {
         self.query(prompt, system_prompt_file, continue_session)
     }
+
+    /// One-off query outside the conversation (e.g. a translation): no agent
+    /// prompt, no history, and nothing added to the history the next
+    /// `continue_session` turn sees. Default: delegates to a first-turn `query()`.
+    fn side_query(&self, prompt: &str) -> Result<String> {
+        self.query(prompt, Path::new(""), false)
+    }
 }
 
 /// Mock backend returning predefined responses in order, cycling when exhausted.
@@ -102,6 +109,38 @@ impl LlmBackend for FailingMockLlmBackend {
     }
 }
 
+/// Mock backend that records every prompt it receives.
+/// Used for testing prompt assembly.
+#[cfg(test)]
+pub struct RecordingMockLlmBackend {
+    inner: MockLlmBackend,
+    pub prompts: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl RecordingMockLlmBackend {
+    pub fn new(responses: Vec<String>) -> Self {
+        Self {
+            inner: MockLlmBackend::new(responses),
+            prompts: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+impl LlmBackend for RecordingMockLlmBackend {
+    fn query(
+        &self,
+        prompt: &str,
+        system_prompt_file: &Path,
+        continue_session: bool,
+    ) -> Result<String> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        self.inner
+            .query(prompt, system_prompt_file, continue_session)
+    }
+}
+
 /// Real Claude CLI backend. Spawns `claude -p` per turn with timeout and retry.
 pub struct ClaudeCliBackend {
     session_dir: std::path::PathBuf,
@@ -109,13 +148,15 @@ pub struct ClaudeCliBackend {
 
 /// Maximum number of retry attempts for Claude CLI queries.
 const MAX_RETRIES: u32 = 3;
-/// Delay between retry attempts.
-const RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+/// Delay before the first retry; doubles for each further retry, with jitter.
+const RETRY_BASE_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
 /// Timeout for a single Claude CLI invocation.
 const QUERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
 /// Predefined error message sent to user via TTS when all retries fail.
-const ERROR_FALLBACK: &str =
-    "I'm sorry, I'm having trouble connecting right now. Please try again in a moment.";
+const ERROR_FALLBACK: &str = "I'm sorry, I'm having trouble connecting right now. Please try again in a moment, and check the orchestrator logs if it keeps happening.";
+/// Subdirectory of the session dir side queries run in: `--continue` resumes the
+/// latest conversation of the working directory, which must stay the tutor's.
+const SIDE_QUERY_DIR: &str = "side-queries";
 /// Tools to enable for Claude CLI invocations.
 /// WebSearch allows topic-based discussions with current information (FR12).
 const ALLOWED_TOOLS: &str = "WebSearch";
@@ -125,15 +166,16 @@ impl ClaudeCliBackend {
         Self { session_dir }
     }
 
-    /// Execute a single Claude CLI query with a 30-second timeout.
+    /// Execute a single Claude CLI query with a 30-second timeout, from `cwd`.
     ///
     /// If `status_tx` is provided, stderr is streamed line-by-line and web search
     /// activity is detected and reported via the channel.
     fn query_once(
         &self,
         prompt: &str,
-        system_prompt_file: &Path,
+        system_prompt_file: Option<&Path>,
         continue_session: bool,
+        cwd: &Path,
         status_tx: Option<&std::sync::mpsc::Sender<String>>,
     ) -> Result<String> {
         use space_lt_common::debug;
@@ -148,10 +190,13 @@ impl ClaudeCliBackend {
         let mut cmd = Command::new("claude");
         cmd.arg("-p");
 
-        // Always pass system prompt so profile/instructions are available on every turn
-        let system_prompt =
-            std::fs::read_to_string(system_prompt_file).context("reading system prompt file")?;
-        cmd.args(["--system-prompt", &system_prompt]);
+        // Pass the system prompt on every turn so profile/instructions are always
+        // available (side queries run without one)
+        if let Some(file) = system_prompt_file {
+            let system_prompt =
+                std::fs::read_to_string(file).context("reading system prompt file")?;
+            cmd.args(["--system-prompt", &system_prompt]);
+        }
 
         if continue_session {
             cmd.arg("--continue");
@@ -161,7 +206,7 @@ impl ClaudeCliBackend {
         cmd.stdin(Stdio::piped());
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
-        cmd.current_dir(&self.session_dir);
+        cmd.current_dir(cwd);
         cmd.env_remove("CLAUDECODE");
 
         let mut child = cmd.spawn().context("spawning Claude CLI")?;
@@ -270,6 +315,13 @@ impl LlmBackend for ClaudeCliBackend {
             Some(&status_tx),
         )
     }
+
+    fn side_query(&self, prompt: &str) -> Result<String> {
+        // Single attempt: the caller has a fallback and the user is waiting
+        let dir = self.session_dir.join(SIDE_QUERY_DIR);
+        std::fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+        self.query_once(prompt, None, false, &dir, None)
+    }
 }
 
 impl ClaudeCliBackend {
@@ -280,28 +332,80 @@ impl ClaudeCliBackend {
         continue_session: bool,
         status_tx: Option<&std::sync::mpsc::Sender<String>>,
     ) -> Result<String> {
-        use space_lt_common::{info, warn};
-
         // NOTE: if continue_session=true and a previous attempt was killed mid-response,
         // the Claude CLI session file may be in an inconsistent state. The retry with
         // --continue might fail for that reason. This is a known limitation.
-        for attempt in 1..=MAX_RETRIES {
-            match self.query_once(prompt, system_prompt_file, continue_session, status_tx) {
-                Ok(response) => return Ok(response),
-                Err(e) => {
-                    warn!("[orchestrator] Claude CLI attempt {attempt}/{MAX_RETRIES} failed: {e}");
-                    if attempt < MAX_RETRIES {
-                        info!("[orchestrator] Retrying in {}s...", RETRY_DELAY.as_secs());
-                        std::thread::sleep(RETRY_DELAY);
+        Ok(retry_query(
+            || {
+                self.query_once(
+                    prompt,
+                    Some(system_prompt_file),
+                    continue_session,
+                    &self.session_dir,
+                    status_tx,
+                )
+            },
+            status_tx,
+            std::thread::sleep,
+        ))
+    }
+}
+
+/// Run `attempt` up to `MAX_RETRIES` times, sleeping a jittered, growing delay
+/// between attempts and telling the user about each retry on `status_tx`.
+///
+/// Returns the first successful response, or `ERROR_FALLBACK` (not an error) so
+/// the user hears something when every attempt failed.
+fn retry_query(
+    mut attempt: impl FnMut() -> Result<String>,
+    status_tx: Option<&std::sync::mpsc::Sender<String>>,
+    sleep: impl Fn(std::time::Duration),
+) -> String {
+    use space_lt_common::{info, warn};
+
+    for n in 1..=MAX_RETRIES {
+        match attempt() {
+            Ok(response) => return response,
+            Err(e) => {
+                warn!("[orchestrator] Claude CLI attempt {n}/{MAX_RETRIES} failed: {e}");
+                if n < MAX_RETRIES {
+                    let delay = retry_delay(n, jitter());
+                    info!("[orchestrator] Retrying in {:.1}s...", delay.as_secs_f64());
+                    if let Some(tx) = status_tx {
+                        let _ = tx.send(format!(
+                            "Connection problem, retrying {} of {MAX_RETRIES}...",
+                            n + 1
+                        ));
                     }
+                    sleep(delay);
                 }
             }
         }
-
-        // All retries exhausted — return error fallback (NOT Err)
-        warn!("[orchestrator] All {MAX_RETRIES} Claude CLI attempts failed, sending error to user");
-        Ok(ERROR_FALLBACK.to_string())
     }
+
+    warn!("[orchestrator] All {MAX_RETRIES} Claude CLI attempts failed, sending error to user");
+    ERROR_FALLBACK.to_string()
+}
+
+/// Delay after failed attempt `attempt` (1-based): the base delay doubled per
+/// attempt, scaled by 0.5–1.5 from `jitter` (in `0.0..1.0`) so concurrent
+/// sessions do not retry in lockstep.
+fn retry_delay(attempt: u32, jitter: f64) -> std::time::Duration {
+    let backoff = RETRY_BASE_DELAY * 2u32.pow(attempt.saturating_sub(1).min(8));
+    backoff.mul_f64(0.5 + jitter.clamp(0.0, 1.0))
+}
+
+/// Pseudo-random value in `0.0..1.0`, seeded per call by the std hasher.
+fn jitter() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
 }
 
 #[cfg(test)]
@@ -359,6 +463,60 @@ mod tests {
         assert_eq!(result, "OK");
     }
 
+    #[test]
+    fn retry_reports_progress_then_succeeds() {
+        let backend = FailingMockLlmBackend::new(2, vec!["Recovered".to_string()]);
+        let p = PathBuf::from("agent.md");
+        let (tx, rx) = std::sync::mpsc::channel();
+        let slept = std::sync::Mutex::new(Vec::new());
+
+        let result = retry_query(
+            || backend.query("hi", &p, false),
+            Some(&tx),
+            |d| slept.lock().unwrap().push(d),
+        );
+        drop(tx);
+
+        assert_eq!(result, "Recovered");
+        assert_eq!(
+            rx.iter().collect::<Vec<_>>(),
+            [
+                "Connection problem, retrying 2 of 3...",
+                "Connection problem, retrying 3 of 3...",
+            ]
+        );
+        assert_eq!(slept.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn retry_exhausted_returns_fallback_with_hint() {
+        let backend = FailingMockLlmBackend::new(10, vec!["never".to_string()]);
+        let p = PathBuf::from("agent.md");
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let result = retry_query(|| backend.query("hi", &p, false), Some(&tx), |_| {});
+        drop(tx);
+
+        assert_eq!(result, ERROR_FALLBACK);
+        assert!(result.contains("orchestrator logs"));
+        // No retry announced after the last attempt
+        assert_eq!(rx.iter().count(), (MAX_RETRIES - 1) as usize);
+    }
+
+    #[test]
+    fn retry_delay_grows_with_bounded_jitter() {
+        use std::time::Duration;
+        assert_eq!(retry_delay(1, 0.5), RETRY_BASE_DELAY);
+        assert_eq!(retry_delay(2, 0.5), RETRY_BASE_DELAY * 2);
+        assert_eq!(retry_delay(1, 0.0), RETRY_BASE_DELAY / 2);
+        assert_eq!(retry_delay(1, 1.0), RETRY_BASE_DELAY * 3 / 2);
+        for _ in 0..100 {
+            let j = jitter();
+            assert!((0.0..1.0).contains(&j), "jitter {j} out of range");
+        }
+        assert!(retry_delay(2, jitter()) >= Duration::from_secs(2));
+    }
+
     #[test]
     fn allowed_tools_enables_web_search() {
         assert!(
@@ -51,12 +51,30 @@ impl OrchestratorConnection {
             ServerOrcMsg::TranscribedText(_) => {
                 anyhow::bail!("Unexpected TranscribedText during session start")
             }
+            ServerOrcMsg::SessionEnd => {
+                anyhow::bail!("Server shutting down during session start")
+            }
             ServerOrcMsg::FeedbackChoice(_) => {
                 anyhow::bail!("Unexpected FeedbackChoice during session start")
             }
             ServerOrcMsg::SummaryRequest => {
                 anyhow::bail!("Unexpected SummaryRequest during session start")
             }
+            ServerOrcMsg::TranslateRequest => {
+                anyhow::bail!("Unexpected TranslateRequest during session start")
+            }
+            ServerOrcMsg::SimplifyRequest => {
+                anyhow::bail!("Unexpected SimplifyRequest during session start")
+            }
+            ServerOrcMsg::DisregardLast => {
+                anyhow::bail!("Unexpected DisregardLast during session start")
+            }
+            ServerOrcMsg::BranchTo(_) => {
+                anyhow::bail!("Unexpected BranchTo during session start")
+            }
+            ServerOrcMsg::CancelExchange => {
+                anyhow::bail!("Unexpected CancelExchange during session start")
+            }
         }
     }
 
@@ -0,0 +1,163 @@
+use anyhow::{Result, bail};
+
+/// Opens the prompt that replays the kept exchanges into a fresh conversation.
+const BRANCH_CONTEXT: &str = "[The conversation was rewound to an earlier point. Here is everything that was said up to that point; anything you remember after it never happened. Do not mention the rewind, and continue from here.]\n\n";
+
+/// Closes the replayed exchanges, before the turn that follows.
+const BRANCH_CONTEXT_END: &str =
+    "[End of the earlier conversation. The user's next turn follows.]\n\n";
+
+/// One answered turn: what the user said and what the tutor replied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exchange {
+    pub user: String,
+    pub reply: String,
+}
+
+/// A rewind: the conversation went back from turn `from` to turn `to`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Branch {
+    pub from: u32,
+    pub to: u32,
+}
+
+/// The exchanges of the current conversation, kept so it can be rewound.
+///
+/// Only answered turns count: a turn dropped for a retry, or disregarded, is
+/// not one, so turn numbers match the client's list.
+#[derive(Debug, Default)]
+pub struct ConversationHistory {
+    exchanges: Vec<Exchange>,
+    branches: Vec<Branch>,
+}
+
+impl ConversationHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of answered turns.
+    pub fn len(&self) -> u32 {
+        self.exchanges.len() as u32
+    }
+
+    pub fn record(&mut self, user: &str, reply: &str) {
+        self.exchanges.push(Exchange {
+            user: user.to_string(),
+            reply: reply.to_string(),
+        });
+    }
+
+    /// Forget the last exchange (the user disregarded it).
+    pub fn drop_last(&mut self) {
+        self.exchanges.pop();
+    }
+
+    /// The reply of the last kept exchange.
+    pub fn last_reply(&self) -> Option<&str> {
+        self.exchanges.last().map(|e| e.reply.as_str())
+    }
+
+    /// Keep the exchanges up to turn `to` (0 = start over) and forget the rest.
+    pub fn branch(&mut self, to: u32) -> Result<Branch> {
+        let from = self.len();
+        if to >= from {
+            bail!("Cannot rewind to turn {to}: the conversation is at turn {from}");
+        }
+        self.exchanges.truncate(to as usize);
+        let branch = Branch { from, to };
+        self.branches.push(branch);
+        Ok(branch)
+    }
+
+    /// Prompt prefix replaying the kept exchanges, for the first query of the
+    /// fresh conversation that follows a rewind.
+    pub fn seed_prompt(&self) -> String {
+        let mut out = BRANCH_CONTEXT.to_string();
+        for (i, exchange) in self.exchanges.iter().enumerate() {
+            out.push_str(&format!(
+                "Turn {}\nUser: {}\nYou: {}\n\n",
+                i + 1,
+                exchange.user,
+                exchange.reply
+            ));
+        }
+        out.push_str(BRANCH_CONTEXT_END);
+        out
+    }
+
+    /// Summary section listing the rewinds, or `None` when there was none.
+    pub fn summary_section(&self) -> Option<String> {
+        if self.branches.is_empty() {
+            return None;
+        }
+        let mut out = "### Rewinds\n".to_string();
+        for branch in &self.branches {
+            out.push_str(&format!(
+                "- At turn {}, rewound to turn {}\n",
+                branch.from, branch.to
+            ));
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(turns: u32) -> ConversationHistory {
+        let mut history = ConversationHistory::new();
+        for i in 1..=turns {
+            history.record(&format!("question {i}"), &format!("answer {i}"));
+        }
+        history
+    }
+
+    #[test]
+    fn branch_keeps_turns_up_to_the_target() {
+        let mut history = history(10);
+        assert_eq!(history.branch(7).unwrap(), Branch { from: 10, to: 7 });
+        assert_eq!(history.len(), 7);
+        assert_eq!(history.last_reply(), Some("answer 7"));
+
+        // Not forward, nor to the current turn
+        assert!(history.branch(7).is_err());
+        assert!(history.branch(9).is_err());
+        assert_eq!(history.branch(0).unwrap(), Branch { from: 7, to: 0 });
+        assert_eq!(history.len(), 0);
+    }
+
+    #[test]
+    fn seed_prompt_replays_only_kept_turns() {
+        let mut history = history(4);
+        history.branch(2).unwrap();
+        let seed = history.seed_prompt();
+        assert!(seed.starts_with(BRANCH_CONTEXT));
+        assert!(seed.contains("Turn 1\nUser: question 1\nYou: answer 1\n"));
+        assert!(seed.contains("Turn 2\nUser: question 2\nYou: answer 2\n"));
+        assert!(!seed.contains("question 3"));
+        assert!(seed.ends_with(BRANCH_CONTEXT_END));
+    }
+
+    #[test]
+    fn disregarded_turn_is_not_counted() {
+        let mut history = history(3);
+        history.drop_last();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.last_reply(), Some("answer 2"));
+    }
+
+    #[test]
+    fn summary_lists_rewinds() {
+        let mut history = history(5);
+        assert_eq!(history.summary_section(), None);
+        history.branch(3).unwrap();
+        history.record("again", "reply");
+        history.branch(1).unwrap();
+        assert_eq!(
+            history.summary_section().unwrap(),
+            "### Rewinds\n- At turn 5, rewound to turn 3\n- At turn 4, rewound to turn 1\n"
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Whether the tutor gives `[FEEDBACK]` corrections during a stage.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackPolicy {
+    #[default]
+    On,
+    Off,
+}
+
+/// One stage of a lesson plan (warm-up, drill, role-play, review...).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Stage {
+    pub name: String,
+    /// Stage instructions for the tutor. Filled from `agent_file` when loading.
+    #[serde(default)]
+    pub agent: String,
+    /// Path to a file holding the stage instructions, relative to the plan file.
+    #[serde(default)]
+    agent_file: Option<String>,
+    /// Number of user turns before moving on. `None` = open-ended (last stage only).
+    #[serde(default)]
+    pub turns: Option<u32>,
+    #[serde(default)]
+    pub feedback: FeedbackPolicy,
+    /// TTS speed applied when the stage starts (same scale as `[SPEED:X.X]`).
+    #[serde(default)]
+    pub speed: Option<f32>,
+}
+
+/// A structured lesson loaded with `--lesson <path>`.
+///
+/// ```toml
+/// title = "Past tenses"
+///
+/// [[stage]]
+/// name = "Warm-up"
+/// turns = 5
+/// agent = "Chat casually about the user's weekend."
+///
+/// [[stage]]
+/// name = "Review"
+/// agent_file = "review.md"
+/// feedback = "off"
+/// speed = 0.6
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LessonPlan {
+    pub title: String,
+    #[serde(rename = "stage")]
+    pub stages: Vec<Stage>,
+}
+
+impl LessonPlan {
+    /// Load and validate a plan, resolving `agent_file` next to the plan file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("reading lesson plan {}", path.display()))?;
+        let mut plan =
+            Self::parse(&content).with_context(|| format!("parsing {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        for stage in &mut plan.stages {
+            if let Some(file) = stage.agent_file.take() {
+                let file = base.join(file);
+                stage.agent = std::fs::read_to_string(&file)
+                    .with_context(|| format!("reading agent file for stage '{}'", stage.name))?
+                    .trim()
+                    .to_string();
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Parse a plan from TOML. `agent_file` entries are left unresolved.
+    pub fn parse(content: &str) -> Result<Self> {
+        let plan: Self = toml::from_str(content)?;
+        if plan.stages.is_empty() {
+            bail!("lesson plan has no [[stage]] entries");
+        }
+        let last = plan.stages.len() - 1;
+        for (i, stage) in plan.stages.iter().enumerate() {
+            match (stage.agent.is_empty(), &stage.agent_file) {
+                (true, None) => bail!("stage '{}' needs `agent` or `agent_file`", stage.name),
+                (false, Some(_)) => {
+                    bail!("stage '{}' sets both `agent` and `agent_file`", stage.name)
+                }
+                _ => {}
+            }
+            match stage.turns {
+                Some(0) => bail!("stage '{}' has a turn budget of 0", stage.name),
+                None if i != last => {
+                    bail!(
+                        "stage '{}' needs a `turns` budget (only the last stage may be open-ended)",
+                        stage.name
+                    )
+                }
+                _ => {}
+            }
+        }
+        Ok(plan)
+    }
+}
+
+/// Per-stage counters reported in the session summary.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StageStats {
+    pub turns: u32,
+    pub corrections: u32,
+    pub retries: u32,
+}
+
+/// Tracks where the session is within a lesson plan.
+pub struct LessonProgress {
+    plan: LessonPlan,
+    current: usize,
+    /// Completed turns in the current stage
+    stage_turns: u32,
+    /// Set when the current stage's instructions still have to be sent to the LLM
+    announce_pending: bool,
+    stats: Vec<StageStats>,
+}
+
+impl LessonProgress {
+    pub fn new(plan: LessonPlan) -> Self {
+        let stats = vec![StageStats::default(); plan.stages.len()];
+        Self {
+            plan,
+            current: 0,
+            stage_turns: 0,
+            announce_pending: true,
+            stats,
+        }
+    }
+
+    pub fn stage(&self) -> &Stage {
+        &self.plan.stages[self.current]
+    }
+
+    /// Client-facing label for the current stage, e.g. "Stage 2/3: Grammar drill".
+    pub fn status_line(&self) -> String {
+        format!(
+            "Stage {}/{}: {}",
+            self.current + 1,
+            self.plan.stages.len(),
+            self.stage().name
+        )
+    }
+
+    /// Prompt prefix for the next LLM query: the stage instructions on the first
+    /// turn of a stage, nothing otherwise. Stays pending until [`Self::stage_announced`].
+    pub fn pending_stage_prompt(&self) -> Option<String> {
+        if !self.announce_pending {
+            return None;
+        }
+        let previous = self.current.checked_sub(1).map(|i| &self.plan.stages[i]);
+        Some(transition_prompt(
+            &self.plan.title,
+            previous,
+            self.stage(),
+            self.current,
+            self.plan.stages.len(),
+        ))
+    }
+
+    /// The LLM received the stage instructions.
+    pub fn stage_announced(&mut self) {
+        self.announce_pending = false;
+    }
+
+    /// Count a completed turn. Returns true when this moved the lesson to the next stage.
+    pub fn record_turn(&mut self, had_feedback: bool) -> bool {
+        let stats = &mut self.stats[self.current];
+        stats.turns += 1;
+        if had_feedback {
+            stats.corrections += 1;
+        }
+        self.stage_turns += 1;
+
+        let budget_spent = self.stage().turns.is_some_and(|t| self.stage_turns >= t);
+        if budget_spent && self.current + 1 < self.plan.stages.len() {
+            self.current += 1;
+            self.stage_turns = 0;
+            self.announce_pending = true;
+            return true;
+        }
+        false
+    }
+
+    /// Count a turn the user chose to redo after feedback.
+    pub fn record_retry(&mut self) {
+        self.stats[self.current].retries += 1;
+    }
+
+    /// Markdown section appended to the session summary.
+    pub fn summary_section(&self) -> String {
+        let mut out = format!("### Lesson Plan — {}\n", self.plan.title);
+        for (i, (stage, stats)) in self.plan.stages.iter().zip(&self.stats).enumerate() {
+            let budget = stage
+                .turns
+                .map(|t| format!("{}/{t}", stats.turns))
+                .unwrap_or_else(|| stats.turns.to_string());
+            let state = if i < self.current {
+                "done"
+            } else if i == self.current {
+                "current"
+            } else {
+                "not reached"
+            };
+            out.push_str(&format!(
+                "- **{}** ({state}): {budget} turns, {} corrections, {} retries\n",
+                stage.name, stats.corrections, stats.retries
+            ));
+        }
+        out
+    }
+
+    /// One-line outline of the plan for the summary prompt.
+    pub fn outline(&self) -> String {
+        let names: Vec<&str> = self.plan.stages.iter().map(|s| s.name.as_str()).collect();
+        format!(
+            "This session followed the lesson plan \"{}\" with stages: {}.",
+            self.plan.title,
+            names.join(" → ")
+        )
+    }
+}
+
+/// Build the instruction block that starts a stage.
+pub fn transition_prompt(
+    title: &str,
+    previous: Option<&Stage>,
+    next: &Stage,
+    index: usize,
+    total: usize,
+) -> String {
+    let mut out = match previous {
+        None => format!(
+            "[LESSON \"{title}\" — stage {}/{total}: {}. ",
+            index + 1,
+            next.name
+        ),
+        Some(prev) => format!(
+            "[LESSON \"{title}\" — the {} stage is finished. Now switch to stage {}/{total}, {}: ",
+            prev.name,
+            index + 1,
+            next.name
+        ),
+    };
+    out.push_str(next.agent.trim());
+    if let Some(turns) = next.turns {
+        out.push_str(&format!(" This stage lasts about {turns} turns."));
+    }
+    if next.feedback == FeedbackPolicy::Off {
+        out.push_str(" Do NOT give [FEEDBACK] blocks during this stage.");
+    }
+    out.push_str("]\n\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = r#"
+title = "Past tenses"
+
+[[stage]]
+name = "Warm-up"
+turns = 2
+agent = "Chat about the weekend."
+
+[[stage]]
+name = "Drill"
+turns = 1
+agent = "Drill irregular past forms."
+speed = 0.6
+
+[[stage]]
+name = "Review"
+agent = "Review the mistakes."
+feedback = "off"
+"#;
+
+    #[test]
+    fn parse_three_stage_plan() {
+        let plan = LessonPlan::parse(PLAN).unwrap();
+        assert_eq!(plan.title, "Past tenses");
+        assert_eq!(plan.stages.len(), 3);
+        assert_eq!(plan.stages[0].turns, Some(2));
+        assert_eq!(plan.stages[0].feedback, FeedbackPolicy::On);
+        assert_eq!(plan.stages[1].speed, Some(0.6));
+        assert_eq!(plan.stages[2].turns, None);
+        assert_eq!(plan.stages[2].feedback, FeedbackPolicy::Off);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_plans() {
+        assert!(LessonPlan::parse("title = \"x\"\nstage = []").is_err());
+        // Open-ended stage before the last one
+        let open_middle = "title = \"x\"\n[[stage]]\nname = \"a\"\nagent = \"a\"\n[[stage]]\nname = \"b\"\nagent = \"b\"\nturns = 1\n";
+        assert!(LessonPlan::parse(open_middle).is_err());
+        // Missing instructions
+        assert!(LessonPlan::parse("title = \"x\"\n[[stage]]\nname = \"a\"\n").is_err());
+        // Unknown option
+        assert!(
+            LessonPlan::parse("title = \"x\"\n[[stage]]\nname = \"a\"\nagent = \"a\"\nloud = 1\n")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn load_resolves_agent_file_relative_to_plan() {
+        let dir = std::env::temp_dir().join(format!("space_lt_lesson_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("roleplay.md"), "You are a waiter.\n").unwrap();
+        std::fs::write(
+            dir.join("plan.toml"),
+            "title = \"Cafe\"\n[[stage]]\nname = \"Role-play\"\nagent_file = \"roleplay.md\"\n",
+        )
+        .unwrap();
+
+        let plan = LessonPlan::load(&dir.join("plan.toml")).unwrap();
+        assert_eq!(plan.stages[0].agent, "You are a waiter.");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn progress_advances_through_stages() {
+        let mut progress = LessonProgress::new(LessonPlan::parse(PLAN).unwrap());
+        assert_eq!(progress.status_line(), "Stage 1/3: Warm-up");
+
+        let first = progress.pending_stage_prompt().unwrap();
+        assert!(first.contains("stage 1/3: Warm-up"));
+        assert!(first.contains("Chat about the weekend."));
+        // Still pending until the query goes through
+        assert_eq!(progress.pending_stage_prompt(), Some(first));
+        progress.stage_announced();
+        assert!(progress.pending_stage_prompt().is_none());
+
+        assert!(!progress.record_turn(false));
+        assert!(progress.record_turn(true));
+        assert_eq!(progress.status_line(), "Stage 2/3: Drill");
+        let switch = progress.pending_stage_prompt().unwrap();
+        assert!(switch.contains("the Warm-up stage is finished"));
+        assert!(switch.contains("Drill irregular past forms."));
+
+        progress.record_retry();
+        assert!(progress.record_turn(false));
+        assert_eq!(progress.stage().name, "Review");
+        assert!(
+            progress
+                .pending_stage_prompt()
+                .unwrap()
+                .contains("Do NOT give [FEEDBACK]")
+        );
+
+        // Last stage is open-ended: never advances
+        for _ in 0..10 {
+            assert!(!progress.record_turn(false));
+        }
+        assert_eq!(progress.stats[0].corrections, 1);
+        assert_eq!(progress.stats[1].retries, 1);
+        assert_eq!(progress.stats[2].turns, 10);
+    }
+
+    #[test]
+    fn summary_section_lists_stage_stats() {
+        let mut progress = LessonProgress::new(LessonPlan::parse(PLAN).unwrap());
+        progress.record_turn(true);
+        let summary = progress.summary_section();
+        assert!(summary.contains("### Lesson Plan — Past tenses"));
+        assert!(summary.contains("**Warm-up** (current): 1/2 turns, 1 corrections, 0 retries"));
+        assert!(summary.contains("**Review** (not reached): 0 turns"));
+        assert!(progress.outline().contains("Warm-up → Drill → Review"));
+    }
+}
@@ -1,5 +1,9 @@
+mod ab_test;
 mod claude;
 mod connection;
+mod history;
+mod lesson;
+mod quiz;
 mod voice_loop;
 
 use anyhow::Result;
@@ -7,8 +11,8 @@ use std::net::Shutdown;
 
 use claude::{ClaudeCliBackend, LlmBackend, MockLlmBackend};
 use connection::OrchestratorConnection;
-use space_lt_common::info;
 use space_lt_common::protocol::{OrchestratorMsg, write_orchestrator_msg};
+use space_lt_common::{info, profile, warn};
 
 const DEFAULT_SOCKET_PATH: &str = "/tmp/space_lt_server.sock";
 
@@ -19,6 +23,18 @@ fn find_arg_value(args: &[String], flag: &str) -> Option<String> {
         .cloned()
 }
 
+fn make_backend(use_mock: bool, session_dir: std::path::PathBuf) -> Result<Box<dyn LlmBackend>> {
+    if use_mock {
+        return Ok(Box::new(MockLlmBackend::new(vec![
+            "Hello! I'm your English tutor. What would you like to practice today?".to_string(),
+            "That's great! Let's keep going. Can you tell me more?".to_string(),
+            "Excellent work! Your English is improving. Let's try another topic.".to_string(),
+        ])));
+    }
+    std::fs::create_dir_all(&session_dir)?;
+    Ok(Box::new(ClaudeCliBackend::new(session_dir)))
+}
+
 fn main() -> Result<()> {
     let args: Vec<String> = std::env::args().collect();
 
@@ -26,9 +42,33 @@ fn main() -> Result<()> {
         space_lt_common::log::set_debug(true);
     }
 
-    let agent_file = find_arg_value(&args, "--agent").ok_or_else(|| {
+    // --trace-protocol: log every message sent or received
+    if args.iter().any(|a| a == "--trace-protocol") {
+        space_lt_common::trace::enable();
+    }
+
+    // --profile: collect per-stage timings and print them on exit
+    let profile_json = find_arg_value(&args, "--profile-json");
+    let profiling = args.iter().any(|a| a == "--profile") || profile_json.is_some();
+    profile::set_enabled(profiling);
+
+    let result = run(&args);
+    if profiling && let Err(e) = profile::dump(profile_json.as_deref().map(std::path::Path::new)) {
+        warn!("Could not write profile: {e:#}");
+    }
+    result
+}
+
+fn run(args: &[String]) -> Result<()> {
+    // --dump-protocol: print the wire format description (JSON) and exit
+    if args.iter().any(|a| a == "--dump-protocol") {
+        print!("{}", space_lt_common::protocol::describe_protocol_json());
+        return Ok(());
+    }
+
+    let agent_file = find_arg_value(args, "--agent").ok_or_else(|| {
         anyhow::anyhow!(
-            "Usage: space_lt_orchestrator --agent <path> [--socket <path>] [--session-dir <path>] [--mock] [--debug]"
+            "Usage: space_lt_orchestrator --agent <path> [--socket <path>] [--session-dir <path>] [--lesson <plan.toml>] [--max-prompt-chars <n>] [--native-language <lang>] [--agent-b <path>] [--ab-order <alternate|random>] [--ab-seed <n>] [--mock] [--debug] [--profile] [--profile-json <path>] [--trace-protocol]"
         )
     })?;
     let agent_path = std::path::PathBuf::from(&agent_file);
@@ -38,9 +78,9 @@ fn main() -> Result<()> {
     }
 
     let socket_path =
-        find_arg_value(&args, "--socket").unwrap_or_else(|| DEFAULT_SOCKET_PATH.to_string());
+        find_arg_value(args, "--socket").unwrap_or_else(|| DEFAULT_SOCKET_PATH.to_string());
 
-    let session_dir = match find_arg_value(&args, "--session-dir") {
+    let session_dir = match find_arg_value(args, "--session-dir") {
         Some(dir) => {
             let p = std::path::PathBuf::from(&dir);
             std::fs::create_dir_all(&p)?;
@@ -59,6 +99,53 @@ fn main() -> Result<()> {
 
     let use_mock = args.iter().any(|a| a == "--mock");
 
+    // Optional lesson plan: staged instructions on top of the agent file
+    let lesson = match find_arg_value(args, "--lesson") {
+        Some(path) => {
+            let plan = lesson::LessonPlan::load(std::path::Path::new(&path))?;
+            info!(
+                "[orchestrator] Lesson plan: {} ({} stages)",
+                plan.title,
+                plan.stages.len()
+            );
+            Some(plan)
+        }
+        None => None,
+    };
+
+    // Cap on each turn's prompt; long turns are cut in the middle
+    let max_prompt_chars: usize = find_arg_value(args, "--max-prompt-chars")
+        .map(|s| s.parse())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --max-prompt-chars value: {e}"))?
+        .unwrap_or(voice_loop::DEFAULT_MAX_PROMPT_CHARS);
+
+    // Language the 'l' key translates replies into (e.g. "French")
+    let native_language = find_arg_value(args, "--native-language");
+
+    // A/B test: a second agent prompt answers every other (or a random) turn
+    let agent_b = match find_arg_value(args, "--agent-b") {
+        Some(path) => {
+            let p = std::path::PathBuf::from(&path);
+            if !p.exists() {
+                anyhow::bail!("Agent B file not found: {path}");
+            }
+            Some(p)
+        }
+        None => None,
+    };
+    let ab_seed: Option<u64> = find_arg_value(args, "--ab-seed")
+        .map(|s| s.parse())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --ab-seed value: {e}"))?;
+    let ab_order = ab_test::AbOrder::parse(
+        find_arg_value(args, "--ab-order")
+            .as_deref()
+            .unwrap_or("alternate"),
+        ab_seed,
+    )
+    .map_err(|e| anyhow::anyhow!("Invalid --ab-order value: {e}"))?;
+
     // Build config JSON before session_dir is moved
     let config_json = format!(
         r#"{{"agent_file": "{}", "session_dir": "{}"}}"#,
@@ -66,17 +153,28 @@ fn main() -> Result<()> {
         session_dir.display()
     );
 
-    let backend: Box<dyn LlmBackend> = if use_mock {
+    if use_mock {
         info!("[orchestrator] Using mock backend");
-        Box::new(MockLlmBackend::new(vec![
-            "Hello! I'm your English tutor. What would you like to practice today?".to_string(),
-            "That's great! Let's keep going. Can you tell me more?".to_string(),
-            "Excellent work! Your English is improving. Let's try another topic.".to_string(),
-        ]))
     } else {
         info!("[orchestrator] Using Claude CLI backend");
         info!("[orchestrator] Session dir: {}", session_dir.display());
-        Box::new(ClaudeCliBackend::new(session_dir))
+    }
+    // Each variant of an A/B test keeps its own Claude CLI conversation
+    let (backend, ab) = match agent_b {
+        Some(agent_b) => {
+            info!(
+                "[orchestrator] A/B test: {} vs {} ({ab_order})",
+                agent_path.display(),
+                agent_b.display()
+            );
+            let backend_a = make_backend(use_mock, session_dir.join("variant-a"))?;
+            let backend_b = make_backend(use_mock, session_dir.join("variant-b"))?;
+            (
+                backend_a,
+                Some(ab_test::AbTest::new(backend_b, agent_b, ab_order)),
+            )
+        }
+        None => (make_backend(use_mock, session_dir)?, None),
     };
 
     // Connect to server via Unix socket
@@ -95,7 +193,16 @@ fn main() -> Result<()> {
 
     // Run voice loop
     let (mut reader, mut writer) = conn.into_split();
-    voice_loop::run_voice_loop(&mut reader, &mut writer, backend.as_ref(), &agent_path)?;
+    voice_loop::run_voice_loop(
+        &mut reader,
+        &mut writer,
+        backend.as_ref(),
+        &agent_path,
+        lesson,
+        max_prompt_chars,
+        native_language.as_deref(),
+        ab,
+    )?;
 
     // Attempt to send SessionEnd on exit (succeeds on normal exit; on Ctrl+C the
     // stream is already closed so this will fail — server detects disconnect instead)
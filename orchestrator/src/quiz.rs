@@ -0,0 +1,262 @@
+/// Opens a listening passage: a reply that may run longer than the usual
+/// 1-3 sentences.
+const PASSAGE_OPEN: &str = "[PASSAGE]";
+const PASSAGE_CLOSE: &str = "[/PASSAGE]";
+
+/// Ends a reply that asks a comprehension question.
+const AWAIT_ANSWER: &str = "[AWAIT_ANSWER]";
+
+/// Opens a reply judging the user's answer.
+const RIGHT: &str = "[RIGHT]";
+const WRONG: &str = "[WRONG]";
+
+/// Tags a passage in the conversation history, so a replay after a rewind
+/// shows which reply was the text the questions were about.
+const PASSAGE_TAG: &str = "[Listening passage]";
+
+/// The tutor's judgement of a comprehension answer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Right,
+    Wrong,
+}
+
+/// The quiz markers of a reply, and the reply without them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuizMarkers {
+    /// The reply as it is spoken, markers removed.
+    pub spoken: String,
+    /// The reply read a passage.
+    pub passage: bool,
+    /// The reply asks a question and waits for the answer.
+    pub awaits_answer: bool,
+    /// The reply judges the previous answer.
+    pub verdict: Option<Verdict>,
+}
+
+/// Find and remove the quiz markers of a reply. A leading `[SPEED:x]` stays
+/// in front, where the TTS looks for it.
+pub fn parse_markers(spoken: &str) -> QuizMarkers {
+    let mut text = spoken.trim().to_string();
+    let mut take = |marker: &str| {
+        let Some((before, after)) = text.split_once(marker) else {
+            return false;
+        };
+        let (before, after) = (before.trim_end(), after.trim_start());
+        let gap = if before.is_empty() || after.is_empty() {
+            ""
+        } else {
+            " "
+        };
+        text = format!("{before}{gap}{after}");
+        true
+    };
+    let passage = take(PASSAGE_OPEN);
+    take(PASSAGE_CLOSE);
+    let awaits_answer = take(AWAIT_ANSWER);
+    let verdict = if take(RIGHT) {
+        Some(Verdict::Right)
+    } else if take(WRONG) {
+        Some(Verdict::Wrong)
+    } else {
+        None
+    };
+    QuizMarkers {
+        spoken: text,
+        passage,
+        awaits_answer,
+        verdict,
+    }
+}
+
+/// A reply as the conversation history records it: a passage is tagged.
+pub fn history_reply(markers: &QuizMarkers, heard: &str) -> String {
+    if markers.passage {
+        format!("{PASSAGE_TAG} {heard}")
+    } else {
+        heard.to_string()
+    }
+}
+
+/// The result of a finished quiz.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuizScore {
+    pub right: u32,
+    /// Answers the tutor judged.
+    pub judged: u32,
+}
+
+impl QuizScore {
+    /// Feedback line reporting the score.
+    pub fn feedback_line(&self) -> String {
+        format!(
+            "BLUE: Listening quiz: {}/{} answers right",
+            self.right, self.judged
+        )
+    }
+}
+
+/// A listening quiz in progress: the passage was read, questions follow one
+/// at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Quiz {
+    /// Questions asked so far.
+    pub asked: u32,
+    judged: u32,
+    right: u32,
+    /// The last question still waits for its answer.
+    awaiting: bool,
+}
+
+/// The quiz after a reply (`None` when there is none), and its score when
+/// the reply ended it.
+///
+/// A passage starts a new quiz. While a question waits for its answer, the
+/// reply's verdict is counted. The first reply after the questions that asks
+/// nothing more ends the quiz.
+pub fn advance(quiz: Option<Quiz>, markers: &QuizMarkers) -> (Option<Quiz>, Option<QuizScore>) {
+    let mut quiz = match (quiz, markers.passage) {
+        (_, true) => Quiz::default(),
+        (Some(quiz), false) => quiz,
+        (None, false) => return (None, None),
+    };
+    if quiz.awaiting
+        && let Some(verdict) = markers.verdict
+    {
+        quiz.judged += 1;
+        if verdict == Verdict::Right {
+            quiz.right += 1;
+        }
+    }
+    quiz.awaiting = markers.awaits_answer;
+    if quiz.awaiting {
+        quiz.asked += 1;
+        return (Some(quiz), None);
+    }
+    if quiz.asked == 0 {
+        // The passage was read; the questions have not started yet
+        return (Some(quiz), None);
+    }
+    let score = QuizScore {
+        right: quiz.right,
+        judged: quiz.judged,
+    };
+    (None, Some(score))
+}
+
+/// Add the score line to a reply's feedback.
+pub fn with_score(feedback: Option<String>, score: QuizScore) -> String {
+    match feedback {
+        Some(feedback) => format!("{feedback}\n{}", score.feedback_line()),
+        None => score.feedback_line(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run replies through the quiz, returning the final state and score.
+    fn run(replies: &[&str]) -> (Option<Quiz>, Option<QuizScore>) {
+        let mut quiz = None;
+        let mut score = None;
+        for reply in replies {
+            (quiz, score) = advance(quiz, &parse_markers(reply));
+        }
+        (quiz, score)
+    }
+
+    #[test]
+    fn markers_are_removed_and_speed_stays_in_front() {
+        let markers = parse_markers(
+            "[SPEED:0.6] [PASSAGE]Tom missed the bus.[/PASSAGE] Why was Tom late? [AWAIT_ANSWER]",
+        );
+        assert_eq!(
+            markers,
+            QuizMarkers {
+                spoken: "[SPEED:0.6] Tom missed the bus. Why was Tom late?".to_string(),
+                passage: true,
+                awaits_answer: true,
+                verdict: None,
+            }
+        );
+        let markers = parse_markers("[WRONG] Not quite, he missed the bus.");
+        assert_eq!(markers.spoken, "Not quite, he missed the bus.");
+        assert_eq!(markers.verdict, Some(Verdict::Wrong));
+        assert_eq!(parse_markers("Plain reply.").spoken, "Plain reply.");
+    }
+
+    #[test]
+    fn passage_then_questions_scores_the_judged_answers() {
+        let (quiz, score) = run(&[
+            "[PASSAGE]Tom missed the bus.[/PASSAGE] Why was Tom late? [AWAIT_ANSWER]",
+            "[RIGHT] Yes! Where was he going? [AWAIT_ANSWER]",
+            "[WRONG] He was going to work. What did he do next? [AWAIT_ANSWER]",
+            "[RIGHT] Exactly. Well done!",
+        ]);
+        assert_eq!(quiz, None);
+        assert_eq!(
+            score,
+            Some(QuizScore {
+                right: 2,
+                judged: 3
+            })
+        );
+        assert_eq!(
+            score.unwrap().feedback_line(),
+            "BLUE: Listening quiz: 2/3 answers right"
+        );
+    }
+
+    #[test]
+    fn questions_may_follow_the_passage_later() {
+        let (quiz, score) = run(&["[PASSAGE]A short story.[/PASSAGE]", "Ready for questions?"]);
+        assert_eq!(quiz, Some(Quiz::default()));
+        assert_eq!(score, None);
+    }
+
+    #[test]
+    fn verdicts_outside_a_quiz_are_ignored() {
+        assert_eq!(
+            run(&["[RIGHT] Good.", "Next? [AWAIT_ANSWER]"]),
+            (None, None)
+        );
+        // A verdict with no question waiting does not count
+        let (_, score) = run(&[
+            "[PASSAGE]Text.[/PASSAGE]",
+            "[RIGHT] First question? [AWAIT_ANSWER]",
+            "[WRONG] Thanks, that's all.",
+        ]);
+        assert_eq!(
+            score,
+            Some(QuizScore {
+                right: 0,
+                judged: 1
+            })
+        );
+    }
+
+    #[test]
+    fn score_is_appended_to_feedback() {
+        let score = QuizScore {
+            right: 1,
+            judged: 2,
+        };
+        assert_eq!(with_score(None, score), score.feedback_line());
+        assert_eq!(
+            with_score(Some("RED: x".to_string()), score),
+            format!("RED: x\n{}", score.feedback_line())
+        );
+    }
+
+    #[test]
+    fn history_tags_passages() {
+        let markers = parse_markers("[PASSAGE]Text.[/PASSAGE]");
+        assert_eq!(
+            history_reply(&markers, &markers.spoken),
+            "[Listening passage] Text."
+        );
+        let markers = parse_markers("Reply.");
+        assert_eq!(history_reply(&markers, &markers.spoken), "Reply.");
+    }
+}
@@ -4,16 +4,122 @@ use std::os::unix::net::UnixStream;
 use std::path::Path;
 
 use space_lt_common::protocol::{
-    OrchestratorMsg, ServerOrcMsg, is_disconnect, read_server_orc_msg, write_orchestrator_msg,
+    OrchestratorMsg, ServerOrcMsg, is_disconnect, read_server_orc_msg, split_language_tag,
+    write_orchestrator_msg,
 };
-use space_lt_common::{info, warn};
+use space_lt_common::{info, profile, warn};
 
+use crate::ab_test::{AbTest, AbTurn, Variant};
 use crate::claude::LlmBackend;
+use crate::history::ConversationHistory;
+use crate::lesson::{FeedbackPolicy, LessonPlan, LessonProgress};
+use crate::quiz::{self, Quiz};
 
 /// Short reminder prepended to every user prompt to reinforce voice output rules.
 /// On --continue turns, Claude may "forget" the system prompt's formatting rules,
 /// especially when using web search. This inline reminder keeps it on track.
-const FORMAT_REMINDER: &str = "[CRITICAL: Your response is spoken aloud by TTS. Write ONLY plain conversational sentences. No markdown, no formatting, no lists, no URLs, no sources. 1-3 sentences max. If you notice grammar errors or unnatural phrasing, prepend a [FEEDBACK] block. Inside the block, every line MUST start with RED:, BLUE:, or CORRECTED: — never write prose. Example:\n[FEEDBACK]\nRED: \"I have went\" → \"I went\" (past simple)\nCORRECTED: I <<went>> to the store.\n[/FEEDBACK]\nYour spoken reply here.\nIf the user asks to speak slower/faster/normal, you MUST prefix your response with [SPEED:X.X] (0.5=much slower, 0.6=slower, 0.8=normal, 1.0=faster). You DO control speech speed via this tag. Speed persists until changed — to return to normal, use [SPEED:0.8].]\n\n";
+const FORMAT_REMINDER: &str = "[CRITICAL: Your response is spoken aloud by TTS. Write ONLY plain conversational sentences. No markdown, no formatting, no lists, no URLs, no sources. 1-3 sentences max (only a listening passage inside [PASSAGE]...[/PASSAGE] may be longer). If you notice grammar errors or unnatural phrasing, prepend a [FEEDBACK] block. Inside the block, every line MUST start with RED:, BLUE:, or CORRECTED: — never write prose. Example:\n[FEEDBACK]\nRED: \"I have went\" → \"I went\" (past simple)\nCORRECTED: I <<went>> to the store.\n[/FEEDBACK]\nYour spoken reply here.\nIf the user asks to speak slower/faster, you MUST prefix your response with [SPEED_DEFAULT:X.X] (0.5=much slower, 0.6=slower, 0.8=normal, 1.0=faster): it applies to this and every later response. If they ask for the normal speed again, prefix it with [SPEED:reset]. To say only the current response at another speed, prefix it with [SPEED:X.X] instead. You DO control speech speed via these tags.]\n\n";
+
+/// Note prepended to the user's text when they rephrase after a correction.
+const RETRY_CONTEXT: &str = "[The user chose to rephrase their previous statement. Their new attempt follows. Do NOT comment on the correction or praise the grammar — just respond naturally to the content as if it were a normal conversational turn.]\n\n";
+
+/// Note prepended to the user's text after they disregarded their previous message.
+const DISREGARD_CONTEXT: &str = "[The user's previous message was not meant for you: they were talking to someone else in the room. Ignore that message and your reply to it, do not mention it, and continue the conversation from before it.]\n\n";
+
+/// Note prepended to a message the server heard in a language outside the
+/// practiced ones (`--languages`).
+fn other_language_note(language: &str) -> String {
+    format!(
+        "[The user said this in another language (detected: {language}) than the ones practiced in this session. Mention it briefly and invite them to say it in the practiced language.]\n\n"
+    )
+}
+
+/// Default cap on an assembled turn prompt, in characters (`--max-prompt-chars`).
+pub const DEFAULT_MAX_PROMPT_CHARS: usize = 8000;
+
+/// The user's text is never cut below this many characters, even when the
+/// reminder and instructions alone exceed the cap.
+const MIN_USER_CHARS: usize = 400;
+
+/// Tells the model that the middle of the user's turn was cut.
+const TRUNCATION_NOTE: &str = "[The user's turn was very long; its middle part was cut, marked with […]. Respond to what remains.]\n\n";
+
+/// Marks where the user's text was cut.
+const TRUNCATION_MARKER: &str = " […] ";
+
+/// One voice turn to send to the LLM.
+pub struct TurnPrompt<'a> {
+    /// Lesson stage instructions, on the first turn of a stage.
+    pub stage: Option<&'a str>,
+    /// The user is rephrasing after choosing "retry" on a correction.
+    pub retry: bool,
+    /// The user disregarded their previous message (it was an aside).
+    pub disregard: bool,
+    /// The language the user spoke, when the server flagged it as outside the
+    /// practiced ones.
+    pub other_language: Option<&'a str>,
+    /// What the user said.
+    pub text: &'a str,
+}
+
+/// Assemble the prompt of a voice turn.
+///
+/// In order: `FORMAT_REMINDER` (exactly once), the stage instructions, the
+/// disregard and retry notes (at most once each), the other-language note,
+/// then the user's text. When the result would
+/// exceed `max_chars`, the middle of the user's text is cut (keeping its start
+/// and end) and a note tells the model so. Copies of the reminder or notes
+/// already at the start of the text are dropped, so a prompt that went through
+/// the fallback path never snowballs.
+pub fn assemble_prompt(turn: &TurnPrompt, max_chars: usize) -> String {
+    let mut text = turn.text.trim();
+    loop {
+        let stripped = text
+            .strip_prefix(FORMAT_REMINDER.trim_end())
+            .or_else(|| text.strip_prefix(RETRY_CONTEXT.trim_end()))
+            .or_else(|| text.strip_prefix(DISREGARD_CONTEXT.trim_end()))
+            .map(str::trim_start);
+        match stripped {
+            Some(rest) => text = rest,
+            None => break,
+        }
+    }
+
+    let mut prefix = String::from(FORMAT_REMINDER);
+    if let Some(stage) = turn.stage {
+        prefix.push_str(stage);
+    }
+    if turn.disregard {
+        prefix.push_str(DISREGARD_CONTEXT);
+    }
+    if turn.retry {
+        prefix.push_str(RETRY_CONTEXT);
+    }
+    if let Some(language) = turn.other_language {
+        prefix.push_str(&other_language_note(language));
+    }
+
+    let prefix_chars = prefix.chars().count();
+    let text_chars = text.chars().count();
+    if prefix_chars + text_chars <= max_chars {
+        return prefix + text;
+    }
+
+    let budget = max_chars
+        .saturating_sub(prefix_chars + TRUNCATION_NOTE.chars().count())
+        .max(MIN_USER_CHARS);
+    if text_chars <= budget {
+        return prefix + text;
+    }
+    let keep = budget.saturating_sub(TRUNCATION_MARKER.chars().count());
+    let head: String = text.chars().take(keep - keep / 2).collect();
+    let tail: String = text.chars().skip(text_chars - keep / 2).collect();
+    format!(
+        "{prefix}{TRUNCATION_NOTE}{}{TRUNCATION_MARKER}{}",
+        head.trim_end(),
+        tail.trim_start()
+    )
+}
 
 /// Prompt for generating a structured session summary in markdown.
 /// Sent with continue_session=true so Claude has full conversation context.
@@ -41,6 +147,39 @@ If during the session any practical information about New Zealand was discussed
 
 Output ONLY the markdown content. No preamble, no closing remarks. Do not wrap the output in a code block."#;
 
+/// Context-free prompt for translating the last reply (`{lang}`, then `{text}`).
+/// Does NOT include FORMAT_REMINDER — the result is displayed, not spoken.
+const TRANSLATE_PROMPT: &str =
+    "Translate this to {lang}. Output ONLY the translation, no notes or quotes:\n\n{text}";
+
+/// Build the side-query prompt translating `text` into `language`.
+pub fn translate_prompt(language: &str, text: &str) -> String {
+    TRANSLATE_PROMPT
+        .replacen("{lang}", language, 1)
+        .replacen("{text}", text, 1)
+}
+
+/// Shown to the user once their last message is disregarded.
+const DISREGARDED_STATUS: &str = "Last message disregarded";
+
+/// Turn text sent when the user asks for a simpler version of the last reply.
+/// Goes through `assemble_prompt` like a spoken turn, in the tutor conversation.
+const SIMPLIFY_REQUEST: &str = "[The user did not fully understand your last reply. Rephrase your last reply in simpler words, with shorter sentences. Say the same thing, add nothing new, and do not give feedback.]";
+
+/// The reply text as heard by the user, without its leading speed and voice
+/// markers.
+fn spoken_text(response: &str) -> &str {
+    let mut text = response;
+    while let Some(rest) = ["[SPEED:", "[SPEED_DEFAULT:", "[VOICE:"]
+        .iter()
+        .find_map(|marker| text.strip_prefix(marker))
+        && let Some(end) = rest.find(']')
+    {
+        text = rest[end + 1..].trim_start();
+    }
+    text
+}
+
 /// Voice loop state (for logging).
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VoiceLoopState {
@@ -84,16 +223,74 @@ pub fn parse_feedback(text: String) -> (Option<String>, String) {
 
 /// Run the main voice loop: read transcriptions, query LLM, send responses.
 ///
+/// With a lesson plan, each stage's instructions are injected into the first
+/// prompt of the stage, the client is told about stage changes, and the summary
+/// gets per-stage stats.
+///
+/// A translate request is answered with a side query on the last reply, into
+/// `native_language`; it never touches the tutor conversation. A simplify
+/// request is the opposite: a continuation turn asking the tutor to rephrase
+/// its last reply, answered with a regular `ResponseText`.
+///
+/// When the user disregards their last message (an aside meant for someone
+/// else), a reply still waiting on a feedback choice is dropped, and the next
+/// turn tells the model to ignore that exchange.
+///
+/// A branch request rewinds the conversation to an earlier turn: later
+/// exchanges are forgotten, and the next query starts a fresh conversation
+/// (no `continue_session`, so the Claude CLI starts a new session) whose prompt
+/// replays the kept exchanges. The lesson plan does not rewind.
+///
+/// With an A/B test, each turn is answered by one of the two agent prompts,
+/// each in its own conversation, and the summary gets per-variant stats.
+/// Branching is refused then.
+///
+/// A reply reading a `[PASSAGE]` starts a listening quiz: the questions that
+/// follow end with `[AWAIT_ANSWER]`, the replies to the answers open with a
+/// `[RIGHT]` or `[WRONG]` verdict, and the first reply asking nothing more
+/// ends the quiz with its score added to that reply's feedback. The markers
+/// are never spoken.
+///
+/// A summary does not end the session: the user may abandon it and carry on,
+/// so the loop keeps going until the client leaves.
+///
 /// Blocks until the server disconnects or an unrecoverable error occurs.
+#[allow(clippy::too_many_arguments)]
 pub fn run_voice_loop(
     reader: &mut BufReader<UnixStream>,
     writer: &mut BufWriter<UnixStream>,
     backend: &dyn LlmBackend,
     agent_path: &Path,
+    lesson: Option<LessonPlan>,
+    max_prompt_chars: usize,
+    native_language: Option<&str>,
+    mut ab: Option<AbTest>,
 ) -> Result<()> {
     let mut turn_count: u32 = 0;
     let mut state = VoiceLoopState::WaitingForTranscription;
-    let mut retry_context: Option<String> = None;
+    // What the user last heard, for translate and simplify requests
+    let mut last_spoken: Option<String> = None;
+    // Which A/B variant gave the last reply, so a simplify request stays in its conversation
+    let mut last_variant: Option<Variant> = None;
+    // Set when the user chose to rephrase; cleared once a query succeeds
+    let mut retry_pending = false;
+    // Set when the user disregarded their last message; cleared once a query succeeds
+    let mut disregard_pending = false;
+    // Answered turns, replayed into a fresh conversation after a rewind
+    let mut history = ConversationHistory::new();
+    // Set by a rewind; cleared once the fresh conversation got its first query
+    let mut reseed = false;
+    // The listening quiz in progress, if any
+    let mut listening_quiz: Option<Quiz> = None;
+    let mut lesson = lesson.map(LessonProgress::new);
+
+    if let Some(progress) = &lesson {
+        info!("[orchestrator] Lesson {}", progress.status_line());
+        let _ = write_orchestrator_msg(
+            writer,
+            &OrchestratorMsg::StatusNotification(progress.status_line()),
+        );
+    }
 
     loop {
         // 1. Wait for transcribed text from server
@@ -116,36 +313,237 @@ pub fn run_voice_loop(
                 info!("[orchestrator] Unexpected Ready during voice loop");
                 continue;
             }
+            ServerOrcMsg::SessionEnd => {
+                info!("[orchestrator] Server shutting down");
+                break;
+            }
             ServerOrcMsg::FeedbackChoice(_) => {
                 warn!("[orchestrator] Unexpected FeedbackChoice outside feedback flow");
                 continue;
             }
+            ServerOrcMsg::TranslateRequest => {
+                let translation = match (native_language, &last_spoken) {
+                    (None, _) => {
+                        "Translation unavailable: no native language set (--native-language)"
+                            .to_string()
+                    }
+                    (Some(_), None) => "Nothing to translate yet".to_string(),
+                    (Some(language), Some(spoken)) => {
+                        info!("[orchestrator] Translating the last reply to {language}");
+                        let prompt = translate_prompt(language, spoken);
+                        match profile::time("llm_query", || backend.side_query(&prompt)) {
+                            Ok(t) => t,
+                            Err(e) => {
+                                warn!("[orchestrator] Translation failed: {e}");
+                                "Translation unavailable".to_string()
+                            }
+                        }
+                    }
+                };
+                write_orchestrator_msg(writer, &OrchestratorMsg::Translation(translation))?;
+                continue;
+            }
+            ServerOrcMsg::DisregardLast => {
+                if turn_count == 0 {
+                    info!("[orchestrator] Disregard requested before any message (ignoring)");
+                    let _ = write_orchestrator_msg(
+                        writer,
+                        &OrchestratorMsg::StatusNotification(
+                            "Nothing to disregard yet".to_string(),
+                        ),
+                    );
+                    continue;
+                }
+                // The model already saw it: the next turn asks it to ignore the exchange
+                info!("[orchestrator] User disregarded their last message");
+                disregard_pending = true;
+                last_spoken = None;
+                history.drop_last();
+                let _ = write_orchestrator_msg(
+                    writer,
+                    &OrchestratorMsg::StatusNotification(DISREGARDED_STATUS.to_string()),
+                );
+                continue;
+            }
+            ServerOrcMsg::CancelExchange => {
+                // Only sent for an answered turn, whose reply the server dropped:
+                // disregarded like an aside, without telling the client
+                info!("[orchestrator] User cancelled the last exchange");
+                disregard_pending = true;
+                last_spoken = None;
+                history.drop_last();
+                continue;
+            }
+            ServerOrcMsg::BranchTo(turn) => {
+                if ab.is_some() {
+                    info!("[orchestrator] Rewind requested during an A/B test (ignoring)");
+                    let _ = write_orchestrator_msg(
+                        writer,
+                        &OrchestratorMsg::StatusNotification(
+                            "Rewinding is not available during an A/B test".to_string(),
+                        ),
+                    );
+                    continue;
+                }
+                match history.branch(turn) {
+                    Ok(branch) => {
+                        info!(
+                            "[orchestrator] Branch point: rewound from turn {} to turn {}",
+                            branch.from, branch.to
+                        );
+                        reseed = true;
+                        turn_count = turn;
+                        retry_pending = false;
+                        disregard_pending = false;
+                        last_spoken = history.last_reply().map(str::to_string);
+                        last_variant = None;
+                        listening_quiz = None;
+                        write_orchestrator_msg(
+                            writer,
+                            &OrchestratorMsg::ResponseText(format!("Rewound to turn {turn}.")),
+                        )?;
+                    }
+                    Err(e) => {
+                        info!("[orchestrator] {e}");
+                        let _ = write_orchestrator_msg(
+                            writer,
+                            &OrchestratorMsg::StatusNotification(e.to_string()),
+                        );
+                    }
+                }
+                continue;
+            }
+            ServerOrcMsg::SimplifyRequest => {
+                if last_spoken.is_none() {
+                    info!("[orchestrator] Simplify requested before any reply (ignoring)");
+                    let _ = write_orchestrator_msg(
+                        writer,
+                        &OrchestratorMsg::StatusNotification("Nothing to simplify yet".to_string()),
+                    );
+                    continue;
+                }
+                info!("[orchestrator] Rephrasing the last reply more simply");
+                let _ = write_orchestrator_msg(
+                    writer,
+                    &OrchestratorMsg::StatusNotification("Simplifying...".to_string()),
+                );
+                let (turn_backend, turn_agent) = match (&ab, last_variant) {
+                    (Some(ab), Some(Variant::B)) => (ab.backend_b(), ab.agent_b()),
+                    _ => (backend, agent_path),
+                };
+                let mut prompt = assemble_prompt(
+                    &TurnPrompt {
+                        stage: None,
+                        retry: false,
+                        disregard: false,
+                        other_language: None,
+                        text: SIMPLIFY_REQUEST,
+                    },
+                    max_prompt_chars,
+                );
+                if reseed {
+                    prompt = format!("{}{prompt}", history.seed_prompt());
+                }
+                match profile::time("llm_query", || {
+                    turn_backend.query(&prompt, turn_agent, !reseed)
+                }) {
+                    Ok(response) => {
+                        reseed = false;
+                        // The request wasn't the user's own words: nothing to correct
+                        let (_, spoken) = parse_feedback(response);
+                        let spoken = quiz::parse_markers(&spoken).spoken;
+                        info!("[orchestrator] Simplified response: '{spoken}'");
+                        last_spoken = Some(spoken_text(&spoken).to_string());
+                        write_orchestrator_msg(writer, &OrchestratorMsg::ResponseText(spoken))?;
+                    }
+                    Err(e) => {
+                        warn!("[orchestrator] Simplify query failed: {e}");
+                        let _ = write_orchestrator_msg(
+                            writer,
+                            &OrchestratorMsg::StatusNotification(
+                                "Could not simplify the last reply".to_string(),
+                            ),
+                        );
+                    }
+                }
+                continue;
+            }
             ServerOrcMsg::SummaryRequest => {
                 info!("[orchestrator] Summary requested, querying LLM...");
                 let _ = write_orchestrator_msg(
                     writer,
                     &OrchestratorMsg::StatusNotification("Generating summary...".to_string()),
                 );
-                let summary = match backend.query(SUMMARY_PROMPT, agent_path, turn_count > 0) {
+                let mut prompt = match &lesson {
+                    Some(progress) => format!("{SUMMARY_PROMPT}\n\n{}", progress.outline()),
+                    None => SUMMARY_PROMPT.to_string(),
+                };
+                // Right after a rewind, the old conversation holds forgotten turns
+                if reseed && history.len() > 0 {
+                    prompt = format!("{}{prompt}", history.seed_prompt());
+                }
+                // With an A/B test, variant A's conversation writes the summary
+                let continue_summary = match &ab {
+                    Some(ab) => ab.has_context(Variant::A),
+                    None => turn_count > 0 && !reseed,
+                };
+                let mut summary = match backend.query(&prompt, agent_path, continue_summary) {
                     Ok(s) => s,
                     Err(e) => {
                         warn!("[orchestrator] Summary generation failed: {e}");
                         format!("## Session Summary\n\n*Summary generation failed: {e}*")
                     }
                 };
+                if let Some(progress) = &lesson {
+                    summary = format!("{}\n\n{}", summary.trim_end(), progress.summary_section());
+                }
+                if let Some(ab) = &ab {
+                    summary = format!(
+                        "{}\n\n{}",
+                        summary.trim_end(),
+                        ab.summary_section(agent_path)
+                    );
+                }
+                if let Some(rewinds) = history.summary_section() {
+                    summary = format!("{}\n\n{rewinds}", summary.trim_end());
+                }
                 info!("[orchestrator] Summary generated ({} bytes)", summary.len());
                 write_orchestrator_msg(writer, &OrchestratorMsg::SummaryResponse(summary))?;
-                break;
+                continue;
             }
         };
 
+        // A language outside the server's --languages arrives tagged
+        let (other_language, text) = match split_language_tag(&text) {
+            (Some(language), rest) => (Some(language.to_string()), rest.to_string()),
+            (None, _) => (None, text),
+        };
+        if let Some(language) = &other_language {
+            info!("[orchestrator] User spoke {language}, outside the practiced languages");
+        }
+
         // 2. Query LLM
         let prev_state = state;
         state = VoiceLoopState::QueryingLlm;
         info!("[orchestrator] State: {prev_state} → {state}");
 
         turn_count += 1;
-        info!("[orchestrator] Turn {turn_count}: received '{text}'");
+        let ab_turn = ab.as_mut().map(|ab| ab.start_turn(retry_pending));
+        let tag = ab_turn
+            .map(|t| format!(" [{}]", t.variant))
+            .unwrap_or_default();
+        info!("[orchestrator] Turn {turn_count}{tag}: received '{text}'");
+        let (turn_backend, turn_agent, continue_session) = match (&ab, ab_turn) {
+            (
+                Some(ab),
+                Some(AbTurn {
+                    variant: Variant::B,
+                    continue_session,
+                }),
+            ) => (ab.backend_b(), ab.agent_b(), continue_session),
+            (_, Some(t)) => (backend, agent_path, t.continue_session),
+            _ => (backend, agent_path, turn_count > 1 && !reseed),
+        };
 
         // Notify client that LLM is processing
         let _ = write_orchestrator_msg(
@@ -153,12 +551,23 @@ pub fn run_voice_loop(
             &OrchestratorMsg::StatusNotification("Thinking...".to_string()),
         );
 
-        // Prepend retry context if user chose to rephrase on previous turn
-        let augmented_prompt = if let Some(ctx) = retry_context.take() {
-            format!("{FORMAT_REMINDER}{ctx}{text}")
-        } else {
-            format!("{FORMAT_REMINDER}{text}")
-        };
+        // Add the lesson stage instructions (first turn of a stage) and the
+        // retry note if the user chose to rephrase on the previous turn
+        let stage_prompt = lesson.as_ref().and_then(|l| l.pending_stage_prompt());
+        let mut augmented_prompt = assemble_prompt(
+            &TurnPrompt {
+                stage: stage_prompt.as_deref(),
+                retry: retry_pending,
+                disregard: disregard_pending,
+                other_language: other_language.as_deref(),
+                text: &text,
+            },
+            max_prompt_chars,
+        );
+        // First turn after a rewind: replay the kept exchanges
+        if reseed && history.len() > 0 {
+            augmented_prompt = format!("{}{augmented_prompt}", history.seed_prompt());
+        }
         let query_start = std::time::Instant::now();
 
         // Run query in a scoped thread so we can forward status updates
@@ -166,7 +575,14 @@ pub fn run_voice_loop(
         let (status_tx, status_rx) = std::sync::mpsc::channel::<String>();
         let response = std::thread::scope(|s| -> Result<String> {
             let handle = s.spawn(|| {
-                backend.query_with_status(&augmented_prompt, agent_path, turn_count > 1, status_tx)
+                profile::time("llm_query", || {
+                    turn_backend.query_with_status(
+                        &augmented_prompt,
+                        turn_agent,
+                        continue_session,
+                        status_tx,
+                    )
+                })
             });
 
             // Drain status updates until sender is dropped (query finished)
@@ -183,11 +599,19 @@ pub fn run_voice_loop(
         });
 
         let response = match response {
-            Ok(r) => r,
+            Ok(r) => {
+                retry_pending = false;
+                disregard_pending = false;
+                reseed = false;
+                r
+            }
             Err(e) => {
+                // A pending retry note carries over to the next turn (still once)
                 warn!("[orchestrator] LLM query failed unexpectedly: {e}");
                 // Attempt to notify user via TTS
                 let fallback = "I'm sorry, something went wrong. Please try again.";
+                // Heard as a reply, so it counts as a turn (as on the client)
+                history.record(&text, fallback);
                 if let Err(send_err) = write_orchestrator_msg(
                     writer,
                     &OrchestratorMsg::ResponseText(fallback.to_string()),
@@ -203,19 +627,53 @@ pub fn run_voice_loop(
         // 3. Parse feedback and send response
         let prev_state = state;
         state = VoiceLoopState::WaitingForTts;
+        let llm_time = query_start.elapsed();
         info!(
-            "[orchestrator] LLM query: {:.2}s",
-            query_start.elapsed().as_secs_f64()
+            "[orchestrator] LLM query{tag}: {:.2}s",
+            llm_time.as_secs_f64()
         );
         info!("[orchestrator] State: {prev_state} → {state}");
 
-        let (feedback, spoken) = parse_feedback(response);
+        let (mut feedback, spoken) = parse_feedback(response);
+        // Quiz markers go before the lesson may put a speed in front
+        let markers = quiz::parse_markers(&spoken);
+        let mut spoken = markers.spoken.clone();
+
+        if let Some(progress) = &mut lesson {
+            if progress.stage().feedback == FeedbackPolicy::Off && feedback.take().is_some() {
+                info!("[orchestrator] Dropping feedback (disabled for this lesson stage)");
+            }
+            if stage_prompt.is_some() {
+                progress.stage_announced();
+                // Apply the stage speed unless the tutor already picked one
+                if let Some(speed) = progress.stage().speed
+                    && !spoken.starts_with("[SPEED:")
+                {
+                    spoken = format!("[SPEED:{speed}] {spoken}");
+                }
+            }
+        }
+        let had_feedback = feedback.is_some();
+
+        // The quiz moves on once the reply is heard (not on a retry)
+        let (next_quiz, score) = quiz::advance(listening_quiz, &markers);
+        if let Some(score) = score {
+            info!(
+                "[orchestrator] Listening quiz over: {}/{} answers right",
+                score.right, score.judged
+            );
+            feedback = Some(quiz::with_score(feedback, score));
+        }
 
         if let Some(fb) = feedback {
             info!("[orchestrator] Feedback detected, sending to client");
             write_orchestrator_msg(writer, &OrchestratorMsg::FeedbackText(fb))?;
 
-            // Wait for user's choice: continue or retry (ignore stray messages)
+            // Wait for user's choice: continue or retry (ignore stray messages).
+            // Disregarding the message drops the reply like a retry, and so
+            // does cancelling the exchange (the server dropped the feedback).
+            let mut disregarded = false;
+            let mut cancelled = false;
             let feedback_choice = loop {
                 let choice_msg = match read_server_orc_msg(reader) {
                     Ok(msg) => msg,
@@ -229,6 +687,19 @@ pub fn run_voice_loop(
                 };
                 match choice_msg {
                     ServerOrcMsg::FeedbackChoice(proceed) => break Some(proceed),
+                    ServerOrcMsg::SessionEnd => {
+                        info!("[orchestrator] Server shutting down");
+                        break None;
+                    }
+                    ServerOrcMsg::DisregardLast => {
+                        disregarded = true;
+                        break Some(false);
+                    }
+                    ServerOrcMsg::CancelExchange => {
+                        disregarded = true;
+                        cancelled = true;
+                        break Some(false);
+                    }
                     other => {
                         warn!(
                             "[orchestrator] Ignoring unexpected message while waiting for FeedbackChoice: {other:?}"
@@ -241,30 +712,72 @@ pub fn run_voice_loop(
             match feedback_choice {
                 Some(true) => {
                     info!("[orchestrator] User chose to continue");
-                    info!("[orchestrator] Response: '{spoken}'");
+                    info!("[orchestrator] Response{tag}: '{spoken}'");
+                    last_spoken = Some(spoken_text(&spoken).to_string());
+                    last_variant = ab_turn.map(|t| t.variant);
+                    history.record(&text, &quiz::history_reply(&markers, spoken_text(&spoken)));
+                    listening_quiz = next_quiz;
                     write_orchestrator_msg(writer, &OrchestratorMsg::ResponseText(spoken))?;
                 }
+                Some(false) if disregarded => {
+                    info!("[orchestrator] User disregarded their message — dropping response");
+                    disregard_pending = true;
+                    if !cancelled {
+                        let _ = write_orchestrator_msg(
+                            writer,
+                            &OrchestratorMsg::StatusNotification(DISREGARDED_STATUS.to_string()),
+                        );
+                    }
+                    state = VoiceLoopState::WaitingForTranscription;
+                    info!("[orchestrator] State: WaitingForTts → {state}");
+                    continue;
+                }
                 Some(false) => {
                     info!("[orchestrator] User chose to retry — skipping response");
-                    retry_context = Some(
-                        "[The user chose to rephrase their previous statement. Their new attempt follows. Do NOT comment on the correction or praise the grammar — just respond naturally to the content as if it were a normal conversational turn.]\n\n"
-                            .to_string(),
-                    );
+                    if let Some(progress) = &mut lesson {
+                        progress.record_retry();
+                    }
+                    if let (Some(ab), Some(t)) = (&mut ab, ab_turn) {
+                        ab.record_retry(t.variant);
+                    }
+                    retry_pending = true;
                     state = VoiceLoopState::WaitingForTranscription;
                     info!("[orchestrator] State: WaitingForTts → {state}");
                     continue;
                 }
                 None => {
-                    // Server disconnected
+                    // Server disconnected or shutting down
                     break;
                 }
             }
         } else {
-            info!("[orchestrator] Response: '{spoken}'");
+            info!("[orchestrator] Response{tag}: '{spoken}'");
+            last_spoken = Some(spoken_text(&spoken).to_string());
+            last_variant = ab_turn.map(|t| t.variant);
+            history.record(&text, &quiz::history_reply(&markers, spoken_text(&spoken)));
+            listening_quiz = next_quiz;
             write_orchestrator_msg(writer, &OrchestratorMsg::ResponseText(spoken))?;
         }
+        if markers.passage {
+            info!("[orchestrator] Listening passage read, quiz started");
+        }
+
+        if let (Some(ab), Some(t)) = (&mut ab, ab_turn) {
+            ab.record_turn(t.variant, had_feedback, llm_time);
+        }
 
-        // 4. Back to waiting
+        // 4. Advance the lesson plan
+        if let Some(progress) = &mut lesson
+            && progress.record_turn(had_feedback)
+        {
+            info!("[orchestrator] Lesson {}", progress.status_line());
+            let _ = write_orchestrator_msg(
+                writer,
+                &OrchestratorMsg::StatusNotification(progress.status_line()),
+            );
+        }
+
+        // 5. Back to waiting
         let prev_state = state;
         state = VoiceLoopState::WaitingForTranscription;
         info!("[orchestrator] State: {prev_state} → {state}");
@@ -292,6 +805,160 @@ mod tests {
         }
     }
 
+    // --- assemble_prompt tests ---
+
+    fn count(haystack: &str, needle: &str) -> usize {
+        haystack.matches(needle).count()
+    }
+
+    fn prompt(stage: Option<&str>, retry: bool, text: &str, max_chars: usize) -> String {
+        assemble_prompt(
+            &TurnPrompt {
+                stage,
+                retry,
+                disregard: false,
+                other_language: None,
+                text,
+            },
+            max_chars,
+        )
+    }
+
+    #[test]
+    fn assemble_prompt_plain_turn() {
+        let p = prompt(None, false, "Hello there", DEFAULT_MAX_PROMPT_CHARS);
+        assert_eq!(p, format!("{FORMAT_REMINDER}Hello there"));
+    }
+
+    #[test]
+    fn assemble_prompt_orders_stage_and_retry() {
+        let p = prompt(
+            Some("[Stage note]\n\n"),
+            true,
+            "I went",
+            DEFAULT_MAX_PROMPT_CHARS,
+        );
+        assert_eq!(
+            p,
+            format!("{FORMAT_REMINDER}[Stage note]\n\n{RETRY_CONTEXT}I went")
+        );
+
+        let p = prompt(None, true, "I went", DEFAULT_MAX_PROMPT_CHARS);
+        assert_eq!(p, format!("{FORMAT_REMINDER}{RETRY_CONTEXT}I went"));
+
+        let p = prompt(
+            Some("[Stage note]\n\n"),
+            false,
+            "I went",
+            DEFAULT_MAX_PROMPT_CHARS,
+        );
+        assert_eq!(p, format!("{FORMAT_REMINDER}[Stage note]\n\nI went"));
+    }
+
+    #[test]
+    fn assemble_prompt_mentions_another_language() {
+        let turn = TurnPrompt {
+            stage: None,
+            retry: true,
+            disregard: false,
+            other_language: Some("de"),
+            text: "Guten Tag",
+        };
+        let p = assemble_prompt(&turn, DEFAULT_MAX_PROMPT_CHARS);
+        assert_eq!(
+            p,
+            format!(
+                "{FORMAT_REMINDER}{RETRY_CONTEXT}{}Guten Tag",
+                other_language_note("de")
+            )
+        );
+        assert!(p.contains("(detected: de)"));
+    }
+
+    #[test]
+    fn assemble_prompt_puts_disregard_note_before_retry() {
+        let turn = TurnPrompt {
+            stage: Some("[Stage note]\n\n"),
+            retry: true,
+            disregard: true,
+            other_language: None,
+            text: "Where were we?",
+        };
+        let p = assemble_prompt(&turn, DEFAULT_MAX_PROMPT_CHARS);
+        assert_eq!(
+            p,
+            format!(
+                "{FORMAT_REMINDER}[Stage note]\n\n{DISREGARD_CONTEXT}{RETRY_CONTEXT}Where were we?"
+            )
+        );
+
+        // A copy already in the text is not repeated
+        let text = format!("{DISREGARD_CONTEXT}Where were we?");
+        let turn = TurnPrompt {
+            stage: None,
+            retry: false,
+            disregard: true,
+            other_language: None,
+            text: &text,
+        };
+        let p = assemble_prompt(&turn, DEFAULT_MAX_PROMPT_CHARS);
+        assert_eq!(count(&p, DISREGARD_CONTEXT), 1);
+    }
+
+    #[test]
+    fn assemble_prompt_never_repeats_reminder_or_retry_note() {
+        // Text that already carries them (e.g. re-sent after a fallback)
+        let text = format!("{FORMAT_REMINDER}{RETRY_CONTEXT}{FORMAT_REMINDER}I went");
+        let p = prompt(None, true, &text, DEFAULT_MAX_PROMPT_CHARS);
+        assert_eq!(count(&p, FORMAT_REMINDER), 1);
+        assert_eq!(count(&p, RETRY_CONTEXT), 1);
+        assert!(p.ends_with("I went"));
+
+        let p = prompt(None, false, &text, DEFAULT_MAX_PROMPT_CHARS);
+        assert_eq!(count(&p, RETRY_CONTEXT), 0);
+    }
+
+    #[test]
+    fn assemble_prompt_cuts_the_middle_of_long_input() {
+        let text = format!("START {}END", "blah ".repeat(5000));
+        let p = prompt(None, false, &text, DEFAULT_MAX_PROMPT_CHARS);
+        assert!(p.chars().count() <= DEFAULT_MAX_PROMPT_CHARS);
+        assert!(p.starts_with(FORMAT_REMINDER));
+        assert_eq!(count(&p, TRUNCATION_NOTE), 1);
+        assert_eq!(count(&p, TRUNCATION_MARKER), 1);
+        let user = p.split(TRUNCATION_NOTE).nth(1).unwrap();
+        assert!(user.starts_with("START blah"));
+        assert!(user.ends_with("blah END"));
+    }
+
+    #[test]
+    fn assemble_prompt_cuts_long_input_with_stage_and_retry() {
+        let stage = "[Stage: ".to_string() + &"x".repeat(1000) + "]\n\n";
+        let text = format!("début {} fin", "é".repeat(20_000));
+        let p = prompt(Some(&stage), true, &text, DEFAULT_MAX_PROMPT_CHARS);
+        assert!(p.chars().count() <= DEFAULT_MAX_PROMPT_CHARS);
+        assert!(p.starts_with(&format!(
+            "{FORMAT_REMINDER}{stage}{RETRY_CONTEXT}{TRUNCATION_NOTE}"
+        )));
+        assert!(p.ends_with(" fin"));
+        assert!(p.contains("début"));
+    }
+
+    #[test]
+    fn assemble_prompt_keeps_some_user_text_under_a_tiny_cap() {
+        let text = "word ".repeat(1000);
+        let p = prompt(None, true, &text, 10);
+        assert_eq!(count(&p, FORMAT_REMINDER), 1);
+        assert_eq!(count(&p, RETRY_CONTEXT), 1);
+        let user = p.split(TRUNCATION_NOTE).nth(1).unwrap();
+        assert!(user.chars().count() <= MIN_USER_CHARS);
+        assert!(user.chars().count() >= MIN_USER_CHARS - 10);
+
+        // Short text is left alone even when the fixed parts exceed the cap
+        let p = prompt(None, false, "Hi", 10);
+        assert_eq!(p, format!("{FORMAT_REMINDER}Hi"));
+    }
+
     // --- parse_feedback tests ---
 
     #[test]
@@ -386,7 +1053,16 @@ mod tests {
         let mut writer = BufWriter::new(orch_stream);
         let agent_path = PathBuf::from("agent.md");
 
-        let result = run_voice_loop(&mut reader, &mut writer, &backend, &agent_path);
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
         assert!(result.is_ok());
 
         server_handle.join().unwrap();
@@ -428,7 +1104,16 @@ mod tests {
         let mut writer = BufWriter::new(orch_stream);
         let agent_path = PathBuf::from("agent.md");
 
-        let result = run_voice_loop(&mut reader, &mut writer, &backend, &agent_path);
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
         assert!(result.is_ok());
 
         server_handle.join().unwrap();
@@ -475,7 +1160,16 @@ mod tests {
         let mut writer = BufWriter::new(orch_stream);
         let agent_path = PathBuf::from("agent.md");
 
-        let result = run_voice_loop(&mut reader, &mut writer, &backend, &agent_path);
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
         assert!(result.is_ok());
 
         server_handle.join().unwrap();
@@ -532,7 +1226,16 @@ mod tests {
         let mut writer = BufWriter::new(orch_stream);
         let agent_path = PathBuf::from("agent.md");
 
-        let result = run_voice_loop(&mut reader, &mut writer, &backend, &agent_path);
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
         assert!(result.is_ok());
 
         server_handle.join().unwrap();
@@ -583,7 +1286,16 @@ mod tests {
         let mut writer = BufWriter::new(orch_stream);
         let agent_path = PathBuf::from("agent.md");
 
-        let result = run_voice_loop(&mut reader, &mut writer, &backend, &agent_path);
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
         assert!(result.is_ok());
 
         server_handle.join().unwrap();
@@ -639,7 +1351,16 @@ mod tests {
         let mut writer = BufWriter::new(orch_stream);
         let agent_path = PathBuf::from("agent.md");
 
-        let result = run_voice_loop(&mut reader, &mut writer, &backend, &agent_path);
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
         assert!(result.is_ok());
 
         server_handle.join().unwrap();
@@ -676,7 +1397,16 @@ mod tests {
         let mut writer = BufWriter::new(orch_stream);
         let agent_path = PathBuf::from("agent.md");
 
-        let result = run_voice_loop(&mut reader, &mut writer, &backend, &agent_path);
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
         assert!(result.is_ok());
 
         server_handle.join().unwrap();
@@ -706,7 +1436,17 @@ mod tests {
             let backend = MockLlmBackend::new(vec!["Response one".to_string()]);
             let (mut reader, mut writer) = conn.into_split();
             let agent_path = PathBuf::from("agent.md");
-            run_voice_loop(&mut reader, &mut writer, &backend, &agent_path).unwrap();
+            run_voice_loop(
+                &mut reader,
+                &mut writer,
+                &backend,
+                &agent_path,
+                None,
+                DEFAULT_MAX_PROMPT_CHARS,
+                None,
+                None,
+            )
+            .unwrap();
         });
 
         // Server side
@@ -791,9 +1531,739 @@ mod tests {
         let mut writer = BufWriter::new(orch_stream);
         let agent_path = PathBuf::from("agent.md");
 
-        let result = run_voice_loop(&mut reader, &mut writer, &backend, &agent_path);
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
+        assert!(result.is_ok());
+
+        server_handle.join().unwrap();
+    }
+
+    #[test]
+    fn voice_loop_alternates_ab_variants() {
+        use crate::ab_test::{AbOrder, AbTest};
+
+        let (orch_stream, server_stream) = UnixStream::pair().unwrap();
+
+        let server_handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(server_stream.try_clone().unwrap());
+            let mut writer = BufWriter::new(server_stream);
+
+            let mut replies = Vec::new();
+            for text in ["One", "Two", "Three"] {
+                write_orchestrator_msg(&mut writer, &OrchestratorMsg::TranscribedText(text.into()))
+                    .unwrap();
+                match read_next_non_status(&mut reader) {
+                    OrchestratorMsg::ResponseText(t) => replies.push(t),
+                    other => panic!("Expected ResponseText, got {other:?}"),
+                }
+            }
+            assert_eq!(replies, ["A1", "B1", "A2"]);
+
+            write_orchestrator_msg(&mut writer, &OrchestratorMsg::SummaryRequest).unwrap();
+            match read_next_non_status(&mut reader) {
+                OrchestratorMsg::SummaryResponse(s) => {
+                    // Variant A's conversation writes the summary
+                    assert!(s.starts_with("A summary"), "{s}");
+                    assert!(s.contains("### A/B Test (alternate)"), "{s}");
+                    assert!(s.contains("- **A** (agent.md): 2 turns"), "{s}");
+                    assert!(s.contains("- **B** (agent_b.md): 1 turns"), "{s}");
+                }
+                other => panic!("Expected SummaryResponse, got {other:?}"),
+            }
+        });
+
+        let backend_a = MockLlmBackend::new(vec![
+            "A1".to_string(),
+            "A2".to_string(),
+            "A summary".to_string(),
+        ]);
+        let backend_b = MockLlmBackend::new(vec!["B1".to_string()]);
+        let ab = AbTest::new(
+            Box::new(backend_b),
+            PathBuf::from("agent_b.md"),
+            AbOrder::Alternate,
+        );
+        let mut reader = BufReader::new(orch_stream.try_clone().unwrap());
+        let mut writer = BufWriter::new(orch_stream);
+        let agent_path = PathBuf::from("agent.md");
+
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend_a,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            Some(ab),
+        );
+        assert!(result.is_ok());
+
+        server_handle.join().unwrap();
+    }
+
+    #[test]
+    fn voice_loop_drives_three_stage_lesson() {
+        use crate::claude::RecordingMockLlmBackend;
+
+        let plan = LessonPlan::parse(
+            r#"
+title = "Cafe"
+
+[[stage]]
+name = "Warm-up"
+turns = 1
+agent = "Small talk."
+
+[[stage]]
+name = "Drill"
+turns = 1
+agent = "Drill ordering phrases."
+feedback = "off"
+speed = 0.6
+
+[[stage]]
+name = "Review"
+agent = "Review the session."
+"#,
+        )
+        .unwrap();
+
+        let (orch_stream, server_stream) = UnixStream::pair().unwrap();
+
+        let server_handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(server_stream.try_clone().unwrap());
+            let mut writer = BufWriter::new(server_stream);
+            let mut statuses = Vec::new();
+            let mut next = |reader: &mut BufReader<UnixStream>| loop {
+                match read_orchestrator_msg(reader).unwrap() {
+                    OrchestratorMsg::StatusNotification(s) => statuses.push(s),
+                    other => return other,
+                }
+            };
+
+            let mut turn = |text: &str, reader: &mut BufReader<UnixStream>| {
+                write_orchestrator_msg(&mut writer, &OrchestratorMsg::TranscribedText(text.into()))
+                    .unwrap();
+                match next(reader) {
+                    OrchestratorMsg::ResponseText(t) => t,
+                    other => panic!("Expected ResponseText, got {other:?}"),
+                }
+            };
+
+            assert_eq!(turn("Hi", &mut reader), "Warm-up reply");
+            // Feedback is disabled in the drill and the stage speed is applied
+            assert_eq!(
+                turn("I want coffee", &mut reader),
+                "[SPEED:0.6] Drill reply"
+            );
+            assert_eq!(turn("Done", &mut reader), "Review reply");
+
+            write_orchestrator_msg(&mut writer, &OrchestratorMsg::SummaryRequest).unwrap();
+            let summary = match next(&mut reader) {
+                OrchestratorMsg::SummaryResponse(s) => s,
+                other => panic!("Expected SummaryResponse, got {other:?}"),
+            };
+            assert!(summary.starts_with("Mock summary"));
+            assert!(summary.contains("### Lesson Plan — Cafe"));
+            assert!(summary.contains("**Drill** (done): 1/1 turns, 0 corrections"));
+            assert!(summary.contains("**Review** (current): 1 turns"));
+
+            for stage in [
+                "Stage 1/3: Warm-up",
+                "Stage 2/3: Drill",
+                "Stage 3/3: Review",
+            ] {
+                assert!(statuses.iter().any(|s| s == stage), "missing {stage}");
+            }
+        });
+
+        let backend = RecordingMockLlmBackend::new(vec![
+            "Warm-up reply".to_string(),
+            "[FEEDBACK]\nRED: \"I want\" → \"I'd like\"\n[/FEEDBACK]\nDrill reply".to_string(),
+            "Review reply".to_string(),
+            "Mock summary".to_string(),
+        ]);
+        let mut reader = BufReader::new(orch_stream.try_clone().unwrap());
+        let mut writer = BufWriter::new(orch_stream);
+        let agent_path = PathBuf::from("agent.md");
+
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            Some(plan),
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
+        assert!(result.is_ok());
+        server_handle.join().unwrap();
+
+        let prompts = backend.prompts.lock().unwrap();
+        assert!(prompts[0].contains("stage 1/3: Warm-up. Small talk."));
+        assert!(prompts[1].contains("the Warm-up stage is finished"));
+        assert!(prompts[1].contains("Drill ordering phrases."));
+        assert!(prompts[1].contains("Do NOT give [FEEDBACK]"));
+        assert!(prompts[2].contains("stage 3/3, Review"));
+        assert!(prompts[3].contains("Warm-up → Drill → Review"));
+    }
+
+    /// Fails the queries whose response is `None`, recording every prompt.
+    struct FlakyRecordingBackend {
+        responses: Vec<Option<&'static str>>,
+        prompts: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl crate::claude::LlmBackend for FlakyRecordingBackend {
+        fn query(&self, prompt: &str, _: &std::path::Path, _: bool) -> Result<String> {
+            let mut prompts = self.prompts.lock().unwrap();
+            let response = self.responses[prompts.len()];
+            prompts.push(prompt.to_string());
+            response
+                .map(str::to_string)
+                .ok_or_else(|| anyhow::anyhow!("mock failure"))
+        }
+    }
+
+    #[test]
+    fn voice_loop_keeps_retry_note_once_across_a_failed_query() {
+        let (orch_stream, server_stream) = UnixStream::pair().unwrap();
+
+        let server_handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(server_stream.try_clone().unwrap());
+            let mut writer = BufWriter::new(server_stream);
+            let mut say = |text: &str, writer: &mut BufWriter<UnixStream>| {
+                write_orchestrator_msg(writer, &OrchestratorMsg::TranscribedText(text.into()))
+                    .unwrap();
+                read_next_non_status(&mut reader)
+            };
+
+            let msg = say("I have went", &mut writer);
+            assert!(matches!(msg, OrchestratorMsg::FeedbackText(_)));
+            write_orchestrator_msg(&mut writer, &OrchestratorMsg::FeedbackChoice(false)).unwrap();
+
+            // The rephrased turn hits an LLM failure and gets the fallback reply
+            match say("I went", &mut writer) {
+                OrchestratorMsg::ResponseText(t) => assert!(t.contains("something went wrong")),
+                other => panic!("Expected fallback ResponseText, got {other:?}"),
+            }
+            match say("I went", &mut writer) {
+                OrchestratorMsg::ResponseText(t) => assert_eq!(t, "Nice!"),
+                other => panic!("Expected ResponseText, got {other:?}"),
+            }
+            match say("And then?", &mut writer) {
+                OrchestratorMsg::ResponseText(t) => assert_eq!(t, "Go on."),
+                other => panic!("Expected ResponseText, got {other:?}"),
+            }
+        });
+
+        let backend = FlakyRecordingBackend {
+            responses: vec![
+                Some("[FEEDBACK]\nRED: \"I have went\" → \"I went\"\n[/FEEDBACK]\nOops"),
+                None,
+                Some("Nice!"),
+                Some("Go on."),
+            ],
+            prompts: Default::default(),
+        };
+        let mut reader = BufReader::new(orch_stream.try_clone().unwrap());
+        let mut writer = BufWriter::new(orch_stream);
+        let agent_path = PathBuf::from("agent.md");
+
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
+        assert!(result.is_ok());
+        server_handle.join().unwrap();
+
+        let prompts = backend.prompts.lock().unwrap();
+        let retry_notes: Vec<usize> = prompts.iter().map(|p| count(p, RETRY_CONTEXT)).collect();
+        assert_eq!(retry_notes, [0, 1, 1, 0]);
+        assert!(prompts.iter().all(|p| count(p, FORMAT_REMINDER) == 1));
+    }
+
+    #[test]
+    fn voice_loop_disregards_the_last_message() {
+        let (orch_stream, server_stream) = UnixStream::pair().unwrap();
+
+        let server_handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(server_stream.try_clone().unwrap());
+            let mut writer = BufWriter::new(server_stream);
+            let say =
+                |text: &str, writer: &mut BufWriter<UnixStream>, reader: &mut BufReader<_>| {
+                    write_orchestrator_msg(writer, &OrchestratorMsg::TranscribedText(text.into()))
+                        .unwrap();
+                    read_next_non_status(reader)
+                };
+            let disregard = |writer: &mut BufWriter<UnixStream>, reader: &mut BufReader<_>| {
+                write_orchestrator_msg(writer, &OrchestratorMsg::DisregardLast).unwrap();
+                match read_orchestrator_msg(reader).unwrap() {
+                    OrchestratorMsg::StatusNotification(s) => s,
+                    other => panic!("Expected StatusNotification, got {other:?}"),
+                }
+            };
+
+            let status = disregard(&mut writer, &mut reader);
+            assert_eq!(status, "Nothing to disregard yet");
+
+            // After the reply: the next turn carries the note
+            let msg = say("Honey, where are my keys?", &mut writer, &mut reader);
+            assert!(matches!(msg, OrchestratorMsg::ResponseText(_)));
+            assert_eq!(disregard(&mut writer, &mut reader), DISREGARDED_STATUS);
+
+            // While the reply waits on a feedback choice: it is never sent
+            let msg = say("I has finished, darling", &mut writer, &mut reader);
+            assert!(matches!(msg, OrchestratorMsg::FeedbackText(_)));
+            assert_eq!(disregard(&mut writer, &mut reader), DISREGARDED_STATUS);
+
+            match say("Sorry, where were we?", &mut writer, &mut reader) {
+                OrchestratorMsg::ResponseText(t) => assert_eq!(t, "Back to it."),
+                other => panic!("Expected ResponseText, got {other:?}"),
+            }
+            match say("And then?", &mut writer, &mut reader) {
+                OrchestratorMsg::ResponseText(t) => assert_eq!(t, "Go on."),
+                other => panic!("Expected ResponseText, got {other:?}"),
+            }
+        });
+
+        let backend = FlakyRecordingBackend {
+            responses: vec![
+                Some("In the kitchen?"),
+                Some("[FEEDBACK]\nRED: \"I has\" → \"I have\"\n[/FEEDBACK]\nWell done!"),
+                Some("Back to it."),
+                Some("Go on."),
+            ],
+            prompts: Default::default(),
+        };
+        let mut reader = BufReader::new(orch_stream.try_clone().unwrap());
+        let mut writer = BufWriter::new(orch_stream);
+        let agent_path = PathBuf::from("agent.md");
+
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
         assert!(result.is_ok());
+        server_handle.join().unwrap();
+
+        let prompts = backend.prompts.lock().unwrap();
+        let notes: Vec<usize> = prompts
+            .iter()
+            .map(|p| count(p, DISREGARD_CONTEXT))
+            .collect();
+        assert_eq!(notes, [0, 1, 1, 0]);
+        assert!(prompts.iter().all(|p| count(p, RETRY_CONTEXT) == 0));
+    }
+
+    /// Records tutor queries (with their continue flag) apart from side queries.
+    #[derive(Default)]
+    struct SideQueryBackend {
+        queries: std::sync::Mutex<Vec<(String, bool)>>,
+        side_queries: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl crate::claude::LlmBackend for SideQueryBackend {
+        fn query(
+            &self,
+            prompt: &str,
+            _: &std::path::Path,
+            continue_session: bool,
+        ) -> Result<String> {
+            let mut queries = self.queries.lock().unwrap();
+            queries.push((prompt.to_string(), continue_session));
+            Ok(format!("[SPEED:0.6] Reply {}.", queries.len()))
+        }
+
+        fn side_query(&self, prompt: &str) -> Result<String> {
+            self.side_queries.lock().unwrap().push(prompt.to_string());
+            Ok("Réponse 1.".to_string())
+        }
+    }
+
+    #[test]
+    fn voice_loop_translates_last_reply_with_a_side_query() {
+        let (orch_stream, server_stream) = UnixStream::pair().unwrap();
 
+        let server_handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(server_stream.try_clone().unwrap());
+            let mut writer = BufWriter::new(server_stream);
+            let mut send = |msg: OrchestratorMsg, writer: &mut BufWriter<UnixStream>| {
+                write_orchestrator_msg(writer, &msg).unwrap();
+                read_next_non_status(&mut reader)
+            };
+
+            match send(OrchestratorMsg::TranslateRequest, &mut writer) {
+                OrchestratorMsg::Translation(t) => assert_eq!(t, "Nothing to translate yet"),
+                other => panic!("Expected Translation, got {other:?}"),
+            }
+            let msg = send(OrchestratorMsg::TranscribedText("Hi".into()), &mut writer);
+            assert!(matches!(msg, OrchestratorMsg::ResponseText(_)));
+            match send(OrchestratorMsg::TranslateRequest, &mut writer) {
+                OrchestratorMsg::Translation(t) => assert_eq!(t, "Réponse 1."),
+                other => panic!("Expected Translation, got {other:?}"),
+            }
+            match send(
+                OrchestratorMsg::TranscribedText("Thanks".into()),
+                &mut writer,
+            ) {
+                OrchestratorMsg::ResponseText(t) => assert_eq!(t, "[SPEED:0.6] Reply 2."),
+                other => panic!("Expected ResponseText, got {other:?}"),
+            }
+        });
+
+        let backend = SideQueryBackend::default();
+        let mut reader = BufReader::new(orch_stream.try_clone().unwrap());
+        let mut writer = BufWriter::new(orch_stream);
+        let agent_path = PathBuf::from("agent.md");
+
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            Some("French"),
+            None,
+        );
+        assert!(result.is_ok());
         server_handle.join().unwrap();
+
+        // The translation sees the spoken text only and stays out of the conversation
+        let side_queries = backend.side_queries.lock().unwrap();
+        assert_eq!(*side_queries, [translate_prompt("French", "Reply 1.")]);
+        let queries = backend.queries.lock().unwrap();
+        let flags: Vec<bool> = queries.iter().map(|(_, c)| *c).collect();
+        assert_eq!(flags, [false, true]);
+        assert!(queries.iter().all(|(p, _)| !p.contains("Translate")));
+    }
+
+    #[test]
+    fn voice_loop_simplifies_last_reply_in_the_conversation() {
+        let (orch_stream, server_stream) = UnixStream::pair().unwrap();
+
+        let server_handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(server_stream.try_clone().unwrap());
+            let mut writer = BufWriter::new(server_stream);
+
+            // Nothing heard yet: refused with a status, no query
+            write_orchestrator_msg(&mut writer, &OrchestratorMsg::SimplifyRequest).unwrap();
+            match read_orchestrator_msg(&mut reader).unwrap() {
+                OrchestratorMsg::StatusNotification(s) => assert_eq!(s, "Nothing to simplify yet"),
+                other => panic!("Expected StatusNotification, got {other:?}"),
+            }
+
+            let mut send = |msg: OrchestratorMsg, writer: &mut BufWriter<UnixStream>| {
+                write_orchestrator_msg(writer, &msg).unwrap();
+                read_next_non_status(&mut reader)
+            };
+            let msg = send(OrchestratorMsg::TranscribedText("Hi".into()), &mut writer);
+            assert!(matches!(msg, OrchestratorMsg::ResponseText(_)));
+            match send(OrchestratorMsg::SimplifyRequest, &mut writer) {
+                OrchestratorMsg::ResponseText(t) => assert_eq!(t, "[SPEED:0.6] Reply 2."),
+                other => panic!("Expected ResponseText, got {other:?}"),
+            }
+            // The simpler version is now what the user heard last
+            match send(OrchestratorMsg::TranslateRequest, &mut writer) {
+                OrchestratorMsg::Translation(_) => {}
+                other => panic!("Expected Translation, got {other:?}"),
+            }
+        });
+
+        let backend = SideQueryBackend::default();
+        let mut reader = BufReader::new(orch_stream.try_clone().unwrap());
+        let mut writer = BufWriter::new(orch_stream);
+        let agent_path = PathBuf::from("agent.md");
+
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            Some("French"),
+            None,
+        );
+        assert!(result.is_ok());
+        server_handle.join().unwrap();
+
+        let queries = backend.queries.lock().unwrap();
+        let flags: Vec<bool> = queries.iter().map(|(_, c)| *c).collect();
+        assert_eq!(flags, [false, true]);
+        assert!(queries[1].0.starts_with(FORMAT_REMINDER));
+        assert!(queries[1].0.ends_with(SIMPLIFY_REQUEST));
+        let side_queries = backend.side_queries.lock().unwrap();
+        assert_eq!(*side_queries, [translate_prompt("French", "Reply 2.")]);
+    }
+
+    #[test]
+    fn voice_loop_continues_after_a_summary() {
+        let (orch_stream, server_stream) = UnixStream::pair().unwrap();
+
+        let server_handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(server_stream.try_clone().unwrap());
+            let mut writer = BufWriter::new(server_stream);
+            let send = |msg: OrchestratorMsg,
+                        writer: &mut BufWriter<UnixStream>,
+                        reader: &mut BufReader<UnixStream>| {
+                write_orchestrator_msg(writer, &msg).unwrap();
+                read_next_non_status(reader)
+            };
+
+            // Quit with a summary, abandon it, talk some more, quit again
+            for text in ["one", "two"] {
+                let msg = send(
+                    OrchestratorMsg::TranscribedText(text.into()),
+                    &mut writer,
+                    &mut reader,
+                );
+                assert!(matches!(msg, OrchestratorMsg::ResponseText(_)));
+                let msg = send(OrchestratorMsg::SummaryRequest, &mut writer, &mut reader);
+                assert!(matches!(msg, OrchestratorMsg::SummaryResponse(_)));
+            }
+        });
+
+        let backend = SideQueryBackend::default();
+        let mut reader = BufReader::new(orch_stream.try_clone().unwrap());
+        let mut writer = BufWriter::new(orch_stream);
+        let agent_path = PathBuf::from("agent.md");
+
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
+        assert!(result.is_ok());
+        server_handle.join().unwrap();
+
+        let queries = backend.queries.lock().unwrap();
+        let flags: Vec<bool> = queries.iter().map(|(_, c)| *c).collect();
+        assert_eq!(flags, [false, true, true, true]);
+        assert!(queries[2].0.ends_with("two"));
+    }
+
+    #[test]
+    fn voice_loop_branches_into_a_seeded_conversation() {
+        let (orch_stream, server_stream) = UnixStream::pair().unwrap();
+
+        let server_handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(server_stream.try_clone().unwrap());
+            let mut writer = BufWriter::new(server_stream);
+            let send = |msg: OrchestratorMsg,
+                        writer: &mut BufWriter<UnixStream>,
+                        reader: &mut BufReader<UnixStream>| {
+                write_orchestrator_msg(writer, &msg).unwrap();
+                read_next_non_status(reader)
+            };
+
+            for text in ["one", "two", "three"] {
+                let msg = send(
+                    OrchestratorMsg::TranscribedText(text.into()),
+                    &mut writer,
+                    &mut reader,
+                );
+                assert!(matches!(msg, OrchestratorMsg::ResponseText(_)));
+            }
+            write_orchestrator_msg(&mut writer, &OrchestratorMsg::BranchTo(3)).unwrap();
+            match read_orchestrator_msg(&mut reader).unwrap() {
+                OrchestratorMsg::StatusNotification(s) => {
+                    assert_eq!(s, "Cannot rewind to turn 3: the conversation is at turn 3")
+                }
+                other => panic!("Expected StatusNotification, got {other:?}"),
+            }
+            match send(OrchestratorMsg::BranchTo(1), &mut writer, &mut reader) {
+                OrchestratorMsg::ResponseText(t) => assert_eq!(t, "Rewound to turn 1."),
+                other => panic!("Expected ResponseText, got {other:?}"),
+            }
+            for text in ["deux", "trois"] {
+                let msg = send(
+                    OrchestratorMsg::TranscribedText(text.into()),
+                    &mut writer,
+                    &mut reader,
+                );
+                assert!(matches!(msg, OrchestratorMsg::ResponseText(_)));
+            }
+            match send(OrchestratorMsg::SummaryRequest, &mut writer, &mut reader) {
+                OrchestratorMsg::SummaryResponse(s) => {
+                    assert!(
+                        s.ends_with("### Rewinds\n- At turn 3, rewound to turn 1\n"),
+                        "{s}"
+                    )
+                }
+                other => panic!("Expected SummaryResponse, got {other:?}"),
+            }
+        });
+
+        let backend = SideQueryBackend::default();
+        let mut reader = BufReader::new(orch_stream.try_clone().unwrap());
+        let mut writer = BufWriter::new(orch_stream);
+        let agent_path = PathBuf::from("agent.md");
+
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
+        assert!(result.is_ok());
+        server_handle.join().unwrap();
+
+        let queries = backend.queries.lock().unwrap();
+        let flags: Vec<bool> = queries.iter().map(|(_, c)| *c).collect();
+        // The first turn after the rewind starts a fresh conversation
+        assert_eq!(flags, [false, true, true, false, true, true]);
+        let seeded = &queries[3].0;
+        assert!(
+            seeded.contains("Turn 1\nUser: one\nYou: Reply 1.\n"),
+            "{seeded}"
+        );
+        assert!(!seeded.contains("two") && !seeded.contains("three"));
+        assert!(seeded.ends_with("deux"));
+        assert!(!queries[4].0.contains("User: one"));
+    }
+
+    #[test]
+    fn voice_loop_runs_a_listening_quiz() {
+        use crate::claude::RecordingMockLlmBackend;
+
+        let (orch_stream, server_stream) = UnixStream::pair().unwrap();
+
+        let server_handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(server_stream.try_clone().unwrap());
+            let mut writer = BufWriter::new(server_stream);
+            let say =
+                |text: &str, writer: &mut BufWriter<UnixStream>, reader: &mut BufReader<_>| {
+                    write_orchestrator_msg(writer, &OrchestratorMsg::TranscribedText(text.into()))
+                        .unwrap();
+                    read_next_non_status(reader)
+                };
+            let reply = |msg: OrchestratorMsg| match msg {
+                OrchestratorMsg::ResponseText(t) => t,
+                other => panic!("Expected ResponseText, got {other:?}"),
+            };
+
+            // The passage is spoken whole, markers removed
+            let msg = say("Can we do a listening exercise?", &mut writer, &mut reader);
+            assert_eq!(
+                reply(msg),
+                "[SPEED:0.6] Tom missed the bus, so he walked to work in the rain. Why was Tom late?"
+            );
+            let msg = say("He missed the bus", &mut writer, &mut reader);
+            assert_eq!(reply(msg), "Yes! How did he get to work?");
+
+            // A wrong answer with a grammar slip: feedback, retried
+            let msg = say("He taked a taxi", &mut writer, &mut reader);
+            match msg {
+                OrchestratorMsg::FeedbackText(fb) => {
+                    assert!(fb.contains("Listening quiz: 1/2 answers right"));
+                }
+                other => panic!("Expected FeedbackText, got {other:?}"),
+            }
+            write_orchestrator_msg(&mut writer, &OrchestratorMsg::FeedbackChoice(false)).unwrap();
+
+            // The retried answer is judged again: the score only counts it once
+            let msg = say("He walked", &mut writer, &mut reader);
+            match msg {
+                OrchestratorMsg::FeedbackText(fb) => {
+                    assert_eq!(fb, "BLUE: Listening quiz: 2/2 answers right");
+                }
+                other => panic!("Expected FeedbackText, got {other:?}"),
+            }
+            write_orchestrator_msg(&mut writer, &OrchestratorMsg::FeedbackChoice(true)).unwrap();
+            assert_eq!(
+                reply(read_next_non_status(&mut reader)),
+                "Exactly, in the rain. Well done!"
+            );
+
+            // The quiz is over: back to plain replies
+            let msg = say("Thanks", &mut writer, &mut reader);
+            assert_eq!(reply(msg), "You're welcome.");
+
+            // A rewind replays the passage, tagged
+            write_orchestrator_msg(&mut writer, &OrchestratorMsg::BranchTo(2)).unwrap();
+            assert_eq!(
+                reply(read_next_non_status(&mut reader)),
+                "Rewound to turn 2."
+            );
+            let msg = say("Again please", &mut writer, &mut reader);
+            assert_eq!(reply(msg), "You're welcome.");
+        });
+
+        let backend = RecordingMockLlmBackend::new(vec![
+            "[SPEED:0.6] [PASSAGE]Tom missed the bus, so he walked to work in the rain.[/PASSAGE] Why was Tom late? [AWAIT_ANSWER]".to_string(),
+            "[RIGHT] Yes! How did he get to work? [AWAIT_ANSWER]".to_string(),
+            "[FEEDBACK]\nRED: \"taked\" → \"took\" (irregular past)\n[/FEEDBACK]\n[WRONG] No, he walked. Well done anyway!".to_string(),
+            "[RIGHT] Exactly, in the rain. Well done!".to_string(),
+            "You're welcome.".to_string(),
+            "You're welcome.".to_string(),
+        ]);
+        let mut reader = BufReader::new(orch_stream.try_clone().unwrap());
+        let mut writer = BufWriter::new(orch_stream);
+        let agent_path = PathBuf::from("agent.md");
+
+        let result = run_voice_loop(
+            &mut reader,
+            &mut writer,
+            &backend,
+            &agent_path,
+            None,
+            DEFAULT_MAX_PROMPT_CHARS,
+            None,
+            None,
+        );
+        assert!(result.is_ok());
+        server_handle.join().unwrap();
+
+        let prompts = backend.prompts.lock().unwrap();
+        let replay = prompts.last().unwrap();
+        assert!(replay.contains(
+            "You: [Listening passage] Tom missed the bus, so he walked to work in the rain. Why was Tom late?\n"
+        ));
+        assert!(!replay.contains("[PASSAGE]Tom"));
+    }
+
+    #[test]
+    fn spoken_text_drops_speed_marker() {
+        assert_eq!(spoken_text("[SPEED:0.6] Slowly now."), "Slowly now.");
+        assert_eq!(
+            spoken_text("[SPEED_DEFAULT:0.6] [VOICE:am_adam] Slowly now."),
+            "Slowly now."
+        );
+        assert_eq!(spoken_text("No marker."), "No marker.");
+        assert!(translate_prompt("French", "Hello.").starts_with("Translate this to French."));
+        assert!(translate_prompt("French", "Hello.").ends_with("\n\nHello."));
     }
 }
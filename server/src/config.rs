@@ -0,0 +1,232 @@
+//! `server.toml`: the server flags kept in a file, so a long invocation needs
+//! no shell alias. Each key is a flag without its dashes; a flag given on the
+//! command line wins over the file.
+//!
+//! ```toml
+//! model = "large-v3-turbo"
+//! tts-model = "/opt/kokoro-multi-lang-v1_0"
+//! language = "fr"
+//! port = 9500
+//! strict-lang = true
+//! ```
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use toml::de::{DeTable, DeValue};
+
+use space_lt_common::{debug, info, paths, warn};
+
+/// What a key holds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Text,
+    Port,
+    Number,
+    /// A flag without a value: `true` sets it, `false` leaves it out.
+    Switch,
+}
+
+impl Kind {
+    fn expected(self) -> &'static str {
+        match self {
+            Kind::Text => "a string",
+            Kind::Port => "a port number (0-65535)",
+            Kind::Number => "a whole number",
+            Kind::Switch => "true or false",
+        }
+    }
+}
+
+/// The flags the file can set (one-shot commands like `--tts-test` excluded).
+const KEYS: &[(&str, Kind)] = &[
+    ("model", Kind::Text),
+    ("tts-model", Kind::Text),
+    ("language", Kind::Text),
+    ("languages", Kind::Text),
+    ("tts-lang", Kind::Text),
+    ("tts-engine", Kind::Text),
+    ("strict-lang", Kind::Switch),
+    ("port", Kind::Port),
+    ("socket-path", Kind::Text),
+    ("tls-cert", Kind::Text),
+    ("tls-key", Kind::Text),
+    ("debug", Kind::Switch),
+    ("trace-protocol", Kind::Switch),
+    ("profile", Kind::Switch),
+    ("profile-json", Kind::Text),
+    ("sequential-load", Kind::Switch),
+    ("idle-timeout", Kind::Number),
+    ("no-echo-guard", Kind::Switch),
+    ("tts-lead-ms", Kind::Number),
+    ("tts-cache-mb", Kind::Number),
+    ("tts-parallel", Kind::Number),
+    ("tts-chunk-ms", Kind::Number),
+    ("tts-first-chunk-ms", Kind::Number),
+    ("session-log", Kind::Text),
+    ("min-segment-ms", Kind::Number),
+    ("max-segment-ms", Kind::Number),
+    ("long-segments", Kind::Text),
+    ("hallucination-list", Kind::Text),
+    ("min-confidence", Kind::Number),
+    ("stt-context", Kind::Switch),
+    ("dump-audio", Kind::Text),
+    ("dump-audio-keep", Kind::Number),
+    ("dump-audio-mb", Kind::Number),
+];
+
+/// Sections kept for settings of later versions: these keys are accepted
+/// and not used yet.
+const RESERVED: &[(&str, &[&str])] = &[("tts", &["voice"]), ("stt", &["language"])];
+
+/// `server.toml` in [`paths::config_dir`].
+fn default_path() -> PathBuf {
+    paths::config_dir().join("server.toml")
+}
+
+/// The command line with the flags of the config file added: `--config
+/// <path>`, or the default file when it exists.
+pub fn with_file_args(args: Vec<String>) -> Result<Vec<String>> {
+    let path = match crate::find_arg_value(&args, "--config") {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let path = default_path();
+            if !path.exists() {
+                return Ok(args);
+            }
+            path
+        }
+    };
+    let content =
+        std::fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    let file_args = parse(&content, &path)?;
+    info!("[server] Config: {}", path.display());
+    Ok(merge(args, file_args))
+}
+
+/// Flags are looked up by their first occurrence: the command line comes
+/// first, so its flags win.
+fn merge(mut args: Vec<String>, file_args: Vec<String>) -> Vec<String> {
+    args.extend(file_args);
+    args
+}
+
+/// The flags `content` sets, as command-line arguments. Unknown keys are
+/// only warned about; a value of the wrong type is an error.
+fn parse(content: &str, path: &Path) -> Result<Vec<String>> {
+    let document =
+        DeTable::parse(content).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+    let place = |offset: usize| {
+        let line = content[..offset].matches('\n').count() + 1;
+        format!("{} line {line}", path.display())
+    };
+    let mut args = Vec::new();
+    for (key, value) in document.get_ref().iter() {
+        let name: &str = key.get_ref();
+        let at = place(key.span().start);
+        if let DeValue::Table(section) = value.get_ref() {
+            let reserved = RESERVED.iter().find(|(s, _)| *s == name);
+            let Some((_, keys)) = reserved else {
+                warn!("[server] {at}: unknown section [{name}] (ignored)");
+                continue;
+            };
+            for (key, _) in section.iter() {
+                let at = place(key.span().start);
+                let key: &str = key.get_ref();
+                if keys.contains(&key) {
+                    debug!("[server] {at}: {name}.{key} is not used yet");
+                } else {
+                    warn!("[server] {at}: unknown key {name}.{key} (ignored)");
+                }
+            }
+            continue;
+        }
+        let Some(&(_, kind)) = KEYS.iter().find(|(k, _)| *k == name) else {
+            warn!("[server] {at}: unknown key \"{name}\" (ignored)");
+            continue;
+        };
+        let flag = format!("--{name}");
+        let invalid = || anyhow::anyhow!("{at}: {name} must be {}", kind.expected());
+        match (kind, value.get_ref()) {
+            (Kind::Text, DeValue::String(text)) => args.extend([flag, text.to_string()]),
+            (Kind::Port, DeValue::Integer(port)) => {
+                let port =
+                    u16::from_str_radix(port.as_str(), port.radix()).map_err(|_| invalid())?;
+                args.extend([flag, port.to_string()]);
+            }
+            (Kind::Number, DeValue::Integer(n)) => {
+                let n = u64::from_str_radix(n.as_str(), n.radix()).map_err(|_| invalid())?;
+                args.extend([flag, n.to_string()]);
+            }
+            (Kind::Switch, DeValue::Boolean(on)) => {
+                if *on {
+                    args.push(flag);
+                }
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_arg_value;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn parse_str(content: &str) -> Result<Vec<String>> {
+        parse(content, Path::new("server.toml"))
+    }
+
+    #[test]
+    fn command_line_wins_over_the_file_over_defaults() {
+        let file = parse_str(
+            "model = \"small\"\nport = 9501\nidle-timeout = 30\nstrict-lang = true\ndebug = false\n\n[tts]\nvoice = \"ff_siwis\"\n",
+        )
+        .unwrap();
+        let args = merge(strings(&["space_lt_server", "--port", "9600"]), file);
+        assert_eq!(find_arg_value(&args, "--port").as_deref(), Some("9600"));
+        assert_eq!(find_arg_value(&args, "--model").as_deref(), Some("small"));
+        assert!(args.iter().any(|a| a == "--strict-lang"));
+        assert_eq!(
+            find_arg_value(&args, "--idle-timeout").as_deref(),
+            Some("30")
+        );
+        assert!(!args.iter().any(|a| a == "--debug"));
+        // Left to the default
+        assert_eq!(find_arg_value(&args, "--socket-path"), None);
+    }
+
+    #[test]
+    fn wrong_values_name_the_key_and_line() {
+        let err = parse_str("model = \"small\"\n\nport = \"9500\"\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "server.toml line 3: port must be a port number (0-65535)"
+        );
+        let err = parse_str("port = 70000\n").unwrap_err();
+        assert!(err.to_string().starts_with("server.toml line 1: port"));
+        let err = parse_str("idle-timeout = -5\n").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("idle-timeout must be a whole number")
+        );
+        let err = parse_str("debug = \"yes\"\n").unwrap_err();
+        assert!(err.to_string().contains("debug must be true or false"));
+        // Not TOML at all
+        let err = parse_str("model = small\n").unwrap_err();
+        assert!(err.to_string().contains("line 1"), "{err}");
+    }
+
+    #[test]
+    fn unknown_keys_are_ignored() {
+        assert_eq!(
+            parse_str("colour = \"blue\"\n[stt]\nlanguage = \"fr\"\nbeam = 5\n[gpu]\nid = 0\n")
+                .unwrap(),
+            Vec::<String>::new()
+        );
+    }
+}
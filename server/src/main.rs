@@ -1,14 +1,32 @@
+mod config;
 mod listener;
 mod server;
 mod session;
+mod session_log;
+mod startup;
+mod stats;
 mod transcribe;
 mod tts;
+mod tts_text;
+mod wav_dump;
 
 use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
 
-use space_lt_common::{debug, info};
-use transcribe::Transcriber;
-use tts::TtsEngine;
+use session::{SegmentLimits, SessionOptions, TtsChunks};
+use space_lt_common::{debug, info, profile, warn};
+use transcribe::{Transcriber, TranscriptFilter};
+
+/// Orchestrator socket; the admin socket (`--stats`) sits next to it.
+const DEFAULT_SOCKET_PATH: &str = "/tmp/space_lt_server.sock";
+
+/// How far reply audio runs ahead of the client's playback (`--tts-lead-ms`):
+/// 8 chunks, well within the client's playback queue.
+const DEFAULT_TTS_LEAD: Duration = Duration::from_secs(2);
+
+/// Audio kept of recent reply sentences (`--tts-cache-mb`): about 25 minutes.
+const DEFAULT_TTS_CACHE_MB: usize = 50;
 
 fn find_arg_value(args: &[String], flag: &str) -> Option<String> {
     args.iter()
@@ -18,13 +36,77 @@ fn find_arg_value(args: &[String], flag: &str) -> Option<String> {
 }
 
 fn main() -> Result<()> {
-    let args: Vec<String> = std::env::args().collect();
+    // --config: flags kept in a file (server.toml), the command line wins
+    let args = config::with_file_args(std::env::args().collect())?;
 
     // Parse --debug flag
     if args.iter().any(|a| a == "--debug") {
         space_lt_common::log::set_debug(true);
     }
 
+    // --trace-protocol: log every message sent or received
+    if args.iter().any(|a| a == "--trace-protocol") {
+        space_lt_common::trace::enable();
+    }
+
+    // --profile: collect per-stage timings and print them on exit (or Ctrl+C)
+    let profile_json = find_arg_value(&args, "--profile-json");
+    let profiling = args.iter().any(|a| a == "--profile") || profile_json.is_some();
+    profile::set_enabled(profiling);
+
+    // Ctrl+C or SIGTERM during a session ends it cleanly so the client and the
+    // orchestrator learn why; otherwise (or on a second signal) exit right away
+    let stop = Arc::new(server::StopSignal::default());
+    let handler_stop = stop.clone();
+    let json_path = profile_json.clone();
+    ctrlc::set_handler(move || {
+        if handler_stop.request() {
+            info!("[server] Stopping (press Ctrl+C again to force)...");
+            return;
+        }
+        handler_stop.remove_socket();
+        if profiling {
+            dump_profile(json_path.as_deref());
+        }
+        std::process::exit(130);
+    })?;
+
+    let result = run(&args, &stop);
+    if profiling {
+        dump_profile(profile_json.as_deref());
+    }
+    result
+}
+
+fn dump_profile(json_path: Option<&str>) {
+    if let Err(e) = profile::dump(json_path.map(std::path::Path::new)) {
+        warn!("[server] Could not write profile: {e:#}");
+    }
+}
+
+fn run(args: &[String], stop: &server::StopSignal) -> Result<()> {
+    // --dump-protocol: print the wire format description (JSON) and exit
+    if args.iter().any(|a| a == "--dump-protocol") {
+        print!("{}", space_lt_common::protocol::describe_protocol_json());
+        return Ok(());
+    }
+
+    // --stats: ask a running server for its statistics and exit
+    if args.iter().any(|a| a == "--stats") {
+        use std::io::IsTerminal;
+        let socket_path = find_arg_value(args, "--socket-path")
+            .unwrap_or_else(|| DEFAULT_SOCKET_PATH.to_string());
+        let snapshot = stats::request(&stats::admin_socket_path(std::path::Path::new(
+            &socket_path,
+        )))?;
+        if std::io::stdout().is_terminal() {
+            print!("{}", snapshot.display());
+        } else {
+            println!("{}", snapshot.to_json());
+        }
+        return Ok(());
+    }
+
     // --list-models: print local models and exit
     if args.iter().any(|a| a == "--list-models") {
         use std::io::IsTerminal;
@@ -47,12 +129,35 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // --list-voices: print the TTS model's speakers and exit (requires --tts-model)
+    if args.iter().any(|a| a == "--list-voices") {
+        use std::io::IsTerminal;
+        let tts_model_dir = find_arg_value(args, "--tts-model")
+            .ok_or_else(|| anyhow::anyhow!("--list-voices requires --tts-model <path>"))?;
+        let speakers = tts::kokoro_speakers(std::path::Path::new(&tts_model_dir))?;
+        if std::io::stdout().is_terminal() {
+            println!("Voices of {tts_model_dir} ({}):\n", speakers.len());
+            for (id, name) in &speakers {
+                println!("  {id:>3}  {name}");
+            }
+        } else {
+            for (id, name) in &speakers {
+                println!("{id}\t{name}");
+            }
+        }
+        return Ok(());
+    }
+
     // --tts-test: synthesize text, write WAV, exit (requires --tts-model)
-    if let Some(test_text) = find_arg_value(&args, "--tts-test") {
-        let tts_model_dir = find_arg_value(&args, "--tts-model")
+    if let Some(test_text) = find_arg_value(args, "--tts-test") {
+        let tts_model_dir = find_arg_value(args, "--tts-model")
             .ok_or_else(|| anyhow::anyhow!("--tts-test requires --tts-model <path>"))?;
-        let tts_lang = find_arg_value(&args, "--language").unwrap_or_else(|| "en".to_string());
-        let tts = tts::KokoroTts::new(std::path::Path::new(&tts_model_dir), &tts_lang)?;
+        let tts_lang = find_arg_value(args, "--language").unwrap_or_else(|| "en".to_string());
+        let tts = tts::TtsBackend::parse(find_arg_value(args, "--tts-engine").as_deref())?.load(
+            std::path::Path::new(&tts_model_dir),
+            &tts_lang,
+            1,
+        )?;
         let samples = tts.synthesize(&test_text)?;
         info!(
             "[server] Synthesized {} samples ({:.2}s at 16kHz)",
@@ -75,56 +180,351 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // --stt-test: transcribe a WAV file, print the text, exit (requires --model)
+    if let Some(wav_path) = find_arg_value(args, "--stt-test") {
+        let model_arg = find_arg_value(args, "--model")
+            .ok_or_else(|| anyhow::anyhow!("--stt-test requires --model <name>"))?;
+        let model = space_lt_common::models::resolve_model_path(&model_arg);
+        let language = find_arg_value(args, "--language").unwrap_or_else(|| "en".to_string());
+        let allowed_languages = find_arg_value(args, "--languages")
+            .map(|list| transcribe::parse_language_list(&list))
+            .unwrap_or_default();
+        let audio = transcribe::read_wav_16k(std::path::Path::new(&wav_path))?;
+        let mut transcriber = transcribe::LocalTranscriber::new(
+            &model.to_string_lossy(),
+            &language,
+            allowed_languages,
+        )?;
+        let start = std::time::Instant::now();
+        let text = transcriber.transcribe(&audio)?;
+        info!(
+            "[server] Transcribed {:.2}s of audio in {:.2}s",
+            audio.len() as f64 / 16000.0,
+            start.elapsed().as_secs_f64()
+        );
+        if let Some(detected) = transcriber.detected_language() {
+            let note = if detected.allowed {
+                ""
+            } else {
+                " (not in --languages)"
+            };
+            info!("[server] Detected language: {}{note}", detected.code);
+        }
+        if text.is_empty() {
+            anyhow::bail!("No speech transcribed from {wav_path}");
+        }
+        println!("{text}");
+        return Ok(());
+    }
+
     // Default: run as daemon server (requires --model and --tts-model)
-    let model_arg = find_arg_value(&args, "--model").ok_or_else(|| {
+    let model_arg = find_arg_value(args, "--model").ok_or_else(|| {
         anyhow::anyhow!(
-            "Usage: space_lt_server --model <name> --tts-model <path> [--port <port>] [--socket-path <path>]\n       space_lt_server --list-models\n       space_lt_server --tts-test \"text\" --tts-model <path>"
+            "Usage: space_lt_server --model <name> --tts-model <path> [--port <port>] [--socket-path <path>] [--language <code>|auto [--languages <codes>]] [--tts-lang <codes>] [--strict-lang] [--tls-cert <pem> --tls-key <pem>] [--profile] [--profile-json <path>] [--trace-protocol] [--sequential-load] [--idle-timeout <minutes>] [--no-echo-guard] [--tts-lead-ms <ms>] [--tts-engine kokoro|piper] [--tts-cache-mb <MB>] [--tts-parallel <n>] [--tts-chunk-ms <ms>] [--tts-first-chunk-ms <ms>] [--session-log <dir>] [--min-segment-ms <ms>] [--max-segment-ms <ms>] [--long-segments split|reject] [--hallucination-list <file>] [--min-confidence <percent>] [--stt-context] [--dump-audio <dir> [--dump-audio-keep <n>] [--dump-audio-mb <MB>]] [--config <path>]\n       space_lt_server --stats [--socket-path <path>]\n       space_lt_server --list-models\n       space_lt_server --list-voices --tts-model <path>\n       space_lt_server --dump-protocol\n       space_lt_server --tts-test \"text\" --tts-model <path> [--tts-engine kokoro|piper]\n       space_lt_server --stt-test <wav> --model <name> [--language <code>]"
         )
     })?;
     let model = space_lt_common::models::resolve_model_path(&model_arg);
-    let language = find_arg_value(&args, "--language").unwrap_or_else(|| "en".to_string());
+    let language = find_arg_value(args, "--language").unwrap_or_else(|| "en".to_string());
+    let auto_language = language == transcribe::AUTO_LANGUAGE;
+    let allowed_languages = find_arg_value(args, "--languages")
+        .map(|list| transcribe::parse_language_list(&list))
+        .unwrap_or_default();
+    if !allowed_languages.is_empty() && !auto_language {
+        warn!("[server] --languages only applies with --language auto (ignored)");
+    }
+    // The languages replies are expected in: with auto detection, those of
+    // --languages (the first one sets the TTS default)
+    let spoken_languages = if auto_language {
+        allowed_languages.clone()
+    } else {
+        vec![language.clone()]
+    };
+    let tts_language = spoken_languages
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "en".to_string());
 
-    let tts_model_dir = find_arg_value(&args, "--tts-model").ok_or_else(|| {
-        anyhow::anyhow!("Daemon mode requires --tts-model <path> (Kokoro model directory)")
+    let tts_model_dir = find_arg_value(args, "--tts-model").ok_or_else(|| {
+        anyhow::anyhow!(
+            "Daemon mode requires --tts-model <path> (Kokoro model directory, or Piper voice with --tts-engine piper)"
+        )
     })?;
 
-    let port: u16 = find_arg_value(&args, "--port")
+    let port: u16 = find_arg_value(args, "--port")
         .map(|p| p.parse())
         .transpose()
         .map_err(|e| anyhow::anyhow!("Invalid --port value: {e}"))?
         .unwrap_or(9500);
 
-    let socket_path = find_arg_value(&args, "--socket-path")
-        .unwrap_or_else(|| "/tmp/space_lt_server.sock".to_string());
-
-    // Sequential model loading (G4): Whisper first, then Kokoro
-    info!("[server] Loading Whisper model: {model_arg}...");
-    let start = std::time::Instant::now();
-    let mut transcriber = transcribe::LocalTranscriber::new(&model.to_string_lossy(), &language)?;
-    info!(
-        "[server] Whisper model loaded in {:.1}s",
-        start.elapsed().as_secs_f64()
-    );
-
-    // Warm up Whisper (GPU graph init)
-    debug!("[server] Warming up Whisper...");
-    let silence = vec![0i16; 16000];
-    let _ = transcriber.transcribe(&silence);
-    debug!("[server] Whisper warm-up complete");
-
-    info!("[server] Loading TTS model: {tts_model_dir}...");
-    let start = std::time::Instant::now();
-    let tts_engine = tts::KokoroTts::new(std::path::Path::new(&tts_model_dir), &language)?;
-    info!(
-        "[server] TTS model loaded in {:.1}s",
-        start.elapsed().as_secs_f64()
-    );
+    let socket_path =
+        find_arg_value(args, "--socket-path").unwrap_or_else(|| DEFAULT_SOCKET_PATH.to_string());
+
+    let idle_timeout = find_arg_value(args, "--idle-timeout")
+        .map(|m| parse_idle_timeout(&m))
+        .transpose()?;
+    let echo_guard = !args.iter().any(|a| a == "--no-echo-guard");
+    let stt_context = args.iter().any(|a| a == "--stt-context");
+    // Created now, so a directory that cannot be made fails at startup
+    let audio_dump = parse_audio_dump(args)?;
+    let tts_lead = find_arg_value(args, "--tts-lead-ms")
+        .map(|ms| ms.parse().map(Duration::from_millis))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --tts-lead-ms value: {e}"))?
+        .unwrap_or(DEFAULT_TTS_LEAD);
+    let tts_cache_mb: usize = find_arg_value(args, "--tts-cache-mb")
+        .map(|mb| mb.parse())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --tts-cache-mb value: {e}"))?
+        .unwrap_or(DEFAULT_TTS_CACHE_MB);
+    let tts_parallel: usize = match find_arg_value(args, "--tts-parallel") {
+        Some(n) => match n.parse() {
+            Ok(0) | Err(_) => {
+                anyhow::bail!("Invalid --tts-parallel value: {n} (expected 1 or more)")
+            }
+            Ok(n) => n,
+        },
+        None => 1,
+    };
+    let tts_backend = tts::TtsBackend::parse(find_arg_value(args, "--tts-engine").as_deref())?;
+
+    let segment_limits = parse_segment_limits(args)?;
+    let transcript_filter = parse_transcript_filter(args)?;
+    let tts_chunks = parse_tts_chunks(args)?;
+
+    // Created now, so a directory that cannot be made fails at startup
+    let session_log = find_arg_value(args, "--session-log").map(std::path::PathBuf::from);
+    if let Some(dir) = &session_log {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow::anyhow!("Invalid --session-log {}: {e}", dir.display()))?;
+    }
+
+    // Optional TLS for the client link: both --tls-cert and --tls-key are required
+    let tls = match (
+        find_arg_value(args, "--tls-cert"),
+        find_arg_value(args, "--tls-key"),
+    ) {
+        (Some(cert), Some(key)) => {
+            let config = space_lt_common::transport::server_config(
+                std::path::Path::new(&cert),
+                std::path::Path::new(&key),
+            )?;
+            info!("[server] TLS enabled (cert: {cert})");
+            Some(config)
+        }
+        (None, None) => None,
+        _ => anyhow::bail!("--tls-cert and --tls-key must be given together"),
+    };
+
+    // The TTS model must speak the language Whisper transcribes; checked before
+    // loading anything so --strict-lang fails fast
+    let tts_languages = match find_arg_value(args, "--tts-lang") {
+        Some(list) => tts::TtsLanguages::declared(&list),
+        None => tts::detect_tts_languages(std::path::Path::new(&tts_model_dir)),
+    };
+    let mut warnings = Vec::new();
+    let mismatch = spoken_languages
+        .iter()
+        .find_map(|spoken| tts::language_mismatch(spoken, &tts_languages));
+    match mismatch {
+        Some(warning) if args.iter().any(|a| a == "--strict-lang") => {
+            anyhow::bail!("{warning} (--strict-lang)")
+        }
+        Some(warning) => {
+            warn!("[server] ==================== LANGUAGE MISMATCH ====================");
+            warn!("[server] {warning}");
+            warn!("[server] ================================================================");
+            warnings.push(warning);
+        }
+        None if tts_languages == tts::TtsLanguages::Unknown => debug!(
+            "[server] Could not tell which languages the TTS model speaks (declare them with --tts-lang)"
+        ),
+        None => debug!("[server] TTS languages: {tts_languages:?}"),
+    }
+
+    // Whisper and the TTS load (and warm up) side by side; --sequential-load
+    // loads Whisper first, then the TTS, for GPUs that can't hold both loads
+    let load_whisper = || -> Result<_> {
+        info!("[server] Loading Whisper model: {model_arg}...");
+        let start = std::time::Instant::now();
+        let mut transcriber = transcribe::LocalTranscriber::new(
+            &model.to_string_lossy(),
+            &language,
+            allowed_languages.clone(),
+        )?;
+        info!(
+            "[server] Whisper model loaded in {:.1}s",
+            start.elapsed().as_secs_f64()
+        );
+
+        // Warm up Whisper (GPU graph init); a model that can't transcribe stops here
+        debug!("[server] Warming up Whisper...");
+        let whisper_warmup = transcribe::warm_up(&mut transcriber).map_err(|e| {
+            anyhow::anyhow!(
+                "Whisper warm-up failed with model {}: {e:#}",
+                model.display()
+            )
+        })?;
+        info!(
+            "[server] Whisper warm-up: {:.2}s",
+            whisper_warmup.as_secs_f64()
+        );
+        let warning = (whisper_warmup > transcribe::WARMUP_SLOW).then(|| {
+            format!(
+                "Whisper warm-up took {:.1}s: it may be running on the CPU, expect slow transcriptions",
+                whisper_warmup.as_secs_f64()
+            )
+        });
+        Ok((transcriber, warning))
+    };
+    let load_tts = || -> Result<_> {
+        info!("[server] Loading TTS model: {tts_model_dir}...");
+        let start = std::time::Instant::now();
+        let tts_engine = tts_backend.load(
+            std::path::Path::new(&tts_model_dir),
+            &tts_language,
+            tts_parallel,
+        )?;
+        info!(
+            "[server] TTS model loaded in {:.1}s",
+            start.elapsed().as_secs_f64()
+        );
+
+        let tts_warmup = tts::warm_up(&*tts_engine)
+            .map_err(|e| anyhow::anyhow!("TTS warm-up failed with model {tts_model_dir}: {e:#}"))?;
+        info!("[server] TTS warm-up: {:.2}s", tts_warmup.as_secs_f64());
+        let warning = (tts_warmup > tts::WARMUP_SLOW).then(|| {
+            format!(
+                "TTS warm-up took {:.1}s: it may be running on the CPU, expect slow replies",
+                tts_warmup.as_secs_f64()
+            )
+        });
+        Ok((tts_engine, warning))
+    };
+    let sequential = args.iter().any(|a| a == "--sequential-load");
+    let ((transcriber, whisper_warning), (tts_engine, tts_warning)) =
+        startup::load_models(load_whisper, load_tts, sequential)?;
+    for warning in [whisper_warning, tts_warning].into_iter().flatten() {
+        warn!("[server] {warning}");
+        warnings.push(warning);
+    }
 
     // Run daemon
     server::run_daemon(
         Box::new(transcriber),
-        Box::new(tts_engine),
+        tts_engine,
         port,
         std::path::Path::new(&socket_path),
+        tls,
+        stop,
+        SessionOptions {
+            idle_timeout,
+            echo_guard,
+            tts_lead: Some(tts_lead),
+            tts_cache_bytes: tts_cache_mb.saturating_mul(1 << 20),
+            tts_chunks,
+            session_log,
+            segment_limits,
+            transcript_filter,
+            stt_context,
+            audio_dump,
+        },
+        warnings,
     )
 }
+
+/// `--dump-audio <dir>`, with `--dump-audio-keep <segments>` and
+/// `--dump-audio-mb <MB>`.
+fn parse_audio_dump(args: &[String]) -> Result<Option<wav_dump::AudioDump>> {
+    let Some(dir) = find_arg_value(args, "--dump-audio") else {
+        return Ok(None);
+    };
+    let defaults = wav_dump::Rotation::default();
+    let rotation = wav_dump::Rotation {
+        max_segments: match find_arg_value(args, "--dump-audio-keep") {
+            Some(n) => match n.parse() {
+                Ok(n) if n > 0 => n,
+                _ => anyhow::bail!("Invalid --dump-audio-keep value: {n} (expected 1 or more)"),
+            },
+            None => defaults.max_segments,
+        },
+        max_bytes: find_arg_value(args, "--dump-audio-mb")
+            .map(|mb| mb.parse::<u64>().map(|mb| mb.saturating_mul(1 << 20)))
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --dump-audio-mb value: {e}"))?
+            .unwrap_or(defaults.max_bytes),
+    };
+    let dump = wav_dump::AudioDump::create(std::path::Path::new(&dir), rotation)
+        .map_err(|e| anyhow::anyhow!("Invalid --dump-audio {dir}: {e:#}"))?;
+    Ok(Some(dump))
+}
+
+/// `--hallucination-list <file>` and `--min-confidence <percent>`.
+fn parse_transcript_filter(args: &[String]) -> Result<TranscriptFilter> {
+    let min_confidence = match find_arg_value(args, "--min-confidence") {
+        Some(percent) => match percent.parse::<u8>() {
+            Ok(percent) if percent <= 100 => f32::from(percent) / 100.0,
+            _ => anyhow::bail!(
+                "Invalid --min-confidence value \"{percent}\": expected a percentage (0-100)"
+            ),
+        },
+        None => transcribe::DEFAULT_MIN_CONFIDENCE,
+    };
+    match find_arg_value(args, "--hallucination-list") {
+        Some(path) => TranscriptFilter::load(std::path::Path::new(&path), min_confidence)
+            .map_err(|e| anyhow::anyhow!("Invalid --hallucination-list: {e:#}")),
+        None => Ok(TranscriptFilter::new("", min_confidence)),
+    }
+}
+
+/// `--tts-chunk-ms` and `--tts-first-chunk-ms`, over the defaults.
+fn parse_tts_chunks(args: &[String]) -> Result<TtsChunks> {
+    let samples = |flag: &str| -> Result<Option<usize>> {
+        find_arg_value(args, flag)
+            .map(|ms| match ms.parse::<usize>() {
+                Ok(ms) if ms > 0 => Ok(ms.saturating_mul(16)),
+                _ => anyhow::bail!("Invalid {flag} value: {ms} (expected milliseconds above 0)"),
+            })
+            .transpose()
+    };
+    let defaults = TtsChunks::default();
+    Ok(TtsChunks {
+        size: samples("--tts-chunk-ms")?.unwrap_or(defaults.size),
+        first: samples("--tts-first-chunk-ms")?.or(defaults.first),
+    })
+}
+
+/// `--min-segment-ms`, `--max-segment-ms` and `--long-segments`, over the defaults.
+fn parse_segment_limits(args: &[String]) -> Result<SegmentLimits> {
+    let millis = |flag: &str| -> Result<Option<Duration>> {
+        find_arg_value(args, flag)
+            .map(|ms| ms.parse().map(Duration::from_millis))
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid {flag} value: {e}"))
+    };
+    let defaults = SegmentLimits::default();
+    let limits = SegmentLimits {
+        min: millis("--min-segment-ms")?.unwrap_or(defaults.min),
+        max: millis("--max-segment-ms")?.unwrap_or(defaults.max),
+        split_long: match find_arg_value(args, "--long-segments").as_deref() {
+            None | Some("split") => true,
+            Some("reject") => false,
+            Some(other) => anyhow::bail!(
+                "Invalid --long-segments value \"{other}\": expected \"split\" or \"reject\""
+            ),
+        },
+    };
+    if limits.min > limits.max {
+        anyhow::bail!("--min-segment-ms is above --max-segment-ms");
+    }
+    Ok(limits)
+}
+
+/// `--idle-timeout <minutes>`, a whole number of minutes above zero.
+fn parse_idle_timeout(value: &str) -> Result<Duration> {
+    match value.parse::<u64>() {
+        Ok(minutes) if minutes > 0 => Ok(Duration::from_secs(minutes * 60)),
+        _ => {
+            anyhow::bail!("Invalid --idle-timeout value \"{value}\": expected minutes (1 or more)")
+        }
+    }
+}
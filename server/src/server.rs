@@ -1,79 +1,595 @@
 use anyhow::{Context, Result};
 use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use space_lt_common::info;
-use space_lt_common::protocol::{
-    OrchestratorMsg, ServerMsg, read_orchestrator_msg, write_server_msg,
-};
+use crossbeam_channel::Sender;
+use space_lt_common::protocol::{ClientMsg, ServerMsg, read_client_msg, write_server_msg};
+use space_lt_common::transport::{TlsServerConfig, Transport};
+use space_lt_common::{info, warn};
 
 use crate::listener;
-use crate::session;
+use crate::session::{
+    self, ClientHandoff, OrchestratorHandoff, SessionOptions, SessionOutcome, await_session_start,
+};
+use crate::stats::{self, SessionStats};
 use crate::transcribe::Transcriber;
 use crate::tts::TtsEngine;
 
+/// How long a client announced an active session has to answer the takeover prompt.
+const TAKEOVER_CHOICE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// An orchestrator that has not sent SessionStart by then is dropped.
+const ORCHESTRATOR_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sent to a client that connects while another one is still being greeted
+/// or waits for its session.
+pub const ERR_CLIENT_BUSY: &str = "another client is connected";
+
+/// Shutdown request shared between the daemon and the signal handler.
+#[derive(Default)]
+pub struct StopSignal {
+    requested: AtomicBool,
+    in_session: AtomicBool,
+    /// The socket files (orchestrator and admin), once listening.
+    socket_paths: Mutex<Vec<PathBuf>>,
+}
+
+impl StopSignal {
+    /// Ask the running session to end cleanly (the client is told why).
+    ///
+    /// Returns `false` when no session is running or a stop was already
+    /// requested, in which case the caller should exit directly.
+    pub fn request(&self) -> bool {
+        let first = !self.requested.swap(true, Ordering::SeqCst);
+        first && self.in_session.load(Ordering::SeqCst)
+    }
+
+    /// Remove the socket files, for an exit that skips the daemon's own cleanup.
+    pub fn remove_socket(&self) {
+        let paths = self.socket_paths.lock().unwrap_or_else(|e| e.into_inner());
+        for path in paths.iter() {
+            remove_socket_file(path);
+        }
+    }
+}
+
 /// Run the server in daemon mode: TCP listener for client + Unix socket for orchestrator.
 ///
-/// Models must already be loaded and passed as trait objects.
+/// Models must already be loaded and passed as trait objects. When `tls` is set,
+/// the client link is wrapped in TLS; a client that fails the handshake is dropped
+/// and the server keeps waiting for the next one.
+///
+/// Clients that connect while a session is running are told so in the Ready
+/// handshake and may take it over or ask for a fresh one. A fresh start ends the
+/// current orchestrator link and waits for the next orchestrator with the new client.
+/// An orchestrator that drops mid-session is replaced by the next one to connect.
+///
+/// Statistics are served on the admin socket next to `socket_path` (see
+/// [`stats::admin_socket_path`]).
+///
+/// Each session runs with `options`; one ended for its idle timeout leaves the
+/// server waiting for the next client.
+///
+/// A [`StopSignal`] request ends the running session and returns.
+#[allow(clippy::too_many_arguments)]
 pub fn run_daemon(
     transcriber: Box<dyn Transcriber>,
     tts: Box<dyn TtsEngine>,
     port: u16,
     socket_path: &Path,
+    tls: Option<Arc<TlsServerConfig>>,
+    stop: &StopSignal,
+    options: SessionOptions,
+    warnings: Vec<String>,
 ) -> Result<()> {
+    let mut transcriber = transcriber;
+    let tts: Arc<dyn TtsEngine> = Arc::from(tts);
+
     // Start listeners
     let tcp_listener = listener::start_tcp(port)?;
     let unix_listener = listener::start_unix(socket_path)?;
+    let admin_path = stats::admin_socket_path(socket_path);
+    let admin_listener = listener::start_unix(&admin_path)?;
+    *stop.socket_paths.lock().unwrap_or_else(|e| e.into_inner()) =
+        vec![socket_path.to_path_buf(), admin_path.clone()];
+
+    let stats = Arc::new(SessionStats::default());
+    let admin_stats = stats.clone();
+    std::thread::Builder::new()
+        .name("admin".into())
+        .spawn(move || stats::serve(admin_listener, &admin_stats))?;
+
+    // Unix seconds when the current session started, 0 until the first client is bound
+    let active_since = Arc::new(AtomicU64::new(0));
+
+    let (handoff_tx, handoff_rx) = crossbeam_channel::bounded::<ClientHandoff>(1);
+    let acceptor_since = active_since.clone();
+    std::thread::Builder::new()
+        .name("tcp_acceptor".into())
+        .spawn(move || accept_clients(tcp_listener, tls, acceptor_since, handoff_tx, warnings))?;
+
+    // Orchestrators are accepted on a thread so a running session can take a
+    // replacement for one that dropped
+    let (orchestrator_tx, orchestrator_rx) = crossbeam_channel::bounded::<OrchestratorHandoff>(0);
+    std::thread::Builder::new()
+        .name("unix_acceptor".into())
+        .spawn(move || accept_orchestrators(unix_listener, orchestrator_tx))?;
 
     info!("[server] Waiting for client connection on port {port}...");
-    let (tcp_stream, client_addr) = tcp_listener
-        .accept()
-        .context("accepting TCP client connection")?;
-    info!("[server] Client connected from {client_addr}");
-
-    // Send Ready to client
-    let mut client_writer = BufWriter::new(
-        tcp_stream
+    let mut client = handoff_rx.recv().context("client acceptor stopped")?.stream;
+    stats.client_connected.store(true, Ordering::SeqCst);
+
+    loop {
+        info!(
+            "[server] Waiting for orchestrator connection on {}...",
+            socket_path.display()
+        );
+        let OrchestratorHandoff {
+            stream: unix_stream,
+            config,
+        } = orchestrator_rx
+            .recv()
+            .context("orchestrator acceptor stopped")?;
+        info!("[server] Orchestrator connected");
+        info!("[server] SessionStart received: {config}");
+        if let Err(e) = write_server_msg(&mut &unix_stream, &ServerMsg::Ready) {
+            warn!("[server] Could not send Ready to the orchestrator: {e:#}");
+            continue;
+        }
+        stats.orchestrator_connected.store(true, Ordering::SeqCst);
+        info!("[server] Sent Ready to orchestrator");
+
+        info!("[server] Starting session routing...");
+        stop.in_session.store(true, Ordering::SeqCst);
+        let outcome = session::run_session(
+            &mut *transcriber,
+            tts.clone(),
+            client,
+            unix_stream,
+            &handoff_rx,
+            &orchestrator_rx,
+            &stop.requested,
+            &options,
+            &stats,
+        );
+        stop.in_session.store(false, Ordering::SeqCst);
+        stats.orchestrator_connected.store(false, Ordering::SeqCst);
+        stats.client_connected.store(
+            matches!(outcome, Ok(SessionOutcome::StartFresh(_))),
+            Ordering::SeqCst,
+        );
+
+        match outcome? {
+            SessionOutcome::Ended | SessionOutcome::Shutdown => break,
+            SessionOutcome::StartFresh(next) => {
+                info!("[server] Starting a fresh session for the new client");
+                active_since.store(unix_now(), Ordering::SeqCst);
+                client = next;
+            }
+            SessionOutcome::Idle => {
+                // The next client starts a session of its own
+                active_since.store(0, Ordering::SeqCst);
+                info!("[server] Waiting for client connection on port {port}...");
+                client = handoff_rx.recv().context("client acceptor stopped")?.stream;
+                stats.client_connected.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    remove_socket_file(socket_path);
+    remove_socket_file(&admin_path);
+
+    info!("[server] Server shutdown complete");
+    Ok(())
+}
+
+fn remove_socket_file(path: &Path) {
+    if path.exists() {
+        std::fs::remove_file(path).ok();
+    }
+}
+
+/// Accept orchestrator connections for the lifetime of the daemon. Each one
+/// is taken through SessionStart here, so a slow one never holds up a running
+/// session, then waits for the daemon or the session to take it.
+fn accept_orchestrators(listener: UnixListener, orchestrators: Sender<OrchestratorHandoff>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("[server] Failed to accept orchestrator: {e}");
+                continue;
+            }
+        };
+        match handshake_orchestrator(stream, ORCHESTRATOR_HANDSHAKE_TIMEOUT) {
+            Ok(orchestrator) => {
+                if orchestrators.send(orchestrator).is_err() {
+                    break; // daemon is shutting down
+                }
+            }
+            Err(e) => warn!("[server] Orchestrator failed its handshake: {e:#}"),
+        }
+    }
+}
+
+/// Wait up to `timeout` for the orchestrator's SessionStart.
+fn handshake_orchestrator(stream: UnixStream, timeout: Duration) -> Result<OrchestratorHandoff> {
+    // An orchestrator that never speaks would otherwise hold up the next one
+    stream
+        .set_read_timeout(Some(timeout))
+        .context("setting the orchestrator handshake timeout")?;
+    let config = await_session_start(&stream)?;
+    stream
+        .set_read_timeout(None)
+        .context("clearing the orchestrator handshake timeout")?;
+    Ok(OrchestratorHandoff { stream, config })
+}
+
+/// Accept client connections for the lifetime of the daemon.
+///
+/// Each client is greeted on its own thread, so a client slow to answer the
+/// takeover prompt never leaves the next one hanging without a reply. One
+/// client is handled at a time: while one is being greeted or its handoff
+/// waits for the session, others get [`ERR_CLIENT_BUSY`] and are closed.
+fn accept_clients(
+    listener: TcpListener,
+    tls: Option<Arc<TlsServerConfig>>,
+    active_since: Arc<AtomicU64>,
+    handoffs: Sender<ClientHandoff>,
+    warnings: Vec<String>,
+) {
+    let greeting = Arc::new(AtomicBool::new(false));
+    let warnings = Arc::new(warnings);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("[server] Failed to accept client: {e}");
+                continue;
+            }
+        };
+        let (tls, active_since, handoffs, warnings, greeting) = (
+            tls.clone(),
+            active_since.clone(),
+            handoffs.clone(),
+            warnings.clone(),
+            greeting.clone(),
+        );
+        let spawned = std::thread::Builder::new()
+            .name("client_greeter".into())
+            .spawn(move || {
+                serve_client(stream, tls, &active_since, &handoffs, &warnings, &greeting)
+            });
+        if let Err(e) = spawned {
+            warn!("[server] Failed to start the client greeter: {e}");
+        }
+    }
+}
+
+/// Greet one client and hand it to the daemon, or turn it away when another
+/// client holds the `greeting` slot or a handoff is still pending.
+fn serve_client(
+    stream: TcpStream,
+    tls: Option<Arc<TlsServerConfig>>,
+    active_since: &AtomicU64,
+    handoffs: &Sender<ClientHandoff>,
+    warnings: &[String],
+    greeting: &AtomicBool,
+) {
+    let client_addr = stream
+        .peer_addr()
+        .map(|a| a.to_string())
+        .unwrap_or_else(|_| "unknown".into());
+
+    let transport = match tls {
+        Some(config) => match Transport::accept_tls(stream, config) {
+            Ok(t) => t,
+            Err(e) => {
+                warn!("[server] Rejected client {client_addr}: {e:#}");
+                return;
+            }
+        },
+        None => Transport::Plain(stream),
+    };
+    info!(
+        "[server] Client connected from {client_addr}{}",
+        if transport.is_tls() { " (TLS)" } else { "" }
+    );
+
+    let busy = greeting.swap(true, Ordering::SeqCst);
+    if busy || handoffs.is_full() {
+        if !busy {
+            greeting.store(false, Ordering::SeqCst);
+        }
+        warn!("[server] Turning away {client_addr}: {ERR_CLIENT_BUSY}");
+        let mut writer = BufWriter::new(transport);
+        let _ = write_server_msg(&mut writer, &ServerMsg::Error(ERR_CLIENT_BUSY.into()));
+        return;
+    }
+
+    match greet_client(transport, active_since, warnings) {
+        Ok(handoff) => {
+            if handoffs.send(handoff).is_err() {
+                warn!("[server] Dropping {client_addr}: the daemon is shutting down");
+            }
+        }
+        Err(e) => warn!("[server] Handshake with {client_addr} failed: {e:#}"),
+    }
+    greeting.store(false, Ordering::SeqCst);
+}
+
+/// Send the Ready handshake and, if a session is running, wait for the client's
+/// takeover choice. The first client to be greeted claims the session slot.
+fn greet_client(
+    transport: Transport,
+    active_since: &AtomicU64,
+    warnings: &[String],
+) -> Result<ClientHandoff> {
+    let mut writer = BufWriter::new(
+        transport
             .try_clone()
             .context("cloning TCP stream for Ready")?,
     );
-    write_server_msg(&mut client_writer, &ServerMsg::Ready)?;
-    client_writer.flush()?;
+    let send_warnings = |writer: &mut BufWriter<Transport>| -> Result<()> {
+        for warning in warnings {
+            write_server_msg(writer, &ServerMsg::Warning(warning.clone()))?;
+        }
+        Ok(())
+    };
+
+    let now = unix_now();
+    let claimed = active_since.compare_exchange(0, now, Ordering::SeqCst, Ordering::SeqCst);
+    let Err(active_since) = claimed else {
+        let greeted = write_server_msg(&mut writer, &ServerMsg::Ready)
+            .and_then(|()| send_warnings(&mut writer))
+            .and_then(|()| Ok(writer.flush()?));
+        if greeted.is_err() {
+            // Never handed off: the slot goes to the next client
+            let _ = active_since.compare_exchange(now, 0, Ordering::SeqCst, Ordering::SeqCst);
+        }
+        greeted?;
+        return Ok(ClientHandoff {
+            stream: transport,
+            take_over: false,
+        });
+    };
+
+    info!("[server] Session active since {active_since}, asking client to take over or restart");
+    write_server_msg(&mut writer, &ServerMsg::ReadyActiveSession(active_since))?;
+    send_warnings(&mut writer)?;
+    writer.flush()?;
+
+    // Read unbuffered so no bytes meant for the session's reader are consumed here
+    let mut reader = transport
+        .try_clone()
+        .context("cloning TCP stream for takeover choice")?;
+    reader.set_read_timeout(Some(TAKEOVER_CHOICE_TIMEOUT))?;
+    let msg = read_client_msg(&mut reader).context("waiting for takeover choice")?;
+    reader.set_read_timeout(None)?;
 
-    info!(
-        "[server] Waiting for orchestrator connection on {}...",
-        socket_path.display()
-    );
-    let (unix_stream, _) = unix_listener
-        .accept()
-        .context("accepting Unix socket orchestrator connection")?;
-    info!("[server] Orchestrator connected");
-
-    // SessionStart handshake: read SessionStart, send Ready back on Unix socket.
-    // Use raw stream (not BufReader) to avoid read-ahead stealing bytes from the fd
-    // that run_session's BufReaders would then miss.
-    let msg = read_orchestrator_msg(&mut &unix_stream)
-        .context("reading SessionStart from orchestrator")?;
     match msg {
-        OrchestratorMsg::SessionStart(config) => {
-            info!("[server] SessionStart received: {config}");
+        ClientMsg::SessionTakeover(take_over) => {
+            info!(
+                "[server] Client chose to {}",
+                if take_over {
+                    "take over the session"
+                } else {
+                    "start fresh"
+                }
+            );
+            Ok(ClientHandoff {
+                stream: transport,
+                take_over,
+            })
         }
-        other => {
-            anyhow::bail!("Expected SessionStart from orchestrator, got {other:?}");
+        other => anyhow::bail!("Expected SessionTakeover, got {other:?}"),
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use space_lt_common::protocol::{read_server_msg, write_client_msg};
+    use std::io::{BufReader, Read};
+
+    fn spawn_acceptor(
+        active_since: u64,
+        warnings: Vec<String>,
+    ) -> (
+        std::net::SocketAddr,
+        Arc<AtomicU64>,
+        crossbeam_channel::Receiver<ClientHandoff>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let since = Arc::new(AtomicU64::new(active_since));
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        let since_clone = since.clone();
+        std::thread::spawn(move || accept_clients(listener, None, since_clone, tx, warnings));
+        (addr, since, rx)
+    }
+
+    #[test]
+    fn first_client_gets_plain_ready() {
+        let (addr, _since, rx) = spawn_acceptor(0, Vec::new());
+
+        let client = TcpStream::connect(addr).unwrap();
+        let mut reader = BufReader::new(client);
+        assert!(matches!(
+            read_server_msg(&mut reader).unwrap(),
+            ServerMsg::Ready
+        ));
+
+        let handoff = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert!(!handoff.take_over);
+    }
+
+    #[test]
+    fn startup_warnings_follow_ready() {
+        let (addr, _since, rx) = spawn_acceptor(0, vec!["TTS speaks en only".into()]);
+
+        let client = TcpStream::connect(addr).unwrap();
+        let mut reader = BufReader::new(client);
+        assert!(matches!(
+            read_server_msg(&mut reader).unwrap(),
+            ServerMsg::Ready
+        ));
+        match read_server_msg(&mut reader).unwrap() {
+            ServerMsg::Warning(w) => assert_eq!(w, "TTS speaks en only"),
+            other => panic!("Expected Warning, got {other:?}"),
         }
+        rx.recv_timeout(Duration::from_secs(2)).unwrap();
     }
 
-    write_server_msg(&mut &unix_stream, &ServerMsg::Ready)?;
-    info!("[server] Sent Ready to orchestrator");
+    #[test]
+    fn a_failed_greeting_gives_the_slot_back() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        stream.shutdown(std::net::Shutdown::Write).unwrap();
 
-    info!("[server] Starting session routing...");
-    session::run_session(transcriber, tts, tcp_stream, unix_stream)?;
+        let since = AtomicU64::new(0);
+        assert!(greet_client(Transport::Plain(stream), &since, &[]).is_err());
+        assert_eq!(since.load(Ordering::SeqCst), 0);
+    }
 
-    // Clean up Unix socket file
-    if socket_path.exists() {
-        std::fs::remove_file(socket_path).ok();
+    #[test]
+    fn second_client_is_offered_takeover() {
+        let (addr, since, rx) = spawn_acceptor(0, Vec::new());
+
+        // First client binds, then the session becomes active
+        let first = TcpStream::connect(addr).unwrap();
+        let mut first_r = BufReader::new(first);
+        assert!(matches!(
+            read_server_msg(&mut first_r).unwrap(),
+            ServerMsg::Ready
+        ));
+        rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        since.store(1_771_000_000, Ordering::SeqCst);
+
+        // Second client is told about the running session and takes over
+        let second = TcpStream::connect(addr).unwrap();
+        let mut second_r = BufReader::new(second.try_clone().unwrap());
+        match read_server_msg(&mut second_r).unwrap() {
+            ServerMsg::ReadyActiveSession(s) => assert_eq!(s, 1_771_000_000),
+            other => panic!("Expected ReadyActiveSession, got {other:?}"),
+        }
+        let mut second_w = BufWriter::new(second);
+        write_client_msg(&mut second_w, &ClientMsg::SessionTakeover(true)).unwrap();
+
+        let handoff = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert!(handoff.take_over);
+
+        // Third client asks for a fresh session
+        let third = TcpStream::connect(addr).unwrap();
+        let mut third_r = BufReader::new(third.try_clone().unwrap());
+        assert!(matches!(
+            read_server_msg(&mut third_r).unwrap(),
+            ServerMsg::ReadyActiveSession(_)
+        ));
+        let mut third_w = BufWriter::new(third);
+        write_client_msg(&mut third_w, &ClientMsg::SessionTakeover(false)).unwrap();
+
+        let handoff = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert!(!handoff.take_over);
     }
 
-    info!("[server] Server shutdown complete");
-    Ok(())
+    fn assert_turned_away(client: TcpStream) {
+        let mut reader = BufReader::new(client);
+        match read_server_msg(&mut reader).unwrap() {
+            ServerMsg::Error(e) => assert_eq!(e, ERR_CLIENT_BUSY),
+            other => panic!("Expected Error, got {other:?}"),
+        }
+        // Then the server closes the connection
+        assert_eq!(reader.read(&mut [0; 1]).unwrap(), 0);
+    }
+
+    #[test]
+    fn client_arriving_during_a_takeover_prompt_is_turned_away() {
+        let (addr, _since, rx) = spawn_acceptor(1_771_000_000, Vec::new());
+
+        // First client is asked about the running session and has not answered
+        let first = TcpStream::connect(addr).unwrap();
+        let mut first_r = BufReader::new(first.try_clone().unwrap());
+        assert!(matches!(
+            read_server_msg(&mut first_r).unwrap(),
+            ServerMsg::ReadyActiveSession(_)
+        ));
+
+        assert_turned_away(TcpStream::connect(addr).unwrap());
+
+        // The first client's answer still goes through
+        let mut first_w = BufWriter::new(first);
+        write_client_msg(&mut first_w, &ClientMsg::SessionTakeover(true)).unwrap();
+        drop(first_w);
+        assert!(rx.recv_timeout(Duration::from_secs(2)).unwrap().take_over);
+    }
+
+    #[test]
+    fn client_arriving_while_a_handoff_is_pending_is_turned_away() {
+        let (addr, since, rx) = spawn_acceptor(0, Vec::new());
+
+        let first = TcpStream::connect(addr).unwrap();
+        let mut first_r = BufReader::new(first);
+        assert!(matches!(
+            read_server_msg(&mut first_r).unwrap(),
+            ServerMsg::Ready
+        ));
+        // The handoff waits in the channel until the daemon takes it
+        while !rx.is_full() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_ne!(since.load(Ordering::SeqCst), 0);
+
+        assert_turned_away(TcpStream::connect(addr).unwrap());
+
+        // Once the daemon has the first client, the next one is greeted again
+        rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        // Let the first greeter release its slot after the send
+        std::thread::sleep(Duration::from_millis(50));
+        let third = TcpStream::connect(addr).unwrap();
+        let mut third_r = BufReader::new(third);
+        assert!(matches!(
+            read_server_msg(&mut third_r).unwrap(),
+            ServerMsg::ReadyActiveSession(_)
+        ));
+    }
+
+    #[test]
+    fn orchestrator_handshake_times_out_on_a_silent_peer() {
+        let (server, _silent) = UnixStream::pair().unwrap();
+        let started = std::time::Instant::now();
+        assert!(handshake_orchestrator(server, Duration::from_millis(100)).is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn orchestrator_handshake_returns_the_config_without_ready() {
+        use space_lt_common::protocol::{OrchestratorMsg, write_orchestrator_msg};
+
+        let (server, orch) = UnixStream::pair().unwrap();
+        write_orchestrator_msg(
+            &mut &orch,
+            &OrchestratorMsg::SessionStart("{\"lang\": \"en\"}".into()),
+        )
+        .unwrap();
+        let handoff = handshake_orchestrator(server, Duration::from_secs(5)).unwrap();
+        assert_eq!(handoff.config, "{\"lang\": \"en\"}");
+        assert_eq!(handoff.stream.read_timeout().unwrap(), None);
+        // Ready is sent by whoever routes for it
+        orch.set_nonblocking(true).unwrap();
+        assert!((&orch).read(&mut [0u8; 1]).is_err());
+    }
 }
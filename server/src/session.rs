@@ -1,39 +1,553 @@
 use anyhow::{Context, Result};
+use std::collections::{BTreeMap, VecDeque};
 use std::io::{BufReader, BufWriter, Write};
-use std::net::{Shutdown, TcpStream};
+use std::net::Shutdown;
 use std::os::unix::net::UnixStream;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::ScopedJoinHandle;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, RecvError, Sender, TrySendError};
 
 use space_lt_common::protocol::{
-    ClientMsg, OrchestratorMsg, ServerMsg, is_disconnect, read_client_msg, read_orchestrator_msg,
+    ClientMsg, OrchestratorMsg, RETRYABLE_ERROR_PREFIX, ServerMsg, TurnStats, is_disconnect,
+    read_client_msg, read_orchestrator_msg, split_language_tag, tag_language,
     write_orchestrator_msg, write_server_msg,
 };
-use space_lt_common::{debug, info, warn};
-
-use crate::transcribe::Transcriber;
-use crate::tts::TtsEngine;
+use space_lt_common::reply_text::sanitize_reply;
+use space_lt_common::transport::Transport;
+use space_lt_common::{debug, info, profile, warn};
+
+use crate::session_log::{Event, Outcome, SessionLog};
+use crate::stats::SessionStats;
+use crate::transcribe::{
+    SILENCE_THRESHOLD, TRIM_PADDING_MS, Transcriber, TranscriptFilter, trim_silence,
+};
+use crate::tts::{self, TtsEngine};
+use crate::tts_text::normalize_for_tts;
+use crate::wav_dump::AudioDump;
 
-/// Number of i16 samples per TtsAudioChunk (250ms at 16kHz).
+/// Number of i16 samples per TtsAudioChunk by default (250ms at 16kHz).
 const TTS_CHUNK_SIZE: usize = 4000;
 
 /// Crossfade length in samples for sentence boundaries (10ms at 16kHz).
 const CROSSFADE_LEN: usize = 160;
 
+/// Longest text accepted in a `SpeakWord` request (a word or a short phrase).
+const MAX_SPOKEN_WORD_LEN: usize = 48;
+
+/// Pronunciations kept per session for repeated `SpeakWord` requests.
+const WORD_CACHE_SIZE: usize = 32;
+
+/// Sequence numbers of a client's last NumberedSegments kept to spot a copy.
+const RECENT_SEGMENTS: usize = 64;
+
+/// A client that connected while a session was already running, along with
+/// its answer to the takeover prompt.
+pub struct ClientHandoff {
+    pub stream: Transport,
+    pub take_over: bool,
+}
+
+/// An orchestrator that has sent SessionStart and waits for Ready.
+pub struct OrchestratorHandoff {
+    pub stream: UnixStream,
+    /// The configuration it sent with SessionStart.
+    pub config: String,
+}
+
+/// Reasons sent to the client in `ServerMsg::SessionEnded`.
+pub const END_REASON_ORCHESTRATOR: &str = "The orchestrator ended the session";
+pub const END_REASON_TAKEOVER: &str = "Another client took over the session";
+pub const END_REASON_FRESH_START: &str = "Another client started a fresh session";
+pub const END_REASON_SHUTDOWN: &str = "The server is shutting down";
+pub const END_REASON_IDLE: &str = "The session was idle for too long";
+
+/// How long an idle session runs on after its warning.
+const IDLE_GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+/// Shown to the client while the session waits for a new orchestrator.
+pub const STATUS_ORCHESTRATOR_RESTARTING: &str = "Conversation engine restarting\u{2026}";
+/// Sent (retryable) for a turn lost while the orchestrator was gone.
+pub const ERR_ORCHESTRATOR_RESTARTING: &str =
+    "The conversation engine is restarting, your sentence was not sent";
+
+/// Shown to the client for a segment under `--min-segment-ms` (a stray hotkey tap).
+pub const STATUS_SEGMENT_TOO_SHORT: &str = "Too short, ignored";
+
+/// Shown to the client for a transcription the [`TranscriptFilter`] dropped.
+pub const STATUS_NOT_CAUGHT: &str = "Didn't catch that \u{2014} please repeat";
+
+/// Long segments are transcribed in windows of whisper's own length.
+const SEGMENT_WINDOW: usize = 30 * 16000;
+
+/// Errors sent to the orchestrator for messages its session state does not allow.
+pub const ERR_SESSION_NOT_STARTED: &str = "session not started";
+pub const ERR_SESSION_ALREADY_STARTED: &str = "session already started";
+
+/// Session lifecycle of an orchestrator link, as the server sees it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrchestratorState {
+    /// Connected, waiting for SessionStart.
+    AwaitingStart,
+    /// SessionStart was answered with Ready; messages are routed.
+    Active,
+    /// SessionEnd was received; only a new SessionStart is accepted.
+    Ended,
+}
+
+/// What to do with an orchestrator message, see [`OrchestratorState::on_message`].
+#[derive(Debug, PartialEq)]
+pub enum OrchestratorVerdict {
+    /// Start the session: answer Ready.
+    Start,
+    /// Route the message as usual.
+    Route,
+    /// End the session.
+    End,
+    /// Answer `ServerMsg::Error` with this reason and ignore the message.
+    Reject(&'static str),
+}
+
+impl OrchestratorState {
+    /// Next state and verdict for `msg`. Rejected messages leave the state unchanged.
+    pub fn on_message(self, msg: &OrchestratorMsg) -> (Self, OrchestratorVerdict) {
+        use OrchestratorState::*;
+        use OrchestratorVerdict::*;
+        match (self, msg) {
+            (AwaitingStart | Ended, OrchestratorMsg::SessionStart(_)) => (Active, Start),
+            (AwaitingStart | Ended, _) => (self, Reject(ERR_SESSION_NOT_STARTED)),
+            (Active, OrchestratorMsg::SessionStart(_)) => {
+                (Active, Reject(ERR_SESSION_ALREADY_STARTED))
+            }
+            (Active, OrchestratorMsg::SessionEnd) => (Ended, End),
+            (Active, _) => (Active, Route),
+        }
+    }
+}
+
+/// Per-exchange timings: stamped by `stt_router` when a turn is forwarded and
+/// completed by `tts_router` when the reply's first audio goes out. Reported as
+/// `TurnStats` only to a client that sent `EnableTimings`.
+#[derive(Default)]
+struct TurnTiming {
+    enabled: AtomicBool,
+    current: Mutex<Option<TurnTimer>>,
+}
+
+struct TurnTimer {
+    received: Instant,
+    forwarded: Instant,
+    /// Time to the orchestrator's first answer (feedback or reply).
+    llm: Option<Duration>,
+}
+
+impl TurnTiming {
+    fn lock(&self) -> MutexGuard<'_, Option<TurnTimer>> {
+        self.current.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A turn that reached the server at `received` is being forwarded now.
+    fn forwarded(&self, received: Instant) {
+        *self.lock() = Some(TurnTimer {
+            received,
+            forwarded: Instant::now(),
+            llm: None,
+        });
+    }
+
+    /// The orchestrator answered; only the first answer of a turn counts, so a
+    /// feedback choice the user thinks over is not billed to the LLM.
+    fn answered(&self) {
+        if let Some(timer) = self.lock().as_mut()
+            && timer.llm.is_none()
+        {
+            timer.llm = Some(timer.forwarded.elapsed());
+        }
+    }
+
+    /// Close the pending turn. Returns its stats when the reply received at
+    /// `reply_at` produced audio and the client asked for timings.
+    fn finish(&self, reply_at: Instant, first_audio: Option<Instant>) -> Option<TurnStats> {
+        let timer = self.lock().take()?;
+        let first_audio = first_audio?;
+        if !self.enabled.load(Ordering::SeqCst) {
+            return None;
+        }
+        let stt = timer.forwarded.duration_since(timer.received);
+        let llm = timer
+            .llm
+            .unwrap_or_else(|| reply_at.duration_since(timer.forwarded));
+        let tts = first_audio.duration_since(reply_at);
+        Some(TurnStats {
+            stt_ms: millis(stt),
+            llm_ms: millis(llm),
+            tts_ms: millis(tts),
+            first_audio_ms: millis(stt + llm + tts),
+        })
+    }
+}
+
+/// The audio segments stt_router accepts (`--min-segment-ms`, `--max-segment-ms`,
+/// `--long-segments`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentLimits {
+    /// Shorter segments are dropped: whisper makes up words for them.
+    pub min: Duration,
+    /// Longer segments are split, or refused when `split_long` is off.
+    pub max: Duration,
+    /// Transcribe a segment over `max` in [`SEGMENT_WINDOW`]s, one after the
+    /// other, as one turn.
+    pub split_long: bool,
+}
+
+impl Default for SegmentLimits {
+    fn default() -> Self {
+        Self {
+            min: Duration::from_millis(300),
+            max: Duration::from_secs(60),
+            split_long: true,
+        }
+    }
+}
+
+/// How reply audio is cut into TtsAudioChunks (`--tts-chunk-ms`,
+/// `--tts-first-chunk-ms`), in samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TtsChunks {
+    pub size: usize,
+    /// A smaller first chunk of each reply, so playback starts sooner.
+    pub first: Option<usize>,
+}
+
+impl Default for TtsChunks {
+    fn default() -> Self {
+        Self {
+            size: TTS_CHUNK_SIZE,
+            first: None,
+        }
+    }
+}
+
+/// How a session runs, from the server's command line. The default is a bare
+/// session: no idle timeout, no log, reply audio neither paced nor cached.
+pub struct SessionOptions {
+    /// A session without client activity gets a warning (StatusNotification),
+    /// then is ended like a shutdown.
+    pub idle_timeout: Option<Duration>,
+    /// Audio segments arriving while a reply is streamed are taken for the TTS
+    /// picked up by the microphone and dropped, unless the client interrupted
+    /// the reply (barge-in).
+    pub echo_guard: bool,
+    /// Reply audio is paced to stay this far ahead of the client's playback;
+    /// without it, it is sent as fast as the link allows.
+    pub tts_lead: Option<Duration>,
+    /// Reply sentences are cached, up to this much audio, so a phrase said
+    /// again is not synthesized again.
+    pub tts_cache_bytes: usize,
+    pub tts_chunks: TtsChunks,
+    /// The session's turns, replies, feedback and exchange outcomes are logged
+    /// to a new file in this directory.
+    pub session_log: Option<PathBuf>,
+    /// Audio segments outside them are dropped, split or refused.
+    pub segment_limits: SegmentLimits,
+    /// Transcriptions it rejects are not forwarded.
+    pub transcript_filter: TranscriptFilter,
+    /// Whisper is given the last lines of the conversation.
+    pub stt_context: bool,
+    /// Each audio segment is saved with its transcription.
+    pub audio_dump: Option<AudioDump>,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            idle_timeout: None,
+            echo_guard: true,
+            tts_lead: None,
+            tts_cache_bytes: 0,
+            tts_chunks: TtsChunks::default(),
+            session_log: None,
+            segment_limits: SegmentLimits::default(),
+            transcript_filter: TranscriptFilter::default(),
+            stt_context: false,
+            audio_dump: None,
+        }
+    }
+}
+
+/// The client's exchange in flight, for `CancelExchange`: client_reader counts
+/// the cancels, stt_router marks turns forwarded, tts_router drops the answers
+/// of cancelled ones.
+#[derive(Default)]
+struct Exchange {
+    /// Bumped by each CancelExchange: a turn read before the bump is dropped.
+    cancels: AtomicU64,
+    replies: Mutex<Replies>,
+}
+
+#[derive(Default)]
+struct Replies {
+    /// A forwarded turn waits for the orchestrator's first answer.
+    pending: bool,
+    /// Answers still to come for cancelled turns (the orchestrator answers in order).
+    to_drop: u32,
+}
+
+impl Exchange {
+    fn replies(&self) -> MutexGuard<'_, Replies> {
+        self.replies.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn cancels(&self) -> u64 {
+        self.cancels.load(Ordering::SeqCst)
+    }
+
+    /// Whether a turn read when `cancels` was `epoch` has been cancelled since.
+    fn cancelled_since(&self, epoch: u64) -> bool {
+        self.cancels() != epoch
+    }
+
+    fn forwarded(&self) {
+        self.replies().pending = true;
+    }
+
+    /// Cancel the exchange in flight. Returns `true` when its turn already
+    /// reached the orchestrator, whose answer is then dropped.
+    fn cancel(&self) -> bool {
+        self.cancels.fetch_add(1, Ordering::SeqCst);
+        let mut replies = self.replies();
+        if !std::mem::take(&mut replies.pending) {
+            return false;
+        }
+        replies.to_drop += 1;
+        true
+    }
+
+    /// The orchestrator answered a turn (feedback or reply): whether to pass it on.
+    fn answered(&self) -> bool {
+        let mut replies = self.replies();
+        if replies.to_drop > 0 {
+            replies.to_drop -= 1;
+            return false;
+        }
+        replies.pending = false;
+        true
+    }
+
+    /// A new orchestrator owes no answers.
+    fn reset(&self) {
+        *self.replies() = Replies::default();
+    }
+}
+
+/// Lines of the conversation kept for `--stt-context`.
+const CONTEXT_LINES: usize = 6;
+/// Whisper's prompt holds about 224 tokens: the context keeps its latest
+/// characters under this.
+const CONTEXT_MAX_CHARS: usize = 600;
+
+/// `--stt-context`: the last lines of the conversation, turns (stt_router)
+/// and replies (tts_router), given to whisper before each transcription so
+/// names and words already said are heard right.
+#[derive(Default)]
+struct ConversationContext {
+    lines: Mutex<VecDeque<String>>,
+}
+
+impl ConversationContext {
+    fn lines(&self) -> MutexGuard<'_, VecDeque<String>> {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn push(&self, line: &str) {
+        let mut lines = self.lines();
+        if lines.len() == CONTEXT_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line.trim().to_string());
+    }
+
+    /// The kept lines, oldest first, cut at a word to the last
+    /// [`CONTEXT_MAX_CHARS`].
+    fn text(&self) -> String {
+        let text = self
+            .lines()
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let chars = text.chars().count();
+        if chars <= CONTEXT_MAX_CHARS {
+            return text;
+        }
+        let (start, _) = text.char_indices().nth(chars - CONTEXT_MAX_CHARS).unwrap();
+        let tail = &text[start..];
+        match tail.split_once(' ') {
+            Some((_, words)) => words.to_string(),
+            None => tail.to_string(),
+        }
+    }
+}
+
+/// `--idle-timeout`: a session whose client does nothing for `timeout` is
+/// warned, then ended after [`IDLE_GRACE_PERIOD`].
+///
+/// Activity is what the user does (speaking, typing, feedback choices, pause
+/// and resume): a paused session still goes idle.
+struct IdleTimer {
+    timeout: Duration,
+    last_activity: Mutex<Instant>,
+    warned: AtomicBool,
+}
+
+/// What an idle check calls for.
+#[derive(Debug, PartialEq)]
+enum IdleAction {
+    None,
+    Warn,
+    End,
+}
+
+impl IdleTimer {
+    fn new(timeout: Duration, now: Instant) -> Self {
+        Self {
+            timeout,
+            last_activity: Mutex::new(now),
+            warned: AtomicBool::new(false),
+        }
+    }
+
+    fn activity(&self, now: Instant) {
+        *self.last_activity.lock().unwrap_or_else(|e| e.into_inner()) = now;
+        self.warned.store(false, Ordering::SeqCst);
+    }
+
+    /// `Warn` once when the timeout is reached, `End` when the grace period
+    /// after it is over too.
+    fn check(&self, now: Instant) -> IdleAction {
+        let last = *self.last_activity.lock().unwrap_or_else(|e| e.into_inner());
+        let idle = now.saturating_duration_since(last);
+        if idle >= self.timeout + IDLE_GRACE_PERIOD {
+            IdleAction::End
+        } else if idle >= self.timeout && !self.warned.swap(true, Ordering::SeqCst) {
+            IdleAction::Warn
+        } else {
+            IdleAction::None
+        }
+    }
+
+    fn warning(&self) -> String {
+        format!(
+            "No activity for {} min: the session ends in {}s unless you speak",
+            self.timeout.as_secs() / 60,
+            IDLE_GRACE_PERIOD.as_secs()
+        )
+    }
+}
+
+fn millis(d: Duration) -> u32 {
+    d.as_millis().min(u32::MAX as u128) as u32
+}
+
+/// What a session's threads share. A takeover swaps the stream inside
+/// `client_writer`, a new orchestrator the one inside `orchestrator_writer`;
+/// everything else lasts as long as the session.
+struct SessionShared<'a> {
+    /// stt_router sends "You: ..." display text, tts_router "AI: ..." display
+    /// text and the TTS audio.
+    client_writer: Mutex<BufWriter<Transport>>,
+    /// stt_router forwards the client's turns, tts_router answers
+    /// out-of-order orchestrator messages with an error.
+    orchestrator_writer: Mutex<BufWriter<UnixStream>>,
+    /// Set by client_reader on PauseRequest, checked by the routers.
+    paused: AtomicBool,
+    /// Bumped by each PauseRequest: segments read before it are dropped.
+    pauses: AtomicU64,
+    /// Set while the orchestrator is gone: stt_router drops the client's turns.
+    orchestrator_down: AtomicBool,
+    /// Set on InterruptTts, checked between chunks and by the synthesis threads.
+    tts_interrupted: Arc<AtomicBool>,
+    /// Set by tts_router while it streams a reply, for stt_router's echo guard.
+    tts_active: AtomicBool,
+    /// stt_router starts a turn, tts_router reports it.
+    turn_timing: TurnTiming,
+    /// The exchange in flight, which the client may cancel.
+    exchange: Exchange,
+    /// The session's TTS voice (index into `tts.voices()`), set on SetVoice.
+    voice: AtomicUsize,
+    /// Client activity recorded by the readers, checked by run_session.
+    idle: Option<IdleTimer>,
+    /// Turns and replies, for whisper's prompt.
+    context: Option<ConversationContext>,
+    log: SessionLog,
+    audio_dump: Option<&'a AudioDump>,
+    stats: &'a SessionStats,
+}
+
+/// Why `tts_router` stopped reading the orchestrator.
+#[derive(Debug, PartialEq)]
+enum OrchestratorExit {
+    /// SessionEnd: the session is over.
+    SessionEnd,
+    /// The link dropped without a SessionEnd (a crash, a Ctrl+C): the client
+    /// is kept until the next orchestrator connects.
+    Lost,
+}
+
+/// Why a session stopped routing.
+pub enum SessionOutcome {
+    /// A connection closed, an error occurred, or the orchestrator sent SessionEnd.
+    Ended,
+    /// `stop` was set (server shutdown); the client and the orchestrator were
+    /// told before the teardown.
+    Shutdown,
+    /// A new client chose to start fresh: the old session was torn down and the
+    /// new client now waits for the next orchestrator.
+    StartFresh(Transport),
+    /// The `--idle-timeout` ran out; the client and the orchestrator were told
+    /// and the server can take a new client.
+    Idle,
+}
+
 /// Run the message routing session between a TCP client and a Unix socket orchestrator.
 ///
 /// Spawns two worker threads:
 /// - stt_router: reads ClientMsg from TCP → transcribes → writes TranscribedText to Unix
 /// - tts_router: reads OrchestratorMsg from Unix → synthesizes TTS → writes TtsAudioChunk to TCP
 ///
-/// Returns when either connection closes or an error occurs.
+/// Clients arriving on `handoffs` either take over the running session (the old
+/// client is disconnected and the orchestrator link is kept) or end it so a fresh
+/// one can start.
+///
+/// An orchestrator that disconnects without SessionEnd does not end the session:
+/// the client is told the conversation engine is restarting, its turns are
+/// dropped with a retryable error, and routing resumes with the next
+/// orchestrator on `orchestrators`, which is sent Ready once it is routed.
+///
+/// The rest of its behavior is set by `options` (see [`SessionOptions`]).
+///
+/// Every deliberate teardown (orchestrator SessionEnd, takeover, fresh start,
+/// idle timeout, `stop`) first sends the affected client a `SessionEnded` with
+/// the reason.
+///
+/// Returns when the client disconnects, the orchestrator ends the session, an
+/// error occurs, a fresh start is requested, the session goes idle, or `stop`
+/// is set.
+#[allow(clippy::too_many_arguments)]
 pub fn run_session(
-    transcriber: Box<dyn Transcriber>,
-    tts: Box<dyn TtsEngine>,
-    tcp_stream: TcpStream,
+    transcriber: &mut dyn Transcriber,
+    tts: Arc<dyn TtsEngine>,
+    tcp_stream: Transport,
     unix_stream: UnixStream,
-) -> Result<()> {
+    handoffs: &Receiver<ClientHandoff>,
+    orchestrators: &Receiver<OrchestratorHandoff>,
+    stop: &AtomicBool,
+    options: &SessionOptions,
+    stats: &SessionStats,
+) -> Result<SessionOutcome> {
     // Clone streams for split read/write across threads
     let tcp_for_read = tcp_stream
         .try_clone()
@@ -43,212 +557,1327 @@ pub fn run_session(
         .context("cloning Unix stream for reader")?;
 
     // Keep clones for shutdown: shutdown() unblocks threads stuck on blocking reads
-    let tcp_cleanup = tcp_stream
+    let mut tcp_cleanup = tcp_stream
         .try_clone()
         .context("cloning TCP stream for cleanup")?;
-    let unix_cleanup = unix_stream
+    let mut unix_cleanup = unix_stream
         .try_clone()
         .context("cloning Unix stream for cleanup")?;
 
-    // tcp_for_read → reader for stt_router
-    // unix_stream → writer for stt_router, unix_for_read → reader for tts_router
-    // tcp_stream → shared BufWriter for both threads (display text + TTS audio)
-
-    // Shared TCP writer: stt_router sends "You: ..." display text,
-    // tts_router sends "AI: ..." display text + TTS audio chunks
-    let client_writer = Arc::new(Mutex::new(BufWriter::new(tcp_stream)));
-    let client_writer_stt = client_writer.clone();
-
-    // Shared pause state between stt_router and tts_router
-    let paused = Arc::new(AtomicBool::new(false));
-    let paused_stt = paused.clone();
-    let paused_tts = paused;
-
-    // Shared TTS interrupt flag: stt_router sets on InterruptTts, tts_router checks between chunks
-    let tts_interrupted = Arc::new(AtomicBool::new(false));
-    let interrupted_stt = tts_interrupted.clone();
-    let interrupted_tts = tts_interrupted;
-
-    let stt_handle = std::thread::Builder::new()
-        .name("stt_router".into())
-        .spawn(move || {
-            stt_router(
-                tcp_for_read,
-                unix_stream,
-                transcriber,
-                paused_stt,
-                client_writer_stt,
-                interrupted_stt,
-            )
-        })?;
-
-    let tts: Arc<dyn TtsEngine> = Arc::from(tts);
-    let tts_handle = std::thread::Builder::new()
-        .name("tts_router".into())
-        .spawn(move || {
-            tts_router(
-                unix_for_read,
-                client_writer,
-                tts,
-                paused_tts,
-                interrupted_tts,
-            )
-        })?;
+    // tcp_for_read → reader for stt_router, unix_for_read → reader for tts_router,
+    // tcp_stream and unix_stream → the writers both routers share
+
+    // A session goes on without the log it could not open
+    let log = match options
+        .session_log
+        .as_deref()
+        .map(SessionLog::create)
+        .transpose()
+    {
+        Ok(log) => log.unwrap_or_else(SessionLog::disabled),
+        Err(e) => {
+            warn!("[server] No session log: {e:#}");
+            SessionLog::disabled()
+        }
+    };
+
+    let shared = &SessionShared {
+        client_writer: Mutex::new(BufWriter::new(tcp_stream)),
+        orchestrator_writer: Mutex::new(BufWriter::new(
+            unix_stream
+                .try_clone()
+                .context("cloning Unix stream for writer")?,
+        )),
+        paused: AtomicBool::new(false),
+        pauses: AtomicU64::new(0),
+        orchestrator_down: AtomicBool::new(false),
+        tts_interrupted: Arc::new(AtomicBool::new(false)),
+        tts_active: AtomicBool::new(false),
+        turn_timing: TurnTiming::default(),
+        exchange: Exchange::default(),
+        voice: AtomicUsize::new(0),
+        idle: options
+            .idle_timeout
+            .map(|timeout| IdleTimer::new(timeout, Instant::now())),
+        context: options.stt_context.then(ConversationContext::default),
+        log,
+        audio_dump: options.audio_dump.as_ref(),
+        stats,
+    };
+
+    // Only one stt_router runs at a time, but a takeover respawns it with the same model
+    let transcriber = Mutex::new(transcriber);
+
+    std::thread::scope(|s| {
+        let spawn_stt = |tcp_read: Transport| -> Result<ScopedJoinHandle<'_, Result<()>>> {
+            let transcriber = &transcriber;
+            // stt_router answers SpeakWord requests itself
+            let tts = tts.as_ref();
+
+            // The client is read ahead of stt_router, so a CancelExchange takes
+            // effect while a segment is still being transcribed
+            let (messages_tx, messages) = crossbeam_channel::bounded(CLIENT_QUEUE);
+            std::thread::Builder::new()
+                .name("client_reader".into())
+                .spawn_scoped(s, move || client_reader(tcp_read, messages_tx, shared))?;
+            Ok(std::thread::Builder::new()
+                .name("stt_router".into())
+                .spawn_scoped(s, move || {
+                    stt_router(messages, shared, transcriber, tts, options)
+                })?)
+        };
+
+        // A new orchestrator respawns tts_router on its stream
+        let spawn_tts =
+            |unix_read: UnixStream| -> Result<ScopedJoinHandle<'_, Result<OrchestratorExit>>> {
+                let tts = tts.clone();
+                Ok(std::thread::Builder::new()
+                    .name("tts_router".into())
+                    .spawn_scoped(s, move || tts_router(unix_read, shared, tts, options))?)
+            };
+
+        let mut stt_handle = spawn_stt(tcp_for_read)?;
+        // None while waiting for a new orchestrator
+        let mut tts_handle = Some(spawn_tts(unix_for_read)?);
+
+        // Wait for either thread to finish (connection close or error) or a new client
+        let outcome = loop {
+            if stt_handle.is_finished() {
+                break SessionOutcome::Ended;
+            }
+            if let Some(handle) = tts_handle.take_if(|h| h.is_finished()) {
+                let exit = log_router_exit("tts_router", handle.join(), stats);
+                if exit != Some(OrchestratorExit::Lost) {
+                    break SessionOutcome::Ended;
+                }
+                warn!("[server] Orchestrator lost, keeping the client until a new one connects");
+                shared.orchestrator_down.store(true, Ordering::SeqCst);
+                shared.exchange.reset();
+                // The lost tts_router may have been streaming
+                shared.tts_active.store(false, Ordering::SeqCst);
+                stats.orchestrator_connected.store(false, Ordering::SeqCst);
+                notify_orchestrator_lost(&shared.client_writer, &shared.turn_timing);
+            }
+            if stop.load(Ordering::SeqCst) {
+                info!("[server] Shutdown requested, ending the session");
+                // A reply being spoken stops at the next chunk and ends with
+                // TtsEnd, which the writer lock puts before SessionEnded
+                shared.tts_interrupted.store(true, Ordering::SeqCst);
+                notify_session_ended(&shared.client_writer, END_REASON_SHUTDOWN);
+                notify_orchestrator_session_end(&shared.orchestrator_writer);
+                break SessionOutcome::Shutdown;
+            }
+            if let Some(idle) = &shared.idle {
+                match idle.check(Instant::now()) {
+                    IdleAction::None => {}
+                    IdleAction::Warn => {
+                        info!("[server] Session idle, warning the client");
+                        let mut w = shared
+                            .client_writer
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner());
+                        let _ = write_server_msg(
+                            &mut *w,
+                            &ServerMsg::StatusNotification(idle.warning()),
+                        );
+                    }
+                    IdleAction::End => {
+                        info!("[server] Session idle for too long, ending it");
+                        shared.tts_interrupted.store(true, Ordering::SeqCst);
+                        notify_session_ended(&shared.client_writer, END_REASON_IDLE);
+                        notify_orchestrator_session_end(&shared.orchestrator_writer);
+                        break SessionOutcome::Idle;
+                    }
+                }
+            }
+
+            match handoffs.try_recv() {
+                Ok(ClientHandoff {
+                    stream,
+                    take_over: true,
+                }) => {
+                    info!("[server] Client takeover: disconnecting the previous client");
+                    notify_session_ended(&shared.client_writer, END_REASON_TAKEOVER);
+                    let _ = tcp_cleanup.shutdown(Shutdown::Both);
+                    log_router_exit("stt_router", stt_handle.join(), stats);
+
+                    let tcp_read = stream
+                        .try_clone()
+                        .context("cloning TCP stream for reader")?;
+                    tcp_cleanup = stream
+                        .try_clone()
+                        .context("cloning TCP stream for cleanup")?;
+                    *shared
+                        .client_writer
+                        .lock()
+                        .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))? =
+                        BufWriter::new(stream);
+                    // The new client opts in to timings on its own
+                    shared.turn_timing.enabled.store(false, Ordering::SeqCst);
+                    stt_handle = spawn_stt(tcp_read)?;
+                    info!("[server] New client bound to the running session");
+                }
+                Ok(ClientHandoff {
+                    stream,
+                    take_over: false,
+                }) => {
+                    info!("[server] New client asked for a fresh session, ending this one");
+                    notify_session_ended(&shared.client_writer, END_REASON_FRESH_START);
+                    break SessionOutcome::StartFresh(stream);
+                }
+                Err(_) => {}
+            }
+
+            if tts_handle.is_none()
+                && let Ok(OrchestratorHandoff {
+                    stream: unix_stream,
+                    config,
+                }) = orchestrators.try_recv()
+            {
+                info!("[server] New orchestrator connected: {config}");
+                let unix_read = unix_stream
+                    .try_clone()
+                    .context("cloning Unix stream for reader")?;
+                let new_cleanup = unix_stream
+                    .try_clone()
+                    .context("cloning Unix stream for cleanup")?;
+                // Held until the session is back, so a turn sent right after
+                // Ready is not dropped as if the orchestrator were still away
+                let mut writer = shared
+                    .orchestrator_writer
+                    .lock()
+                    .map_err(|e| anyhow::anyhow!("orchestrator writer poisoned: {e}"))?;
+                match write_server_msg(&mut &unix_stream, &ServerMsg::Ready) {
+                    Ok(()) => {
+                        unix_cleanup = new_cleanup;
+                        *writer = BufWriter::new(unix_stream);
+                        shared.orchestrator_down.store(false, Ordering::SeqCst);
+                        drop(writer);
+                        tts_handle = Some(spawn_tts(unix_read)?);
+                        stats.orchestrator_connected.store(true, Ordering::SeqCst);
+                        info!("[server] Session resumed with the new orchestrator");
+                    }
+                    Err(e) => warn!("[server] Could not send Ready to the new orchestrator: {e:#}"),
+                }
+            }
+
+            std::thread::sleep(Duration::from_millis(100));
+        };
+
+        // Shutdown streams to unblock the remaining thread stuck on a blocking read
+        let _ = tcp_cleanup.shutdown(Shutdown::Both);
+        let _ = unix_cleanup.shutdown(Shutdown::Both);
+
+        // Join both threads
+        log_router_exit("stt_router", stt_handle.join(), stats);
+        if let Some(handle) = tts_handle {
+            log_router_exit("tts_router", handle.join(), stats);
+        }
 
-    // Wait for either thread to finish (connection close or error)
+        info!("[server] Session ended");
+        Ok(outcome)
+    })
+}
+
+/// SessionStart handshake: wait for SessionStart on the Unix socket and return
+/// its configuration. Anything sent before it is answered with an error and
+/// dropped. Ready is left to whoever routes for the orchestrator.
+///
+/// Reads the raw stream (not a BufReader) to avoid read-ahead stealing bytes
+/// from the fd that run_session's BufReaders would then miss.
+pub fn await_session_start(unix_stream: &UnixStream) -> Result<String> {
+    let mut state = OrchestratorState::AwaitingStart;
     loop {
-        if stt_handle.is_finished() || tts_handle.is_finished() {
-            break;
+        let msg = read_orchestrator_msg(&mut &*unix_stream)
+            .context("reading SessionStart from orchestrator")?;
+        let verdict;
+        (state, verdict) = state.on_message(&msg);
+        match (verdict, msg) {
+            (OrchestratorVerdict::Start, OrchestratorMsg::SessionStart(config)) => {
+                return Ok(config);
+            }
+            (OrchestratorVerdict::Reject(reason), msg) => {
+                warn!("[server] Rejecting orchestrator message ({reason}): {msg:?}");
+                write_server_msg(&mut &*unix_stream, &ServerMsg::Error(reason.to_string()))?;
+            }
+            // Only SessionStart gets past a session that has not started
+            (verdict, msg) => {
+                anyhow::bail!("Unexpected {verdict:?} for {msg:?} before SessionStart")
+            }
         }
-        std::thread::sleep(Duration::from_millis(100));
     }
+}
 
-    // Shutdown streams to unblock the remaining thread stuck on a blocking read
-    let _ = tcp_cleanup.shutdown(Shutdown::Both);
-    let _ = unix_cleanup.shutdown(Shutdown::Both);
+/// Tell the current client why its session is being torn down.
+/// Best effort: the client may already be gone.
+fn notify_session_ended(client_writer: &Mutex<BufWriter<Transport>>, reason: &str) {
+    let mut w = client_writer.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = write_server_msg(&mut *w, &ServerMsg::SessionEnded(reason.to_string())) {
+        debug!("[server] Could not send SessionEnded: {e}");
+    }
+}
 
-    // Join both threads
-    match stt_handle.join() {
-        Ok(Ok(())) => debug!("[server] stt_router exited cleanly"),
-        Ok(Err(e)) => debug!("[server] stt_router error: {e}"),
-        Err(_) => warn!("[server] stt_router thread panicked"),
+/// Tell the client its orchestrator is gone. A turn still waiting for its
+/// reply will get none: it fails with a retryable error so the client goes
+/// back to idle.
+fn notify_orchestrator_lost(client_writer: &Mutex<BufWriter<Transport>>, turn_timing: &TurnTiming) {
+    let pending_turn = turn_timing.lock().take().is_some();
+    let mut w = client_writer.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = write_server_msg(
+        &mut *w,
+        &ServerMsg::StatusNotification(STATUS_ORCHESTRATOR_RESTARTING.to_string()),
+    );
+    if pending_turn {
+        let _ = write_server_msg(&mut *w, &orchestrator_restarting_error());
     }
-    match tts_handle.join() {
-        Ok(Ok(())) => debug!("[server] tts_router exited cleanly"),
-        Ok(Err(e)) => debug!("[server] tts_router error: {e}"),
-        Err(_) => warn!("[server] tts_router thread panicked"),
+}
+
+fn orchestrator_restarting_error() -> ServerMsg {
+    ServerMsg::Error(format!(
+        "{RETRYABLE_ERROR_PREFIX}{ERR_ORCHESTRATOR_RESTARTING}"
+    ))
+}
+
+/// Tell the orchestrator the server ended the session (SessionEnd), on
+/// shutdown or idle timeout. Best effort.
+fn notify_orchestrator_session_end(orchestrator_writer: &Mutex<BufWriter<UnixStream>>) {
+    let mut w = orchestrator_writer
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = write_orchestrator_msg(&mut *w, &OrchestratorMsg::SessionEnd) {
+        debug!("[server] Could not send SessionEnd to the orchestrator: {e}");
     }
+}
 
-    info!("[server] Session ended");
-    Ok(())
+/// Log how a router thread ended and return what it returned, if it did.
+fn log_router_exit<T>(
+    name: &str,
+    result: std::thread::Result<Result<T>>,
+    stats: &SessionStats,
+) -> Option<T> {
+    match result {
+        Ok(Ok(value)) => {
+            debug!("[server] {name} exited cleanly");
+            Some(value)
+        }
+        Ok(Err(e)) => {
+            debug!("[server] {name} error: {e}");
+            if !is_disconnect(&e) {
+                stats.record_error(&format!("{name}: {e:#}"));
+            }
+            None
+        }
+        Err(_) => {
+            warn!("[server] {name} thread panicked");
+            stats.record_error(&format!("{name} thread panicked"));
+            None
+        }
+    }
 }
 
-/// STT routing: reads ClientMsg from TCP, transcribes audio, forwards text to orchestrator.
-fn stt_router(
-    tcp_read: TcpStream,
-    unix_write: UnixStream,
-    mut transcriber: Box<dyn Transcriber>,
-    paused: Arc<AtomicBool>,
-    client_writer: Arc<Mutex<BufWriter<TcpStream>>>,
-    tts_interrupted: Arc<AtomicBool>,
+/// Write `msg` to the orchestrator. While it is gone the message is dropped.
+fn forward_to_orchestrator(
+    orchestrator_writer: &Mutex<BufWriter<UnixStream>>,
+    orchestrator_down: &AtomicBool,
+    msg: &OrchestratorMsg,
 ) -> Result<()> {
-    let mut reader = BufReader::new(tcp_read);
-    let mut writer = BufWriter::new(unix_write);
+    if orchestrator_down.load(Ordering::SeqCst) {
+        debug!("[server] No orchestrator, dropping {msg:?}");
+        return Ok(());
+    }
+    let mut w = orchestrator_writer
+        .lock()
+        .map_err(|e| anyhow::anyhow!("orchestrator writer poisoned: {e}"))?;
+    match write_orchestrator_msg(&mut *w, msg) {
+        // tts_router sees the same disconnect and the session waits for
+        // the next orchestrator
+        Err(e) if is_disconnect(&e) => {
+            warn!("[server] Orchestrator gone, dropping {msg:?}");
+            Ok(())
+        }
+        result => result,
+    }
+}
 
+/// Client messages read ahead of stt_router; one arriving while it is that far
+/// behind is refused. The messages client_reader acts on itself never wait.
+const CLIENT_QUEUE: usize = 16;
+
+/// A client message as client_reader read it.
+struct Received {
+    msg: ClientMsg,
+    /// Cancels seen before it (see [`Exchange::cancelled_since`]).
+    epoch: u64,
+    /// PauseRequests seen before it.
+    pauses: u64,
+    /// Read while the session was paused.
+    paused: bool,
+}
+
+/// Read the client's messages for stt_router. CancelExchange, InterruptTts,
+/// PauseRequest and ResumeRequest are acted on at once, however far behind
+/// stt_router is: turns read before a cancel are dropped, and a turn the
+/// orchestrator already has is cancelled there too, with a TtsEnd ending the
+/// client's wait for its reply.
+///
+/// Stops at the first read error, which is passed on.
+fn client_reader(tcp_read: Transport, messages: Sender<Result<Received>>, shared: &SessionShared) {
+    let SessionShared {
+        ref client_writer,
+        ref orchestrator_writer,
+        ref paused,
+        ref pauses,
+        ref orchestrator_down,
+        ref tts_interrupted,
+        ref turn_timing,
+        ref exchange,
+        ref idle,
+        ref log,
+        ..
+    } = *shared;
+    let mut reader = BufReader::new(tcp_read);
     loop {
         let msg = match read_client_msg(&mut reader) {
             Ok(msg) => msg,
             Err(e) => {
-                if is_disconnect(&e) {
-                    info!("[server] Client disconnected");
-                    break;
-                }
-                return Err(e.context("reading client message"));
+                // The last one: worth waiting for
+                let _ = messages.send(Err(e));
+                break;
             }
         };
-
+        if let Some(idle) = idle
+            && matches!(msg, ClientMsg::PauseRequest | ClientMsg::ResumeRequest)
+        {
+            idle.activity(Instant::now());
+        }
         match msg {
-            ClientMsg::AudioSegment(samples) => {
-                if paused.load(Ordering::SeqCst) {
-                    debug!(
-                        "[server] Paused — dropping audio segment ({} samples)",
-                        samples.len()
-                    );
-                    continue;
-                }
-
-                debug!(
-                    "[server] Audio segment: {} samples ({:.0}ms)",
-                    samples.len(),
-                    samples.len() as f64 / 16.0
-                );
-
-                let text = transcriber
-                    .transcribe(&samples)
-                    .context("transcribing audio")?;
-
-                if !text.is_empty() {
-                    debug!("[server] Transcribed: \"{}\"", text);
-                    // Display transcription on client
-                    if let Ok(mut w) = client_writer.lock() {
-                        let _ = write_server_msg(&mut *w, &ServerMsg::Text(format!("You: {text}")));
+            ClientMsg::CancelExchange => {
+                info!("[server] Exchange cancelled by the client");
+                if exchange.cancel() {
+                    log.record(Event::Exchange(Outcome::Cancelled));
+                    turn_timing.lock().take();
+                    if let Err(e) = forward_to_orchestrator(
+                        orchestrator_writer,
+                        orchestrator_down,
+                        &OrchestratorMsg::CancelExchange,
+                    ) {
+                        warn!("[server] Could not cancel the exchange: {e:#}");
                     }
-                    write_orchestrator_msg(&mut writer, &OrchestratorMsg::TranscribedText(text))?;
+                    let mut w = client_writer.lock().unwrap_or_else(|e| e.into_inner());
+                    let _ = write_server_msg(&mut *w, &ServerMsg::TtsEnd);
                 }
             }
+            ClientMsg::InterruptTts => {
+                tts_interrupted.store(true, Ordering::SeqCst);
+                info!("[server] TTS interrupted by client");
+            }
             ClientMsg::PauseRequest => {
                 paused.store(true, Ordering::SeqCst);
+                pauses.fetch_add(1, Ordering::SeqCst);
                 info!("[server] Session paused");
             }
             ClientMsg::ResumeRequest => {
                 paused.store(false, Ordering::SeqCst);
                 info!("[server] Session resumed");
             }
-            ClientMsg::InterruptTts => {
-                tts_interrupted.store(true, Ordering::SeqCst);
-                info!("[server] TTS interrupted by client");
-            }
-            ClientMsg::FeedbackChoice(proceed) => {
-                info!(
-                    "[server] FeedbackChoice: {}",
-                    if proceed { "continue" } else { "retry" }
-                );
-                write_orchestrator_msg(&mut writer, &OrchestratorMsg::FeedbackChoice(proceed))?;
-            }
-            ClientMsg::SummaryRequest => {
-                info!("[server] Summary requested by client, forwarding to orchestrator");
-                write_orchestrator_msg(&mut writer, &OrchestratorMsg::SummaryRequest)?;
+            msg => {
+                let received = Received {
+                    msg,
+                    epoch: exchange.cancels(),
+                    pauses: pauses.load(Ordering::SeqCst),
+                    paused: paused.load(Ordering::SeqCst),
+                };
+                match messages.try_send(Ok(received)) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        warn!("[server] Client message queue full, dropping a message");
+                        let mut w = client_writer.lock().unwrap_or_else(|e| e.into_inner());
+                        let _ = write_server_msg(
+                            &mut *w,
+                            &ServerMsg::Error(format!(
+                                "{RETRYABLE_ERROR_PREFIX}The server is still busy with your earlier requests"
+                            )),
+                        );
+                    }
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
             }
         }
     }
-
-    Ok(())
 }
 
-/// TTS routing: reads OrchestratorMsg from Unix, synthesizes speech, streams to client.
-fn tts_router(
-    unix_read: UnixStream,
-    client_writer: Arc<Mutex<BufWriter<TcpStream>>>,
-    tts: Arc<dyn TtsEngine>,
-    paused: Arc<AtomicBool>,
-    tts_interrupted: Arc<AtomicBool>,
+/// STT routing: reads ClientMsg from TCP, acts on control messages at once
+/// and queues turns for the [`transcription_worker`], which forwards their
+/// text to the orchestrator.
+fn stt_router(
+    messages: Receiver<Result<Received>>,
+    shared: &SessionShared,
+    transcriber: &Mutex<&mut dyn Transcriber>,
+    tts: &dyn TtsEngine,
+    options: &SessionOptions,
 ) -> Result<()> {
-    let mut reader = BufReader::new(unix_read);
-
-    loop {
-        let msg = match read_orchestrator_msg(&mut reader) {
-            Ok(msg) => msg,
-            Err(e) => {
-                if is_disconnect(&e) {
-                    info!("[server] Orchestrator disconnected");
-                    break;
+    let SessionShared {
+        ref client_writer,
+        ref orchestrator_writer,
+        ref orchestrator_down,
+        ref tts_interrupted,
+        ref tts_active,
+        ref turn_timing,
+        ref exchange,
+        ref voice,
+        ref idle,
+        audio_dump,
+        ..
+    } = *shared;
+    let SessionOptions {
+        echo_guard,
+        tts_chunks,
+        segment_limits,
+        ref transcript_filter,
+        ..
+    } = *options;
+    let echo_guard = echo_guard.then_some(tts_active);
+    let forward = |msg: &OrchestratorMsg| -> Result<()> {
+        forward_to_orchestrator(orchestrator_writer, orchestrator_down, msg)
+    };
+    // A turn the orchestrator is not there to answer
+    let drop_turn = || orchestrator_absent(orchestrator_writer, orchestrator_down, client_writer);
+    let mut word_cache = WordCache::new(WORD_CACHE_SIZE);
+    let mut segments = RecentSegments::new(RECENT_SEGMENTS);
+
+    // Turns are transcribed on a worker, in order, so that control messages
+    // (pause, barge-in) are handled while whisper is busy
+    let (turns_tx, turns) = crossbeam_channel::bounded(TURN_QUEUE);
+    // Disconnected when the worker stops
+    let (worker_alive, worker_exit) = crossbeam_channel::bounded::<()>(0);
+    std::thread::scope(|s| {
+        let worker = std::thread::Builder::new()
+            .name("transcription".into())
+            .spawn_scoped(s, move || {
+                let _alive = worker_alive;
+                transcription_worker(turns, transcriber, shared, transcript_filter)
+            })?;
+
+        let queue = |turn: Turn| {
+            if let Err(TrySendError::Full(_)) = turns_tx.try_send(turn) {
+                warn!("[server] Transcription queue full, dropping a turn");
+                if let Ok(mut w) = client_writer.lock() {
+                    let _ = write_server_msg(
+                        &mut *w,
+                        &ServerMsg::Error(format!(
+                            "{RETRYABLE_ERROR_PREFIX}Still transcribing your earlier sentences"
+                        )),
+                    );
                 }
-                return Err(e.context("reading orchestrator message"));
             }
         };
 
-        match msg {
-            OrchestratorMsg::ResponseText(text) => {
-                tts_interrupted.store(false, Ordering::SeqCst);
+        let read = (|| -> Result<()> {
+            loop {
+                let read = crossbeam_channel::select! {
+                    recv(messages) -> read => read,
+                    // The worker failed: its error is returned below
+                    recv(worker_exit) -> _ => Err(RecvError),
+                };
+                let Received {
+                    msg,
+                    epoch,
+                    pauses: paused_at,
+                    paused,
+                } = match read {
+                    Ok(Ok(read)) => read,
+                    Ok(Err(e)) => {
+                        if is_disconnect(&e) {
+                            info!("[server] Client disconnected");
+                            break;
+                        }
+                        return Err(e.context("reading client message"));
+                    }
+                    // client_reader stopped: the stream was shut down
+                    Err(_) => break,
+                };
 
-                if paused.load(Ordering::SeqCst) {
-                    debug!(
-                        "[server] Paused — skipping TTS for response ({} chars)",
-                        text.len()
-                    );
-                    let mut w = client_writer
-                        .lock()
-                        .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
-                    write_server_msg(&mut *w, &ServerMsg::TtsEnd)?;
-                    continue;
+                if let ClientMsg::NumberedSegment(seq, _) = msg {
+                    // A copy is acknowledged too, so the client stops sending it
+                    if let Ok(mut w) = client_writer.lock() {
+                        let _ = write_server_msg(&mut *w, &ServerMsg::SegmentReceived(seq));
+                    }
+                    if !segments.first_time(seq) {
+                        info!("[server] Segment {seq} received twice, dropping the copy");
+                        continue;
+                    }
                 }
 
-                // Parse optional speed marker (e.g. "[SPEED:0.6] Hello")
-                let (speed, clean_text) = parse_speed_marker(&text);
-                if let Some(s) = speed {
-                    tts.set_speed(s);
-                    info!("[server] TTS speed set to {s}");
+                if let Some(idle) = idle
+                    && matches!(
+                        msg,
+                        ClientMsg::AudioSegment(_)
+                            | ClientMsg::NumberedSegment(..)
+                            | ClientMsg::TextInput(_)
+                            | ClientMsg::FeedbackChoice(_)
+                    )
+                {
+                    idle.activity(Instant::now());
+                }
+
+                match msg {
+                    ClientMsg::AudioSegment(samples) | ClientMsg::NumberedSegment(_, samples) => {
+                        // Saved as received, whatever happens to it next
+                        let dumped = audio_dump.and_then(|dump| dump.segment(&samples));
+                        if paused {
+                            debug!(
+                                "[server] Paused — dropping audio segment ({} samples)",
+                                samples.len()
+                            );
+                            continue;
+                        }
+                        if let Some(tts_active) = echo_guard
+                            && tts_active.load(Ordering::SeqCst)
+                            && !tts_interrupted.load(Ordering::SeqCst)
+                        {
+                            debug!(
+                                "[server] Reply playing — dropping audio segment as echo ({} samples)",
+                                samples.len()
+                            );
+                            continue;
+                        }
+                        if drop_turn() {
+                            continue;
+                        }
+                        if exchange.cancelled_since(epoch) {
+                            debug!("[server] Cancelled — dropping audio segment");
+                            continue;
+                        }
+                        let duration = Duration::from_millis(samples.len() as u64 / 16);
+                        if duration < segment_limits.min {
+                            info!(
+                                "[server] Segment too short ({}ms), ignored",
+                                duration.as_millis()
+                            );
+                            if let Ok(mut w) = client_writer.lock() {
+                                let _ = write_server_msg(
+                                    &mut *w,
+                                    &ServerMsg::StatusNotification(
+                                        STATUS_SEGMENT_TOO_SHORT.to_string(),
+                                    ),
+                                );
+                            }
+                            continue;
+                        }
+                        let split = duration > segment_limits.max;
+                        if split && !segment_limits.split_long {
+                            warn!(
+                                "[server] Segment too long ({}s), refused",
+                                duration.as_secs()
+                            );
+                            if let Ok(mut w) = client_writer.lock() {
+                                let _ = write_server_msg(
+                                    &mut *w,
+                                    &ServerMsg::Error(format!(
+                                        "{RETRYABLE_ERROR_PREFIX}You spoke for {}s, more than the {}s a turn can last",
+                                        duration.as_secs(),
+                                        segment_limits.max.as_secs()
+                                    )),
+                                );
+                            }
+                            continue;
+                        }
+
+                        queue(Turn::Spoken {
+                            epoch,
+                            pauses: paused_at,
+                            samples,
+                            split,
+                            received: Instant::now(),
+                            dumped,
+                        });
+                    }
+                    ClientMsg::TextInput(text) => queue(Turn::Typed {
+                        epoch,
+                        text,
+                        received: Instant::now(),
+                    }),
+                    ClientMsg::FeedbackChoice(proceed) => {
+                        info!(
+                            "[server] FeedbackChoice: {}",
+                            if proceed { "continue" } else { "retry" }
+                        );
+                        forward(&OrchestratorMsg::FeedbackChoice(proceed))?;
+                    }
+                    ClientMsg::SessionTakeover(_) => {
+                        debug!("[server] Unexpected SessionTakeover mid-session (ignoring)");
+                    }
+                    ClientMsg::CancelExchange
+                    | ClientMsg::InterruptTts
+                    | ClientMsg::PauseRequest
+                    | ClientMsg::ResumeRequest => {
+                        // Acted on by client_reader as soon as they arrive
+                    }
+                    ClientMsg::SummaryRequest => {
+                        info!("[server] Summary requested by client, forwarding to orchestrator");
+                        forward(&OrchestratorMsg::SummaryRequest)?;
+                    }
+                    ClientMsg::SpeakWord(word) => {
+                        // Answered directly: the orchestrator never sees it and the pause
+                        // gate does not apply (the client asks while idle or in feedback).
+                        let voice = voice.load(Ordering::SeqCst);
+                        speak_word(
+                            &word,
+                            tts,
+                            voice,
+                            tts_chunks,
+                            &mut word_cache,
+                            client_writer,
+                        )?;
+                    }
+                    ClientMsg::EnableTimings => {
+                        turn_timing.enabled.store(true, Ordering::SeqCst);
+                        info!("[server] Client asked for per-exchange timings");
+                    }
+                    ClientMsg::AudioInput(input) => {
+                        info!("[server] Client audio input: {input}");
+                    }
+                    ClientMsg::TranslateLast => {
+                        debug!(
+                            "[server] Translation requested by client, forwarding to orchestrator"
+                        );
+                        forward(&OrchestratorMsg::TranslateRequest)?;
+                    }
+                    ClientMsg::SimplifyLast => {
+                        debug!(
+                            "[server] Simpler rephrasing requested by client, forwarding to orchestrator"
+                        );
+                        forward(&OrchestratorMsg::SimplifyRequest)?;
+                    }
+                    ClientMsg::DisregardLast => {
+                        info!(
+                            "[server] Client disregarded its last message, forwarding to orchestrator"
+                        );
+                        forward(&OrchestratorMsg::DisregardLast)?;
+                    }
+                    ClientMsg::BranchTo(turn) => {
+                        info!(
+                            "[server] Client rewinds the conversation to turn {turn}, forwarding to orchestrator"
+                        );
+                        forward(&OrchestratorMsg::BranchTo(turn))?;
+                    }
+                    ClientMsg::ListVoices => {
+                        let mut w = client_writer
+                            .lock()
+                            .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
+                        write_server_msg(&mut *w, &ServerMsg::VoiceList(tts.voices()))?;
+                    }
+                    // Never refused: the segments after it must not be
+                    // transcribed in the old language. Waits for the segment
+                    // being transcribed at most.
+                    ClientMsg::SetLanguage(code) => {
+                        if turns_tx.send(Turn::Language(code)).is_err() {
+                            debug!("[server] Transcription worker gone, language switch dropped");
+                        }
+                    }
+                    ClientMsg::SetVoice(name) => match tts::voice_index(tts, &name) {
+                        Ok(index) => {
+                            voice.store(index, Ordering::SeqCst);
+                            // Cached pronunciations are in the old voice
+                            word_cache = WordCache::new(WORD_CACHE_SIZE);
+                            info!("[server] TTS voice set to {name}");
+                        }
+                        Err(e) => {
+                            warn!("[server] {e}");
+                            let mut w = client_writer
+                                .lock()
+                                .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
+                            write_server_msg(&mut *w, &ServerMsg::Error(e.to_string()))?;
+                        }
+                    },
+                }
+            }
+            Ok(())
+        })();
+
+        // The worker empties the queue, then stops
+        drop(turns_tx);
+        let worked = worker
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("transcription worker panicked")));
+        read.and(worked)
+    })
+}
+
+/// A turn for the transcription worker, in the order the client sent it.
+enum Turn {
+    /// An audio segment that passed stt_router's checks, read after
+    /// `pauses` PauseRequests; `split` into windows when long, `dumped` to
+    /// this WAV file with `--dump-audio`.
+    Spoken {
+        epoch: u64,
+        pauses: u64,
+        samples: Vec<i16>,
+        split: bool,
+        received: Instant,
+        dumped: Option<PathBuf>,
+    },
+    Typed {
+        epoch: u64,
+        text: String,
+        received: Instant,
+    },
+    /// SetLanguage, for the segments after it.
+    Language(String),
+}
+
+/// Turns queued behind the one being transcribed; more are refused.
+const TURN_QUEUE: usize = 4;
+
+/// Transcribe the spoken `turns` (typed ones pass as they are) and forward
+/// them to the orchestrator, one at a time. A segment queued before a pause,
+/// or whose exchange is cancelled before or while it is transcribed, is
+/// dropped. A language switch applies from the next segment on.
+fn transcription_worker(
+    turns: Receiver<Turn>,
+    transcriber: &Mutex<&mut dyn Transcriber>,
+    shared: &SessionShared,
+    transcript_filter: &TranscriptFilter,
+) -> Result<()> {
+    let SessionShared {
+        ref client_writer,
+        ref orchestrator_writer,
+        ref pauses,
+        ref orchestrator_down,
+        ref turn_timing,
+        ref exchange,
+        ref context,
+        ref log,
+        audio_dump,
+        stats,
+        ..
+    } = *shared;
+    let forward = |msg: &OrchestratorMsg| -> Result<()> {
+        forward_to_orchestrator(orchestrator_writer, orchestrator_down, msg)
+    };
+    for turn in turns {
+        match turn {
+            Turn::Spoken {
+                epoch,
+                pauses: paused_at,
+                samples,
+                split,
+                received,
+                dumped,
+            } => {
+                if exchange.cancelled_since(epoch) {
+                    debug!("[server] Cancelled — dropping queued audio segment");
+                    continue;
+                }
+                if pauses.load(Ordering::SeqCst) != paused_at {
+                    debug!("[server] Paused — dropping queued audio segment");
+                    continue;
+                }
+                let started = Instant::now();
+                debug!(
+                    "[server] Audio segment: {} samples ({:.0}ms)",
+                    samples.len(),
+                    samples.len() as f64 / 16.0
+                );
+                // The hotkey's reaction time before the speech, and the wait after it
+                let speech = trim_silence(&samples, SILENCE_THRESHOLD, TRIM_PADDING_MS);
+                if speech.len() < samples.len() {
+                    debug!(
+                        "[server] Trimmed {}ms of silence",
+                        (samples.len() - speech.len()) / 16
+                    );
+                }
+
+                let (transcribed, language) = {
+                    let mut transcriber = transcriber
+                        .lock()
+                        .map_err(|e| anyhow::anyhow!("transcriber poisoned: {e}"))?;
+                    if let Some(context) = context {
+                        transcriber.set_context(&context.text());
+                    }
+                    let transcribed = profile::time("transcription", || {
+                        if split {
+                            transcribe_in_windows(&mut **transcriber, speech)
+                                .map(|text| (text, None))
+                        } else {
+                            // What whisper has decoded so far, shown while it goes on
+                            transcriber.transcribe_with_confidence(speech, &mut |partial| {
+                                if let Ok(mut w) = client_writer.lock() {
+                                    let _ = write_server_msg(
+                                        &mut *w,
+                                        &ServerMsg::PartialTranscript(partial.to_string()),
+                                    );
+                                }
+                            })
+                        }
+                    });
+                    (transcribed, transcriber.detected_language())
+                };
+                // Unfiltered, next to its audio
+                if let Some(dump) = audio_dump
+                    && let Some(wav) = &dumped
+                {
+                    match &transcribed {
+                        Ok((text, _)) => dump.transcript(wav, text),
+                        Err(e) => dump.transcript(wav, &format!("(transcription failed: {e:#})")),
+                    }
+                }
+                let text = match transcribed {
+                    // Cancelled while whisper was at it
+                    Ok(_) if exchange.cancelled_since(epoch) => {
+                        info!("[server] Exchange cancelled, dropping its transcription");
+                        log.record(Event::Exchange(Outcome::Cancelled));
+                        continue;
+                    }
+                    Ok((text, confidence)) => {
+                        stats.record_transcription(started.elapsed());
+                        if !text.is_empty()
+                            && let Some(reason) = transcript_filter.rejection(&text, confidence)
+                        {
+                            info!("[server] Dropped \"{text}\": {reason}");
+                            if let Ok(mut w) = client_writer.lock() {
+                                let _ = write_server_msg(
+                                    &mut *w,
+                                    &ServerMsg::StatusNotification(STATUS_NOT_CAUGHT.to_string()),
+                                );
+                            }
+                            continue;
+                        }
+                        text
+                    }
+                    Err(e) => {
+                        // One bad segment must not end the session: tell the user
+                        // and keep reading
+                        warn!("[server] Transcription failed: {e:#}");
+                        stats.record_error(&format!("Transcription failed: {e:#}"));
+                        if let Ok(mut w) = client_writer.lock() {
+                            let _ = write_server_msg(
+                                &mut *w,
+                                &ServerMsg::Error(format!(
+                                    "{RETRYABLE_ERROR_PREFIX}Could not transcribe your speech"
+                                )),
+                            );
+                        }
+                        continue;
+                    }
+                };
+
+                if !text.is_empty() {
+                    // With --language auto, the detected language is shown and
+                    // one outside --languages is flagged to the orchestrator
+                    let (label, text) = match &language {
+                        Some(language) => {
+                            debug!("[server] Transcribed [{}]: \"{text}\"", language.code);
+                            let tagged = if language.allowed {
+                                text
+                            } else {
+                                info!("[server] Spoken in {}, not in --languages", language.code);
+                                tag_language(&language.code, &text)
+                            };
+                            (format!("You [{}]", language.code), tagged)
+                        }
+                        None => {
+                            debug!("[server] Transcribed: \"{}\"", text);
+                            ("You".to_string(), text)
+                        }
+                    };
+                    // Display transcription on client
+                    let shown = split_language_tag(&text).1;
+                    if let Ok(mut w) = client_writer.lock() {
+                        let _ = write_server_msg(
+                            &mut *w,
+                            &ServerMsg::Text(format!("{label}: {shown}")),
+                        );
+                    }
+                    turn_timing.forwarded(received);
+                    exchange.forwarded();
+                    if let Some(context) = context {
+                        context.push(shown);
+                    }
+                    log.record(Event::Transcription(&text));
+                    forward(&OrchestratorMsg::TranscribedText(text))?;
+                }
+            }
+            Turn::Typed {
+                epoch,
+                text,
+                received,
+            } => {
+                // Typed input bypasses the transcriber (and the pause gate) but is
+                // otherwise indistinguishable from a spoken turn downstream.
+                let text = text.trim().to_string();
+                if !text.is_empty()
+                    && !exchange.cancelled_since(epoch)
+                    && !orchestrator_absent(orchestrator_writer, orchestrator_down, client_writer)
+                {
+                    debug!("[server] Typed: \"{}\"", text);
+                    if let Ok(mut w) = client_writer.lock() {
+                        let _ = write_server_msg(&mut *w, &ServerMsg::Text(format!("You: {text}")));
+                    }
+                    turn_timing.forwarded(received);
+                    exchange.forwarded();
+                    if let Some(context) = context {
+                        context.push(&text);
+                    }
+                    log.record(Event::Typed(&text));
+                    forward(&OrchestratorMsg::TranscribedText(text))?;
+                }
+            }
+            Turn::Language(code) => {
+                let switched = transcriber
+                    .lock()
+                    .map_err(|e| anyhow::anyhow!("transcriber poisoned: {e}"))?
+                    .set_language(&code);
+                match switched {
+                    Ok(()) => info!("[server] Transcription language set to {code}"),
+                    Err(e) => {
+                        warn!("[server] {e:#}");
+                        if let Ok(mut w) = client_writer.lock() {
+                            let _ = write_server_msg(&mut *w, &ServerMsg::Error(format!("{e:#}")));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether the orchestrator is gone, in which case the client is told its
+/// turn was not sent.
+fn orchestrator_absent(
+    orchestrator_writer: &Mutex<BufWriter<UnixStream>>,
+    orchestrator_down: &AtomicBool,
+    client_writer: &Mutex<BufWriter<Transport>>,
+) -> bool {
+    // A new orchestrator's handshake holds the writer until the flag is cleared
+    drop(orchestrator_writer.lock());
+    if !orchestrator_down.load(Ordering::SeqCst) {
+        return false;
+    }
+    info!("[server] No orchestrator, dropping the client's turn");
+    if let Ok(mut w) = client_writer.lock() {
+        let _ = write_server_msg(&mut *w, &orchestrator_restarting_error());
+    }
+    true
+}
+
+/// Transcribe a segment too long for one go, a [`SEGMENT_WINDOW`] at a time,
+/// as one text.
+fn transcribe_in_windows(transcriber: &mut dyn Transcriber, samples: &[i16]) -> Result<String> {
+    debug!(
+        "[server] Long segment: transcribing {} windows",
+        samples.len().div_ceil(SEGMENT_WINDOW)
+    );
+    let mut parts = Vec::new();
+    for window in samples.chunks(SEGMENT_WINDOW) {
+        let text = transcriber.transcribe(window)?;
+        let text = text.trim();
+        if !text.is_empty() {
+            parts.push(text.to_string());
+        }
+    }
+    Ok(parts.join(" "))
+}
+
+/// The sequence numbers of a client's last segments, oldest first.
+struct RecentSegments {
+    recent: VecDeque<u32>,
+    capacity: usize,
+}
+
+impl RecentSegments {
+    fn new(capacity: usize) -> Self {
+        Self {
+            recent: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record `seq`; false when it is already among the last ones.
+    fn first_time(&mut self, seq: u32) -> bool {
+        if self.recent.contains(&seq) {
+            return false;
+        }
+        if self.recent.len() == self.capacity {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(seq);
+        true
+    }
+}
+
+/// Synthesized single words, most recently used last.
+struct WordCache {
+    entries: Vec<(String, Arc<Vec<i16>>)>,
+    capacity: usize,
+}
+
+impl WordCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            capacity,
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<Arc<Vec<i16>>> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(pos);
+        let samples = entry.1.clone();
+        self.entries.push(entry);
+        Some(samples)
+    }
+
+    fn insert(&mut self, key: String, samples: Arc<Vec<i16>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.remove(0);
+        }
+        self.entries.push((key, samples));
+    }
+}
+
+/// What a reply sentence was synthesized from: its text with whitespace
+/// collapsed, the speed of its reply (`None` for the engine's) and the voice.
+#[derive(Debug, Clone, PartialEq)]
+struct PhraseKey {
+    text: String,
+    speed: Option<u32>,
+    voice: usize,
+}
+
+impl PhraseKey {
+    fn new(sentence: &str, speed: Option<f32>, voice: usize) -> Self {
+        Self {
+            text: sentence.split_whitespace().collect::<Vec<_>>().join(" "),
+            speed: speed.map(f32::to_bits),
+            voice,
+        }
+    }
+}
+
+/// Synthesized reply sentences, most recently used last, within a budget of
+/// sample bytes.
+struct PhraseCache {
+    entries: Vec<(PhraseKey, Arc<Vec<i16>>)>,
+    bytes: usize,
+    budget: usize,
+}
+
+impl PhraseCache {
+    fn new(budget: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            bytes: 0,
+            budget,
+        }
+    }
+
+    fn get(&mut self, key: &PhraseKey) -> Option<Arc<Vec<i16>>> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(pos);
+        let samples = entry.1.clone();
+        self.entries.push(entry);
+        Some(samples)
+    }
+
+    fn insert(&mut self, key: PhraseKey, samples: Arc<Vec<i16>>) {
+        let size = samples.len() * 2;
+        if size > self.budget || self.entries.iter().any(|(k, _)| *k == key) {
+            return;
+        }
+        while self.bytes + size > self.budget {
+            let (_, evicted) = self.entries.remove(0);
+            self.bytes -= evicted.len() * 2;
+        }
+        self.bytes += size;
+        self.entries.push((key, samples));
+    }
+}
+
+/// A reply sentence from `cache`, or synthesized and added to it. Only a
+/// finished synthesis is added: a failure leaves the cache as it was.
+fn synthesize_cached(
+    tts: &dyn TtsEngine,
+    cache: &Mutex<PhraseCache>,
+    sentence: &str,
+    speed: Option<f32>,
+    voice: usize,
+) -> Result<Arc<Vec<i16>>> {
+    let key = PhraseKey::new(sentence, speed, voice);
+    // The lock is not held while synthesizing
+    let cached = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key);
+    if let Some(samples) = cached {
+        debug!("[server] TTS cache hit ({} samples)", samples.len());
+        return Ok(samples);
+    }
+    let samples = Arc::new(profile::time("synthesis", || {
+        tts.synthesize_at(sentence, voice, speed)
+    })?);
+    cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, samples.clone());
+    Ok(samples)
+}
+
+/// Normalize a `SpeakWord` request: trimmed, bounded, and non-empty.
+fn normalize_spoken_word(word: &str) -> Option<&str> {
+    let word = word.trim();
+    if word.is_empty() || word.chars().count() > MAX_SPOKEN_WORD_LEN {
+        return None;
+    }
+    Some(word)
+}
+
+/// Pronounce one word as a short TTS exchange (audio chunks + TtsEnd).
+///
+/// Synthesis failures are logged and answered with an empty exchange so the
+/// client never waits on a TtsEnd that will not come.
+fn speak_word(
+    word: &str,
+    tts: &dyn TtsEngine,
+    voice: usize,
+    chunks: TtsChunks,
+    cache: &mut WordCache,
+    client_writer: &Mutex<BufWriter<Transport>>,
+) -> Result<()> {
+    let samples = match normalize_spoken_word(word) {
+        None => {
+            debug!("[server] Ignoring SpeakWord request ({} bytes)", word.len());
+            Arc::new(Vec::new())
+        }
+        Some(word) => {
+            let key = word.to_lowercase();
+            match cache.get(&key) {
+                Some(samples) => samples,
+                None => match profile::time("synthesis", || tts.synthesize_at(word, voice, None)) {
+                    Ok(samples) => {
+                        info!("[server] Pronouncing \"{word}\"");
+                        let samples = Arc::new(samples);
+                        cache.insert(key, samples.clone());
+                        samples
+                    }
+                    Err(e) => {
+                        warn!("[server] Failed to synthesize \"{word}\": {e}");
+                        Arc::new(Vec::new())
+                    }
+                },
+            }
+        }
+    };
+
+    // A word is short: no pacing
+    send_tts_audio(
+        client_writer,
+        &samples,
+        chunks,
+        &AtomicBool::new(false),
+        None,
+    )?;
+    Ok(())
+}
+
+/// TTS routing: reads OrchestratorMsg from Unix, synthesizes speech, streams to client.
+///
+/// The session has started when this runs; messages its state does not allow
+/// (a second SessionStart) are answered with an error on `orchestrator_writer`.
+fn tts_router(
+    unix_read: UnixStream,
+    shared: &SessionShared,
+    tts: Arc<dyn TtsEngine>,
+    options: &SessionOptions,
+) -> Result<OrchestratorExit> {
+    let SessionShared {
+        ref client_writer,
+        ref orchestrator_writer,
+        ref paused,
+        ref tts_interrupted,
+        ref tts_active,
+        ref turn_timing,
+        ref exchange,
+        ref voice,
+        ref context,
+        ref log,
+        stats,
+        ..
+    } = *shared;
+    let SessionOptions {
+        tts_lead,
+        tts_cache_bytes,
+        tts_chunks,
+        ..
+    } = *options;
+    let mut reader = BufReader::new(unix_read);
+    let mut state = OrchestratorState::Active;
+    let cache = Arc::new(Mutex::new(PhraseCache::new(tts_cache_bytes)));
+    // Set by `[SPEED_DEFAULT:x]`; without it, replies take the engine's speed
+    let mut session_speed = None;
+
+    loop {
+        let msg = match read_orchestrator_msg(&mut reader) {
+            Ok(msg) => msg,
+            Err(e) => {
+                if is_disconnect(&e) {
+                    info!("[server] Orchestrator disconnected");
+                    return Ok(OrchestratorExit::Lost);
+                }
+                return Err(e.context("reading orchestrator message"));
+            }
+        };
+
+        let verdict;
+        (state, verdict) = state.on_message(&msg);
+        if let OrchestratorVerdict::Reject(reason) = verdict {
+            warn!("[server] Rejecting orchestrator message ({reason}): {msg:?}");
+            let mut w = orchestrator_writer
+                .lock()
+                .map_err(|e| anyhow::anyhow!("orchestrator writer poisoned: {e}"))?;
+            write_server_msg(&mut *w, &ServerMsg::Error(reason.to_string()))?;
+            continue;
+        }
+
+        if matches!(
+            msg,
+            OrchestratorMsg::ResponseText(_) | OrchestratorMsg::FeedbackText(_)
+        ) && !exchange.answered()
+        {
+            info!("[server] Dropping the answer to a cancelled exchange");
+            continue;
+        }
+
+        match msg {
+            OrchestratorMsg::ResponseText(text) => {
+                tts_interrupted.store(false, Ordering::SeqCst);
+                let reply_at = Instant::now();
+                turn_timing.answered();
+                log.record(Event::Response(&text));
+
+                if paused.load(Ordering::SeqCst) {
+                    debug!(
+                        "[server] Paused — skipping TTS for response ({} chars)",
+                        text.len()
+                    );
+                    turn_timing.finish(reply_at, None);
+                    let mut w = client_writer
+                        .lock()
+                        .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
+                    write_server_msg(&mut *w, &ServerMsg::TtsEnd)?;
+                    log.record(Event::Exchange(Outcome::Completed));
+                    continue;
+                }
+
+                // Optional speed and voice markers (e.g. "[SPEED:0.6] [VOICE:am_adam] Hello")
+                let (speed, voice_name, marked_text) = take_markers(&text, &mut session_speed);
+                // Markdown, emoji and URLs are neither shown nor spoken
+                let sanitized = sanitize_reply(marked_text);
+                if sanitized != marked_text {
+                    debug!("[server] Raw response: {marked_text:?}");
                 }
+                let clean_text = sanitized.as_str();
 
                 debug!("[server] ResponseText: {} chars", clean_text.len());
 
@@ -260,9 +1889,34 @@ fn tts_router(
                     let _ =
                         write_server_msg(&mut *w, &ServerMsg::Text(format!("AI: {clean_text}")));
                 }
+                if let Some(context) = context {
+                    context.push(clean_text);
+                }
 
+                tts_active.store(true, Ordering::SeqCst);
+                let mut pacer = tts_lead.map(TtsPacer::new);
+                // The small first chunk, if any, goes to this reply's first sentence
+                let mut chunks = tts_chunks;
                 let tts_start = std::time::Instant::now();
-                let sentences = split_sentences(clean_text);
+                // Spelled out for the TTS only: the client was shown `clean_text`
+                let sentences: Vec<String> = split_sentences(clean_text)
+                    .into_iter()
+                    .map(|s| normalize_for_tts(s, tts.language()))
+                    .collect();
+                // A voice marker voices this reply only
+                let voice = match voice_name.map(|name| (name, tts::voice_index(&*tts, name))) {
+                    None => voice.load(Ordering::SeqCst),
+                    Some((name, Ok(index))) => {
+                        info!("[server] Reply voiced by {name}");
+                        index
+                    }
+                    Some((_, Err(e))) => {
+                        warn!("[server] {e}: keeping the current voice");
+                        voice.load(Ordering::SeqCst)
+                    }
+                };
+                let mut first_audio = None;
+                let mut was_interrupted = false;
 
                 if sentences.is_empty() {
                     let mut w = client_writer
@@ -271,8 +1925,9 @@ fn tts_router(
                     write_server_msg(&mut *w, &ServerMsg::TtsEnd)?;
                 } else if sentences.len() == 1 {
                     // Single sentence: no pipeline overhead
-                    match tts.synthesize(sentences[0]) {
+                    match synthesize_cached(&*tts, &cache, &sentences[0], speed, voice) {
                         Ok(samples) => {
+                            first_audio = Some(Instant::now());
                             let audio_duration = samples.len() as f64 / 16000.0;
                             info!(
                                 "[server] TTS: {:.2}s synthesis, {:.2}s audio ({} chars)",
@@ -280,11 +1935,13 @@ fn tts_router(
                                 audio_duration,
                                 clean_text.len()
                             );
-                            let mut w = client_writer
-                                .lock()
-                                .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
-                            let was_interrupted =
-                                send_tts_audio(&mut *w, &samples, &tts_interrupted)?;
+                            was_interrupted = send_tts_audio(
+                                client_writer,
+                                &samples,
+                                chunks,
+                                tts_interrupted,
+                                pacer.as_mut(),
+                            )?;
                             if was_interrupted {
                                 info!(
                                     "[server] TTS interrupted after {:.2}s",
@@ -294,6 +1951,7 @@ fn tts_router(
                         }
                         Err(e) => {
                             warn!("[server] TTS synthesis failed: {e}");
+                            stats.record_error(&format!("TTS synthesis failed: {e}"));
                             let mut w = client_writer
                                 .lock()
                                 .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
@@ -308,23 +1966,34 @@ fn tts_router(
                         num_sentences,
                         clean_text.len()
                     );
-                    let (tx, rx) = crossbeam_channel::bounded::<Vec<i16>>(2);
-                    let tts_clone = tts.clone();
-                    let interrupted_producer = tts_interrupted.clone();
-                    let sentence_strs: Vec<String> =
-                        sentences.iter().map(|s| s.to_string()).collect();
-
-                    // Producer: synthesize sentences sequentially
-                    std::thread::Builder::new()
-                        .name("tts_synth".into())
-                        .spawn(move || {
-                            for (i, sentence) in sentence_strs.iter().enumerate() {
+                    let workers = tts.parallelism().clamp(1, num_sentences);
+                    let (tx, rx) =
+                        crossbeam_channel::bounded::<(usize, Arc<Vec<i16>>)>(workers + 1);
+                    let sentence_strs = Arc::new(sentences);
+                    // Index of the next sentence to synthesize, shared by the workers
+                    let next_sentence = Arc::new(AtomicUsize::new(0));
+
+                    // Producers: each worker synthesizes the next sentence not taken yet
+                    for _ in 0..workers {
+                        let tts_clone = tts.clone();
+                        let cache_producer = cache.clone();
+                        let interrupted_producer = tts_interrupted.clone();
+                        let sentence_strs = sentence_strs.clone();
+                        let next_sentence = next_sentence.clone();
+                        let tx = tx.clone();
+                        std::thread::Builder::new()
+                            .name("tts_synth".into())
+                            .spawn(move || loop {
+                                let i = next_sentence.fetch_add(1, Ordering::SeqCst);
+                                let Some(sentence) = sentence_strs.get(i) else {
+                                    break;
+                                };
                                 if interrupted_producer.load(Ordering::SeqCst) {
                                     debug!("[server] TTS producer: interrupted before sentence {}", i + 1);
                                     break;
                                 }
                                 let synth_start = std::time::Instant::now();
-                                match tts_clone.synthesize(sentence) {
+                                match synthesize_cached(&*tts_clone, &cache_producer, sentence, speed, voice) {
                                     Ok(samples) => {
                                         let audio_dur = samples.len() as f64 / 16000.0;
                                         debug!(
@@ -335,7 +2004,7 @@ fn tts_router(
                                             samples.len(),
                                             audio_dur,
                                         );
-                                        if tx.send(samples).is_err() {
+                                        if tx.send((i, samples)).is_err() {
                                             break; // consumer dropped
                                         }
                                     }
@@ -345,41 +2014,64 @@ fn tts_router(
                                             i + 1,
                                             sentence_strs.len()
                                         );
+                                        // No worker starts a later sentence: the reply
+                                        // ends before this one
+                                        next_sentence.store(sentence_strs.len(), Ordering::SeqCst);
                                         break;
                                     }
                                 }
-                            }
-                            drop(tx); // signal end of production
-                        })?;
+                            })?;
+                    }
+                    drop(tx); // the workers' copies signal the end of production
 
-                    // Consumer: send each sentence's audio as it arrives (with crossfade)
-                    let mut was_interrupted = false;
+                    // Consumer: send each sentence's audio in order as it arrives (with crossfade)
                     {
+                        let mut crossfader = Crossfader::default();
+                        // Sentences synthesized ahead of one still in progress
+                        let mut ahead: BTreeMap<usize, Arc<Vec<i16>>> = BTreeMap::new();
+                        let mut next_to_send = 0;
+                        'sentences: for (i, samples) in rx {
+                            ahead.insert(i, samples);
+                            while let Some(samples) = ahead.remove(&next_to_send) {
+                                next_to_send += 1;
+                                first_audio.get_or_insert_with(Instant::now);
+                                // The cache keeps the sentence as synthesized
+                                let samples = crossfader.push(Arc::unwrap_or_clone(samples));
+                                was_interrupted = send_tts_chunks(
+                                    client_writer,
+                                    &samples,
+                                    &mut chunks,
+                                    tts_interrupted,
+                                    pacer.as_mut(),
+                                )?;
+
+                                if was_interrupted {
+                                    break 'sentences;
+                                }
+                            }
+                        }
+                        // Sentences after one that failed are not sent
+                        if !ahead.is_empty() {
+                            debug!(
+                                "[server] TTS: {} sentence(s) after sentence {} dropped",
+                                ahead.len(),
+                                next_to_send + 1
+                            );
+                        }
+                        // The last tail, also when a sentence failed to synthesize
+                        // (dropped when interrupted, like the rest of the reply)
+                        if !was_interrupted {
+                            was_interrupted = send_tts_chunks(
+                                client_writer,
+                                &crossfader.flush(),
+                                &mut chunks,
+                                tts_interrupted,
+                                pacer.as_mut(),
+                            )?;
+                        }
                         let mut w = client_writer
                             .lock()
                             .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
-                        let mut prev_tail: Option<Vec<i16>> = None;
-                        for samples in rx {
-                            let mut samples = samples;
-                            // Apply crossfade at sentence boundary
-                            if let Some(tail) = &prev_tail
-                                && samples.len() >= CROSSFADE_LEN
-                            {
-                                apply_crossfade(tail, &mut samples);
-                            }
-                            // Save tail for next sentence's crossfade
-                            if samples.len() >= CROSSFADE_LEN {
-                                prev_tail = Some(samples[samples.len() - CROSSFADE_LEN..].to_vec());
-                            } else {
-                                // Short sentence: reset prev_tail (no reliable tail to crossfade from)
-                                prev_tail = None;
-                            }
-                            was_interrupted = send_tts_chunks(&mut *w, &samples, &tts_interrupted)?;
-
-                            if was_interrupted {
-                                break;
-                            }
-                        }
                         write_server_msg(&mut *w, &ServerMsg::TtsEnd)?;
                     }
 
@@ -397,8 +2089,25 @@ fn tts_router(
                         );
                     }
                 }
+                tts_active.store(false, Ordering::SeqCst);
+                log.record(Event::Exchange(if was_interrupted {
+                    Outcome::Interrupted
+                } else {
+                    Outcome::Completed
+                }));
+
+                stats.record_exchange(first_audio.map(|at| at.duration_since(reply_at)));
+                if let Some(turn_stats) = turn_timing.finish(reply_at, first_audio) {
+                    debug!("[server] Turn timings: {turn_stats:?}");
+                    let mut w = client_writer
+                        .lock()
+                        .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
+                    write_server_msg(&mut *w, &ServerMsg::TurnStats(turn_stats))?;
+                }
             }
             OrchestratorMsg::FeedbackText(text) => {
+                turn_timing.answered();
+                log.record(Event::Feedback(&text));
                 // Forward language feedback directly to client (no TTS synthesis)
                 info!(
                     "[server] Forwarding feedback to client ({} chars)",
@@ -415,12 +2124,13 @@ fn tts_router(
             OrchestratorMsg::TranscribedText(_) => {
                 debug!("[server] Unexpected TranscribedText from orchestrator (ignoring)");
             }
-            OrchestratorMsg::SessionStart(json) => {
-                debug!("[server] SessionStart in tts_router (unexpected): {}", json);
+            OrchestratorMsg::SessionStart(_) => {
+                // Rejected above: the session is already running
             }
             OrchestratorMsg::SessionEnd => {
                 info!("[server] SessionEnd received, stopping session");
-                break;
+                notify_session_ended(client_writer, END_REASON_ORCHESTRATOR);
+                return Ok(OrchestratorExit::SessionEnd);
             }
             OrchestratorMsg::SummaryResponse(text) => {
                 info!(
@@ -442,73 +2152,287 @@ fn tts_router(
                     .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
                 write_server_msg(&mut *w, &ServerMsg::StatusNotification(text))?;
             }
-        }
-    }
-
-    Ok(())
+            OrchestratorMsg::Translation(text) => {
+                // Display only, like feedback: never synthesized
+                debug!("[server] Forwarding translation ({} chars)", text.len());
+                let mut w = client_writer
+                    .lock()
+                    .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
+                write_server_msg(&mut *w, &ServerMsg::Translation(text))?;
+            }
+            OrchestratorMsg::TranslateRequest => {
+                debug!("[server] Unexpected TranslateRequest in tts_router (ignoring)");
+            }
+            OrchestratorMsg::SimplifyRequest => {
+                debug!("[server] Unexpected SimplifyRequest in tts_router (ignoring)");
+            }
+            OrchestratorMsg::DisregardLast => {
+                debug!("[server] Unexpected DisregardLast in tts_router (ignoring)");
+            }
+            OrchestratorMsg::BranchTo(_) => {
+                debug!("[server] Unexpected BranchTo in tts_router (ignoring)");
+            }
+            OrchestratorMsg::CancelExchange => {
+                debug!("[server] Unexpected CancelExchange in tts_router (ignoring)");
+            }
+        }
+    }
 }
 
-/// Parse an optional `[SPEED:X.X]` marker at the start of a response.
-/// Returns the speed value (if present) and the remaining text.
-fn parse_speed_marker(text: &str) -> (Option<f32>, &str) {
-    if let Some(rest) = text.strip_prefix("[SPEED:")
-        && let Some(end) = rest.find(']')
-        && let Ok(speed) = rest[..end].parse::<f32>()
-    {
-        let remaining = rest[end + 1..].trim_start();
-        return (Some(speed), remaining);
+/// The speed markers a response can start with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpeedMarker {
+    /// `[SPEED:x]`: this reply only.
+    Reply(f32),
+    /// `[SPEED_DEFAULT:x]`: this reply and the next ones.
+    Default(f32),
+    /// `[SPEED:reset]`: back to the engine's speed, for this reply and the next ones.
+    Reset,
+}
+
+/// `[TAG:value]` at the start of `text`: the tag, the trimmed value and the
+/// text after the marker.
+fn leading_marker(text: &str) -> Option<(&str, &str, &str)> {
+    let rest = text.strip_prefix('[')?;
+    let end = rest.find(']')?;
+    let (tag, value) = rest[..end].split_once(':')?;
+    Some((tag, value.trim(), rest[end + 1..].trim_start()))
+}
+
+/// Parse an optional speed marker at the start of a response.
+/// Returns the marker (if present) and the remaining text.
+fn parse_speed_marker(text: &str) -> (Option<SpeedMarker>, &str) {
+    let marker = match leading_marker(text) {
+        Some(("SPEED", "reset", rest)) => Some((SpeedMarker::Reset, rest)),
+        Some(("SPEED", value, rest)) => parse_speed(value).map(|s| (SpeedMarker::Reply(s), rest)),
+        Some(("SPEED_DEFAULT", value, rest)) => {
+            parse_speed(value).map(|s| (SpeedMarker::Default(s), rest))
+        }
+        _ => None,
+    };
+    match marker {
+        Some((marker, rest)) => (Some(marker), rest),
+        None => (None, text),
+    }
+}
+
+/// A marker's speed, clamped into [`tts::SPEED_RANGE`] with a warning.
+fn parse_speed(value: &str) -> Option<f32> {
+    let speed = value.parse::<f32>().ok().filter(|s| s.is_finite())?;
+    let clamped = speed.clamp(*tts::SPEED_RANGE.start(), *tts::SPEED_RANGE.end());
+    if clamped != speed {
+        warn!("[server] TTS speed {speed} is out of range, using {clamped}");
+    }
+    Some(clamped)
+}
+
+/// Parse an optional `[VOICE:name]` marker at the start of a response.
+/// Returns the voice name (if present) and the remaining text.
+fn parse_voice_marker(text: &str) -> (Option<&str>, &str) {
+    match leading_marker(text) {
+        Some(("VOICE", name, rest)) if !name.is_empty() => (Some(name), rest),
+        _ => (None, text),
+    }
+}
+
+/// The speed and voice markers leading a response, in any order. A default
+/// speed marker updates `session_speed`; the speed returned is this reply's
+/// (`None` for the engine's), with the voice name and the text to speak.
+fn take_markers<'a>(
+    text: &'a str,
+    session_speed: &mut Option<f32>,
+) -> (Option<f32>, Option<&'a str>, &'a str) {
+    let (mut reply_speed, mut voice) = (None, None);
+    let mut text = text;
+    loop {
+        if let (Some(marker), rest) = parse_speed_marker(text) {
+            match marker {
+                SpeedMarker::Reply(s) => reply_speed = Some(s),
+                SpeedMarker::Default(s) => {
+                    *session_speed = Some(s);
+                    info!("[server] TTS speed set to {s}");
+                }
+                SpeedMarker::Reset => {
+                    *session_speed = None;
+                    info!("[server] TTS speed reset");
+                }
+            }
+            text = rest;
+        } else if let (Some(name), rest) = parse_voice_marker(text) {
+            voice = Some(name);
+            text = rest;
+        } else {
+            return (reply_speed.or(*session_speed), voice, text);
+        }
     }
-    (None, text)
 }
 
+/// Words whose dot does not end a sentence ("Mr. Smith"), lowercase.
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "st", "jr", "sr", "vs", "etc", "e.g", "i.e", "approx",
+];
+
+/// Closing quotes and brackets, kept with the sentence they close.
+const CLOSERS: &[char] = &['"', '\'', '\u{201d}', '\u{2019}', '\u{bb}', ')', ']'];
+
+/// Fewer words than this is not worth a synthesis call of its own: such a
+/// fragment is joined to the next sentence (or the previous one, at the end).
+const MIN_SENTENCE_WORDS: usize = 3;
+
 /// Split text into sentences for streaming TTS synthesis.
 ///
-/// Sentences are split on `.` `!` `?` followed by whitespace or end-of-string.
-/// Punctuation stays attached to the preceding sentence (important for TTS intonation).
-/// Empty segments are skipped.
+/// Sentences are split on `.` `!` `?` `…` (or a run of them, like "..." or
+/// "?!") followed by whitespace or end-of-string, with the closing quotes and
+/// brackets after them. Punctuation stays attached to the preceding sentence
+/// (important for TTS intonation). Not a boundary: the dot of an abbreviation,
+/// and an ellipsis followed by a lowercase word ("Wait... really?"). Empty
+/// segments are skipped, and fragments under `MIN_SENTENCE_WORDS` words are
+/// merged into a neighbor.
 fn split_sentences(text: &str) -> Vec<&str> {
     let text = text.trim();
-    if text.is_empty() {
-        return Vec::new();
-    }
+    let is_terminator = |c: char| matches!(c, '.' | '!' | '?' | '\u{2026}');
 
-    let mut sentences = Vec::new();
+    // Byte ranges of the sentences, before merging
+    let mut ranges = Vec::new();
     let mut start = 0;
-    let bytes = text.as_bytes();
-
-    for (i, &b) in bytes.iter().enumerate() {
-        if (b == b'.' || b == b'!' || b == b'?')
-            && (i + 1 == bytes.len() || bytes[i + 1].is_ascii_whitespace())
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !is_terminator(c) {
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, c)) = chars.peek()
+            && is_terminator(c)
+        {
+            end = j + c.len_utf8();
+            chars.next();
+        }
+        let run_end = end;
+        while let Some(&(j, c)) = chars.peek()
+            && CLOSERS.contains(&c)
         {
-            let sentence = text[start..=i].trim();
-            if !sentence.is_empty() {
-                sentences.push(sentence);
+            end = j + c.len_utf8();
+            chars.next();
+        }
+        let rest = &text[end..];
+        if rest.starts_with(|c: char| !c.is_whitespace()) {
+            continue;
+        }
+        let run = &text[i..run_end];
+        if run == "." && end == run_end {
+            let word = text[start..i]
+                .rsplit(char::is_whitespace)
+                .next()
+                .unwrap_or_default()
+                .trim_start_matches(['(', '"', '\u{201c}']);
+            if ABBREVIATIONS.contains(&word.to_lowercase().as_str()) {
+                continue;
             }
-            start = i + 1;
         }
+        if (run.contains("...") || run.contains('\u{2026}'))
+            && rest.trim_start().starts_with(char::is_lowercase)
+        {
+            continue;
+        }
+        if !text[start..end].trim().is_empty() {
+            ranges.push((start, end));
+        }
+        start = end;
     }
-
     // Remaining text after last sentence-ending punctuation
-    let tail = text[start..].trim();
-    if !tail.is_empty() {
-        sentences.push(tail);
+    if !text[start..].trim().is_empty() {
+        ranges.push((start, text.len()));
+    }
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    let mut pending: Option<(usize, usize)> = None;
+    for (start, end) in ranges {
+        let start = pending
+            .take()
+            .map_or(start, |(pending_start, _)| pending_start);
+        if text[start..end].split_whitespace().count() < MIN_SENTENCE_WORDS {
+            pending = Some((start, end));
+        } else {
+            merged.push((start, end));
+        }
+    }
+    if let Some((start, end)) = pending {
+        match merged.last_mut() {
+            Some(last) => last.1 = end,
+            None => merged.push((start, end)),
+        }
+    }
+    merged
+        .into_iter()
+        .map(|(start, end)| text[start..end].trim())
+        .collect()
+}
+
+/// Keeps a reply's audio at most `lead` ahead of the client's playback, so
+/// its playback queue never fills and blocks the client writer.
+struct TtsPacer {
+    lead: Duration,
+    /// When the client will have played everything sent so far.
+    played_at: Option<Instant>,
+}
+
+impl TtsPacer {
+    fn new(lead: Duration) -> Self {
+        Self {
+            lead,
+            played_at: None,
+        }
     }
 
-    sentences
+    /// Record `samples` sent at `now`; returns how long to wait before the
+    /// next chunk. Playback that ran dry (slow synthesis) restarts from `now`.
+    fn sent(&mut self, samples: usize, now: Instant) -> Duration {
+        let start = self.played_at.filter(|at| *at > now).unwrap_or(now);
+        let played_at = start + Duration::from_secs_f64(samples as f64 / 16000.0);
+        self.played_at = Some(played_at);
+        (played_at - now).saturating_sub(self.lead)
+    }
 }
 
 /// Send TTS audio chunks without TtsEnd. Returns `true` if interrupted.
 /// Caller is responsible for sending TtsEnd.
-fn send_tts_chunks(
-    writer: &mut impl Write,
+///
+/// The writer is locked per chunk, so other messages get through a long
+/// reply. With a `pacer`, the wait between chunks still checks `interrupted`.
+/// The first chunk size of `chunks` is used once: the caller keeps one
+/// `TtsChunks` per reply.
+fn send_tts_chunks<W: Write>(
+    writer: &Mutex<W>,
     samples: &[i16],
+    chunks: &mut TtsChunks,
     interrupted: &AtomicBool,
+    mut pacer: Option<&mut TtsPacer>,
 ) -> Result<bool> {
-    for chunk in samples.chunks(TTS_CHUNK_SIZE) {
+    let mut rest = samples;
+    while !rest.is_empty() {
         if interrupted.load(Ordering::SeqCst) {
             return Ok(true);
         }
-        write_server_msg(writer, &ServerMsg::TtsAudioChunk(chunk.to_vec()))?;
+        let size = chunks.first.take().unwrap_or(chunks.size);
+        let chunk;
+        (chunk, rest) = rest.split_at(size.clamp(1, rest.len()));
+        {
+            let mut w = writer
+                .lock()
+                .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
+            write_server_msg(&mut *w, &ServerMsg::TtsAudioChunk(chunk.to_vec()))?;
+        }
+        if let Some(pacer) = pacer.as_deref_mut() {
+            let now = Instant::now();
+            let until = now + pacer.sent(chunk.len(), now);
+            while !interrupted.load(Ordering::SeqCst) {
+                let left = until.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    break;
+                }
+                std::thread::sleep(left.min(Duration::from_millis(10)));
+            }
+        }
     }
     Ok(false)
 }
@@ -529,26 +2453,66 @@ fn apply_crossfade(prev_tail: &[i16], samples: &mut [i16]) {
     }
 }
 
+/// Sentence audio joined by overlap-add: the tail of each sentence is held back
+/// and mixed into the head of the next, so the boundary is heard once.
+#[derive(Default)]
+struct Crossfader {
+    /// The last `CROSSFADE_LEN` samples of the previous sentence, not sent yet.
+    held: Vec<i16>,
+}
+
+impl Crossfader {
+    /// The part of `samples` ready to send: its head mixed with the held tail,
+    /// its own tail held back for the next sentence. A sentence shorter than
+    /// `CROSSFADE_LEN` is sent whole, after the held tail.
+    fn push(&mut self, mut samples: Vec<i16>) -> Vec<i16> {
+        if samples.len() < CROSSFADE_LEN {
+            let mut out = std::mem::take(&mut self.held);
+            out.extend(samples);
+            return out;
+        }
+        if !self.held.is_empty() {
+            apply_crossfade(&self.held, &mut samples);
+        }
+        self.held = samples.split_off(samples.len() - CROSSFADE_LEN);
+        samples
+    }
+
+    /// The held tail, to send once the last sentence is in.
+    fn flush(&mut self) -> Vec<i16> {
+        std::mem::take(&mut self.held)
+    }
+}
+
 /// Chunk TTS audio samples and send as TtsAudioChunk messages, followed by TtsEnd.
 /// Returns `true` if interrupted mid-stream, `false` if completed normally.
-fn send_tts_audio(
-    writer: &mut impl Write,
+fn send_tts_audio<W: Write>(
+    writer: &Mutex<W>,
     samples: &[i16],
+    mut chunks: TtsChunks,
     interrupted: &AtomicBool,
+    pacer: Option<&mut TtsPacer>,
 ) -> Result<bool> {
-    let was_interrupted = send_tts_chunks(writer, samples, interrupted)?;
+    let was_interrupted = send_tts_chunks(writer, samples, &mut chunks, interrupted, pacer)?;
     if was_interrupted {
         info!("[server] TTS streaming interrupted — aborting remaining chunks");
     }
-    write_server_msg(writer, &ServerMsg::TtsEnd)?;
+    let mut w = writer
+        .lock()
+        .map_err(|e| anyhow::anyhow!("client writer poisoned: {e}"))?;
+    write_server_msg(&mut *w, &ServerMsg::TtsEnd)?;
     Ok(was_interrupted)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use space_lt_common::protocol::{read_server_msg, write_client_msg, write_orchestrator_msg};
-    use std::net::TcpListener;
+    use crate::transcribe::DetectedLanguage;
+    use space_lt_common::protocol::{
+        ServerOrcMsg, read_server_msg, read_server_orc_msg, write_client_msg,
+        write_orchestrator_msg,
+    };
+    use std::net::{TcpListener, TcpStream};
     use std::os::unix::net::UnixListener;
     use std::sync::atomic::{AtomicU32, Ordering};
 
@@ -572,6 +2536,134 @@ mod tests {
         }
     }
 
+    /// Fails its first `failures` calls, then transcribes to `text`.
+    struct FlakyTranscriber {
+        failures: usize,
+        text: String,
+    }
+
+    impl Transcriber for FlakyTranscriber {
+        fn transcribe(&mut self, _audio_i16: &[i16]) -> anyhow::Result<String> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                anyhow::bail!("whisper exploded");
+            }
+            Ok(self.text.clone())
+        }
+    }
+
+    /// Takes `delay` to transcribe to `text`, like whisper on a long segment.
+    struct SlowTranscriber {
+        delay: Duration,
+        text: String,
+    }
+
+    impl Transcriber for SlowTranscriber {
+        fn transcribe(&mut self, _audio_i16: &[i16]) -> anyhow::Result<String> {
+            std::thread::sleep(self.delay);
+            Ok(self.text.clone())
+        }
+    }
+
+    /// Transcribes to `text` in a fixed detected language.
+    struct DetectingTranscriber {
+        text: String,
+        language: DetectedLanguage,
+    }
+
+    impl Transcriber for DetectingTranscriber {
+        fn transcribe(&mut self, _audio_i16: &[i16]) -> anyhow::Result<String> {
+            Ok(self.text.clone())
+        }
+
+        fn detected_language(&self) -> Option<DetectedLanguage> {
+            Some(self.language.clone())
+        }
+    }
+
+    /// Shows each word of `text` as a partial before the whole of it.
+    struct PartialsTranscriber {
+        text: String,
+    }
+
+    impl Transcriber for PartialsTranscriber {
+        fn transcribe(&mut self, _audio_i16: &[i16]) -> anyhow::Result<String> {
+            Ok(self.text.clone())
+        }
+
+        fn transcribe_with_partials(
+            &mut self,
+            audio_i16: &[i16],
+            on_partial: &mut (dyn FnMut(&str) + Send),
+        ) -> anyhow::Result<String> {
+            let words: Vec<&str> = self.text.split(' ').collect();
+            for n in 1..words.len() {
+                on_partial(&words[..n].join(" "));
+            }
+            self.transcribe(audio_i16)
+        }
+    }
+
+    /// Transcribes to `text`, noting the context it was given before each segment.
+    struct ContextTranscriber {
+        text: String,
+        contexts: Arc<Mutex<Vec<String>>>,
+        context: String,
+    }
+
+    impl Transcriber for ContextTranscriber {
+        fn transcribe(&mut self, _audio_i16: &[i16]) -> anyhow::Result<String> {
+            self.contexts.lock().unwrap().push(self.context.clone());
+            Ok(self.text.clone())
+        }
+
+        fn set_context(&mut self, text: &str) {
+            self.context = text.to_string();
+        }
+    }
+
+    /// Notes the language each segment is transcribed in, taking `delay`
+    /// over it; knows no "xx".
+    struct LanguageTranscriber {
+        language: String,
+        languages: Arc<Mutex<Vec<String>>>,
+        delay: Duration,
+    }
+
+    impl Transcriber for LanguageTranscriber {
+        fn transcribe(&mut self, _audio_i16: &[i16]) -> anyhow::Result<String> {
+            std::thread::sleep(self.delay);
+            self.languages.lock().unwrap().push(self.language.clone());
+            Ok("Hallo".into())
+        }
+
+        fn set_language(&mut self, language: &str) -> anyhow::Result<()> {
+            anyhow::ensure!(language != "xx", "Unknown language code \"{language}\"");
+            self.language = language.to_string();
+            Ok(())
+        }
+    }
+
+    /// Gives its `results`, text and confidence, one per segment.
+    struct ScriptedTranscriber {
+        results: std::vec::IntoIter<(&'static str, Option<f32>)>,
+    }
+
+    impl Transcriber for ScriptedTranscriber {
+        fn transcribe(&mut self, audio_i16: &[i16]) -> anyhow::Result<String> {
+            Ok(self.transcribe_with_confidence(audio_i16, &mut |_| {})?.0)
+        }
+
+        fn transcribe_with_confidence(
+            &mut self,
+            _audio_i16: &[i16],
+            _on_partial: &mut (dyn FnMut(&str) + Send),
+        ) -> anyhow::Result<(String, Option<f32>)> {
+            let (text, confidence) = self.results.next().expect("no result left");
+            Ok((text.to_string(), confidence))
+        }
+    }
+
     struct MockTtsEngine {
         sample_count: usize,
     }
@@ -583,12 +2675,87 @@ mod tests {
     }
 
     impl TtsEngine for MockTtsEngine {
-        fn synthesize(&self, _text: &str) -> anyhow::Result<Vec<i16>> {
-            // Return a simple ramp pattern for easy verification
-            Ok((0..self.sample_count).map(|i| i as i16).collect())
+        /// A simple ramp pattern for easy verification, with one extra sample
+        /// per voice index to tell voices apart.
+        fn synthesize_at(
+            &self,
+            _text: &str,
+            voice: usize,
+            _speed: Option<f32>,
+        ) -> anyhow::Result<Vec<i16>> {
+            let mut samples: Vec<i16> = (0..self.sample_count).map(|i| i as i16).collect();
+            samples.extend(std::iter::repeat_n(0, voice));
+            Ok(samples)
+        }
+
+        fn voices(&self) -> Vec<String> {
+            vec!["alto".into(), "bass".into()]
+        }
+    }
+
+    /// Takes `delay` per sentence, so a reply is streamed for a while.
+    struct SlowTtsEngine {
+        delay: Duration,
+    }
+
+    impl TtsEngine for SlowTtsEngine {
+        fn synthesize_at(
+            &self,
+            _text: &str,
+            _voice: usize,
+            _speed: Option<f32>,
+        ) -> anyhow::Result<Vec<i16>> {
+            std::thread::sleep(self.delay);
+            Ok(vec![0; 4000])
         }
+    }
+
+    /// Takes 5 ms per character and returns 1000 samples of the text's
+    /// length, synthesizing up to `workers` sentences at once.
+    struct WorkersTtsEngine {
+        workers: usize,
+    }
+
+    impl TtsEngine for WorkersTtsEngine {
+        fn synthesize_at(
+            &self,
+            text: &str,
+            _voice: usize,
+            _speed: Option<f32>,
+        ) -> anyhow::Result<Vec<i16>> {
+            std::thread::sleep(Duration::from_millis(5 * text.len() as u64));
+            Ok(vec![text.len() as i16; 1000])
+        }
+
+        fn parallelism(&self) -> usize {
+            self.workers
+        }
+    }
 
-        fn set_speed(&self, _speed: f32) {}
+    /// Counts its syntheses and records their texts and speeds; a text of
+    /// "fail" fails.
+    #[derive(Default)]
+    struct CountingTtsEngine {
+        calls: AtomicUsize,
+        texts: Mutex<Vec<String>>,
+        speeds: Mutex<Vec<Option<f32>>>,
+    }
+
+    impl TtsEngine for CountingTtsEngine {
+        fn synthesize_at(
+            &self,
+            text: &str,
+            _voice: usize,
+            speed: Option<f32>,
+        ) -> anyhow::Result<Vec<i16>> {
+            self.texts.lock().unwrap().push(text.to_string());
+            self.speeds.lock().unwrap().push(speed);
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if text == "fail" {
+                anyhow::bail!("no voice today");
+            }
+            Ok(vec![1; 100 * text.len()])
+        }
     }
 
     // --- Helper to generate unique socket paths ---
@@ -603,46 +2770,1721 @@ mod tests {
 
     // --- Integration tests ---
 
-    #[test]
-    fn stt_routing_audio_to_transcribed_text() {
-        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
-        let tcp_port = tcp_listener.local_addr().unwrap().port();
-        let sock_path = temp_socket_path();
+    #[test]
+    fn stt_routing_audio_to_transcribed_text() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        // Connect mock client (TCP) and mock orchestrator (Unix)
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        // Run session in thread
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut MockTranscriber::new("Hello world"),
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        // Client sends AudioSegment (use try_clone for independent writer)
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+
+        // Orchestrator reads TranscribedText
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+        let msg = read_orchestrator_msg(&mut orch_r).unwrap();
+        match msg {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Hello world"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+
+        // Cleanup: close connections to stop session
+        drop(client_w);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        let _ = session_handle.join();
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn a_segment_delivered_twice_is_transcribed_once() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut MockTranscriber::new("Hello world"),
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        // The client sent segment 1 again: its first copy had arrived after all
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        for seq in [1, 1, 2] {
+            write_client_msg(
+                &mut client_w,
+                &ClientMsg::NumberedSegment(seq, vec![0; 8000]),
+            )
+            .unwrap();
+        }
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("Typed".into())).unwrap();
+
+        // Every copy is acknowledged
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut acks = Vec::new();
+        while acks.len() < 3 {
+            if let ServerMsg::SegmentReceived(seq) = read_server_msg(&mut client_r).unwrap() {
+                acks.push(seq);
+            }
+        }
+        assert_eq!(acks, [1, 1, 2]);
+
+        // Only one of them is transcribed
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+        let mut turns = Vec::new();
+        for _ in 0..3 {
+            match read_orchestrator_msg(&mut orch_r).unwrap() {
+                OrchestratorMsg::TranscribedText(t) => turns.push(t),
+                other => panic!("Expected TranscribedText, got {other:?}"),
+            }
+        }
+        assert_eq!(turns, ["Hello world", "Hello world", "Typed"]);
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        let _ = session_handle.join();
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn recent_segments_forget_the_oldest() {
+        let mut recent = RecentSegments::new(2);
+        assert!(recent.first_time(1));
+        assert!(recent.first_time(2));
+        assert!(!recent.first_time(1));
+        assert!(recent.first_time(3));
+        // 1 has left the window
+        assert!(recent.first_time(1));
+        assert!(!recent.first_time(3));
+    }
+
+    #[test]
+    fn transcription_failure_is_reported_and_session_continues() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut FlakyTranscriber {
+                    failures: 1,
+                    text: "Second try".into(),
+                },
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Error(e) => assert!(e.starts_with(RETRYABLE_ERROR_PREFIX), "{e}"),
+            other => panic!("Expected Error, got {other:?}"),
+        }
+
+        // The next segment still flows to the orchestrator
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "You: Second try"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Second try"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn language_outside_the_list_is_shown_and_tagged() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let allowed = vec!["en".to_string(), "es".to_string()];
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut DetectingTranscriber {
+                    text: "Guten Tag".into(),
+                    language: DetectedLanguage::new("de", &allowed),
+                },
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "You [de]: Guten Tag"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "[lang:de] Guten Tag"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn filtered_transcriptions_never_reach_the_orchestrator() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut ScriptedTranscriber {
+                    results: vec![
+                        ("Subtitles by the Amara.org community", Some(0.9)),
+                        ("Hmm okay", Some(0.2)),
+                        ("I went to the market", Some(0.8)),
+                    ]
+                    .into_iter(),
+                },
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions {
+                    transcript_filter: TranscriptFilter::new("Subtitles by\u{2026}", 0.4),
+                    ..Default::default()
+                },
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        // On the list, then too unsure
+        for _ in 0..2 {
+            write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::StatusNotification(s) => assert_eq!(s, STATUS_NOT_CAUGHT),
+                other => panic!("Expected StatusNotification, got {other:?}"),
+            }
+        }
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "You: I went to the market"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+        // The first thing the orchestrator gets is the kept turn
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "I went to the market"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn stt_context_holds_the_turns_and_replies_so_far() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let contexts = Arc::new(Mutex::new(Vec::new()));
+        let transcriber_contexts = contexts.clone();
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut ContextTranscriber {
+                    text: "Kokoro is nice".into(),
+                    contexts: transcriber_contexts,
+                    context: String::new(),
+                },
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions {
+                    stt_context: true,
+                    ..Default::default()
+                },
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("I use Kokoro".into())).unwrap();
+        read_orchestrator_msg(&mut orch_r).unwrap();
+        write_orchestrator_msg(
+            &mut orch_w,
+            &OrchestratorMsg::ResponseText("[SPEED:0.8] What is **Kokoro**?".into()),
+        )
+        .unwrap();
+        while !matches!(read_server_msg(&mut client_r).unwrap(), ServerMsg::TtsEnd) {}
+        for _ in 0..2 {
+            write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+            match read_orchestrator_msg(&mut orch_r).unwrap() {
+                OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Kokoro is nice"),
+                other => panic!("Expected TranscribedText, got {other:?}"),
+            }
+        }
+        // The reply as shown, and the spoken turns as they come
+        assert_eq!(
+            *contexts.lock().unwrap(),
+            [
+                "I use Kokoro What is Kokoro?",
+                "I use Kokoro What is Kokoro? Kokoro is nice",
+            ]
+        );
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_w);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn set_language_applies_to_the_next_segments() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let languages = Arc::new(Mutex::new(Vec::new()));
+        let transcriber_languages = languages.clone();
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut LanguageTranscriber {
+                    language: "en".into(),
+                    languages: transcriber_languages,
+                    delay: Duration::ZERO,
+                },
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        let mut segment = |client_w: &mut BufWriter<TcpStream>| {
+            write_client_msg(client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+            match read_orchestrator_msg(&mut orch_r).unwrap() {
+                OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Hallo"),
+                other => panic!("Expected TranscribedText, got {other:?}"),
+            }
+        };
+        segment(&mut client_w);
+        write_client_msg(&mut client_w, &ClientMsg::SetLanguage("de".into())).unwrap();
+        segment(&mut client_w);
+        write_client_msg(&mut client_w, &ClientMsg::SetLanguage("xx".into())).unwrap();
+        let error = loop {
+            if let ServerMsg::Error(e) = read_server_msg(&mut client_r).unwrap() {
+                break e;
+            }
+        };
+        assert_eq!(error, "Unknown language code \"xx\"");
+        // Refused: the language stays as it was
+        segment(&mut client_w);
+        assert_eq!(*languages.lock().unwrap(), ["en", "de", "de"]);
+
+        drop(orch_r);
+        drop(client_w);
+        drop(client_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn set_language_waits_for_room_in_a_full_queue() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let languages = Arc::new(Mutex::new(Vec::new()));
+        let transcriber_languages = languages.clone();
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut LanguageTranscriber {
+                    language: "en".into(),
+                    languages: transcriber_languages,
+                    delay: Duration::from_millis(200),
+                },
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+        let segment = |client_w: &mut BufWriter<TcpStream>| {
+            write_client_msg(client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        };
+        let mut transcribed = |n: usize| {
+            for _ in 0..n {
+                match read_orchestrator_msg(&mut orch_r).unwrap() {
+                    OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Hallo"),
+                    other => panic!("Expected TranscribedText, got {other:?}"),
+                }
+            }
+        };
+
+        // One segment being transcribed, and the queue full behind it
+        segment(&mut client_w);
+        std::thread::sleep(Duration::from_millis(50));
+        for _ in 0..TURN_QUEUE {
+            segment(&mut client_w);
+        }
+        write_client_msg(&mut client_w, &ClientMsg::SetLanguage("de".into())).unwrap();
+        // Sent once the queue has room again
+        transcribed(2);
+        segment(&mut client_w);
+        transcribed(4);
+        assert_eq!(
+            *languages.lock().unwrap(),
+            ["en", "en", "en", "en", "en", "de"]
+        );
+
+        drop(orch_r);
+        drop(client_w);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn partial_transcriptions_come_before_the_echo() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut PartialsTranscriber {
+                    text: "I went to the market".into(),
+                },
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        for partial in ["I", "I went", "I went to", "I went to the"] {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::PartialTranscript(t) => assert_eq!(t, partial),
+                other => panic!("Expected PartialTranscript, got {other:?}"),
+            }
+        }
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "You: I went to the market"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+        // The orchestrator only gets the final text
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "I went to the market"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn tts_routing_response_to_audio_chunks() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        // 8000 samples = 2 chunks of 4000
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut MockTranscriber::new("ignored"),
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        // Orchestrator sends ResponseText
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Test".into())).unwrap();
+
+        // Client reads TtsAudioChunk messages
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+
+        let mut total_samples = Vec::new();
+        loop {
+            let msg = read_server_msg(&mut client_r).unwrap();
+            match msg {
+                ServerMsg::Text(_) => {} // display text (AI: ...)
+                ServerMsg::TtsAudioChunk(samples) => {
+                    assert!(samples.len() <= TTS_CHUNK_SIZE);
+                    total_samples.extend_from_slice(&samples);
+                }
+                ServerMsg::TtsEnd => break,
+                other => panic!("Expected Text, TtsAudioChunk or TtsEnd, got {other:?}"),
+            }
+        }
+
+        // Verify total samples match mock output
+        assert_eq!(total_samples.len(), 8000);
+
+        // Cleanup
+        drop(client_r);
+        drop(orch_w);
+        drop(mock_client);
+        drop(mock_orch);
+        let _ = session_handle.join();
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn session_survives_an_orchestrator_restart() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let (orch_tx, orch_rx) = crossbeam_channel::unbounded();
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut MockTranscriber::new("Hello world"),
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &orch_rx,
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+        });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let retry_error = format!("{RETRYABLE_ERROR_PREFIX}{ERR_ORCHESTRATOR_RESTARTING}");
+
+        // The orchestrator crashes with a turn in flight
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::TranscribedText(_)
+        ));
+        drop(orch_r);
+        drop(mock_orch);
+
+        assert!(matches!(
+            read_server_msg(&mut client_r).unwrap(),
+            ServerMsg::Text(_)
+        ));
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::StatusNotification(s) => assert_eq!(s, STATUS_ORCHESTRATOR_RESTARTING),
+            other => panic!("Expected StatusNotification, got {other:?}"),
+        }
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Error(e) => assert_eq!(e, retry_error),
+            other => panic!("Expected Error, got {other:?}"),
+        }
+
+        // A segment spoken during the gap is dropped with the same error
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Error(e) => assert_eq!(e, retry_error),
+            other => panic!("Expected Error, got {other:?}"),
+        }
+
+        // A new orchestrator has started the session again
+        let (server_unix, new_orch) = UnixStream::pair().unwrap();
+        orch_tx
+            .send(OrchestratorHandoff {
+                stream: server_unix,
+                config: "{}".into(),
+            })
+            .unwrap();
+        let mut orch_w = BufWriter::new(new_orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(new_orch.try_clone().unwrap());
+        assert!(matches!(
+            read_server_orc_msg(&mut orch_r).unwrap(),
+            ServerOrcMsg::Ready
+        ));
+
+        // The client's next segment gets a spoken reply
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Hello world"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Hi.".into())).unwrap();
+        let mut total_samples = 0;
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::Text(_) => {}
+                ServerMsg::TtsAudioChunk(samples) => total_samples += samples.len(),
+                ServerMsg::TtsEnd => break,
+                other => panic!("Expected Text, TtsAudioChunk or TtsEnd, got {other:?}"),
+            }
+        }
+        assert_eq!(total_samples, 8000);
+
+        drop(client_w);
+        drop(client_r);
+        drop(mock_client);
+        assert!(matches!(
+            session_handle.join().unwrap().unwrap(),
+            SessionOutcome::Ended
+        ));
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn tts_chunking_splits_large_audio() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        // 10000 samples = 2 full chunks (4000) + 1 partial chunk (2000)
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut MockTranscriber::new("ignored"),
+                Arc::new(MockTtsEngine::new(10000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Test".into())).unwrap();
+
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+
+        let mut chunk_sizes = Vec::new();
+        let mut total_samples = Vec::new();
+        loop {
+            let msg = read_server_msg(&mut client_r).unwrap();
+            match msg {
+                ServerMsg::Text(_) => {} // display text (AI: ...)
+                ServerMsg::TtsAudioChunk(samples) => {
+                    assert!(
+                        samples.len() <= TTS_CHUNK_SIZE,
+                        "Chunk size {} exceeds max {}",
+                        samples.len(),
+                        TTS_CHUNK_SIZE
+                    );
+                    chunk_sizes.push(samples.len());
+                    total_samples.extend_from_slice(&samples);
+                }
+                ServerMsg::TtsEnd => break,
+                other => panic!("Expected Text, TtsAudioChunk or TtsEnd, got {other:?}"),
+            }
+        }
+
+        // Verify chunking: 4000 + 4000 + 2000 = 10000
+        assert_eq!(chunk_sizes, vec![4000, 4000, 2000]);
+        assert_eq!(total_samples.len(), 10000);
+
+        // Verify sample content (ramp pattern from MockTtsEngine)
+        for (i, &sample) in total_samples.iter().enumerate() {
+            assert_eq!(sample, i as i16);
+        }
+
+        // Cleanup
+        drop(client_r);
+        drop(orch_w);
+        drop(mock_client);
+        drop(mock_orch);
+        let _ = session_handle.join();
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    // --- Pause/Resume tests ---
+
+    /// Helper: set up a session with TCP + Unix connections, returning handles for test interaction.
+    fn setup_session(
+        transcriber_text: &str,
+        tts_samples: usize,
+    ) -> (
+        TcpStream,  // mock client
+        UnixStream, // mock orchestrator
+        String,     // socket path
+        std::thread::JoinHandle<Result<()>>,
+    ) {
+        setup_session_with(
+            transcriber_text,
+            Arc::new(MockTtsEngine::new(tts_samples)),
+            cached_options(),
+        )
+    }
+
+    /// The options of [`setup_session`]: defaults, with a reply cache.
+    fn cached_options() -> SessionOptions {
+        SessionOptions {
+            tts_cache_bytes: 1 << 20,
+            ..Default::default()
+        }
+    }
+
+    fn setup_session_with(
+        transcriber_text: &str,
+        tts: Arc<dyn TtsEngine>,
+        options: SessionOptions,
+    ) -> (
+        TcpStream,
+        UnixStream,
+        String,
+        std::thread::JoinHandle<Result<()>>,
+    ) {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let text = transcriber_text.to_string();
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut MockTranscriber::new(&text),
+                tts,
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &options,
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        (mock_client, mock_orch, sock_path, session_handle)
+    }
+
+    #[test]
+    fn cancel_during_transcription_drops_the_turn() {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut SlowTranscriber {
+                    delay: Duration::from_millis(300),
+                    text: "Cancelled words".into(),
+                },
+                Arc::new(MockTtsEngine::new(8000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        // Cancelled while whisper is still at it, then a new turn is typed
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        write_client_msg(&mut client_w, &ClientMsg::CancelExchange).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("Next".into())).unwrap();
+
+        // The orchestrator only ever hears the new turn
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Next"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+        // Nor does the client see or hear the cancelled one
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "You: Next"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Hi.".into())).unwrap();
+        let mut total_samples = 0;
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::Text(t) => assert_eq!(t, "AI: Hi."),
+                ServerMsg::TtsAudioChunk(samples) => total_samples += samples.len(),
+                ServerMsg::TtsEnd => break,
+                other => panic!("Expected Text, TtsAudioChunk or TtsEnd, got {other:?}"),
+            }
+        }
+        assert_eq!(total_samples, 8000);
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_w);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn a_cancel_gets_past_a_full_client_queue() {
+        let (mock_client, mock_orch, sock_path, session_handle) = setup_session_with(
+            "unused",
+            Arc::new(SlowTtsEngine {
+                delay: Duration::from_millis(500),
+            }),
+            SessionOptions::default(),
+        );
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("First".into())).unwrap();
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::TranscribedText(t) if t == "First"
+        ));
+        assert!(matches!(
+            read_server_msg(&mut client_r).unwrap(),
+            ServerMsg::Text(t) if t == "You: First"
+        ));
+
+        // stt_router is busy pronouncing a word while the queue fills up
+        write_client_msg(&mut client_w, &ClientMsg::SpeakWord("slow".into())).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        for i in 0..=CLIENT_QUEUE {
+            write_client_msg(&mut client_w, &ClientMsg::TextInput(format!("Turn {i}"))).unwrap();
+        }
+        write_client_msg(&mut client_w, &ClientMsg::CancelExchange).unwrap();
+
+        // The one over the limit is refused, the cancel is not held up
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Error(e) => assert!(e.starts_with(RETRYABLE_ERROR_PREFIX), "{e}"),
+            other => panic!("Expected Error, got {other:?}"),
+        }
+        assert!(matches!(
+            read_server_msg(&mut client_r).unwrap(),
+            ServerMsg::TtsEnd
+        ));
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::CancelExchange
+        ));
+        // Then the word, once synthesized
+        assert!(matches!(
+            read_server_msg(&mut client_r).unwrap(),
+            ServerMsg::TtsAudioChunk(_)
+        ));
+
+        // The queued turns were cancelled; the session goes on
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("Next".into())).unwrap();
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::TranscribedText(t) if t == "Next"
+        ));
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn cancel_after_forwarding_drops_the_reply() {
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session("Hello world", 8000);
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::TranscribedText(_)
+        ));
+        assert!(matches!(
+            read_server_msg(&mut client_r).unwrap(),
+            ServerMsg::Text(_)
+        ));
+
+        // The orchestrator is told at once, the client stops waiting
+        write_client_msg(&mut client_w, &ClientMsg::CancelExchange).unwrap();
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::CancelExchange
+        ));
+        assert!(matches!(
+            read_server_msg(&mut client_r).unwrap(),
+            ServerMsg::TtsEnd
+        ));
+
+        // A reply already on its way is dropped; the next turn is answered
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Late.".into()))
+            .unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("Again".into())).unwrap();
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Again"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Hi.".into())).unwrap();
+        let mut texts = Vec::new();
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::Text(t) => texts.push(t),
+                ServerMsg::TtsAudioChunk(_) => {}
+                ServerMsg::TtsEnd => break,
+                other => panic!("Expected Text, TtsAudioChunk or TtsEnd, got {other:?}"),
+            }
+        }
+        assert_eq!(texts, ["You: Again", "AI: Hi."]);
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_w);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn segment_during_a_reply_is_dropped_as_echo() {
+        let tts = Arc::new(SlowTtsEngine {
+            delay: Duration::from_millis(200),
+        });
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session_with("Echo", tts, cached_options());
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        write_orchestrator_msg(
+            &mut orch_w,
+            &OrchestratorMsg::ResponseText("This is one. This is two.".into()),
+        )
+        .unwrap();
+        // The microphone hears the first sentence while the second is synthesized
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::Text(_) => {}
+                ServerMsg::TtsAudioChunk(_) => break,
+                other => panic!("Expected Text or TtsAudioChunk, got {other:?}"),
+            }
+        }
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        while !matches!(read_server_msg(&mut client_r).unwrap(), ServerMsg::TtsEnd) {}
+
+        // The orchestrator only hears the next turn
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("Next".into())).unwrap();
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Next"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "You: Next"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_w);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    /// A session whose transcriber is `transcriber`, with a slow TTS.
+    fn setup_session_transcribing(
+        transcriber: SlowTranscriber,
+    ) -> (
+        TcpStream,
+        UnixStream,
+        String,
+        std::thread::JoinHandle<Result<()>>,
+    ) {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let session_handle = std::thread::spawn(move || {
+            let mut transcriber = transcriber;
+            run_session(
+                &mut transcriber,
+                Arc::new(SlowTtsEngine {
+                    delay: Duration::from_millis(200),
+                }),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+            .map(|_| ())
+        });
+        (mock_client, mock_orch, sock_path, session_handle)
+    }
+
+    #[test]
+    fn barge_in_is_handled_while_whisper_is_busy() {
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session_transcribing(SlowTranscriber {
+                delay: Duration::from_secs(2),
+                text: "Sorry".into(),
+            });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        // A reply of five sentences, one chunk each, while the segment is transcribed
+        write_orchestrator_msg(
+            &mut orch_w,
+            &OrchestratorMsg::ResponseText("One. Two. Three. Four. Five.".into()),
+        )
+        .unwrap();
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::Text(t) => assert_eq!(t, "AI: One. Two. Three. Four. Five."),
+                ServerMsg::TtsAudioChunk(_) => break,
+                other => panic!("Expected Text or TtsAudioChunk, got {other:?}"),
+            }
+        }
+        write_client_msg(&mut client_w, &ClientMsg::InterruptTts).unwrap();
+        let mut chunks = 1;
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::TtsAudioChunk(_) => chunks += 1,
+                ServerMsg::TtsEnd => break,
+                other => panic!("Expected TtsAudioChunk or TtsEnd, got {other:?}"),
+            }
+        }
+        assert!(
+            chunks < 5,
+            "the reply was not interrupted ({chunks} chunks)"
+        );
+
+        // The segment is still transcribed and forwarded
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "You: Sorry"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Sorry"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_w);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn pause_drops_the_queued_segments() {
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session_transcribing(SlowTranscriber {
+                delay: Duration::from_millis(300),
+                text: "Spoken".into(),
+            });
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        // The first segment is being transcribed, the second waits for it
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::PauseRequest).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::ResumeRequest).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("Typed".into())).unwrap();
+
+        // The one in flight goes on, the queued one is dropped, the order is kept
+        for expected in ["Spoken", "Typed"] {
+            match read_orchestrator_msg(&mut orch_r).unwrap() {
+                OrchestratorMsg::TranscribedText(t) => assert_eq!(t, expected),
+                other => panic!("Expected TranscribedText, got {other:?}"),
+            }
+        }
+
+        drop(client_w);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn segment_after_barge_in_is_forwarded() {
+        let tts = Arc::new(SlowTtsEngine {
+            delay: Duration::from_millis(200),
+        });
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session_with("Wait, stop", tts, cached_options());
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        write_orchestrator_msg(
+            &mut orch_w,
+            &OrchestratorMsg::ResponseText("This is one. This is two.".into()),
+        )
+        .unwrap();
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::Text(_) => {}
+                ServerMsg::TtsAudioChunk(_) => break,
+                other => panic!("Expected Text or TtsAudioChunk, got {other:?}"),
+            }
+        }
+        // The user talks over the reply
+        write_client_msg(&mut client_w, &ClientMsg::InterruptTts).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Wait, stop"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_w);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn messages_get_through_a_paced_reply() {
+        // 1.5 s of audio, paced 250 ms ahead of playback
+        let (mock_client, mock_orch, sock_path, session_handle) = setup_session_with(
+            "unused",
+            Arc::new(MockTtsEngine::new(24000)),
+            SessionOptions {
+                tts_lead: Some(Duration::from_millis(250)),
+                ..cached_options()
+            },
+        );
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Long.".into()))
+            .unwrap();
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::Text(_) => {}
+                ServerMsg::TtsAudioChunk(_) => break,
+                other => panic!("Expected Text or TtsAudioChunk, got {other:?}"),
+            }
+        }
+        // Typed mid-reply, shown before the reply's audio is all sent
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("Wait".into())).unwrap();
+        let mut chunks_after = 0;
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::TtsAudioChunk(_) => chunks_after += 1,
+                ServerMsg::Text(t) => {
+                    assert_eq!(t, "You: Wait");
+                    break;
+                }
+                other => panic!("Expected TtsAudioChunk or Text, got {other:?}"),
+            }
+        }
+        let mut chunks_left = 0;
+        while let ServerMsg::TtsAudioChunk(_) = read_server_msg(&mut client_r).unwrap() {
+            chunks_left += 1;
+        }
+        assert_eq!(chunks_after + chunks_left, 5);
+        assert!(chunks_left > 0, "the Text waited for the whole reply");
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_w);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn repeated_replies_are_not_synthesized_again() {
+        let tts = Arc::new(CountingTtsEngine::default());
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session_with("unused", tts.clone(), cached_options());
+
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut reply = |text: &str| {
+            write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText(text.into()))
+                .unwrap();
+            let mut samples = 0;
+            loop {
+                match read_server_msg(&mut client_r).unwrap() {
+                    ServerMsg::TtsAudioChunk(chunk) => samples += chunk.len(),
+                    ServerMsg::TtsEnd => return samples,
+                    _ => {}
+                }
+            }
+        };
+
+        // Streamed (two sentences), then a single sentence
+        let first = reply("That was a great job! Now say it again.");
+        assert_eq!(tts.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(reply("That was a great job! Now say it again."), first);
+        reply("That was a great job!");
+        assert_eq!(tts.calls.load(Ordering::SeqCst), 2);
+
+        // A new speed is a cache miss
+        reply("[SPEED:0.6] That was a great job!");
+        assert_eq!(tts.calls.load(Ordering::SeqCst), 3);
+
+        drop(client_r);
+        drop(orch_w);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn a_speed_marker_does_not_outlast_its_reply() {
+        let tts = Arc::new(CountingTtsEngine::default());
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session_with("unused", tts.clone(), cached_options());
+
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        for text in [
+            "[SPEED:0.6] Listen closely.",
+            "Back to normal.",
+            "[SPEED_DEFAULT:1.2] We go faster now. And after that too.",
+            "Still faster.",
+            "[SPEED:reset] Normal again.",
+        ] {
+            write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText(text.into()))
+                .unwrap();
+            while !matches!(read_server_msg(&mut client_r).unwrap(), ServerMsg::TtsEnd) {}
+        }
+        assert_eq!(
+            *tts.speeds.lock().unwrap(),
+            [Some(0.6), None, Some(1.2), Some(1.2), Some(1.2), None]
+        );
+
+        drop(client_r);
+        drop(orch_w);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn markdown_is_neither_shown_nor_spoken() {
+        let tts = Arc::new(CountingTtsEngine::default());
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session_with("unused", tts.clone(), cached_options());
+
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut reply = |text: &str| {
+            write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText(text.into()))
+                .unwrap();
+            let shown = match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::Text(t) => t,
+                other => panic!("Expected Text, got {other:?}"),
+            };
+            while !matches!(read_server_msg(&mut client_r).unwrap(), ServerMsg::TtsEnd) {}
+            shown
+        };
+
+        // The speed marker is still read before the text is cleaned
+        assert_eq!(
+            reply("[SPEED:0.6] **Well done, Sam!** 🎉\n- Keep going like this"),
+            "AI: Well done, Sam! Keep going like this"
+        );
+        // Nothing left once cleaned: kept as it was
+        assert_eq!(reply("👏👏"), "AI: 👏👏");
+        assert_eq!(
+            *tts.texts.lock().unwrap(),
+            ["Well done, Sam!", "Keep going like this", "👏👏"]
+        );
+        assert_eq!(tts.speeds.lock().unwrap()[0], Some(0.6));
+
+        drop(client_r);
+        drop(orch_w);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn numbers_are_spelled_out_for_the_tts_only() {
+        let tts = Arc::new(CountingTtsEngine::default());
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session_with("unused", tts.clone(), cached_options());
+
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        write_orchestrator_msg(
+            &mut orch_w,
+            &OrchestratorMsg::ResponseText("It's $3.50. Dr. Lee comes at 3:30.".into()),
+        )
+        .unwrap();
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "AI: It's $3.50. Dr. Lee comes at 3:30."),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+        while !matches!(read_server_msg(&mut client_r).unwrap(), ServerMsg::TtsEnd) {}
+        assert_eq!(
+            *tts.texts.lock().unwrap(),
+            ["It's three dollars and fifty cents. Doctor Lee comes at three thirty."]
+        );
+
+        drop(client_r);
+        drop(orch_w);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn short_segments_never_reach_the_orchestrator() {
+        let (mock_client, mock_orch, sock_path, session_handle) = setup_session("Thank you.", 8000);
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        // A 50 ms hotkey tap
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 800])).unwrap();
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::StatusNotification(s) => assert_eq!(s, STATUS_SEGMENT_TOO_SHORT),
+            other => panic!("Expected StatusNotification, got {other:?}"),
+        }
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("Next".into())).unwrap();
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Next"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+
+        drop(client_w);
+        drop(client_r);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        assert!(session_handle.join().unwrap().is_ok());
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn long_segments_are_split_or_refused() {
+        for split_long in [true, false] {
+            let limits = SegmentLimits {
+                max: Duration::from_secs(40),
+                split_long,
+                ..SegmentLimits::default()
+            };
+            let (mock_client, mock_orch, sock_path, session_handle) = setup_session_with(
+                "Blah",
+                Arc::new(MockTtsEngine::new(8000)),
+                SessionOptions {
+                    segment_limits: limits,
+                    ..cached_options()
+                },
+            );
+
+            let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+            let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+            let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+            // 70 s: three whisper windows
+            write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 70 * 16000])).unwrap();
+            if split_long {
+                match read_orchestrator_msg(&mut orch_r).unwrap() {
+                    OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Blah Blah Blah"),
+                    other => panic!("Expected TranscribedText, got {other:?}"),
+                }
+            } else {
+                match read_server_msg(&mut client_r).unwrap() {
+                    ServerMsg::Error(e) => {
+                        assert!(e.starts_with(RETRYABLE_ERROR_PREFIX), "{e}");
+                        assert!(e.contains("70s"), "{e}");
+                    }
+                    other => panic!("Expected Error, got {other:?}"),
+                }
+                write_client_msg(&mut client_w, &ClientMsg::TextInput("Next".into())).unwrap();
+                match read_orchestrator_msg(&mut orch_r).unwrap() {
+                    OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Next"),
+                    other => panic!("Expected TranscribedText, got {other:?}"),
+                }
+            }
+
+            drop(client_w);
+            drop(client_r);
+            drop(orch_r);
+            drop(mock_client);
+            drop(mock_orch);
+            assert!(session_handle.join().unwrap().is_ok());
+            std::fs::remove_file(&sock_path).ok();
+        }
+    }
+
+    #[test]
+    fn text_input_forwarded_as_transcribed_text() {
+        let (mock_client, mock_orch, sock_path, session_handle) =
+            setup_session("never transcribed", 8000);
+
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        // Typed input is accepted even while paused (the mic path is idle)
+        write_client_msg(&mut client_w, &ClientMsg::PauseRequest).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("  Bonjour !  ".into())).unwrap();
 
-        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+        // Client gets the same "You: ..." echo as for a spoken turn
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "You: Bonjour !"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+        // Orchestrator sees a regular TranscribedText (the mock transcriber was bypassed)
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Bonjour !"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
 
-        // Connect mock client (TCP) and mock orchestrator (Unix)
-        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
-        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        // Blank input is ignored: the next message through is the audio transcription
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("   ".into())).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::ResumeRequest).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "never transcribed"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
 
-        let mock_orch = UnixStream::connect(&sock_path).unwrap();
-        let (server_unix, _) = unix_listener.accept().unwrap();
+        drop(client_w);
+        drop(client_r);
+        drop(orch_r);
+        drop(mock_client);
+        drop(mock_orch);
+        let _ = session_handle.join();
+        std::fs::remove_file(&sock_path).ok();
+    }
 
-        // Run session in thread
-        let session_handle = std::thread::spawn(move || {
-            run_session(
-                Box::new(MockTranscriber::new("Hello world")),
-                Box::new(MockTtsEngine::new(8000)),
-                server_tcp,
-                server_unix,
-            )
-        });
+    /// Read client messages up to and including TtsEnd, returning the message after it.
+    fn read_past_tts_end(client_r: &mut BufReader<TcpStream>) -> ServerMsg {
+        loop {
+            if let ServerMsg::TtsEnd = read_server_msg(client_r).unwrap() {
+                return read_server_msg(client_r).unwrap();
+            }
+        }
+    }
 
-        // Client sends AudioSegment (use try_clone for independent writer)
+    #[test]
+    fn turn_stats_follow_tts_end_when_enabled() {
+        let (mock_client, mock_orch, sock_path, session_handle) = setup_session("Salut", 8000);
         let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
-        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 1600])).unwrap();
-
-        // Orchestrator reads TranscribedText
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
         let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
-        let msg = read_orchestrator_msg(&mut orch_r).unwrap();
-        match msg {
-            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Hello world"),
-            other => panic!("Expected TranscribedText, got {other:?}"),
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+
+        write_client_msg(&mut client_w, &ClientMsg::EnableTimings).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::TranscribedText(_)
+        ));
+        std::thread::sleep(Duration::from_millis(150));
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Bien.".into()))
+            .unwrap();
+
+        let stats = match read_past_tts_end(&mut client_r) {
+            ServerMsg::TurnStats(stats) => stats,
+            other => panic!("Expected TurnStats, got {other:?}"),
+        };
+        assert!(stats.stt_ms < 1000, "{stats:?}");
+        assert!((150..5000).contains(&stats.llm_ms), "{stats:?}");
+        assert!(stats.tts_ms < 1000, "{stats:?}");
+        let sum = stats.stt_ms + stats.llm_ms + stats.tts_ms;
+        assert!((sum..=sum + 2).contains(&stats.first_audio_ms), "{stats:?}");
+
+        // A feedback choice the user thinks over is not counted
+        write_client_msg(&mut client_w, &ClientMsg::TextInput("Salut".into())).unwrap();
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::TranscribedText(_)
+        ));
+        std::thread::sleep(Duration::from_millis(100));
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::FeedbackText("RED: x".into()))
+            .unwrap();
+        loop {
+            if let ServerMsg::Feedback(_) = read_server_msg(&mut client_r).unwrap() {
+                break;
+            }
         }
+        std::thread::sleep(Duration::from_millis(400));
+        write_client_msg(&mut client_w, &ClientMsg::FeedbackChoice(true)).unwrap();
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::FeedbackChoice(true)
+        ));
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Bien.".into()))
+            .unwrap();
+        let stats = match read_past_tts_end(&mut client_r) {
+            ServerMsg::TurnStats(stats) => stats,
+            other => panic!("Expected TurnStats, got {other:?}"),
+        };
+        assert!((100..400).contains(&stats.llm_ms), "{stats:?}");
+        assert!(stats.first_audio_ms < 400, "{stats:?}");
 
-        // Cleanup: close connections to stop session
         drop(client_w);
+        drop(client_r);
         drop(orch_r);
+        drop(orch_w);
         drop(mock_client);
         drop(mock_orch);
         let _ = session_handle.join();
@@ -650,56 +4492,81 @@ mod tests {
     }
 
     #[test]
-    fn tts_routing_response_to_audio_chunks() {
-        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
-        let tcp_port = tcp_listener.local_addr().unwrap().port();
-        let sock_path = temp_socket_path();
-
-        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+    fn no_turn_stats_without_enable_timings() {
+        let (mock_client, mock_orch, sock_path, session_handle) = setup_session("Salut", 8000);
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
 
-        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
-        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        assert!(matches!(
+            read_orchestrator_msg(&mut orch_r).unwrap(),
+            OrchestratorMsg::TranscribedText(_)
+        ));
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Bien.".into()))
+            .unwrap();
+        write_orchestrator_msg(
+            &mut orch_w,
+            &OrchestratorMsg::StatusNotification("next".into()),
+        )
+        .unwrap();
 
-        let mock_orch = UnixStream::connect(&sock_path).unwrap();
-        let (server_unix, _) = unix_listener.accept().unwrap();
+        match read_past_tts_end(&mut client_r) {
+            ServerMsg::StatusNotification(text) => assert_eq!(text, "next"),
+            other => panic!("Expected StatusNotification, got {other:?}"),
+        }
 
-        // 8000 samples = 2 chunks of 4000
-        let session_handle = std::thread::spawn(move || {
-            run_session(
-                Box::new(MockTranscriber::new("ignored")),
-                Box::new(MockTtsEngine::new(8000)),
-                server_tcp,
-                server_unix,
-            )
-        });
+        drop(client_w);
+        drop(client_r);
+        drop(orch_r);
+        drop(orch_w);
+        drop(mock_client);
+        drop(mock_orch);
+        let _ = session_handle.join();
+        std::fs::remove_file(&sock_path).ok();
+    }
 
-        // Orchestrator sends ResponseText
-        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
-        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Test".into())).unwrap();
+    #[test]
+    fn speak_word_streams_a_mini_exchange() {
+        let (mock_client, mock_orch, sock_path, session_handle) = setup_session("unused", 5000);
 
-        // Client reads TtsAudioChunk messages
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
         let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let orch_clone = mock_orch.try_clone().unwrap();
+        orch_clone
+            .set_read_timeout(Some(Duration::from_millis(300)))
+            .unwrap();
+        let mut orch_r = BufReader::new(orch_clone);
 
-        let mut total_samples = Vec::new();
-        loop {
-            let msg = read_server_msg(&mut client_r).unwrap();
-            match msg {
-                ServerMsg::Text(_) => {} // display text (AI: ...)
-                ServerMsg::TtsAudioChunk(samples) => {
-                    assert!(samples.len() <= TTS_CHUNK_SIZE);
-                    total_samples.extend_from_slice(&samples);
+        // Works while paused, twice (the second one from the cache)
+        write_client_msg(&mut client_w, &ClientMsg::PauseRequest).unwrap();
+        for word in ["  oiseaux ", "Oiseaux"] {
+            write_client_msg(&mut client_w, &ClientMsg::SpeakWord(word.into())).unwrap();
+            let mut samples = Vec::new();
+            loop {
+                match read_server_msg(&mut client_r).unwrap() {
+                    ServerMsg::TtsAudioChunk(chunk) => samples.extend(chunk),
+                    ServerMsg::TtsEnd => break,
+                    other => panic!("Expected TtsAudioChunk/TtsEnd, got {other:?}"),
                 }
-                ServerMsg::TtsEnd => break,
-                other => panic!("Expected Text, TtsAudioChunk or TtsEnd, got {other:?}"),
             }
+            assert_eq!(samples.len(), 5000);
         }
 
-        // Verify total samples match mock output
-        assert_eq!(total_samples.len(), 8000);
+        // An empty request still gets its TtsEnd
+        write_client_msg(&mut client_w, &ClientMsg::SpeakWord("   ".into())).unwrap();
+        assert!(matches!(
+            read_server_msg(&mut client_r).unwrap(),
+            ServerMsg::TtsEnd
+        ));
 
-        // Cleanup
+        // The orchestrator never hears about it
+        assert!(read_orchestrator_msg(&mut orch_r).is_err());
+
+        drop(client_w);
         drop(client_r);
-        drop(orch_w);
+        drop(orch_r);
         drop(mock_client);
         drop(mock_orch);
         let _ = session_handle.join();
@@ -707,110 +4574,172 @@ mod tests {
     }
 
     #[test]
-    fn tts_chunking_splits_large_audio() {
-        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
-        let tcp_port = tcp_listener.local_addr().unwrap().port();
-        let sock_path = temp_socket_path();
+    fn voices_are_listed_and_selected_by_name() {
+        let (mock_client, mock_orch, sock_path, session_handle) = setup_session("unused", 5000);
 
-        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+        let mut client_w = BufWriter::new(mock_client.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        let word_samples = |client_w: &mut BufWriter<TcpStream>,
+                            client_r: &mut BufReader<TcpStream>| {
+            write_client_msg(client_w, &ClientMsg::SpeakWord("mot".into())).unwrap();
+            let mut samples = 0;
+            loop {
+                match read_server_msg(client_r).unwrap() {
+                    ServerMsg::TtsAudioChunk(chunk) => samples += chunk.len(),
+                    ServerMsg::TtsEnd => break samples,
+                    other => panic!("Expected TtsAudioChunk/TtsEnd, got {other:?}"),
+                }
+            }
+        };
 
-        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
-        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::ListVoices).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::SetVoice("tenor".into())).unwrap();
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::VoiceList(voices) => assert_eq!(voices, ["alto", "bass"]),
+            other => panic!("Expected VoiceList, got {other:?}"),
+        }
+        // An unknown name is an error and keeps the current voice
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Error(e) => assert!(e.contains("Unknown voice \"tenor\""), "{e}"),
+            other => panic!("Expected Error, got {other:?}"),
+        }
+        assert_eq!(word_samples(&mut client_w, &mut client_r), 5000);
 
-        let mock_orch = UnixStream::connect(&sock_path).unwrap();
-        let (server_unix, _) = unix_listener.accept().unwrap();
+        // The cached word is synthesized again in the new voice
+        write_client_msg(&mut client_w, &ClientMsg::SetVoice("bass".into())).unwrap();
+        assert_eq!(word_samples(&mut client_w, &mut client_r), 5001);
 
-        // 10000 samples = 2 full chunks (4000) + 1 partial chunk (2000)
-        let session_handle = std::thread::spawn(move || {
-            run_session(
-                Box::new(MockTranscriber::new("ignored")),
-                Box::new(MockTtsEngine::new(10000)),
-                server_tcp,
-                server_unix,
-            )
-        });
+        drop(client_w);
+        drop(client_r);
+        drop(mock_client);
+        drop(mock_orch);
+        let _ = session_handle.join();
+        std::fs::remove_file(&sock_path).ok();
+    }
 
-        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
-        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Test".into())).unwrap();
+    #[test]
+    fn voice_markers_voice_one_reply() {
+        let (mock_client, mock_orch, sock_path, session_handle) = setup_session("unused", 5000);
 
         let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
-
-        let mut chunk_sizes = Vec::new();
-        let mut total_samples = Vec::new();
-        loop {
-            let msg = read_server_msg(&mut client_r).unwrap();
-            match msg {
-                ServerMsg::Text(_) => {} // display text (AI: ...)
-                ServerMsg::TtsAudioChunk(samples) => {
-                    assert!(
-                        samples.len() <= TTS_CHUNK_SIZE,
-                        "Chunk size {} exceeds max {}",
-                        samples.len(),
-                        TTS_CHUNK_SIZE
-                    );
-                    chunk_sizes.push(samples.len());
-                    total_samples.extend_from_slice(&samples);
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        // The mock adds one sample per voice index; the text shown is returned too
+        let mut reply = |text: &str| {
+            write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText(text.into()))
+                .unwrap();
+            let (mut shown, mut samples) = (String::new(), 0);
+            loop {
+                match read_server_msg(&mut client_r).unwrap() {
+                    ServerMsg::Text(t) => shown = t,
+                    ServerMsg::TtsAudioChunk(chunk) => samples += chunk.len(),
+                    ServerMsg::TtsEnd => return (shown, samples),
+                    other => panic!("Expected Text, TtsAudioChunk or TtsEnd, got {other:?}"),
                 }
-                ServerMsg::TtsEnd => break,
-                other => panic!("Expected Text, TtsAudioChunk or TtsEnd, got {other:?}"),
             }
-        }
-
-        // Verify chunking: 4000 + 4000 + 2000 = 10000
-        assert_eq!(chunk_sizes, vec![4000, 4000, 2000]);
-        assert_eq!(total_samples.len(), 10000);
+        };
 
-        // Verify sample content (ramp pattern from MockTtsEngine)
-        for (i, &sample) in total_samples.iter().enumerate() {
-            assert_eq!(sample, i as i16);
-        }
+        assert_eq!(
+            reply("[VOICE:bass] Your table is ready."),
+            ("AI: Your table is ready.".to_string(), 5001)
+        );
+        assert_eq!(reply("Thank you.").1, 5000);
+        assert_eq!(
+            reply("[VOICE:bass] [SPEED:0.6] Enjoy it, sir. Your meal is served."),
+            (
+                "AI: Enjoy it, sir. Your meal is served.".to_string(),
+                10002 - CROSSFADE_LEN
+            )
+        );
+        // An unknown voice still speaks the reply, in the current voice
+        assert_eq!(
+            reply("[VOICE:tenor] Next please."),
+            ("AI: Next please.".to_string(), 5000)
+        );
 
-        // Cleanup
         drop(client_r);
         drop(orch_w);
         drop(mock_client);
         drop(mock_orch);
-        let _ = session_handle.join();
+        assert!(session_handle.join().unwrap().is_ok());
         std::fs::remove_file(&sock_path).ok();
     }
 
-    // --- Pause/Resume tests ---
-
-    /// Helper: set up a session with TCP + Unix connections, returning handles for test interaction.
-    fn setup_session(
-        transcriber_text: &str,
-        tts_samples: usize,
-    ) -> (
-        TcpStream,  // mock client
-        UnixStream, // mock orchestrator
-        String,     // socket path
-        std::thread::JoinHandle<Result<()>>,
-    ) {
-        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
-        let tcp_port = tcp_listener.local_addr().unwrap().port();
-        let sock_path = temp_socket_path();
-        let unix_listener = UnixListener::bind(&sock_path).unwrap();
-
-        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
-        let (server_tcp, _) = tcp_listener.accept().unwrap();
+    #[test]
+    fn word_cache_evicts_least_recently_used() {
+        let mut cache = WordCache::new(2);
+        cache.insert("un".into(), Arc::new(vec![1]));
+        cache.insert("deux".into(), Arc::new(vec![2]));
+        assert!(cache.get("un").is_some());
+        cache.insert("trois".into(), Arc::new(vec![3]));
+        assert!(cache.get("deux").is_none());
+        assert_eq!(*cache.get("un").unwrap(), vec![1]);
+        assert_eq!(*cache.get("trois").unwrap(), vec![3]);
+    }
 
-        let mock_orch = UnixStream::connect(&sock_path).unwrap();
-        let (server_unix, _) = unix_listener.accept().unwrap();
+    #[test]
+    fn phrase_cache_evicts_least_recently_used_within_its_budget() {
+        let key = |text: &str| PhraseKey::new(text, None, 0);
+        // Room for two 100-sample sentences
+        let mut cache = PhraseCache::new(400);
+        cache.insert(key("Un."), Arc::new(vec![1; 100]));
+        cache.insert(key("Deux."), Arc::new(vec![2; 100]));
+        assert!(cache.get(&key("Un.")).is_some());
+        cache.insert(key("Trois."), Arc::new(vec![3; 100]));
+        assert!(cache.get(&key("Deux.")).is_none());
+        assert!(cache.get(&key("Un.")).is_some());
+        assert_eq!(cache.bytes, 400);
+
+        // Larger than the whole budget: not kept, nothing evicted
+        cache.insert(key("Long."), Arc::new(vec![4; 201]));
+        assert!(cache.get(&key("Long.")).is_none());
+        assert_eq!(cache.entries.len(), 2);
+
+        // A budget of 0 keeps nothing
+        let mut off = PhraseCache::new(0);
+        off.insert(key("Un."), Arc::new(vec![1]));
+        assert!(off.get(&key("Un.")).is_none());
+    }
 
-        let text = transcriber_text.to_string();
-        let session_handle = std::thread::spawn(move || {
-            run_session(
-                Box::new(MockTranscriber::new(&text)),
-                Box::new(MockTtsEngine::new(tts_samples)),
-                server_tcp,
-                server_unix,
-            )
-        });
+    #[test]
+    fn cached_synthesis_keys_on_text_speed_and_voice() {
+        let tts = CountingTtsEngine::default();
+        let cache = Mutex::new(PhraseCache::new(1 << 20));
+        let calls = || tts.calls.load(Ordering::SeqCst);
+
+        let first = synthesize_cached(&tts, &cache, "Très bien !", None, 0).unwrap();
+        let again = synthesize_cached(&tts, &cache, "Très  bien !", None, 0).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(calls(), 1);
+
+        // Another speed or voice is another recording
+        synthesize_cached(&tts, &cache, "Très bien !", Some(0.6), 0).unwrap();
+        synthesize_cached(&tts, &cache, "Très bien !", None, 1).unwrap();
+        assert_eq!(calls(), 3);
+        synthesize_cached(&tts, &cache, "Très bien !", Some(0.6), 0).unwrap();
+        assert_eq!(calls(), 3);
+
+        // A failed synthesis is not kept: the next one tries again
+        assert!(synthesize_cached(&tts, &cache, "fail", None, 0).is_err());
+        assert!(synthesize_cached(&tts, &cache, "fail", None, 0).is_err());
+        assert_eq!(calls(), 5);
+    }
 
-        (mock_client, mock_orch, sock_path, session_handle)
+    #[test]
+    fn normalize_spoken_word_trims_and_bounds() {
+        assert_eq!(normalize_spoken_word("  chat "), Some("chat"));
+        assert_eq!(normalize_spoken_word(" \t "), None);
+        assert_eq!(
+            normalize_spoken_word(&"é".repeat(MAX_SPOKEN_WORD_LEN)).map(str::len),
+            Some(MAX_SPOKEN_WORD_LEN * 2)
+        );
+        assert_eq!(
+            normalize_spoken_word(&"a".repeat(MAX_SPOKEN_WORD_LEN + 1)),
+            None
+        );
     }
 
     #[test]
+    #[allow(clippy::unnecessary_map_or)]
     fn pause_drops_audio_segments() {
         let (mock_client, mock_orch, sock_path, session_handle) = setup_session("Hello", 8000);
 
@@ -822,7 +4751,7 @@ mod tests {
         let mut orch_r = BufReader::new(orch_clone);
 
         // 1. Normal: send audio → orchestrator receives TranscribedText
-        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 1600])).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
         let msg = read_orchestrator_msg(&mut orch_r).unwrap();
         match msg {
             OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Hello"),
@@ -835,7 +4764,7 @@ mod tests {
         std::thread::sleep(Duration::from_millis(50));
 
         // 3. Send audio during pause → orchestrator should NOT receive anything
-        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 1600])).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
         match read_orchestrator_msg(&mut orch_r) {
             Err(e) => {
                 let is_timeout = e.downcast_ref::<std::io::Error>().map_or(false, |io| {
@@ -872,7 +4801,7 @@ mod tests {
         std::thread::sleep(Duration::from_millis(50));
 
         // 3. Send audio → orchestrator should receive TranscribedText again
-        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 1600])).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
         let msg = read_orchestrator_msg(&mut orch_r).unwrap();
         match msg {
             OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Resumed"),
@@ -925,6 +4854,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_map_or)]
     fn full_pause_resume_cycle() {
         let (mock_client, mock_orch, sock_path, session_handle) = setup_session("Cycle", 4000);
 
@@ -938,7 +4868,7 @@ mod tests {
         let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
 
         // 1. Normal: audio flows through
-        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 1600])).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
         let msg = read_orchestrator_msg(&mut orch_r).unwrap();
         match msg {
             OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Cycle"),
@@ -949,7 +4879,7 @@ mod tests {
         write_client_msg(&mut client_w, &ClientMsg::PauseRequest).unwrap();
         std::thread::sleep(Duration::from_millis(50));
 
-        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 1600])).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
         match read_orchestrator_msg(&mut orch_r) {
             Err(e) => {
                 let is_timeout = e.downcast_ref::<std::io::Error>().map_or(false, |io| {
@@ -976,7 +4906,7 @@ mod tests {
         write_client_msg(&mut client_w, &ClientMsg::ResumeRequest).unwrap();
         std::thread::sleep(Duration::from_millis(50));
 
-        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 1600])).unwrap();
+        write_client_msg(&mut client_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
 
         // Reset read timeout for resumed operation
         orch_r.get_ref().set_read_timeout(None).unwrap();
@@ -1022,6 +4952,16 @@ mod tests {
         write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::SessionEnd).unwrap();
         drop(orch_w);
 
+        // Client is told why before the connection closes
+        mock_client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::SessionEnded(reason) => assert_eq!(reason, END_REASON_ORCHESTRATOR),
+            other => panic!("Expected SessionEnded, got {other:?}"),
+        }
+
         // Session should end cleanly (join with timeout via mpsc channel)
         let (done_tx, done_rx) = std::sync::mpsc::channel::<Result<()>>();
         std::thread::spawn(move || {
@@ -1035,9 +4975,77 @@ mod tests {
             .expect("session should end within 5 seconds");
         assert!(result.is_ok(), "session should end cleanly on SessionEnd");
 
-        // Cleanup
+        // Cleanup
+        drop(mock_client);
+        drop(mock_orch);
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn orchestrator_state_transitions() {
+        use OrchestratorState::*;
+        use OrchestratorVerdict::*;
+        let start = OrchestratorMsg::SessionStart("{}".into());
+        let reply = OrchestratorMsg::ResponseText("Hi".into());
+        let end = OrchestratorMsg::SessionEnd;
+
+        assert_eq!(
+            AwaitingStart.on_message(&reply),
+            (AwaitingStart, Reject(ERR_SESSION_NOT_STARTED))
+        );
+        assert_eq!(
+            AwaitingStart.on_message(&end),
+            (AwaitingStart, Reject(ERR_SESSION_NOT_STARTED))
+        );
+        assert_eq!(AwaitingStart.on_message(&start), (Active, Start));
+        assert_eq!(Active.on_message(&reply), (Active, Route));
+        assert_eq!(
+            Active.on_message(&start),
+            (Active, Reject(ERR_SESSION_ALREADY_STARTED))
+        );
+        assert_eq!(Active.on_message(&end), (Ended, End));
+        assert_eq!(
+            Ended.on_message(&reply),
+            (Ended, Reject(ERR_SESSION_NOT_STARTED))
+        );
+        assert_eq!(Ended.on_message(&start), (Active, Start));
+    }
+
+    #[test]
+    fn second_session_start_is_rejected_without_disturbing_the_session() {
+        let (mock_client, mock_orch, sock_path, session_handle) = setup_session("test", 4000);
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+        let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::SessionStart("{}".into())).unwrap();
+        match read_server_orc_msg(&mut orch_r).unwrap() {
+            ServerOrcMsg::Error(e) => assert_eq!(e, ERR_SESSION_ALREADY_STARTED),
+            other => panic!("Expected Error, got {other:?}"),
+        }
+
+        // The client never hears about it and the next reply plays as usual
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText("Hi".into())).unwrap();
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "AI: Hi"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+        let mut samples = 0;
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::TtsAudioChunk(chunk) => samples += chunk.len(),
+                ServerMsg::TtsEnd => break,
+                other => panic!("Expected TtsAudioChunk or TtsEnd, got {other:?}"),
+            }
+        }
+        assert_eq!(samples, 4000);
+
+        drop(orch_w);
+        drop(orch_r);
+        drop(client_r);
         drop(mock_client);
         drop(mock_orch);
+        let _ = session_handle.join();
         std::fs::remove_file(&sock_path).ok();
     }
 
@@ -1069,6 +5077,279 @@ mod tests {
         std::fs::remove_file(&sock_path).ok();
     }
 
+    // --- Session takeover tests ---
+
+    /// Handles returned by `setup_takeover_session`.
+    struct TakeoverSession {
+        client: TcpStream,
+        orch: UnixStream,
+        /// Listener for later clients.
+        listener: TcpListener,
+        handoff_tx: crossbeam_channel::Sender<ClientHandoff>,
+        stop: Arc<AtomicBool>,
+        sock_path: String,
+        handle: std::thread::JoinHandle<Result<SessionOutcome>>,
+    }
+
+    /// Helper: like `setup_session`, but with a handoff channel for later clients
+    /// and a stop flag.
+    fn setup_takeover_session() -> TakeoverSession {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = tcp_listener.local_addr().unwrap().port();
+        let sock_path = temp_socket_path();
+        let unix_listener = UnixListener::bind(&sock_path).unwrap();
+
+        let mock_client = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+
+        let mock_orch = UnixStream::connect(&sock_path).unwrap();
+        let (server_unix, _) = unix_listener.accept().unwrap();
+
+        let (handoff_tx, handoff_rx) = crossbeam_channel::bounded(1);
+        let stop = Arc::new(AtomicBool::new(false));
+        let session_stop = stop.clone();
+        let session_handle = std::thread::spawn(move || {
+            run_session(
+                &mut MockTranscriber::new("Still here"),
+                Arc::new(MockTtsEngine::new(4000)),
+                Transport::Plain(server_tcp),
+                server_unix,
+                &handoff_rx,
+                &crossbeam_channel::never(),
+                &session_stop,
+                &SessionOptions::default(),
+                &SessionStats::default(),
+            )
+        });
+
+        TakeoverSession {
+            client: mock_client,
+            orch: mock_orch,
+            listener: tcp_listener,
+            handoff_tx,
+            stop,
+            sock_path,
+            handle: session_handle,
+        }
+    }
+
+    #[test]
+    fn takeover_rebinds_new_client_and_keeps_orchestrator() {
+        let TakeoverSession {
+            client: old_client,
+            orch: mock_orch,
+            listener: tcp_listener,
+            handoff_tx,
+            sock_path,
+            handle: session_handle,
+            ..
+        } = setup_takeover_session();
+        let port = tcp_listener.local_addr().unwrap().port();
+
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(mock_orch.try_clone().unwrap());
+
+        // Second client connects and takes over
+        let new_client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        handoff_tx
+            .send(ClientHandoff {
+                stream: Transport::Plain(server_tcp),
+                take_over: true,
+            })
+            .unwrap();
+
+        // Old client is told why, then disconnected
+        old_client
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut old_r = BufReader::new(old_client);
+        match read_server_msg(&mut old_r).unwrap() {
+            ServerMsg::SessionEnded(reason) => assert_eq!(reason, END_REASON_TAKEOVER),
+            other => panic!("Expected SessionEnded, got {other:?}"),
+        }
+        let err = read_server_msg(&mut old_r).unwrap_err();
+        assert!(is_disconnect(&err), "old client should see EOF, got {err}");
+
+        // New client's audio reaches the same orchestrator link
+        let mut new_w = BufWriter::new(new_client.try_clone().unwrap());
+        let mut new_r = BufReader::new(new_client.try_clone().unwrap());
+        write_client_msg(&mut new_w, &ClientMsg::AudioSegment(vec![0; 8000])).unwrap();
+        match read_orchestrator_msg(&mut orch_r).unwrap() {
+            OrchestratorMsg::TranscribedText(t) => assert_eq!(t, "Still here"),
+            other => panic!("Expected TranscribedText, got {other:?}"),
+        }
+        match read_server_msg(&mut new_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "You: Still here"),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+
+        // ...and responses are routed to the new client
+        write_orchestrator_msg(
+            &mut orch_w,
+            &OrchestratorMsg::ResponseText("Welcome back.".into()),
+        )
+        .unwrap();
+        match read_server_msg(&mut new_r).unwrap() {
+            ServerMsg::Text(t) => assert_eq!(t, "AI: Welcome back."),
+            other => panic!("Expected Text, got {other:?}"),
+        }
+
+        // SessionEnd still ends the session normally
+        write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::SessionEnd).unwrap();
+        let outcome = session_handle.join().unwrap().unwrap();
+        assert!(matches!(outcome, SessionOutcome::Ended));
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn fresh_start_ends_session_and_returns_new_client() {
+        let TakeoverSession {
+            client: old_client,
+            orch: mock_orch,
+            listener: tcp_listener,
+            handoff_tx,
+            sock_path,
+            handle: session_handle,
+            ..
+        } = setup_takeover_session();
+        let port = tcp_listener.local_addr().unwrap().port();
+
+        let new_client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let (server_tcp, _) = tcp_listener.accept().unwrap();
+        handoff_tx
+            .send(ClientHandoff {
+                stream: Transport::Plain(server_tcp),
+                take_over: false,
+            })
+            .unwrap();
+
+        let outcome = session_handle.join().unwrap().unwrap();
+        let SessionOutcome::StartFresh(next) = outcome else {
+            panic!("Expected StartFresh");
+        };
+
+        // Orchestrator link was closed
+        mock_orch
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut orch_r = BufReader::new(mock_orch);
+        assert!(is_disconnect(
+            &read_orchestrator_msg(&mut orch_r).unwrap_err()
+        ));
+
+        // The returned stream is still connected to the new client
+        let mut w = BufWriter::new(next);
+        write_server_msg(&mut w, &ServerMsg::TtsEnd).unwrap();
+        let mut new_r = BufReader::new(new_client);
+        assert!(matches!(
+            read_server_msg(&mut new_r).unwrap(),
+            ServerMsg::TtsEnd
+        ));
+
+        // The old client was told why its session ended
+        old_client
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut old_r = BufReader::new(old_client);
+        match read_server_msg(&mut old_r).unwrap() {
+            ServerMsg::SessionEnded(reason) => assert_eq!(reason, END_REASON_FRESH_START),
+            other => panic!("Expected SessionEnded, got {other:?}"),
+        }
+
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn stop_flag_tells_client_and_ends_session() {
+        let TakeoverSession {
+            client,
+            orch: mock_orch,
+            stop,
+            sock_path,
+            handle: session_handle,
+            ..
+        } = setup_takeover_session();
+
+        stop.store(true, Ordering::SeqCst);
+        let outcome = session_handle.join().unwrap().unwrap();
+        assert!(matches!(outcome, SessionOutcome::Shutdown));
+
+        client
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut client_r = BufReader::new(client);
+        match read_server_msg(&mut client_r).unwrap() {
+            ServerMsg::SessionEnded(reason) => assert_eq!(reason, END_REASON_SHUTDOWN),
+            other => panic!("Expected SessionEnded, got {other:?}"),
+        }
+        assert!(is_disconnect(&read_server_msg(&mut client_r).unwrap_err()));
+
+        // The orchestrator gets SessionEnd before its link closes
+        mock_orch
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut orch_r = BufReader::new(mock_orch);
+        assert!(matches!(
+            read_server_orc_msg(&mut orch_r).unwrap(),
+            ServerOrcMsg::SessionEnd
+        ));
+        assert!(is_disconnect(
+            &read_server_orc_msg(&mut orch_r).unwrap_err()
+        ));
+
+        std::fs::remove_file(&sock_path).ok();
+    }
+
+    #[test]
+    fn stop_after_a_reply_sends_goodbye_after_tts_end() {
+        let TakeoverSession {
+            client,
+            orch: mock_orch,
+            stop,
+            sock_path,
+            handle: session_handle,
+            ..
+        } = setup_takeover_session();
+
+        let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+        write_orchestrator_msg(
+            &mut orch_w,
+            &OrchestratorMsg::ResponseText("Goodbye for now.".into()),
+        )
+        .unwrap();
+
+        client
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut client_r = BufReader::new(client);
+        loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::TtsEnd => break,
+                ServerMsg::SessionEnded(reason) => panic!("SessionEnded before TtsEnd: {reason}"),
+                _ => {}
+            }
+        }
+
+        stop.store(true, Ordering::SeqCst);
+        let outcome = session_handle.join().unwrap().unwrap();
+        assert!(matches!(outcome, SessionOutcome::Shutdown));
+
+        // The goodbye is the last message before EOF
+        let goodbye = loop {
+            match read_server_msg(&mut client_r).unwrap() {
+                ServerMsg::SessionEnded(reason) => break reason,
+                ServerMsg::TurnStats(_) => {}
+                other => panic!("Expected SessionEnded, got {other:?}"),
+            }
+        };
+        assert_eq!(goodbye, END_REASON_SHUTDOWN);
+        assert!(is_disconnect(&read_server_msg(&mut client_r).unwrap_err()));
+
+        drop(mock_orch);
+        std::fs::remove_file(&sock_path).ok();
+    }
+
     // --- Barge-in / InterruptTts tests ---
 
     #[test]
@@ -1182,10 +5463,77 @@ mod tests {
 
     // --- Speed marker parsing tests ---
 
+    #[test]
+    fn messages_before_session_start_are_rejected() {
+        use space_lt_common::protocol::{
+            ServerOrcMsg, read_server_orc_msg, write_orchestrator_msg,
+        };
+
+        let (server, orch) = UnixStream::pair().unwrap();
+        let handshake = std::thread::spawn(move || await_session_start(&server));
+
+        let mut orch_w = BufWriter::new(orch.try_clone().unwrap());
+        let mut orch_r = BufReader::new(orch);
+        for msg in [
+            OrchestratorMsg::ResponseText("Too early".into()),
+            OrchestratorMsg::SessionEnd,
+            OrchestratorMsg::SessionStart("{}".into()),
+        ] {
+            write_orchestrator_msg(&mut orch_w, &msg).unwrap();
+        }
+        for _ in 0..2 {
+            match read_server_orc_msg(&mut orch_r).unwrap() {
+                ServerOrcMsg::Error(e) => assert_eq!(e, ERR_SESSION_NOT_STARTED),
+                other => panic!("Expected Error, got {other:?}"),
+            }
+        }
+        assert_eq!(handshake.join().unwrap().unwrap(), "{}");
+        // Ready waits for the session that routes for it
+        orch_r.get_ref().set_nonblocking(true).unwrap();
+        assert!(read_server_orc_msg(&mut orch_r).is_err());
+    }
+
+    #[test]
+    fn context_keeps_the_latest_lines_and_characters() {
+        let context = ConversationContext::default();
+        assert_eq!(context.text(), "");
+        for n in 1..=CONTEXT_LINES + 2 {
+            context.push(&format!(" Line {n}. "));
+        }
+        assert_eq!(
+            context.text(),
+            "Line 3. Line 4. Line 5. Line 6. Line 7. Line 8."
+        );
+
+        // Too long: cut at a word, the end kept
+        context.push(&"Kokoro speaks. ".repeat(60));
+        let text = context.text();
+        assert!(text.chars().count() <= CONTEXT_MAX_CHARS, "{text}");
+        assert!(text.starts_with("Kokoro speaks."), "{text}");
+        assert!(text.ends_with("Kokoro speaks."), "{text}");
+    }
+
+    #[test]
+    fn idle_timer_warns_once_then_ends() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let timer = IdleTimer::new(Duration::from_secs(600), start);
+        assert_eq!(timer.check(at(599)), IdleAction::None);
+        assert_eq!(timer.check(at(600)), IdleAction::Warn);
+        assert_eq!(timer.check(at(630)), IdleAction::None);
+
+        // Activity starts the count again, warning included
+        timer.activity(at(640));
+        assert_eq!(timer.check(at(1239)), IdleAction::None);
+        assert_eq!(timer.check(at(1240)), IdleAction::Warn);
+        assert_eq!(timer.check(at(1240) + IDLE_GRACE_PERIOD), IdleAction::End);
+        assert!(timer.warning().starts_with("No activity for 10 min"));
+    }
+
     #[test]
     fn parse_speed_marker_with_valid_marker() {
         let (speed, text) = parse_speed_marker("[SPEED:0.6] Sure, I will speak more slowly.");
-        assert_eq!(speed, Some(0.6));
+        assert_eq!(speed, Some(SpeedMarker::Reply(0.6)));
         assert_eq!(text, "Sure, I will speak more slowly.");
     }
 
@@ -1199,10 +5547,60 @@ mod tests {
     #[test]
     fn parse_speed_marker_with_different_speeds() {
         let (speed, _) = parse_speed_marker("[SPEED:1.2] Fast speech");
-        assert_eq!(speed, Some(1.2));
+        assert_eq!(speed, Some(SpeedMarker::Reply(1.2)));
 
         let (speed, _) = parse_speed_marker("[SPEED:0.5] Very slow");
-        assert_eq!(speed, Some(0.5));
+        assert_eq!(speed, Some(SpeedMarker::Reply(0.5)));
+    }
+
+    #[test]
+    fn parse_speed_marker_defaults_resets_and_clamps() {
+        assert_eq!(
+            parse_speed_marker("[SPEED_DEFAULT:0.6] From now on."),
+            (Some(SpeedMarker::Default(0.6)), "From now on.")
+        );
+        assert_eq!(
+            parse_speed_marker("[SPEED:reset] Back to normal."),
+            (Some(SpeedMarker::Reset), "Back to normal.")
+        );
+        assert_eq!(
+            parse_speed_marker("[SPEED:5] Fast.").0,
+            Some(SpeedMarker::Reply(2.0))
+        );
+        assert_eq!(
+            parse_speed_marker("[SPEED_DEFAULT:0.1] Slow.").0,
+            Some(SpeedMarker::Default(0.3))
+        );
+        // Not a speed: left as text
+        for text in [
+            "[SPEED:fast] Hi",
+            "[SPEED:NaN] Hi",
+            "[SPEED_DEFAULT:reset] Hi",
+        ] {
+            assert_eq!(parse_speed_marker(text), (None, text));
+        }
+    }
+
+    #[test]
+    fn speed_markers_last_one_reply_unless_made_the_default() {
+        let mut session = None;
+        assert_eq!(
+            take_markers("[SPEED:0.6] [VOICE:bass] Slowly.", &mut session),
+            (Some(0.6), Some("bass"), "Slowly.")
+        );
+        assert_eq!(
+            take_markers("Normal.", &mut session),
+            (None, None, "Normal.")
+        );
+
+        assert_eq!(
+            take_markers("[VOICE:bass] [SPEED_DEFAULT:1.2] Faster.", &mut session),
+            (Some(1.2), Some("bass"), "Faster.")
+        );
+        assert_eq!(take_markers("[SPEED:0.5] Once.", &mut session).0, Some(0.5));
+        assert_eq!(take_markers("Still fast.", &mut session).0, Some(1.2));
+        assert_eq!(take_markers("[SPEED:reset] Normal.", &mut session).0, None);
+        assert_eq!(session, None);
     }
 
     // --- Deterministic send_tts_audio unit tests ---
@@ -1211,13 +5609,14 @@ mod tests {
     fn send_tts_audio_interrupted_before_first_chunk() {
         let interrupted = AtomicBool::new(true);
         let samples: Vec<i16> = (0..20000).map(|i| i as i16).collect();
-        let mut buf = Vec::new();
+        let buf = Mutex::new(Vec::new());
 
-        let was_interrupted = send_tts_audio(&mut buf, &samples, &interrupted).unwrap();
+        let was_interrupted =
+            send_tts_audio(&buf, &samples, TtsChunks::default(), &interrupted, None).unwrap();
         assert!(was_interrupted, "Should report interruption");
 
         // Should contain only TtsEnd (no audio chunks)
-        let mut cursor = std::io::Cursor::new(buf);
+        let mut cursor = std::io::Cursor::new(buf.into_inner().unwrap());
         let msg = read_server_msg(&mut cursor).unwrap();
         assert!(
             matches!(msg, ServerMsg::TtsEnd),
@@ -1231,13 +5630,14 @@ mod tests {
     fn send_tts_audio_completes_without_interrupt() {
         let interrupted = AtomicBool::new(false);
         let samples: Vec<i16> = (0..20000).map(|i| i as i16).collect();
-        let mut buf = Vec::new();
+        let buf = Mutex::new(Vec::new());
 
-        let was_interrupted = send_tts_audio(&mut buf, &samples, &interrupted).unwrap();
+        let was_interrupted =
+            send_tts_audio(&buf, &samples, TtsChunks::default(), &interrupted, None).unwrap();
         assert!(!was_interrupted, "Should not report interruption");
 
         // Should contain 5 TtsAudioChunk + 1 TtsEnd
-        let mut cursor = std::io::Cursor::new(buf);
+        let mut cursor = std::io::Cursor::new(buf.into_inner().unwrap());
         let mut chunk_count = 0;
         loop {
             let msg = read_server_msg(&mut cursor).unwrap();
@@ -1256,32 +5656,62 @@ mod tests {
     #[test]
     fn send_tts_audio_interrupted_mid_stream() {
         let interrupted = AtomicBool::new(false);
-        let mut buf = Vec::new();
+        let buf = Mutex::new(Vec::new());
 
         // First call: send 2 chunks normally (no interrupt)
         let small_samples: Vec<i16> = (0..8000).map(|i| i as i16).collect();
-        let was_interrupted = send_tts_audio(&mut buf, &small_samples, &interrupted).unwrap();
+        let was_interrupted = send_tts_audio(
+            &buf,
+            &small_samples,
+            TtsChunks::default(),
+            &interrupted,
+            None,
+        )
+        .unwrap();
         assert!(!was_interrupted);
 
         // Now test with flag pre-set: 0 chunks should be sent
-        buf.clear();
+        buf.lock().unwrap().clear();
         interrupted.store(true, Ordering::SeqCst);
         let big_samples: Vec<i16> = (0..20000).map(|i| i as i16).collect();
-        let was_interrupted = send_tts_audio(&mut buf, &big_samples, &interrupted).unwrap();
+        let was_interrupted =
+            send_tts_audio(&buf, &big_samples, TtsChunks::default(), &interrupted, None).unwrap();
         assert!(was_interrupted);
 
-        let mut cursor = std::io::Cursor::new(buf);
+        let mut cursor = std::io::Cursor::new(buf.into_inner().unwrap());
         let msg = read_server_msg(&mut cursor).unwrap();
         assert!(matches!(msg, ServerMsg::TtsEnd));
     }
 
+    #[test]
+    fn parse_voice_marker_strips_a_leading_marker() {
+        assert_eq!(
+            parse_voice_marker("[VOICE:am_adam] Your table is ready."),
+            (Some("am_adam"), "Your table is ready.")
+        );
+        assert_eq!(
+            parse_voice_marker("Say [VOICE:am_adam] here"),
+            (None, "Say [VOICE:am_adam] here")
+        );
+        // Empty or unterminated: left as text
+        assert_eq!(parse_voice_marker("[VOICE:] Hi"), (None, "[VOICE:] Hi"));
+        assert_eq!(
+            parse_voice_marker("[VOICE:am_adam Hi"),
+            (None, "[VOICE:am_adam Hi")
+        );
+    }
+
     // --- Sentence splitting tests ---
 
     #[test]
     fn split_sentences_multiple() {
         assert_eq!(
-            split_sentences("Hello. How are you? I'm fine!"),
-            vec!["Hello.", "How are you?", "I'm fine!"]
+            split_sentences("Hello there, friend. How are you today? I'm fine, thanks!"),
+            vec![
+                "Hello there, friend.",
+                "How are you today?",
+                "I'm fine, thanks!"
+            ]
         );
     }
 
@@ -1316,16 +5746,16 @@ mod tests {
     #[test]
     fn split_sentences_extra_spaces() {
         assert_eq!(
-            split_sentences("Hello.  Extra  spaces.  "),
-            vec!["Hello.", "Extra  spaces."]
+            split_sentences("Hello to you.  Extra  spaces here.  "),
+            vec!["Hello to you.", "Extra  spaces here."]
         );
     }
 
     #[test]
     fn split_sentences_mixed_punctuation() {
         assert_eq!(
-            split_sentences("Really? Yes! OK."),
-            vec!["Really?", "Yes!", "OK."]
+            split_sentences("Is that really true? Yes it is! OK, I see."),
+            vec!["Is that really true?", "Yes it is!", "OK, I see."]
         );
     }
 
@@ -1341,9 +5771,108 @@ mod tests {
     #[test]
     fn split_sentences_trailing_no_punctuation() {
         assert_eq!(
-            split_sentences("First sentence. And then more"),
-            vec!["First sentence.", "And then more"]
+            split_sentences("This is the first sentence. And then some more"),
+            vec!["This is the first sentence.", "And then some more"]
+        );
+    }
+
+    #[test]
+    fn split_sentences_keeps_abbreviations() {
+        assert_eq!(
+            split_sentences("Mr. Smith went home. He met Dr. Lee and Mrs. Brown there."),
+            vec![
+                "Mr. Smith went home.",
+                "He met Dr. Lee and Mrs. Brown there."
+            ]
+        );
+        assert_eq!(
+            split_sentences("Bring fruit, e.g. apples or pears. Cats vs. dogs is fun."),
+            vec![
+                "Bring fruit, e.g. apples or pears.",
+                "Cats vs. dogs is fun."
+            ]
+        );
+        // Case does not matter, but the word must be whole
+        assert_eq!(
+            split_sentences("I saw DR. Jones today. It was at the ER. Then I left."),
+            vec![
+                "I saw DR. Jones today.",
+                "It was at the ER.",
+                "Then I left."
+            ]
+        );
+    }
+
+    #[test]
+    fn split_sentences_keeps_decimals() {
+        assert_eq!(
+            split_sentences("It costs $3.50 today. Version 2.0 is out now."),
+            vec!["It costs $3.50 today.", "Version 2.0 is out now."]
+        );
+    }
+
+    #[test]
+    fn split_sentences_handles_ellipses() {
+        // A pause inside one sentence
+        assert_eq!(
+            split_sentences("Wait... are you really sure? That is amazing news!"),
+            vec!["Wait... are you really sure?", "That is amazing news!"]
+        );
+        // A sentence ending in an ellipsis, dots kept together
+        assert_eq!(
+            split_sentences("I was thinking\u{2026} We could go out tonight."),
+            vec!["I was thinking\u{2026}", "We could go out tonight."]
+        );
+        assert_eq!(
+            split_sentences("Let me think about it... Maybe we could go tomorrow."),
+            vec!["Let me think about it...", "Maybe we could go tomorrow."]
+        );
+        assert_eq!(
+            split_sentences("You did what?! That is so brave of you."),
+            vec!["You did what?!", "That is so brave of you."]
+        );
+    }
+
+    #[test]
+    fn split_sentences_keeps_closing_quotes_and_brackets() {
+        assert_eq!(
+            split_sentences("He said \"go home.\" Then he left the room."),
+            vec!["He said \"go home.\"", "Then he left the room."]
+        );
+        assert_eq!(
+            split_sentences("Try this one (it is easy.) Then try the next one."),
+            vec!["Try this one (it is easy.)", "Then try the next one."]
         );
+        assert_eq!(
+            split_sentences("She asked \u{201c}why not?\u{201d} And she smiled at me."),
+            vec![
+                "She asked \u{201c}why not?\u{201d}",
+                "And she smiled at me."
+            ]
+        );
+    }
+
+    #[test]
+    fn split_sentences_merges_short_fragments() {
+        // Into the next sentence
+        assert_eq!(
+            split_sentences("Hello. How are you today? Great."),
+            vec!["Hello. How are you today? Great."]
+        );
+        assert_eq!(
+            split_sentences("Yes! Exactly. That is the right answer. Well done, my friend."),
+            vec![
+                "Yes! Exactly. That is the right answer.",
+                "Well done, my friend."
+            ]
+        );
+        // The last one into the previous sentence
+        assert_eq!(
+            split_sentences("That is the right answer. Well done!"),
+            vec!["That is the right answer. Well done!"]
+        );
+        // Short on its own
+        assert_eq!(split_sentences("Hi. Bye."), vec!["Hi. Bye."]);
     }
 
     // --- Sentence-level mock TTS for streaming tests ---
@@ -1361,12 +5890,15 @@ mod tests {
     }
 
     impl TtsEngine for SentenceMockTtsEngine {
-        fn synthesize(&self, text: &str) -> anyhow::Result<Vec<i16>> {
+        fn synthesize_at(
+            &self,
+            text: &str,
+            _voice: usize,
+            _speed: Option<f32>,
+        ) -> anyhow::Result<Vec<i16>> {
             let count = self.samples_per_char * text.len();
             Ok((0..count).map(|i| i as i16).collect())
         }
-
-        fn set_speed(&self, _speed: f32) {}
     }
 
     fn setup_sentence_session(
@@ -1392,11 +5924,17 @@ mod tests {
         let text = transcriber_text.to_string();
         let session_handle = std::thread::spawn(move || {
             run_session(
-                Box::new(MockTranscriber::new(&text)),
-                Box::new(SentenceMockTtsEngine::new(samples_per_char)),
-                server_tcp,
+                &mut MockTranscriber::new(&text),
+                Arc::new(SentenceMockTtsEngine::new(samples_per_char)),
+                Transport::Plain(server_tcp),
                 server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
             )
+            .map(|_| ())
         });
 
         (mock_client, mock_orch, sock_path, session_handle)
@@ -1412,10 +5950,10 @@ mod tests {
         let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
         let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
 
-        // "Hi. Bye." = 2 sentences, each ~4 chars = 400 samples each
+        // 2 sentences, 100 samples per char
         write_orchestrator_msg(
             &mut orch_w,
-            &OrchestratorMsg::ResponseText("Hi. Bye.".into()),
+            &OrchestratorMsg::ResponseText("Hi there, Tom. See you soon.".into()),
         )
         .unwrap();
 
@@ -1430,8 +5968,9 @@ mod tests {
             }
         }
 
-        // "Hi." = 3 chars * 100 = 300 samples, "Bye." = 4 chars * 100 = 400 samples
-        assert_eq!(total_samples, 700);
+        // "Hi there, Tom." = 14 chars * 100 = 1400 samples, "See you soon." = 1300 samples,
+        // overlapping at the boundary
+        assert_eq!(total_samples, 2700 - CROSSFADE_LEN);
 
         drop(client_r);
         drop(orch_w);
@@ -1557,7 +6096,12 @@ mod tests {
     }
 
     impl TtsEngine for FailingMockTtsEngine {
-        fn synthesize(&self, text: &str) -> anyhow::Result<Vec<i16>> {
+        fn synthesize_at(
+            &self,
+            text: &str,
+            _voice: usize,
+            _speed: Option<f32>,
+        ) -> anyhow::Result<Vec<i16>> {
             let mut count = self.call_count.lock().unwrap();
             let current = *count;
             *count += 1;
@@ -1569,8 +6113,6 @@ mod tests {
             let n = self.samples_per_char * text.len();
             Ok((0..n).map(|i| i as i16).collect())
         }
-
-        fn set_speed(&self, _speed: f32) {}
     }
 
     fn setup_failing_session(
@@ -1594,16 +6136,76 @@ mod tests {
 
         let session_handle = std::thread::spawn(move || {
             run_session(
-                Box::new(MockTranscriber::new("ignored")),
-                Box::new(FailingMockTtsEngine::new(100, fail_on_call)),
-                server_tcp,
+                &mut MockTranscriber::new("ignored"),
+                Arc::new(FailingMockTtsEngine::new(100, fail_on_call)),
+                Transport::Plain(server_tcp),
                 server_unix,
+                &crossbeam_channel::never(),
+                &crossbeam_channel::never(),
+                &AtomicBool::new(false),
+                &SessionOptions::default(),
+                &SessionStats::default(),
             )
+            .map(|_| ())
         });
 
         (mock_client, mock_orch, sock_path, session_handle)
     }
 
+    #[test]
+    fn parallel_synthesis_shortens_a_long_reply_and_keeps_its_order() {
+        // The first sentence takes longest, so the later ones are ready before it
+        let text = "This first sentence is by far the longest one. Then a shorter one. \
+                    And one more after. The very end.";
+        let lengths = [46, 19, 19, 13];
+        let speak = |workers: usize| {
+            let (mock_client, mock_orch, sock_path, session_handle) = setup_session_with(
+                "unused",
+                Arc::new(WorkersTtsEngine { workers }),
+                cached_options(),
+            );
+            let mut client_r = BufReader::new(mock_client.try_clone().unwrap());
+            let mut orch_w = BufWriter::new(mock_orch.try_clone().unwrap());
+
+            let start = Instant::now();
+            write_orchestrator_msg(&mut orch_w, &OrchestratorMsg::ResponseText(text.into()))
+                .unwrap();
+            let mut samples = Vec::new();
+            loop {
+                match read_server_msg(&mut client_r).unwrap() {
+                    ServerMsg::Text(_) => {}
+                    ServerMsg::TtsAudioChunk(chunk) => samples.extend(chunk),
+                    ServerMsg::TtsEnd => break,
+                    other => panic!("Expected Text, TtsAudioChunk or TtsEnd, got {other:?}"),
+                }
+            }
+            let elapsed = start.elapsed();
+
+            drop(client_r);
+            drop(orch_w);
+            drop(mock_client);
+            drop(mock_orch);
+            assert!(session_handle.join().unwrap().is_ok());
+            std::fs::remove_file(&sock_path).ok();
+            (elapsed, samples)
+        };
+
+        let (sequential, in_order) = speak(1);
+        let (parallel, samples) = speak(4);
+        assert_eq!(samples, in_order);
+        assert_eq!(samples.len(), 4000 - 3 * CROSSFADE_LEN);
+        // The middle of each sentence, in the order of the reply
+        let middles: Vec<i16> = (0..4)
+            .map(|k| samples[k * (1000 - CROSSFADE_LEN) + 500])
+            .collect();
+        assert_eq!(middles, lengths);
+        // About 485 ms one after the other, 230 ms (the first sentence) at once
+        assert!(
+            parallel < sequential * 3 / 4,
+            "parallel {parallel:?}, sequential {sequential:?}"
+        );
+    }
+
     #[test]
     fn streaming_error_on_second_sentence_preserves_first() {
         // Fail on call 1 (second sentence) — first sentence audio should be preserved
@@ -1615,7 +6217,9 @@ mod tests {
         // 3 sentences: first synthesizes OK, second fails, third never attempted
         write_orchestrator_msg(
             &mut orch_w,
-            &OrchestratorMsg::ResponseText("First OK. Second fails. Third never.".into()),
+            &OrchestratorMsg::ResponseText(
+                "The first works. The second fails. The third never comes.".into(),
+            ),
         )
         .unwrap();
 
@@ -1630,8 +6234,9 @@ mod tests {
             }
         }
 
-        // "First OK." = 9 chars * 100 = 900 samples (first sentence delivered)
-        assert_eq!(total_samples, 900);
+        // "The first works." = 16 chars * 100 = 1600 samples (first sentence delivered,
+        // its held-back tail included)
+        assert_eq!(total_samples, 1600);
 
         drop(client_r);
         drop(orch_w);
@@ -1685,13 +6290,20 @@ mod tests {
     fn send_tts_chunks_no_tts_end() {
         let interrupted = AtomicBool::new(false);
         let samples: Vec<i16> = (0..8000).map(|i| i as i16).collect();
-        let mut buf = Vec::new();
-
-        let was_interrupted = send_tts_chunks(&mut buf, &samples, &interrupted).unwrap();
+        let buf = Mutex::new(Vec::new());
+
+        let was_interrupted = send_tts_chunks(
+            &buf,
+            &samples,
+            &mut TtsChunks::default(),
+            &interrupted,
+            None,
+        )
+        .unwrap();
         assert!(!was_interrupted);
 
         // Should contain 2 TtsAudioChunk messages, NO TtsEnd
-        let mut cursor = std::io::Cursor::new(buf);
+        let mut cursor = std::io::Cursor::new(buf.into_inner().unwrap());
         let mut chunk_count = 0;
         loop {
             match read_server_msg(&mut cursor) {
@@ -1703,6 +6315,107 @@ mod tests {
         assert_eq!(chunk_count, 2);
     }
 
+    /// The sizes of the TtsAudioChunks in `bytes`.
+    fn chunk_sizes(bytes: Vec<u8>) -> Vec<usize> {
+        let mut cursor = std::io::Cursor::new(bytes);
+        let mut sizes = Vec::new();
+        while let Ok(msg) = read_server_msg(&mut cursor) {
+            match msg {
+                ServerMsg::TtsAudioChunk(samples) => sizes.push(samples.len()),
+                other => panic!("Expected TtsAudioChunk only, got {other:?}"),
+            }
+        }
+        sizes
+    }
+
+    #[test]
+    fn send_tts_chunks_cuts_a_small_first_chunk_once() {
+        let interrupted = AtomicBool::new(false);
+        let samples = vec![0i16; 10000];
+        let send = |chunks: &mut TtsChunks| {
+            let buf = Mutex::new(Vec::new());
+            assert!(!send_tts_chunks(&buf, &samples, chunks, &interrupted, None).unwrap());
+            chunk_sizes(buf.into_inner().unwrap())
+        };
+
+        // By default, 250 ms chunks
+        let mut chunks = TtsChunks::default();
+        assert_eq!(send(&mut chunks), [4000, 4000, 2000]);
+        assert_eq!(send(&mut chunks), [4000, 4000, 2000]);
+
+        // A 100 ms first chunk, then 375 ms ones; the next sentence is all regular
+        let mut chunks = TtsChunks {
+            size: 6000,
+            first: Some(1600),
+        };
+        assert_eq!(send(&mut chunks), [1600, 6000, 2400]);
+        assert_eq!(send(&mut chunks), [6000, 4000]);
+
+        // A sentence shorter than the first chunk uses it up
+        let mut chunks = TtsChunks {
+            size: 6000,
+            first: Some(1600),
+        };
+        let buf = Mutex::new(Vec::new());
+        send_tts_chunks(&buf, &[0; 1000], &mut chunks, &interrupted, None).unwrap();
+        assert_eq!(chunk_sizes(buf.into_inner().unwrap()), [1000]);
+        assert_eq!(chunks.first, None);
+    }
+
+    /// Raises `interrupted` once more than `limit` bytes are written.
+    struct InterruptingWriter<'a> {
+        out: Vec<u8>,
+        limit: usize,
+        interrupted: &'a AtomicBool,
+    }
+
+    impl Write for InterruptingWriter<'_> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.out.extend_from_slice(buf);
+            if self.out.len() > self.limit {
+                self.interrupted.store(true, Ordering::SeqCst);
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn send_tts_chunks_stops_between_small_chunks() {
+        let interrupted = AtomicBool::new(false);
+        // Interrupted while the second chunk (800 bytes) is written
+        let buf = Mutex::new(InterruptingWriter {
+            out: Vec::new(),
+            limit: 1000,
+            interrupted: &interrupted,
+        });
+        let mut chunks = TtsChunks {
+            size: 400,
+            first: Some(160),
+        };
+        assert!(send_tts_chunks(&buf, &[0; 4000], &mut chunks, &interrupted, None).unwrap());
+        assert_eq!(chunk_sizes(buf.into_inner().unwrap().out), [160, 400]);
+    }
+
+    #[test]
+    fn pacer_keeps_the_lead_and_restarts_after_a_gap() {
+        let mut pacer = TtsPacer::new(Duration::from_millis(500));
+        let t0 = Instant::now();
+        // The first 500 ms are sent at once
+        assert_eq!(pacer.sent(4000, t0), Duration::ZERO);
+        assert_eq!(pacer.sent(4000, t0), Duration::ZERO);
+        // Then one chunk per chunk played
+        assert_eq!(pacer.sent(4000, t0), Duration::from_millis(250));
+        let t1 = t0 + Duration::from_millis(250);
+        assert_eq!(pacer.sent(4000, t1), Duration::from_millis(250));
+        // Playback ran dry during a slow synthesis
+        let t2 = t0 + Duration::from_secs(5);
+        assert_eq!(pacer.sent(4000, t2), Duration::ZERO);
+    }
+
     #[test]
     fn crossfade_smooths_sentence_boundary() {
         // Sentence A: constant amplitude 10000
@@ -1766,4 +6479,43 @@ mod tests {
         // Here we test the function itself handles short inputs gracefully
         assert_eq!(short_sentence.len(), original.len());
     }
+
+    #[test]
+    fn crossfader_sends_each_overlap_once() {
+        let sentences = [
+            vec![10000i16; 1000],
+            vec![-5000i16; 700],
+            vec![3000i16; 50], // too short to overlap
+            vec![8000i16; 400],
+        ];
+        let mut crossfader = Crossfader::default();
+        let mut sent = Vec::new();
+        for sentence in &sentences {
+            sent.extend(crossfader.push(sentence.clone()));
+        }
+        sent.extend(crossfader.flush());
+
+        // One overlap: the 50 samples follow the tail of the 700 unmixed, and so do
+        // the 400 after them
+        let total: usize = sentences.iter().map(Vec::len).sum();
+        assert_eq!(sent.len(), total - CROSSFADE_LEN);
+
+        // The first boundary ramps from one level to the other
+        let boundary = &sent[1000 - CROSSFADE_LEN..1000];
+        assert!((boundary[0] as i32 - 10000).abs() < 200);
+        assert!((boundary[CROSSFADE_LEN - 1] as i32 + 5000).abs() < 200);
+        let max_delta = sent[800..1100]
+            .windows(2)
+            .map(|w| (w[1] as i32 - w[0] as i32).abs())
+            .max()
+            .unwrap();
+        assert!(max_delta < 200, "max delta = {max_delta}");
+
+        // Then the untouched rest, in order
+        assert_eq!(sent[1000], -5000);
+        assert_eq!(&sent[1540..1590], &[3000; 50][..]);
+        assert!(sent[1590..].iter().all(|&s| s == 8000));
+        // Nothing left behind
+        assert!(crossfader.flush().is_empty());
+    }
 }
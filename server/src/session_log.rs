@@ -0,0 +1,212 @@
+//! `--session-log <dir>`: the server's own record of each session, one JSON
+//! object per line in `<dir>/<unix ms>.jsonl`.
+//!
+//! ```text
+//! {"at_ms": 1760000000123, "event": "transcription", "text": "Je suis allé au marché"}
+//! {"at_ms": 1760000002456, "event": "feedback", "text": "..."}
+//! {"at_ms": 1760000004789, "event": "response", "text": "Qu'as-tu acheté ?"}
+//! {"at_ms": 1760000006012, "event": "exchange", "outcome": "completed"}
+//! ```
+//!
+//! Both routers write to it. Each event is flushed as it is written, so a
+//! router that panics loses nothing already logged.
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use space_lt_common::{info, warn};
+
+use crate::stats::quote;
+
+/// Something that happened in a session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event<'a> {
+    /// A spoken turn, as forwarded to the orchestrator.
+    Transcription(&'a str),
+    /// A typed turn.
+    Typed(&'a str),
+    /// The orchestrator's reply, speed marker included.
+    Response(&'a str),
+    /// A feedback block.
+    Feedback(&'a str),
+    /// How an exchange ended.
+    Exchange(Outcome),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// The reply was spoken to the end.
+    Completed,
+    /// The user cancelled the exchange (CancelExchange).
+    Cancelled,
+    /// The user talked over the reply (InterruptTts).
+    Interrupted,
+}
+
+impl Outcome {
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Completed => "completed",
+            Outcome::Cancelled => "cancelled",
+            Outcome::Interrupted => "interrupted",
+        }
+    }
+}
+
+impl Event<'_> {
+    /// The event as one line of JSON, timestamped `at_ms` (Unix milliseconds).
+    pub fn to_json(self, at_ms: u128) -> String {
+        let (event, field, value) = match self {
+            Event::Transcription(text) => ("transcription", "text", quote(text)),
+            Event::Typed(text) => ("typed", "text", quote(text)),
+            Event::Response(text) => ("response", "text", quote(text)),
+            Event::Feedback(text) => ("feedback", "text", quote(text)),
+            Event::Exchange(outcome) => ("exchange", "outcome", quote(outcome.name())),
+        };
+        format!(r#"{{"at_ms": {at_ms}, "event": "{event}", "{field}": {value}}}"#)
+    }
+}
+
+/// The log of one session; a disabled one records nothing.
+#[derive(Default)]
+pub struct SessionLog {
+    out: Option<Mutex<Box<dyn Write + Send>>>,
+    /// A write failed: later failures are not warned about again.
+    failed: AtomicBool,
+}
+
+impl SessionLog {
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// A new log file in `dir`, named after the current time.
+    pub fn create(dir: &Path) -> Result<Self> {
+        let path = log_path(dir, unix_ms());
+        let file =
+            File::create_new(&path).with_context(|| format!("creating {}", path.display()))?;
+        info!("[server] Session log: {}", path.display());
+        Ok(Self::to(Box::new(file)))
+    }
+
+    fn to(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out: Some(Mutex::new(out)),
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    /// Append `event`. A failed write is warned about once and never ends the
+    /// session: the log just misses those events.
+    pub fn record(&self, event: Event) {
+        let Some(out) = &self.out else {
+            return;
+        };
+        let line = event.to_json(unix_ms());
+        // A router that panicked mid-write leaves the lock poisoned, not the file
+        let mut out = out.lock().unwrap_or_else(|e| e.into_inner());
+        let written = writeln!(out, "{line}").and_then(|()| out.flush());
+        if let Err(e) = written
+            && !self.failed.swap(true, Ordering::SeqCst)
+        {
+            warn!("[server] Could not write the session log (further errors not shown): {e}");
+        }
+    }
+}
+
+fn log_path(dir: &Path, at_ms: u128) -> PathBuf {
+    dir.join(format!("{at_ms}.jsonl"))
+}
+
+pub(crate) fn unix_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn events_serialize_as_flat_json() {
+        assert_eq!(
+            Event::Transcription("Il a dit \"bonjour\"\n").to_json(1000),
+            r#"{"at_ms": 1000, "event": "transcription", "text": "Il a dit \"bonjour\"\n"}"#
+        );
+        assert_eq!(
+            Event::Response("[SPEED:0.8] Salut !").to_json(2),
+            r#"{"at_ms": 2, "event": "response", "text": "[SPEED:0.8] Salut !"}"#
+        );
+        assert_eq!(
+            Event::Exchange(Outcome::Interrupted).to_json(3),
+            r#"{"at_ms": 3, "event": "exchange", "outcome": "interrupted"}"#
+        );
+        assert_eq!(
+            log_path(Path::new("/var/log/space_lt"), 1760000000123),
+            Path::new("/var/log/space_lt/1760000000123.jsonl")
+        );
+    }
+
+    /// A disk that is full: every write fails.
+    struct FullDisk {
+        attempts: Arc<AtomicUsize>,
+    }
+
+    impl Write for FullDisk {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+            Err(std::io::Error::other("no space left on device"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn failed_writes_are_warned_once_and_not_fatal() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let log = SessionLog::to(Box::new(FullDisk {
+            attempts: attempts.clone(),
+        }));
+        log.record(Event::Typed("Bonjour"));
+        assert!(log.failed.load(Ordering::SeqCst));
+        // Later events are still tried, in case the disk frees up
+        log.record(Event::Exchange(Outcome::Cancelled));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        // A disabled log writes nothing
+        SessionLog::disabled().record(Event::Typed("Bonjour"));
+    }
+
+    #[test]
+    fn events_are_flushed_one_line_each() {
+        let dir = std::env::temp_dir().join(format!("space_lt_log_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = SessionLog::create(&dir).unwrap();
+        log.record(Event::Typed("Bonjour"));
+        log.record(Event::Exchange(Outcome::Completed));
+
+        let path = std::fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(r#""event": "typed", "text": "Bonjour"}"#));
+        assert!(lines[1].ends_with(r#""outcome": "completed"}"#));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,68 @@
+//! Model loading at startup: Whisper and Kokoro load on two threads, unless
+//! `--sequential-load` asks for one after the other (GPUs short on memory).
+
+use anyhow::Result;
+
+/// Run the two loaders, on two threads or one after the other, and return
+/// both models. Whisper's error is reported first when both fail.
+pub fn load_models<W: Send, T: Send>(
+    load_whisper: impl FnOnce() -> Result<W> + Send,
+    load_tts: impl FnOnce() -> Result<T> + Send,
+    sequential: bool,
+) -> Result<(W, T)> {
+    if sequential {
+        let whisper = load_whisper()?;
+        return Ok((whisper, load_tts()?));
+    }
+    std::thread::scope(|s| {
+        let tts = std::thread::Builder::new()
+            .name("tts_load".into())
+            .spawn_scoped(s, load_tts)?;
+        let whisper = load_whisper();
+        let tts = tts
+            .join()
+            .map_err(|_| anyhow::anyhow!("TTS loading thread panicked"))?;
+        Ok((whisper?, tts?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// A loader that takes `LOAD` and returns when it ran.
+    fn stub() -> Result<(Instant, Instant)> {
+        let start = Instant::now();
+        std::thread::sleep(LOAD);
+        Ok((start, Instant::now()))
+    }
+
+    const LOAD: Duration = Duration::from_millis(200);
+
+    #[test]
+    fn models_load_concurrently() {
+        let ((w_start, w_end), (t_start, t_end)) = load_models(stub, stub, false).unwrap();
+        assert!(w_start < t_end && t_start < w_end, "loads did not overlap");
+    }
+
+    #[test]
+    fn sequential_load_runs_one_after_the_other() {
+        let ((_, w_end), (t_start, _)) = load_models(stub, stub, true).unwrap();
+        assert!(w_end <= t_start);
+    }
+
+    #[test]
+    fn a_failed_load_is_reported() {
+        let err = load_models(
+            || -> Result<()> { anyhow::bail!("no whisper") },
+            stub,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "no whisper");
+        let err =
+            load_models(stub, || -> Result<()> { anyhow::bail!("no tts") }, false).unwrap_err();
+        assert_eq!(err.to_string(), "no tts");
+    }
+}
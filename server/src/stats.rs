@@ -0,0 +1,373 @@
+//! Server statistics, served on an admin Unix socket next to the orchestrator
+//! one: `space_lt_server --stats` asks a running server how it is doing.
+//!
+//! The admin protocol is one line each way: the command (`stats`), then a
+//! flat JSON object (`{"error": "..."}` for an unknown command).
+
+use anyhow::{Context, Result, bail};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use space_lt_common::debug;
+
+/// How long an admin connection has to send its command.
+const ADMIN_READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The admin socket of the server whose orchestrator socket is `socket_path`:
+/// `/tmp/space_lt_server.sock` → `/tmp/space_lt_server.admin.sock`.
+pub fn admin_socket_path(socket_path: &Path) -> PathBuf {
+    let name = socket_path.to_string_lossy();
+    match name.strip_suffix(".sock") {
+        Some(stem) => PathBuf::from(format!("{stem}.admin.sock")),
+        None => PathBuf::from(format!("{name}.admin")),
+    }
+}
+
+/// Running average of durations.
+#[derive(Default)]
+struct Average {
+    count: AtomicU64,
+    total_ms: AtomicU64,
+}
+
+impl Average {
+    fn record(&self, d: Duration) {
+        self.total_ms
+            .fetch_add(d.as_millis() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn get_ms(&self) -> Option<u64> {
+        let count = self.count.load(Ordering::Relaxed);
+        (count > 0).then(|| self.total_ms.load(Ordering::Relaxed) / count)
+    }
+}
+
+/// Counters of a server run, updated by the daemon and the session routers.
+pub struct SessionStats {
+    started: Instant,
+    pub client_connected: AtomicBool,
+    pub orchestrator_connected: AtomicBool,
+    exchanges: AtomicU64,
+    transcription: Average,
+    /// Reply received to first audio sent.
+    synthesis: Average,
+    last_error: Mutex<Option<String>>,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            client_connected: AtomicBool::new(false),
+            orchestrator_connected: AtomicBool::new(false),
+            exchanges: AtomicU64::default(),
+            transcription: Average::default(),
+            synthesis: Average::default(),
+            last_error: Mutex::new(None),
+        }
+    }
+}
+
+impl SessionStats {
+    pub fn record_transcription(&self, d: Duration) {
+        self.transcription.record(d);
+    }
+
+    /// A reply went out; `first_audio` is how long its synthesis kept the
+    /// client waiting (`None` when it produced no audio).
+    pub fn record_exchange(&self, first_audio: Option<Duration>) {
+        self.exchanges.fetch_add(1, Ordering::Relaxed);
+        if let Some(d) = first_audio {
+            self.synthesis.record(d);
+        }
+    }
+
+    pub fn record_error(&self, error: &str) {
+        *self.last_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(error.to_string());
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            uptime_secs: self.started.elapsed().as_secs(),
+            client_connected: self.client_connected.load(Ordering::SeqCst),
+            orchestrator_connected: self.orchestrator_connected.load(Ordering::SeqCst),
+            exchanges: self.exchanges.load(Ordering::Relaxed),
+            avg_transcription_ms: self.transcription.get_ms(),
+            avg_synthesis_ms: self.synthesis.get_ms(),
+            last_error: self
+                .last_error
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        }
+    }
+}
+
+/// The stats as `--stats` receives them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsSnapshot {
+    pub uptime_secs: u64,
+    pub client_connected: bool,
+    pub orchestrator_connected: bool,
+    pub exchanges: u64,
+    pub avg_transcription_ms: Option<u64>,
+    pub avg_synthesis_ms: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+impl StatsSnapshot {
+    pub fn to_json(&self) -> String {
+        let number = |n: Option<u64>| n.map_or("null".to_string(), |n| n.to_string());
+        format!(
+            r#"{{"uptime_secs": {}, "client_connected": {}, "orchestrator_connected": {}, "exchanges": {}, "avg_transcription_ms": {}, "avg_synthesis_ms": {}, "last_error": {}}}"#,
+            self.uptime_secs,
+            self.client_connected,
+            self.orchestrator_connected,
+            self.exchanges,
+            number(self.avg_transcription_ms),
+            number(self.avg_synthesis_ms),
+            self.last_error.as_deref().map_or("null".to_string(), quote)
+        )
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let fields = parse_flat_json(json)?;
+        let get = |key: &str| -> Result<Option<&str>> {
+            let Some((_, value)) = fields.iter().find(|(k, _)| k == key) else {
+                bail!("missing \"{key}\" in {json}");
+            };
+            Ok(value.as_deref())
+        };
+        if let Some(error) = get("error").ok().flatten() {
+            bail!("{error}");
+        }
+        let number = |key: &str| -> Result<Option<u64>> {
+            get(key)?
+                .map(|v| {
+                    v.parse()
+                        .with_context(|| format!("\"{key}\" is not a number"))
+                })
+                .transpose()
+        };
+        let flag = |key: &str| -> Result<bool> { Ok(get(key)? == Some("true")) };
+        Ok(Self {
+            uptime_secs: number("uptime_secs")?.unwrap_or(0),
+            client_connected: flag("client_connected")?,
+            orchestrator_connected: flag("orchestrator_connected")?,
+            exchanges: number("exchanges")?.unwrap_or(0),
+            avg_transcription_ms: number("avg_transcription_ms")?,
+            avg_synthesis_ms: number("avg_synthesis_ms")?,
+            last_error: get("last_error")?.map(str::to_string),
+        })
+    }
+
+    /// The stats for a terminal, one per line.
+    pub fn display(&self) -> String {
+        let connected = |on: bool| if on { "connected" } else { "not connected" };
+        let average =
+            |ms: Option<u64>| ms.map_or("-".to_string(), |ms| format!("{ms} ms on average"));
+        let s = self.uptime_secs;
+        format!(
+            "Uptime:          {}h {:02}m {:02}s\nClient:          {}\nOrchestrator:    {}\nExchanges:       {}\nTranscription:   {}\nFirst audio:     {}\nLast error:      {}\n",
+            s / 3600,
+            s / 60 % 60,
+            s % 60,
+            connected(self.client_connected),
+            connected(self.orchestrator_connected),
+            self.exchanges,
+            average(self.avg_transcription_ms),
+            average(self.avg_synthesis_ms),
+            self.last_error.as_deref().unwrap_or("none")
+        )
+    }
+}
+
+/// A JSON string literal.
+pub(crate) fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The fields of a flat JSON object: strings unescaped, `null` as `None`,
+/// numbers and booleans as written.
+fn parse_flat_json(json: &str) -> Result<Vec<(String, Option<String>)>> {
+    let malformed = || anyhow::anyhow!("malformed stats: {json}");
+    let mut chars = json.trim().chars().peekable();
+    if chars.next() != Some('{') {
+        return Err(malformed());
+    }
+    let skip_spaces = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    };
+    let string = |chars: &mut std::iter::Peekable<std::str::Chars>| -> Option<String> {
+        let mut out = String::new();
+        loop {
+            match chars.next()? {
+                '"' => return Some(out),
+                '\\' => match chars.next()? {
+                    'n' => out.push('\n'),
+                    'u' => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        out.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                    }
+                    c => out.push(c),
+                },
+                c => out.push(c),
+            }
+        }
+    };
+    let mut fields = Vec::new();
+    loop {
+        skip_spaces(&mut chars);
+        match chars.next() {
+            Some('}') => return Ok(fields),
+            Some('"') => {}
+            _ => return Err(malformed()),
+        }
+        let key = string(&mut chars).ok_or_else(malformed)?;
+        skip_spaces(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(malformed());
+        }
+        skip_spaces(&mut chars);
+        let value = if chars.next_if_eq(&'"').is_some() {
+            Some(string(&mut chars).ok_or_else(malformed)?)
+        } else {
+            let mut token = String::new();
+            while let Some(c) = chars.next_if(|c| !matches!(c, ',' | '}')) {
+                token.push(c);
+            }
+            let token = token.trim().to_string();
+            (token != "null").then_some(token)
+        };
+        fields.push((key, value));
+        skip_spaces(&mut chars);
+        chars.next_if_eq(&',');
+    }
+}
+
+/// Answer admin connections until the listener fails. One connection at a
+/// time: each is a single short request.
+pub fn serve(listener: UnixListener, stats: &SessionStats) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = answer(stream, stats) {
+                    debug!("[server] Admin request failed: {e}");
+                }
+            }
+            Err(e) => {
+                debug!("[server] Admin socket closed: {e}");
+                break;
+            }
+        }
+    }
+}
+
+fn answer(stream: UnixStream, stats: &SessionStats) -> Result<()> {
+    stream.set_read_timeout(Some(ADMIN_READ_TIMEOUT))?;
+    let mut command = String::new();
+    BufReader::new(&stream).read_line(&mut command)?;
+    let reply = match command.trim() {
+        "stats" => stats.snapshot().to_json(),
+        other => format!(
+            r#"{{"error": {}}}"#,
+            quote(&format!("unknown admin command \"{other}\""))
+        ),
+    };
+    (&stream).write_all(format!("{reply}\n").as_bytes())?;
+    Ok(())
+}
+
+/// Ask the server behind the admin socket `path` for its stats.
+pub fn request(path: &Path) -> Result<StatsSnapshot> {
+    let mut stream = UnixStream::connect(path)
+        .with_context(|| format!("connecting to {} (is the server running?)", path.display()))?;
+    stream.set_read_timeout(Some(ADMIN_READ_TIMEOUT))?;
+    stream.write_all(b"stats\n")?;
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    StatsSnapshot::from_json(&reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_socket_sits_next_to_the_orchestrator_socket() {
+        assert_eq!(
+            admin_socket_path(Path::new("/tmp/space_lt_server.sock")),
+            Path::new("/tmp/space_lt_server.admin.sock")
+        );
+        assert_eq!(
+            admin_socket_path(Path::new("/run/lt")),
+            Path::new("/run/lt.admin")
+        );
+    }
+
+    #[test]
+    fn snapshot_round_trips_through_json() {
+        let stats = SessionStats::default();
+        stats.client_connected.store(true, Ordering::SeqCst);
+        stats.record_transcription(Duration::from_millis(800));
+        stats.record_transcription(Duration::from_millis(400));
+        stats.record_exchange(Some(Duration::from_millis(300)));
+        stats.record_exchange(None);
+        stats.record_error("TTS synthesis failed: \"bad\"\nline two");
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.exchanges, 2);
+        assert_eq!(snapshot.avg_transcription_ms, Some(600));
+        assert_eq!(snapshot.avg_synthesis_ms, Some(300));
+
+        let json = snapshot.to_json();
+        assert!(json.contains(r#""client_connected": true"#), "{json}");
+        assert_eq!(StatsSnapshot::from_json(&json).unwrap(), snapshot);
+
+        // Nothing measured yet
+        let empty = SessionStats::default().snapshot();
+        assert!(empty.to_json().contains(r#""avg_synthesis_ms": null"#));
+        assert_eq!(StatsSnapshot::from_json(&empty.to_json()).unwrap(), empty);
+        assert!(empty.display().contains("Last error:      none"));
+    }
+
+    #[test]
+    fn admin_socket_answers_stats_and_rejects_other_commands() {
+        let path =
+            std::env::temp_dir().join(format!("space-lt-test-admin-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let stats = std::sync::Arc::new(SessionStats::default());
+        stats.record_exchange(Some(Duration::from_millis(120)));
+        let served = stats.clone();
+        std::thread::spawn(move || serve(listener, &served));
+
+        let snapshot = request(&path).unwrap();
+        assert_eq!(snapshot.exchanges, 1);
+        assert_eq!(snapshot.avg_synthesis_ms, Some(120));
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream.write_all(b"reboot\n").unwrap();
+        let mut reply = String::new();
+        BufReader::new(&stream).read_line(&mut reply).unwrap();
+        let err = StatsSnapshot::from_json(&reply).unwrap_err();
+        assert_eq!(err.to_string(), "unknown admin command \"reboot\"");
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,22 +1,185 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 use space_lt_common::warn;
 use whisper_rs::{
-    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
-    convert_integer_to_float_audio,
+    FullParams, SamplingStrategy, SegmentCallbackData, WhisperContext, WhisperContextParameters,
+    WhisperState, convert_integer_to_float_audio,
 };
 
 pub trait Transcriber: Send {
     fn transcribe(&mut self, audio_i16: &[i16]) -> Result<String>;
+
+    /// `transcribe`, calling `on_partial` with the text so far as whisper
+    /// decodes it (before hallucinations are filtered out).
+    fn transcribe_with_partials(
+        &mut self,
+        audio_i16: &[i16],
+        on_partial: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String> {
+        let _ = on_partial;
+        self.transcribe(audio_i16)
+    }
+
+    /// `transcribe_with_partials`, with how sure whisper is of the text: its
+    /// average token probability (0 to 1), `None` when unknown.
+    fn transcribe_with_confidence(
+        &mut self,
+        audio_i16: &[i16],
+        on_partial: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<(String, Option<f32>)> {
+        Ok((self.transcribe_with_partials(audio_i16, on_partial)?, None))
+    }
+
+    /// Language detected in the last transcribed segment, with `--language
+    /// auto`; `None` when the language is fixed.
+    fn detected_language(&self) -> Option<DetectedLanguage> {
+        None
+    }
+
+    /// What was said lately, to bias the next transcriptions towards its
+    /// names and words (`--stt-context`); empty for none.
+    fn set_context(&mut self, text: &str) {
+        let _ = text;
+    }
+
+    /// Transcribe the next segments in `language`, a code or
+    /// [`AUTO_LANGUAGE`]. An unknown code is an error and changes nothing.
+    fn set_language(&mut self, language: &str) -> Result<()> {
+        bail!("This transcriber cannot switch to \"{language}\"")
+    }
+}
+
+/// `--language auto`: whisper detects the language of each segment.
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// A language whisper detected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedLanguage {
+    /// ISO 639-1 code, e.g. "es".
+    pub code: String,
+    /// In the `--languages` list (always, when there is no list).
+    pub allowed: bool,
+}
+
+impl DetectedLanguage {
+    pub fn new(code: &str, allowed_languages: &[String]) -> Self {
+        Self {
+            code: code.to_string(),
+            allowed: allowed_languages.is_empty() || allowed_languages.iter().any(|l| l == code),
+        }
+    }
+}
+
+/// Parse `--languages` (comma-separated codes, e.g. "en,es,fr").
+pub fn parse_language_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|code| code.trim().to_lowercase())
+        .filter(|code| !code.is_empty())
+        .collect()
+}
+
+/// A warm-up slower than this suggests Whisper is running on the CPU.
+pub const WARMUP_SLOW: Duration = Duration::from_secs(3);
+
+/// Transcribe a second of silence (GPU graph init) and check that inference
+/// works at all, so a broken model fails at startup rather than on the first
+/// utterance. Returns how long it took.
+pub fn warm_up(transcriber: &mut dyn Transcriber) -> Result<Duration> {
+    let start = Instant::now();
+    transcriber.transcribe(&[0i16; 16000])?;
+    Ok(start.elapsed())
+}
+
+/// Read a WAV file (16-bit PCM or 32-bit float, any rate and channel count)
+/// as the 16kHz mono audio Whisper takes.
+pub fn read_wav_16k(path: &Path) -> Result<Vec<i16>> {
+    let reader =
+        hound::WavReader::open(path).with_context(|| format!("opening {}", path.display()))?;
+    wav_to_16k(reader, &path.display().to_string())
+}
+
+/// The audio of `reader` (`name` in errors) as 16kHz mono samples.
+pub fn wav_to_16k<R: std::io::Read>(
+    mut reader: hound::WavReader<R>,
+    name: &str,
+) -> Result<Vec<i16>> {
+    let spec = reader.spec();
+    let interleaved: Vec<f32> = match (spec.sample_format, spec.bits_per_sample) {
+        (hound::SampleFormat::Int, 16) => reader
+            .samples::<i16>()
+            .map(|s| s.map(|s| s as f32 / 32768.0))
+            .collect::<Result<_, _>>()?,
+        (hound::SampleFormat::Float, 32) => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        (format, bits) => bail!(
+            "{name}: {bits}-bit {} WAV is not supported (use 16-bit PCM or 32-bit float)",
+            match format {
+                hound::SampleFormat::Int => "integer",
+                hound::SampleFormat::Float => "float",
+            }
+        ),
+    };
+    let channels = spec.channels.max(1) as usize;
+    let mono: Vec<f32> = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    let resampled = crate::tts::resample_to_16k(&mono, spec.sample_rate)?;
+    Ok(resampled
+        .iter()
+        .map(|&s| (s.clamp(-1.0, 1.0) * 32767.0) as i16)
+        .collect())
+}
+
+/// A 10ms frame quieter than this (RMS, in 16-bit sample units) is silence.
+pub const SILENCE_THRESHOLD: f32 = 300.0;
+
+/// Silence kept on each side of the speech by [`trim_silence`].
+pub const TRIM_PADDING_MS: usize = 200;
+
+/// A trim that would cut more of a segment than this is not done: the speech
+/// is more likely too quiet for the threshold than that short.
+const MAX_TRIMMED_PERCENT: usize = 90;
+
+/// `samples` without the silence before and after the speech, `padding_ms`
+/// of it kept on each side. A segment with no frame over `threshold`, or
+/// that would lose more than 90% of its length, is returned whole.
+pub fn trim_silence(samples: &[i16], threshold: f32, padding_ms: usize) -> &[i16] {
+    const FRAME: usize = 160;
+    let loud = |frame: &[i16]| {
+        let power = frame.iter().map(|&s| (s as f32).powi(2)).sum::<f32>() / frame.len() as f32;
+        power.sqrt() >= threshold
+    };
+    let (Some(first), Some(last)) = (
+        samples.chunks(FRAME).position(loud),
+        samples.chunks(FRAME).rposition(loud),
+    ) else {
+        return samples;
+    };
+    let padding = padding_ms * 16;
+    let start = (first * FRAME).saturating_sub(padding);
+    let end = ((last + 1) * FRAME + padding).min(samples.len());
+    if (end - start) * 100 < samples.len() * (100 - MAX_TRIMMED_PERCENT) {
+        return samples;
+    }
+    &samples[start..end]
 }
 
 pub struct LocalTranscriber {
     state: WhisperState,
     language: String,
+    /// With `--language auto`: the expected languages (empty: any).
+    allowed_languages: Vec<String>,
+    detected: Option<DetectedLanguage>,
+    /// Given to whisper after the language's prompt.
+    context: String,
 }
 
 impl LocalTranscriber {
-    pub fn new(model_path: &str, language: &str) -> Result<Self> {
+    /// `language` is a code, or [`AUTO_LANGUAGE`] to detect it per segment;
+    /// detected languages outside `allowed_languages` are flagged.
+    pub fn new(model_path: &str, language: &str, allowed_languages: Vec<String>) -> Result<Self> {
         let ctx = WhisperContext::new_with_params(model_path, WhisperContextParameters::new())
             .map_err(|e| anyhow::anyhow!("Failed to load whisper model: {e}"))?;
         let state = ctx
@@ -25,12 +188,58 @@ impl LocalTranscriber {
         Ok(Self {
             state,
             language: language.to_string(),
+            allowed_languages,
+            detected: None,
+            context: String::new(),
         })
     }
 }
 
 impl Transcriber for LocalTranscriber {
     fn transcribe(&mut self, audio_i16: &[i16]) -> Result<String> {
+        Ok(self.run(audio_i16, None)?.0)
+    }
+
+    fn transcribe_with_partials(
+        &mut self,
+        audio_i16: &[i16],
+        on_partial: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String> {
+        Ok(self.run(audio_i16, Some(on_partial))?.0)
+    }
+
+    fn transcribe_with_confidence(
+        &mut self,
+        audio_i16: &[i16],
+        on_partial: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<(String, Option<f32>)> {
+        self.run(audio_i16, Some(on_partial))
+    }
+
+    fn detected_language(&self) -> Option<DetectedLanguage> {
+        self.detected.clone()
+    }
+
+    fn set_context(&mut self, text: &str) {
+        self.context = text.to_string();
+    }
+
+    fn set_language(&mut self, language: &str) -> Result<()> {
+        let language = language.trim().to_lowercase();
+        if language != AUTO_LANGUAGE && whisper_rs::get_lang_id(&language).is_none() {
+            bail!("Unknown language code \"{language}\"");
+        }
+        self.language = language;
+        Ok(())
+    }
+}
+
+impl LocalTranscriber {
+    fn run(
+        &mut self,
+        audio_i16: &[i16],
+        on_partial: Option<&mut (dyn FnMut(&str) + Send)>,
+    ) -> Result<(String, Option<f32>)> {
         // Convert i16 to f32
         let mut audio_f32 = vec![0.0f32; audio_i16.len()];
         convert_integer_to_float_audio(audio_i16, &mut audio_f32)
@@ -40,6 +249,7 @@ impl Transcriber for LocalTranscriber {
             beam_size: 5,
             patience: -1.0,
         });
+        let auto = self.language == AUTO_LANGUAGE;
         params.set_language(Some(&self.language));
         params.set_print_special(false);
         params.set_print_progress(false);
@@ -48,23 +258,72 @@ impl Transcriber for LocalTranscriber {
         params.set_suppress_nst(true);
         params.set_no_speech_thold(0.6);
         // Initial prompt helps Whisper stay in the target language and use proper vocabulary
-        params.set_initial_prompt(initial_prompt(&self.language));
+        // (none when detecting: it would pull every segment towards its language)
+        let prompt = prompt(
+            (!auto).then(|| initial_prompt(&self.language)),
+            &self.context,
+        );
+        if !prompt.is_empty() {
+            params.set_initial_prompt(&prompt);
+        }
 
-        if let Err(e) = self.state.full(params, &audio_f32) {
+        self.detected = None;
+        let result = match on_partial {
+            None => self.state.full(params, &audio_f32),
+            Some(on_partial) => {
+                // whisper's segment callback must be 'static: it hands each new
+                // segment to a thread that calls `on_partial` while whisper goes on
+                let (segments, new_segments) = crossbeam_channel::unbounded();
+                params.set_segment_callback_safe_lossy(move |segment: SegmentCallbackData| {
+                    let _ = segments.send(segment.text);
+                });
+                let state = &mut self.state;
+                std::thread::scope(|scope| {
+                    scope.spawn(move || {
+                        let mut so_far = String::new();
+                        // Ends when `full` drops the params, and the callback with them
+                        for text in new_segments {
+                            so_far.push_str(&text);
+                            let partial = so_far.trim();
+                            if !partial.is_empty() {
+                                on_partial(partial);
+                            }
+                        }
+                    });
+                    state.full(params, &audio_f32)
+                })
+            }
+        };
+        if let Err(e) = result {
             warn!("Transcription error: {e}");
-            return Ok(String::new());
+            return Ok((String::new(), None));
+        }
+        if auto {
+            let id = self.state.full_lang_id_from_state();
+            self.detected = whisper_rs::get_lang_str(id)
+                .map(|code| DetectedLanguage::new(code, &self.allowed_languages));
         }
 
         let mut text = String::new();
+        let (mut probabilities, mut tokens) = (0.0f32, 0usize);
         for segment in self.state.as_iter() {
             match segment.to_str_lossy() {
                 Ok(s) => text.push_str(&s),
                 Err(e) => warn!("Segment text error: {e}"),
             }
+            for token in (0..segment.n_tokens()).filter_map(|i| segment.get_token(i)) {
+                // Special and timestamp tokens ([_BEG_], [_TT_42]) are not words
+                if token.to_str_lossy().is_ok_and(|t| t.starts_with("[_")) {
+                    continue;
+                }
+                probabilities += token.token_probability();
+                tokens += 1;
+            }
         }
+        let confidence = (tokens > 0).then(|| probabilities / tokens as f32);
 
         let text = text.trim().to_string();
-        Ok(filter_hallucinations(&text))
+        Ok((filter_hallucinations(&text), confidence))
     }
 }
 
@@ -258,6 +517,112 @@ fn is_repetitive(text: &str) -> bool {
     false
 }
 
+/// `--min-confidence` when not given.
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.4;
+
+/// The transcriptions stt_router drops instead of forwarding: phrases of
+/// `--hallucination-list`, and text whisper is less sure of than
+/// `--min-confidence`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptFilter {
+    phrases: Vec<Phrase>,
+    /// Lowest average token probability kept (0 keeps everything).
+    pub min_confidence: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Phrase {
+    /// The whole transcription.
+    Whole(String),
+    /// The start of a transcription, for a list entry ending in "…" or "...".
+    Start(String),
+}
+
+impl Default for TranscriptFilter {
+    fn default() -> Self {
+        Self {
+            phrases: Vec::new(),
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+}
+
+impl TranscriptFilter {
+    /// A filter for `list`: one phrase per line, `#` comments and blank
+    /// lines skipped. Case and end punctuation are ignored; a phrase ending
+    /// in "…" matches any transcription starting with it ("Subtitles by…").
+    pub fn new(list: &str, min_confidence: f32) -> Self {
+        let phrases = list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let stem = line.strip_suffix('\u{2026}').or(line.strip_suffix("..."));
+                let phrase = normalize(stem.unwrap_or(line));
+                if phrase.is_empty() {
+                    None
+                } else if stem.is_some() {
+                    Some(Phrase::Start(phrase))
+                } else {
+                    Some(Phrase::Whole(phrase))
+                }
+            })
+            .collect();
+        Self {
+            phrases,
+            min_confidence,
+        }
+    }
+
+    pub fn load(path: &Path, min_confidence: f32) -> Result<Self> {
+        let list =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Ok(Self::new(&list, min_confidence))
+    }
+
+    /// Why `text` (with whisper's `confidence`) is dropped, or `None` to keep it.
+    pub fn rejection(&self, text: &str, confidence: Option<f32>) -> Option<String> {
+        let said = normalize(text);
+        let listed = self.phrases.iter().any(|phrase| match phrase {
+            Phrase::Whole(p) => said == *p,
+            Phrase::Start(p) => said
+                .strip_prefix(p.as_str())
+                .is_some_and(|rest| !rest.starts_with(char::is_alphanumeric)),
+        });
+        if listed {
+            return Some("on the hallucination list".to_string());
+        }
+        match confidence {
+            Some(confidence) if confidence < self.min_confidence => Some(format!(
+                "confidence {:.0}% under {:.0}%",
+                confidence * 100.0,
+                self.min_confidence * 100.0
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// `text` lowercased, without surrounding punctuation, spaces collapsed.
+fn normalize(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words
+        .join(" ")
+        .to_lowercase()
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_string()
+}
+
+/// Whisper's initial prompt: the language's own, then the conversation so far.
+fn prompt(language_prompt: Option<&str>, context: &str) -> String {
+    [language_prompt.unwrap_or(""), context.trim()]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 fn initial_prompt(language: &str) -> &'static str {
     match language {
         "fr" => "Bonjour, ceci est une transcription en français.",
@@ -275,6 +640,103 @@ fn initial_prompt(language: &str) -> &'static str {
 mod tests {
     use super::*;
 
+    struct MockTranscriber(Result<String, String>);
+
+    impl Transcriber for MockTranscriber {
+        fn transcribe(&mut self, _audio_i16: &[i16]) -> Result<String> {
+            self.0.clone().map_err(|e| anyhow::anyhow!(e))
+        }
+    }
+
+    #[test]
+    fn detected_languages_are_checked_against_the_list() {
+        let list = parse_language_list("en, ES,,fr");
+        assert_eq!(list, ["en", "es", "fr"]);
+        assert!(DetectedLanguage::new("es", &list).allowed);
+        assert!(!DetectedLanguage::new("de", &list).allowed);
+        // No list: every language is expected
+        assert!(DetectedLanguage::new("de", &[]).allowed);
+        assert_eq!(MockTranscriber(Ok(String::new())).detected_language(), None);
+    }
+
+    #[test]
+    fn warm_up_reports_inference_failure() {
+        let mut ok = MockTranscriber(Ok(String::new()));
+        assert!(warm_up(&mut ok).unwrap() < WARMUP_SLOW);
+
+        let mut broken = MockTranscriber(Err("whisper_full failed: -6".to_string()));
+        let err = warm_up(&mut broken).unwrap_err();
+        assert!(format!("{err:#}").contains("whisper_full failed: -6"));
+    }
+
+    /// Write a 440 Hz sine of `secs` seconds, the same on every channel.
+    fn sine_wav(name: &str, spec: hound::WavSpec, secs: f32) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("space-lt-test-{}-{name}.wav", std::process::id()));
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        let frames = (spec.sample_rate as f32 * secs) as usize;
+        for i in 0..frames {
+            let t = i as f32 / spec.sample_rate as f32;
+            let s = 0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+            for _ in 0..spec.channels {
+                match spec.sample_format {
+                    hound::SampleFormat::Int => writer.write_sample((s * 32767.0) as i16).unwrap(),
+                    hound::SampleFormat::Float => writer.write_sample(s).unwrap(),
+                }
+            }
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    #[test]
+    fn wav_is_read_as_16k_mono() {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let path = sine_wav("stereo48k", spec, 0.5);
+        let samples = read_wav_16k(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!((7900..=8100).contains(&samples.len()), "{}", samples.len());
+        let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap();
+        assert!((15000..=17500).contains(&peak), "{peak}");
+
+        let float = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let path = sine_wav("float16k", float, 0.25);
+        assert_eq!(read_wav_16k(&path).unwrap().len(), 4000);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn unsupported_bit_depth_is_named() {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 24,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let path =
+            std::env::temp_dir().join(format!("space-lt-test-{}-24bit.wav", std::process::id()));
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        writer.write_sample(0i32).unwrap();
+        writer.finalize().unwrap();
+        let err = read_wav_16k(&path).unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(
+            err.to_string()
+                .contains("24-bit integer WAV is not supported"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn filter_full_hallucination() {
         assert_eq!(filter_hallucinations("Merci d'avoir regardé la vidéo!"), "");
@@ -370,6 +832,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn context_follows_the_language_prompt() {
+        let context = "I use Kokoro for the voice.";
+        assert_eq!(
+            prompt(Some(initial_prompt("en")), context),
+            "Hello, this is an English transcription. I use Kokoro for the voice."
+        );
+        // Detecting the language: the conversation alone
+        assert_eq!(prompt(None, context), context);
+        assert_eq!(
+            prompt(Some(initial_prompt("en")), " "),
+            "Hello, this is an English transcription."
+        );
+        assert_eq!(prompt(None, ""), "");
+    }
+
+    #[test]
+    fn listed_phrases_are_rejected() {
+        let filter = TranscriptFilter::new(
+            "# Heard on silence\nThank you.\n\nSubtitles by\u{2026}\n  Sous-titres par...  \n\u{2026}\n",
+            0.0,
+        );
+        for text in [
+            "Thank you.",
+            "thank you!",
+            "  Thank   you ",
+            "Subtitles by the Amara.org community",
+            "Subtitles by",
+            "Sous-titres par Jean",
+        ] {
+            assert!(filter.rejection(text, None).is_some(), "{text}");
+        }
+        for text in [
+            "Thank you for the help",
+            "Subtitles bywords",
+            "I read the subtitles by myself",
+            "Heard on silence",
+            "",
+        ] {
+            assert_eq!(filter.rejection(text, None), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn unsure_transcriptions_are_rejected() {
+        let filter = TranscriptFilter::default();
+        assert_eq!(
+            filter.rejection("I went to the market", Some(0.25)),
+            Some("confidence 25% under 40%".to_string())
+        );
+        assert_eq!(filter.rejection("I went to the market", Some(0.9)), None);
+        // A transcriber without probabilities is trusted
+        assert_eq!(filter.rejection("I went to the market", None), None);
+        // 0 keeps everything
+        let filter = TranscriptFilter::new("", 0.0);
+        assert_eq!(filter.rejection("Mmh", Some(0.01)), None);
+    }
+
+    /// `ms` of a square wave at `amplitude`.
+    fn tone(ms: usize, amplitude: i16) -> Vec<i16> {
+        (0..ms * 16)
+            .map(|i| if i % 40 < 20 { amplitude } else { -amplitude })
+            .collect()
+    }
+
+    #[test]
+    fn silence_is_trimmed_around_the_speech() {
+        let segment = [tone(1000, 50), tone(1000, 3000), tone(1000, 0)].concat();
+        let trimmed = trim_silence(&segment, SILENCE_THRESHOLD, TRIM_PADDING_MS);
+        // 200ms of padding on each side
+        assert_eq!(trimmed.len(), 1400 * 16);
+        assert_eq!(trimmed, &segment[800 * 16..2200 * 16]);
+
+        // Speech from the first sample: nothing before it to trim
+        let segment = [tone(500, 3000), tone(1000, 0)].concat();
+        assert_eq!(
+            trim_silence(&segment, SILENCE_THRESHOLD, TRIM_PADDING_MS),
+            &segment[..700 * 16]
+        );
+        // All speech
+        let segment = tone(1000, 3000);
+        assert_eq!(
+            trim_silence(&segment, SILENCE_THRESHOLD, TRIM_PADDING_MS),
+            &segment[..]
+        );
+    }
+
+    #[test]
+    fn trimming_most_of_a_segment_is_not_done() {
+        // No speech at all
+        let silence = tone(2000, 100);
+        assert_eq!(
+            trim_silence(&silence, SILENCE_THRESHOLD, TRIM_PADDING_MS).len(),
+            silence.len()
+        );
+        // Half a second over the threshold in ten of silence: 0.9s would be left
+        let segment = [tone(5000, 0), tone(500, 3000), tone(5000, 0)].concat();
+        assert_eq!(
+            trim_silence(&segment, SILENCE_THRESHOLD, TRIM_PADDING_MS).len(),
+            segment.len()
+        );
+        // Over 10% is left: trimmed
+        let segment = [tone(1000, 0), tone(500, 3000), tone(1000, 0)].concat();
+        assert_eq!(
+            trim_silence(&segment, SILENCE_THRESHOLD, TRIM_PADDING_MS).len(),
+            900 * 16
+        );
+    }
+
     #[test]
     fn filter_keeps_real_text() {
         assert_eq!(
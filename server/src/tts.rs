@@ -1,22 +1,230 @@
-use anyhow::{Context, Result};
-use std::path::Path;
-use std::sync::Mutex;
+use anyhow::{Context, Result, bail};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
-use space_lt_common::debug;
+use space_lt_common::{debug, warn};
 
 /// Trait abstracting TTS synthesis. Returns 16kHz mono i16 samples.
 pub trait TtsEngine: Send + Sync {
-    fn synthesize(&self, text: &str) -> Result<Vec<i16>>;
-    fn set_speed(&self, speed: f32);
+    /// Synthesize `text` with voice `voice`, an index into `voices()`, at
+    /// `speed`, or at the engine's own speed without one. An engine without
+    /// voices or speeds ignores them.
+    fn synthesize_at(&self, text: &str, voice: usize, speed: Option<f32>) -> Result<Vec<i16>>;
+
+    /// `synthesize_at` with the first voice, at the engine's own speed.
+    fn synthesize(&self, text: &str) -> Result<Vec<i16>> {
+        self.synthesize_at(text, 0, None)
+    }
+
+    /// Names of the voices, indexed as `synthesize_at` takes them. Empty for
+    /// an engine with a single voice.
+    fn voices(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The language replies are spoken in (ISO 639-1 code).
+    fn language(&self) -> &str {
+        "en"
+    }
+
+    /// How many sentences the engine synthesizes at once; calls beyond that
+    /// wait for one another.
+    fn parallelism(&self) -> usize {
+        1
+    }
+}
+
+/// Speeds Kokoro accepts; others are clamped into it.
+pub const SPEED_RANGE: RangeInclusive<f32> = 0.3..=2.0;
+
+/// Kokoro's speed for a reply without a speed marker.
+const DEFAULT_SPEED: f32 = 0.8;
+
+/// Index of the voice called `name`, or an error the client can show.
+pub fn voice_index(engine: &dyn TtsEngine, name: &str) -> Result<usize> {
+    let voices = engine.voices();
+    match voices.iter().position(|voice| voice == name) {
+        Some(index) => Ok(index),
+        None if voices.is_empty() => bail!("Unknown voice \"{name}\": the TTS has a single voice"),
+        None => bail!(
+            "Unknown voice \"{name}\": the TTS offers {} voices ({}, ...)",
+            voices.len(),
+            voices
+                .iter()
+                .take(3)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Phrase synthesized at startup to check the TTS model.
+const WARMUP_PHRASE: &str = "Hello, I am ready.";
+
+/// Plausible length of the spoken `WARMUP_PHRASE`, in seconds.
+const WARMUP_AUDIO_SECS: RangeInclusive<f64> = 0.3..=6.0;
+
+/// A warm-up slower than this suggests the TTS model is running on the CPU.
+pub const WARMUP_SLOW: Duration = Duration::from_secs(3);
+
+/// Synthesize a short fixed phrase and check the result is audio of a plausible
+/// length, so a model that loads but can't speak fails at startup. Returns how
+/// long synthesis took.
+pub fn warm_up(engine: &dyn TtsEngine) -> Result<Duration> {
+    let start = Instant::now();
+    let samples = engine.synthesize(WARMUP_PHRASE)?;
+    let elapsed = start.elapsed();
+    if samples.is_empty() {
+        bail!("synthesized no audio for \"{WARMUP_PHRASE}\"");
+    }
+    let secs = samples.len() as f64 / 16000.0;
+    if !WARMUP_AUDIO_SECS.contains(&secs) {
+        bail!(
+            "synthesized {secs:.2}s of audio for \"{WARMUP_PHRASE}\", expected {:.1}-{:.1}s",
+            WARMUP_AUDIO_SECS.start(),
+            WARMUP_AUDIO_SECS.end()
+        );
+    }
+    Ok(elapsed)
+}
+
+/// Speakers of the kokoro-multi-lang-v1_0 release, in speaker id order.
+const KOKORO_V1_0_VOICES: &[&str] = &[
+    "af_alloy",
+    "af_aoede",
+    "af_bella",
+    "af_heart",
+    "af_jessica",
+    "af_kore",
+    "af_nicole",
+    "af_nova",
+    "af_river",
+    "af_sarah",
+    "af_sky",
+    "am_adam",
+    "am_echo",
+    "am_eric",
+    "am_fenrir",
+    "am_liam",
+    "am_michael",
+    "am_onyx",
+    "am_puck",
+    "am_santa",
+    "bf_alice",
+    "bf_emma",
+    "bf_isabella",
+    "bf_lily",
+    "bm_daniel",
+    "bm_fable",
+    "bm_george",
+    "bm_lewis",
+    "ef_dora",
+    "em_alex",
+    "ff_siwis",
+    "hf_alpha",
+    "hf_beta",
+    "hm_omega",
+    "hm_psi",
+    "if_sara",
+    "im_nicola",
+    "jf_alpha",
+    "jf_gongitsune",
+    "jf_nezumi",
+    "jf_tebukuro",
+    "jm_kumo",
+    "pf_dora",
+    "pm_alex",
+    "pm_santa",
+    "zf_xiaobei",
+    "zf_xiaoni",
+    "zf_xiaoxiao",
+    "zf_xiaoyi",
+    "zm_yunjian",
+    "zm_yunxi",
+    "zm_yunxia",
+    "zm_yunyang",
+];
+
+/// Size of one speaker embedding in voices.bin: 510 × 256 f32.
+const KOKORO_VOICE_BYTES: u64 = 510 * 256 * 4;
+
+/// Speaker names for a voices.bin of `size` bytes: the v1.0 names when the
+/// speaker count matches, else `speaker_<id>` (voices.bin stores no names).
+fn kokoro_voice_names(size: u64) -> Vec<String> {
+    let count = (size / KOKORO_VOICE_BYTES) as usize;
+    if count == KOKORO_V1_0_VOICES.len() {
+        return KOKORO_V1_0_VOICES.iter().map(|v| v.to_string()).collect();
+    }
+    (0..count).map(|id| format!("speaker_{id}")).collect()
+}
+
+/// Optional name table in a model directory, for models other than v1.0:
+/// one `<id> <name>` line per speaker.
+const KOKORO_NAME_TABLE: &str = "voices.txt";
+
+/// Parse a name table: `<id> <name>` lines, blank lines and `#` comments skipped.
+fn parse_voice_table(content: &str) -> Result<Vec<(i32, String)>> {
+    let mut names = Vec::new();
+    for (n, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parsed = line
+            .split_once(char::is_whitespace)
+            .and_then(|(id, name)| Some((id.parse::<i32>().ok()?, name.trim())));
+        let Some((id, name)) = parsed.filter(|(id, _)| *id >= 0) else {
+            bail!("line {}: expected \"<id> <name>\", got \"{line}\"", n + 1);
+        };
+        names.push((id, name.to_string()));
+    }
+    Ok(names)
+}
+
+/// Speaker ids and names of the Kokoro model in `model_dir`, without loading
+/// it: the count comes from voices.bin, the names from `voices.txt` when the
+/// model has one.
+pub fn kokoro_speakers(model_dir: &Path) -> Result<Vec<(i32, String)>> {
+    let voices_path = model_dir.join("voices.bin");
+    let size = std::fs::metadata(&voices_path)
+        .with_context(|| format!("reading {}", voices_path.display()))?
+        .len();
+    let mut names = kokoro_voice_names(size);
+    let table_path = model_dir.join(KOKORO_NAME_TABLE);
+    if table_path.exists() {
+        let content = std::fs::read_to_string(&table_path)
+            .with_context(|| format!("reading {}", table_path.display()))?;
+        let table =
+            parse_voice_table(&content).with_context(|| format!("{}", table_path.display()))?;
+        for (id, name) in table {
+            let Some(slot) = names.get_mut(id as usize) else {
+                bail!(
+                    "{}: speaker {id} is past the {} speakers of voices.bin",
+                    table_path.display(),
+                    names.len()
+                );
+            };
+            *slot = name;
+        }
+    }
+    Ok((0..).zip(names).collect())
 }
 
 /// Kokoro TTS engine via sherpa-rs (sherpa-onnx FFI).
 /// Uses a Mutex because sherpa-rs KokoroTts::create() requires &mut self,
-/// while our TtsEngine trait uses &self.
+/// while our TtsEngine trait uses &self. Each loaded instance synthesizes one
+/// sentence at a time.
 pub struct KokoroTts {
-    tts: Mutex<sherpa_rs::tts::KokoroTts>,
-    speaker_id: i32,
-    speed: Mutex<f32>,
+    tts: Vec<Mutex<sherpa_rs::tts::KokoroTts>>,
+    /// The instance waited for when all are busy.
+    next: AtomicUsize,
+    /// Speaker ids and names, in id order.
+    speakers: Vec<(i32, String)>,
+    lang: String,
 }
 
 impl KokoroTts {
@@ -29,11 +237,11 @@ impl KokoroTts {
     /// - espeak-ng-data/ — phoneme data
     /// - dict/ — dictionary data
     /// - lexicon-us-en.txt — English lexicon
-    pub fn new(model_dir: &Path, lang: &str) -> Result<Self> {
-        let model_dir_str = model_dir
-            .to_str()
-            .context("model directory path is not valid UTF-8")?;
-
+    ///
+    /// `instances` copies of the model are loaded, to synthesize that many
+    /// sentences at once (each one takes the model's memory again).
+    pub fn new(model_dir: &Path, lang: &str, instances: usize) -> Result<Self> {
+        let instances = instances.max(1);
         let model_path = model_dir.join("model.onnx");
         if !model_path.exists() {
             anyhow::bail!("model.onnx not found in {}", model_dir.display());
@@ -51,40 +259,41 @@ impl KokoroTts {
 
         debug!("[server] Loading TTS model from {}", model_dir.display());
 
-        // Build lexicon: include all lexicon-*.txt files found in the directory
-        let lexicon = build_lexicon_path(model_dir);
+        let files = KokoroFiles::resolve(model_dir)?;
 
         let provider = if cfg!(feature = "cuda-tts") {
             "cuda"
         } else {
             "cpu"
         };
-        let num_threads = 16;
-
-        debug!("[server] TTS provider: {provider}, threads: {num_threads}");
+        // The CPU threads are shared between the instances
+        let num_threads = (16 / instances as i32).max(1);
 
-        let config = sherpa_rs::tts::KokoroTtsConfig {
-            model: format!("{model_dir_str}/model.onnx"),
-            voices: format!("{model_dir_str}/voices.bin"),
-            tokens: format!("{model_dir_str}/tokens.txt"),
-            data_dir: format!("{model_dir_str}/espeak-ng-data"),
-            dict_dir: if model_dir.join("dict").is_dir() {
-                format!("{model_dir_str}/dict")
-            } else {
-                String::new()
-            },
-            lexicon,
-            length_scale: 1.0,
-            lang: lang.to_string(),
-            onnx_config: sherpa_rs::OnnxConfig {
-                provider: provider.to_string(),
-                num_threads,
-                debug: false,
-            },
-            ..Default::default()
-        };
+        debug!(
+            "[server] TTS provider: {provider}, instances: {instances}, threads: {num_threads} each"
+        );
 
-        let tts = sherpa_rs::tts::KokoroTts::new(config);
+        let tts = (0..instances)
+            .map(|_| {
+                let config = sherpa_rs::tts::KokoroTtsConfig {
+                    model: files.model.clone(),
+                    voices: files.voices.clone(),
+                    tokens: files.tokens.clone(),
+                    data_dir: files.data_dir.clone(),
+                    dict_dir: files.dict_dir.clone(),
+                    lexicon: files.lexicon.clone(),
+                    length_scale: 1.0,
+                    lang: lang.to_string(),
+                    onnx_config: sherpa_rs::OnnxConfig {
+                        provider: provider.to_string(),
+                        num_threads,
+                        debug: false,
+                    },
+                    ..Default::default()
+                };
+                Mutex::new(sherpa_rs::tts::KokoroTts::new(config))
+            })
+            .collect();
 
         // Log model file size as proxy for memory usage (VRAM not directly queryable via sherpa-rs)
         let model_size_mb = std::fs::metadata(&model_path)
@@ -95,28 +304,51 @@ impl KokoroTts {
             model_size_mb
         );
 
+        let speakers = kokoro_speakers(model_dir)?;
+        debug!("[server] TTS voices: {}", speakers.len());
+
         Ok(Self {
-            tts: Mutex::new(tts),
-            speaker_id: 0, // default: first voice (af_alloy)
-            speed: Mutex::new(0.8),
+            tts,
+            next: AtomicUsize::new(0),
+            speakers,
+            lang: lang.to_string(),
         })
     }
+
+    /// A free instance, or the next one in turn when all are busy.
+    fn instance(&self) -> Result<MutexGuard<'_, sherpa_rs::tts::KokoroTts>> {
+        if let Some(free) = self.tts.iter().find_map(|tts| tts.try_lock().ok()) {
+            return Ok(free);
+        }
+        let turn = self.next.fetch_add(1, Ordering::Relaxed) % self.tts.len();
+        self.tts[turn]
+            .lock()
+            .map_err(|e| anyhow::anyhow!("TTS mutex poisoned: {e}"))
+    }
 }
 
 impl TtsEngine for KokoroTts {
-    fn synthesize(&self, text: &str) -> Result<Vec<i16>> {
-        let mut tts = self
-            .tts
-            .lock()
-            .map_err(|e| anyhow::anyhow!("TTS mutex poisoned: {e}"))?;
+    fn language(&self) -> &str {
+        &self.lang
+    }
 
-        let speed = *self
-            .speed
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Speed mutex poisoned: {e}"))?;
+    fn voices(&self) -> Vec<String> {
+        self.speakers.iter().map(|(_, name)| name.clone()).collect()
+    }
+
+    fn parallelism(&self) -> usize {
+        self.tts.len()
+    }
+
+    fn synthesize_at(&self, text: &str, voice: usize, speed: Option<f32>) -> Result<Vec<i16>> {
+        let mut tts = self.instance()?;
+
+        let speed = speed.map_or(DEFAULT_SPEED, |s| {
+            s.clamp(*SPEED_RANGE.start(), *SPEED_RANGE.end())
+        });
 
         let audio = tts
-            .create(text, self.speaker_id, speed)
+            .create(text, voice as i32, speed)
             .map_err(|e| anyhow::anyhow!("TTS synthesis failed: {e}"))?;
 
         debug!(
@@ -125,33 +357,196 @@ impl TtsEngine for KokoroTts {
         );
 
         // Resample 24kHz -> 16kHz
-        let resampled = resample_24k_to_16k(&audio.samples)?;
+        let resampled = resample_to_16k(&audio.samples, audio.sample_rate)?;
 
         // Convert f32 -> i16 (clamp to [-1.0, 1.0], scale by i16::MAX)
-        let samples: Vec<i16> = resampled
+        let mut samples: Vec<i16> = resampled
             .iter()
             .map(|&s| (s.clamp(-1.0, 1.0) * 32767.0) as i16)
             .collect();
 
+        // Every sentence and voice at the same level, before chunking and crossfades
+        normalize_i16(&mut samples, TARGET_PEAK);
+
+        Ok(samples)
+    }
+}
+
+/// Runs piper with these arguments on a line of text; returns the WAV it wrote.
+type PiperRun = Box<dyn Fn(&[String], &str) -> Result<Vec<u8>> + Send + Sync>;
+
+/// Piper voice (`<voice>.onnx`, with its `<voice>.onnx.json` next to it), a
+/// lighter engine than Kokoro. Each sentence runs the `piper` program found
+/// on the PATH once, which loads the voice again: fine for a small voice,
+/// slower than Kokoro for a large one.
+pub struct PiperTts {
+    model: PathBuf,
+    lang: String,
+    /// Sentences synthesized at once, each by its own piper process.
+    instances: usize,
+    run: PiperRun,
+}
+
+impl PiperTts {
+    /// Piper with the voice `model`, checked to exist; piper itself is first
+    /// run by the warm-up.
+    pub fn new(model: &Path, lang: &str, instances: usize) -> Result<Self> {
+        if !model.is_file() {
+            bail!("Piper voice {} not found", model.display());
+        }
+        let config = PathBuf::from(format!("{}.json", model.display()));
+        if !config.is_file() {
+            bail!(
+                "{} not found: a Piper voice comes with its .onnx.json",
+                config.display()
+            );
+        }
+        debug!("[server] Piper voice: {}", model.display());
+        Ok(Self::with_run(model, lang, instances, Box::new(run_piper)))
+    }
+
+    fn with_run(model: &Path, lang: &str, instances: usize, run: PiperRun) -> Self {
+        Self {
+            model: model.to_path_buf(),
+            lang: lang.to_string(),
+            instances: instances.max(1),
+            run,
+        }
+    }
+
+    /// Piper's arguments for `voice` (a speaker of a multi-speaker voice) at
+    /// `speed`: a faster speed is a shorter length scale.
+    fn args(&self, voice: usize, speed: Option<f32>) -> Vec<String> {
+        let speed = speed.map_or(DEFAULT_SPEED, |s| {
+            s.clamp(*SPEED_RANGE.start(), *SPEED_RANGE.end())
+        });
+        let mut args = vec![
+            "--model".to_string(),
+            self.model.display().to_string(),
+            "--output_file".to_string(),
+            "-".to_string(),
+            "--length_scale".to_string(),
+            format!("{:.3}", 1.0 / speed),
+        ];
+        if voice > 0 {
+            args.extend(["--speaker".to_string(), voice.to_string()]);
+        }
+        args
+    }
+}
+
+impl TtsEngine for PiperTts {
+    fn language(&self) -> &str {
+        &self.lang
+    }
+
+    fn synthesize_at(&self, text: &str, voice: usize, speed: Option<f32>) -> Result<Vec<i16>> {
+        // Piper reads one utterance per line
+        let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let wav = (self.run)(&self.args(voice, speed), &line)?;
+        let reader =
+            hound::WavReader::new(std::io::Cursor::new(wav)).context("reading piper's output")?;
+        let mut samples = crate::transcribe::wav_to_16k(reader, "piper output")?;
+        debug!("[server] Piper synthesized {}ms audio", samples.len() / 16);
+        normalize_i16(&mut samples, TARGET_PEAK);
         Ok(samples)
     }
 
-    fn set_speed(&self, speed: f32) {
-        if let Ok(mut s) = self.speed.lock() {
-            *s = speed.clamp(0.3, 2.0);
+    fn parallelism(&self) -> usize {
+        self.instances
+    }
+}
+
+/// Run `piper` with `args`, `text` on its stdin.
+fn run_piper(args: &[String], text: &str) -> Result<Vec<u8>> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("piper")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("starting piper (is it on the PATH?)")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "{text}").context("writing to piper")?;
+    }
+    let output = child.wait_with_output().context("waiting for piper")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "piper failed ({}): {}",
+            output.status,
+            stderr.lines().last().unwrap_or_default()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// The TTS engines `--tts-engine` chooses from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TtsBackend {
+    Kokoro,
+    Piper,
+}
+
+impl TtsBackend {
+    /// The `--tts-engine` value, Kokoro without one.
+    pub fn parse(name: Option<&str>) -> Result<Self> {
+        match name {
+            None | Some("kokoro") => Ok(TtsBackend::Kokoro),
+            Some("piper") => Ok(TtsBackend::Piper),
+            Some(other) => {
+                bail!("Invalid --tts-engine value \"{other}\": expected \"kokoro\" or \"piper\"")
+            }
         }
     }
+
+    /// Load `model` (a Kokoro model directory or a Piper voice file) to speak
+    /// `lang`, with `instances` sentences synthesized at once.
+    pub fn load(self, model: &Path, lang: &str, instances: usize) -> Result<Box<dyn TtsEngine>> {
+        Ok(match self {
+            TtsBackend::Kokoro => Box::new(KokoroTts::new(model, lang, instances)?),
+            TtsBackend::Piper => Box::new(PiperTts::new(model, lang, instances)?),
+        })
+    }
+}
+
+/// Peak each synthesized sentence is scaled to: -3 dBFS.
+pub const TARGET_PEAK: f32 = 0.708;
+
+/// Most a sentence is amplified (+12 dB), so a near-silent one is not blown up
+/// into noise.
+const MAX_GAIN: f32 = 4.0;
+
+/// Scale `samples` so their peak is `target_peak` (a fraction of full scale),
+/// amplifying by [`MAX_GAIN`] at most. Silence is left alone.
+pub fn normalize_i16(samples: &mut [i16], target_peak: f32) {
+    let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0);
+    if peak == 0 {
+        return;
+    }
+    let gain = (target_peak * i16::MAX as f32 / peak as f32).min(MAX_GAIN);
+    for s in samples.iter_mut() {
+        *s = (*s as f32 * gain)
+            .round()
+            .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
 }
 
-/// Resample f32 audio from 24kHz to 16kHz mono.
-fn resample_24k_to_16k(input: &[f32]) -> Result<Vec<f32>> {
+/// Resample f32 mono audio from `rate` Hz to 16kHz.
+pub fn resample_to_16k(input: &[f32], rate: u32) -> Result<Vec<f32>> {
     use audioadapter_buffers::direct::SequentialSliceOfVecs;
     use rubato::{
         Async, FixedAsync, Resampler, SincInterpolationParameters, SincInterpolationType,
         WindowFunction,
     };
 
-    let ratio = 16000.0 / 24000.0;
+    if rate == 16000 {
+        return Ok(input.to_vec());
+    }
+    let ratio = 16000.0 / rate as f64;
     let chunk_size = 1024;
 
     let params = SincInterpolationParameters {
@@ -164,7 +559,7 @@ fn resample_24k_to_16k(input: &[f32]) -> Result<Vec<f32>> {
 
     let mut resampler =
         Async::<f64>::new_sinc(ratio, 1.1, &params, chunk_size, 1, FixedAsync::Input)
-            .context("creating 24kHz→16kHz resampler")?;
+            .with_context(|| format!("creating {rate}Hz→16kHz resampler"))?;
 
     let input_f64: Vec<f64> = input.iter().map(|&s| s as f64).collect();
     let mut output_all: Vec<f32> = Vec::new();
@@ -214,25 +609,191 @@ fn resample_24k_to_16k(input: &[f32]) -> Result<Vec<f32>> {
     Ok(output_all)
 }
 
-/// Build comma-separated lexicon path from all lexicon-*.txt files in the directory.
-fn build_lexicon_path(model_dir: &Path) -> String {
-    let mut paths: Vec<String> = Vec::new();
+/// Languages of the multilingual Kokoro release (kokoro-multi-lang-*).
+const KOKORO_MULTI_LANGS: &[&str] = &["en", "es", "fr", "hi", "it", "ja", "pt", "zh"];
+
+/// Languages a TTS model can speak, as far as its files tell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TtsLanguages {
+    /// ISO 639-1 codes, e.g. `["en"]`.
+    Known(Vec<String>),
+    /// Nothing in the model directory says; the language check is skipped.
+    Unknown,
+}
+
+impl TtsLanguages {
+    /// Languages declared with `--tts-lang` (comma-separated, e.g. "en,fr").
+    pub fn declared(list: &str) -> Self {
+        TtsLanguages::Known(
+            list.split(',')
+                .map(primary_language)
+                .filter(|l| !l.is_empty())
+                .collect(),
+        )
+    }
+}
+
+/// "en-US", "EN" → "en".
+fn primary_language(code: &str) -> String {
+    code.trim()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Work out which languages the Kokoro model in `model_dir` speaks: all of
+/// the multilingual release's for a `*multi-lang*` directory, else those of
+/// its `lexicon-<region>-<lang>.txt` files, else English for an `*-en-*`
+/// directory (the English-only releases ship no lexicon). A Piper voice
+/// speaks the language its file is named after (`fr_FR-siwis-medium.onnx`).
+pub fn detect_tts_languages(model_dir: &Path) -> TtsLanguages {
+    let dir_name = model_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if let Some(voice) = dir_name.strip_suffix(".onnx") {
+        let lang = primary_language(voice);
+        return if lang.len() == 2 {
+            TtsLanguages::Known(vec![lang])
+        } else {
+            TtsLanguages::Unknown
+        };
+    }
+    if dir_name.contains("multi-lang") {
+        return TtsLanguages::Known(KOKORO_MULTI_LANGS.iter().map(|l| l.to_string()).collect());
+    }
+
+    let mut languages: Vec<String> = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(model_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if let Some(lang) = name
+                .strip_prefix("lexicon-")
+                .and_then(|rest| rest.strip_suffix(".txt"))
+                .and_then(|rest| rest.rsplit('-').next())
+                .map(primary_language)
+                && !lang.is_empty()
+                && !languages.contains(&lang)
+            {
+                languages.push(lang);
+            }
+        }
+    }
+    if !languages.is_empty() {
+        languages.sort();
+        return TtsLanguages::Known(languages);
+    }
+
+    if dir_name.split(['-', '_']).any(|part| part == "en") {
+        return TtsLanguages::Known(vec!["en".into()]);
+    }
+    TtsLanguages::Unknown
+}
+
+/// A warning when the TTS model cannot speak `stt_language` (`--language`),
+/// `None` when it can or nothing is known about it.
+pub fn language_mismatch(stt_language: &str, tts: &TtsLanguages) -> Option<String> {
+    let TtsLanguages::Known(languages) = tts else {
+        return None;
+    };
+    let wanted = primary_language(stt_language);
+    if languages.contains(&wanted) {
+        return None;
+    }
+    Some(format!(
+        "The TTS model speaks {} only, but --language is {stt_language}: replies will be mispronounced. Use a multilingual TTS model (or declare its languages with --tts-lang).",
+        languages.join(", ")
+    ))
+}
+
+/// Paths of a Kokoro model's files, as the UTF-8 strings sherpa-onnx takes.
+#[derive(Debug)]
+struct KokoroFiles {
+    model: String,
+    voices: String,
+    tokens: String,
+    data_dir: String,
+    /// Empty when the model has no `dict/`.
+    dict_dir: String,
+    lexicon: String,
+}
+
+impl KokoroFiles {
+    /// Resolve `model_dir` (following symlinks, which may also get rid of a
+    /// non-UTF-8 ancestor directory) and build the file paths under it.
+    fn resolve(model_dir: &Path) -> Result<Self> {
+        let dir = std::fs::canonicalize(model_dir)
+            .with_context(|| format!("resolving model directory {}", model_dir.display()))?;
+        let dict = dir.join("dict");
+        Ok(Self {
+            model: utf8_path(&dir.join("model.onnx"))?,
+            voices: utf8_path(&dir.join("voices.bin"))?,
+            tokens: utf8_path(&dir.join("tokens.txt"))?,
+            data_dir: utf8_path(&dir.join("espeak-ng-data"))?,
+            dict_dir: if dict.is_dir() {
+                utf8_path(&dict)?
+            } else {
+                String::new()
+            },
+            lexicon: build_lexicon_path(&dir)?,
+        })
+    }
+}
+
+/// `path` as a string, or an error naming its first component that is not UTF-8.
+fn utf8_path(path: &Path) -> Result<String> {
+    if let Some(s) = path.to_str() {
+        return Ok(s.to_string());
+    }
+    let component = path
+        .components()
+        .map(|c| c.as_os_str())
+        .find(|c| c.to_str().is_none())
+        .unwrap_or(path.as_os_str());
+    bail!(
+        "TTS model path {} is not valid UTF-8 (in \"{}\"), which sherpa-onnx requires; rename the directory or move the model",
+        path.display(),
+        component.to_string_lossy()
+    )
+}
+
+/// Build comma-separated lexicon path from all lexicon-*.txt files in the
+/// directory, symlinked ones included. Names that are not UTF-8 are skipped
+/// with a warning, since sherpa-onnx could not open them.
+fn build_lexicon_path(model_dir: &Path) -> Result<String> {
+    let mut paths: Vec<PathBuf> = Vec::new();
     if let Ok(entries) = std::fs::read_dir(model_dir) {
         for entry in entries.flatten() {
             let name = entry.file_name();
-            let name_str = name.to_string_lossy();
-            if name_str.starts_with("lexicon-") && name_str.ends_with(".txt") {
-                paths.push(entry.path().to_string_lossy().into_owned());
+            let Some(name_str) = name.to_str() else {
+                let lossy = name.to_string_lossy();
+                if lossy.starts_with("lexicon-") {
+                    warn!("[server] Skipping lexicon with a non-UTF-8 name: {lossy}");
+                }
+                continue;
+            };
+            // metadata() follows symlinks: dangling ones and directories are left out
+            if name_str.starts_with("lexicon-")
+                && name_str.ends_with(".txt")
+                && entry.path().metadata().is_ok_and(|m| m.is_file())
+            {
+                paths.push(entry.path());
             }
         }
     }
     paths.sort();
-    paths.join(",")
+    let paths = paths
+        .iter()
+        .map(|p| utf8_path(p))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(paths.join(","))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
 
     /// Mock TTS engine returning a 440Hz sine wave for testing.
     struct MockTtsEngine {
@@ -250,7 +811,12 @@ mod tests {
     }
 
     impl TtsEngine for MockTtsEngine {
-        fn synthesize(&self, _text: &str) -> Result<Vec<i16>> {
+        fn synthesize_at(
+            &self,
+            _text: &str,
+            _voice: usize,
+            _speed: Option<f32>,
+        ) -> Result<Vec<i16>> {
             let num_samples = (self.sample_rate as f64 * self.duration_secs) as usize;
             let samples: Vec<i16> = (0..num_samples)
                 .map(|i| {
@@ -260,8 +826,6 @@ mod tests {
                 .collect();
             Ok(samples)
         }
-
-        fn set_speed(&self, _speed: f32) {}
     }
 
     #[test]
@@ -285,4 +849,371 @@ mod tests {
         // 16kHz * 0.25s = 4000 samples
         assert_eq!(samples_short.len(), 4000);
     }
+
+    struct FailingTtsEngine;
+
+    impl TtsEngine for FailingTtsEngine {
+        fn synthesize_at(
+            &self,
+            _text: &str,
+            _voice: usize,
+            _speed: Option<f32>,
+        ) -> Result<Vec<i16>> {
+            bail!("onnxruntime: invalid model")
+        }
+    }
+
+    fn sine(amplitude: f64, len: usize) -> Vec<i16> {
+        (0..len)
+            .map(|i| {
+                (f64::sin(2.0 * std::f64::consts::PI * 440.0 * i as f64 / 16000.0) * amplitude)
+                    as i16
+            })
+            .collect()
+    }
+
+    fn peak(samples: &[i16]) -> u16 {
+        samples.iter().map(|s| s.unsigned_abs()).max().unwrap()
+    }
+
+    #[test]
+    fn normalization_brings_sines_to_the_target_peak() {
+        let target = (TARGET_PEAK * 32767.0).round() as u16;
+        // A loud exclamation is turned down, a quiet sentence up
+        for amplitude in [32767.0, 12000.0] {
+            let mut samples = sine(amplitude, 1600);
+            normalize_i16(&mut samples, TARGET_PEAK);
+            assert!(peak(&samples).abs_diff(target) <= 1, "{}", peak(&samples));
+        }
+        // Full-scale negative peaks do not wrap around
+        let mut samples = vec![i16::MIN, 0, i16::MAX];
+        normalize_i16(&mut samples, 1.0);
+        assert!(samples[0] <= -32766 && samples[2] >= 32766, "{samples:?}");
+    }
+
+    #[test]
+    fn normalization_gain_is_capped() {
+        // Constant 1000: 23x would reach the target, 4x at most is applied
+        let mut samples = vec![1000; 100];
+        normalize_i16(&mut samples, TARGET_PEAK);
+        assert!(samples.iter().all(|&s| s == 4000));
+        let mut silence = vec![0; 100];
+        normalize_i16(&mut silence, TARGET_PEAK);
+        assert!(silence.iter().all(|&s| s == 0));
+    }
+
+    #[test]
+    fn warm_up_accepts_plausible_audio() {
+        let took = warm_up(&MockTtsEngine::new(16000, 1.2)).unwrap();
+        assert!(took < WARMUP_SLOW);
+    }
+
+    #[test]
+    fn warm_up_rejects_failing_empty_or_implausible_output() {
+        let err = warm_up(&FailingTtsEngine).unwrap_err();
+        assert!(err.to_string().contains("onnxruntime: invalid model"));
+
+        let err = warm_up(&MockTtsEngine::new(16000, 0.0)).unwrap_err();
+        assert!(err.to_string().contains("no audio"), "{err}");
+
+        // A few samples, or a minute of audio, for a four-word phrase
+        assert!(warm_up(&MockTtsEngine::new(16000, 0.01)).is_err());
+        let err = warm_up(&MockTtsEngine::new(16000, 60.0)).unwrap_err();
+        assert!(err.to_string().contains("60.00s"), "{err}");
+    }
+
+    #[test]
+    fn voice_names_follow_the_speaker_count() {
+        let names = kokoro_voice_names(53 * KOKORO_VOICE_BYTES);
+        assert_eq!(names[0], "af_alloy");
+        assert_eq!(names[30], "ff_siwis");
+        assert_eq!(
+            kokoro_voice_names(2 * KOKORO_VOICE_BYTES),
+            ["speaker_0", "speaker_1"]
+        );
+        assert!(kokoro_voice_names(0).is_empty());
+    }
+
+    #[test]
+    fn name_table_renames_speakers() {
+        let table = "# id name\n0 af_alloy\n\n2  my voice \n";
+        assert_eq!(
+            parse_voice_table(table).unwrap(),
+            [(0, "af_alloy".to_string()), (2, "my voice".to_string())]
+        );
+        let err = parse_voice_table("0 a\nb 1\n").unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"), "{err}");
+        assert!(parse_voice_table("-1 a\n").is_err());
+
+        let dir = model_dir("speakers", &[]);
+        std::fs::File::create(dir.join("voices.bin"))
+            .unwrap()
+            .set_len(3 * KOKORO_VOICE_BYTES)
+            .unwrap();
+        std::fs::write(dir.join(KOKORO_NAME_TABLE), "1 bf_emma\n").unwrap();
+        assert_eq!(
+            kokoro_speakers(&dir).unwrap(),
+            [
+                (0, "speaker_0".to_string()),
+                (1, "bf_emma".to_string()),
+                (2, "speaker_2".to_string())
+            ]
+        );
+        std::fs::write(dir.join(KOKORO_NAME_TABLE), "3 extra\n").unwrap();
+        assert!(kokoro_speakers(&dir).is_err());
+    }
+
+    #[test]
+    fn unknown_voice_names_are_rejected() {
+        // The mock has a single voice
+        let err = voice_index(&MockTtsEngine::new(16000, 1.0), "af_alloy").unwrap_err();
+        assert!(err.to_string().contains("single voice"), "{err}");
+    }
+
+    /// A fake model directory, removed with everything beside it when dropped.
+    struct ModelDir {
+        root: PathBuf,
+        dir: PathBuf,
+    }
+
+    impl std::ops::Deref for ModelDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.dir
+        }
+    }
+
+    impl AsRef<Path> for ModelDir {
+        fn as_ref(&self) -> &Path {
+            &self.dir
+        }
+    }
+
+    impl Drop for ModelDir {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.root).ok();
+        }
+    }
+
+    /// A fake model directory named `name` holding `files` (contents are irrelevant).
+    fn model_dir(name: &str, files: &[&str]) -> ModelDir {
+        let root =
+            std::env::temp_dir().join(format!("space-lt-test-tts-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["model.onnx", "voices.bin", "tokens.txt"]
+            .iter()
+            .chain(files)
+        {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+        ModelDir { root, dir }
+    }
+
+    #[test]
+    fn detects_languages_from_model_layout() {
+        let multi = model_dir(
+            "kokoro-multi-lang-v1_0",
+            &["lexicon-us-en.txt", "lexicon-zh.txt"],
+        );
+        let TtsLanguages::Known(langs) = detect_tts_languages(&multi) else {
+            panic!("multi-lang model should be known");
+        };
+        assert!(langs.contains(&"fr".to_string()) && langs.contains(&"zh".to_string()));
+
+        let lexicons = model_dir("my-kokoro", &["lexicon-us-en.txt", "lexicon-gb-en.txt"]);
+        assert_eq!(
+            detect_tts_languages(&lexicons),
+            TtsLanguages::Known(vec!["en".into()])
+        );
+
+        let english = model_dir("kokoro-en-v0_19", &[]);
+        assert_eq!(
+            detect_tts_languages(&english),
+            TtsLanguages::Known(vec!["en".into()])
+        );
+
+        let bare = model_dir("tts", &[]);
+        assert_eq!(detect_tts_languages(&bare), TtsLanguages::Unknown);
+
+        assert_eq!(
+            detect_tts_languages(Path::new("/voices/fr_FR-siwis-medium.onnx")),
+            TtsLanguages::Known(vec!["fr".into()])
+        );
+        assert_eq!(
+            detect_tts_languages(Path::new("/voices/custom.onnx")),
+            TtsLanguages::Unknown
+        );
+    }
+
+    #[test]
+    fn tts_engine_is_chosen_by_name() {
+        assert_eq!(TtsBackend::parse(None).unwrap(), TtsBackend::Kokoro);
+        assert_eq!(
+            TtsBackend::parse(Some("kokoro")).unwrap(),
+            TtsBackend::Kokoro
+        );
+        assert_eq!(TtsBackend::parse(Some("piper")).unwrap(), TtsBackend::Piper);
+        assert_eq!(
+            TtsBackend::parse(Some("espeak")).unwrap_err().to_string(),
+            "Invalid --tts-engine value \"espeak\": expected \"kokoro\" or \"piper\""
+        );
+
+        let dir = model_dir("piper", &["fr_FR-siwis-medium.onnx"]);
+        let voice = dir.join("fr_FR-siwis-medium.onnx");
+        let err = TtsBackend::Piper.load(&voice, "fr", 2).err().unwrap();
+        assert!(err.to_string().contains(".onnx.json not found"), "{err}");
+        std::fs::write(dir.join("fr_FR-siwis-medium.onnx.json"), b"{}").unwrap();
+        let piper = TtsBackend::Piper.load(&voice, "fr", 2).unwrap();
+        assert_eq!(piper.language(), "fr");
+        assert_eq!(piper.parallelism(), 2);
+        assert!(
+            TtsBackend::Piper
+                .load(&dir.join("missing.onnx"), "fr", 1)
+                .is_err()
+        );
+    }
+
+    /// A WAV of a sine at `rate` Hz over `channels`, as piper writes it.
+    fn wav(rate: u32, channels: u16, secs: f64) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate: rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut out = std::io::Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut out, spec).unwrap();
+        for s in sine(8000.0, (rate as f64 * secs) as usize) {
+            for _ in 0..channels {
+                writer.write_sample(s).unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn piper_output_is_16k_mono_at_the_asked_speed() {
+        let calls = Arc::new(Mutex::new(Vec::<(Vec<String>, String)>::new()));
+        let recorded = calls.clone();
+        let piper = PiperTts::with_run(
+            Path::new("/voices/en_US-lessac-medium.onnx"),
+            "en",
+            1,
+            Box::new(move |args, text| {
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push((args.to_vec(), text.to_string()));
+                if text == "fail" {
+                    bail!("piper failed (exit status: 1): bad input");
+                }
+                Ok(wav(22050, 2, 0.5))
+            }),
+        );
+
+        // 22.05 kHz stereo becomes half a second at 16 kHz, at the usual level
+        let samples = piper.synthesize("Hello\nthere.").unwrap();
+        assert!(samples.len().abs_diff(8000) < 160, "{}", samples.len());
+        assert!(peak(&samples).abs_diff((TARGET_PEAK * 32767.0) as u16) < 400);
+
+        piper.synthesize_at("Faster.", 3, Some(2.0)).unwrap();
+        assert!(piper.synthesize("fail").is_err());
+
+        let calls = calls.lock().unwrap();
+        let (args, text) = &calls[0];
+        assert_eq!(text, "Hello there.");
+        assert_eq!(
+            args.join(" "),
+            "--model /voices/en_US-lessac-medium.onnx --output_file - --length_scale 1.250"
+        );
+        assert_eq!(
+            calls[1].0[4..].join(" "),
+            "--length_scale 0.500 --speaker 3"
+        );
+    }
+
+    #[test]
+    fn language_check_flags_only_real_mismatches() {
+        let english = TtsLanguages::Known(vec!["en".into()]);
+        let warning = language_mismatch("fr", &english).unwrap();
+        assert!(warning.contains("speaks en only"), "{warning}");
+        assert!(warning.contains("--language is fr"), "{warning}");
+        assert_eq!(language_mismatch("en", &english), None);
+        assert_eq!(language_mismatch("en-GB", &english), None);
+        assert_eq!(language_mismatch("fr", &TtsLanguages::Unknown), None);
+        // An explicit declaration wins over the layout
+        let declared = TtsLanguages::declared("en, FR-ca");
+        assert_eq!(
+            declared,
+            TtsLanguages::Known(vec!["en".into(), "fr".into()])
+        );
+        assert_eq!(language_mismatch("fr", &declared), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lexicon_scan_follows_symlinks_and_skips_unusable_names() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::fs::symlink;
+
+        let shared = model_dir("shared-lexicons", &["lexicon-gb-en.txt"]);
+        let dir = model_dir("kokoro-lexicons", &["lexicon-us-en.txt", "lexicon-é ß.txt"]);
+        symlink(
+            shared.join("lexicon-gb-en.txt"),
+            dir.join("lexicon-gb-en.txt"),
+        )
+        .unwrap();
+        symlink(dir.join("missing.txt"), dir.join("lexicon-dangling.txt")).unwrap();
+        std::fs::create_dir(dir.join("lexicon-dir.txt")).unwrap();
+        std::fs::write(dir.join(OsStr::from_bytes(b"lexicon-\xff.txt")), b"").unwrap();
+
+        let dir_str = dir.to_str().unwrap();
+        assert_eq!(
+            build_lexicon_path(&dir).unwrap(),
+            format!(
+                "{dir_str}/lexicon-gb-en.txt,{dir_str}/lexicon-us-en.txt,{dir_str}/lexicon-é ß.txt"
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn model_files_resolve_through_a_non_utf8_symlink() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::fs::symlink;
+
+        let real = model_dir("kokoro-real", &["lexicon-us-en.txt"]);
+        std::fs::create_dir(real.join("dict")).unwrap();
+        let odd = real
+            .parent()
+            .unwrap()
+            .join(OsStr::from_bytes(b"models-\xfe"));
+        let _ = std::fs::remove_dir_all(&odd);
+        std::fs::create_dir(&odd).unwrap();
+        let link = odd.join("kokoro");
+        symlink(&real, &link).unwrap();
+        assert!(link.to_str().is_none());
+
+        let files = KokoroFiles::resolve(&link).unwrap();
+        let real = std::fs::canonicalize(&real).unwrap();
+        assert_eq!(files.model, real.join("model.onnx").to_str().unwrap());
+        assert_eq!(files.dict_dir, real.join("dict").to_str().unwrap());
+        assert_eq!(
+            files.lexicon,
+            real.join("lexicon-us-en.txt").to_str().unwrap()
+        );
+
+        // A model really stored under a non-UTF-8 directory is named in the error
+        let stored = odd.join("kokoro-copy");
+        std::fs::create_dir(&stored).unwrap();
+        let err = KokoroFiles::resolve(&stored).unwrap_err().to_string();
+        assert!(err.contains("models-\u{fffd}"), "{err}");
+        assert!(err.contains("not valid UTF-8"), "{err}");
+    }
 }
@@ -0,0 +1,492 @@
+//! Reply text as the TTS should read it. Kokoro mangles raw numbers, times,
+//! currency and abbreviations ("3.5", "Dr.", "$20"), so they are spelled out
+//! in words before synthesis. The client is still shown the reply as written.
+//!
+//! Rules are per language; a language without rules is spoken as written.
+
+/// `sentence` with numbers, times, currency and abbreviations spelled out for
+/// `lang` (an ISO 639-1 code such as "en", or a tag such as "en-us").
+pub fn normalize_for_tts(sentence: &str, lang: &str) -> String {
+    match lang.split(['-', '_']).next().unwrap_or_default() {
+        "en" => spell_out(sentence, english_token),
+        _ => sentence.to_string(),
+    }
+}
+
+/// `text` with each token `token` recognizes replaced by its spoken form.
+/// Tokens only start at a word boundary, so "mp3" or "B2" are left alone.
+fn spell_out(text: &str, token: fn(&str) -> Option<(String, usize)>) -> String {
+    let mut out = String::with_capacity(text.len() + 16);
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        let in_word = text[..i]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        if !in_word && let Some((spoken, len)) = token(&text[i..]) {
+            out.push_str(&spoken);
+            i += len;
+            continue;
+        }
+        out.push(c);
+        i += c.len_utf8();
+    }
+    out
+}
+
+/// Whether `text` does not go on with a letter or digit.
+fn ends_word(text: &str) -> bool {
+    !text.chars().next().is_some_and(char::is_alphanumeric)
+}
+
+/// The number `text` starts with: its digits (thousands separators dropped),
+/// the dot-separated digit groups after them ("3.5.1": "5" and "1"), and its
+/// length in bytes.
+fn scan_number(text: &str) -> Option<(String, Vec<&str>, usize)> {
+    let b = text.as_bytes();
+    let digits = |from: usize| b[from..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let mut len = digits(0);
+    if len == 0 {
+        return None;
+    }
+    let mut int = text[..len].to_string();
+    // "1,000,000", but not "1,2" or "1,0000"
+    while b.get(len) == Some(&b',') && digits(len + 1) == 3 {
+        int.push_str(&text[len + 1..len + 4]);
+        len += 4;
+    }
+    let mut groups = Vec::new();
+    while b.get(len) == Some(&b'.') && digits(len + 1) > 0 {
+        let n = digits(len + 1);
+        groups.push(&text[len + 1..len + 1 + n]);
+        len += 1 + n;
+    }
+    Some((int, groups, len))
+}
+
+// --- English ---
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [(u64, &str); 4] = [
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+/// Abbreviations read as their full word. Lowercase ones also match
+/// capitalized, at the start of a sentence.
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("e.g.", "for example"),
+    ("i.e.", "that is"),
+    ("etc.", "et cetera"),
+    ("vs.", "versus"),
+    ("approx.", "approximately"),
+    ("Dr.", "Doctor"),
+    ("Mr.", "Mister"),
+    ("Mrs.", "Missus"),
+    ("Ms.", "Miz"),
+    ("Prof.", "Professor"),
+    ("Jr.", "Junior"),
+    ("Sr.", "Senior"),
+];
+
+/// Currency symbols: the unit and the hundredth, singular and plural.
+const CURRENCIES: &[(char, [&str; 4])] = &[
+    ('$', ["dollar", "dollars", "cent", "cents"]),
+    ('€', ["euro", "euros", "cent", "cents"]),
+    ('£', ["pound", "pounds", "penny", "pence"]),
+];
+
+/// The English spoken form of what `text` starts with, and its length.
+fn english_token(text: &str) -> Option<(String, usize)> {
+    english_abbreviation(text)
+        .or_else(|| english_currency(text))
+        .or_else(|| english_time(text))
+        .or_else(|| english_number(text))
+}
+
+fn english_abbreviation(text: &str) -> Option<(String, usize)> {
+    ABBREVIATIONS.iter().find_map(|(abbreviation, spoken)| {
+        let len = abbreviation.len();
+        let written = text.get(..len)?;
+        let capitalized = abbreviation.starts_with(|c: char| c.is_ascii_lowercase())
+            && written.as_bytes()[0] == abbreviation.as_bytes()[0].to_ascii_uppercase()
+            && written.as_bytes()[1..] == abbreviation.as_bytes()[1..];
+        if (written != *abbreviation && !capitalized) || !ends_word(&text[len..]) {
+            return None;
+        }
+        let mut spoken = spoken.to_string();
+        if capitalized {
+            spoken[..1].make_ascii_uppercase();
+        }
+        // Its dot also ended the sentence
+        if text[len..].trim().is_empty() {
+            spoken.push('.');
+        }
+        Some((spoken, len))
+    })
+}
+
+/// "$20", "$3.50", "£0.99", "$2.5 million".
+fn english_currency(text: &str) -> Option<(String, usize)> {
+    let symbol = text.chars().next()?;
+    let &(_, [one, many, hundredth, hundredths]) = CURRENCIES.iter().find(|(c, _)| *c == symbol)?;
+    let start = symbol.len_utf8();
+    let (int, groups, len) = scan_number(&text[start..])?;
+    let value: u64 = int.parse().ok()?;
+    let mut len = start + len;
+    let unit = |n: u64| if n == 1 { one } else { many };
+
+    let scale = SCALES.iter().map(|(_, name)| *name).find(|name| {
+        text[len..]
+            .strip_prefix(' ')
+            .and_then(|rest| rest.strip_prefix(name))
+            .is_some_and(ends_word)
+    });
+    let spoken = match (scale, groups.as_slice()) {
+        (Some(scale), _) => {
+            len += 1 + scale.len();
+            format!("{} {scale} {many}", english_decimal(value, &groups))
+        }
+        (None, []) => format!("{} {}", english_cardinal(value), unit(value)),
+        (None, [fraction]) if fraction.len() == 2 => {
+            let cents: u64 = fraction.parse().ok()?;
+            let cents_spoken = format!(
+                "{} {}",
+                english_cardinal(cents),
+                if cents == 1 { hundredth } else { hundredths }
+            );
+            match (value, cents) {
+                (0, _) => cents_spoken,
+                (_, 0) => format!("{} {}", english_cardinal(value), unit(value)),
+                _ => format!(
+                    "{} {} and {cents_spoken}",
+                    english_cardinal(value),
+                    unit(value)
+                ),
+            }
+        }
+        (None, _) => format!("{} {many}", english_decimal(value, &groups)),
+    };
+    ends_word(&text[len..]).then_some((spoken, len))
+}
+
+/// "3:30" and "15:45", with an optional "am" or "pm".
+fn english_time(text: &str) -> Option<(String, usize)> {
+    let b = text.as_bytes();
+    let hour_len = b.iter().take(3).take_while(|c| c.is_ascii_digit()).count();
+    if !(1..=2).contains(&hour_len)
+        || b.get(hour_len) != Some(&b':')
+        || !b[hour_len + 1..].iter().take(2).all(u8::is_ascii_digit)
+        || b.len() < hour_len + 3
+    {
+        return None;
+    }
+    let mut len = hour_len + 3;
+    if b.get(len).is_some_and(|c| c.is_ascii_digit() || *c == b':') {
+        return None;
+    }
+    let hour: u64 = text[..hour_len].parse().ok()?;
+    let minutes: u64 = text[hour_len + 1..len].parse().ok()?;
+    if hour > 23 || minutes > 59 {
+        return None;
+    }
+    let meridiem = english_meridiem(&text[len..]);
+    let mut spoken = english_cardinal(hour);
+    match minutes {
+        0 if meridiem.is_none() => spoken.push_str(" o'clock"),
+        0 => {}
+        1..=9 => spoken.push_str(&format!(" oh {}", ONES[minutes as usize])),
+        _ => spoken.push_str(&format!(" {}", english_cardinal(minutes))),
+    }
+    if let Some((period, period_len)) = meridiem {
+        spoken.push_str(period);
+        len += period_len;
+    }
+    ends_word(&text[len..]).then_some((spoken, len))
+}
+
+/// "am", " pm", " p.m" (the last dot stays, it may end the sentence).
+fn english_meridiem(text: &str) -> Option<(&'static str, usize)> {
+    let space = usize::from(text.starts_with(' '));
+    let rest = &text[space..];
+    ["am", "pm", "a.m", "p.m"].iter().find_map(|form| {
+        let len = form.len();
+        (rest
+            .get(..len)
+            .is_some_and(|s| s.eq_ignore_ascii_case(form))
+            && ends_word(&rest[len..]))
+        .then(|| {
+            let period = if form.starts_with('a') {
+                " A M"
+            } else {
+                " P M"
+            };
+            (period, space + len)
+        })
+    })
+}
+
+/// "42", "-5", "3.5", "1st", "50%", "1999", "3 pm".
+fn english_number(text: &str) -> Option<(String, usize)> {
+    let negative = text.starts_with('-');
+    let start = usize::from(negative);
+    let (int, groups, len) = scan_number(&text[start..])?;
+    let value: u64 = int.parse().ok()?;
+    let mut len = start + len;
+    let rest = &text[len..];
+
+    let ordinal_suffix = ["st", "nd", "rd", "th"]
+        .iter()
+        .any(|s| rest.get(..2).is_some_and(|r| r.eq_ignore_ascii_case(s)) && ends_word(&rest[2..]));
+    let mut spoken = if groups.is_empty() && !negative && ordinal_suffix {
+        len += 2;
+        english_ordinal(value)
+    } else if groups.is_empty() && !negative && int.len() == len && len == 4 {
+        english_year(value).unwrap_or_else(|| english_cardinal(value))
+    } else {
+        english_decimal(value, &groups)
+    };
+    if negative {
+        spoken.insert_str(0, "minus ");
+    }
+
+    if text[len..].starts_with('%') {
+        spoken.push_str(" percent");
+        len += 1;
+    } else if groups.is_empty()
+        && !negative
+        && value <= 12
+        && let Some((period, period_len)) = english_meridiem(&text[len..])
+    {
+        spoken.push_str(period);
+        len += period_len;
+    }
+    ends_word(&text[len..]).then_some((spoken, len))
+}
+
+/// `value`, then each dot-separated group digit by digit: "three point one four".
+fn english_decimal(value: u64, groups: &[&str]) -> String {
+    let mut spoken = english_cardinal(value);
+    for group in groups {
+        spoken.push_str(" point");
+        for digit in group.bytes() {
+            spoken.push(' ');
+            spoken.push_str(ONES[usize::from(digit - b'0')]);
+        }
+    }
+    spoken
+}
+
+fn english_cardinal(n: u64) -> String {
+    if n < 20 {
+        return ONES[n as usize].to_string();
+    }
+    // Beyond the trillions: digit by digit
+    if n >= 1_000_000_000_000_000 {
+        let digits: Vec<&str> = n
+            .to_string()
+            .bytes()
+            .map(|d| ONES[usize::from(d - b'0')])
+            .collect();
+        return digits.join(" ");
+    }
+    let mut words = Vec::new();
+    let mut n = n;
+    for (scale, name) in SCALES {
+        if n >= scale {
+            words.push(format!("{} {name}", english_below_thousand(n / scale)));
+            n %= scale;
+        }
+    }
+    if n > 0 {
+        words.push(english_below_thousand(n));
+    }
+    words.join(" ")
+}
+
+/// 1 to 999: "one hundred twenty-three".
+fn english_below_thousand(n: u64) -> String {
+    let mut words = Vec::new();
+    if n >= 100 {
+        words.push(format!("{} hundred", ONES[(n / 100) as usize]));
+    }
+    if !n.is_multiple_of(100) {
+        words.push(english_tens(n % 100));
+    }
+    words.join(" ")
+}
+
+/// 0 to 99: "forty-two".
+fn english_tens(n: u64) -> String {
+    match n {
+        0..20 => ONES[n as usize].to_string(),
+        _ if n.is_multiple_of(10) => TENS[(n / 10) as usize].to_string(),
+        _ => format!("{}-{}", TENS[(n / 10) as usize], ONES[(n % 10) as usize]),
+    }
+}
+
+fn english_ordinal(n: u64) -> String {
+    let cardinal = english_cardinal(n);
+    let split = cardinal.rfind([' ', '-']).map_or(0, |i| i + 1);
+    let (head, last) = cardinal.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        word if word.ends_with('y') => format!("{}ieth", &word[..word.len() - 1]),
+        word => format!("{word}th"),
+    };
+    format!("{head}{last}")
+}
+
+/// A four-digit number read as a year: "nineteen ninety-nine", "twenty
+/// twenty-four". 2000 to 2009 and numbers out of 1100-2099 are not years.
+fn english_year(n: u64) -> Option<String> {
+    if !(1100..=1999).contains(&n) && !(2010..=2099).contains(&n) {
+        return None;
+    }
+    let (high, low) = (english_tens(n / 100), n % 100);
+    Some(match low {
+        0 => format!("{high} hundred"),
+        1..=9 => format!("{high} oh {}", ONES[low as usize]),
+        _ => format!("{high} {}", english_tens(low)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_expansions() {
+        for (written, spoken) in [
+            // Cardinals and decimals
+            ("I have 3 cats.", "I have three cats."),
+            (
+                "It costs 1,250 yen.",
+                "It costs one thousand two hundred fifty yen.",
+            ),
+            ("That's 1000000 words.", "That's one million words."),
+            ("Add 42 and 7.", "Add forty-two and seven."),
+            ("Version 3.5 is out.", "Version three point five is out."),
+            ("Pi is 3.14.", "Pi is three point one four."),
+            (
+                "Run version 3.5.1 now.",
+                "Run version three point five point one now.",
+            ),
+            ("It's -5 outside.", "It's minus five outside."),
+            ("Half is 0.5.", "Half is zero point five."),
+            // Currency
+            ("It's $20.", "It's twenty dollars."),
+            ("Only $1!", "Only one dollar!"),
+            (
+                "It's $3.50 each.",
+                "It's three dollars and fifty cents each.",
+            ),
+            ("Just $0.99.", "Just ninety-nine cents."),
+            ("Exactly $5.00.", "Exactly five dollars."),
+            (
+                "It raised $2.5 million.",
+                "It raised two point five million dollars.",
+            ),
+            (
+                "That's €10, or £1.01.",
+                "That's ten euros, or one pound and one penny.",
+            ),
+            // Ordinals, percentages, years
+            ("The 1st, 2nd and 3rd.", "The first, second and third."),
+            ("Her 21st birthday.", "Her twenty-first birthday."),
+            ("The 12th and 40th.", "The twelfth and fortieth."),
+            ("About 50% of them.", "About fifty percent of them."),
+            ("Up 2.5%.", "Up two point five percent."),
+            ("Born in 1999.", "Born in nineteen ninety-nine."),
+            (
+                "In 1905 and 1900.",
+                "In nineteen oh five and nineteen hundred.",
+            ),
+            ("Since 2024.", "Since twenty twenty-four."),
+            ("In 2005.", "In two thousand five."),
+            ("About 5000 people.", "About five thousand people."),
+            // Times
+            ("Meet at 3:30.", "Meet at three thirty."),
+            ("At 9:05 sharp.", "At nine oh five sharp."),
+            ("At 10:00.", "At ten o'clock."),
+            ("At 15:45.", "At fifteen forty-five."),
+            ("At 7:00 pm.", "At seven P M."),
+            ("At 6:15am.", "At six fifteen A M."),
+            ("Call at 3 p.m.", "Call at three P M."),
+            // Abbreviations
+            ("Dr. Smith is here.", "Doctor Smith is here."),
+            ("Mr. and Mrs. Jones.", "Mister and Missus Jones."),
+            ("Fruit, e.g. apples.", "Fruit, for example apples."),
+            ("Cats vs. dogs, etc.", "Cats versus dogs, et cetera."),
+            ("E.g. this one.", "For example this one."),
+            ("It takes 5 ms. Then more.", "It takes five ms. Then more."),
+            // Left alone
+            ("An mp3 player, a B2 level.", "An mp3 player, a B2 level."),
+            ("A 3D movie in 4K.", "A 3D movie in 4K."),
+            ("Drive down the road.", "Drive down the road."),
+            ("Ratio 25:100.", "Ratio twenty-five:one hundred."),
+        ] {
+            assert_eq!(normalize_for_tts(written, "en"), spoken, "{written}");
+        }
+    }
+
+    #[test]
+    fn english_numbers_in_words() {
+        assert_eq!(english_cardinal(0), "zero");
+        assert_eq!(english_cardinal(115), "one hundred fifteen");
+        assert_eq!(
+            english_cardinal(2_000_017_000),
+            "two billion seventeen thousand"
+        );
+        assert_eq!(
+            english_cardinal(1_234_567_890_123_456),
+            "one two three four five six seven eight nine zero one two three four five six"
+        );
+        assert_eq!(english_ordinal(100), "one hundredth");
+        assert_eq!(english_ordinal(33), "thirty-third");
+        assert_eq!(english_ordinal(11), "eleventh");
+    }
+
+    #[test]
+    fn other_languages_are_spoken_as_written() {
+        assert_eq!(normalize_for_tts("Il a 3 chats.", "fr"), "Il a 3 chats.");
+        assert_eq!(
+            normalize_for_tts("I have 3 cats.", "en-us"),
+            "I have three cats."
+        );
+    }
+}
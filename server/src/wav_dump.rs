@@ -0,0 +1,268 @@
+//! `--dump-audio <dir>`: the audio segments stt_router receives, as
+//! `<dir>/<unix ms>-<seq>.wav` (16 kHz mono, 16-bit), each with its
+//! transcription in the `.txt` of the same name, to tell bad capture from bad
+//! transcription.
+//!
+//! Only the latest segments are kept (`--dump-audio-keep`, `--dump-audio-mb`).
+//! A dump that fails is warned about once and never ends the session.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use space_lt_common::{info, warn};
+
+use crate::session_log::unix_ms;
+
+/// `--dump-audio-keep` when not given.
+pub const DEFAULT_KEEP_SEGMENTS: usize = 200;
+/// `--dump-audio-mb` when not given.
+pub const DEFAULT_KEEP_MB: u64 = 500;
+
+/// How many dumped segments are kept: the oldest go first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation {
+    pub max_segments: usize,
+    /// WAV and text files together.
+    pub max_bytes: u64,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self {
+            max_segments: DEFAULT_KEEP_SEGMENTS,
+            max_bytes: DEFAULT_KEEP_MB << 20,
+        }
+    }
+}
+
+pub struct AudioDump {
+    dir: PathBuf,
+    rotation: Rotation,
+    /// Segments dumped since the server started.
+    seq: AtomicU64,
+    /// A dump failed: later failures are not warned about again.
+    failed: AtomicBool,
+}
+
+impl AudioDump {
+    /// Dump into `dir`, created if needed.
+    pub fn create(dir: &Path, rotation: Rotation) -> Result<Self> {
+        std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        info!("[server] Dumping audio segments to {}", dir.display());
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            rotation,
+            seq: AtomicU64::new(0),
+            failed: AtomicBool::new(false),
+        })
+    }
+
+    /// Write `samples` (16 kHz) to a new WAV file, dropping the oldest ones
+    /// past the rotation limits. Returns its path, for [`Self::transcript`].
+    pub fn segment(&self, samples: &[i16]) -> Option<PathBuf> {
+        let seq = self.seq.fetch_add(1, Ordering::SeqCst);
+        let path = self.dir.join(segment_name(unix_ms(), seq));
+        let written = write_wav(&path, samples)
+            .with_context(|| format!("writing {}", path.display()))
+            .and_then(|()| rotate(&self.dir, self.rotation));
+        match written {
+            Ok(()) => Some(path),
+            Err(e) => {
+                self.warn(&e);
+                None
+            }
+        }
+    }
+
+    /// Write the transcription of the segment dumped at `wav`.
+    pub fn transcript(&self, wav: &Path, text: &str) {
+        let path = wav.with_extension("txt");
+        if let Err(e) = std::fs::write(&path, format!("{text}\n")) {
+            self.warn(&anyhow::Error::from(e).context(format!("writing {}", path.display())));
+        }
+    }
+
+    fn warn(&self, e: &anyhow::Error) {
+        if !self.failed.swap(true, Ordering::SeqCst) {
+            warn!("[server] Could not dump audio (further errors not shown): {e:#}");
+        }
+    }
+}
+
+/// `<unix ms>-<seq>.wav`, the sequence number padded so that names sort in
+/// the order the segments came.
+fn segment_name(at_ms: u128, seq: u64) -> String {
+    format!("{at_ms}-{seq:06}.wav")
+}
+
+fn write_wav(path: &Path, samples: &[i16]) -> Result<()> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 16000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for &sample in samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Remove the oldest dumped segments of `dir`, with their transcriptions,
+/// until `rotation` holds. Other files are left alone.
+fn rotate(dir: &Path, rotation: Rotation) -> Result<()> {
+    let mut segments: Vec<(PathBuf, u64)> = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if !is_segment(&path) {
+            continue;
+        }
+        let size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let bytes = size(&path) + size(&path.with_extension("txt"));
+        segments.push((path, bytes));
+    }
+    segments.sort();
+    let mut count = segments.len();
+    let mut bytes: u64 = segments.iter().map(|(_, bytes)| bytes).sum();
+    for (path, size) in segments {
+        // The newest one stays, whatever its size
+        if count <= 1 || (count <= rotation.max_segments && bytes <= rotation.max_bytes) {
+            break;
+        }
+        std::fs::remove_file(&path).with_context(|| format!("removing {}", path.display()))?;
+        let _ = std::fs::remove_file(path.with_extension("txt"));
+        count -= 1;
+        bytes -= size;
+    }
+    Ok(())
+}
+
+/// A WAV file named like [`segment_name`]'s.
+fn is_segment(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let Some((at, seq)) = name
+        .strip_suffix(".wav")
+        .and_then(|stem| stem.split_once('-'))
+    else {
+        return false;
+    };
+    [at, seq]
+        .iter()
+        .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("space_lt_dump_test_{name}_{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        dir
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn segments_are_named_in_order() {
+        assert_eq!(segment_name(1760000000123, 7), "1760000000123-000007.wav");
+        // The same millisecond still sorts by sequence
+        assert!(segment_name(1760000000123, 9) < segment_name(1760000000123, 10));
+        assert!(is_segment(Path::new("/tmp/1760000000123-000007.wav")));
+        assert!(!is_segment(Path::new("/tmp/1760000000123-000007.txt")));
+        assert!(!is_segment(Path::new("/tmp/take-1.wav")));
+        assert!(!is_segment(Path::new("/tmp/1760000000123.wav")));
+
+        let dir = temp_dir("order");
+        let dump = AudioDump::create(&dir, Rotation::default()).unwrap();
+        let first = dump.segment(&[0; 1600]).unwrap();
+        let second = dump.segment(&[1; 1600]).unwrap();
+        assert!(first < second);
+        dump.transcript(&first, "Hello there");
+        assert_eq!(
+            std::fs::read_to_string(first.with_extension("txt")).unwrap(),
+            "Hello there\n"
+        );
+        let reader = hound::WavReader::open(&second).unwrap();
+        assert_eq!(
+            (reader.spec().channels, reader.spec().sample_rate),
+            (1, 16000)
+        );
+        let audio: Vec<i16> = reader.into_samples().map(Result::unwrap).collect();
+        assert_eq!(audio, vec![1; 1600]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn oldest_segments_go_first() {
+        let dir = temp_dir("rotation");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "mine").unwrap();
+        let dump = AudioDump::create(
+            &dir,
+            Rotation {
+                max_segments: 2,
+                max_bytes: 1 << 20,
+            },
+        )
+        .unwrap();
+        let mut paths = Vec::new();
+        for _ in 0..4 {
+            let path = dump.segment(&[0; 160]).unwrap();
+            dump.transcript(&path, "Hi");
+            paths.push(path);
+        }
+        let stem = |path: &PathBuf| path.file_stem().unwrap().to_str().unwrap().to_string();
+        let (third, fourth) = (stem(&paths[2]), stem(&paths[3]));
+        assert_eq!(
+            names(&dir),
+            [
+                format!("{third}.txt"),
+                format!("{third}.wav"),
+                format!("{fourth}.txt"),
+                format!("{fourth}.wav"),
+                "notes.txt".to_string(),
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+
+        // By size: each segment is 44 + 3200 bytes of WAV
+        let dir = temp_dir("rotation_size");
+        let dump = AudioDump::create(
+            &dir,
+            Rotation {
+                max_segments: 100,
+                max_bytes: 6000,
+            },
+        )
+        .unwrap();
+        dump.segment(&[0; 1600]).unwrap();
+        let last = dump.segment(&[0; 1600]).unwrap();
+        assert_eq!(names(&dir), [last.file_name().unwrap().to_str().unwrap()]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn failed_dumps_are_not_fatal() {
+        let dir = temp_dir("gone");
+        let dump = AudioDump::create(&dir, Rotation::default()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(dump.segment(&[0; 160]), None);
+        assert!(dump.failed.load(Ordering::SeqCst));
+        dump.transcript(&dir.join("1-000000.wav"), "Hi");
+    }
+}
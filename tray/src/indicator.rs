@@ -0,0 +1,175 @@
+//! The tray icon: the client's state as an icon and tooltip, and a menu
+//! sending its commands.
+
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+
+use ksni::menu::StandardItem;
+use ksni::{MenuItem, Status, ToolTip};
+use space_lt_common::control::{self, ClientState, Command};
+use space_lt_common::warn;
+
+/// The connection to the client, while there is one.
+pub type Link = Arc<Mutex<Option<UnixStream>>>;
+
+pub struct Indicator {
+    /// `None` while no client is connected.
+    pub state: Option<ClientState>,
+    pub link: Link,
+}
+
+impl Indicator {
+    fn send(&self, command: Command) {
+        let mut link = self.link.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(stream) = link.as_mut() else {
+            return;
+        };
+        if let Err(e) = writeln!(stream, "{}", control::command_line(command)) {
+            warn!("[tray] Could not send {}: {e}", command.name());
+        }
+    }
+}
+
+/// What the indicator says for `state`.
+pub fn label(state: Option<ClientState>) -> &'static str {
+    match state {
+        None => "Not connected",
+        Some(ClientState::Idle) => "Idle",
+        Some(ClientState::Listening) => "Listening",
+        Some(ClientState::Thinking) => "Waiting for the reply",
+        Some(ClientState::Speaking) => "AI speaking",
+        Some(ClientState::NeedsInput) => "Needs your input",
+    }
+}
+
+/// A freedesktop icon name for `state`.
+pub fn icon_name(state: Option<ClientState>) -> &'static str {
+    match state {
+        None => "network-offline",
+        Some(ClientState::Idle) => "user-available",
+        Some(ClientState::Listening) => "audio-input-microphone",
+        Some(ClientState::Thinking) => "content-loading",
+        Some(ClientState::Speaking) => "audio-volume-high",
+        Some(ClientState::NeedsInput) => "dialog-question",
+    }
+}
+
+fn menu_label(command: Command) -> &'static str {
+    match command {
+        Command::Cancel => "Cancel",
+        Command::Replay => "Replay",
+        Command::SlowReplay => "Slow replay",
+    }
+}
+
+/// Whether `command` does anything in `state`: a cancel needs an exchange
+/// going on, a replay a client that is free to play it.
+pub fn available(state: Option<ClientState>, command: Command) -> bool {
+    match (state, command) {
+        (None, _) => false,
+        (Some(state), Command::Cancel) => matches!(
+            state,
+            ClientState::Listening | ClientState::Thinking | ClientState::Speaking
+        ),
+        (Some(state), Command::Replay | Command::SlowReplay) => state == ClientState::Idle,
+    }
+}
+
+impl ksni::Tray for Indicator {
+    fn id(&self) -> String {
+        env!("CARGO_PKG_NAME").into()
+    }
+
+    fn title(&self) -> String {
+        format!("Space LT \u{2014} {}", label(self.state))
+    }
+
+    fn status(&self) -> Status {
+        match self.state {
+            Some(ClientState::NeedsInput) => Status::NeedsAttention,
+            _ => Status::Active,
+        }
+    }
+
+    fn icon_name(&self) -> String {
+        icon_name(self.state).into()
+    }
+
+    fn tool_tip(&self) -> ToolTip {
+        ToolTip {
+            title: self.title(),
+            ..Default::default()
+        }
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        let mut items: Vec<MenuItem<Self>> = Command::ALL
+            .into_iter()
+            .map(|command| {
+                StandardItem {
+                    label: menu_label(command).into(),
+                    enabled: available(self.state, command),
+                    activate: Box::new(move |tray: &mut Self| tray.send(command)),
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect();
+        items.push(MenuItem::Separator);
+        items.push(
+            StandardItem {
+                label: "Quit".into(),
+                icon_name: "application-exit".into(),
+                activate: Box::new(|_| std::process::exit(0)),
+                ..Default::default()
+            }
+            .into(),
+        );
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn commands_follow_the_state() {
+        for command in Command::ALL {
+            assert!(!available(None, command));
+        }
+        assert!(available(Some(ClientState::Speaking), Command::Cancel));
+        assert!(available(Some(ClientState::Thinking), Command::Cancel));
+        assert!(!available(Some(ClientState::Idle), Command::Cancel));
+        assert!(available(Some(ClientState::Idle), Command::Replay));
+        assert!(!available(Some(ClientState::Speaking), Command::SlowReplay));
+        // A prompt reads keys, not commands
+        assert!(!available(Some(ClientState::NeedsInput), Command::Replay));
+    }
+
+    #[test]
+    fn menu_items_send_their_command_line() {
+        let (client, tray) = UnixStream::pair().unwrap();
+        let indicator = Indicator {
+            state: Some(ClientState::Speaking),
+            link: Arc::new(Mutex::new(Some(tray))),
+        };
+        indicator.send(Command::Cancel);
+        indicator.send(Command::SlowReplay);
+        let mut lines = BufReader::new(client).lines();
+        for expected in [Command::Cancel, Command::SlowReplay] {
+            let line = lines.next().unwrap().unwrap();
+            assert_eq!(control::parse_command(&line).unwrap(), expected);
+        }
+
+        // Nothing to send to: dropped
+        let offline = Indicator {
+            state: None,
+            link: Link::default(),
+        };
+        offline.send(Command::Replay);
+        assert_eq!(icon_name(offline.state), "network-offline");
+    }
+}
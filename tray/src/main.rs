@@ -0,0 +1,110 @@
+mod indicator;
+
+use anyhow::{Context, Result};
+use ksni::blocking::TrayMethods;
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use indicator::{Indicator, Link};
+use space_lt_common::control::{self, ClientState};
+use space_lt_common::{debug, info, warn};
+
+/// How often a client that is not running yet (or has quit) is looked for.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+
+fn find_arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|a| a == "--help") {
+        println!("Usage: space_lt_tray [--socket <path>] [--debug]");
+        return Ok(());
+    }
+    if args.iter().any(|a| a == "--debug") {
+        space_lt_common::log::set_debug(true);
+    }
+    let socket_path = find_arg_value(&args, "--socket")
+        .map(PathBuf::from)
+        .unwrap_or_else(control::default_socket_path);
+
+    let link = Link::default();
+    let tray = Indicator {
+        state: None,
+        link: link.clone(),
+    }
+    .spawn()
+    .context("starting the tray icon (is a StatusNotifierItem host running?)")?;
+    info!("[tray] Following the client at {}", socket_path.display());
+
+    while !tray.is_closed() {
+        match UnixStream::connect(&socket_path) {
+            Ok(stream) => {
+                info!("[tray] Connected to the client");
+                *link.lock().unwrap_or_else(|e| e.into_inner()) = stream.try_clone().ok();
+                let followed = follow(stream, |state| {
+                    tray.update(|tray| tray.state = Some(state));
+                });
+                *link.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                tray.update(|tray| tray.state = None);
+                match followed {
+                    Ok(()) => info!("[tray] The client is gone"),
+                    Err(e) => warn!("[tray] Lost the client: {e:#}"),
+                }
+            }
+            Err(e) => debug!("[tray] No client at {}: {e}", socket_path.display()),
+        }
+        std::thread::sleep(RECONNECT_INTERVAL);
+    }
+    Ok(())
+}
+
+/// Hand each state the client announces to `on_state`, until it disconnects.
+fn follow(stream: UnixStream, mut on_state: impl FnMut(ClientState)) -> Result<()> {
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        match control::parse_state(&line) {
+            Ok(Some(state)) => on_state(state),
+            // An event of a newer client
+            Ok(None) => {}
+            Err(e) => warn!("[tray] {e:#}"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn states_are_followed_until_the_client_leaves() {
+        let (mut client, tray) = UnixStream::pair().unwrap();
+        let announcer = std::thread::spawn(move || {
+            for state in [ClientState::Idle, ClientState::Listening] {
+                writeln!(client, "{}", control::state_line(state)).unwrap();
+            }
+            writeln!(client, "{{\"event\": \"volume\", \"level\": \"80\"}}").unwrap();
+            writeln!(client, "not json").unwrap();
+            writeln!(client, "{}", control::state_line(ClientState::NeedsInput)).unwrap();
+        });
+        let mut states = Vec::new();
+        follow(tray, |state| states.push(state)).unwrap();
+        announcer.join().unwrap();
+        assert_eq!(
+            states,
+            [
+                ClientState::Idle,
+                ClientState::Listening,
+                ClientState::NeedsInput
+            ]
+        );
+    }
+}